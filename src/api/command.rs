@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error as ThisError;
+
+use crate::game::business::BusinessError;
+use crate::game::commands::CommandError;
+use crate::zoom::Direction;
+
+/// Something a frontend asks the game to do
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Command {
+    /// Run the world forward by exactly this much simulation time,
+    /// whether or not the clock is paused
+    Advance {
+        seconds: u64,
+    },
+    SetPaused {
+        paused: bool,
+    },
+    /// How many simulation seconds pass per real second
+    SetSpeed {
+        multiplier: f64,
+    },
+    /// Descend into the place under the cursor
+    ZoomIn,
+    ZoomOut,
+    /// Move the cursor one spot on the current map
+    Move {
+        direction: Direction,
+    },
+    /// Move the view to a place by name
+    Goto {
+        name: String,
+    },
+    /// Save the view in a slot from 1 to 9
+    Bookmark {
+        slot: u8,
+    },
+    JumpToBookmark {
+        slot: u8,
+    },
+    /// Set the money supply's annual growth, in percent
+    SetMoneyGrowth {
+        percent: f64,
+    },
+    /// Found a firm for the player in the local area in view
+    FoundBusiness {
+        name: String,
+        recipe: String,
+        /// Most workers it will hire
+        workers: u32,
+        /// Cash put in from the player's purse
+        capital: f64,
+    },
+    /// Run a line as if typed into the command console
+    Console {
+        line: String,
+    },
+}
+
+/// Why a command was refused
+#[derive(ThisError, Debug)]
+#[non_exhaustive]
+pub enum ApiError {
+    #[error("speed must be a positive number, not {0}")]
+    InvalidSpeed(f64),
+    #[error("no place named '{0}'")]
+    UnknownPlace(String),
+    #[error("no bookmark slot {0}")]
+    NoSuchSlot(u8),
+    #[error(transparent)]
+    Business(#[from] BusinessError),
+    #[error(transparent)]
+    Console(#[from] CommandError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commands_read_from_json() {
+        let command: Command =
+            serde_json::from_str(r#"{ "kind": "move", "direction": "left" }"#).unwrap();
+        assert_eq!(
+            command,
+            Command::Move {
+                direction: Direction::Left
+            }
+        );
+        let json = serde_json::to_string(&Command::ZoomIn).unwrap();
+        assert_eq!(json, r#"{"kind":"zoom_in"}"#);
+    }
+}
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game::events::LogEntry;
+use crate::game::{Alert, Notice, Severity};
+
+/// Something that happened in the game since the frontend last asked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Event {
+    /// A line written to the event log
+    Logged {
+        /// Simulation seconds since the start of the game
+        at: f64,
+        message: String,
+    },
+    /// A scenario's scripted event fired; its message was logged too
+    ScriptedEvent,
+    /// A campaign objective or chapter was completed or failed
+    Objective,
+    /// A story screen, such as a chapter's introduction, for the player
+    /// to read before carrying on
+    Story { title: String, text: String },
+    /// Something to show the player straight away, such as a famine; its
+    /// message was logged too. Since 1.1.
+    Notice { severity: Severity, message: String },
+}
+
+impl From<&LogEntry> for Event {
+    fn from(entry: &LogEntry) -> Self {
+        Event::Logged {
+            at: entry.time.as_secs_f64(),
+            message: entry.message.clone(),
+        }
+    }
+}
+
+impl From<Notice> for Event {
+    fn from(notice: Notice) -> Self {
+        Event::Notice {
+            severity: notice.severity,
+            message: notice.message,
+        }
+    }
+}
+
+impl From<Alert> for Event {
+    fn from(alert: Alert) -> Self {
+        match alert {
+            Alert::ScriptedEvent => Event::ScriptedEvent,
+            Alert::Objective => Event::Objective,
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! The stable interface frontends drive the simulation through.
+//!
+//! The terminal game reaches straight into [`Simulation`] and
+//! [`WorldState`], which change shape whenever the simulation grows. Web
+//! and GUI frontends, and anything talking to the game over a network,
+//! use [`Core`] instead: [`Command`]s go in, and [`Snapshot`]s of what is
+//! in view and [`Event`]s describing what happened come out. Every type
+//! here serializes to JSON, so the same values can cross a socket
+//! unchanged. With the `serve` feature, an `Observer` streams
+//! [`Observation`]s of the whole world over a local socket to dashboards
+//! watching a running game.
+//!
+//! # Stability
+//!
+//! The interface follows semantic versioning through [`API_VERSION`],
+//! separately from the crate's own version:
+//!
+//! - A minor release may add commands, events, errors and fields to
+//!   snapshots. Those types are `#[non_exhaustive]`, so matches need a
+//!   wildcard arm and snapshots are only ever read, never built by
+//!   frontends. Unknown fields are skipped when reading JSON, so older
+//!   readers still understand newer snapshots.
+//! - A major release is needed to remove or rename anything, or to change
+//!   what an existing command does.
+//!
+//! Nothing outside this module is covered by the guarantee.
+
+mod command;
+mod event;
+mod observation;
+#[cfg(feature = "serve")]
+mod observer;
+mod snapshot;
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+use crate::economy::Money;
+use crate::game::state::EntityId;
+use crate::game::{Simulation, WorldState};
+use crate::save::SaveGame;
+
+pub use command::{ApiError, Command};
+pub use event::Event;
+pub use observation::{EntityView, Observation};
+#[cfg(feature = "serve")]
+pub use observer::{DEFAULT_SERVE_ADDR, Observer};
+pub use snapshot::{
+    BusinessView, EconomySummary, FirmView, HistoryPoint, MarketView, PriceHistoryView, PriceQuote,
+    Snapshot, View,
+};
+
+/// The version of the interface this build provides
+pub const API_VERSION: ApiVersion = ApiVersion { major: 1, minor: 3 };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ApiVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl ApiVersion {
+    /// Whether a frontend written against `wanted` can use this version:
+    /// the same major version, with at least the features it expects
+    pub fn supports(self, wanted: ApiVersion) -> bool {
+        self.major == wanted.major && self.minor >= wanted.minor
+    }
+}
+
+impl fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// A running game as a frontend sees it
+pub struct Core {
+    simulation: Simulation,
+    /// Log entries already handed out as events
+    seen: u64,
+}
+
+impl Core {
+    /// The sample world
+    pub fn new() -> Self {
+        Self::with_world(WorldState::new())
+    }
+
+    pub fn with_world(world: WorldState) -> Self {
+        Self::wrap(Simulation::with_world(world))
+    }
+
+    pub fn from_save(save: SaveGame) -> Self {
+        Self::wrap(Simulation::from_save(save))
+    }
+
+    /// Start from the current state of a simulation; only what happens
+    /// from here on is reported as events
+    pub fn wrap(simulation: Simulation) -> Self {
+        let seen = simulation.world().events().pushed();
+        Self { simulation, seen }
+    }
+
+    pub fn save(&self) -> SaveGame {
+        self.simulation.to_save()
+    }
+
+    /// Carry out a command. Whatever it reports comes out as events.
+    pub fn apply(&mut self, command: Command) -> Result<(), ApiError> {
+        let simulation = &mut self.simulation;
+        match command {
+            Command::Advance { seconds } => simulation.advance(Duration::from_secs(seconds)),
+            Command::SetPaused { paused } => {
+                if simulation.time().is_paused() != paused {
+                    simulation.time_mut().toggle_pause();
+                }
+            }
+            Command::SetSpeed { multiplier } => {
+                if !multiplier.is_finite() || multiplier <= 0.0 {
+                    return Err(ApiError::InvalidSpeed(multiplier));
+                }
+                simulation.time_mut().set_speed(multiplier);
+            }
+            Command::ZoomIn => {
+                simulation.zoom_in();
+            }
+            Command::ZoomOut => {
+                simulation.zoom_out();
+            }
+            Command::Move { direction } => {
+                simulation.move_cursor(direction);
+            }
+            Command::Goto { name } => {
+                simulation.goto(&name).ok_or(ApiError::UnknownPlace(name))?;
+            }
+            Command::Bookmark { slot } => {
+                simulation
+                    .bookmark(slot)
+                    .ok_or(ApiError::NoSuchSlot(slot))?;
+            }
+            Command::JumpToBookmark { slot } => {
+                simulation
+                    .jump_to_bookmark(slot)
+                    .ok_or(ApiError::NoSuchSlot(slot))?;
+            }
+            Command::SetMoneyGrowth { percent } => simulation
+                .world_mut()
+                .economy_mut()
+                .set_money_growth(percent / 100.0),
+            Command::FoundBusiness {
+                name,
+                recipe,
+                workers,
+                capital,
+            } => {
+                simulation.found_business(&name, &recipe, workers, Money(capital))?;
+                let world = simulation.world_mut();
+                let capital = world.economy().currency().format(Money(capital));
+                world.log(format!("Founded {} with {}", name, capital));
+            }
+            Command::Console { line } => {
+                let output = crate::game::commands::execute(&line, simulation)?;
+                simulation.world_mut().log(output);
+            }
+        }
+        Ok(())
+    }
+
+    /// Advance by the real time since the last frame at the current speed;
+    /// for frontends that run their own frame loop
+    pub fn step(&mut self) -> Duration {
+        self.simulation.step()
+    }
+
+    /// What is in view now
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot::take(&self.simulation)
+    }
+
+    /// The whole world, not just what is in view
+    pub fn observe(&self) -> Observation {
+        Observation::take(&self.simulation)
+    }
+
+    /// A good's price history on the market of a local area, or None if
+    /// the good is unknown or nothing has been recorded there
+    pub fn price_history(&self, area: EntityId, good: &str) -> Option<PriceHistoryView> {
+        PriceHistoryView::of(self.simulation.world(), area, good)
+    }
+
+    /// Send the world to an observer's clients if it has ticked
+    #[cfg(feature = "serve")]
+    pub fn publish(&self, observer: &mut Observer) {
+        observer.publish(&self.simulation);
+    }
+
+    /// Everything that happened since the last call, oldest first
+    pub fn poll_events(&mut self) -> Vec<Event> {
+        let world = self.simulation.world_mut();
+        let mut events: Vec<Event> = world.events().since(self.seen).map(Event::from).collect();
+        self.seen = world.events().pushed();
+        events.extend(world.take_alerts().into_iter().map(Event::from));
+        events.extend(world.take_notices().into_iter().map(Event::from));
+        if let Some(screen) = world.take_interstitial() {
+            events.push(Event::Story {
+                title: screen.title,
+                text: screen.text,
+            });
+        }
+        events
+    }
+}
+
+impl Default for Core {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versions_are_compatible_within_a_major() {
+        let wanted = ApiVersion { major: 1, minor: 0 };
+        assert!(API_VERSION.supports(wanted));
+        assert!(!ApiVersion { major: 1, minor: 0 }.supports(ApiVersion { major: 1, minor: 1 }));
+        assert!(!API_VERSION.supports(ApiVersion { major: 2, minor: 0 }));
+        assert!(API_VERSION.supports(ApiVersion { major: 1, minor: 1 }));
+        assert_eq!(API_VERSION.to_string(), "1.3");
+    }
+
+    #[test]
+    fn test_events_are_reported_once() {
+        let mut core = Core::new();
+        core.apply(Command::Console {
+            line: String::from("money"),
+        })
+        .unwrap();
+
+        let events = core.poll_events();
+        assert!(matches!(events.as_slice(), [Event::Logged { .. }]));
+        assert!(core.poll_events().is_empty());
+    }
+}
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game::Simulation;
+use crate::game::state::EntityId;
+use crate::zoom::ZoomLevel;
+
+use super::snapshot::{EconomySummary, MarketView};
+use super::{API_VERSION, ApiVersion};
+
+/// Levels whose places are listed in an observation, from the top down
+const LISTED_LEVELS: [ZoomLevel; 5] = [
+    ZoomLevel::SolarSystem,
+    ZoomLevel::Planet,
+    ZoomLevel::Region,
+    ZoomLevel::LocalArea,
+    ZoomLevel::Room,
+];
+
+/// The whole world as an outside tool sees it, whatever the player is
+/// looking at. Since 1.2.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Observation {
+    pub api_version: ApiVersion,
+    pub tick: u64,
+    /// Simulation seconds since the start of the game
+    pub elapsed: f64,
+    pub date: String,
+    pub paused: bool,
+    pub speed: f64,
+    pub economy: EconomySummary,
+    /// Every place below the galaxy
+    pub entities: Vec<EntityView>,
+    /// Every local market
+    pub markets: Vec<MarketView>,
+}
+
+/// A place in the world
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct EntityView {
+    pub level: ZoomLevel,
+    pub id: EntityId,
+    pub name: String,
+    pub parent: Option<EntityId>,
+    pub population: Option<u64>,
+}
+
+impl Observation {
+    pub(crate) fn take(simulation: &Simulation) -> Self {
+        let world = simulation.world();
+        let time = simulation.time();
+
+        let entities = LISTED_LEVELS
+            .into_iter()
+            .flat_map(|level| world.ids(level).into_iter().map(move |id| (level, id)))
+            .map(|(level, id)| EntityView {
+                level,
+                id,
+                name: world.entity_name(level, id),
+                parent: world
+                    .placement_of(level, id)
+                    .and_then(|placement| placement.parent),
+                population: world.population(level, id),
+            })
+            .collect();
+
+        let mut markets: Vec<MarketView> = world
+            .economy()
+            .markets()
+            .map(|(area, market)| MarketView::of(world, area, market))
+            .collect();
+        markets.sort_by_key(|market| market.area);
+
+        Observation {
+            api_version: API_VERSION,
+            tick: world.tick_count(),
+            elapsed: time.simulation_time().as_secs_f64(),
+            date: time.current_date().to_string(),
+            paused: time.is_paused(),
+            speed: time.speed_multiplier(),
+            economy: EconomySummary::of(world),
+            entities,
+            markets,
+        }
+    }
+}
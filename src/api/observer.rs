@@ -0,0 +1,267 @@
+use std::io::{self, ErrorKind, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::game::Simulation;
+
+use super::Observation;
+
+/// Where `--serve` listens when no address is given
+pub const DEFAULT_SERVE_ADDR: &str = "127.0.0.1:7878";
+
+/// Bytes a client can fall behind by, counting what is waiting to be sent
+/// and the observations skipped meanwhile, before it is dropped
+pub const MAX_BACKLOG: usize = 16 * 1024 * 1024;
+
+/// How long closing waits for each client to take the rest of its line
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Streams an observation of the world to every connected client once a
+/// tick, one JSON document per line. Clients only listen; anything they
+/// send is ignored.
+///
+/// Nothing here blocks the game: connections are accepted when the next
+/// observation goes out, and each client is sent as much of its line as
+/// its socket takes each frame. Observations that come up while a client
+/// is still part way through a line are skipped for it, so it always reads
+/// whole documents, and a client more than [`MAX_BACKLOG`] behind is
+/// dropped rather than waited for.
+pub struct Observer {
+    listener: TcpListener,
+    clients: Vec<Client>,
+    /// The tick last published, so a paused world is not sent again
+    last_tick: Option<u64>,
+    max_backlog: usize,
+}
+
+/// A connected client and the line it is part way through
+struct Client {
+    stream: TcpStream,
+    pending: Vec<u8>,
+    /// How much of `pending` has been sent
+    sent: usize,
+    /// Bytes of observations skipped since `pending` was queued
+    skipped: usize,
+}
+
+impl Client {
+    fn new(stream: TcpStream) -> Self {
+        Self {
+            stream,
+            pending: Vec::new(),
+            sent: 0,
+            skipped: 0,
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        self.sent == self.pending.len()
+    }
+
+    /// Write as much of the pending line as the socket takes without
+    /// blocking
+    fn flush(&mut self) -> io::Result<()> {
+        while !self.is_idle() {
+            match self.stream.write(&self.pending[self.sent..]) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(written) => self.sent += written,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(()),
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        self.pending.clear();
+        self.sent = 0;
+        self.skipped = 0;
+        Ok(())
+    }
+
+    /// Queue a line if the last one has gone, otherwise skip it
+    fn send(&mut self, line: &[u8]) -> io::Result<()> {
+        if self.is_idle() {
+            self.pending.extend_from_slice(line);
+            self.flush()
+        } else {
+            self.skipped += line.len();
+            Ok(())
+        }
+    }
+
+    fn backlog(&self) -> usize {
+        self.pending.len() - self.sent + self.skipped
+    }
+}
+
+impl Observer {
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            clients: Vec::new(),
+            last_tick: None,
+            max_backlog: MAX_BACKLOG,
+        })
+    }
+
+    /// Drop clients once they are this many bytes behind rather than
+    /// [`MAX_BACKLOG`]
+    pub fn set_max_backlog(&mut self, bytes: usize) {
+        self.max_backlog = bytes;
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn client_count(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Carry on sending lines clients are part way through, then send the
+    /// world to every client if it has ticked since the last call
+    pub fn publish(&mut self, simulation: &Simulation) {
+        self.accept();
+        let tick = simulation.world().tick_count();
+        let line = if self.clients.is_empty() || self.last_tick == Some(tick) {
+            None
+        } else {
+            self.last_tick = Some(tick);
+            serde_json::to_vec(&Observation::take(simulation))
+                .ok()
+                .map(|mut line| {
+                    line.push(b'\n');
+                    line
+                })
+        };
+
+        let max_backlog = self.max_backlog;
+        self.clients.retain_mut(|client| {
+            let sent = match &line {
+                Some(line) => client.flush().and_then(|()| client.send(line)),
+                None => client.flush(),
+            };
+            match sent {
+                Ok(()) if client.backlog() <= max_backlog => true,
+                Ok(()) => {
+                    tracing::warn!("Observer dropped a client that fell behind");
+                    false
+                }
+                Err(e) => {
+                    tracing::info!("Observer disconnected: {}", e);
+                    false
+                }
+            }
+        });
+    }
+
+    /// Give each client a moment to take the rest of its line, then hang
+    /// up on it, so each sees the stream end after a whole observation
+    pub fn close(self) -> io::Result<()> {
+        for mut client in self.clients {
+            if !client.is_idle() {
+                client.stream.set_nonblocking(false)?;
+                client.stream.set_write_timeout(Some(CLOSE_TIMEOUT))?;
+                let _ = client.stream.write_all(&client.pending[client.sent..]);
+            }
+            match client.stream.shutdown(Shutdown::Both) {
+                Err(e) if e.kind() != ErrorKind::NotConnected => return Err(e),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn accept(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((client, addr)) => {
+                    if client.set_nonblocking(true).is_ok() {
+                        tracing::info!("Observer connected from {}", addr);
+                        self.clients.push(Client::new(client));
+                        // Newcomers get the current world straight away
+                        self.last_tick = None;
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return,
+                Err(e) => {
+                    tracing::warn!("Observer connection failed: {}", e);
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+
+    #[test]
+    fn test_clients_receive_each_tick_once() {
+        let mut observer = Observer::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(observer.local_addr().unwrap()).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut reader = BufReader::new(client);
+
+        let mut simulation = Simulation::new();
+        observer.publish(&simulation);
+        observer.publish(&simulation);
+        simulation.advance(Duration::from_secs(3600));
+        observer.publish(&simulation);
+        assert_eq!(observer.client_count(), 1);
+
+        let mut ticks = Vec::new();
+        for _ in 0..2 {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let observation: Observation = serde_json::from_str(&line).unwrap();
+            assert!(!observation.entities.is_empty());
+            assert!(!observation.markets.is_empty());
+            ticks.push(observation.tick);
+        }
+        assert_eq!(ticks, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_stalled_clients_never_break_a_line() {
+        let mut observer = Observer::bind("127.0.0.1:0").unwrap();
+        observer.set_max_backlog(4 * 1024 * 1024);
+        let addr = observer.local_addr().unwrap();
+        // Connected but never read from
+        let _stalled = TcpStream::connect(addr).unwrap();
+        let reader = TcpStream::connect(addr).unwrap();
+        reader
+            .set_read_timeout(Some(Duration::from_secs(30)))
+            .unwrap();
+        let reading = std::thread::spawn(move || {
+            let mut ticks = Vec::new();
+            for line in BufReader::new(reader).lines() {
+                let observation: Observation = serde_json::from_str(&line.unwrap()).unwrap();
+                ticks.push(observation.tick);
+            }
+            ticks
+        });
+
+        // The stalled client's socket fills up, its observations are
+        // skipped and it is eventually dropped; the reader keeps up
+        let mut simulation = Simulation::new();
+        observer.publish(&simulation);
+        assert_eq!(observer.client_count(), 2);
+        let mut published = 0;
+        while observer.client_count() == 2 && published < 100_000 {
+            simulation.advance(Duration::from_secs(60));
+            observer.publish(&simulation);
+            published += 1;
+        }
+        assert_eq!(observer.client_count(), 1);
+        observer.close().unwrap();
+
+        let ticks = reading.join().unwrap();
+        assert!(!ticks.is_empty());
+        assert!(ticks.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
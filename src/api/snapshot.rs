@@ -0,0 +1,238 @@
+use serde::{Deserialize, Serialize};
+
+use crate::economy::Market;
+use crate::game::state::EntityId;
+use crate::game::{Simulation, WorldState};
+use crate::zoom::ZoomLevel;
+
+use super::{API_VERSION, ApiVersion};
+
+/// The game as it stands, from the player's point of view
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Snapshot {
+    /// The interface version that produced this snapshot
+    pub api_version: ApiVersion,
+    pub tick: u64,
+    /// Simulation seconds since the start of the game
+    pub elapsed: f64,
+    /// Calendar date, such as "Jan 02, Y3000"
+    pub date: String,
+    pub time_of_day: String,
+    pub paused: bool,
+    pub speed: f64,
+    pub view: View,
+    pub economy: EconomySummary,
+    /// The local market, when a local area or room is in view
+    pub market: Option<MarketView>,
+    pub business: BusinessView,
+    /// The current campaign objective, if a campaign is running
+    pub objective: Option<String>,
+}
+
+/// Where the player is looking
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct View {
+    pub level: ZoomLevel,
+    /// The place whose map is shown; None at the galaxy
+    pub id: Option<EntityId>,
+    pub name: String,
+    /// Names from the galaxy down to the place in view
+    pub path: Vec<String>,
+    pub population: Option<u64>,
+}
+
+/// Galaxy-wide figures
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct EconomySummary {
+    pub currency_symbol: String,
+    pub money_supply: f64,
+    pub price_level: f64,
+    pub firms: usize,
+    pub population: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct MarketView {
+    pub area: EntityId,
+    pub open: bool,
+    pub quotes: Vec<PriceQuote>,
+}
+
+/// One good on a market's board
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct PriceQuote {
+    pub good: String,
+    pub price: f64,
+    pub stock: f64,
+    /// Units sold per hour over the latest hour recorded. Since 1.3.
+    #[serde(default)]
+    pub volume: f64,
+}
+
+/// A good's recorded prices on one market, oldest first. Older points
+/// cover a whole day each. Since 1.3.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct PriceHistoryView {
+    pub area: EntityId,
+    pub good: String,
+    pub points: Vec<HistoryPoint>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct HistoryPoint {
+    /// Simulation hour the point starts at
+    pub hour: u64,
+    pub hours: u32,
+    /// Mean price over the point
+    pub price: f64,
+    /// Units sold over the point
+    pub volume: f64,
+}
+
+/// The player's purse and the firms they own
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct BusinessView {
+    pub purse: f64,
+    pub firms: Vec<FirmView>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct FirmView {
+    pub id: EntityId,
+    pub name: String,
+    pub area: EntityId,
+    pub workers: u32,
+    pub cash: f64,
+    /// Cash beyond the capital put in
+    pub profit: f64,
+}
+
+impl EconomySummary {
+    pub(super) fn of(world: &WorldState) -> Self {
+        let economy = world.economy();
+        EconomySummary {
+            currency_symbol: economy.currency().symbol.clone(),
+            money_supply: economy.money_supply().issued().amount(),
+            price_level: economy.money_supply().price_level(),
+            firms: economy.firm_count(),
+            population: world.census().totals().population,
+        }
+    }
+}
+
+impl MarketView {
+    pub(super) fn of(world: &WorldState, area: EntityId, market: &Market) -> Self {
+        let history = world.economy().history();
+        MarketView {
+            area,
+            open: world.market_status(area).is_some_and(|s| s.is_open()),
+            quotes: world
+                .economy()
+                .catalog()
+                .iter()
+                .map(|(good, info)| PriceQuote {
+                    good: info.name.clone(),
+                    price: market.price(good).amount(),
+                    stock: market.stock(good),
+                    volume: history
+                        .series(area, good)
+                        .and_then(|series| series.latest())
+                        .map_or(0.0, |point| point.volume / point.hours.max(1) as f64),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl PriceHistoryView {
+    pub(super) fn of(world: &WorldState, area: EntityId, good: &str) -> Option<Self> {
+        let economy = world.economy();
+        let id = economy.catalog().id(good)?;
+        let series = economy.history().series(area, id)?;
+        Some(PriceHistoryView {
+            area,
+            good: good.to_string(),
+            points: series
+                .points()
+                .map(|point| HistoryPoint {
+                    hour: point.hour,
+                    hours: point.hours,
+                    price: point.price,
+                    volume: point.volume,
+                })
+                .collect(),
+        })
+    }
+}
+
+impl Snapshot {
+    pub(super) fn take(simulation: &Simulation) -> Self {
+        let world = simulation.world();
+        let economy = world.economy();
+        let time = simulation.time();
+        let level = simulation.zoom().current_level();
+        let position = simulation.zoom().position();
+        let id = position.map_owner(level);
+
+        let market = match level {
+            ZoomLevel::LocalArea | ZoomLevel::Room => position
+                .map_owner(ZoomLevel::LocalArea)
+                .and_then(|area| Some((area, economy.market(area)?))),
+            _ => None,
+        }
+        .map(|(area, market)| MarketView::of(world, area, market));
+
+        let firms = world
+            .business()
+            .holdings()
+            .iter()
+            .filter_map(|holding| {
+                let firm = economy.firm(holding.firm)?;
+                Some(FirmView {
+                    id: firm.id,
+                    name: firm.name.clone(),
+                    area: firm.area_id,
+                    workers: firm.workers,
+                    cash: firm.cash.amount(),
+                    profit: holding.profit(economy)?.amount(),
+                })
+            })
+            .collect();
+
+        Snapshot {
+            api_version: API_VERSION,
+            tick: world.tick_count(),
+            elapsed: time.simulation_time().as_secs_f64(),
+            date: time.current_date().to_string(),
+            time_of_day: time.format_time(),
+            paused: time.is_paused(),
+            speed: time.speed_multiplier(),
+            view: View {
+                level,
+                id,
+                name: world.location_name(position, level),
+                path: world.location_path(position, level),
+                population: id.and_then(|id| world.population(level, id)),
+            },
+            economy: EconomySummary::of(world),
+            market,
+            business: BusinessView {
+                purse: economy.purse().amount(),
+                firms,
+            },
+            objective: world
+                .campaign()
+                .and_then(|campaign| campaign.objective())
+                .map(|objective| objective.description.clone()),
+        }
+    }
+}
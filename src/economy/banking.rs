@@ -0,0 +1,354 @@
+//! Deposits, loans and interest.
+//!
+//! A single [`Bank`] serves every firm. Firms bank cash beyond what they
+//! need to run and draw on it again when they run short; once their
+//! deposits are gone they borrow, up to a credit limit, at the lending rate.
+//! Interest accrues on both sides every tick, and a firm whose debt grows
+//! too far past its limit defaults: the bank takes its deposits and writes
+//! off the rest.
+//!
+//! The bank only lends out reserves above a share of its deposits, and its
+//! lending rate climbs with recent losses, so a run of defaults tightens
+//! credit for everyone until the losses are forgotten.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+use super::{Firm, Money};
+use crate::game::state::EntityId;
+use crate::time::DAYS_PER_YEAR;
+
+/// Annual interest rate the bank starts out following
+pub const DEFAULT_POLICY_RATE: f64 = 0.04;
+
+/// Highest policy rate that can be set
+pub const MAX_POLICY_RATE: f64 = 0.5;
+
+/// Added to the policy rate for lending
+const LENDING_SPREAD: f64 = 0.03;
+
+/// Share of the policy rate paid on deposits
+const DEPOSIT_SHARE: f64 = 0.5;
+
+/// Added to the lending rate for each unit of recent losses per unit lent
+const LOSS_PREMIUM: f64 = 0.5;
+
+/// Days over which recent losses fall to half
+const LOSS_HALF_LIFE_DAYS: f64 = 30.0;
+
+/// Share of deposits the bank keeps in reserve rather than lending
+const RESERVE_RATIO: f64 = 0.1;
+
+/// Firms bank cash above this
+const SURPLUS_CASH: Money = Money(6_000.0);
+
+/// Firms draw on deposits or borrow when cash falls below this...
+const SHORT_CASH: Money = Money(1_000.0);
+
+/// ...up to this much
+const WORKING_CASH: Money = Money(3_000.0);
+
+/// Most a firm that has never defaulted can owe before it stops borrowing
+const CREDIT_LIMIT: Money = Money(5_000.0);
+
+/// A firm defaults once its debt passes its credit limit by this factor
+const DEFAULT_MARGIN: f64 = 1.25;
+
+/// Daily samples kept for the monetary dashboard
+pub const BANK_HISTORY_DAYS: usize = 60;
+
+/// A firm's standing with the bank
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Account {
+    pub deposit: Money,
+    /// Owed to the bank, interest included
+    pub loan: Money,
+    /// Loans the firm has defaulted on, each of which cuts its credit
+    pub defaults: u32,
+}
+
+impl Account {
+    pub fn credit_limit(&self) -> Money {
+        CREDIT_LIMIT / (1.0 + self.defaults as f64)
+    }
+}
+
+/// One day of banking figures
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BankSample {
+    pub money_supply: Money,
+    pub deposits: Money,
+    pub loans: Money,
+    /// Lent during the day
+    pub lent: Money,
+    /// Written off during the day
+    pub written_off: Money,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bank {
+    /// Annual rate every other rate follows
+    policy_rate: f64,
+    /// Cash in the vault
+    reserves: Money,
+    accounts: BTreeMap<EntityId, Account>,
+    /// Written off lately, fading with [`LOSS_HALF_LIFE_DAYS`]
+    recent_losses: Money,
+    /// Lent and written off since the last sample
+    today: BankSample,
+    history: VecDeque<BankSample>,
+    /// Firms that defaulted since they were last collected
+    #[serde(skip)]
+    defaulted: Vec<EntityId>,
+}
+
+impl Bank {
+    pub fn new() -> Self {
+        Self {
+            policy_rate: DEFAULT_POLICY_RATE,
+            reserves: Money::ZERO,
+            accounts: BTreeMap::new(),
+            recent_losses: Money::ZERO,
+            today: BankSample::default(),
+            history: VecDeque::new(),
+            defaulted: Vec::new(),
+        }
+    }
+
+    pub fn policy_rate(&self) -> f64 {
+        self.policy_rate
+    }
+
+    pub fn set_policy_rate(&mut self, rate: f64) {
+        self.policy_rate = rate.clamp(0.0, MAX_POLICY_RATE);
+    }
+
+    /// Annual rate charged on loans
+    pub fn lending_rate(&self) -> f64 {
+        let lent = self.loans() + self.recent_losses;
+        let loss_ratio = if lent > Money::ZERO {
+            self.recent_losses / lent
+        } else {
+            0.0
+        };
+        self.policy_rate + LENDING_SPREAD + LOSS_PREMIUM * loss_ratio
+    }
+
+    /// Annual rate paid on deposits
+    pub fn deposit_rate(&self) -> f64 {
+        self.policy_rate * DEPOSIT_SHARE
+    }
+
+    pub fn reserves(&self) -> Money {
+        self.reserves
+    }
+
+    /// Owed to depositors
+    pub fn deposits(&self) -> Money {
+        self.accounts.values().map(|a| a.deposit).sum()
+    }
+
+    /// Owed by borrowers
+    pub fn loans(&self) -> Money {
+        self.accounts.values().map(|a| a.loan).sum()
+    }
+
+    pub fn account(&self, firm: EntityId) -> Account {
+        self.accounts.get(&firm).copied().unwrap_or_default()
+    }
+
+    /// Daily figures, oldest first
+    pub fn history(&self) -> impl Iterator<Item = &BankSample> {
+        self.history.iter()
+    }
+
+    /// Reserves the bank is free to lend
+    fn lendable(&self) -> Money {
+        (self.reserves - self.deposits() * RESERVE_RATIO).max(Money::ZERO)
+    }
+
+    /// Accrue interest over `hours`, then let every firm bank its surplus,
+    /// repay or borrow, and default where its debt has grown too far
+    pub fn update(&mut self, hours: f64, firms: &mut BTreeMap<EntityId, Firm>) {
+        let years = hours / (DAYS_PER_YEAR as f64 * 24.0);
+        let loan_growth = (1.0 + self.lending_rate()).powf(years);
+        let deposit_growth = (1.0 + self.deposit_rate()).powf(years);
+        self.recent_losses = self.recent_losses * 0.5f64.powf(hours / 24.0 / LOSS_HALF_LIFE_DAYS);
+
+        for firm in firms.values_mut() {
+            let mut account = self.account(firm.id);
+            account.loan = account.loan * loan_growth;
+            account.deposit = account.deposit * deposit_growth;
+
+            if firm.cash > SURPLUS_CASH {
+                let surplus = firm.cash - SURPLUS_CASH;
+                let repaid = surplus.min(account.loan);
+                account.loan -= repaid;
+                account.deposit += surplus - repaid;
+                firm.cash -= surplus;
+                self.reserves += surplus;
+            } else if firm.cash < SHORT_CASH {
+                let mut need = WORKING_CASH - firm.cash;
+                let drawn = need.min(account.deposit).min(self.reserves);
+                account.deposit -= drawn;
+                need -= drawn;
+                let headroom = (account.credit_limit() - account.loan).max(Money::ZERO);
+                let lent = need.min(headroom).min(self.lendable());
+                account.loan += lent;
+                self.today.lent += lent;
+                firm.cash += drawn + lent;
+                self.reserves -= drawn + lent;
+            }
+
+            if account.loan > account.credit_limit() * DEFAULT_MARGIN {
+                let seized = account.deposit.min(account.loan);
+                account.deposit -= seized;
+                account.loan -= seized;
+                self.write_off(&mut account);
+                self.defaulted.push(firm.id);
+            }
+
+            if account == Account::default() {
+                self.accounts.remove(&firm.id);
+            } else {
+                self.accounts.insert(firm.id, account);
+            }
+        }
+    }
+
+    fn write_off(&mut self, account: &mut Account) {
+        self.today.written_off += account.loan;
+        self.recent_losses += account.loan;
+        account.loan = Money::ZERO;
+        account.defaults += 1;
+    }
+
+    /// Settle a closing firm's account: pay out its deposit as far as
+    /// reserves allow, take what it owes out of its cash and write off
+    /// whatever is left
+    pub fn close_account(&mut self, firm: &mut Firm) {
+        let Some(mut account) = self.accounts.remove(&firm.id) else {
+            return;
+        };
+        let paid = account.deposit.min(self.reserves);
+        self.reserves -= paid;
+        firm.cash += paid;
+        let repaid = account.loan.min(firm.cash);
+        account.loan -= repaid;
+        firm.cash -= repaid;
+        self.reserves += repaid;
+        if account.loan > Money::ZERO {
+            self.write_off(&mut account);
+        }
+    }
+
+    /// Firms that defaulted since the last call
+    pub fn take_defaults(&mut self) -> Vec<EntityId> {
+        std::mem::take(&mut self.defaulted)
+    }
+
+    /// Close the day's figures, with the money supply as it stands
+    pub fn sample(&mut self, money_supply: Money) {
+        let mut day = std::mem::take(&mut self.today);
+        day.money_supply = money_supply;
+        day.deposits = self.deposits();
+        day.loans = self.loans();
+        if self.history.len() == BANK_HISTORY_DAYS {
+            self.history.pop_front();
+        }
+        self.history.push_back(day);
+    }
+}
+
+impl Default for Bank {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::RecipeId;
+
+    fn firm(id: EntityId, cash: f64) -> Firm {
+        let mut firm = Firm::new(id, "Firm", 1, RecipeId(0));
+        firm.cash = Money(cash);
+        firm
+    }
+
+    fn firms(list: impl IntoIterator<Item = Firm>) -> BTreeMap<EntityId, Firm> {
+        list.into_iter().map(|firm| (firm.id, firm)).collect()
+    }
+
+    #[test]
+    fn test_savers_fund_borrowers() {
+        let mut bank = Bank::new();
+        let mut firms = firms([firm(1, 30_000.0), firm(2, 0.0)]);
+        bank.update(1.0, &mut firms);
+
+        assert_eq!(firms[&1].cash, SURPLUS_CASH);
+        assert_eq!(bank.account(1).deposit, Money(24_000.0));
+        assert_eq!(firms[&2].cash, WORKING_CASH);
+        assert_eq!(bank.account(2).loan, WORKING_CASH);
+        assert_eq!(bank.reserves(), Money(21_000.0));
+        assert!(bank.lending_rate() > bank.deposit_rate());
+
+        // Interest builds up on both sides
+        bank.update(24.0 * 30.0, &mut firms);
+        assert!(bank.account(1).deposit > Money(24_000.0));
+        assert!(bank.account(2).loan > WORKING_CASH);
+
+        // A flush borrower pays its loan off before saving
+        firms.get_mut(&2).unwrap().cash = Money(16_000.0);
+        bank.update(0.0, &mut firms);
+        assert_eq!(bank.account(2).loan, Money::ZERO);
+        assert!(bank.account(2).deposit > Money::ZERO);
+        assert!(bank.account(2).deposit < Money(10_000.0));
+
+        bank.sample(Money(100_000.0));
+        let day = bank.history().last().unwrap();
+        assert_eq!(day.lent, WORKING_CASH);
+        assert_eq!(day.loans, Money::ZERO);
+    }
+
+    #[test]
+    fn test_defaults_tighten_credit() {
+        let mut bank = Bank::new();
+        let mut firms = firms([firm(1, 100_000.0), firm(2, 0.0)]);
+        bank.set_policy_rate(MAX_POLICY_RATE);
+        let calm = bank.lending_rate();
+
+        // The borrower spends everything it borrows and never recovers
+        for _ in 0..40 {
+            firms.get_mut(&2).unwrap().cash = Money::ZERO;
+            bank.update(24.0 * 30.0, &mut firms);
+        }
+        assert_eq!(bank.take_defaults().first(), Some(&2));
+        assert!(bank.account(2).defaults > 0);
+        assert!(bank.account(2).credit_limit() < CREDIT_LIMIT);
+        assert!(bank.lending_rate() > calm);
+        let written_off = bank.today.written_off;
+        assert!(written_off > Money::ZERO);
+    }
+
+    #[test]
+    fn test_closing_settles_the_account() {
+        let mut bank = Bank::new();
+        let mut firms = firms([firm(1, 12_000.0), firm(2, 0.0)]);
+        bank.update(0.0, &mut firms);
+        assert_eq!(bank.account(2).loan, WORKING_CASH);
+
+        let mut borrower = firms.remove(&2).unwrap();
+        borrower.cash = Money(500.0);
+        bank.close_account(&mut borrower);
+        assert_eq!(borrower.cash, Money::ZERO);
+        assert_eq!(bank.account(2), Account::default());
+        assert_eq!(bank.today.written_off, Money(2_500.0));
+
+        let mut saver = firms.remove(&1).unwrap();
+        bank.close_account(&mut saver);
+        assert_eq!(saver.cash, Money(9_500.0));
+        assert_eq!(bank.reserves(), Money::ZERO);
+    }
+}
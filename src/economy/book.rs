@@ -0,0 +1,271 @@
+//! Each good's order book on a market.
+//!
+//! A market's households stand on both sides of every good's book with a
+//! ladder of [`LOT`]-sized levels priced along the market's stock curve:
+//! each ask at what the good would trade at with another lot off the
+//! shelf, each bid at what it would with another lot on it. Firms' standing
+//! orders sit in the book at their limits. Matching first crosses firms'
+//! bids with their asks wherever they meet, at the limit of whichever order
+//! was placed first, then fills what is left against the ladder a lot at a
+//! time, so the more an order takes the further up or down the ladder it
+//! pays. [`Depth`] is the book as the dashboard shows it.
+
+use std::collections::BTreeSet;
+
+use super::orders::FILLED;
+use super::{Firm, GoodId, GoodsCatalog, Market, Money, Order, Side};
+
+/// Units at each level of the households' ladder
+pub const LOT: f64 = 5.0;
+
+/// Units offered or wanted at one price
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Level {
+    pub price: Money,
+    pub quantity: f64,
+}
+
+/// The best levels on each side of a good's book
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Depth {
+    /// Highest first
+    pub bids: Vec<Level>,
+    /// Lowest first
+    pub asks: Vec<Level>,
+}
+
+impl Depth {
+    pub fn best_bid(&self) -> Option<Money> {
+        self.bids.first().map(|level| level.price)
+    }
+
+    pub fn best_ask(&self) -> Option<Money> {
+        self.asks.first().map(|level| level.price)
+    }
+
+    /// How far the best ask is above the best bid, if both sides have any
+    pub fn spread(&self) -> Option<Money> {
+        Some(self.best_ask()? - self.best_bid()?)
+    }
+
+    /// Units wanted across the bids
+    pub fn bid_depth(&self) -> f64 {
+        self.bids.iter().map(|level| level.quantity).sum()
+    }
+
+    /// Units offered across the asks
+    pub fn ask_depth(&self) -> f64 {
+        self.asks.iter().map(|level| level.quantity).sum()
+    }
+
+    /// Put each side best first
+    pub(super) fn sort(&mut self) {
+        self.bids
+            .sort_by(|a, b| b.price.amount().total_cmp(&a.price.amount()));
+        self.asks
+            .sort_by(|a, b| a.price.amount().total_cmp(&b.price.amount()));
+    }
+}
+
+/// Match a market's standing orders, firms' with each other and then with
+/// the households' ladder, dropping those that are done or whose firm no
+/// longer trades there
+pub(super) fn match_orders(market: &mut Market, firms: &mut [&mut Firm], catalog: &GoodsCatalog) {
+    let mut orders = market.take_orders();
+    orders.retain(|order| firms.iter().any(|firm| firm.id == order.firm));
+    cross(&mut orders, market, firms, catalog);
+    for order in orders.iter_mut() {
+        if let Some(firm) = firms.iter_mut().find(|firm| firm.id == order.firm) {
+            order.fill(market, firm, catalog);
+        }
+    }
+    for order in orders {
+        market.place_order(order);
+    }
+}
+
+/// Trade firms' buy orders against their sell orders wherever a bid meets
+/// an ask, best prices first and the older order first at the same price,
+/// as far as the buyer's cash and room and the seller's stock allow. Each
+/// trade goes at the limit of whichever of the two orders is older.
+fn cross(
+    orders: &mut [Order],
+    market: &mut Market,
+    firms: &mut [&mut Firm],
+    catalog: &GoodsCatalog,
+) {
+    let goods: BTreeSet<GoodId> = orders.iter().map(|order| order.good).collect();
+    for good in goods {
+        // Orders are oldest first, so a stable sort keeps age as the
+        // tie-break
+        let side = |side: Side| -> Vec<usize> {
+            (0..orders.len())
+                .filter(|&i| orders[i].good == good && orders[i].side == side)
+                .collect()
+        };
+        let mut bids = side(Side::Buy);
+        bids.sort_by(|&a, &b| {
+            orders[b]
+                .limit
+                .amount()
+                .total_cmp(&orders[a].limit.amount())
+        });
+        let mut asks = side(Side::Sell);
+        asks.sort_by(|&a, &b| {
+            orders[a]
+                .limit
+                .amount()
+                .total_cmp(&orders[b].limit.amount())
+        });
+
+        for &bid in &bids {
+            for &ask in &asks {
+                if orders[bid].limit < orders[ask].limit || orders[bid].is_filled() {
+                    break;
+                }
+                if orders[ask].is_filled() || orders[ask].firm == orders[bid].firm {
+                    continue;
+                }
+                let price = orders[bid.min(ask)].limit;
+                let (Some(buyer), Some(seller)) = (
+                    firms.iter().position(|firm| firm.id == orders[bid].firm),
+                    firms.iter().position(|firm| firm.id == orders[ask].firm),
+                ) else {
+                    continue;
+                };
+                let Ok([buyer, seller]) = firms.get_disjoint_mut([buyer, seller]) else {
+                    continue;
+                };
+                let mut units = orders[bid]
+                    .quantity
+                    .min(orders[ask].quantity)
+                    .min(seller.held(good))
+                    .min(buyer.stock.room_for(good, catalog));
+                if price > Money::ZERO {
+                    units = units.min(buyer.cash / price);
+                }
+                if units <= FILLED {
+                    continue;
+                }
+                let released = seller.release(good, units);
+                let held = buyer.hold(good, released, catalog);
+                seller.hold(good, released - held, catalog);
+                let cost = price * held;
+                buyer.cash -= cost;
+                seller.cash += cost;
+                market.record_trade(good, held, cost);
+                orders[bid].quantity -= held;
+                orders[ask].quantity -= held;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::data::{default_catalog, default_recipes};
+
+    #[test]
+    fn test_large_orders_walk_up_the_ladder() {
+        let catalog = default_catalog();
+        let grain = catalog.id("grain").unwrap();
+        let info = catalog.get(grain).unwrap();
+        let mut market = Market::new(&catalog);
+        let (ask, bid) = (market.ask(grain, info), market.bid(grain, info));
+        assert!(bid < market.price(grain) && market.price(grain) < ask);
+
+        let mut small = market.clone();
+        let (bought, cost) = small.fill_buy(grain, info, LOT, Money(100.0), Money(100.0));
+        assert_eq!((bought, cost), (LOT, ask * LOT));
+
+        // Forty units reach eight levels deep and pay more for each
+        let (bought, cost) = market.fill_buy(grain, info, 40.0, Money(100.0), Money(100.0));
+        assert_eq!(bought, 40.0);
+        assert!(cost / bought > ask);
+        assert_eq!(market.cash(), cost);
+        assert_eq!(market.volume(grain), 40.0);
+        assert!(market.ask(grain, info) > ask);
+
+        // A limit stops the walk at the first level above it
+        let limit = market.ask(grain, info);
+        let (bought, _) = market.fill_buy(grain, info, 40.0, limit, Money(100.0));
+        assert_eq!(bought, LOT);
+
+        // Selling back goes down the bids and fetches less than it cost
+        let (sold, paid) = market.fill_sell(grain, info, 45.0, Money::ZERO);
+        assert_eq!(sold, 45.0);
+        assert!(paid < cost + limit * LOT);
+    }
+
+    #[test]
+    fn test_crossing_orders_trade_at_the_older_limit() {
+        let catalog = default_catalog();
+        let recipes = default_recipes(&catalog);
+        let grain = catalog.id("grain").unwrap();
+        let baking = recipes.find("baking").unwrap();
+        let mut market = Market::new(&catalog);
+        let mut buyer = Firm::new(1, "Bakery", 1, baking);
+        buyer.cash = Money(100.0);
+        let mut seller = Firm::new(2, "Granary", 1, baking);
+        seller.cash = Money::ZERO;
+        seller.hold(grain, 30.0, &catalog);
+
+        let bid = Order {
+            firm: 1,
+            side: Side::Buy,
+            good: grain,
+            quantity: 20.0,
+            limit: Money(2.0),
+        };
+        market.place_order(bid);
+        market.place_order(Order {
+            firm: 2,
+            side: Side::Sell,
+            quantity: 25.0,
+            limit: Money(1.5),
+            ..bid
+        });
+        let depth = market.depth(grain, catalog.get(grain).unwrap(), 3);
+        // The bakery bids over the households' ask, crossing the book
+        assert_eq!(depth.best_bid(), Some(Money(2.0)));
+        assert!(depth.spread().unwrap() < Money::ZERO);
+
+        match_orders(&mut market, &mut [&mut buyer, &mut seller], &catalog);
+        // The bid came first, so the trade goes at its limit, and none of
+        // it comes off the shelf
+        assert_eq!(buyer.held(grain), 20.0);
+        assert_eq!(seller.held(grain), 10.0);
+        assert_eq!(seller.cash, Money(40.0));
+        assert_eq!(buyer.cash, Money(60.0));
+        assert_eq!(market.stock(grain), crate::economy::TARGET_STOCK);
+        assert_eq!(market.volume(grain), 20.0);
+        // The buy is filled; what is left of the sell stands, too dear
+        // for the households
+        assert_eq!(market.orders().len(), 1);
+        assert_eq!(market.orders()[0].side, Side::Sell);
+        assert_eq!(market.orders()[0].quantity, 5.0);
+    }
+
+    #[test]
+    fn test_depth_lists_the_ladder_best_first() {
+        let catalog = default_catalog();
+        let grain = catalog.id("grain").unwrap();
+        let info = catalog.get(grain).unwrap();
+        let mut market = Market::new(&catalog);
+        market.deposit(Money(1_000.0));
+        market.take(grain, 92.0);
+
+        let depth = market.depth(grain, info, 4);
+        // Eight units left make one full lot and one of three
+        let asks: Vec<f64> = depth.asks.iter().map(|level| level.quantity).collect();
+        assert_eq!(asks, vec![LOT, 3.0]);
+        assert_eq!(depth.bids.len(), 4);
+        assert_eq!(depth.bid_depth(), 4.0 * LOT);
+        assert!(depth.bids.windows(2).all(|w| w[0].price > w[1].price));
+        // Prices stop rising once the shelf is near bare
+        assert!(depth.asks.windows(2).all(|w| w[0].price <= w[1].price));
+        assert!(depth.spread().unwrap() > Money::ZERO);
+        assert_eq!(depth.best_ask(), Some(market.ask(grain, info)));
+    }
+}
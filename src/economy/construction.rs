@@ -0,0 +1,259 @@
+//! Buildings going up in local areas.
+//!
+//! A [`Site`] is a building that has been paid for but not finished. Its
+//! budget buys the materials its kind of building takes from the area's
+//! market, and work goes on for as many hours as the [`BuildPlan`] says,
+//! never getting further ahead than the materials delivered allow. Once
+//! finished the site opens as a [`Firm`] running its recipe, with whatever
+//! is left of the budget as its cash.
+//!
+//! The player commissions buildings, and so do firms that are busy, well
+//! off and surrounded by workers nobody has hired yet.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use super::{BuildingKind, Firm, GoodId, GoodsCatalog, Market, Money, RecipeBook, RecipeId};
+use crate::game::state::EntityId;
+use crate::time::Calendar;
+
+/// How often firms look at putting up another building
+pub const EXPANSION_INTERVAL: Duration = Calendar::DEFAULT_DAY_LENGTH;
+
+/// A firm sets this much aside to put up another building...
+pub const EXPANSION_BUDGET: Money = Money(4_000.0);
+
+/// ...once its cash and deposits come to this much...
+pub const EXPANSION_WORTH: Money = Money(15_000.0);
+
+/// ...and it ran at least this close to full capacity in the last tick
+pub const EXPANSION_UTILIZATION: f64 = 0.9;
+
+/// Shortfall left when a material counts as delivered
+const DELIVERED: f64 = 1e-9;
+
+/// What it takes to put up one kind of building
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BuildPlan {
+    /// Days of work once the materials are in
+    pub days: f64,
+    /// Units of each good, by name; goods missing from the catalog aren't
+    /// needed
+    pub materials: &'static [(&'static str, f64)],
+}
+
+impl BuildPlan {
+    pub fn of(kind: BuildingKind) -> Self {
+        let (days, materials): (f64, &'static [(&'static str, f64)]) = match kind {
+            BuildingKind::Farm => (5.0, &[("timber", 150.0), ("tools", 10.0)]),
+            BuildingKind::Mine => (12.0, &[("timber", 200.0), ("iron", 40.0), ("tools", 20.0)]),
+            BuildingKind::LumberCamp => (4.0, &[("timber", 80.0), ("tools", 10.0)]),
+            BuildingKind::Fishery => (6.0, &[("timber", 200.0), ("tools", 5.0)]),
+            BuildingKind::Factory => (15.0, &[("timber", 300.0), ("iron", 80.0), ("tools", 30.0)]),
+            BuildingKind::Workshop => (7.0, &[("timber", 200.0), ("tools", 15.0)]),
+        };
+        Self { days, materials }
+    }
+}
+
+/// Who commissioned a site
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Builder {
+    Player,
+    Firm(EntityId),
+}
+
+/// A building under construction, which becomes the firm with its id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Site {
+    pub id: EntityId,
+    pub name: String,
+    pub area_id: EntityId,
+    /// Location on the area's map
+    pub coords: (i32, i32),
+    /// What the finished building will make
+    pub recipe: RecipeId,
+    /// Most workers the finished firm will hire
+    pub max_workers: Option<u32>,
+    pub builder: Builder,
+    /// Cash put in when the site was commissioned
+    pub budget: Money,
+    /// What is left of the budget
+    pub cash: Money,
+    /// Units of each material the building takes
+    pub materials: BTreeMap<GoodId, f64>,
+    /// Units of each material bought so far
+    pub delivered: BTreeMap<GoodId, f64>,
+    /// Hours of work the building takes
+    pub hours: f64,
+    /// Hours of work done so far
+    pub worked: f64,
+}
+
+impl Site {
+    /// A site for a building running `recipe`, or None if there is no
+    /// such recipe
+    pub fn new(
+        id: EntityId,
+        name: &str,
+        area_id: EntityId,
+        recipe: RecipeId,
+        recipes: &RecipeBook,
+        catalog: &GoodsCatalog,
+    ) -> Option<Self> {
+        let plan = BuildPlan::of(recipes.get(recipe)?.building);
+        let materials = plan
+            .materials
+            .iter()
+            .filter_map(|&(name, quantity)| Some((catalog.id(name)?, quantity)))
+            .collect();
+        Some(Self {
+            id,
+            name: name.to_string(),
+            area_id,
+            coords: (0, 0),
+            recipe,
+            max_workers: None,
+            builder: Builder::Player,
+            budget: Money::ZERO,
+            cash: Money::ZERO,
+            materials,
+            delivered: BTreeMap::new(),
+            hours: plan.days * 24.0,
+            worked: 0.0,
+        })
+    }
+
+    /// Units of a material still to buy
+    pub fn missing(&self, good: GoodId) -> f64 {
+        let needed = self.materials.get(&good).copied().unwrap_or(0.0);
+        let delivered = self.delivered.get(&good).copied().unwrap_or(0.0);
+        match needed - delivered {
+            left if left > DELIVERED => left,
+            _ => 0.0,
+        }
+    }
+
+    /// Share of the materials delivered, going by the one furthest behind
+    pub fn supplied(&self) -> f64 {
+        self.materials
+            .iter()
+            .filter(|&(_, &needed)| needed > 0.0)
+            .map(|(&good, &needed)| 1.0 - self.missing(good) / needed)
+            .fold(1.0, f64::min)
+    }
+
+    /// Share of the work done
+    pub fn progress(&self) -> f64 {
+        if self.hours <= 0.0 {
+            1.0
+        } else {
+            (self.worked / self.hours).min(1.0)
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.worked >= self.hours && self.supplied() >= 1.0
+    }
+
+    /// What the materials still to buy cost at a market's prices
+    pub fn remaining_cost(&self, market: &Market) -> Money {
+        self.materials
+            .keys()
+            .map(|&good| market.price(good) * self.missing(good))
+            .sum()
+    }
+
+    /// Buy as many of the missing materials as the site's cash stretches
+    /// to, up the market's ladder of asks, then work for up to `hours`
+    pub fn advance(&mut self, market: &mut Market, catalog: &GoodsCatalog, hours: f64) {
+        let goods: Vec<GoodId> = self.materials.keys().copied().collect();
+        for good in goods {
+            let Some(info) = catalog.get(good) else {
+                continue;
+            };
+            let (bought, cost) =
+                market.fill_buy(good, info, self.missing(good), Money::MAX, self.cash);
+            self.cash -= cost;
+            *self.delivered.entry(good).or_insert(0.0) += bought;
+        }
+
+        let allowed = self.hours * self.supplied();
+        if self.worked < allowed {
+            self.worked = (self.worked + hours.max(0.0)).min(allowed);
+        }
+    }
+
+    /// The firm the finished building opens as
+    pub fn into_firm(self) -> Firm {
+        let mut firm = Firm::new(self.id, &self.name, self.area_id, self.recipe);
+        firm.coords = self.coords;
+        firm.max_workers = self.max_workers;
+        firm.cash = self.cash;
+        firm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::data::{default_catalog, default_recipes};
+
+    #[test]
+    fn test_work_waits_for_materials() {
+        let catalog = default_catalog();
+        let recipes = default_recipes(&catalog);
+        let timber = catalog.id("timber").unwrap();
+        let tools = catalog.id("tools").unwrap();
+        let mut market = Market::new(&catalog);
+        market.add(timber, 1_000.0);
+        market.take(tools, f64::INFINITY);
+
+        let baking = recipes.find("baking").unwrap();
+        let mut site = Site::new(5, "Bakery", 1, baking, &recipes, &catalog).unwrap();
+        site.cash = Money(10_000.0);
+        assert_eq!(site.hours, 7.0 * 24.0);
+        assert_eq!(site.materials.len(), 2);
+
+        // Timber comes in but no tools, so no work can be done
+        let cash = market.cash();
+        site.advance(&mut market, &catalog, 24.0);
+        assert_eq!(site.missing(timber), 0.0);
+        assert_eq!(site.supplied(), 0.0);
+        assert_eq!(site.worked, 0.0);
+        let paid = (market.cash() - cash).amount();
+        assert!((paid - (10_000.0 - site.cash.amount())).abs() < 1e-6);
+
+        market.add(tools, 5.0);
+        site.advance(&mut market, &catalog, 24.0);
+        assert!((site.supplied() - 1.0 / 3.0).abs() < 1e-9);
+        assert!((site.worked - 24.0).abs() < 1e-9);
+        site.advance(&mut market, &catalog, 100.0);
+        assert!((site.progress() - 1.0 / 3.0).abs() < 1e-9);
+
+        market.add(tools, 100.0);
+        for _ in 0..7 {
+            site.advance(&mut market, &catalog, 24.0);
+        }
+        assert!(site.is_finished());
+        let left = site.cash;
+        let firm = site.into_firm();
+        assert_eq!((firm.id, firm.recipe, firm.cash), (5, baking, left));
+    }
+
+    #[test]
+    fn test_sites_stall_without_cash() {
+        let catalog = default_catalog();
+        let recipes = default_recipes(&catalog);
+        let mut market = Market::new(&catalog);
+        let logging = recipes.find("logging").unwrap();
+        let mut site = Site::new(5, "Camp", 1, logging, &recipes, &catalog).unwrap();
+        site.cash = site.remaining_cost(&market) / 2.0;
+
+        site.advance(&mut market, &catalog, 1_000.0);
+        assert_eq!(site.cash, Money::ZERO);
+        assert!(site.supplied() < 1.0 && !site.is_finished());
+        assert!(site.worked < site.hours);
+    }
+}
@@ -0,0 +1,263 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// An amount of money in the economy's currency
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct Money(pub f64);
+
+impl Money {
+    pub const ZERO: Money = Money(0.0);
+
+    /// More than any price, as the limit of a buy that takes whatever the
+    /// market asks
+    pub const MAX: Money = Money(f64::MAX);
+
+    pub fn amount(self) -> f64 {
+        self.0
+    }
+
+    pub fn min(self, other: Money) -> Money {
+        Money(self.0.min(other.0))
+    }
+
+    pub fn max(self, other: Money) -> Money {
+        Money(self.0.max(other.0))
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+    fn add(self, rhs: Money) -> Money {
+        Money(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, rhs: Money) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+    fn sub(self, rhs: Money) -> Money {
+        Money(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, rhs: Money) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+    fn neg(self) -> Money {
+        Money(-self.0)
+    }
+}
+
+impl Mul<f64> for Money {
+    type Output = Money;
+    fn mul(self, rhs: f64) -> Money {
+        Money(self.0 * rhs)
+    }
+}
+
+impl Div<f64> for Money {
+    type Output = Money;
+    fn div(self, rhs: f64) -> Money {
+        Money(self.0 / rhs)
+    }
+}
+
+impl Div for Money {
+    type Output = f64;
+    fn div(self, rhs: Money) -> f64 {
+        self.0 / rhs.0
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, Add::add)
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.2}", self.0)
+    }
+}
+
+/// The unit prices and balances are denominated in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Currency {
+    pub name: String,
+    pub symbol: String,
+}
+
+impl Currency {
+    /// Format an amount with the currency symbol, abbreviating large values
+    pub fn format(&self, money: Money) -> String {
+        let amount = money.amount();
+        let (value, suffix) = match amount.abs() {
+            a if a >= 1e9 => (amount / 1e9, "B"),
+            a if a >= 1e6 => (amount / 1e6, "M"),
+            a if a >= 1e4 => (amount / 1e3, "K"),
+            _ => (amount, ""),
+        };
+        format!("{}{:.2}{}", self.symbol, value, suffix)
+    }
+}
+
+impl Default for Currency {
+    fn default() -> Self {
+        Self {
+            name: String::from("Credit"),
+            symbol: String::from("₵"),
+        }
+    }
+}
+
+/// Issues and retires money.
+///
+/// `issued` always equals the sum of every balance in the economy: money only
+/// moves between accounts, except when the bank mints or burns it. The price
+/// level is the ratio of money in circulation to the money originally
+/// endowed, so expanding the supply without more goods raises prices.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MoneySupply {
+    issued: Money,
+    endowed: Money,
+    /// Annual growth rate of the supply; negative values contract it
+    growth_rate: f64,
+}
+
+impl MoneySupply {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn issued(&self) -> Money {
+        self.issued
+    }
+
+    pub fn growth_rate(&self) -> f64 {
+        self.growth_rate
+    }
+
+    pub fn set_growth_rate(&mut self, rate: f64) {
+        self.growth_rate = rate;
+    }
+
+    pub fn price_level(&self) -> f64 {
+        if self.endowed.amount() <= 0.0 {
+            1.0
+        } else {
+            self.issued / self.endowed
+        }
+    }
+
+    /// Create starting money for a new account; does not affect prices
+    pub fn endow(&mut self, amount: Money) -> Money {
+        self.issued += amount;
+        self.endowed += amount;
+        amount
+    }
+
+    /// Return a retired balance to circulation, the reverse of
+    /// [`MoneySupply::retire`]
+    pub fn reinstate(&mut self, amount: Money) {
+        let level = self.price_level();
+        self.issued += amount;
+        self.endowed += amount / level;
+    }
+
+    /// Money to create (positive) or destroy (negative) over the given span
+    /// to follow the growth rate
+    pub fn policy_change(&self, hours: f64) -> Money {
+        const HOURS_PER_YEAR: f64 = 360.0 * 24.0;
+        self.issued * (self.growth_rate * hours / HOURS_PER_YEAR)
+    }
+
+    pub fn mint(&mut self, amount: Money) {
+        self.issued += amount;
+    }
+
+    pub fn burn(&mut self, amount: Money) {
+        self.issued -= amount;
+    }
+
+    /// Destroy the balance of a closed account without changing the price
+    /// level, the reverse of [`MoneySupply::endow`]
+    pub fn retire(&mut self, amount: Money) {
+        let level = self.price_level();
+        self.issued -= amount;
+        self.endowed -= amount / level;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_money_arithmetic() {
+        let mut a = Money(10.0);
+        a += Money(2.5);
+        a -= Money(0.5);
+        assert_eq!(a, Money(12.0));
+        assert_eq!(a * 2.0, Money(24.0));
+        assert_eq!(a / Money(4.0), 3.0);
+        assert_eq!(
+            vec![Money(1.0), Money(2.0)].into_iter().sum::<Money>(),
+            Money(3.0)
+        );
+    }
+
+    #[test]
+    fn test_currency_format() {
+        let currency = Currency::default();
+        assert_eq!(currency.format(Money(12.345)), "₵12.35");
+        assert_eq!(currency.format(Money(25_000.0)), "₵25.00K");
+        assert_eq!(currency.format(Money(3_500_000.0)), "₵3.50M");
+    }
+
+    #[test]
+    fn test_price_level_follows_supply() {
+        let mut supply = MoneySupply::new();
+        assert_eq!(supply.price_level(), 1.0);
+
+        supply.endow(Money(1000.0));
+        assert_eq!(supply.price_level(), 1.0);
+
+        supply.mint(Money(100.0));
+        assert!((supply.price_level() - 1.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_retiring_keeps_price_level() {
+        let mut supply = MoneySupply::new();
+        supply.endow(Money(1000.0));
+        supply.mint(Money(1000.0));
+
+        supply.retire(Money(500.0));
+
+        assert_eq!(supply.issued(), Money(1500.0));
+        assert!((supply.price_level() - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_policy_change_over_a_year() {
+        let mut supply = MoneySupply::new();
+        supply.endow(Money(1000.0));
+        supply.set_growth_rate(0.05);
+
+        let change = supply.policy_change(360.0 * 24.0);
+        assert!((change.amount() - 50.0).abs() < 1e-9);
+    }
+}
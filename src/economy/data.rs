@@ -0,0 +1,410 @@
+//! Goods, recipe and technology definitions.
+//!
+//! These tables are the economy's content: adding a good, a production step
+//! or a technology only requires a new entry in `data/goods.toml`, not new
+//! simulation code.
+//! That file is compiled in as the built-in content, and a copy in the data
+//! directory can replace it at startup with [`install`] so the economy can be
+//! tuned without rebuilding.
+
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use thiserror::Error as ThisError;
+
+use super::{BuildingKind, Good, GoodCategory, GoodsCatalog, Recipe, RecipeBook, Tech, TechTree};
+
+/// Name of the goods file in the data directory
+pub const GOODS_FILE: &str = "goods.toml";
+
+const BUILT_IN: &str = include_str!("../../data/goods.toml");
+
+/// Content installed at startup, if any
+static INSTALLED: OnceLock<GoodsData> = OnceLock::new();
+
+#[derive(ThisError, Debug)]
+pub enum GoodsDataError {
+    #[error("could not read goods file {path}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("invalid goods file")]
+    Parse(#[from] toml::de::Error),
+    #[error("no goods are defined")]
+    NoGoods,
+    #[error("good '{0}' is defined twice")]
+    DuplicateGood(String),
+    #[error("good '{good}' needs a {field} above zero")]
+    NotPositive { good: String, field: &'static str },
+    #[error("recipe '{0}' is defined twice")]
+    DuplicateRecipe(String),
+    #[error("recipe '{recipe}' uses unknown good '{good}'")]
+    UnknownGood { recipe: String, good: String },
+    #[error("recipe '{recipe}' needs a positive quantity of '{good}'")]
+    BadQuantity { recipe: String, good: String },
+    #[error("recipe '{0}' makes nothing")]
+    NoOutputs(String),
+    #[error("technology '{0}' is defined twice")]
+    DuplicateTech(String),
+    #[error("technology '{tech}' needs {field} above zero")]
+    BadTech { tech: String, field: &'static str },
+    #[error("technology '{tech}' requires '{requires}', which must be defined before it")]
+    UnknownPrerequisite { tech: String, requires: String },
+    #[error("technology '{tech}' unlocks unknown recipe '{recipe}'")]
+    UnknownUnlock { tech: String, recipe: String },
+    #[error("goods content is already installed")]
+    AlreadyInstalled,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GoodDef {
+    name: String,
+    category: GoodCategory,
+    base_value: f64,
+    weight: f64,
+    /// Litres per unit, a litre per kilogram if left out
+    #[serde(default)]
+    volume: Option<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RecipeDef {
+    name: String,
+    building: BuildingKind,
+    #[serde(default)]
+    inputs: BTreeMap<String, f64>,
+    outputs: BTreeMap<String, f64>,
+    labor: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TechDef {
+    name: String,
+    days: f64,
+    #[serde(default)]
+    requires: Vec<String>,
+    #[serde(default)]
+    unlocks: Vec<String>,
+    /// Share added to the output of each kind of building
+    #[serde(default)]
+    boosts: BTreeMap<BuildingKind, f64>,
+}
+
+/// Every good, recipe and technology, as read from a goods file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GoodsData {
+    goods: Vec<GoodDef>,
+    #[serde(default)]
+    recipes: Vec<RecipeDef>,
+    #[serde(default)]
+    techs: Vec<TechDef>,
+}
+
+impl GoodsData {
+    /// Read goods, recipes and technologies from TOML, checking that every
+    /// recipe uses known goods, every technology builds on ones before it
+    /// and every amount makes sense
+    pub fn parse(text: &str) -> Result<Self, GoodsDataError> {
+        let data: Self = toml::from_str(text)?;
+        data.validate()?;
+        Ok(data)
+    }
+
+    pub fn read_from(path: &Path) -> Result<Self, GoodsDataError> {
+        let text = std::fs::read_to_string(path).map_err(|source| GoodsDataError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&text)
+    }
+
+    /// The content in `data/goods.toml` at build time
+    pub fn built_in() -> &'static Self {
+        static BUILT_IN_DATA: OnceLock<GoodsData> = OnceLock::new();
+        BUILT_IN_DATA.get_or_init(|| Self::parse(BUILT_IN).expect("built-in goods file is invalid"))
+    }
+
+    fn validate(&self) -> Result<(), GoodsDataError> {
+        if self.goods.is_empty() {
+            return Err(GoodsDataError::NoGoods);
+        }
+        let mut names = HashSet::new();
+        for good in &self.goods {
+            if !names.insert(good.name.as_str()) {
+                return Err(GoodsDataError::DuplicateGood(good.name.clone()));
+            }
+            for (field, value) in [
+                ("base value", good.base_value),
+                ("weight", good.weight),
+                ("volume", good.volume.unwrap_or(good.weight)),
+            ] {
+                if !(value > 0.0 && value.is_finite()) {
+                    return Err(GoodsDataError::NotPositive {
+                        good: good.name.clone(),
+                        field,
+                    });
+                }
+            }
+        }
+
+        let mut recipes = HashSet::new();
+        for recipe in &self.recipes {
+            if !recipes.insert(recipe.name.as_str()) {
+                return Err(GoodsDataError::DuplicateRecipe(recipe.name.clone()));
+            }
+            if recipe.outputs.is_empty() {
+                return Err(GoodsDataError::NoOutputs(recipe.name.clone()));
+            }
+            for (good, &quantity) in recipe.inputs.iter().chain(&recipe.outputs) {
+                if !names.contains(good.as_str()) {
+                    return Err(GoodsDataError::UnknownGood {
+                        recipe: recipe.name.clone(),
+                        good: good.clone(),
+                    });
+                }
+                if !(quantity > 0.0 && quantity.is_finite()) {
+                    return Err(GoodsDataError::BadQuantity {
+                        recipe: recipe.name.clone(),
+                        good: good.clone(),
+                    });
+                }
+            }
+        }
+
+        let mut techs = HashSet::new();
+        for tech in &self.techs {
+            let bad = |field| GoodsDataError::BadTech {
+                tech: tech.name.clone(),
+                field,
+            };
+            if !(tech.days > 0.0 && tech.days.is_finite()) {
+                return Err(bad("days"));
+            }
+            if !tech.boosts.values().all(|&b| b > 0.0 && b.is_finite()) {
+                return Err(bad("every boost"));
+            }
+            if let Some(requires) = tech.requires.iter().find(|r| !techs.contains(r.as_str())) {
+                return Err(GoodsDataError::UnknownPrerequisite {
+                    tech: tech.name.clone(),
+                    requires: requires.clone(),
+                });
+            }
+            if let Some(recipe) = tech.unlocks.iter().find(|r| !recipes.contains(r.as_str())) {
+                return Err(GoodsDataError::UnknownUnlock {
+                    tech: tech.name.clone(),
+                    recipe: recipe.clone(),
+                });
+            }
+            if !techs.insert(tech.name.as_str()) {
+                return Err(GoodsDataError::DuplicateTech(tech.name.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn catalog(&self) -> GoodsCatalog {
+        let mut catalog = GoodsCatalog::new();
+        for def in &self.goods {
+            catalog.add(Good {
+                name: def.name.clone(),
+                category: def.category,
+                base_value: def.base_value,
+                weight: def.weight,
+                volume: def.volume.unwrap_or(def.weight),
+            });
+        }
+        catalog
+    }
+
+    /// The recipes, with goods looked up in a catalog built from this data
+    pub fn recipes(&self, catalog: &GoodsCatalog) -> RecipeBook {
+        let resolve = |list: &BTreeMap<String, f64>| {
+            list.iter()
+                .filter_map(|(name, &qty)| Some((catalog.id(name)?, qty)))
+                .collect()
+        };
+
+        let mut book = RecipeBook::new();
+        for def in &self.recipes {
+            book.add(Recipe {
+                name: def.name.clone(),
+                building: def.building,
+                inputs: resolve(&def.inputs),
+                outputs: resolve(&def.outputs),
+                labor: def.labor,
+            });
+        }
+        book
+    }
+
+    /// The technologies, with recipes looked up in a book built from this
+    /// data
+    pub fn techs(&self, recipes: &RecipeBook) -> TechTree {
+        let mut tree = TechTree::new();
+        for def in &self.techs {
+            let requires = def
+                .requires
+                .iter()
+                .filter_map(|name| tree.find(name))
+                .collect();
+            tree.add(Tech {
+                name: def.name.clone(),
+                days: def.days,
+                requires,
+                unlocks: def
+                    .unlocks
+                    .iter()
+                    .filter_map(|name| recipes.find(name))
+                    .collect(),
+                boosts: def
+                    .boosts
+                    .iter()
+                    .map(|(&building, &share)| (building, share))
+                    .collect(),
+            });
+        }
+        tree
+    }
+}
+
+/// Use this content for every economy created from now on. Only the first
+/// call takes effect, so it belongs at startup before any world is made.
+pub fn install(data: GoodsData) -> Result<(), GoodsDataError> {
+    INSTALLED
+        .set(data)
+        .map_err(|_| GoodsDataError::AlreadyInstalled)
+}
+
+/// The installed content, or the built-in content if none was installed
+fn content() -> &'static GoodsData {
+    INSTALLED.get().unwrap_or_else(GoodsData::built_in)
+}
+
+pub fn default_catalog() -> GoodsCatalog {
+    content().catalog()
+}
+
+pub fn default_recipes(catalog: &GoodsCatalog) -> RecipeBook {
+    content().recipes(catalog)
+}
+
+pub fn default_techs(recipes: &RecipeBook) -> TechTree {
+    content().techs(recipes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_built_in_goods_load() {
+        let catalog = default_catalog();
+        assert_eq!(catalog.len(), 8);
+        let grain = catalog.id("grain").unwrap();
+        assert_eq!(catalog.get(grain).unwrap().category, GoodCategory::Food);
+
+        let recipes = default_recipes(&catalog);
+        let smelting = recipes.get(recipes.find("smelting").unwrap()).unwrap();
+        assert_eq!(smelting.building, BuildingKind::Factory);
+        assert_eq!(
+            smelting.inputs,
+            vec![
+                (catalog.id("ore").unwrap(), 6.0),
+                (catalog.id("timber").unwrap(), 2.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_goods_are_reported() {
+        let good = |name: &str, value: f64| {
+            format!(
+                "[[goods]]\nname = \"{}\"\ncategory = \"Raw\"\nbase_value = {:?}\nweight = 1.0\n",
+                name, value
+            )
+        };
+        let recipe = |output: &str| {
+            format!(
+                "[[recipes]]\nname = \"digging\"\nbuilding = \"Mine\"\noutputs = {{ {} = 1.0 }}\nlabor = 2\n",
+                output
+            )
+        };
+
+        let valid = [good("clay", 1.0), recipe("clay")].concat();
+        assert!(GoodsData::parse(&valid).is_ok());
+        assert!(matches!(
+            GoodsData::parse(&[good("clay", 1.0), good("clay", 2.0)].concat()),
+            Err(GoodsDataError::DuplicateGood(name)) if name == "clay"
+        ));
+        assert!(matches!(
+            GoodsData::parse(&good("clay", 0.0)),
+            Err(GoodsDataError::NotPositive {
+                field: "base value",
+                ..
+            })
+        ));
+        assert!(matches!(
+            GoodsData::parse(&[good("clay", 1.0), recipe("brick")].concat()),
+            Err(GoodsDataError::UnknownGood { good, .. }) if good == "brick"
+        ));
+        assert!(matches!(
+            GoodsData::parse(&valid.replace("Raw", "Gas")),
+            Err(GoodsDataError::Parse(_))
+        ));
+        assert!(matches!(
+            GoodsData::parse("goods = []"),
+            Err(GoodsDataError::NoGoods)
+        ));
+    }
+
+    #[test]
+    fn test_techs_build_on_earlier_ones() {
+        let base = "[[goods]]\nname = \"clay\"\ncategory = \"Raw\"\nbase_value = 1.0\nweight = 1.0\n\
+            [[recipes]]\nname = \"digging\"\nbuilding = \"Mine\"\noutputs = { clay = 1.0 }\nlabor = 2\n";
+        let tech = |name: &str, extra: &str| {
+            format!("[[techs]]\nname = \"{}\"\ndays = 10.0\n{}\n", name, extra)
+        };
+
+        let valid = [
+            base.to_string(),
+            tech("spades", "boosts = { Mine = 0.1 }"),
+            tech("pits", "requires = [\"spades\"]\nunlocks = [\"digging\"]"),
+        ]
+        .concat();
+        let data = GoodsData::parse(&valid).unwrap();
+        let catalog = data.catalog();
+        let recipes = data.recipes(&catalog);
+        let techs = data.techs(&recipes);
+        let pits = techs.get(techs.find("pits").unwrap()).unwrap();
+        assert_eq!(pits.requires, vec![techs.find("spades").unwrap()]);
+        assert_eq!(pits.unlocks, vec![recipes.find("digging").unwrap()]);
+        let spades = techs.get(techs.find("spades").unwrap()).unwrap();
+        assert_eq!(spades.boosts, vec![(BuildingKind::Mine, 0.1)]);
+
+        let out_of_order = [
+            base.to_string(),
+            tech("pits", "requires = [\"spades\"]"),
+            tech("spades", ""),
+        ]
+        .concat();
+        assert!(matches!(
+            GoodsData::parse(&out_of_order),
+            Err(GoodsDataError::UnknownPrerequisite { requires, .. }) if requires == "spades"
+        ));
+        assert!(matches!(
+            GoodsData::parse(&[base.to_string(), tech("kilns", "unlocks = [\"firing\"]")].concat()),
+            Err(GoodsDataError::UnknownUnlock { recipe, .. }) if recipe == "firing"
+        ));
+        assert!(matches!(
+            GoodsData::parse(&valid.replace("10.0", "-1.0")),
+            Err(GoodsDataError::BadTech { field: "days", .. })
+        ));
+    }
+}
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Index of a good within the [`GoodsCatalog`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct GoodId(pub u16);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GoodCategory {
+    Raw,
+    Food,
+    Material,
+    Tool,
+    Luxury,
+}
+
+impl fmt::Display for GoodCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GoodCategory::Raw => write!(f, "Raw"),
+            GoodCategory::Food => write!(f, "Food"),
+            GoodCategory::Material => write!(f, "Material"),
+            GoodCategory::Tool => write!(f, "Tool"),
+            GoodCategory::Luxury => write!(f, "Luxury"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Good {
+    pub name: String,
+    pub category: GoodCategory,
+    /// Reference value used to seed market prices
+    pub base_value: f64,
+    /// Mass per unit in kilograms
+    pub weight: f64,
+    /// Space per unit in litres
+    #[serde(default)]
+    pub volume: f64,
+}
+
+/// Registry of every tradeable good, addressable by id or name
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GoodsCatalog {
+    goods: Vec<Good>,
+    by_name: HashMap<String, GoodId>,
+}
+
+impl GoodsCatalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, good: Good) -> GoodId {
+        let id = GoodId(self.goods.len() as u16);
+        self.by_name.insert(good.name.clone(), id);
+        self.goods.push(good);
+        id
+    }
+
+    pub fn get(&self, id: GoodId) -> Option<&Good> {
+        self.goods.get(id.0 as usize)
+    }
+
+    pub fn id(&self, name: &str) -> Option<GoodId> {
+        self.by_name.get(name).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (GoodId, &Good)> {
+        self.goods
+            .iter()
+            .enumerate()
+            .map(|(i, good)| (GoodId(i as u16), good))
+    }
+
+    pub fn len(&self) -> usize {
+        self.goods.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.goods.is_empty()
+    }
+}
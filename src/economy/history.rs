@@ -0,0 +1,285 @@
+//! Price and volume history for every good on every market.
+//!
+//! Each series keeps its newest hours one point per hour in a ring buffer.
+//! Hours that fall out of it are merged into daily points, which are kept
+//! for as long as the [`Retention`] allows, so memory stays bounded however
+//! long a game runs.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use super::GoodId;
+use crate::game::state::EntityId;
+
+/// Hours merged into each downsampled point
+pub const HOURS_PER_DAY: u32 = 24;
+
+/// How much history each series keeps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Retention {
+    /// Hourly points kept before they are merged into days
+    pub hours: usize,
+    /// Daily points kept before the oldest are discarded
+    pub days: usize,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self {
+            hours: 48,
+            days: 30,
+        }
+    }
+}
+
+/// A good's price and trading over a stretch of hours
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PricePoint {
+    /// Simulation hour the stretch starts at
+    pub hour: u64,
+    /// Hours the point covers: usually one, more when the world skipped
+    /// ahead, or up to a day once downsampled
+    pub hours: u32,
+    /// Mean price over the stretch
+    pub price: f64,
+    /// Units sold over the stretch
+    pub volume: f64,
+}
+
+impl PricePoint {
+    /// The point covering both stretches, with `later` following `self`
+    fn merge(self, later: PricePoint) -> PricePoint {
+        let hours = self.hours + later.hours;
+        PricePoint {
+            hour: self.hour,
+            hours,
+            price: (self.price * self.hours as f64 + later.price * later.hours as f64)
+                / hours as f64,
+            volume: self.volume + later.volume,
+        }
+    }
+
+    fn day(&self) -> u64 {
+        self.hour / u64::from(HOURS_PER_DAY)
+    }
+}
+
+/// One good's history on one market, oldest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PriceSeries {
+    /// Whole days, downsampled from hours
+    days: VecDeque<PricePoint>,
+    /// The day being filled from hours leaving `hours`
+    partial: Option<PricePoint>,
+    /// The newest hours, one point each
+    hours: VecDeque<PricePoint>,
+}
+
+impl PriceSeries {
+    fn push(&mut self, point: PricePoint, retention: Retention) {
+        self.hours.push_back(point);
+        self.trim(retention);
+    }
+
+    /// Downsample and discard points beyond the retention
+    fn trim(&mut self, retention: Retention) {
+        while self.hours.len() > retention.hours {
+            let Some(oldest) = self.hours.pop_front() else {
+                break;
+            };
+            self.partial = match self.partial.take() {
+                Some(day) if day.day() == oldest.day() => Some(day.merge(oldest)),
+                Some(day) => {
+                    self.days.push_back(day);
+                    Some(oldest)
+                }
+                None => Some(oldest),
+            };
+        }
+        while self.days.len() > retention.days {
+            self.days.pop_front();
+        }
+        if retention.days == 0 {
+            self.partial = None;
+        }
+    }
+
+    /// Every point, oldest first; downsampled days come before hours
+    pub fn points(&self) -> impl Iterator<Item = &PricePoint> {
+        self.days
+            .iter()
+            .chain(self.partial.as_ref())
+            .chain(self.hours.iter())
+    }
+
+    pub fn len(&self) -> usize {
+        self.days.len() + usize::from(self.partial.is_some()) + self.hours.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn latest(&self) -> Option<&PricePoint> {
+        self.hours.back().or(self.partial.as_ref())
+    }
+
+    /// Prices oldest first, ready to chart
+    pub fn prices(&self) -> Vec<f64> {
+        self.points().map(|point| point.price).collect()
+    }
+
+    /// Units sold per hour, oldest first, so that days and hours chart on
+    /// the same scale
+    pub fn volumes(&self) -> Vec<f64> {
+        self.points()
+            .map(|point| point.volume / point.hours.max(1) as f64)
+            .collect()
+    }
+}
+
+/// Price history for every market, keyed by the area it trades in
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PriceHistory {
+    retention: Retention,
+    markets: BTreeMap<EntityId, BTreeMap<GoodId, PriceSeries>>,
+    /// The hour last recorded
+    last_hour: Option<u64>,
+}
+
+impl PriceHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn retention(&self) -> Retention {
+        self.retention
+    }
+
+    /// Keep more or less history from now on; shrinking it downsamples or
+    /// discards what is already recorded
+    pub fn set_retention(&mut self, retention: Retention) {
+        self.retention = retention;
+        for series in self.markets.values_mut().flat_map(BTreeMap::values_mut) {
+            series.trim(retention);
+        }
+    }
+
+    pub fn last_hour(&self) -> Option<u64> {
+        self.last_hour
+    }
+
+    /// Record trading on a market up to the end of an hour: each good's
+    /// price, and the units sold since the last hour recorded
+    pub fn record(
+        &mut self,
+        hour: u64,
+        area: EntityId,
+        prices: impl IntoIterator<Item = (GoodId, f64)>,
+        mut volume: HashMap<GoodId, f64>,
+    ) {
+        let hours = self
+            .last_hour
+            .filter(|&last| last < hour)
+            .map_or(1, |last| (hour - last).min(u64::from(HOURS_PER_DAY)) as u32);
+        let market = self.markets.entry(area).or_default();
+        for (good, price) in prices {
+            let point = PricePoint {
+                hour: hour + 1 - u64::from(hours),
+                hours,
+                price,
+                volume: volume.remove(&good).unwrap_or(0.0),
+            };
+            market.entry(good).or_default().push(point, self.retention);
+        }
+    }
+
+    /// Close an hour once every market has recorded it
+    pub fn finish_hour(&mut self, hour: u64) {
+        self.last_hour = self.last_hour.max(Some(hour));
+    }
+
+    pub fn series(&self, area: EntityId, good: GoodId) -> Option<&PriceSeries> {
+        self.markets.get(&area)?.get(&good)
+    }
+
+    /// Every good's history on a market
+    pub fn market(&self, area: EntityId) -> impl Iterator<Item = (GoodId, &PriceSeries)> {
+        self.markets
+            .get(&area)
+            .into_iter()
+            .flat_map(|goods| goods.iter().map(|(&good, series)| (good, series)))
+    }
+
+    /// Forget a market that has closed
+    pub fn forget(&mut self, area: EntityId) {
+        self.markets.remove(&area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRAIN: GoodId = GoodId(0);
+
+    fn record_hours(history: &mut PriceHistory, hours: std::ops::Range<u64>) {
+        for hour in hours {
+            let volume = HashMap::from([(GRAIN, 2.0)]);
+            history.record(hour, 1, [(GRAIN, hour as f64)], volume);
+            history.finish_hour(hour);
+        }
+    }
+
+    #[test]
+    fn test_old_hours_are_downsampled_to_days() {
+        let mut history = PriceHistory::new();
+        history.set_retention(Retention { hours: 6, days: 2 });
+        record_hours(&mut history, 0..32);
+
+        let series = history.series(1, GRAIN).unwrap();
+        let points: Vec<&PricePoint> = series.points().collect();
+        // Day 0 whole, then the start of day 1, then the newest six hours
+        assert_eq!(points.len(), 8);
+        assert_eq!((points[0].hour, points[0].hours), (0, 24));
+        assert_eq!(points[0].price, 11.5);
+        assert_eq!(points[0].volume, 48.0);
+        assert_eq!((points[1].hour, points[1].hours), (24, 2));
+        assert_eq!(series.latest().unwrap().hour, 31);
+        assert_eq!(series.volumes()[0], 2.0);
+        assert_eq!(history.last_hour(), Some(31));
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut history = PriceHistory::new();
+        history.set_retention(Retention { hours: 4, days: 2 });
+        record_hours(&mut history, 0..24 * 10);
+
+        let series = history.series(1, GRAIN).unwrap();
+        // Two whole days, the partial day and four hours
+        assert_eq!(series.len(), 2 + 1 + 4);
+        assert_eq!(series.points().next().unwrap().hour, 24 * 7);
+
+        history.set_retention(Retention { hours: 1, days: 0 });
+        let series = history.series(1, GRAIN).unwrap();
+        assert_eq!(series.prices(), vec![(24 * 10 - 1) as f64]);
+
+        history.forget(1);
+        assert!(history.series(1, GRAIN).is_none());
+    }
+
+    #[test]
+    fn test_skipped_hours_widen_a_point() {
+        let mut history = PriceHistory::new();
+        record_hours(&mut history, 0..1);
+        history.record(5, 1, [(GRAIN, 3.0)], HashMap::from([(GRAIN, 8.0)]));
+
+        let series = history.series(1, GRAIN).unwrap();
+        let latest = series.latest().unwrap();
+        assert_eq!((latest.hour, latest.hours), (1, 5));
+        assert_eq!(series.volumes(), vec![2.0, 1.6]);
+    }
+}
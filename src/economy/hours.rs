@@ -0,0 +1,204 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+use crate::time::{Calendar, CalendarDate};
+
+/// A day in the year on which no market trades
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Holiday {
+    pub month: u32,
+    pub day: u32,
+    pub name: String,
+}
+
+impl Holiday {
+    pub fn new(month: u32, day: u32, name: &str) -> Self {
+        Self {
+            month,
+            day,
+            name: name.to_string(),
+        }
+    }
+
+    /// Holidays every new world observes
+    pub fn defaults() -> Vec<Holiday> {
+        vec![
+            Holiday::new(3, 20, "Planting Day"),
+            Holiday::new(7, 1, "Founders' Day"),
+            Holiday::new(12, 30, "Year's End"),
+        ]
+    }
+
+    pub fn falls_on(&self, date: CalendarDate) -> bool {
+        self.month == date.month && self.day == date.day
+    }
+}
+
+/// Whether a market is trading at some moment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarketStatus {
+    Open,
+    /// Outside trading hours, until the given local hour
+    Closed {
+        opens: u32,
+    },
+    /// Shut all day for a holiday
+    Holiday(String),
+}
+
+impl MarketStatus {
+    pub fn is_open(&self) -> bool {
+        *self == MarketStatus::Open
+    }
+}
+
+impl fmt::Display for MarketStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarketStatus::Open => f.write_str("open"),
+            MarketStatus::Closed { opens } => write!(f, "closed until {:02}:00", opens),
+            MarketStatus::Holiday(name) => write!(f, "closed for {}", name),
+        }
+    }
+}
+
+/// The local hours, from 0 to 24, a market trades between.
+///
+/// A market whose closing hour is before its opening hour trades through
+/// midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TradingHours {
+    pub open: u32,
+    pub close: u32,
+}
+
+impl Default for TradingHours {
+    fn default() -> Self {
+        Self { open: 6, close: 22 }
+    }
+}
+
+impl TradingHours {
+    /// Trading around the clock
+    pub const ALWAYS: TradingHours = TradingHours { open: 0, close: 24 };
+
+    /// Whether the market trades during a local hour of the day
+    pub fn is_open_at(&self, hour: u32) -> bool {
+        if self.open <= self.close {
+            (self.open..self.close).contains(&hour)
+        } else {
+            hour >= self.open || hour < self.close
+        }
+    }
+
+    /// The market's status at a local time
+    pub fn status(
+        &self,
+        local: Duration,
+        calendar: &Calendar,
+        holidays: &[Holiday],
+    ) -> MarketStatus {
+        let date = calendar.date_at(local);
+        if let Some(holiday) = holidays.iter().find(|h| h.falls_on(date)) {
+            return MarketStatus::Holiday(holiday.name.clone());
+        }
+        if self.is_open_at(hour_of_day(local, calendar)) {
+            MarketStatus::Open
+        } else {
+            MarketStatus::Closed {
+                opens: self.open % 24,
+            }
+        }
+    }
+
+    /// Share of the `span` ending at local time `end` that the market was
+    /// trading, checked an hour at a time
+    pub fn open_share(
+        &self,
+        end: Duration,
+        span: Duration,
+        calendar: &Calendar,
+        holidays: &[Holiday],
+    ) -> f64 {
+        if span.is_zero() {
+            return if self.status(end, calendar, holidays).is_open() {
+                1.0
+            } else {
+                0.0
+            };
+        }
+
+        let hour = calendar.day_length() / 24;
+        let mut at = end.saturating_sub(span);
+        let mut open = Duration::ZERO;
+        while at < end {
+            let next_hour = hour * (at.as_nanos() / hour.as_nanos()) as u32 + hour;
+            let until = next_hour.min(end);
+            if self.status(at, calendar, holidays).is_open() {
+                open += until - at;
+            }
+            at = until;
+        }
+        open.as_secs_f64() / span.as_secs_f64()
+    }
+}
+
+fn hour_of_day(local: Duration, calendar: &Calendar) -> u32 {
+    ((calendar.time_of_day(local) * 24.0) as u32).min(23)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    #[test]
+    fn test_hours_wrap_past_midnight() {
+        let day = TradingHours::default();
+        assert!(day.is_open_at(6) && day.is_open_at(21));
+        assert!(!day.is_open_at(22) && !day.is_open_at(3));
+
+        let night = TradingHours { open: 20, close: 4 };
+        assert!(night.is_open_at(23) && night.is_open_at(0));
+        assert!(!night.is_open_at(12));
+        assert!((0..24).all(|hour| TradingHours::ALWAYS.is_open_at(hour)));
+    }
+
+    #[test]
+    fn test_holidays_close_all_day() {
+        let calendar = Calendar::default();
+        let hours = TradingHours::default();
+        let holidays = [Holiday::new(1, 1, "New Year")];
+        let noon = HOUR * 12;
+        assert_eq!(
+            hours.status(noon, &calendar, &holidays),
+            MarketStatus::Holiday(String::from("New Year"))
+        );
+        assert_eq!(
+            hours.status(calendar.day_length() + noon, &calendar, &holidays),
+            MarketStatus::Open
+        );
+        assert_eq!(
+            hours.status(HOUR * 2, &calendar, &[]).to_string(),
+            "closed until 06:00"
+        );
+    }
+
+    #[test]
+    fn test_open_share_counts_trading_hours() {
+        let calendar = Calendar::default();
+        let hours = TradingHours::default();
+        let day = calendar.day_length();
+        let share = hours.open_share(day * 2, day, &calendar, &[]);
+        assert!((share - 16.0 / 24.0).abs() < 1e-9);
+
+        // Half an hour either side of opening
+        let share = hours.open_share(HOUR * 6 + HOUR / 2, HOUR, &calendar, &[]);
+        assert!((share - 0.5).abs() < 1e-9);
+
+        let holidays = [Holiday::new(1, 2, "Rest Day")];
+        assert_eq!(hours.open_share(day * 2, day, &calendar, &holidays), 0.0);
+    }
+}
@@ -0,0 +1,200 @@
+//! Goods held by someone, and how much they have room for.
+//!
+//! An [`Inventory`] is what a character carries or a firm keeps in its
+//! storehouse: units of each good, up to the mass and volume its
+//! [`Capacity`] allows. Ships carry one cargo at a time and only need the
+//! capacity of their hold. Goods only move in and out through the transfer
+//! methods, which take as much as fits or is there and say how much that
+//! was, the same way markets hand over what stock they have.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::{Good, GoodId, GoodsCatalog};
+
+/// Goods left when an amount counts as used up
+const EMPTY: f64 = 1e-9;
+
+/// The most mass and volume something holds; None means no limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Capacity {
+    /// Kilograms
+    pub mass: Option<f64>,
+    /// Litres
+    pub volume: Option<f64>,
+}
+
+impl Capacity {
+    pub const UNLIMITED: Capacity = Capacity {
+        mass: None,
+        volume: None,
+    };
+
+    pub fn new(mass: f64, volume: f64) -> Self {
+        Self {
+            mass: Some(mass),
+            volume: Some(volume),
+        }
+    }
+
+    /// Units of a good that fit in `mass` kilograms and `volume` litres of
+    /// room, whichever runs out first
+    fn fits(mass: Option<f64>, volume: Option<f64>, good: &Good) -> f64 {
+        let per_unit = |room: Option<f64>, size: f64| match room {
+            Some(room) if size > 0.0 => room.max(0.0) / size,
+            _ => f64::INFINITY,
+        };
+        per_unit(mass, good.weight).min(per_unit(volume, good.volume))
+    }
+
+    /// Units of a good that fit when nothing else is held
+    pub fn units_of(&self, good: &Good) -> f64 {
+        Self::fits(self.mass, self.volume, good)
+    }
+}
+
+/// Units of each good held, within a capacity
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Inventory {
+    goods: BTreeMap<GoodId, f64>,
+    #[serde(default)]
+    capacity: Capacity,
+}
+
+impl Inventory {
+    pub fn new(capacity: Capacity) -> Self {
+        Self {
+            goods: BTreeMap::new(),
+            capacity,
+        }
+    }
+
+    pub fn capacity(&self) -> Capacity {
+        self.capacity
+    }
+
+    /// Units of a good held
+    pub fn get(&self, good: GoodId) -> f64 {
+        self.goods.get(&good).copied().unwrap_or(0.0)
+    }
+
+    /// Every good held, in id order
+    pub fn iter(&self) -> impl Iterator<Item = (GoodId, f64)> + '_ {
+        self.goods.iter().map(|(&good, &quantity)| (good, quantity))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.goods.is_empty()
+    }
+
+    /// Kilograms held. Goods missing from the catalog weigh nothing.
+    pub fn mass(&self, catalog: &GoodsCatalog) -> f64 {
+        self.total(catalog, |good| good.weight)
+    }
+
+    /// Litres held
+    pub fn volume(&self, catalog: &GoodsCatalog) -> f64 {
+        self.total(catalog, |good| good.volume)
+    }
+
+    fn total(&self, catalog: &GoodsCatalog, size: impl Fn(&Good) -> f64) -> f64 {
+        self.iter()
+            .filter_map(|(id, quantity)| Some(size(catalog.get(id)?) * quantity))
+            .sum()
+    }
+
+    /// Units of a good there is still room for
+    pub fn room_for(&self, good: GoodId, catalog: &GoodsCatalog) -> f64 {
+        let Some(def) = catalog.get(good) else {
+            return f64::INFINITY;
+        };
+        let mass = self.capacity.mass.map(|cap| cap - self.mass(catalog));
+        let volume = self.capacity.volume.map(|cap| cap - self.volume(catalog));
+        Capacity::fits(mass, volume, def)
+    }
+
+    /// Put away up to `quantity` units of a good, as many as there is room
+    /// for, returning how many that was
+    pub fn add(&mut self, good: GoodId, quantity: f64, catalog: &GoodsCatalog) -> f64 {
+        let added = quantity.min(self.room_for(good, catalog));
+        if added > 0.0 {
+            *self.goods.entry(good).or_insert(0.0) += added;
+            added
+        } else {
+            0.0
+        }
+    }
+
+    /// Take out up to `quantity` units of a good, returning how much there
+    /// was to take
+    pub fn remove(&mut self, good: GoodId, quantity: f64) -> f64 {
+        let Some(held) = self.goods.get_mut(&good) else {
+            return 0.0;
+        };
+        let taken = quantity.clamp(0.0, *held);
+        *held -= taken;
+        if *held <= EMPTY {
+            self.goods.remove(&good);
+        }
+        taken
+    }
+
+    /// Move up to `quantity` units of a good into another inventory, as
+    /// many as are held and fit, returning how many moved
+    pub fn transfer(
+        &mut self,
+        to: &mut Inventory,
+        good: GoodId,
+        quantity: f64,
+        catalog: &GoodsCatalog,
+    ) -> f64 {
+        let moving = quantity.min(self.get(good)).min(to.room_for(good, catalog));
+        let taken = self.remove(good, moving);
+        to.add(good, taken, catalog)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::data::default_catalog;
+
+    #[test]
+    fn test_goods_fit_by_mass_or_volume() {
+        let catalog = default_catalog();
+        let ore = catalog.id("ore").unwrap();
+        let timber = catalog.id("timber").unwrap();
+        let mut pack = Inventory::new(Capacity::new(20.0, 20.0));
+
+        // Ore runs out of mass first, timber of volume
+        assert_eq!(pack.room_for(ore, &catalog), 4.0);
+        assert!((pack.room_for(timber, &catalog) - 20.0 / 6.0).abs() < 1e-9);
+        assert_eq!(pack.add(ore, 10.0, &catalog), 4.0);
+        assert_eq!(pack.get(ore), 4.0);
+        assert_eq!(pack.add(timber, 1.0, &catalog), 0.0);
+        assert_eq!(pack.mass(&catalog), 20.0);
+
+        assert_eq!(pack.remove(ore, 1.0), 1.0);
+        assert!((pack.add(timber, 1.0, &catalog) - 1.0).abs() < 1e-9);
+        assert_eq!(pack.remove(ore, 10.0), 3.0);
+        assert_eq!(pack.remove(ore, 1.0), 0.0);
+
+        let mut unlimited = Inventory::default();
+        assert_eq!(unlimited.add(ore, 1e6, &catalog), 1e6);
+    }
+
+    #[test]
+    fn test_transfers_move_what_is_held_and_fits() {
+        let catalog = default_catalog();
+        let grain = catalog.id("grain").unwrap();
+        let mut store = Inventory::default();
+        store.add(grain, 100.0, &catalog);
+        let mut pack = Inventory::new(Capacity::new(30.0, 100.0));
+
+        assert_eq!(store.transfer(&mut pack, grain, 50.0, &catalog), 30.0);
+        assert_eq!((store.get(grain), pack.get(grain)), (70.0, 30.0));
+        assert_eq!(pack.transfer(&mut store, grain, 50.0, &catalog), 30.0);
+        assert!(pack.is_empty());
+        assert_eq!(store.get(grain), 100.0);
+    }
+}
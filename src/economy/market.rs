@@ -0,0 +1,405 @@
+use crate::game::state::EntityId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::book::{Depth, LOT, Level};
+use super::orders::FILLED;
+use super::{Good, GoodId, GoodsCatalog, Money, Order, Side, TradingHours};
+
+/// Stock level at which a good trades at its base value
+pub const TARGET_STOCK: f64 = 100.0;
+
+/// Hourly wage per worker at a price level of 1.0
+pub const BASE_WAGE: f64 = 0.5;
+
+const MIN_PRICE_FACTOR: f64 = 0.2;
+const MAX_PRICE_FACTOR: f64 = 5.0;
+
+/// A local marketplace holding pooled stock for one area.
+///
+/// Prices are each good's base value scaled by the economy-wide price level,
+/// rising as stock runs short of [`TARGET_STOCK`] and falling as it piles up.
+/// The same curve prices the households' side of each good's order book: a
+/// lot further off the shelf for each ask, a lot further onto it for each
+/// bid. The market's cash balance stands in for the area's households and
+/// merchants: it receives wages and input payments and pays for output.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Market {
+    stock: HashMap<GoodId, f64>,
+    prices: HashMap<GoodId, Money>,
+    #[serde(default)]
+    cash: Money,
+    #[serde(default = "default_price_level")]
+    price_level: f64,
+    /// How far prices stray from base value with stock, as last updated
+    #[serde(default = "default_volatility")]
+    volatility: f64,
+    /// Value of goods produced here since output was last collected
+    #[serde(default)]
+    output: Money,
+    #[serde(default)]
+    hours: TradingHours,
+    /// Duty every price carries, as a share of the price without it
+    #[serde(default)]
+    tariff: f64,
+    /// Tax paid by firms here since it was last collected
+    #[serde(default)]
+    taxes: Money,
+    /// Firms' standing orders, oldest first
+    #[serde(default)]
+    orders: Vec<Order>,
+    /// Units of each good sold since volume was last collected
+    #[serde(default)]
+    volume: HashMap<GoodId, f64>,
+    /// Value of goods sold since sales were last taxed
+    #[serde(default)]
+    turnover: Money,
+}
+
+fn default_price_level() -> f64 {
+    1.0
+}
+
+fn default_volatility() -> f64 {
+    1.0
+}
+
+impl Market {
+    pub fn new(catalog: &GoodsCatalog) -> Self {
+        let mut market = Self {
+            price_level: default_price_level(),
+            volatility: default_volatility(),
+            ..Self::default()
+        };
+        for (id, good) in catalog.iter() {
+            market.list(id, good);
+        }
+        market
+    }
+
+    /// Start trading a good at its base value with a full shelf
+    pub fn list(&mut self, id: GoodId, good: &Good) {
+        self.stock.insert(id, TARGET_STOCK);
+        self.prices.insert(id, Money(self.reference_price(good)));
+    }
+
+    /// What a good trades at with its stock on target: its base value at
+    /// the current price level, plus any tariff
+    fn reference_price(&self, good: &Good) -> f64 {
+        good.base_value * self.price_level * (1.0 + self.tariff)
+    }
+
+    /// How far a good's price sits from its reference value with `stock`
+    /// units on the shelf
+    fn price_factor(&self, stock: f64) -> f64 {
+        (TARGET_STOCK / stock.max(1.0))
+            .powf(0.5 * self.volatility)
+            .clamp(MIN_PRICE_FACTOR, MAX_PRICE_FACTOR)
+    }
+
+    /// What a good trades at with `stock` units on the shelf
+    fn price_with_stock(&self, good: &Good, stock: f64) -> Money {
+        Money(self.reference_price(good) * self.price_factor(stock))
+    }
+
+    pub fn stock(&self, good: GoodId) -> f64 {
+        self.stock.get(&good).copied().unwrap_or(0.0)
+    }
+
+    pub fn price(&self, good: GoodId) -> Money {
+        self.prices.get(&good).copied().unwrap_or(Money::ZERO)
+    }
+
+    /// Move a good's stock to the level at which it trades at `price`, as
+    /// near as prices can reach, and reprice it straight away
+    pub fn set_price(&mut self, id: GoodId, good: &Good, price: Money) {
+        let base = self.reference_price(good);
+        let factor = (price.amount() / base).clamp(MIN_PRICE_FACTOR, MAX_PRICE_FACTOR);
+        self.stock.insert(id, TARGET_STOCK / (factor * factor));
+        self.prices.insert(id, Money(base * factor));
+    }
+
+    /// The prices a good can reach at the current price level, lowest first
+    pub fn price_range(&self, good: &Good) -> (Money, Money) {
+        let base = self.reference_price(good);
+        (
+            Money(base * MIN_PRICE_FACTOR),
+            Money(base * MAX_PRICE_FACTOR),
+        )
+    }
+
+    /// Current hourly wage for one worker
+    pub fn wage(&self) -> Money {
+        Money(BASE_WAGE * self.price_level)
+    }
+
+    pub fn hours(&self) -> TradingHours {
+        self.hours
+    }
+
+    pub fn set_hours(&mut self, hours: TradingHours) {
+        self.hours = hours;
+    }
+
+    pub fn tariff(&self) -> f64 {
+        self.tariff
+    }
+
+    /// Set the duty prices carry; they take it on when next updated
+    pub fn set_tariff(&mut self, tariff: f64) {
+        self.tariff = tariff.max(0.0);
+    }
+
+    pub fn cash(&self) -> Money {
+        self.cash
+    }
+
+    pub fn deposit(&mut self, amount: Money) {
+        self.cash += amount;
+    }
+
+    /// Pay out up to `amount`, returning how much was actually paid
+    pub fn withdraw(&mut self, amount: Money) -> Money {
+        let paid = amount.min(self.cash).max(Money::ZERO);
+        self.cash -= paid;
+        paid
+    }
+
+    /// Count newly produced goods towards the area's output
+    pub fn record_output(&mut self, value: Money) {
+        self.output += value;
+    }
+
+    /// Output recorded since the last call, resetting the tally
+    pub fn take_output(&mut self) -> Money {
+        std::mem::take(&mut self.output)
+    }
+
+    /// Count tax paid by a firm here
+    pub fn record_tax(&mut self, tax: Money) {
+        self.taxes += tax;
+    }
+
+    /// Tax recorded and not yet collected
+    pub fn taxes(&self) -> Money {
+        self.taxes
+    }
+
+    /// Tax recorded since the last call, resetting the tally
+    pub fn take_taxes(&mut self) -> Money {
+        std::mem::take(&mut self.taxes)
+    }
+
+    /// Standing orders, oldest first
+    pub fn orders(&self) -> &[Order] {
+        &self.orders
+    }
+
+    /// Stand an order on the market, replacing the firm's earlier order on
+    /// the same side for the same good. An order for nothing just cancels.
+    pub fn place_order(&mut self, order: Order) {
+        self.orders
+            .retain(|o| (o.firm, o.side, o.good) != (order.firm, order.side, order.good));
+        if !order.is_filled() {
+            self.orders.push(order);
+        }
+    }
+
+    /// Withdraw every order a firm has standing
+    pub fn cancel_orders(&mut self, firm: EntityId) {
+        self.orders.retain(|order| order.firm != firm);
+    }
+
+    /// Every standing order, leaving none
+    pub fn take_orders(&mut self) -> Vec<Order> {
+        std::mem::take(&mut self.orders)
+    }
+
+    /// Mean ratio of current prices to base values; 1.0 when every good
+    /// trades at its reference value
+    pub fn price_index(&self, catalog: &GoodsCatalog) -> f64 {
+        let ratios: Vec<f64> = catalog
+            .iter()
+            .filter(|(_, good)| good.base_value > 0.0)
+            .map(|(id, good)| self.price(id).amount() / good.base_value)
+            .collect();
+        if ratios.is_empty() {
+            return 1.0;
+        }
+        ratios.iter().sum::<f64>() / ratios.len() as f64
+    }
+
+    pub fn add(&mut self, good: GoodId, quantity: f64) {
+        *self.stock.entry(good).or_insert(0.0) += quantity;
+    }
+
+    /// Remove up to `quantity` units, returning how much was actually taken
+    pub fn take(&mut self, good: GoodId, quantity: f64) -> f64 {
+        let stock = self.stock.entry(good).or_insert(0.0);
+        let taken = quantity.min(*stock).max(0.0);
+        *stock -= taken;
+        taken
+    }
+
+    /// Sell up to `quantity` units to a buyer, counting them towards the
+    /// good's trading volume. Returns how much was actually sold.
+    pub fn sell(&mut self, good: GoodId, quantity: f64) -> f64 {
+        let sold = self.take(good, quantity);
+        self.record_trade(good, sold, self.price(good) * sold);
+        sold
+    }
+
+    /// Count units that changed hands here, and what they fetched, towards
+    /// the good's volume and the market's turnover
+    pub(super) fn record_trade(&mut self, good: GoodId, units: f64, value: Money) {
+        *self.volume.entry(good).or_insert(0.0) += units;
+        self.turnover += value;
+    }
+
+    /// The lowest price the households sell a good at: what it would trade
+    /// at with another lot gone from the shelf
+    pub fn ask(&self, id: GoodId, good: &Good) -> Money {
+        self.price_with_stock(good, self.stock(id) - LOT)
+    }
+
+    /// The highest price the households buy a good at: what it would trade
+    /// at with another lot on the shelf
+    pub fn bid(&self, id: GoodId, good: &Good) -> Money {
+        self.price_with_stock(good, self.stock(id) + LOT)
+    }
+
+    /// Buy up to `quantity` units of a good off the shelf a lot at a time,
+    /// each at the ask it is quoted at, for as long as the ask is within
+    /// `limit` and the cost within `budget`. The cost is paid into the
+    /// market's cash. Returns the units bought and what they cost.
+    pub fn fill_buy(
+        &mut self,
+        id: GoodId,
+        good: &Good,
+        quantity: f64,
+        limit: Money,
+        budget: Money,
+    ) -> (f64, Money) {
+        let (mut bought, mut cost) = (0.0, Money::ZERO);
+        loop {
+            let price = self.ask(id, good);
+            if price > limit {
+                break;
+            }
+            let mut lot = (quantity - bought).min(LOT).min(self.stock(id));
+            if price > Money::ZERO {
+                lot = lot.min((budget - cost) / price);
+            }
+            if lot <= FILLED {
+                break;
+            }
+            let taken = self.take(id, lot);
+            self.record_trade(id, taken, price * taken);
+            bought += taken;
+            cost += price * taken;
+        }
+        self.deposit(cost);
+        (bought, cost)
+    }
+
+    /// Sell up to `quantity` units of a good onto the shelf a lot at a
+    /// time, each at the bid it is quoted at, for as long as the bid is at
+    /// least `limit` and the market's cash pays for it. Returns the units
+    /// sold and what they fetched, paid out of the market's cash.
+    pub fn fill_sell(
+        &mut self,
+        id: GoodId,
+        good: &Good,
+        quantity: f64,
+        limit: Money,
+    ) -> (f64, Money) {
+        let (mut sold, mut paid) = (0.0, Money::ZERO);
+        loop {
+            let price = self.bid(id, good);
+            if price < limit {
+                break;
+            }
+            let mut lot = (quantity - sold).min(LOT);
+            if price > Money::ZERO {
+                lot = lot.min(self.cash / price);
+            }
+            if lot <= FILLED {
+                break;
+            }
+            self.add(id, lot);
+            self.record_trade(id, lot, price * lot);
+            sold += lot;
+            paid += self.withdraw(price * lot);
+        }
+        (sold, paid)
+    }
+
+    /// The first `levels` bids and asks on a good's book, best first: the
+    /// households' ladder and firms' standing orders at their limits
+    pub fn depth(&self, id: GoodId, good: &Good, levels: usize) -> Depth {
+        let stock = self.stock(id);
+        let mut depth = Depth::default();
+        let mut cash = self.cash;
+        for level in 1..=levels {
+            let offset = LOT * level as f64;
+            let quantity = (stock - offset + LOT).min(LOT);
+            if quantity > FILLED {
+                depth.asks.push(Level {
+                    price: self.price_with_stock(good, stock - offset),
+                    quantity,
+                });
+            }
+            let price = self.price_with_stock(good, stock + offset);
+            let quantity = if price > Money::ZERO {
+                LOT.min(cash / price)
+            } else {
+                LOT
+            };
+            if quantity > FILLED {
+                cash -= price * quantity;
+                depth.bids.push(Level { price, quantity });
+            }
+        }
+        for order in self.orders.iter().filter(|order| order.good == id) {
+            let level = Level {
+                price: order.limit,
+                quantity: order.quantity,
+            };
+            match order.side {
+                Side::Buy => depth.bids.push(level),
+                Side::Sell => depth.asks.push(level),
+            }
+        }
+        depth.sort();
+        depth.bids.truncate(levels);
+        depth.asks.truncate(levels);
+        depth
+    }
+
+    /// Value of goods sold since the last call, resetting the tally
+    pub fn take_turnover(&mut self) -> Money {
+        std::mem::take(&mut self.turnover)
+    }
+
+    /// Units of a good sold since volume was last collected
+    pub fn volume(&self, good: GoodId) -> f64 {
+        self.volume.get(&good).copied().unwrap_or(0.0)
+    }
+
+    /// Volume recorded since the last call, resetting the tally
+    pub fn take_volume(&mut self) -> HashMap<GoodId, f64> {
+        std::mem::take(&mut self.volume)
+    }
+
+    /// Reprice every good from its stock. `volatility` scales how far
+    /// prices stray from base value: 1 for the usual square-root response,
+    /// 0 for fixed prices.
+    pub fn update_prices(&mut self, catalog: &GoodsCatalog, price_level: f64, volatility: f64) {
+        self.price_level = price_level;
+        self.volatility = volatility;
+        for (id, good) in catalog.iter() {
+            self.prices
+                .insert(id, self.price_with_stock(good, self.stock(id)));
+        }
+    }
+}
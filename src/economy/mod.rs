@@ -0,0 +1,1257 @@
+mod banking;
+mod book;
+mod construction;
+mod currency;
+pub mod data;
+mod goods;
+mod history;
+mod hours;
+mod inventory;
+mod market;
+mod orders;
+mod production;
+mod recipe;
+mod technology;
+
+pub use banking::{Account, Bank, BankSample, DEFAULT_POLICY_RATE, MAX_POLICY_RATE};
+pub use book::{Depth, LOT, Level};
+pub use construction::{
+    BuildPlan, Builder, EXPANSION_BUDGET, EXPANSION_INTERVAL, EXPANSION_UTILIZATION,
+    EXPANSION_WORTH, Site,
+};
+pub use currency::{Currency, Money, MoneySupply};
+pub use goods::{Good, GoodCategory, GoodId, GoodsCatalog};
+pub use history::{PriceHistory, PricePoint, PriceSeries, Retention};
+pub use hours::{Holiday, MarketStatus, TradingHours};
+pub use inventory::{Capacity, Inventory};
+pub use market::{Market, TARGET_STOCK};
+pub use orders::{Order, Side};
+pub use production::{Firm, STOREHOUSE};
+pub use recipe::{BuildingKind, Recipe, RecipeBook, RecipeId};
+pub use technology::{Research, Tech, TechBoosts, TechId, TechTree};
+
+use crate::game::state::EntityId;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Food each resident worker eats per simulated hour
+const FOOD_PER_WORKER_HOUR: f64 = 0.1;
+
+/// Starting cash for a newly opened market's households and merchants
+const MARKET_ENDOWMENT: Money = Money(20_000.0);
+
+/// Starting capital for a newly founded firm
+const FIRM_ENDOWMENT: Money = Money(5_000.0);
+
+/// Multipliers that make the economy easier or harder to run
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Difficulty {
+    /// Scales what firms make from their inputs and labor
+    pub productivity: f64,
+    /// Scales how much food households eat
+    pub appetite: f64,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self {
+            productivity: 1.0,
+            appetite: 1.0,
+        }
+    }
+}
+
+/// Time a market advances by in one update
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MarketTick {
+    pub elapsed: Duration,
+    /// Share of `elapsed` the market was trading, from 0 to 1. Firms only
+    /// produce while it trades; households eat regardless.
+    pub open_share: f64,
+    /// How well the land around the market suits its resource buildings
+    pub yields: Yields,
+    /// Taxes levied by whoever governs the market
+    pub policy: FiscalPolicy,
+    /// Multiplier on every firm's output from the roads, power and docks
+    /// built around the market
+    pub infrastructure: f64,
+    /// Multipliers on output from what whoever governs the market knows
+    pub technology: TechBoosts,
+}
+
+impl MarketTick {
+    /// A tick during which the market traded throughout
+    pub fn open(elapsed: Duration) -> Self {
+        Self {
+            elapsed,
+            open_share: 1.0,
+            yields: Yields::EVEN,
+            policy: FiscalPolicy::NONE,
+            infrastructure: 1.0,
+            technology: TechBoosts::NONE,
+        }
+    }
+}
+
+/// Taxes a government levies on the markets it governs, and how fast it
+/// spends what they raise
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct FiscalPolicy {
+    /// Share of a firm's takings over its costs paid in tax, from 0 to 1
+    pub tax_rate: f64,
+    /// Duty on goods brought to market, as a share of their price, which
+    /// every price in the market carries
+    pub tariff: f64,
+    /// Tax on every sale in the market, as a share of what it fetched,
+    /// paid by the buyer's side
+    #[serde(default)]
+    pub sales_tax: f64,
+    /// Share of the government's budget spent on infrastructure each day
+    #[serde(default)]
+    pub spending: f64,
+}
+
+impl FiscalPolicy {
+    /// No taxes at all, as in ungoverned places
+    pub const NONE: FiscalPolicy = FiscalPolicy {
+        tax_rate: 0.0,
+        tariff: 0.0,
+        sales_tax: 0.0,
+        spending: 0.0,
+    };
+}
+
+/// Output multipliers for buildings that work the land, set by the terrain
+/// around a market
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Yields {
+    pub farming: f64,
+    pub mining: f64,
+    pub logging: f64,
+    pub fishing: f64,
+}
+
+impl Yields {
+    /// Land that neither helps nor hinders
+    pub const EVEN: Yields = Yields {
+        farming: 1.0,
+        mining: 1.0,
+        logging: 1.0,
+        fishing: 1.0,
+    };
+
+    /// The multiplier for a kind of building; those that don't work the
+    /// land are unaffected
+    pub fn for_building(&self, building: BuildingKind) -> f64 {
+        match building {
+            BuildingKind::Farm => self.farming,
+            BuildingKind::Mine => self.mining,
+            BuildingKind::LumberCamp => self.logging,
+            BuildingKind::Fishery => self.fishing,
+            BuildingKind::Factory | BuildingKind::Workshop => 1.0,
+        }
+    }
+}
+
+impl Default for Yields {
+    fn default() -> Self {
+        Self::EVEN
+    }
+}
+
+/// Markets and firms for every local area, plus the content they trade in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Economy {
+    catalog: GoodsCatalog,
+    recipes: RecipeBook,
+    #[serde(default)]
+    techs: TechTree,
+    markets: BTreeMap<EntityId, Market>,
+    firms: BTreeMap<EntityId, Firm>,
+    #[serde(default)]
+    currency: Currency,
+    #[serde(default)]
+    money_supply: MoneySupply,
+    #[serde(default)]
+    difficulty: Difficulty,
+    /// Cash the player holds outside the firms they own
+    #[serde(default)]
+    purse: Money,
+    #[serde(default)]
+    history: PriceHistory,
+    /// Multiplier on how far prices move away from base value
+    #[serde(default = "default_volatility")]
+    volatility: f64,
+    /// Budgets of the governments taxing the markets, by their id
+    #[serde(default)]
+    treasuries: BTreeMap<EntityId, Money>,
+    #[serde(default)]
+    bank: Bank,
+    /// Buildings under construction, by the id of the firm each will be
+    #[serde(default)]
+    sites: BTreeMap<EntityId, Site>,
+    /// Sites finished since they were last collected
+    #[serde(skip)]
+    finished: Vec<Site>,
+}
+
+fn default_volatility() -> f64 {
+    1.0
+}
+
+impl Economy {
+    pub fn new() -> Self {
+        let catalog = data::default_catalog();
+        let recipes = data::default_recipes(&catalog);
+        let techs = data::default_techs(&recipes);
+        Self {
+            catalog,
+            recipes,
+            techs,
+            markets: BTreeMap::new(),
+            firms: BTreeMap::new(),
+            currency: Currency::default(),
+            money_supply: MoneySupply::new(),
+            difficulty: Difficulty::default(),
+            purse: Money::ZERO,
+            history: PriceHistory::new(),
+            volatility: default_volatility(),
+            treasuries: BTreeMap::new(),
+            bank: Bank::new(),
+            sites: BTreeMap::new(),
+            finished: Vec::new(),
+        }
+    }
+
+    pub fn catalog(&self) -> &GoodsCatalog {
+        &self.catalog
+    }
+
+    /// Add a good to the catalog and list it in every market. Returns None
+    /// if a good of that name already exists.
+    pub fn add_good(&mut self, good: Good) -> Option<GoodId> {
+        if self.catalog.id(&good.name).is_some() {
+            return None;
+        }
+        let id = self.catalog.add(good.clone());
+        for market in self.markets.values_mut() {
+            market.list(id, &good);
+        }
+        Some(id)
+    }
+
+    pub fn recipes(&self) -> &RecipeBook {
+        &self.recipes
+    }
+
+    pub fn techs(&self) -> &TechTree {
+        &self.techs
+    }
+
+    pub fn market(&self, area_id: EntityId) -> Option<&Market> {
+        self.markets.get(&area_id)
+    }
+
+    pub fn market_mut(&mut self, area_id: EntityId) -> Option<&mut Market> {
+        self.markets.get_mut(&area_id)
+    }
+
+    pub fn markets(&self) -> impl Iterator<Item = (EntityId, &Market)> {
+        self.markets.iter().map(|(&id, market)| (id, market))
+    }
+
+    pub fn history(&self) -> &PriceHistory {
+        &self.history
+    }
+
+    pub fn set_history_retention(&mut self, retention: Retention) {
+        self.history.set_retention(retention);
+    }
+
+    /// Add an hour to every market's price history, collecting the volume
+    /// each has traded since the last
+    pub fn record_history(&mut self, hour: u64) {
+        for (&area_id, market) in self.markets.iter_mut() {
+            let volume = market.take_volume();
+            let prices = self
+                .catalog
+                .iter()
+                .map(|(good, _)| (good, market.price(good).amount()));
+            self.history.record(hour, area_id, prices, volume);
+        }
+        self.history.finish_hour(hour);
+    }
+
+    /// Value of everything an area produced since this was last called
+    pub fn take_output(&mut self, area_id: EntityId) -> Money {
+        self.markets
+            .get_mut(&area_id)
+            .map_or(Money::ZERO, Market::take_output)
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+    }
+
+    pub fn volatility(&self) -> f64 {
+        self.volatility
+    }
+
+    pub fn set_volatility(&mut self, volatility: f64) {
+        self.volatility = volatility.max(0.0);
+    }
+
+    pub fn currency(&self) -> &Currency {
+        &self.currency
+    }
+
+    pub fn money_supply(&self) -> &MoneySupply {
+        &self.money_supply
+    }
+
+    /// Set the annual rate at which the money supply grows (or, if
+    /// negative, shrinks)
+    pub fn set_money_growth(&mut self, rate: f64) {
+        self.money_supply.set_growth_rate(rate);
+    }
+
+    /// Mint `share` of the money in circulation at once, spread evenly
+    /// across market households, and reprice every market at the new price
+    /// level. Returns how much was minted.
+    pub fn debase(&mut self, share: f64) -> Money {
+        if self.markets.is_empty() || share <= 0.0 {
+            return Money::ZERO;
+        }
+        let minted = self.money_supply.issued() * share;
+        let each = minted / self.markets.len() as f64;
+        for market in self.markets.values_mut() {
+            market.deposit(each);
+        }
+        self.money_supply.mint(minted);
+
+        let price_level = self.money_supply.price_level();
+        for market in self.markets.values_mut() {
+            market.update_prices(&self.catalog, price_level, self.volatility);
+        }
+        minted
+    }
+
+    /// Cash held across every market, firm, building site and treasury, tax
+    /// not yet collected and the player's purse; always equal to the money
+    /// supply's issued total
+    pub fn total_balances(&self) -> Money {
+        self.markets
+            .values()
+            .map(|market| market.cash() + market.taxes())
+            .sum::<Money>()
+            + self.firms.values().map(|f| f.cash).sum::<Money>()
+            + self.sites.values().map(|s| s.cash).sum::<Money>()
+            + self.treasuries.values().copied().sum::<Money>()
+            + self.bank.reserves()
+            + self.purse
+    }
+
+    pub fn bank(&self) -> &Bank {
+        &self.bank
+    }
+
+    pub fn bank_mut(&mut self) -> &mut Bank {
+        &mut self.bank
+    }
+
+    /// Close the bank's figures for the day
+    pub fn record_banking(&mut self) {
+        self.bank.sample(self.money_supply.issued());
+    }
+
+    /// Budget a government has to spend
+    pub fn treasury(&self, government: EntityId) -> Money {
+        self.treasuries
+            .get(&government)
+            .copied()
+            .unwrap_or(Money::ZERO)
+    }
+
+    /// Collect the tax paid in an area into the treasury of the government
+    /// governing it, returning how much was collected. Tax paid where
+    /// nobody governs any more goes back to the market's households.
+    pub fn levy(&mut self, area_id: EntityId, government: Option<EntityId>) -> Money {
+        let Some(market) = self.markets.get_mut(&area_id) else {
+            return Money::ZERO;
+        };
+        let taxes = market.take_taxes();
+        match government {
+            Some(government) => *self.treasuries.entry(government).or_default() += taxes,
+            None => market.deposit(taxes),
+        }
+        taxes
+    }
+
+    /// Spend up to `amount` of a government's budget in an area, paying the
+    /// market's households to build there. Returns how much was spent.
+    pub fn spend_treasury(
+        &mut self,
+        government: EntityId,
+        area_id: EntityId,
+        amount: Money,
+    ) -> Money {
+        let (Some(market), Some(budget)) = (
+            self.markets.get_mut(&area_id),
+            self.treasuries.get_mut(&government),
+        ) else {
+            return Money::ZERO;
+        };
+        let spent = amount.min(*budget).max(Money::ZERO);
+        *budget -= spent;
+        market.deposit(spent);
+        spent
+    }
+
+    pub fn purse(&self) -> Money {
+        self.purse
+    }
+
+    /// Give the player starting money; does not affect prices
+    pub fn endow_purse(&mut self, amount: Money) {
+        self.purse += self.money_supply.endow(amount);
+    }
+
+    /// Set the player's purse to an amount, creating or destroying the
+    /// difference without affecting prices
+    pub fn replace_purse(&mut self, amount: Money) {
+        if amount > self.purse {
+            self.endow_purse(amount - self.purse);
+        } else {
+            self.money_supply.retire(self.purse - amount);
+            self.purse = amount;
+        }
+    }
+
+    /// Buy up to `quantity` units of a good on an area's market for the
+    /// player, up its ladder of asks for as many as are in stock and the
+    /// purse pays for. Returns the units bought and what they cost, or None
+    /// if the area has no market.
+    pub fn buy_for_player(
+        &mut self,
+        area_id: EntityId,
+        good: GoodId,
+        quantity: f64,
+    ) -> Option<(f64, Money)> {
+        let market = self.markets.get_mut(&area_id)?;
+        let info = self.catalog.get(good)?;
+        let (bought, cost) = market.fill_buy(good, info, quantity.max(0.0), Money::MAX, self.purse);
+        self.purse -= cost;
+        Some((bought, cost))
+    }
+
+    /// Sell up to `quantity` units of a good from the player to an area's
+    /// market, down its ladder of bids for as many as its households' cash
+    /// pays for. Returns the units sold and what they fetched, or None if
+    /// the area has no market.
+    pub fn sell_for_player(
+        &mut self,
+        area_id: EntityId,
+        good: GoodId,
+        quantity: f64,
+    ) -> Option<(f64, Money)> {
+        let market = self.markets.get_mut(&area_id)?;
+        let info = self.catalog.get(good)?;
+        let (sold, paid) = market.fill_sell(good, info, quantity.max(0.0), Money::ZERO);
+        self.purse += paid;
+        Some((sold, paid))
+    }
+
+    /// Pay the player `hours` of an area's wage out of its households'
+    /// cash, as much as they have. Returns what was paid, or None if the
+    /// area has no market.
+    pub fn pay_player_wage(&mut self, area_id: EntityId, hours: f64) -> Option<Money> {
+        let market = self.markets.get_mut(&area_id)?;
+        let paid = market.withdraw(market.wage() * hours);
+        self.purse += paid;
+        Some(paid)
+    }
+
+    /// Pay an area's households out of the player's purse, as for a fare.
+    /// Returns what was paid: as much as the purse holds, or nothing if the
+    /// area has no market.
+    pub fn pay_from_purse(&mut self, area_id: EntityId, amount: Money) -> Money {
+        let Some(market) = self.markets.get_mut(&area_id) else {
+            return Money::ZERO;
+        };
+        let paid = amount.min(self.purse).max(Money::ZERO);
+        self.purse -= paid;
+        market.deposit(paid);
+        paid
+    }
+
+    pub fn open_market(&mut self, area_id: EntityId) {
+        if self.markets.contains_key(&area_id) {
+            return;
+        }
+        let mut market = Market::new(&self.catalog);
+        market.deposit(self.money_supply.endow(MARKET_ENDOWMENT));
+        self.markets.insert(area_id, market);
+    }
+
+    /// Found a firm in an area, opening the area's market if needed.
+    /// Returns false if the recipe is unknown or the id is taken.
+    pub fn add_firm(&mut self, mut firm: Firm) -> bool {
+        if self.recipes.get(firm.recipe).is_none() || self.firms.contains_key(&firm.id) {
+            return false;
+        }
+        self.open_market(firm.area_id);
+        firm.cash += self.money_supply.endow(FIRM_ENDOWMENT);
+        self.firms.insert(firm.id, firm);
+        true
+    }
+
+    /// Found a firm with capital from the player's purse rather than new
+    /// money, opening the area's market if needed. Returns false if the
+    /// recipe is unknown, the id is taken or the purse is short.
+    pub fn invest(&mut self, mut firm: Firm, capital: Money) -> bool {
+        if self.recipes.get(firm.recipe).is_none()
+            || self.firms.contains_key(&firm.id)
+            || capital < Money::ZERO
+            || capital > self.purse
+        {
+            return false;
+        }
+        self.open_market(firm.area_id);
+        self.purse -= capital;
+        firm.cash += capital;
+        self.firms.insert(firm.id, firm);
+        true
+    }
+
+    /// Retire a firm, settling its bank account and withdrawing its orders;
+    /// its cash leaves circulation
+    pub fn remove_firm(&mut self, id: EntityId) -> Option<Firm> {
+        let mut firm = self.firms.remove(&id)?;
+        self.bank.close_account(&mut firm);
+        if let Some(market) = self.markets.get_mut(&firm.area_id) {
+            market.cancel_orders(id);
+        }
+        self.money_supply.retire(firm.cash);
+        Some(firm)
+    }
+
+    /// Shut an area's market and every firm trading in it
+    pub fn close_market(&mut self, area_id: EntityId) {
+        let firm_ids: Vec<EntityId> = self.firms_in_area(area_id).map(|f| f.id).collect();
+        for id in firm_ids {
+            self.remove_firm(id);
+        }
+        let site_ids: Vec<EntityId> = self.sites_in_area(area_id).map(|s| s.id).collect();
+        for id in site_ids {
+            self.abandon_site(id);
+        }
+        if let Some(market) = self.markets.remove(&area_id) {
+            self.money_supply.retire(market.cash());
+        }
+        self.history.forget(area_id);
+    }
+
+    /// Reopen a market closed by [`Economy::close_market`], returning its
+    /// cash to circulation
+    pub fn restore_market(&mut self, area_id: EntityId, market: Market) {
+        self.money_supply.reinstate(market.cash());
+        if let Some(replaced) = self.markets.insert(area_id, market) {
+            self.money_supply.retire(replaced.cash());
+        }
+    }
+
+    /// Bring back a firm retired by [`Economy::remove_firm`] as it was
+    pub fn restore_firm(&mut self, firm: Firm) {
+        self.open_market(firm.area_id);
+        self.money_supply.reinstate(firm.cash);
+        if let Some(replaced) = self.firms.insert(firm.id, firm) {
+            self.money_supply.retire(replaced.cash);
+        }
+    }
+
+    pub fn firm(&self, id: EntityId) -> Option<&Firm> {
+        self.firms.get(&id)
+    }
+
+    pub fn firm_mut(&mut self, id: EntityId) -> Option<&mut Firm> {
+        self.firms.get_mut(&id)
+    }
+
+    /// Every firm, in id order
+    pub fn firms(&self) -> impl Iterator<Item = &Firm> {
+        self.firms.values()
+    }
+
+    /// The firm whose building stands at a spot on an area's map
+    pub fn firm_at(&self, area_id: EntityId, coords: (i32, i32)) -> Option<&Firm> {
+        self.firms_in_area(area_id).find(|f| f.coords == coords)
+    }
+
+    pub fn firms_in_area(&self, area_id: EntityId) -> impl Iterator<Item = &Firm> {
+        self.firms.values().filter(move |f| f.area_id == area_id)
+    }
+
+    /// Put goods into a firm's stock, as many as it has room for,
+    /// returning how many that was
+    pub fn hold(&mut self, firm_id: EntityId, good: GoodId, quantity: f64) -> f64 {
+        match self.firms.get_mut(&firm_id) {
+            Some(firm) => firm.hold(good, quantity, &self.catalog),
+            None => 0.0,
+        }
+    }
+
+    /// Take up to `quantity` of a good for a firm: out of its own stock
+    /// first, then bought up its area's market's ladder of asks for as long
+    /// as its cash lasts. Returns how much it came up with.
+    pub fn source(&mut self, firm_id: EntityId, good: GoodId, quantity: f64) -> f64 {
+        let Some(firm) = self.firms.get_mut(&firm_id) else {
+            return 0.0;
+        };
+        let held = firm.release(good, quantity);
+        let (Some(market), Some(info)) =
+            (self.markets.get_mut(&firm.area_id), self.catalog.get(good))
+        else {
+            return held;
+        };
+        let (bought, cost) = market.fill_buy(good, info, quantity - held, Money::MAX, firm.cash);
+        firm.cash -= cost;
+        held + bought
+    }
+
+    /// Stand an order on the market of the firm's area; see
+    /// [`Market::place_order`]. Returns false if the firm doesn't exist or
+    /// the limit is negative.
+    pub fn place_order(&mut self, order: Order) -> bool {
+        let Some(firm) = self.firms.get(&order.firm) else {
+            return false;
+        };
+        if order.limit < Money::ZERO || order.quantity.is_nan() {
+            return false;
+        }
+        let area_id = firm.area_id;
+        self.open_market(area_id);
+        self.markets.get_mut(&area_id).is_some_and(|market| {
+            market.place_order(order);
+            true
+        })
+    }
+
+    pub fn firm_count(&self) -> usize {
+        self.firms.len()
+    }
+
+    /// Start putting up a building, paying `budget` into the site from the
+    /// player's purse or the commissioning firm's cash. Returns false if
+    /// the recipe is unknown, the id is taken or the builder is short.
+    pub fn commission(&mut self, mut site: Site, budget: Money) -> bool {
+        if self.recipes.get(site.recipe).is_none()
+            || self.firms.contains_key(&site.id)
+            || self.sites.contains_key(&site.id)
+            || budget < Money::ZERO
+        {
+            return false;
+        }
+        let funds = match site.builder {
+            Builder::Player => &mut self.purse,
+            Builder::Firm(id) => match self.firms.get_mut(&id) {
+                Some(firm) => &mut firm.cash,
+                None => return false,
+            },
+        };
+        if budget > *funds {
+            return false;
+        }
+        *funds -= budget;
+        self.open_market(site.area_id);
+        site.budget = budget;
+        site.cash += budget;
+        self.sites.insert(site.id, site);
+        true
+    }
+
+    /// Stop work on a site; its cash leaves circulation
+    pub fn abandon_site(&mut self, id: EntityId) -> Option<Site> {
+        let site = self.sites.remove(&id)?;
+        self.money_supply.retire(site.cash);
+        Some(site)
+    }
+
+    /// What the materials for a building running `recipe` would cost on
+    /// an area's market, or at base value where there is no market yet
+    pub fn building_cost(&self, area_id: EntityId, recipe: RecipeId) -> Option<Money> {
+        let site = Site::new(0, "", area_id, recipe, &self.recipes, &self.catalog)?;
+        let unopened;
+        let market = match self.markets.get(&area_id) {
+            Some(market) => market,
+            None => {
+                unopened = Market::new(&self.catalog);
+                &unopened
+            }
+        };
+        Some(site.remaining_cost(market))
+    }
+
+    pub fn site(&self, id: EntityId) -> Option<&Site> {
+        self.sites.get(&id)
+    }
+
+    /// Every building site, in id order
+    pub fn sites(&self) -> impl Iterator<Item = &Site> {
+        self.sites.values()
+    }
+
+    pub fn sites_in_area(&self, area_id: EntityId) -> impl Iterator<Item = &Site> {
+        self.sites.values().filter(move |s| s.area_id == area_id)
+    }
+
+    /// The site at a spot on an area's map
+    pub fn site_at(&self, area_id: EntityId, coords: (i32, i32)) -> Option<&Site> {
+        self.sites_in_area(area_id).find(|s| s.coords == coords)
+    }
+
+    /// Firms ready to put up another building like their own: busy, well
+    /// off and in an area with nothing going up and enough workers nobody
+    /// has hired to staff it. At most one per area, the lowest id first.
+    pub fn expansions(&self, workforce: impl Fn(EntityId) -> u32) -> Vec<EntityId> {
+        let labor = |recipe: RecipeId| self.recipes.get(recipe).map_or(0, |r| r.labor);
+        let mut chosen: BTreeMap<EntityId, EntityId> = BTreeMap::new();
+        for firm in self.firms.values() {
+            if chosen.contains_key(&firm.area_id)
+                || firm.utilization < EXPANSION_UTILIZATION
+                || firm.cash < EXPANSION_BUDGET
+                || firm.cash + self.bank.account(firm.id).deposit < EXPANSION_WORTH
+                || self.sites_in_area(firm.area_id).next().is_some()
+                || self
+                    .building_cost(firm.area_id, firm.recipe)
+                    .is_none_or(|cost| cost > EXPANSION_BUDGET / 2.0)
+            {
+                continue;
+            }
+            let staffed: u32 = self
+                .firms_in_area(firm.area_id)
+                .map(|other| labor(other.recipe))
+                .sum();
+            if staffed + labor(firm.recipe) <= workforce(firm.area_id) {
+                chosen.insert(firm.area_id, firm.id);
+            }
+        }
+        chosen.into_values().collect()
+    }
+
+    /// Sites finished since the last call, now open as firms
+    pub fn take_finished(&mut self) -> Vec<Site> {
+        std::mem::take(&mut self.finished)
+    }
+
+    /// Advance monetary policy, production and household consumption.
+    ///
+    /// `workforce` reports how many workers live in a given area; firms hire
+    /// from that pool in id order until it runs out.
+    pub fn update(&mut self, delta: Duration, workforce: impl Fn(EntityId) -> u32) {
+        self.update_scheduled(delta, workforce, |_| Some(MarketTick::open(delta)));
+    }
+
+    /// Like [`Economy::update`], but each area's market advances by the tick
+    /// `due` gives for it, or sits the tick out when it gives None. Monetary
+    /// policy always follows `delta`. Markets are simulated in parallel.
+    pub fn update_scheduled(
+        &mut self,
+        delta: Duration,
+        workforce: impl Fn(EntityId) -> u32,
+        mut due: impl FnMut(EntityId) -> Option<MarketTick>,
+    ) {
+        let hours = delta.as_secs_f64() / 3600.0;
+        if hours <= 0.0 {
+            return;
+        }
+
+        self.apply_monetary_policy(hours);
+        for firm in self.firms.values_mut() {
+            firm.runs = 0.0;
+        }
+        let settings = MarketSettings {
+            price_level: self.money_supply.price_level(),
+            difficulty: self.difficulty,
+            volatility: self.volatility,
+        };
+
+        // Each due market is simulated with only its own firms, so markets
+        // can run on as many threads as there are and always come out the
+        // same. What crosses markets happens afterwards, in id order.
+        let mut ticks = BTreeMap::new();
+        for &area_id in self.markets.keys() {
+            if let Some(tick) = due(area_id) {
+                ticks.insert(area_id, (tick, workforce(area_id)));
+            }
+        }
+        let mut firms: BTreeMap<EntityId, Vec<&mut Firm>> = BTreeMap::new();
+        for firm in self.firms.values_mut() {
+            if ticks.contains_key(&firm.area_id) {
+                firms.entry(firm.area_id).or_default().push(firm);
+            }
+        }
+        let mut work: Vec<MarketWork> = self
+            .markets
+            .iter_mut()
+            .filter_map(|(area_id, market)| {
+                let &(tick, workforce) = ticks.get(area_id)?;
+                Some(MarketWork {
+                    market,
+                    firms: firms.remove(area_id).unwrap_or_default(),
+                    tick,
+                    workforce,
+                })
+            })
+            .collect();
+        let (catalog, recipes) = (&self.catalog, &self.recipes);
+        work.par_iter_mut()
+            .for_each(|work| work.run(catalog, recipes, settings));
+
+        self.build(&ticks);
+        self.bank.update(hours, &mut self.firms);
+    }
+
+    /// Advance the sites in due markets, opening the finished ones as firms
+    fn build(&mut self, ticks: &BTreeMap<EntityId, (MarketTick, u32)>) {
+        for site in self.sites.values_mut() {
+            let (Some((tick, _)), Some(market)) = (
+                ticks.get(&site.area_id),
+                self.markets.get_mut(&site.area_id),
+            ) else {
+                continue;
+            };
+            site.advance(market, &self.catalog, tick.elapsed.as_secs_f64() / 3600.0);
+        }
+
+        let done: Vec<EntityId> = self
+            .sites
+            .values()
+            .filter(|site| site.is_finished() && !self.firms.contains_key(&site.id))
+            .map(|site| site.id)
+            .collect();
+        for id in done {
+            let Some(site) = self.sites.remove(&id) else {
+                continue;
+            };
+            self.firms.insert(id, site.clone().into_firm());
+            self.finished.push(site);
+        }
+    }
+
+    /// Mint or burn money to follow the growth rate, spread evenly across
+    /// market households
+    fn apply_monetary_policy(&mut self, hours: f64) {
+        let change = self.money_supply.policy_change(hours);
+        if change == Money::ZERO || self.markets.is_empty() {
+            return;
+        }
+
+        let share = change / self.markets.len() as f64;
+        for market in self.markets.values_mut() {
+            if share > Money::ZERO {
+                market.deposit(share);
+                self.money_supply.mint(share);
+            } else {
+                let burned = market.withdraw(-share);
+                self.money_supply.burn(burned);
+            }
+        }
+    }
+}
+
+impl Default for Economy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Economy-wide figures every market is simulated with
+#[derive(Debug, Clone, Copy)]
+struct MarketSettings {
+    price_level: f64,
+    difficulty: Difficulty,
+    volatility: f64,
+}
+
+/// One market's share of an economy update: the market, the firms trading
+/// in it and how far it advances
+struct MarketWork<'a> {
+    market: &'a mut Market,
+    /// In id order, the order they hire in
+    firms: Vec<&'a mut Firm>,
+    tick: MarketTick,
+    workforce: u32,
+}
+
+impl MarketWork<'_> {
+    fn run(&mut self, catalog: &GoodsCatalog, recipes: &RecipeBook, settings: MarketSettings) {
+        let MarketWork {
+            market,
+            firms,
+            tick,
+            workforce,
+        } = self;
+        let difficulty = settings.difficulty;
+        let hours = tick.elapsed.as_secs_f64() / 3600.0;
+        let trading = hours * tick.open_share.clamp(0.0, 1.0);
+        let mut available = *workforce;
+
+        if tick.open_share > 0.0 {
+            book::match_orders(market, firms, catalog);
+        }
+
+        let tax_rate = tick.policy.tax_rate.clamp(0.0, 1.0);
+        for firm in firms.iter_mut() {
+            if let Some(recipe) = recipes.get(firm.recipe) {
+                let productivity = difficulty.productivity
+                    * tick.yields.for_building(recipe.building)
+                    * tick.infrastructure
+                    * tick.technology.for_building(recipe.building);
+                let before = firm.cash;
+                available -=
+                    firm.produce(recipe, market, catalog, available, trading, productivity);
+                let tax = (firm.cash - before).max(Money::ZERO) * tax_rate;
+                firm.cash -= tax;
+                market.record_tax(tax);
+            }
+        }
+
+        feed_households(catalog, market, *workforce, hours * difficulty.appetite);
+        let sales_tax = market.take_turnover() * tick.policy.sales_tax.clamp(0.0, 1.0);
+        let paid = market.withdraw(sales_tax);
+        market.record_tax(paid);
+        market.set_tariff(tick.policy.tariff);
+        market.update_prices(catalog, settings.price_level, settings.volatility);
+    }
+}
+
+/// Residents eat the cheapest food first
+fn feed_households(catalog: &GoodsCatalog, market: &mut Market, workers: u32, hours: f64) {
+    let mut foods: Vec<GoodId> = catalog
+        .iter()
+        .filter(|(_, good)| good.category == GoodCategory::Food)
+        .map(|(id, _)| id)
+        .collect();
+    foods.sort_by(|a, b| {
+        market
+            .price(*a)
+            .amount()
+            .total_cmp(&market.price(*b).amount())
+    });
+
+    let mut hunger = workers as f64 * FOOD_PER_WORKER_HOUR * hours;
+    for food in foods {
+        if hunger <= 0.0 {
+            break;
+        }
+        hunger -= market.sell(food, hunger);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_firm_opens_market() {
+        let mut economy = Economy::new();
+        let recipe = economy.recipes().find("grain farming").unwrap();
+        assert!(economy.market(5).is_none());
+
+        assert!(economy.add_firm(Firm::new(100, "Farm", 5, recipe)));
+        assert!(economy.market(5).is_some());
+        assert!(!economy.add_firm(Firm::new(100, "Duplicate", 5, recipe)));
+    }
+
+    #[test]
+    fn test_supply_chain_moves_goods_downstream() {
+        let mut economy = Economy::new();
+        let farming = economy.recipes().find("grain farming").unwrap();
+        let baking = economy.recipes().find("baking").unwrap();
+        economy.add_firm(Firm::new(1, "Farm", 1, farming));
+        economy.add_firm(Firm::new(2, "Bakery", 1, baking));
+        let bread = economy.catalog().id("bread").unwrap();
+
+        let before = economy.market(1).unwrap().stock(bread);
+        economy.update(Duration::from_secs(3600), |_| 14);
+
+        // Bread is the pricier food, so households eat grain and bread accumulates
+        assert!(economy.market(1).unwrap().stock(bread) > before);
+    }
+
+    #[test]
+    fn test_firms_compete_for_limited_labor() {
+        let mut economy = Economy::new();
+        let farming = economy.recipes().find("grain farming").unwrap();
+        economy.add_firm(Firm::new(1, "First Farm", 1, farming));
+        economy.add_firm(Firm::new(2, "Second Farm", 1, farming));
+
+        economy.update(Duration::from_secs(60), |_| 15);
+
+        let workers: Vec<u32> = economy.firms_in_area(1).map(|f| f.workers).collect();
+        assert_eq!(workers, vec![10, 5]);
+    }
+
+    #[test]
+    fn test_closed_market_halts_production() {
+        let mut economy = Economy::new();
+        let mining = economy.recipes().find("ore mining").unwrap();
+        economy.add_firm(Firm::new(1, "Mine", 1, mining));
+        let ore = economy.catalog().id("ore").unwrap();
+        let before = economy.market(1).unwrap().stock(ore);
+
+        let closed = MarketTick {
+            elapsed: Duration::from_secs(3600),
+            open_share: 0.0,
+            ..MarketTick::open(Duration::from_secs(3600))
+        };
+        economy.update_scheduled(closed.elapsed, |_| 10, |_| Some(closed));
+        assert_eq!(economy.market(1).unwrap().stock(ore), before);
+
+        economy.update(closed.elapsed, |_| 10);
+        assert!(economy.market(1).unwrap().stock(ore) > before);
+    }
+
+    #[test]
+    fn test_land_scales_resource_output() {
+        let mut poor = Economy::new();
+        let mining = poor.recipes().find("ore mining").unwrap();
+        poor.add_firm(Firm::new(1, "Mine", 1, mining));
+        let mut rich = poor.clone();
+        let ore = poor.catalog().id("ore").unwrap();
+        let before = poor.market(1).unwrap().stock(ore);
+
+        let tick = |mining| MarketTick {
+            yields: Yields {
+                mining,
+                ..Yields::EVEN
+            },
+            ..MarketTick::open(Duration::from_secs(3600))
+        };
+        poor.update_scheduled(Duration::from_secs(3600), |_| 10, |_| Some(tick(0.5)));
+        rich.update_scheduled(Duration::from_secs(3600), |_| 10, |_| Some(tick(2.0)));
+
+        let mined = |economy: &Economy| economy.market(1).unwrap().stock(ore) - before;
+        assert!(mined(&rich) > mined(&poor) * 3.0);
+        assert_eq!(Yields::EVEN.for_building(BuildingKind::Factory), 1.0);
+    }
+
+    #[test]
+    fn test_taxes_and_tariffs_follow_policy() {
+        let mut free = Economy::new();
+        let farming = free.recipes().find("grain farming").unwrap();
+        free.add_firm(Firm::new(1, "Farm", 1, farming));
+        let mut governed = free.clone();
+        let grain = free.catalog().id("grain").unwrap();
+
+        let hour = Duration::from_secs(3600);
+        let tick = MarketTick {
+            policy: FiscalPolicy {
+                tax_rate: 0.5,
+                tariff: 0.2,
+                sales_tax: 0.1,
+                spending: 0.0,
+            },
+            ..MarketTick::open(hour)
+        };
+        free.update(hour, |_| 10);
+        governed.update_scheduled(hour, |_| 10, |_| Some(tick));
+
+        let cash = |economy: &Economy| economy.firm(1).unwrap().cash;
+        assert!(cash(&governed) < cash(&free));
+        let taxed = governed.levy(1, Some(7));
+        assert!(taxed > Money::ZERO);
+        assert_eq!(governed.treasury(7), taxed);
+        assert_eq!(free.levy(1, Some(7)), Money::ZERO);
+        let drift = governed.total_balances() - free.total_balances();
+        assert!(drift.amount().abs() < 1e-6);
+
+        let spent = governed.spend_treasury(7, 1, taxed * 2.0);
+        assert_eq!(spent, taxed);
+        assert_eq!(governed.treasury(7), Money::ZERO);
+
+        let price = |economy: &Economy| economy.market(1).unwrap().price(grain).amount();
+        assert!((price(&governed) / price(&free) - 1.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_scarcity_raises_prices() {
+        let mut economy = Economy::new();
+        let smelting = economy.recipes().find("smelting").unwrap();
+        economy.add_firm(Firm::new(1, "Smelter", 1, smelting));
+        let ore = economy.catalog().id("ore").unwrap();
+        let base = economy.catalog().get(ore).unwrap().base_value;
+
+        // No mine supplies ore, so the smelter drains the pool
+        economy.update(Duration::from_secs(3600 * 10), |_| 6);
+
+        assert!(economy.market(1).unwrap().price(ore) > Money(base));
+    }
+
+    #[test]
+    fn test_large_player_buys_pay_more_per_unit() {
+        let economy = || {
+            let mut economy = Economy::new();
+            economy.open_market(1);
+            economy.endow_purse(Money(10_000.0));
+            economy
+        };
+        let grain = economy().catalog().id("grain").unwrap();
+
+        let (small, small_cost) = economy().buy_for_player(1, grain, 5.0).unwrap();
+        let mut large_buyer = economy();
+        let (large, large_cost) = large_buyer.buy_for_player(1, grain, 80.0).unwrap();
+        assert_eq!((small, large), (5.0, 80.0));
+        assert!(large_cost / large > small_cost / small);
+        assert_eq!(large_buyer.purse(), Money(10_000.0) - large_cost);
+
+        // Selling it all back walks down the bids for less than it cost
+        let (sold, paid) = large_buyer.sell_for_player(1, grain, 80.0).unwrap();
+        assert_eq!(sold, 80.0);
+        assert!(paid < large_cost);
+    }
+
+    #[test]
+    fn test_balances_match_money_supply() {
+        let mut economy = Economy::new();
+        let farming = economy.recipes().find("grain farming").unwrap();
+        let baking = economy.recipes().find("baking").unwrap();
+        economy.add_firm(Firm::new(1, "Farm", 1, farming));
+        economy.add_firm(Firm::new(2, "Bakery", 1, baking));
+        economy.set_money_growth(0.5);
+
+        for _ in 0..48 {
+            economy.update(Duration::from_secs(3600), |_| 14);
+        }
+
+        let issued = economy.money_supply().issued();
+        assert!((economy.total_balances() - issued).amount().abs() < 1e-6);
+        assert!(issued > Money(30_000.0));
+    }
+
+    #[test]
+    fn test_closing_market_retires_its_money() {
+        let mut economy = Economy::new();
+        let farming = economy.recipes().find("grain farming").unwrap();
+        economy.add_firm(Firm::new(1, "Farm", 1, farming));
+        economy.add_firm(Firm::new(2, "Other Farm", 2, farming));
+
+        economy.close_market(1);
+
+        assert!(economy.market(1).is_none());
+        assert!(economy.firm(1).is_none());
+        assert_eq!(economy.money_supply().issued(), economy.total_balances());
+    }
+
+    #[test]
+    fn test_money_growth_inflates_prices() {
+        let mut economy = Economy::new();
+        let farming = economy.recipes().find("grain farming").unwrap();
+        economy.add_firm(Firm::new(1, "Farm", 1, farming));
+        let ore = economy.catalog().id("ore").unwrap();
+
+        economy.update(Duration::from_secs(3600), |_| 0);
+        let before = economy.market(1).unwrap().price(ore);
+
+        economy.set_money_growth(1.0);
+        economy.update(Duration::from_secs(3600 * 24 * 90), |_| 0);
+
+        assert!(economy.money_supply().price_level() > 1.2);
+        assert!(economy.market(1).unwrap().price(ore) > before);
+    }
+
+    #[test]
+    fn test_debasing_lifts_prices_at_once() {
+        let mut economy = Economy::new();
+        economy.open_market(1);
+        economy.open_market(2);
+        let ore = economy.catalog().id("ore").unwrap();
+        let before = economy.market(1).unwrap().price(ore);
+
+        let minted = economy.debase(0.25);
+
+        assert_eq!(minted, MARKET_ENDOWMENT * 2.0 * 0.25);
+        assert!((economy.money_supply().price_level() - 1.25).abs() < 1e-9);
+        assert_eq!(economy.money_supply().issued(), economy.total_balances());
+        assert!(economy.market(2).unwrap().price(ore) > before);
+    }
+
+    #[test]
+    fn test_investing_moves_money_from_the_purse() {
+        let mut economy = Economy::new();
+        let baking = economy.recipes().find("baking").unwrap();
+        economy.endow_purse(Money(1_000.0));
+
+        assert!(!economy.invest(Firm::new(1, "Too Big", 1, baking), Money(2_000.0)));
+        assert!(economy.invest(Firm::new(1, "My Bakery", 1, baking), Money(600.0)));
+        assert_eq!(economy.purse(), Money(400.0));
+        assert_eq!(economy.firm(1).unwrap().cash, Money(600.0));
+        assert_eq!(economy.money_supply().issued(), economy.total_balances());
+    }
+
+    #[test]
+    fn test_sites_open_as_firms_once_built() {
+        let mut economy = Economy::new();
+        let logging = economy.recipes().find("logging").unwrap();
+        economy.endow_purse(Money(3_000.0));
+        let site = Site::new(7, "Camp", 1, logging, economy.recipes(), economy.catalog()).unwrap();
+
+        let mut by_firm = site.clone();
+        by_firm.builder = Builder::Firm(99);
+        assert!(!economy.commission(by_firm, Money(100.0)));
+        assert!(!economy.commission(site.clone(), Money(5_000.0)));
+        assert!(economy.commission(site, Money(2_000.0)));
+        assert_eq!(economy.purse(), Money(1_000.0));
+        assert!(economy.site_at(1, (0, 0)).is_some());
+        assert_eq!(economy.money_supply().issued(), economy.total_balances());
+
+        // A lumber camp takes four days
+        let day = Duration::from_secs(86_400);
+        for _ in 0..3 {
+            economy.update(day, |_| 0);
+        }
+        assert!(economy.firm(7).is_none());
+        assert!(economy.take_finished().is_empty());
+        economy.update(day, |_| 0);
+        assert!(economy.site(7).is_none());
+        let firm = economy.firm(7).unwrap();
+        assert_eq!(firm.recipe, logging);
+        assert!(firm.cash > Money::ZERO && firm.cash < Money(2_000.0));
+        let finished = economy.take_finished();
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].budget, Money(2_000.0));
+        assert_eq!(economy.money_supply().issued(), economy.total_balances());
+    }
+
+    #[test]
+    fn test_busy_firms_with_idle_workers_around_expand() {
+        let mut economy = Economy::new();
+        let farming = economy.recipes().find("grain farming").unwrap();
+        for id in [1, 2] {
+            economy.add_firm(Firm::new(id, "Farm", 1, farming));
+            let firm = economy.firm_mut(id).unwrap();
+            firm.cash = EXPANSION_WORTH;
+            firm.utilization = 1.0;
+        }
+        economy.firm_mut(1).unwrap().utilization = 0.5;
+
+        // Two farms take 20 workers, so a third needs 30 living there
+        assert!(economy.expansions(|_| 29).is_empty());
+        assert_eq!(economy.expansions(|_| 30), vec![2]);
+
+        let mut site = Site::new(3, "Farm", 1, farming, economy.recipes(), economy.catalog());
+        site.as_mut().unwrap().builder = Builder::Firm(2);
+        assert!(economy.commission(site.unwrap(), EXPANSION_BUDGET));
+        assert_eq!(
+            economy.firm(2).unwrap().cash,
+            EXPANSION_WORTH - EXPANSION_BUDGET
+        );
+        assert!(economy.expansions(|_| 100).is_empty());
+    }
+}
@@ -0,0 +1,143 @@
+use crate::game::state::EntityId;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::{Firm, GoodId, GoodsCatalog, Market, Money};
+
+/// Quantity below which an order counts as filled
+pub(super) const FILLED: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Side::Buy => "buy",
+            Side::Sell => "sell",
+        })
+    }
+}
+
+/// A firm's standing order on its area's market.
+///
+/// A buy order takes goods into the firm's held stock at prices at or
+/// below the limit; a sell order lets held stock go at prices at or above
+/// it. Orders trade with each other on the market's book first and then
+/// with its households a lot at a time, as far as the stock and cash on
+/// both sides allow, and stand until their quantity is used up.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Order {
+    pub firm: EntityId,
+    pub side: Side,
+    pub good: GoodId,
+    /// Units still to buy or sell
+    pub quantity: f64,
+    /// Highest price a buy order pays, or lowest a sell order takes
+    pub limit: Money,
+}
+
+impl Order {
+    pub fn is_filled(&self) -> bool {
+        self.quantity <= FILLED
+    }
+
+    /// Whether the order trades at `price`
+    pub fn is_met(&self, price: Money) -> bool {
+        match self.side {
+            Side::Buy => price <= self.limit,
+            Side::Sell => price >= self.limit,
+        }
+    }
+
+    /// Trade as much of the order with the households of `market` as
+    /// `firm` allows, and the firm's storehouse has room for, a lot at a
+    /// time for as long as each lot's price meets the limit. Returns the
+    /// units that changed hands.
+    pub fn fill(&mut self, market: &mut Market, firm: &mut Firm, catalog: &GoodsCatalog) -> f64 {
+        let Some(good) = catalog.get(self.good) else {
+            return 0.0;
+        };
+        let traded = match self.side {
+            Side::Buy => {
+                let wanted = self.quantity.min(firm.stock.room_for(self.good, catalog));
+                let (bought, cost) =
+                    market.fill_buy(self.good, good, wanted, self.limit, firm.cash);
+                let held = firm.hold(self.good, bought, catalog);
+                // Anything that didn't fit after all goes back, refunded at
+                // what it cost on average
+                let refund = if bought > 0.0 {
+                    market.withdraw(cost * ((bought - held) / bought))
+                } else {
+                    Money::ZERO
+                };
+                market.add(self.good, bought - held);
+                firm.cash -= cost - refund;
+                held
+            }
+            Side::Sell => {
+                let offered = self.quantity.min(firm.held(self.good));
+                let (sold, paid) = market.fill_sell(self.good, good, offered, self.limit);
+                firm.release(self.good, sold);
+                firm.cash += paid;
+                sold
+            }
+        };
+        self.quantity -= traded;
+        traded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::data::{default_catalog, default_recipes};
+
+    #[test]
+    fn test_orders_fill_once_the_price_crosses_their_limit() {
+        let catalog = default_catalog();
+        let recipes = default_recipes(&catalog);
+        let grain = catalog.id("grain").unwrap();
+        let mut market = Market::new(&catalog);
+        market.deposit(Money(1_000.0));
+        let mut firm = Firm::new(1, "Granary", 1, recipes.find("baking").unwrap());
+        firm.cash = Money(20.0);
+
+        let mut buy = Order {
+            firm: 1,
+            side: Side::Buy,
+            good: grain,
+            quantity: 50.0,
+            limit: Money(0.9),
+        };
+        // Grain is asked at over 1.0 with a full shelf, above the limit
+        assert_eq!(buy.fill(&mut market, &mut firm, &catalog), 0.0);
+
+        market.add(grain, 300.0);
+        market.update_prices(&catalog, 1.0, 1.0);
+        let price = market.price(grain);
+        assert!(price <= Money(0.9));
+        // The firm can only pay for part of the order, and pays more than
+        // the going price for each lot further up the ladder
+        let bought = buy.fill(&mut market, &mut firm, &catalog);
+        assert!(bought > 0.0 && bought < 20.0 / price.amount());
+        assert_eq!(firm.held(grain), bought);
+        assert!(firm.cash.amount().abs() < 1e-9);
+        assert!(!buy.is_filled());
+
+        let mut sell = Order {
+            side: Side::Sell,
+            quantity: bought,
+            limit: Money(0.45),
+            ..buy
+        };
+        assert_eq!(sell.fill(&mut market, &mut firm, &catalog), bought);
+        assert!(sell.is_filled());
+        assert_eq!(firm.held(grain), 0.0);
+        // Selling straight back down the bids loses the spread
+        assert!(firm.cash < Money(20.0) && firm.cash > Money(19.0));
+    }
+}
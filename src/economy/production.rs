@@ -0,0 +1,339 @@
+use crate::game::state::EntityId;
+use serde::{Deserialize, Serialize};
+
+use super::{Capacity, GoodId, GoodsCatalog, Inventory, Market, Money, Recipe, RecipeId};
+
+/// What a firm's storehouse takes: 50 tonnes in 50 cubic metres
+pub const STOREHOUSE: Capacity = Capacity {
+    mass: Some(50_000.0),
+    volume: Some(50_000.0),
+};
+
+/// A building in a local area that turns inputs and labor into outputs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Firm {
+    pub id: EntityId,
+    pub name: String,
+    pub area_id: EntityId,
+    /// Location on the area's map
+    #[serde(default)]
+    pub coords: (i32, i32),
+    pub recipe: RecipeId,
+    /// Workers employed during the last tick
+    pub workers: u32,
+    /// Most workers the firm will hire, when its owner has set a limit
+    #[serde(default)]
+    pub max_workers: Option<u32>,
+    /// Fraction of full-capacity output achieved during the last tick
+    pub utilization: f64,
+    #[serde(default)]
+    pub cash: Money,
+    /// Goods bought ahead through orders and contracts, used up before
+    /// any more is bought from the market
+    #[serde(default)]
+    pub stock: Inventory,
+    /// Times the recipe ran during the latest economy update; each output
+    /// was made in its recipe quantity times this
+    #[serde(skip)]
+    pub runs: f64,
+}
+
+impl Firm {
+    pub fn new(id: EntityId, name: &str, area_id: EntityId, recipe: RecipeId) -> Self {
+        Self {
+            id,
+            name: name.to_string(),
+            area_id,
+            coords: (0, 0),
+            recipe,
+            workers: 0,
+            max_workers: None,
+            utilization: 0.0,
+            cash: Money::ZERO,
+            stock: Inventory::new(STOREHOUSE),
+            runs: 0.0,
+        }
+    }
+
+    /// Units of a good the firm holds
+    pub fn held(&self, good: GoodId) -> f64 {
+        self.stock.get(good)
+    }
+
+    /// Put goods into the firm's stock, as many as there is room for,
+    /// returning how many that was
+    pub fn hold(&mut self, good: GoodId, quantity: f64, catalog: &GoodsCatalog) -> f64 {
+        self.stock.add(good, quantity, catalog)
+    }
+
+    /// Take up to `quantity` units out of the firm's stock, returning how
+    /// much there was to take
+    pub fn release(&mut self, good: GoodId, quantity: f64) -> f64 {
+        self.stock.remove(good, quantity)
+    }
+
+    /// Run one production step against the local market.
+    ///
+    /// Output is limited by whichever is scarcest: hired labor, the least
+    /// available input, or the cash to pay for both. Inputs come from the
+    /// firm's own stock first and the market after, bought up its ladder of
+    /// asks. Wages and bought inputs are paid into the market and output is
+    /// sold back to it for whatever the market can afford. `productivity` scales output without changing
+    /// costs. Returns the number of workers hired.
+    pub fn produce(
+        &mut self,
+        recipe: &Recipe,
+        market: &mut Market,
+        catalog: &GoodsCatalog,
+        available_labor: u32,
+        hours: f64,
+        productivity: f64,
+    ) -> u32 {
+        self.workers = recipe
+            .labor
+            .min(available_labor)
+            .min(self.max_workers.unwrap_or(u32::MAX));
+        let labor_ratio = if recipe.labor == 0 {
+            1.0
+        } else {
+            self.workers as f64 / recipe.labor as f64
+        };
+
+        let input_ratio = recipe
+            .inputs
+            .iter()
+            .map(|&(good, qty)| {
+                let needed = qty * hours;
+                if needed <= 0.0 {
+                    1.0
+                } else {
+                    ((self.held(good) + market.stock(good)) / needed).min(1.0)
+                }
+            })
+            .fold(1.0, f64::min);
+
+        let wages = market.wage() * (recipe.labor as f64 * hours);
+        let full_cost = recipe
+            .inputs
+            .iter()
+            .map(|&(good, qty)| market.price(good) * (qty * hours))
+            .sum::<Money>()
+            + wages;
+        let cash_ratio = if full_cost <= Money::ZERO {
+            1.0
+        } else {
+            (self.cash / full_cost).clamp(0.0, 1.0)
+        };
+
+        self.utilization = labor_ratio.min(input_ratio).min(cash_ratio);
+        if cash_ratio < labor_ratio {
+            // Lay off the workers the firm can't pay
+            self.workers = (recipe.labor as f64 * self.utilization).ceil() as u32;
+        }
+        if self.utilization <= 0.0 {
+            self.runs = 0.0;
+            return self.workers;
+        }
+        self.runs = hours * self.utilization * productivity;
+
+        for &(good, qty) in &recipe.inputs {
+            let needed = qty * hours * self.utilization;
+            let short = needed - self.release(good, needed);
+            if let Some(info) = catalog.get(good) {
+                let (_, cost) = market.fill_buy(good, info, short, Money::MAX, self.cash);
+                self.cash -= cost;
+            }
+        }
+        let wages = (wages * self.utilization).min(self.cash);
+        self.cash -= wages;
+        market.deposit(wages);
+
+        let mut revenue = Money::ZERO;
+        for &(good, qty) in &recipe.outputs {
+            let made = qty * self.runs;
+            market.add(good, made);
+            revenue += market.price(good) * made;
+        }
+        market.record_output(revenue);
+        self.cash += market.withdraw(revenue);
+
+        self.workers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::data::{default_catalog, default_recipes};
+
+    fn setup() -> (
+        crate::economy::GoodsCatalog,
+        crate::economy::RecipeBook,
+        Market,
+    ) {
+        let catalog = default_catalog();
+        let recipes = default_recipes(&catalog);
+        let mut market = Market::new(&catalog);
+        market.deposit(Money(1_000.0));
+        (catalog, recipes, market)
+    }
+
+    fn funded(mut firm: Firm) -> Firm {
+        firm.cash = Money(1_000.0);
+        firm
+    }
+
+    #[test]
+    fn test_farm_produces_grain_from_labor() {
+        let (catalog, recipes, mut market) = setup();
+        let grain = catalog.id("grain").unwrap();
+        let recipe_id = recipes.find("grain farming").unwrap();
+        let mut farm = funded(Firm::new(10, "Farm", 1, recipe_id));
+
+        let before = market.stock(grain);
+        let hired = farm.produce(
+            recipes.get(recipe_id).unwrap(),
+            &mut market,
+            &catalog,
+            100,
+            1.0,
+            1.0,
+        );
+
+        assert_eq!(hired, 10);
+        assert_eq!(farm.utilization, 1.0);
+        assert!((market.stock(grain) - before - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_bakery_converts_grain_to_bread() {
+        let (catalog, recipes, mut market) = setup();
+        let grain = catalog.id("grain").unwrap();
+        let bread = catalog.id("bread").unwrap();
+        let recipe_id = recipes.find("baking").unwrap();
+        let mut bakery = funded(Firm::new(11, "Bakery", 1, recipe_id));
+
+        bakery.produce(
+            recipes.get(recipe_id).unwrap(),
+            &mut market,
+            &catalog,
+            4,
+            2.0,
+            1.0,
+        );
+
+        assert!((market.stock(grain) - 80.0).abs() < 1e-9);
+        assert!((market.stock(bread) - 112.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_missing_inputs_limit_output() {
+        let (catalog, recipes, mut market) = setup();
+        let grain = catalog.id("grain").unwrap();
+        let bread = catalog.id("bread").unwrap();
+        market.take(grain, 95.0);
+        let recipe_id = recipes.find("baking").unwrap();
+        let mut bakery = funded(Firm::new(11, "Bakery", 1, recipe_id));
+
+        bakery.produce(
+            recipes.get(recipe_id).unwrap(),
+            &mut market,
+            &catalog,
+            4,
+            1.0,
+            1.0,
+        );
+
+        assert!((bakery.utilization - 0.5).abs() < 1e-9);
+        assert!(market.stock(grain).abs() < 1e-9);
+        assert!((market.stock(bread) - 103.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_held_stock_is_used_before_the_market() {
+        let (catalog, recipes, mut market) = setup();
+        let grain = catalog.id("grain").unwrap();
+        market.take(grain, 100.0);
+        let recipe_id = recipes.find("baking").unwrap();
+        let mut bakery = funded(Firm::new(11, "Bakery", 1, recipe_id));
+        bakery.hold(grain, 15.0, &catalog);
+
+        bakery.produce(
+            recipes.get(recipe_id).unwrap(),
+            &mut market,
+            &catalog,
+            4,
+            1.0,
+            1.0,
+        );
+
+        // The market has no grain, but the bakery's own covers the shift
+        // and costs nothing more: 2 in wages, then 6 bread sold at 2.5
+        assert_eq!(bakery.utilization, 1.0);
+        assert_eq!(bakery.held(grain), 5.0);
+        assert!((bakery.cash.amount() - 1_013.0).abs() < 1e-9);
+        assert!((market.cash().amount() - 987.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_labor_shortage_scales_output() {
+        let (catalog, recipes, mut market) = setup();
+        let recipe_id = recipes.find("grain farming").unwrap();
+        let mut farm = funded(Firm::new(10, "Farm", 1, recipe_id));
+
+        let hired = farm.produce(
+            recipes.get(recipe_id).unwrap(),
+            &mut market,
+            &catalog,
+            5,
+            1.0,
+            1.0,
+        );
+
+        assert_eq!(hired, 5);
+        assert!((farm.utilization - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_production_pays_wages_and_earns_revenue() {
+        let (catalog, recipes, mut market) = setup();
+        let grain = catalog.id("grain").unwrap();
+        let recipe_id = recipes.find("grain farming").unwrap();
+        let mut farm = funded(Firm::new(10, "Farm", 1, recipe_id));
+
+        farm.produce(
+            recipes.get(recipe_id).unwrap(),
+            &mut market,
+            &catalog,
+            10,
+            1.0,
+            1.0,
+        );
+
+        // 10 workers at 0.5 each, then 20 grain sold at 1.0 each
+        assert!((farm.cash.amount() - 1_015.0).abs() < 1e-9);
+        assert!((market.cash().amount() - 985.0).abs() < 1e-9);
+        assert_eq!(market.price(grain), Money(1.0));
+    }
+
+    #[test]
+    fn test_broke_firm_cannot_produce() {
+        let (catalog, recipes, mut market) = setup();
+        let recipe_id = recipes.find("grain farming").unwrap();
+        let mut farm = Firm::new(10, "Farm", 1, recipe_id);
+        farm.cash = Money(2.5);
+
+        let hired = farm.produce(
+            recipes.get(recipe_id).unwrap(),
+            &mut market,
+            &catalog,
+            10,
+            1.0,
+            1.0,
+        );
+
+        // Half a shift of wages is all the firm can cover
+        assert_eq!(hired, 5);
+        assert!((farm.utilization - 0.5).abs() < 1e-9);
+    }
+}
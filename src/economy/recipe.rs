@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::GoodId;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct RecipeId(pub u16);
+
+/// The kind of building a recipe runs in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum BuildingKind {
+    Farm,
+    Mine,
+    LumberCamp,
+    Fishery,
+    Factory,
+    Workshop,
+}
+
+impl fmt::Display for BuildingKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildingKind::Farm => write!(f, "Farm"),
+            BuildingKind::Mine => write!(f, "Mine"),
+            BuildingKind::LumberCamp => write!(f, "Lumber Camp"),
+            BuildingKind::Fishery => write!(f, "Fishery"),
+            BuildingKind::Factory => write!(f, "Factory"),
+            BuildingKind::Workshop => write!(f, "Workshop"),
+        }
+    }
+}
+
+/// A production process. Quantities are per simulated hour at full staffing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recipe {
+    pub name: String,
+    pub building: BuildingKind,
+    pub inputs: Vec<(GoodId, f64)>,
+    pub outputs: Vec<(GoodId, f64)>,
+    pub labor: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecipeBook {
+    recipes: Vec<Recipe>,
+}
+
+impl RecipeBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, recipe: Recipe) -> RecipeId {
+        let id = RecipeId(self.recipes.len() as u16);
+        self.recipes.push(recipe);
+        id
+    }
+
+    pub fn get(&self, id: RecipeId) -> Option<&Recipe> {
+        self.recipes.get(id.0 as usize)
+    }
+
+    pub fn find(&self, name: &str) -> Option<RecipeId> {
+        self.recipes
+            .iter()
+            .position(|r| r.name == name)
+            .map(|i| RecipeId(i as u16))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (RecipeId, &Recipe)> {
+        self.recipes
+            .iter()
+            .enumerate()
+            .map(|(i, recipe)| (RecipeId(i as u16), recipe))
+    }
+}
@@ -0,0 +1,269 @@
+//! Technologies that make firms more productive and open new recipes.
+//!
+//! The [`TechTree`] is content like the recipes and is read from the same
+//! goods file. Each [`Tech`] takes some days of research and may need other
+//! technologies first. Once it is known it lifts the output of some kinds of
+//! building and lets firms take up the recipes it unlocks; a recipe no
+//! technology unlocks can be run anywhere from the start. What a polity knows
+//! and is working on is its [`Research`].
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+use super::{BuildingKind, RecipeId};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct TechId(pub u16);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tech {
+    pub name: String,
+    /// Days of research it takes at a rate of one
+    pub days: f64,
+    /// Technologies that must be known before research can start
+    pub requires: Vec<TechId>,
+    /// Recipes firms can only take up once this is known
+    pub unlocks: Vec<RecipeId>,
+    /// Share added to the output of each kind of building
+    pub boosts: Vec<(BuildingKind, f64)>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TechTree {
+    techs: Vec<Tech>,
+}
+
+impl TechTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, tech: Tech) -> TechId {
+        let id = TechId(self.techs.len() as u16);
+        self.techs.push(tech);
+        id
+    }
+
+    pub fn get(&self, id: TechId) -> Option<&Tech> {
+        self.techs.get(id.0 as usize)
+    }
+
+    pub fn find(&self, name: &str) -> Option<TechId> {
+        self.techs
+            .iter()
+            .position(|t| t.name == name)
+            .map(|i| TechId(i as u16))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (TechId, &Tech)> {
+        self.techs
+            .iter()
+            .enumerate()
+            .map(|(i, tech)| (TechId(i as u16), tech))
+    }
+
+    pub fn len(&self) -> usize {
+        self.techs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.techs.is_empty()
+    }
+
+    /// Technologies that unlock a recipe; empty if it needs none
+    pub fn unlocking(&self, recipe: RecipeId) -> impl Iterator<Item = TechId> + '_ {
+        self.iter()
+            .filter(move |(_, tech)| tech.unlocks.contains(&recipe))
+            .map(|(id, _)| id)
+    }
+}
+
+/// Multipliers known technologies put on each kind of building's output
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TechBoosts([f64; 6]);
+
+impl TechBoosts {
+    /// No technology at all
+    pub const NONE: TechBoosts = TechBoosts([1.0; 6]);
+
+    pub fn for_building(&self, building: BuildingKind) -> f64 {
+        self.0[building as usize]
+    }
+
+    fn add(&mut self, building: BuildingKind, share: f64) {
+        self.0[building as usize] += share;
+    }
+}
+
+impl Default for TechBoosts {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// What a polity knows and what it is finding out
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Research {
+    known: BTreeSet<TechId>,
+    /// The technology being researched, picked when the last one finished
+    current: Option<TechId>,
+    /// Days of research put into the current technology
+    progress: f64,
+}
+
+impl Research {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn knows(&self, tech: TechId) -> bool {
+        self.known.contains(&tech)
+    }
+
+    /// Technologies known, in the order the tree lists them
+    pub fn known(&self) -> impl Iterator<Item = TechId> + '_ {
+        self.known.iter().copied()
+    }
+
+    /// The technology being researched and the share of it done, or the
+    /// one research will start on if none has begun
+    pub fn current(&self, tree: &TechTree) -> Option<(TechId, f64)> {
+        let Some(id) = self.current.filter(|&id| self.can_research(tree, id)) else {
+            return self.next(tree).map(|id| (id, 0.0));
+        };
+        let tech = tree.get(id)?;
+        Some((id, (self.progress / tech.days).clamp(0.0, 1.0)))
+    }
+
+    /// Whether research could start on a technology: it isn't known yet but
+    /// everything it requires is
+    pub fn can_research(&self, tree: &TechTree, tech: TechId) -> bool {
+        !self.knows(tech)
+            && tree
+                .get(tech)
+                .is_some_and(|t| t.requires.iter().all(|&r| self.knows(r)))
+    }
+
+    /// Whether firms can take up a recipe: no technology unlocks it, or
+    /// one that does is known
+    pub fn has_unlocked(&self, tree: &TechTree, recipe: RecipeId) -> bool {
+        let mut unlocking = tree.unlocking(recipe).peekable();
+        unlocking.peek().is_none() || unlocking.any(|tech| self.knows(tech))
+    }
+
+    /// The quickest technology to research next, the earliest listed
+    /// breaking ties
+    fn next(&self, tree: &TechTree) -> Option<TechId> {
+        tree.iter()
+            .filter(|&(id, _)| self.can_research(tree, id))
+            .min_by(|(_, a), (_, b)| a.days.total_cmp(&b.days))
+            .map(|(id, _)| id)
+    }
+
+    /// Put `days` of research in, moving on to the next technology each
+    /// time one is finished. Returns the technologies finished, in order.
+    pub fn advance(&mut self, tree: &TechTree, days: f64) -> Vec<TechId> {
+        let mut finished = Vec::new();
+        let mut left = days.max(0.0);
+        loop {
+            if self.current.is_none_or(|id| !self.can_research(tree, id)) {
+                self.current = self.next(tree);
+                self.progress = 0.0;
+            }
+            let Some(id) = self.current else {
+                break;
+            };
+            let Some(tech) = tree.get(id) else {
+                break;
+            };
+            let needed = tech.days - self.progress;
+            if left < needed {
+                self.progress += left;
+                break;
+            }
+            left -= needed;
+            self.known.insert(id);
+            self.current = None;
+            self.progress = 0.0;
+            finished.push(id);
+        }
+        finished
+    }
+
+    /// Output multipliers from every technology known
+    pub fn boosts(&self, tree: &TechTree) -> TechBoosts {
+        let mut boosts = TechBoosts::NONE;
+        for tech in self.known.iter().filter_map(|&id| tree.get(id)) {
+            for &(building, share) in &tech.boosts {
+                boosts.add(building, share);
+            }
+        }
+        boosts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree() -> TechTree {
+        let mut tree = TechTree::new();
+        let rotation = tree.add(Tech {
+            name: String::from("crop rotation"),
+            days: 10.0,
+            requires: vec![],
+            unlocks: vec![],
+            boosts: vec![(BuildingKind::Farm, 0.1)],
+        });
+        tree.add(Tech {
+            name: String::from("ploughs"),
+            days: 5.0,
+            requires: vec![rotation],
+            unlocks: vec![RecipeId(1)],
+            boosts: vec![(BuildingKind::Farm, 0.05), (BuildingKind::Workshop, 0.2)],
+        });
+        tree.add(Tech {
+            name: String::from("shafts"),
+            days: 20.0,
+            requires: vec![],
+            unlocks: vec![],
+            boosts: vec![(BuildingKind::Mine, 0.3)],
+        });
+        tree
+    }
+
+    #[test]
+    fn test_research_follows_prerequisites() {
+        let tree = tree();
+        let (rotation, ploughs, shafts) = (TechId(0), TechId(1), TechId(2));
+        let mut research = Research::new();
+        assert_eq!(research.current(&tree), Some((rotation, 0.0)));
+        assert!(!research.can_research(&tree, ploughs));
+        assert!(!research.has_unlocked(&tree, RecipeId(1)));
+        assert!(research.has_unlocked(&tree, RecipeId(0)));
+
+        assert!(research.advance(&tree, 4.0).is_empty());
+        assert_eq!(research.current(&tree), Some((rotation, 0.4)));
+
+        // What is left over carries into the next technology
+        assert_eq!(research.advance(&tree, 8.0), vec![rotation]);
+        assert_eq!(research.current(&tree), Some((ploughs, 0.4)));
+        assert_eq!(research.advance(&tree, 100.0), vec![ploughs, shafts]);
+        assert!(research.has_unlocked(&tree, RecipeId(1)));
+        assert_eq!(research.current(&tree), None);
+        assert!(research.advance(&tree, 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_known_technologies_boost_output() {
+        let tree = tree();
+        let mut research = Research::new();
+        assert_eq!(research.boosts(&tree), TechBoosts::NONE);
+
+        research.advance(&tree, 15.0);
+        let boosts = research.boosts(&tree);
+        assert!((boosts.for_building(BuildingKind::Farm) - 1.15).abs() < 1e-9);
+        assert_eq!(boosts.for_building(BuildingKind::Workshop), 1.2);
+        assert_eq!(boosts.for_building(BuildingKind::Mine), 1.0);
+    }
+}
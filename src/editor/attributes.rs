@@ -0,0 +1,230 @@
+use crate::game::WorldState;
+use crate::game::state::EntityId;
+use crate::ui::{Field, Form, FormValues};
+use crate::zoom::ZoomLevel;
+
+use super::Selection;
+
+/// Limits shared by editable counts, generous enough for any hand-built world
+const MAX_PLANETS: i64 = 64;
+pub(super) const MAX_POPULATION: i64 = 100_000_000_000;
+const MAX_BUILDINGS: i64 = 10_000;
+pub(super) const MAX_WORKFORCE: i64 = 100_000;
+
+/// An attribute form bound to the entity it edits
+#[derive(Debug, Clone)]
+pub struct AttributeForm {
+    selection: Selection,
+    form: Form,
+}
+
+impl AttributeForm {
+    /// Build a form pre-filled with the selection's current attributes
+    pub fn new(world: &WorldState, selection: Selection) -> Option<Self> {
+        let form = match selection {
+            Selection::Entity(level, id) => entity_form(world, level, id)?,
+            Selection::Building(id) => {
+                let firm = world.economy().firm(id)?;
+                let mut form = Form::new("Building");
+                form.add_field(Field::text("name", "Name", &firm.name));
+                form
+            }
+        };
+        Some(Self { selection, form })
+    }
+
+    pub fn selection(&self) -> Selection {
+        self.selection
+    }
+
+    pub fn form(&self) -> &Form {
+        &self.form
+    }
+
+    pub fn form_mut(&mut self) -> &mut Form {
+        &mut self.form
+    }
+
+    /// Validate the form and write it back to the world. Returns None while
+    /// any field is invalid, leaving the form open for correction.
+    pub fn commit(&mut self, world: &mut WorldState) -> Option<String> {
+        let values = self.form.commit()?;
+        let name = values.text("name").unwrap_or_default().to_string();
+        apply(world, self.selection, &values);
+        Some(format!("Updated {}", name))
+    }
+}
+
+fn entity_form(world: &WorldState, level: ZoomLevel, id: EntityId) -> Option<Form> {
+    let mut form = Form::new(&level.to_string());
+    let name = world.entities().name(level, id)?;
+    form.add_field(Field::text("name", "Name", name));
+    match level {
+        ZoomLevel::Galaxy => return None,
+        ZoomLevel::SolarSystem => {
+            let system = world.get_system(id)?;
+            form.add_field(Field::integer(
+                "planets",
+                "Planets",
+                system.planet_count as i64,
+                0,
+                MAX_PLANETS,
+            ));
+        }
+        ZoomLevel::Planet => {
+            let population = world.population(level, id)?;
+            form.add_field(Field::integer(
+                "population",
+                "Population",
+                population.min(MAX_POPULATION as u64) as i64,
+                0,
+                MAX_POPULATION,
+            ));
+        }
+        ZoomLevel::Region => {
+            let region = world.get_region(id)?;
+            form.add_field(Field::text("terrain", "Terrain", &region.terrain_type));
+        }
+        ZoomLevel::LocalArea => {
+            let area = world.get_area(id)?;
+            let workforce = world.population(level, id)?;
+            form.add_field(Field::integer(
+                "buildings",
+                "Buildings",
+                area.building_count as i64,
+                0,
+                MAX_BUILDINGS,
+            ));
+            form.add_field(Field::integer(
+                "workforce",
+                "Workforce",
+                workforce.min(MAX_WORKFORCE as u64) as i64,
+                0,
+                MAX_WORKFORCE,
+            ));
+        }
+        ZoomLevel::Room => {
+            let room = world.get_room(id)?;
+            form.add_field(Field::text("type", "Room type", &room.room_type));
+        }
+    }
+    Some(form)
+}
+
+/// Copy validated values onto the selection; values missing from the form
+/// leave the attribute unchanged
+fn apply(world: &mut WorldState, selection: Selection, values: &FormValues) {
+    let name = values.text("name").map(str::to_string);
+    let text = |key| values.text(key).map(str::to_string);
+
+    if let (Selection::Entity(level, id), Some(name)) = (selection, &name) {
+        world.entities_mut().rename(level, id, name);
+    }
+    match selection {
+        Selection::Entity(ZoomLevel::SolarSystem, id) => {
+            if let Some(system) = world.get_system_mut(id)
+                && let Some(planets) = values.integer("planets")
+            {
+                system.planet_count = planets as u32;
+            }
+        }
+        Selection::Entity(level @ ZoomLevel::Planet, id) => {
+            if let Some(population) = values.integer("population") {
+                world.set_population(level, id, population as u64);
+            }
+        }
+        Selection::Entity(ZoomLevel::Region, id) => {
+            if let Some(terrain) = text("terrain")
+                && world
+                    .get_region(id)
+                    .is_some_and(|r| r.terrain_type != terrain)
+            {
+                world.set_terrain(id, &terrain);
+            }
+        }
+        Selection::Entity(level @ ZoomLevel::LocalArea, id) => {
+            if let Some(area) = world.get_area_mut(id)
+                && let Some(buildings) = values.integer("buildings")
+            {
+                area.building_count = buildings as u32;
+            }
+            if let Some(workforce) = values.integer("workforce") {
+                world.set_population(level, id, workforce as u64);
+            }
+        }
+        Selection::Entity(ZoomLevel::Room, id) => {
+            if let Some(room) = world.get_room_mut(id)
+                && let Some(room_type) = text("type")
+            {
+                room.room_type = room_type;
+            }
+        }
+        Selection::Entity(ZoomLevel::Galaxy, _) => {}
+        Selection::Building(id) => {
+            if let Some(firm) = world.economy_mut().firm_mut(id) {
+                firm.name = name.unwrap_or_else(|| firm.name.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::TextEdit;
+
+    fn retype(form: &mut Form, text: &str) {
+        form.edit(TextEdit::End);
+        for _ in 0..64 {
+            form.edit(TextEdit::Backspace);
+        }
+        for c in text.chars() {
+            form.edit(TextEdit::Insert(c));
+        }
+    }
+
+    #[test]
+    fn test_edit_area_attributes() {
+        let mut world = WorldState::new();
+        let mut attributes =
+            AttributeForm::new(&world, Selection::Entity(ZoomLevel::LocalArea, 1)).unwrap();
+        assert_eq!(attributes.form().fields().len(), 3);
+
+        let form = attributes.form_mut();
+        retype(form, "Dockside");
+        form.focus_next();
+        form.focus_next();
+        retype(form, "75");
+
+        assert_eq!(
+            attributes.commit(&mut world),
+            Some(String::from("Updated Dockside"))
+        );
+        assert_eq!(world.entity_name(ZoomLevel::LocalArea, 1), "Dockside");
+        assert_eq!(world.population(ZoomLevel::LocalArea, 1), Some(75));
+    }
+
+    #[test]
+    fn test_invalid_values_are_not_applied() {
+        let mut world = WorldState::new();
+        let mut attributes =
+            AttributeForm::new(&world, Selection::Entity(ZoomLevel::SolarSystem, 1)).unwrap();
+        let form = attributes.form_mut();
+        form.focus_next();
+        retype(form, "500");
+
+        assert_eq!(attributes.commit(&mut world), None);
+        assert_eq!(world.get_system(1).unwrap().planet_count, 8);
+    }
+
+    #[test]
+    fn test_rename_building() {
+        let mut world = WorldState::new();
+        let mut attributes = AttributeForm::new(&world, Selection::Building(104)).unwrap();
+        retype(attributes.form_mut(), "Crumb & Co");
+        attributes.commit(&mut world).unwrap();
+
+        assert_eq!(world.economy().firm(104).unwrap().name, "Crumb & Co");
+        assert!(AttributeForm::new(&world, Selection::Building(999)).is_none());
+    }
+}
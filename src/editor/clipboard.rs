@@ -0,0 +1,83 @@
+use crate::economy::Firm;
+use crate::game::state::{EntitySnapshot, Placement};
+use crate::game::{Simulation, WorldState};
+use crate::zoom::ZoomLevel;
+
+use super::Selection;
+
+/// Something copied from the map
+#[derive(Debug, Clone)]
+pub enum ClipContent {
+    Entity(EntitySnapshot),
+    Building(Firm),
+}
+
+/// Copied map contents, positioned relative to the copied region's
+/// top-left corner
+#[derive(Debug, Clone)]
+pub struct Clipboard {
+    /// The zoom level the contents were copied from; they can only be
+    /// pasted into the same kind of view
+    pub view: ZoomLevel,
+    pub items: Vec<((i32, i32), ClipContent)>,
+}
+
+impl Clipboard {
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Map cells the contents would cover with their corner at `origin`
+    pub fn targets(&self, origin: (i32, i32)) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.items
+            .iter()
+            .map(move |((dx, dy), _)| (origin.0 + dx, origin.1 + dy))
+    }
+
+    /// Stamp fresh copies with new ids, returning what was created.
+    /// Buildings start over with new capital and no staff.
+    pub fn stamp(
+        &self,
+        simulation: &mut Simulation,
+        parent: Option<u64>,
+        origin: (i32, i32),
+    ) -> Vec<Selection> {
+        let world = simulation.world_mut();
+        self.items
+            .iter()
+            .filter_map(|((dx, dy), content)| {
+                let coords = (origin.0 + dx, origin.1 + dy);
+                stamp_one(world, content, parent, coords)
+            })
+            .collect()
+    }
+}
+
+fn stamp_one(
+    world: &mut WorldState,
+    content: &ClipContent,
+    parent: Option<u64>,
+    coords: (i32, i32),
+) -> Option<Selection> {
+    let id = world.next_id();
+    match content {
+        ClipContent::Entity(snapshot) => {
+            let copy = snapshot.duplicate(id, Placement::new(parent, coords));
+            let level = copy.level();
+            world.restore(copy);
+            Some(Selection::Entity(level, id))
+        }
+        ClipContent::Building(original) => {
+            let mut firm = Firm::new(id, &original.name, parent?, original.recipe);
+            firm.coords = coords;
+            world
+                .economy_mut()
+                .add_firm(firm)
+                .then_some(Selection::Building(id))
+        }
+    }
+}
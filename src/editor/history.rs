@@ -0,0 +1,91 @@
+use crate::economy::Firm;
+use crate::game::WorldState;
+use crate::game::state::{EntityId, EntitySnapshot};
+
+use super::Selection;
+
+/// Undo steps kept before the oldest is dropped
+pub const HISTORY_LIMIT: usize = 100;
+
+/// A copy of something taken before an edit changed it
+#[derive(Debug, Clone)]
+pub enum Saved {
+    Entity(EntitySnapshot),
+    Building(Firm),
+}
+
+impl Saved {
+    /// Capture the current state of a selection
+    pub fn capture(world: &WorldState, selection: Selection) -> Option<Self> {
+        match selection {
+            Selection::Entity(level, id) => world.snapshot(level, id).map(Saved::Entity),
+            Selection::Building(id) => world.economy().firm(id).cloned().map(Saved::Building),
+        }
+    }
+}
+
+/// One completed editor operation, recorded so it can be reversed
+#[derive(Debug, Clone)]
+pub enum EditOp {
+    /// Entities or buildings were created by placing or pasting
+    Added {
+        label: String,
+        items: Vec<Selection>,
+    },
+    Removed {
+        label: String,
+        saved: Saved,
+    },
+    /// Attributes were edited; `saved` holds the values from before
+    Modified {
+        label: String,
+        saved: Saved,
+    },
+    OpenedMarket {
+        label: String,
+        area_id: EntityId,
+    },
+}
+
+impl EditOp {
+    /// Reverse the operation, returning a description of what was undone
+    pub fn undo(self, world: &mut WorldState) -> String {
+        match self {
+            EditOp::Added { label, items } => {
+                for selection in items.into_iter().rev() {
+                    match selection {
+                        Selection::Entity(level, id) => {
+                            world.remove_entity(level, id);
+                        }
+                        Selection::Building(id) => {
+                            world.economy_mut().remove_firm(id);
+                        }
+                    }
+                }
+                label
+            }
+            EditOp::Removed { label, saved } => {
+                match saved {
+                    Saved::Entity(snapshot) => world.restore(snapshot),
+                    Saved::Building(firm) => world.economy_mut().restore_firm(firm),
+                }
+                label
+            }
+            EditOp::Modified { label, saved } => {
+                match saved {
+                    Saved::Entity(snapshot) => world.restore_attributes(snapshot),
+                    Saved::Building(before) => {
+                        if let Some(firm) = world.economy_mut().firm_mut(before.id) {
+                            firm.name = before.name;
+                        }
+                    }
+                }
+                label
+            }
+            EditOp::OpenedMarket { label, area_id } => {
+                world.economy_mut().close_market(area_id);
+                label
+            }
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use crate::economy::{GoodId, Money};
+use crate::game::WorldState;
+use crate::game::state::EntityId;
+use crate::ui::{Field, FieldValue, Form, FormValues};
+use crate::zoom::ZoomLevel;
+
+use super::attributes::{MAX_POPULATION, MAX_WORKFORCE};
+
+/// Live edits to the values the economy runs on, for the entity in view.
+///
+/// A planet's population, an area's workforce and market prices, and
+/// whether the player owns the firm under the cursor can all be changed
+/// without leaving the game. Fields only accept values the simulation can
+/// handle, and every value that changes is written to the world's journal.
+/// The game only opens it in debug builds.
+#[derive(Debug, Clone)]
+pub struct Inspector {
+    level: ZoomLevel,
+    id: EntityId,
+    firm: Option<EntityId>,
+    /// Goods in the order of their price fields
+    goods: Vec<GoodId>,
+    form: Form,
+    /// The form's values as first shown, to tell what was changed
+    before: FormValues,
+}
+
+impl Inspector {
+    /// A form for the entity in view and the firm under the cursor, if
+    /// either has anything to edit
+    pub fn new(
+        world: &WorldState,
+        level: ZoomLevel,
+        id: EntityId,
+        firm: Option<EntityId>,
+    ) -> Option<Self> {
+        let mut form = Form::new(&format!("Inspect {}", world.entity_name(level, id)));
+        let mut goods = Vec::new();
+        match level {
+            ZoomLevel::Planet => {
+                let population = world.population(level, id)?;
+                form.add_field(Field::integer(
+                    "population",
+                    "Population",
+                    population.min(MAX_POPULATION as u64) as i64,
+                    0,
+                    MAX_POPULATION,
+                ));
+            }
+            ZoomLevel::LocalArea => {
+                let workforce = world.population(level, id)?;
+                form.add_field(Field::integer(
+                    "workforce",
+                    "Workforce",
+                    workforce.min(MAX_WORKFORCE as u64) as i64,
+                    0,
+                    MAX_WORKFORCE,
+                ));
+                let economy = world.economy();
+                if let Some(market) = economy.market(id) {
+                    for (good_id, good) in economy.catalog().iter() {
+                        let (low, high) = market.price_range(good);
+                        let (low, high) = (cents_up(low), cents_down(high));
+                        let price = cents(market.price(good_id)).clamp(low, high);
+                        let label = format!("Price of {}", good.name);
+                        form.add_field(Field::number("price", &label, price, low, high));
+                        goods.push(good_id);
+                    }
+                }
+            }
+            _ => return None,
+        }
+
+        let firm = firm.and_then(|id| world.economy().firm(id));
+        if let Some(firm) = firm {
+            let label = format!("You own {}", firm.name);
+            form.add_field(Field::flag("owned", &label, world.business().owns(firm.id)));
+        }
+
+        let before = form.clone().commit()?;
+        Some(Self {
+            level,
+            id,
+            firm: firm.map(|f| f.id),
+            goods,
+            form,
+            before,
+        })
+    }
+
+    pub fn form(&self) -> &Form {
+        &self.form
+    }
+
+    pub fn form_mut(&mut self) -> &mut Form {
+        &mut self.form
+    }
+
+    /// Validate the form and write whatever changed back to the world,
+    /// journaling each change. Returns None while any field is invalid,
+    /// leaving the form open for correction.
+    pub fn commit(&mut self, world: &mut WorldState) -> Option<String> {
+        let values = self.form.commit()?;
+        let name = world.entity_name(self.level, self.id);
+        let mut prices = self.goods.iter();
+        let mut changed = 0;
+
+        for ((key, value), (_, was)) in values.iter().zip(self.before.iter()) {
+            let good = if key == "price" { prices.next() } else { None };
+            if value == was {
+                continue;
+            }
+            match (key, value) {
+                ("population", &FieldValue::Integer(population)) => {
+                    world.set_population(self.level, self.id, population as u64);
+                    world.record_edit(&name, "population", show(was), population.to_string());
+                    changed += 1;
+                }
+                ("workforce", &FieldValue::Integer(workforce)) => {
+                    world.set_population(self.level, self.id, workforce as u64);
+                    world.record_edit(&name, "workforce", show(was), workforce.to_string());
+                    changed += 1;
+                }
+                ("price", &FieldValue::Number(price)) => {
+                    let Some(&good_id) = good else { continue };
+                    let economy = world.economy_mut();
+                    let Some(good) = economy.catalog().get(good_id).cloned() else {
+                        continue;
+                    };
+                    if let Some(market) = economy.market_mut(self.id) {
+                        market.set_price(good_id, &good, Money(price));
+                    }
+                    let field = format!("{} price", good.name);
+                    world.record_edit(&name, field, show(was), show(value));
+                    changed += 1;
+                }
+                ("owned", &FieldValue::Flag(owned)) => {
+                    let Some(firm) = self.firm else { continue };
+                    world.set_owned(firm, owned);
+                    let firm_name = world
+                        .economy()
+                        .firm(firm)
+                        .map(|f| f.name.clone())
+                        .unwrap_or_default();
+                    world.record_edit(firm_name, "owner", owner(!owned), owner(owned));
+                    changed += 1;
+                }
+                _ => {}
+            }
+        }
+
+        self.before = values;
+        Some(match changed {
+            0 => format!("No changes to {}", name),
+            1 => format!("Changed 1 value of {}", name),
+            n => format!("Changed {} values of {}", n, name),
+        })
+    }
+}
+
+fn show(value: &FieldValue) -> String {
+    match value {
+        FieldValue::Text(text) => text.clone(),
+        FieldValue::Integer(n) => n.to_string(),
+        FieldValue::Number(n) => format!("{:.2}", n),
+        FieldValue::Flag(flag) => String::from(if *flag { "yes" } else { "no" }),
+    }
+}
+
+fn owner(owned: bool) -> &'static str {
+    if owned { "you" } else { "nobody" }
+}
+
+fn cents(money: Money) -> f64 {
+    (money.amount() * 100.0).round() / 100.0
+}
+
+fn cents_up(money: Money) -> f64 {
+    (money.amount() * 100.0).ceil() / 100.0
+}
+
+fn cents_down(money: Money) -> f64 {
+    (money.amount() * 100.0).floor() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::TextEdit;
+
+    fn retype(form: &mut Form, text: &str) {
+        form.edit(TextEdit::End);
+        for _ in 0..64 {
+            form.edit(TextEdit::Backspace);
+        }
+        for c in text.chars() {
+            form.edit(TextEdit::Insert(c));
+        }
+    }
+
+    #[test]
+    fn test_changes_are_applied_and_journaled() {
+        let mut world = WorldState::new();
+        let grain = world.economy().catalog().id("grain").unwrap();
+        let mut inspector = Inspector::new(&world, ZoomLevel::LocalArea, 1, Some(104)).unwrap();
+        let form = inspector.form_mut();
+        retype(form, "80");
+        // Grain is the first good; the owner flag comes last
+        form.focus_next();
+        let price = world.economy().market(1).unwrap().price(grain).amount() * 2.0;
+        retype(form, &format!("{:.2}", price));
+        form.focus_previous();
+        form.focus_previous();
+        retype(form, "yes");
+
+        assert_eq!(
+            inspector.commit(&mut world),
+            Some(String::from("Changed 3 values of Market District"))
+        );
+        assert_eq!(world.population(ZoomLevel::LocalArea, 1).unwrap(), 80);
+        let now = world.economy().market(1).unwrap().price(grain).amount();
+        assert!((now - price).abs() < 0.01, "{} vs {}", now, price);
+        assert!(world.business().owns(104));
+
+        let journal = world.journal().entries();
+        assert_eq!(journal.len(), 3);
+        assert_eq!(journal[0].to_string(), "Market District workforce: 60 → 80");
+        assert_eq!(journal[2].field, "owner");
+
+        assert_eq!(
+            inspector.commit(&mut world),
+            Some(String::from("No changes to Market District"))
+        );
+        assert_eq!(world.journal().entries().len(), 3);
+    }
+
+    #[test]
+    fn test_out_of_range_values_are_refused() {
+        let mut world = WorldState::new();
+        let mut inspector = Inspector::new(&world, ZoomLevel::Planet, 1, None).unwrap();
+        retype(inspector.form_mut(), "-5");
+
+        assert_eq!(inspector.commit(&mut world), None);
+        assert!(world.journal().is_empty());
+        assert!(Inspector::new(&world, ZoomLevel::SolarSystem, 1, None).is_none());
+    }
+}
@@ -0,0 +1,636 @@
+//! Sandbox world editor.
+//!
+//! The editor places and removes entities on the map the player is currently
+//! viewing. What can be placed depends on the zoom level: each view holds the
+//! entities one level down, and local areas also hold buildings and a market.
+
+mod attributes;
+mod clipboard;
+mod history;
+mod inspector;
+mod palette;
+
+pub use attributes::AttributeForm;
+pub use clipboard::{ClipContent, Clipboard};
+pub use inspector::Inspector;
+pub use palette::PaletteItem;
+
+use history::{EditOp, HISTORY_LIMIT, Saved};
+
+use crate::economy::{BuildingKind, Firm};
+use crate::game::Simulation;
+use crate::game::state::{EntityId, Placement};
+use crate::render::Glyphs;
+use crate::zoom::ZoomLevel;
+use thiserror::Error as ThisError;
+
+#[derive(ThisError, Debug, Clone, PartialEq, Eq)]
+pub enum EditError {
+    #[error("nothing can be placed at this zoom level")]
+    NothingToPlace,
+    #[error("({0}, {1}) is already occupied")]
+    Occupied(i32, i32),
+    #[error("nothing to remove at ({0}, {1})")]
+    NothingHere(i32, i32),
+    #[error("{0} already has a market")]
+    MarketExists(String),
+    #[error("nothing to copy in the selection")]
+    NothingToCopy,
+    #[error("the clipboard is empty")]
+    ClipboardEmpty,
+    #[error("the clipboard holds {0} contents; paste it in that view")]
+    WrongView(ZoomLevel),
+    #[error("nothing to undo")]
+    NothingToUndo,
+}
+
+pub type EditResult = std::result::Result<String, EditError>;
+
+/// Cursor, selection, clipboard and undo history for the sandbox editor
+#[derive(Debug, Clone, Default)]
+pub struct Editor {
+    selected: usize,
+    /// Corner of a rectangular selection, with the view it was marked in;
+    /// the cursor is the opposite corner
+    anchor: Option<(ZoomLevel, Option<EntityId>, (i32, i32))>,
+    clipboard: Option<Clipboard>,
+    history: Vec<EditOp>,
+}
+
+impl Editor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn select_next(&mut self, simulation: &Simulation) {
+        let len = palette_len(simulation);
+        self.selected = (self.selected % len + 1) % len;
+    }
+
+    pub fn select_previous(&mut self, simulation: &Simulation) {
+        let len = palette_len(simulation);
+        self.selected = (self.selected % len + len - 1) % len;
+    }
+
+    /// The palette entry under the cursor for the current view. The
+    /// selection wraps, so it stays valid when the palette changes size.
+    pub fn selected_item(&self, simulation: &Simulation) -> Option<PaletteItem> {
+        let palette = PaletteItem::for_view(simulation.zoom().current_level(), simulation);
+        if palette.is_empty() {
+            return None;
+        }
+        let index = self.selected % palette.len();
+        Some(palette[index])
+    }
+
+    /// One-line description of the palette selection, e.g. "Palette 2/8: Farm (grain farming)"
+    pub fn palette_label(&self, simulation: &Simulation) -> String {
+        let palette = PaletteItem::for_view(simulation.zoom().current_level(), simulation);
+        if palette.is_empty() {
+            return String::from("Palette: nothing placeable here");
+        }
+        let index = self.selected % palette.len();
+        format!(
+            "Palette {}/{}: {}  [/] to change",
+            index + 1,
+            palette.len(),
+            palette[index].label(simulation)
+        )
+    }
+
+    /// Place the selected palette item at the cursor
+    pub fn place(&mut self, simulation: &mut Simulation) -> EditResult {
+        let item = self
+            .selected_item(simulation)
+            .ok_or(EditError::NothingToPlace)?;
+        let (parent, coords) = cursor(simulation);
+        let (x, y) = coords;
+
+        match item {
+            PaletteItem::Entity(level) => {
+                if occupant(simulation, coords).is_some() {
+                    return Err(EditError::Occupied(x, y));
+                }
+                let world = simulation.world_mut();
+                let id = world
+                    .spawn_at(level, Placement::new(parent, coords))
+                    .ok_or(EditError::NothingToPlace)?;
+                let name = entity_name(simulation, level, id);
+                self.record(EditOp::Added {
+                    label: format!("placing {}", name),
+                    items: vec![Selection::Entity(level, id)],
+                });
+                Ok(format!("Placed {}", name))
+            }
+            PaletteItem::Building(recipe) => {
+                if occupant(simulation, coords).is_some() {
+                    return Err(EditError::Occupied(x, y));
+                }
+                let area_id = parent.ok_or(EditError::NothingToPlace)?;
+                let world = simulation.world_mut();
+                let id = world.next_id();
+                let kind = world
+                    .economy()
+                    .recipes()
+                    .get(recipe)
+                    .map_or(BuildingKind::Workshop, |r| r.building);
+                let mut firm = Firm::new(id, &format!("{} {}", kind, id), area_id, recipe);
+                firm.coords = coords;
+                let name = firm.name.clone();
+                world.economy_mut().add_firm(firm);
+                self.record(EditOp::Added {
+                    label: format!("building {}", name),
+                    items: vec![Selection::Building(id)],
+                });
+                Ok(format!("Built {}", name))
+            }
+            PaletteItem::Market => {
+                let area_id = parent.ok_or(EditError::NothingToPlace)?;
+                let area_name = simulation
+                    .world()
+                    .location_name(simulation.zoom().position(), ZoomLevel::LocalArea);
+                let economy = simulation.world_mut().economy_mut();
+                if economy.market(area_id).is_some() {
+                    return Err(EditError::MarketExists(area_name));
+                }
+                economy.open_market(area_id);
+                self.record(EditOp::OpenedMarket {
+                    label: format!("opening a market in {}", area_name),
+                    area_id,
+                });
+                Ok(format!("Opened a market in {}", area_name))
+            }
+        }
+    }
+
+    /// Remove whatever stands at the cursor
+    pub fn remove(&mut self, simulation: &mut Simulation) -> EditResult {
+        let (_, coords) = cursor(simulation);
+        let nothing_here = EditError::NothingHere(coords.0, coords.1);
+        let selection = occupant(simulation, coords).ok_or(nothing_here.clone())?;
+        let saved = Saved::capture(simulation.world(), selection).ok_or(nothing_here.clone())?;
+
+        let name = match selection {
+            Selection::Entity(level, id) => {
+                let name = entity_name(simulation, level, id);
+                if !simulation.world_mut().remove_entity(level, id) {
+                    return Err(nothing_here);
+                }
+                name
+            }
+            Selection::Building(id) => {
+                let economy = simulation.world_mut().economy_mut();
+                economy.remove_firm(id).ok_or(nothing_here)?.name
+            }
+        };
+
+        self.record(EditOp::Removed {
+            label: format!("removing {}", name),
+            saved,
+        });
+        Ok(format!("Removed {}", name))
+    }
+
+    /// Start a rectangular selection at the cursor, or clear the current one
+    pub fn toggle_mark(&mut self, simulation: &Simulation) {
+        if self.anchor.take().is_none() {
+            let (parent, coords) = cursor(simulation);
+            self.anchor = Some((simulation.zoom().current_level(), parent, coords));
+        }
+    }
+
+    /// Corners of the selection, or of the cursor cell alone if nothing is
+    /// marked in the current view
+    pub fn selection_bounds(&self, simulation: &Simulation) -> ((i32, i32), (i32, i32)) {
+        let (parent, cursor) = cursor(simulation);
+        let corner = match self.anchor {
+            Some((view, anchor_parent, coords))
+                if view == simulation.zoom().current_level() && anchor_parent == parent =>
+            {
+                coords
+            }
+            _ => cursor,
+        };
+        (
+            (corner.0.min(cursor.0), corner.1.min(cursor.1)),
+            (corner.0.max(cursor.0), corner.1.max(cursor.1)),
+        )
+    }
+
+    /// Copy everything in the selection to the clipboard and clear the mark
+    pub fn copy(&mut self, simulation: &Simulation) -> EditResult {
+        let (min, max) = self.selection_bounds(simulation);
+        let world = simulation.world();
+        let mut items = Vec::new();
+
+        for y in min.1..=max.1 {
+            for x in min.0..=max.0 {
+                let Some(selection) = occupant(simulation, (x, y)) else {
+                    continue;
+                };
+                let content = match Saved::capture(world, selection) {
+                    Some(Saved::Entity(snapshot)) => ClipContent::Entity(snapshot),
+                    Some(Saved::Building(firm)) => ClipContent::Building(firm),
+                    None => continue,
+                };
+                items.push(((x - min.0, y - min.1), content));
+            }
+        }
+
+        if items.is_empty() {
+            return Err(EditError::NothingToCopy);
+        }
+        let clipboard = Clipboard {
+            view: simulation.zoom().current_level(),
+            items,
+        };
+        let message = format!("Copied {} item(s)", clipboard.len());
+        self.clipboard = Some(clipboard);
+        self.anchor = None;
+        Ok(message)
+    }
+
+    /// Stamp the clipboard with its top-left corner at the cursor. Nothing
+    /// is placed unless every target cell is free.
+    pub fn paste(&mut self, simulation: &mut Simulation) -> EditResult {
+        let clipboard = self
+            .clipboard
+            .as_ref()
+            .filter(|c| !c.is_empty())
+            .ok_or(EditError::ClipboardEmpty)?;
+        if clipboard.view != simulation.zoom().current_level() {
+            return Err(EditError::WrongView(clipboard.view));
+        }
+
+        let (parent, origin) = cursor(simulation);
+        if let Some((x, y)) = clipboard
+            .targets(origin)
+            .find(|&coords| occupant(simulation, coords).is_some())
+        {
+            return Err(EditError::Occupied(x, y));
+        }
+
+        let items = clipboard.stamp(simulation, parent, origin);
+        let message = format!("Pasted {} item(s)", items.len());
+        self.record(EditOp::Added {
+            label: format!("pasting {} item(s)", items.len()),
+            items,
+        });
+        Ok(message)
+    }
+
+    /// Reverse the most recent edit
+    pub fn undo(&mut self, simulation: &mut Simulation) -> EditResult {
+        let op = self.history.pop().ok_or(EditError::NothingToUndo)?;
+        Ok(format!("Undid {}", op.undo(simulation.world_mut())))
+    }
+
+    /// Validate an attribute form and apply it, remembering the old values
+    /// so the change can be undone. Returns None while the form is invalid.
+    pub fn commit_attributes(
+        &mut self,
+        attributes: &mut AttributeForm,
+        simulation: &mut Simulation,
+    ) -> Option<String> {
+        let saved = Saved::capture(simulation.world(), attributes.selection());
+        let message = attributes.commit(simulation.world_mut())?;
+        if let Some(saved) = saved {
+            self.record(EditOp::Modified {
+                label: String::from("attribute changes"),
+                saved,
+            });
+        }
+        Some(message)
+    }
+
+    fn record(&mut self, op: EditOp) {
+        if self.history.len() == HISTORY_LIMIT {
+            self.history.remove(0);
+        }
+        self.history.push(op);
+    }
+
+    /// Open a form for the attributes of whatever stands at the cursor
+    pub fn attribute_form(&self, simulation: &Simulation) -> Result<AttributeForm, EditError> {
+        let (_, coords) = cursor(simulation);
+        occupant(simulation, coords)
+            .and_then(|selection| AttributeForm::new(simulation.world(), selection))
+            .ok_or(EditError::NothingHere(coords.0, coords.1))
+    }
+
+    /// Text grid of the current view centred on the cursor. Each cell is
+    /// three characters wide; the cursor cell is bracketed and selected
+    /// cells are parenthesized.
+    pub fn map_rows(
+        &self,
+        simulation: &Simulation,
+        glyphs: &Glyphs,
+        width: usize,
+        height: usize,
+    ) -> Vec<String> {
+        let view = simulation.zoom().current_level();
+        let (parent, cursor) = cursor(simulation);
+        let columns = (width / 3) as i32;
+        let rows = height as i32;
+        let left = cursor.0 - columns / 2;
+        let top = cursor.1 - rows / 2;
+
+        let (min, max) = self.selection_bounds(simulation);
+        let selected =
+            |x: i32, y: i32| (min.0..=max.0).contains(&x) && (min.1..=max.1).contains(&y);
+
+        let world = simulation.world();
+        let mut symbols = std::collections::HashMap::new();
+        if let Some(level) = view.zoom_in() {
+            for (id, coords) in world.placed_in(level, parent) {
+                let glyph = match world.get_region(id) {
+                    Some(region) if level == ZoomLevel::Region => {
+                        glyphs.terrain(&region.terrain_type)
+                    }
+                    _ => glyphs.level(level),
+                };
+                symbols.insert(coords, glyph);
+            }
+        }
+        if view == ZoomLevel::LocalArea
+            && let Some(area_id) = parent
+        {
+            let economy = world.economy();
+            for firm in economy.firms_in_area(area_id) {
+                let kind = economy.recipes().get(firm.recipe).map(|r| r.building);
+                let glyph = kind.map_or(glyphs.get("building"), |kind| glyphs.building(kind));
+                symbols.insert(firm.coords, glyph);
+            }
+            for site in economy.sites_in_area(area_id) {
+                symbols.insert(site.coords, glyphs.site(site.progress()));
+            }
+        }
+
+        (top..top + rows)
+            .map(|y| {
+                (left..left + columns)
+                    .map(|x| {
+                        let glyph = symbols.get(&(x, y)).copied().unwrap_or(glyphs.empty());
+                        if (x, y) == cursor {
+                            format!("[{}]", glyph)
+                        } else if selected(x, y) {
+                            format!("({})", glyph)
+                        } else {
+                            format!(" {} ", glyph)
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Palette size for the current view, never zero so it can be used as a modulus
+fn palette_len(simulation: &Simulation) -> usize {
+    PaletteItem::for_view(simulation.zoom().current_level(), simulation)
+        .len()
+        .max(1)
+}
+
+/// Something occupying a map cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    Entity(ZoomLevel, EntityId),
+    Building(EntityId),
+}
+
+/// The entity whose map is on screen and the cursor's spot on it
+fn cursor(simulation: &Simulation) -> (Option<EntityId>, (i32, i32)) {
+    let view = simulation.zoom().current_level();
+    let position = simulation.zoom().position();
+    (position.map_owner(view), position.coords_for_level(view))
+}
+
+fn occupant(simulation: &Simulation, coords: (i32, i32)) -> Option<Selection> {
+    let view = simulation.zoom().current_level();
+    let (parent, _) = cursor(simulation);
+    let world = simulation.world();
+
+    if view == ZoomLevel::LocalArea
+        && let Some(firm) = parent.and_then(|area| world.economy().firm_at(area, coords))
+    {
+        return Some(Selection::Building(firm.id));
+    }
+
+    let level = view.zoom_in()?;
+    world
+        .entity_at(level, parent, coords)
+        .map(|id| Selection::Entity(level, id))
+}
+
+fn entity_name(simulation: &Simulation, level: ZoomLevel, id: EntityId) -> String {
+    simulation.world().entity_name(level, id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zoom::Direction;
+
+    fn move_to(simulation: &mut Simulation, coords: (i32, i32)) {
+        let level = simulation.zoom().current_level();
+        simulation
+            .zoom_mut()
+            .position_mut()
+            .set_coords_for_level(level, coords);
+    }
+
+    fn zoomed_to(level: ZoomLevel) -> Simulation {
+        let mut simulation = Simulation::new();
+        while simulation.zoom().current_level() > level {
+            simulation.zoom_in();
+        }
+        simulation
+    }
+
+    #[test]
+    fn test_place_and_remove_planet() {
+        let mut simulation = zoomed_to(ZoomLevel::SolarSystem);
+        let mut editor = Editor::new();
+        assert_eq!(
+            editor.selected_item(&simulation),
+            Some(PaletteItem::Entity(ZoomLevel::Planet))
+        );
+
+        // Terra already occupies the origin
+        assert_eq!(
+            editor.place(&mut simulation),
+            Err(EditError::Occupied(0, 0))
+        );
+
+        simulation.move_cursor(Direction::Right);
+        let message = editor.place(&mut simulation).unwrap();
+        assert!(message.starts_with("Placed Planet "));
+        assert_eq!(
+            simulation
+                .world()
+                .placed_in(ZoomLevel::Planet, Some(1))
+                .len(),
+            2
+        );
+
+        assert!(
+            editor
+                .remove(&mut simulation)
+                .unwrap()
+                .starts_with("Removed Planet ")
+        );
+        assert_eq!(
+            editor.remove(&mut simulation),
+            Err(EditError::NothingHere(1, 0))
+        );
+    }
+
+    #[test]
+    fn test_place_building_in_local_area() {
+        let mut simulation = zoomed_to(ZoomLevel::LocalArea);
+        let mut editor = Editor::new();
+        while editor.selected_item(&simulation)
+            != simulation
+                .world()
+                .economy()
+                .recipes()
+                .find("baking")
+                .map(PaletteItem::Building)
+        {
+            editor.select_next(&simulation);
+        }
+
+        simulation.move_cursor(Direction::Down);
+        let firms_before = simulation.world().economy().firm_count();
+        assert!(
+            editor
+                .place(&mut simulation)
+                .unwrap()
+                .starts_with("Built Workshop ")
+        );
+        assert_eq!(simulation.world().economy().firm_count(), firms_before + 1);
+
+        let rows = Editor::new().map_rows(&simulation, &Glyphs::default(), 9, 3);
+        assert_eq!(rows[1], " I [W] W ");
+    }
+
+    #[test]
+    fn test_market_only_opens_once() {
+        let mut simulation = zoomed_to(ZoomLevel::LocalArea);
+        let mut editor = Editor::new();
+        editor.select_previous(&simulation);
+        assert_eq!(editor.selected_item(&simulation), Some(PaletteItem::Market));
+
+        assert_eq!(
+            editor.place(&mut simulation),
+            Err(EditError::MarketExists(String::from("Market District")))
+        );
+    }
+
+    #[test]
+    fn test_copy_and_paste_region() {
+        let mut simulation = zoomed_to(ZoomLevel::LocalArea);
+        let mut editor = Editor::new();
+        let firms_before = simulation.world().economy().firm_count();
+
+        // Select the farm and mine at (-3, -1) and (-1, -1)
+        move_to(&mut simulation, (-3, -1));
+        editor.toggle_mark(&simulation);
+        move_to(&mut simulation, (-1, -1));
+        assert_eq!(
+            editor.copy(&simulation),
+            Ok(String::from("Copied 2 item(s)"))
+        );
+
+        move_to(&mut simulation, (-3, 3));
+        assert_eq!(
+            editor.paste(&mut simulation),
+            Ok(String::from("Pasted 2 item(s)"))
+        );
+        let economy = simulation.world().economy();
+        assert_eq!(economy.firm_count(), firms_before + 2);
+        let copy = economy.firm_at(1, (-1, 3)).unwrap();
+        assert_eq!(copy.name, "Deepvein Mine");
+        assert_ne!(copy.id, 102);
+
+        // Pasting over the copies is refused outright
+        assert_eq!(
+            editor.paste(&mut simulation),
+            Err(EditError::Occupied(-3, 3))
+        );
+
+        simulation.zoom_out();
+        assert_eq!(
+            editor.paste(&mut simulation),
+            Err(EditError::WrongView(ZoomLevel::LocalArea))
+        );
+    }
+
+    #[test]
+    fn test_undo_reverses_edits_in_order() {
+        let mut simulation = zoomed_to(ZoomLevel::LocalArea);
+        let mut editor = Editor::new();
+        let issued = simulation.world().economy().money_supply().issued();
+
+        move_to(&mut simulation, (-3, -1));
+        editor.remove(&mut simulation).unwrap();
+        move_to(&mut simulation, (5, 5));
+        editor.place(&mut simulation).unwrap();
+        assert_eq!(
+            simulation.world().placed_in(ZoomLevel::Room, Some(1)).len(),
+            2
+        );
+
+        assert!(
+            editor
+                .undo(&mut simulation)
+                .unwrap()
+                .starts_with("Undid placing Room ")
+        );
+        assert_eq!(
+            simulation.world().placed_in(ZoomLevel::Room, Some(1)).len(),
+            1
+        );
+
+        assert_eq!(
+            editor.undo(&mut simulation),
+            Ok(String::from("Undid removing Highland Farm"))
+        );
+        let economy = simulation.world().economy();
+        assert_eq!(economy.firm_at(1, (-3, -1)).unwrap().id, 101);
+        assert!((economy.money_supply().issued() - issued).amount().abs() < 1e-6);
+
+        assert_eq!(editor.undo(&mut simulation), Err(EditError::NothingToUndo));
+    }
+
+    #[test]
+    fn test_undo_attribute_changes() {
+        let mut simulation = zoomed_to(ZoomLevel::SolarSystem);
+        let mut editor = Editor::new();
+        let mut attributes = editor.attribute_form(&simulation).unwrap();
+        let form = attributes.form_mut();
+        for _ in 0.."Terra".len() {
+            form.edit(crate::ui::TextEdit::Backspace);
+        }
+        form.edit(crate::ui::TextEdit::Insert('X'));
+
+        editor
+            .commit_attributes(&mut attributes, &mut simulation)
+            .unwrap();
+        assert_eq!(simulation.world().entity_name(ZoomLevel::Planet, 1), "X");
+
+        editor.undo(&mut simulation).unwrap();
+        assert_eq!(
+            simulation.world().entity_name(ZoomLevel::Planet, 1),
+            "Terra"
+        );
+    }
+
+    #[test]
+    fn test_map_marks_cursor() {
+        let simulation = zoomed_to(ZoomLevel::Galaxy);
+        let rows = Editor::new().map_rows(&simulation, &Glyphs::default(), 9, 1);
+        assert_eq!(rows, vec![String::from(" · [*] · ")]);
+    }
+}
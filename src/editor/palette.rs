@@ -0,0 +1,45 @@
+use crate::economy::RecipeId;
+use crate::game::Simulation;
+use crate::zoom::ZoomLevel;
+
+/// Something the sandbox editor can place
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaletteItem {
+    /// A new entity at the given level, on the current view's map
+    Entity(ZoomLevel),
+    /// A firm running the given recipe, in the current local area
+    Building(RecipeId),
+    /// The current local area's market
+    Market,
+}
+
+impl PaletteItem {
+    /// Items placeable while viewing the given level
+    pub fn for_view(view: ZoomLevel, simulation: &Simulation) -> Vec<PaletteItem> {
+        let mut items: Vec<PaletteItem> = view
+            .zoom_in()
+            .map(PaletteItem::Entity)
+            .into_iter()
+            .collect();
+        if view == ZoomLevel::LocalArea {
+            let recipes = simulation.world().economy().recipes();
+            items.extend(recipes.iter().map(|(id, _)| PaletteItem::Building(id)));
+            items.push(PaletteItem::Market);
+        }
+        items
+    }
+
+    pub fn label(self, simulation: &Simulation) -> String {
+        match self {
+            PaletteItem::Entity(level) => level.to_string(),
+            PaletteItem::Building(id) => simulation
+                .world()
+                .economy()
+                .recipes()
+                .get(id)
+                .map(|r| format!("{} ({})", r.building, r.name))
+                .unwrap_or_else(|| String::from("Unknown building")),
+            PaletteItem::Market => String::from("Market"),
+        }
+    }
+}
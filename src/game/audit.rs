@@ -0,0 +1,384 @@
+//! Checks that a world still makes sense.
+//!
+//! Nothing in a healthy simulation should trip these: every placed entity's
+//! parent exists, firms and characters live in areas that exist, ships sail
+//! between planets that exist, stocks, balances, prices and deposits are
+//! real numbers in range, no two polities govern the same place, and the
+//! money supply's issued total matches the cash actually held. Long soak
+//! runs call [`WorldState::audit`] after every tick to catch slow drift
+//! early.
+
+use thiserror::Error as ThisError;
+
+use crate::economy::Money;
+use crate::zoom::ZoomLevel;
+
+use super::WorldState;
+use super::deposits::DepositKind;
+use super::polities::Polity;
+use super::state::EntityId;
+
+/// Rounding allowed when money moves between accounts, as a share of the
+/// money issued
+const MONEY_TOLERANCE: f64 = 1e-6;
+
+/// Rounding allowed below zero for stocks and balances
+const NEGATIVE_TOLERANCE: f64 = 1e-9;
+
+/// Something that should never be true of a world
+#[derive(ThisError, Debug, Clone, PartialEq)]
+pub enum Violation {
+    #[error("money issued ({issued:.4}) differs from money held ({held:.4})")]
+    MoneyNotConserved { issued: f64, held: f64 },
+    #[error("{level} {id} is placed in missing {parent}")]
+    MissingParent {
+        level: ZoomLevel,
+        id: EntityId,
+        parent: EntityId,
+    },
+    #[error("firm {firm} is in area {area}, which has no market")]
+    FirmWithoutMarket { firm: EntityId, area: EntityId },
+    #[error("firm {0} runs a recipe that doesn't exist")]
+    UnknownRecipe(EntityId),
+    #[error("character {character} lives in missing area {area}")]
+    HomelessCharacter { character: EntityId, area: EntityId },
+    #[error("{owner} holds {amount} {good}")]
+    BadStock {
+        owner: String,
+        good: String,
+        amount: f64,
+    },
+    #[error("{owner} holds a balance of {amount}")]
+    BadBalance { owner: String, amount: f64 },
+    #[error("{good} is priced at {price} in area {area}")]
+    BadPrice {
+        area: EntityId,
+        good: String,
+        price: f64,
+    },
+    #[error("the {kind} in region {region} holds {reserve} of {capacity}")]
+    BadDeposit {
+        region: EntityId,
+        kind: DepositKind,
+        reserve: f64,
+        capacity: f64,
+    },
+    #[error("region {region} is governed by both polity {first} and polity {second}")]
+    OverlappingClaims {
+        region: EntityId,
+        first: EntityId,
+        second: EntityId,
+    },
+    #[error("ship {ship} is at or bound for missing planet {planet}")]
+    LostShip { ship: EntityId, planet: EntityId },
+    #[error(
+        "polity {polity} taxes at {tax_rate} with a tariff of {tariff} and a sales tax of \
+         {sales_tax}, spending {spending} a day"
+    )]
+    BadPolicy {
+        polity: EntityId,
+        tax_rate: f64,
+        tariff: f64,
+        sales_tax: f64,
+        spending: f64,
+    },
+}
+
+fn is_negative(amount: f64) -> bool {
+    !amount.is_finite() || amount < -NEGATIVE_TOLERANCE
+}
+
+impl WorldState {
+    /// Every way the world breaks the simulation's invariants; empty if
+    /// it is sound
+    pub fn audit(&self) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        self.audit_placements(&mut violations);
+        self.audit_deposits(&mut violations);
+        self.audit_polities(&mut violations);
+        self.audit_ships(&mut violations);
+        self.audit_economy(&mut violations);
+        self.audit_characters(&mut violations);
+        violations
+    }
+
+    fn audit_placements(&self, violations: &mut Vec<Violation>) {
+        let levels = [
+            ZoomLevel::Planet,
+            ZoomLevel::Region,
+            ZoomLevel::LocalArea,
+            ZoomLevel::Room,
+        ];
+        for level in levels {
+            let Some(parent_level) = level.zoom_out() else {
+                continue;
+            };
+            for id in self.ids(level) {
+                let parent = self.placement_of(level, id).and_then(|p| p.parent);
+                if let Some(parent) = parent
+                    && self.placement_of(parent_level, parent).is_none()
+                {
+                    violations.push(Violation::MissingParent { level, id, parent });
+                }
+            }
+        }
+    }
+
+    fn audit_deposits(&self, violations: &mut Vec<Violation>) {
+        for region in self.ids(ZoomLevel::Region) {
+            let deposits = self.get_region(region).and_then(|r| r.deposits.as_ref());
+            for deposit in deposits.into_iter().flatten() {
+                let (reserve, capacity) = (deposit.reserve, deposit.capacity);
+                if is_negative(reserve) || !capacity.is_finite() || reserve > capacity {
+                    violations.push(Violation::BadDeposit {
+                        region,
+                        kind: deposit.kind,
+                        reserve,
+                        capacity,
+                    });
+                }
+            }
+        }
+    }
+
+    fn audit_polities(&self, violations: &mut Vec<Violation>) {
+        for polity in self.polities() {
+            if !Polity::is_valid_policy(polity.policy) {
+                violations.push(Violation::BadPolicy {
+                    polity: polity.id,
+                    tax_rate: polity.policy.tax_rate,
+                    tariff: polity.policy.tariff,
+                    sales_tax: polity.policy.sales_tax,
+                    spending: polity.policy.spending,
+                });
+            }
+        }
+        for region in self.ids(ZoomLevel::Region) {
+            let planet = self
+                .placement_of(ZoomLevel::Region, region)
+                .and_then(|placement| placement.parent);
+            let mut governing = self
+                .polities()
+                .filter(|polity| polity.governs(region, planet));
+            if let (Some(first), Some(second)) = (governing.next(), governing.next()) {
+                violations.push(Violation::OverlappingClaims {
+                    region,
+                    first: first.id,
+                    second: second.id,
+                });
+            }
+        }
+    }
+
+    fn audit_ships(&self, violations: &mut Vec<Violation>) {
+        let catalog = self.economy().catalog();
+        for ship in self.ships() {
+            let ports = match ship.voyage() {
+                Some(voyage) => vec![voyage.from, voyage.to],
+                None => ship.docked_at().into_iter().collect(),
+            };
+            for planet in ports {
+                if !self.entities().contains(ZoomLevel::Planet, planet) {
+                    violations.push(Violation::LostShip {
+                        ship: ship.id,
+                        planet,
+                    });
+                }
+            }
+            if let Some(cargo) = ship.cargo
+                && is_negative(cargo.quantity)
+            {
+                violations.push(Violation::BadStock {
+                    owner: ship.name.clone(),
+                    good: catalog
+                        .get(cargo.good)
+                        .map_or_else(|| format!("good {}", cargo.good.0), |g| g.name.clone()),
+                    amount: cargo.quantity,
+                });
+            }
+        }
+    }
+
+    fn audit_economy(&self, violations: &mut Vec<Violation>) {
+        let economy = self.economy();
+        let catalog = economy.catalog();
+
+        let issued = economy.money_supply().issued().amount();
+        let held = economy.total_balances().amount();
+        if !held.is_finite() || (issued - held).abs() > MONEY_TOLERANCE * issued.abs().max(1.0) {
+            violations.push(Violation::MoneyNotConserved { issued, held });
+        }
+        let mut balance = |owner: String, amount: Money| {
+            if is_negative(amount.amount()) {
+                violations.push(Violation::BadBalance {
+                    owner,
+                    amount: amount.amount(),
+                });
+            }
+        };
+        balance(String::from("the player's purse"), economy.purse());
+        for (area, market) in economy.markets() {
+            balance(format!("market {}", area), market.cash());
+        }
+        for firm in economy.firms() {
+            balance(format!("firm {}", firm.id), firm.cash);
+        }
+
+        for (area, market) in economy.markets() {
+            for (id, good) in catalog.iter() {
+                let stock = market.stock(id);
+                if is_negative(stock) {
+                    violations.push(Violation::BadStock {
+                        owner: format!("market {}", area),
+                        good: good.name.clone(),
+                        amount: stock,
+                    });
+                }
+                let price = market.price(id).amount();
+                if !(price.is_finite() && price > 0.0) {
+                    violations.push(Violation::BadPrice {
+                        area,
+                        good: good.name.clone(),
+                        price,
+                    });
+                }
+            }
+        }
+        for firm in economy.firms() {
+            if economy.market(firm.area_id).is_none() {
+                violations.push(Violation::FirmWithoutMarket {
+                    firm: firm.id,
+                    area: firm.area_id,
+                });
+            }
+            if economy.recipes().get(firm.recipe).is_none() {
+                violations.push(Violation::UnknownRecipe(firm.id));
+            }
+            for (good, amount) in firm.stock.iter() {
+                if is_negative(amount) {
+                    violations.push(Violation::BadStock {
+                        owner: format!("firm {}", firm.id),
+                        good: catalog
+                            .get(good)
+                            .map_or_else(|| format!("good {}", good.0), |g| g.name.clone()),
+                        amount,
+                    });
+                }
+            }
+        }
+    }
+
+    fn audit_characters(&self, violations: &mut Vec<Violation>) {
+        let catalog = self.economy().catalog();
+        for character in self.characters() {
+            if self.get_area(character.area_id).is_none() {
+                violations.push(Violation::HomelessCharacter {
+                    character: character.id,
+                    area: character.area_id,
+                });
+            }
+            for (good, amount) in character.inventory.iter() {
+                if is_negative(amount) {
+                    violations.push(Violation::BadStock {
+                        owner: character.name.clone(),
+                        good: catalog
+                            .get(good)
+                            .map_or_else(|| format!("good {}", good.0), |g| g.name.clone()),
+                        amount,
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::{FiscalPolicy, Money};
+    use crate::game::ships::Course;
+    use std::time::Duration;
+
+    #[test]
+    fn test_sample_world_is_sound() {
+        assert_eq!(WorldState::new().audit(), Vec::new());
+    }
+
+    #[test]
+    fn test_broken_worlds_are_caught() {
+        let mut world = WorldState::new();
+        world
+            .economy_mut()
+            .market_mut(1)
+            .unwrap()
+            .deposit(Money(5.0));
+        let grain = world.economy().catalog().id("grain").unwrap();
+        let market = world.economy_mut().market_mut(1).unwrap();
+        market.add(grain, -market.stock(grain) - 1.0);
+        world.remove_entity(ZoomLevel::Region, 1);
+
+        let violations = world.audit();
+        assert!(
+            violations
+                .iter()
+                .any(|v| matches!(v, Violation::MoneyNotConserved { .. }))
+        );
+        assert!(
+            violations
+                .iter()
+                .any(|v| matches!(v, Violation::BadStock { good, .. } if good == "grain"))
+        );
+        assert!(violations.iter().any(|v| matches!(
+            v,
+            Violation::MissingParent {
+                level: ZoomLevel::LocalArea,
+                parent: 1,
+                ..
+            }
+        )));
+    }
+
+    #[test]
+    fn test_overlapping_polities_and_lost_ships_are_caught() {
+        let mut world = WorldState::new();
+        let rival = world.found_polity("Rival", FiscalPolicy::NONE);
+        let polity = world.polity_mut(rival).unwrap();
+        polity.planets.insert(1);
+        polity.policy.tariff = -0.5;
+
+        let violations = world.audit();
+        assert!(violations.contains(&Violation::OverlappingClaims {
+            region: 1,
+            first: 301,
+            second: rival,
+        }));
+        assert!(violations.contains(&Violation::BadPolicy {
+            polity: rival,
+            tax_rate: 0.0,
+            tariff: -0.5,
+            sales_tax: 0.0,
+            spending: 0.0,
+        }));
+
+        let ship = world.launch_ship(1).unwrap();
+        world.ship_mut(ship).unwrap().course = Course::Docked {
+            planet: 99,
+            until: Duration::ZERO,
+        };
+        assert!(
+            world
+                .audit()
+                .contains(&Violation::LostShip { ship, planet: 99 })
+        );
+        world.ship_mut(ship).unwrap().course = Course::Docked {
+            planet: 1,
+            until: Duration::ZERO,
+        };
+
+        // Claiming through the world moves the border instead
+        world.polity_mut(rival).unwrap().planets.clear();
+        world.polity_mut(rival).unwrap().policy = FiscalPolicy::NONE;
+        assert!(world.claim(rival, ZoomLevel::Planet, 1));
+        assert_eq!(world.audit(), Vec::new());
+    }
+}
@@ -0,0 +1,106 @@
+//! Views the player has saved to jump back to.
+//!
+//! Each of the nine slots holds a zoom level and position, plus the name
+//! of the place at the time it was bookmarked so the list still reads
+//! sensibly if the place is later renamed or removed.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::zoom::{Position, ZoomLevel};
+
+/// Bookmark slots, numbered from 1
+pub const BOOKMARK_SLOTS: u8 = 9;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub level: ZoomLevel,
+    pub position: Position,
+    pub name: String,
+}
+
+/// Bookmarks by slot
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Bookmarks {
+    slots: BTreeMap<u8, Bookmark>,
+}
+
+impl Bookmarks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_slot(slot: u8) -> bool {
+        (1..=BOOKMARK_SLOTS).contains(&slot)
+    }
+
+    /// Store a bookmark, replacing whatever the slot held. Returns false
+    /// for a slot out of range.
+    pub fn set(&mut self, slot: u8, bookmark: Bookmark) -> bool {
+        if !Self::is_slot(slot) {
+            return false;
+        }
+        self.slots.insert(slot, bookmark);
+        true
+    }
+
+    pub fn get(&self, slot: u8) -> Option<&Bookmark> {
+        self.slots.get(&slot)
+    }
+
+    pub fn remove(&mut self, slot: u8) -> Option<Bookmark> {
+        self.slots.remove(&slot)
+    }
+
+    /// Filled slots in order
+    pub fn iter(&self) -> impl Iterator<Item = (u8, &Bookmark)> {
+        self.slots.iter().map(|(&slot, bookmark)| (slot, bookmark))
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bookmark(name: &str) -> Bookmark {
+        Bookmark {
+            level: ZoomLevel::Planet,
+            position: Position::new(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_slots_are_one_to_nine() {
+        let mut bookmarks = Bookmarks::new();
+        assert!(!bookmarks.set(0, bookmark("Nowhere")));
+        assert!(!bookmarks.set(10, bookmark("Nowhere")));
+        assert!(bookmarks.set(9, bookmark("Terra")));
+        assert!(bookmarks.set(1, bookmark("Mars")));
+        assert!(bookmarks.set(9, bookmark("Luna")));
+
+        let names: Vec<(u8, &str)> = bookmarks
+            .iter()
+            .map(|(slot, b)| (slot, b.name.as_str()))
+            .collect();
+        assert_eq!(names, vec![(1, "Mars"), (9, "Luna")]);
+    }
+
+    #[test]
+    fn test_round_trips_through_json() {
+        let mut bookmarks = Bookmarks::new();
+        bookmarks.set(3, bookmark("Terra"));
+        let json = serde_json::to_string(&bookmarks).unwrap();
+        let loaded: Bookmarks = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.get(3).unwrap().name, "Terra");
+        assert_eq!(loaded.get(3).unwrap().level, ZoomLevel::Planet);
+    }
+}
@@ -0,0 +1,354 @@
+//! Small worlds put together step by step.
+//!
+//! Tests and the editor describe exactly the world they need, such as two
+//! areas with markets and a few characters, instead of starting from the
+//! sample world and picking it apart. Each place goes inside the place
+//! added most recently one level up, and later steps refer to places,
+//! firms and goods by name. The first step that fails is reported by
+//! [`WorldStateBuilder::build`].
+
+use thiserror::Error as ThisError;
+
+use crate::economy::{Firm, Holiday, TradingHours};
+use crate::zoom::ZoomLevel;
+
+use super::migration::RESIDENTS_PER_BUILDING;
+use super::state::{EntityId, Placement, WorldState};
+
+#[derive(ThisError, Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    #[error("'{0}' has no {1} to go in")]
+    NoParent(String, ZoomLevel),
+    #[error("no {1} named '{0}'")]
+    NotFound(String, ZoomLevel),
+    #[error("no recipe named '{0}'")]
+    UnknownRecipe(String),
+    #[error("no good named '{0}'")]
+    UnknownGood(String),
+    #[error("no firm named '{0}' in the area")]
+    UnknownFirm(String),
+}
+
+/// Builds a [`WorldState`] from an empty galaxy
+#[derive(Debug)]
+pub struct WorldStateBuilder {
+    world: WorldState,
+    /// Most recently added system, planet, region and area
+    latest: [Option<EntityId>; 4],
+    error: Option<BuildError>,
+}
+
+impl Default for WorldStateBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WorldStateBuilder {
+    pub fn new() -> Self {
+        Self {
+            world: WorldState::empty(),
+            latest: [None; 4],
+            error: None,
+        }
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.world.reseed(seed);
+        self
+    }
+
+    pub fn system(self, name: &str) -> Self {
+        self.place(ZoomLevel::SolarSystem, name, |world, id| {
+            if let Some(system) = world.get_system_mut(id) {
+                system.planet_count = 0;
+            }
+        })
+    }
+
+    /// A planet in the latest system
+    pub fn planet(self, name: &str, population: u64) -> Self {
+        self.place(ZoomLevel::Planet, name, |world, id| {
+            world.set_population(ZoomLevel::Planet, id, population);
+            if let Some(system) = world
+                .placement_of(ZoomLevel::Planet, id)
+                .and_then(|p| p.parent)
+                && let Some(system) = world.get_system_mut(system)
+            {
+                system.planet_count += 1;
+            }
+        })
+    }
+
+    /// A region on the latest planet
+    pub fn region(self, name: &str, terrain: &str) -> Self {
+        self.place(ZoomLevel::Region, name, |world, id| {
+            world.set_terrain(id, terrain);
+        })
+    }
+
+    /// A local area in the latest region, with just enough buildings to
+    /// house its workforce
+    pub fn area(self, name: &str, workforce: u32) -> Self {
+        self.place(ZoomLevel::LocalArea, name, |world, id| {
+            world.set_population(ZoomLevel::LocalArea, id, workforce as u64);
+            if let Some(area) = world.get_area_mut(id) {
+                area.building_count = workforce.div_ceil(RESIDENTS_PER_BUILDING);
+            }
+        })
+    }
+
+    /// A room in the latest area
+    pub fn room(self, name: &str, kind: &str) -> Self {
+        self.place(ZoomLevel::Room, name, |world, id| {
+            if let Some(room) = world.get_room_mut(id) {
+                room.room_type = kind.to_string();
+            }
+        })
+    }
+
+    /// Open a named area's market with every good at its usual stock
+    pub fn market(self, area: &str) -> Self {
+        self.with_area(area, |world, id| {
+            world.economy_mut().open_market(id);
+            Ok(())
+        })
+    }
+
+    /// Set how much of a good a named area's market holds, opening the
+    /// market if needed
+    pub fn stock(self, area: &str, good: &str, quantity: f64) -> Self {
+        self.with_area(area, |world, id| {
+            let economy = world.economy_mut();
+            let good_id = economy
+                .catalog()
+                .id(good)
+                .ok_or_else(|| BuildError::UnknownGood(good.to_string()))?;
+            economy.open_market(id);
+            if let Some(market) = economy.market_mut(id) {
+                market.take(good_id, f64::MAX);
+                market.add(good_id, quantity.max(0.0));
+            }
+            Ok(())
+        })
+    }
+
+    /// Set a named area's trading hours, opening its market if needed
+    pub fn hours(self, area: &str, hours: TradingHours) -> Self {
+        self.with_area(area, |world, id| {
+            let economy = world.economy_mut();
+            economy.open_market(id);
+            if let Some(market) = economy.market_mut(id) {
+                market.set_hours(hours);
+            }
+            Ok(())
+        })
+    }
+
+    /// Found a firm running a recipe in a named area
+    pub fn firm(self, area: &str, name: &str, recipe: &str) -> Self {
+        self.with_area(area, |world, id| {
+            let recipe_id = world
+                .economy()
+                .recipes()
+                .find(recipe)
+                .ok_or_else(|| BuildError::UnknownRecipe(recipe.to_string()))?;
+            let coords = (world.economy().firms_in_area(id).count() as i32, 0);
+            let mut firm = Firm::new(world.next_id(), name, id, recipe_id);
+            firm.coords = coords;
+            world.economy_mut().add_firm(firm);
+            Ok(())
+        })
+    }
+
+    /// A character living in a named area, working for the named firm if
+    /// one is given
+    pub fn character(self, area: &str, name: &str, employer: Option<&str>) -> Self {
+        self.with_area(area, |world, id| {
+            let employer = match employer {
+                Some(firm) => Some(
+                    world
+                        .economy()
+                        .firms_in_area(id)
+                        .find(|f| f.name == firm)
+                        .map(|f| f.id)
+                        .ok_or_else(|| BuildError::UnknownFirm(firm.to_string()))?,
+                ),
+                None => None,
+            };
+            if let Some(character) = world
+                .add_character(name, id)
+                .and_then(|character| world.character_mut(character))
+            {
+                character.employer = employer;
+            }
+            Ok(())
+        })
+    }
+
+    /// Replace the default market holidays
+    pub fn holidays(mut self, holidays: Vec<Holiday>) -> Self {
+        self.world.set_holidays(holidays);
+        self
+    }
+
+    pub fn build(mut self) -> Result<WorldState, BuildError> {
+        match self.error {
+            Some(error) => Err(error),
+            None => {
+                // Places built here are part of the starting world, not arrivals
+                self.world.take_spawned();
+                self.world.refresh_census();
+                Ok(self.world)
+            }
+        }
+    }
+
+    /// Spawn an entity inside the latest one a level up, in a row to the
+    /// right of its siblings, then fill in its attributes
+    fn place(
+        mut self,
+        level: ZoomLevel,
+        name: &str,
+        fill: impl FnOnce(&mut WorldState, EntityId),
+    ) -> Self {
+        if self.error.is_some() {
+            return self;
+        }
+        let parent = match level.zoom_out() {
+            Some(ZoomLevel::Galaxy) | None => None,
+            Some(up) => match self.latest(up) {
+                Some(id) => Some(id),
+                None => {
+                    self.error = Some(BuildError::NoParent(name.to_string(), up));
+                    return self;
+                }
+            },
+        };
+        let column = self.world.placed_in(level, parent).len() as i32;
+        let Some(id) = self
+            .world
+            .spawn_at(level, Placement::new(parent, (column, 0)))
+        else {
+            return self;
+        };
+        self.world.entities_mut().rename(level, id, name);
+        fill(&mut self.world, id);
+        if let Some(slot) = Self::slot(level) {
+            self.latest[slot] = Some(id);
+            // A new parent starts a fresh line of children
+            self.latest[slot + 1..].fill(None);
+        }
+        self
+    }
+
+    fn with_area(
+        mut self,
+        area: &str,
+        step: impl FnOnce(&mut WorldState, EntityId) -> Result<(), BuildError>,
+    ) -> Self {
+        if self.error.is_some() {
+            return self;
+        }
+        let result = match self.world.find_entity(area) {
+            Some((ZoomLevel::LocalArea, id)) => step(&mut self.world, id),
+            _ => Err(BuildError::NotFound(area.to_string(), ZoomLevel::LocalArea)),
+        };
+        self.error = result.err();
+        self
+    }
+
+    fn latest(&self, level: ZoomLevel) -> Option<EntityId> {
+        Self::slot(level).and_then(|slot| self.latest[slot])
+    }
+
+    fn slot(level: ZoomLevel) -> Option<usize> {
+        match level {
+            ZoomLevel::SolarSystem => Some(0),
+            ZoomLevel::Planet => Some(1),
+            ZoomLevel::Region => Some(2),
+            ZoomLevel::LocalArea => Some(3),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::TARGET_STOCK;
+
+    fn two_towns() -> WorldStateBuilder {
+        WorldState::builder()
+            .system("Kepler")
+            .planet("Verdant", 1_000)
+            .region("Lowlands", "Plains")
+            .area("Millbrook", 20)
+            .area("Stonegate", 10)
+            .market("Millbrook")
+            .firm("Stonegate", "Stonegate Mine", "ore mining")
+    }
+
+    #[test]
+    fn test_builds_exactly_what_was_asked_for() {
+        let mut world = two_towns()
+            .character("Stonegate", "Ada", Some("Stonegate Mine"))
+            .character("Millbrook", "Bram", None)
+            .build()
+            .unwrap();
+
+        assert_eq!(world.ids(ZoomLevel::SolarSystem).len(), 1);
+        assert_eq!(world.ids(ZoomLevel::LocalArea).len(), 2);
+        assert_eq!(world.economy().markets().count(), 2);
+        assert_eq!(world.economy().firm_count(), 1);
+        assert_eq!(world.get_system(1000).unwrap().planet_count, 1);
+
+        let (_, stonegate) = world.find_entity("Stonegate").unwrap();
+        let area = world.get_area(stonegate).unwrap();
+        assert_eq!(area.building_count, 5);
+        assert_eq!(world.population(ZoomLevel::LocalArea, stonegate), Some(10));
+        let placement = world.placement_of(ZoomLevel::LocalArea, stonegate).unwrap();
+        assert_eq!(placement.coords, (1, 0));
+
+        let ada = world.characters_in_area(stonegate).next().unwrap();
+        assert_eq!(ada.name, "Ada");
+        assert!(ada.employer.is_some());
+        assert!(world.take_spawned().is_empty());
+    }
+
+    #[test]
+    fn test_stock_sets_market_shelves() {
+        let world = two_towns()
+            .stock("Millbrook", "grain", 5.0)
+            .build()
+            .unwrap();
+        let (_, millbrook) = world.find_entity("Millbrook").unwrap();
+        let market = world.economy().market(millbrook).unwrap();
+        let catalog = world.economy().catalog();
+        assert_eq!(market.stock(catalog.id("grain").unwrap()), 5.0);
+        assert_eq!(market.stock(catalog.id("ore").unwrap()), TARGET_STOCK);
+    }
+
+    #[test]
+    fn test_reports_first_failed_step() {
+        assert_eq!(
+            WorldState::builder()
+                .planet("Drifter", 10)
+                .build()
+                .unwrap_err(),
+            BuildError::NoParent(String::from("Drifter"), ZoomLevel::SolarSystem)
+        );
+        assert_eq!(
+            two_towns()
+                .firm("Millbrook", "Mill", "weaving")
+                .stock("Atlantis", "grain", 1.0)
+                .build()
+                .unwrap_err(),
+            BuildError::UnknownRecipe(String::from("weaving"))
+        );
+        assert_eq!(
+            two_towns().market("Verdant").build().unwrap_err(),
+            BuildError::NotFound(String::from("Verdant"), ZoomLevel::LocalArea)
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//! Firms the player has founded.
+//!
+//! The player starts with a purse of cash and founds firms in local areas,
+//! putting some of it in as capital, either at once or by commissioning a
+//! building that opens as the firm when it is finished. From then on the
+//! firm runs like any other: it hires from the area's workforce up to the limit the player
+//! set, buys its inputs and sells what it makes on the area's market. Its
+//! profit is whatever cash it holds beyond the capital put in.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error as ThisError;
+
+use crate::economy::{Economy, Money};
+
+use super::state::EntityId;
+
+/// Cash the player starts a new world with
+pub const STARTING_PURSE: Money = Money(2_500.0);
+
+#[derive(ThisError, Debug, Clone, PartialEq)]
+pub enum BusinessError {
+    #[error("firms can only be founded in a local area")]
+    NotInArea,
+    #[error("no recipe named '{0}'")]
+    UnknownRecipe(String),
+    #[error("'{0}' needs a technology nobody governing here has discovered")]
+    NotResearched(String),
+    #[error("a firm needs at least one worker")]
+    NoWorkers,
+    #[error("capital of {capital} is more than the {purse} in your purse")]
+    NotEnoughCash { capital: Money, purse: Money },
+    #[error("capital of {capital} won't cover the {cost} of materials to build with")]
+    CannotBuild { capital: Money, cost: Money },
+}
+
+/// A firm the player owns and what they put into it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Holding {
+    pub firm: EntityId,
+    pub capital: Money,
+}
+
+impl Holding {
+    /// Cash the firm holds beyond its capital, or None once it has closed
+    pub fn profit(&self, economy: &Economy) -> Option<Money> {
+        economy.firm(self.firm).map(|firm| firm.cash - self.capital)
+    }
+}
+
+/// Every firm the player has founded, oldest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Business {
+    holdings: Vec<Holding>,
+}
+
+impl Business {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, firm: EntityId, capital: Money) {
+        self.holdings.push(Holding { firm, capital });
+    }
+
+    /// Give up a firm; it keeps running but no longer counts as the
+    /// player's
+    pub fn remove(&mut self, firm: EntityId) {
+        self.holdings.retain(|h| h.firm != firm);
+    }
+
+    pub fn holdings(&self) -> &[Holding] {
+        &self.holdings
+    }
+
+    pub fn owns(&self, firm: EntityId) -> bool {
+        self.holdings.iter().any(|h| h.firm == firm)
+    }
+
+    /// Profit across every firm still open
+    pub fn profit(&self, economy: &Economy) -> Money {
+        self.holdings
+            .iter()
+            .filter_map(|holding| holding.profit(economy))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::Firm;
+
+    #[test]
+    fn test_profit_is_cash_beyond_capital() {
+        let mut economy = Economy::new();
+        let baking = economy.recipes().find("baking").unwrap();
+        economy.endow_purse(STARTING_PURSE);
+        economy.invest(Firm::new(1, "My Bakery", 1, baking), Money(1_000.0));
+
+        let mut business = Business::new();
+        business.add(1, Money(1_000.0));
+        business.add(2, Money(500.0));
+        assert!(business.owns(1) && !business.owns(3));
+        assert_eq!(business.profit(&economy), Money::ZERO);
+
+        economy.firm_mut(1).unwrap().cash += Money(250.0);
+        assert_eq!(business.holdings()[0].profit(&economy), Some(Money(250.0)));
+        // Firm 2 never opened, so it counts for nothing
+        assert_eq!(business.holdings()[1].profit(&economy), None);
+        assert_eq!(business.profit(&economy), Money(250.0));
+    }
+}
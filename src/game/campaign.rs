@@ -0,0 +1,367 @@
+//! Chapter-by-chapter campaigns layered on scenarios.
+//!
+//! A campaign is data: a list of chapters, each with an introduction, a
+//! sequence of objectives and where to go next on success or failure.
+//! Objectives unlock one at a time; completing the last one finishes the
+//! chapter, while running out of days fails it.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::economy::Money;
+use crate::time::Calendar;
+
+use super::WorldState;
+
+/// Something about the world that can be checked each tick
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Condition {
+    /// At least this many firms operate anywhere
+    FirmsAtLeast { count: usize },
+    /// A named planet or area has at least this many people
+    PopulationAtLeast { target: String, population: u64 },
+    /// A named planet or area has fewer than this many people
+    PopulationBelow { target: String, population: u64 },
+    /// Money in circulation has reached an amount
+    MoneyAtLeast { amount: f64 },
+    /// The price level has fallen under a value
+    PriceLevelBelow { level: f64 },
+    /// The player's firms have made at least this much between them
+    ProfitAtLeast { amount: f64 },
+}
+
+impl Condition {
+    pub fn is_met(&self, world: &WorldState) -> bool {
+        let economy = world.economy();
+        match self {
+            Condition::FirmsAtLeast { count } => economy.firm_count() >= *count,
+            Condition::PopulationAtLeast { target, population } => {
+                population_of(world, target).is_some_and(|p| p >= *population)
+            }
+            Condition::PopulationBelow { target, population } => {
+                population_of(world, target).is_some_and(|p| p < *population)
+            }
+            Condition::MoneyAtLeast { amount } => economy.money_supply().issued() >= Money(*amount),
+            Condition::PriceLevelBelow { level } => economy.money_supply().price_level() < *level,
+            Condition::ProfitAtLeast { amount } => {
+                world.business().profit(economy) >= Money(*amount)
+            }
+        }
+    }
+}
+
+fn population_of(world: &WorldState, target: &str) -> Option<u64> {
+    let (level, id) = world.find_entity(target)?;
+    world.population(level, id)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Objective {
+    pub description: String,
+    pub condition: Condition,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chapter {
+    pub id: String,
+    pub title: String,
+    /// Text shown on the interstitial screen as the chapter opens
+    pub intro: String,
+    pub objectives: Vec<Objective>,
+    /// Days allowed to finish every objective; unlimited when absent
+    #[serde(default)]
+    pub days: Option<u32>,
+    /// Chapter to continue with on success; the campaign is won when absent
+    #[serde(default)]
+    pub on_success: Option<String>,
+    /// Chapter to continue with on failure; the campaign is lost when absent
+    #[serde(default)]
+    pub on_failure: Option<String>,
+}
+
+/// A campaign as authored in a scenario file. The first chapter opens when
+/// the scenario starts.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Campaign {
+    pub chapters: Vec<Chapter>,
+}
+
+/// A text screen to show the player between chapters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interstitial {
+    pub title: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Outcome {
+    Won,
+    Lost,
+}
+
+/// Where a world is in its campaign
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampaignProgress {
+    campaign: Campaign,
+    chapter: Option<usize>,
+    /// Objectives of the current chapter completed so far
+    completed: usize,
+    chapter_started: Duration,
+    outcome: Option<Outcome>,
+    /// Screens waiting to be shown, oldest first
+    #[serde(skip)]
+    interstitials: Vec<Interstitial>,
+}
+
+impl CampaignProgress {
+    /// Begin at the first chapter
+    pub fn start(campaign: Campaign, now: Duration) -> Self {
+        let mut progress = Self {
+            campaign,
+            chapter: None,
+            completed: 0,
+            chapter_started: now,
+            outcome: None,
+            interstitials: Vec::new(),
+        };
+        if progress.campaign.chapters.is_empty() {
+            progress.outcome = Some(Outcome::Won);
+        } else {
+            progress.enter(0, now);
+        }
+        progress
+    }
+
+    pub fn chapter(&self) -> Option<&Chapter> {
+        self.campaign.chapters.get(self.chapter?)
+    }
+
+    /// The objective currently being worked towards
+    pub fn objective(&self) -> Option<&Objective> {
+        self.chapter()?.objectives.get(self.completed)
+    }
+
+    /// When the current chapter started and when it fails if its
+    /// objectives are not met by then
+    pub fn deadline(&self) -> Option<(Duration, Duration)> {
+        let days = self.chapter()?.days?;
+        let start = self.chapter_started;
+        Some((start, start + Calendar::DEFAULT_DAY_LENGTH * days))
+    }
+
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+
+    /// Next screen to show, if any
+    pub fn take_interstitial(&mut self) -> Option<Interstitial> {
+        (!self.interstitials.is_empty()).then(|| self.interstitials.remove(0))
+    }
+
+    /// Check objectives and deadlines, returning messages for the event log
+    pub fn update(&mut self, world: &WorldState, now: Duration) -> Vec<String> {
+        let mut messages = Vec::new();
+        let Some(chapter) = self.chapter().cloned() else {
+            return messages;
+        };
+
+        while let Some(objective) = chapter.objectives.get(self.completed) {
+            if !objective.condition.is_met(world) {
+                break;
+            }
+            messages.push(format!("Objective complete: {}", objective.description));
+            self.completed += 1;
+        }
+
+        if self.completed >= chapter.objectives.len() {
+            messages.push(format!("Chapter complete: {}", chapter.title));
+            self.advance(chapter.on_success.as_deref(), Outcome::Won, now);
+        } else if let Some(days) = chapter.days
+            && now >= self.chapter_started + Calendar::DEFAULT_DAY_LENGTH * days
+        {
+            messages.push(format!("Chapter failed: {}", chapter.title));
+            self.advance(chapter.on_failure.as_deref(), Outcome::Lost, now);
+        }
+        messages
+    }
+
+    /// Move to the named chapter, or end the campaign with `ending` when
+    /// there is none
+    fn advance(&mut self, next: Option<&str>, ending: Outcome, now: Duration) {
+        let index = next.and_then(|id| self.campaign.chapters.iter().position(|c| c.id == id));
+        match index {
+            Some(index) => self.enter(index, now),
+            None => {
+                self.chapter = None;
+                self.outcome = Some(ending);
+                let title = match ending {
+                    Outcome::Won => "Campaign complete",
+                    Outcome::Lost => "Campaign lost",
+                };
+                self.interstitials.push(Interstitial {
+                    title: title.to_string(),
+                    text: String::from("The simulation carries on without objectives."),
+                });
+            }
+        }
+    }
+
+    fn enter(&mut self, index: usize, now: Duration) {
+        let chapter = &self.campaign.chapters[index];
+        self.interstitials.push(Interstitial {
+            title: chapter.title.clone(),
+            text: chapter.intro.clone(),
+        });
+        self.chapter = Some(index);
+        self.completed = 0;
+        self.chapter_started = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zoom::ZoomLevel;
+
+    fn chapter(id: &str, condition: Condition) -> Chapter {
+        Chapter {
+            id: id.to_string(),
+            title: format!("Chapter {}", id),
+            intro: format!("Intro {}", id),
+            objectives: vec![Objective {
+                description: format!("Goal {}", id),
+                condition,
+            }],
+            days: None,
+            on_success: None,
+            on_failure: None,
+        }
+    }
+
+    #[test]
+    fn test_objectives_unlock_in_order() {
+        let world = WorldState::new();
+        let firms = world.economy().firm_count();
+        let mut first = chapter("one", Condition::FirmsAtLeast { count: firms });
+        first.objectives.push(Objective {
+            description: String::from("Grow"),
+            condition: Condition::FirmsAtLeast { count: firms + 1 },
+        });
+        let mut progress = CampaignProgress::start(
+            Campaign {
+                chapters: vec![first],
+            },
+            Duration::ZERO,
+        );
+        assert_eq!(
+            progress.take_interstitial().map(|i| i.title),
+            Some(String::from("Chapter one"))
+        );
+
+        let messages = progress.update(&world, Duration::ZERO);
+        assert_eq!(messages, vec!["Objective complete: Goal one"]);
+        assert_eq!(progress.objective().unwrap().description, "Grow");
+        assert_eq!(progress.outcome(), None);
+    }
+
+    #[test]
+    fn test_profit_counts_only_player_firms() {
+        let mut world = WorldState::new();
+        let goal = Condition::ProfitAtLeast { amount: 100.0 };
+        assert!(!goal.is_met(&world));
+
+        let id = world
+            .found_business(1, "Corner Bakery", "baking", 3, Money(500.0))
+            .unwrap();
+        world.economy_mut().firm_mut(id).unwrap().cash += Money(100.0);
+        assert!(goal.is_met(&world));
+    }
+
+    #[test]
+    fn test_success_branches_to_next_chapter() {
+        let world = WorldState::new();
+        let mut first = chapter("one", Condition::FirmsAtLeast { count: 0 });
+        first.on_success = Some(String::from("two"));
+        let second = chapter("two", Condition::FirmsAtLeast { count: 1_000 });
+        let mut progress = CampaignProgress::start(
+            Campaign {
+                chapters: vec![first, second],
+            },
+            Duration::ZERO,
+        );
+        progress.take_interstitial();
+
+        progress.update(&world, Duration::ZERO);
+        assert_eq!(progress.chapter().unwrap().id, "two");
+        assert_eq!(
+            progress.take_interstitial(),
+            Some(Interstitial {
+                title: String::from("Chapter two"),
+                text: String::from("Intro two"),
+            })
+        );
+    }
+
+    #[test]
+    fn test_deadline_takes_failure_branch() {
+        let world = WorldState::new();
+        let mut first = chapter("one", Condition::FirmsAtLeast { count: 1_000 });
+        first.days = Some(2);
+        first.on_failure = Some(String::from("retry"));
+        let retry = chapter("retry", Condition::FirmsAtLeast { count: 1_000 });
+        let mut progress = CampaignProgress::start(
+            Campaign {
+                chapters: vec![first, retry],
+            },
+            Duration::ZERO,
+        );
+        let day = Calendar::DEFAULT_DAY_LENGTH;
+        assert_eq!(progress.deadline(), Some((Duration::ZERO, day * 2)));
+
+        assert!(progress.update(&world, day).is_empty());
+        assert_eq!(
+            progress.update(&world, day * 2),
+            vec!["Chapter failed: Chapter one"]
+        );
+        assert_eq!(progress.chapter().unwrap().id, "retry");
+        assert_eq!(progress.deadline(), None);
+    }
+
+    #[test]
+    fn test_last_chapter_ends_campaign() {
+        let world = WorldState::new();
+        let mut progress = CampaignProgress::start(
+            Campaign {
+                chapters: vec![chapter("one", Condition::PriceLevelBelow { level: 2.0 })],
+            },
+            Duration::ZERO,
+        );
+
+        progress.update(&world, Duration::ZERO);
+        assert_eq!(progress.outcome(), Some(Outcome::Won));
+        assert!(progress.chapter().is_none());
+    }
+
+    #[test]
+    fn test_population_conditions() {
+        let world = WorldState::new();
+        let terra = world.population(ZoomLevel::Planet, 1).unwrap();
+        let above = Condition::PopulationAtLeast {
+            target: String::from("Terra"),
+            population: terra,
+        };
+        let below = Condition::PopulationBelow {
+            target: String::from("Terra"),
+            population: terra,
+        };
+        let missing = Condition::PopulationBelow {
+            target: String::from("Atlantis"),
+            population: u64::MAX,
+        };
+
+        assert!(above.is_met(&world));
+        assert!(!below.is_met(&world));
+        assert!(!missing.is_met(&world));
+    }
+}
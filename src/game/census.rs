@@ -0,0 +1,197 @@
+//! Totals for the galaxy and each place in it.
+//!
+//! A census is taken at the end of every tick and kept on the world, so the
+//! census screen and anything else that asks read the same figures without
+//! walking every market and firm again. Populations follow the same split
+//! as the daily stats: planets and everything above them count residents,
+//! while regions and areas count workers.
+
+use std::collections::BTreeMap;
+
+use crate::economy::{GoodId, Money};
+use crate::zoom::ZoomLevel;
+
+use super::state::{EntityId, WorldState};
+use super::stats::GALAXY_ID;
+
+/// One place's figures at the latest tick
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Figures {
+    pub population: u64,
+    /// Cash held by the place's households and firms
+    pub wealth: Money,
+    /// Units of every good made during the tick
+    pub produced: f64,
+    pub markets: u32,
+    /// Mean price relative to base value across the place's markets, or
+    /// None where there are none
+    pub price_index: Option<f64>,
+}
+
+/// Figures for every place, taken at one tick
+#[derive(Debug, Clone, Default)]
+pub struct Census {
+    tick: u64,
+    places: BTreeMap<(ZoomLevel, EntityId), Figures>,
+    produced: BTreeMap<GoodId, f64>,
+    prices: BTreeMap<GoodId, Money>,
+}
+
+impl Census {
+    pub fn take(world: &WorldState) -> Self {
+        let economy = world.economy();
+        let mut census = Census {
+            tick: world.tick_count(),
+            ..Census::default()
+        };
+        let mut price_totals: BTreeMap<(ZoomLevel, EntityId), f64> = BTreeMap::new();
+        let mut good_prices: BTreeMap<GoodId, Money> = BTreeMap::new();
+
+        for (area_id, market) in economy.markets() {
+            let wealth = market.cash()
+                + economy
+                    .firms_in_area(area_id)
+                    .map(|firm| firm.cash)
+                    .sum::<Money>();
+            let mut produced = 0.0;
+            for firm in economy.firms_in_area(area_id) {
+                let Some(recipe) = economy.recipes().get(firm.recipe) else {
+                    continue;
+                };
+                for &(good, quantity) in &recipe.outputs {
+                    let made = quantity * firm.runs;
+                    produced += made;
+                    *census.produced.entry(good).or_default() += made;
+                }
+            }
+            let price_index = market.price_index(economy.catalog());
+            for scope in world.ancestry(ZoomLevel::LocalArea, area_id) {
+                let figures = census.places.entry(scope).or_default();
+                figures.wealth += wealth;
+                figures.produced += produced;
+                figures.markets += 1;
+                *price_totals.entry(scope).or_default() += price_index;
+            }
+
+            for (good, _) in economy.catalog().iter() {
+                *good_prices.entry(good).or_default() += market.price(good);
+            }
+        }
+
+        for id in world.ids(ZoomLevel::LocalArea) {
+            let workers = world.population(ZoomLevel::LocalArea, id).unwrap_or(0);
+            for scope in world.ancestry(ZoomLevel::LocalArea, id) {
+                if scope.0 <= ZoomLevel::Region {
+                    census.places.entry(scope).or_default().population += workers;
+                }
+            }
+        }
+        for id in world.ids(ZoomLevel::Planet) {
+            let residents = world.population(ZoomLevel::Planet, id).unwrap_or(0);
+            for scope in world.ancestry(ZoomLevel::Planet, id) {
+                census.places.entry(scope).or_default().population += residents;
+            }
+        }
+
+        for (scope, total) in price_totals {
+            if let Some(figures) = census.places.get_mut(&scope) {
+                figures.price_index = Some(total / figures.markets as f64);
+            }
+        }
+        let markets = economy.markets().count() as f64;
+        census.prices = good_prices
+            .into_iter()
+            .map(|(good, total)| (good, total / markets))
+            .collect();
+        census
+    }
+
+    /// The tick the census was taken at
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Figures for the whole galaxy
+    pub fn totals(&self) -> Figures {
+        self.figures(ZoomLevel::Galaxy, GALAXY_ID)
+            .unwrap_or_default()
+    }
+
+    /// Figures for one place, if it has any markets or people
+    pub fn figures(&self, level: ZoomLevel, id: EntityId) -> Option<Figures> {
+        self.places.get(&(level, id)).copied()
+    }
+
+    /// Units of a good made across the galaxy during the tick
+    pub fn produced(&self, good: GoodId) -> f64 {
+        self.produced.get(&good).copied().unwrap_or(0.0)
+    }
+
+    /// A good's price averaged over every market, or None with no markets
+    pub fn average_price(&self, good: GoodId) -> Option<Money> {
+        self.prices.get(&good).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn world() -> WorldState {
+        WorldState::builder()
+            .system("Kepler")
+            .planet("Verdant", 5_000)
+            .region("Lowlands", "Plains")
+            .area("Millbrook", 20)
+            .area("Stonegate", 10)
+            .firm("Stonegate", "Stonegate Mine", "ore mining")
+            .market("Millbrook")
+            .system("Vega")
+            .planet("Plenty", 3_000)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_figures_roll_up_to_the_galaxy() {
+        let world = world();
+        let census = Census::take(&world);
+        let (_, kepler) = world.find_entity("Kepler").unwrap();
+        let (_, lowlands) = world.find_entity("Lowlands").unwrap();
+
+        let totals = census.totals();
+        assert_eq!(totals.population, 8_000);
+        assert_eq!(totals.markets, 2);
+        assert_eq!(totals.wealth, world.economy().total_balances());
+        assert_eq!(totals.price_index, Some(1.0));
+
+        assert_eq!(
+            census
+                .figures(ZoomLevel::SolarSystem, kepler)
+                .unwrap()
+                .population,
+            5_000
+        );
+        assert_eq!(
+            census
+                .figures(ZoomLevel::Region, lowlands)
+                .unwrap()
+                .population,
+            30
+        );
+    }
+
+    #[test]
+    fn test_counts_goods_made_in_the_latest_tick() {
+        let mut world = world();
+        let ore = world.economy().catalog().id("ore").unwrap();
+        world.update(Duration::from_secs(3600));
+        let census = world.census();
+
+        assert_eq!(census.tick(), 1);
+        assert!(census.produced(ore) > 0.0);
+        assert_eq!(census.totals().produced, census.produced(ore));
+        assert!(census.average_price(ore).is_some());
+    }
+}
@@ -0,0 +1,484 @@
+//! People who live in local areas and go about their day.
+//!
+//! Each character has two needs that drift as time passes and picks
+//! whichever activity scores highest against them: eating when hungry,
+//! sleeping when tired, trading when out of food, and otherwise working.
+//! Work is paid in kind with the employer's goods, which are later traded
+//! at the area's market for food.
+//!
+//! Working happens at the employer's building and trading in the market
+//! square, so characters walk there first, finding their way around the
+//! area's buildings with a [`Grid`]. Eating and sleeping happen wherever
+//! they are.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::economy::{Capacity, Economy, GoodCategory, GoodId, Inventory};
+
+use super::pathfinding::Grid;
+use super::state::EntityId;
+
+/// Where characters go to trade, clear of the buildings along the top of
+/// the area's map
+pub const MARKET_SQUARE: (i32, i32) = (0, 3);
+
+/// Hunger gained per hour awake
+const HUNGER_PER_HOUR: f64 = 0.04;
+/// Energy spent per hour awake, and extra spent while working
+const FATIGUE_PER_HOUR: f64 = 0.03;
+/// Energy regained per hour asleep
+const REST_PER_HOUR: f64 = 0.12;
+/// Food eaten per hour of eating, and the hunger each unit satisfies
+const FOOD_PER_MEAL_HOUR: f64 = 1.0;
+const HUNGER_PER_FOOD: f64 = 0.5;
+/// Units of the employer's output paid per hour of work
+const WAGE_PER_HOUR: f64 = 0.5;
+/// Longest span spent on one activity before choosing again
+const MAX_STEP_HOURS: f64 = 1.0;
+/// Bonus for carrying on with the current activity, so characters finish
+/// what they start instead of switching back and forth
+const INERTIA: f64 = 0.1;
+/// Tiles walked per hour
+const WALK_TILES_PER_HOUR: f64 = 12.0;
+
+/// What a character can carry
+pub const CARRY: Capacity = Capacity {
+    mass: Some(40.0),
+    volume: Some(60.0),
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Activity {
+    Work,
+    Eat,
+    Sleep,
+    Trade,
+}
+
+impl Activity {
+    pub const ALL: [Activity; 4] = [
+        Activity::Work,
+        Activity::Eat,
+        Activity::Sleep,
+        Activity::Trade,
+    ];
+}
+
+impl fmt::Display for Activity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Activity::Work => "working",
+            Activity::Eat => "eating",
+            Activity::Sleep => "sleeping",
+            Activity::Trade => "trading",
+        })
+    }
+}
+
+/// Needs from 0 (satisfied) to 1 (desperate)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Needs {
+    pub hunger: f64,
+    pub fatigue: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Character {
+    pub id: EntityId,
+    pub name: String,
+    /// Local area the character lives and trades in
+    pub area_id: EntityId,
+    /// Room the character spends time in, if any
+    pub room_id: Option<EntityId>,
+    /// Firm that pays the character for work
+    pub employer: Option<EntityId>,
+    pub needs: Needs,
+    pub inventory: Inventory,
+    pub activity: Activity,
+    /// Where the character stands on the area's map
+    #[serde(default)]
+    pub coords: (i32, i32),
+    /// Tiles still to walk to get where the activity happens
+    #[serde(default)]
+    pub path: Vec<(i32, i32)>,
+    /// Part of the next tile already walked
+    #[serde(default)]
+    stride: f64,
+}
+
+impl Character {
+    pub fn new(id: EntityId, name: &str, area_id: EntityId) -> Self {
+        Self {
+            id,
+            name: name.to_string(),
+            area_id,
+            room_id: None,
+            employer: None,
+            needs: Needs {
+                hunger: 0.2,
+                fatigue: 0.2,
+            },
+            inventory: Inventory::new(CARRY),
+            activity: Activity::Work,
+            coords: (0, 0),
+            path: Vec::new(),
+            stride: 0.0,
+        }
+    }
+
+    /// Units of food carried
+    pub fn food(&self, economy: &Economy) -> f64 {
+        self.inventory
+            .iter()
+            .filter(|&(id, _)| is_food(economy, id))
+            .map(|(_, qty)| qty)
+            .sum()
+    }
+
+    /// How much the character wants to do each activity right now, given
+    /// whether the area's market is trading
+    pub fn utility(&self, activity: Activity, economy: &Economy, market_open: bool) -> f64 {
+        let has_food = self.food(economy) > 0.0;
+        let has_wares = self
+            .inventory
+            .iter()
+            .any(|(id, qty)| qty > 0.0 && !is_food(economy, id));
+        let score = match activity {
+            Activity::Eat if has_food => self.needs.hunger,
+            Activity::Eat => 0.0,
+            Activity::Sleep => self.needs.fatigue,
+            Activity::Trade if market_open && !has_food && has_wares => 0.2 + self.needs.hunger,
+            Activity::Trade => 0.0,
+            Activity::Work if self.employer.is_some() => 0.4,
+            Activity::Work => 0.1,
+        };
+        if activity == self.activity {
+            score + INERTIA
+        } else {
+            score
+        }
+    }
+
+    /// Where the character has to be to do an activity, if anywhere in
+    /// particular
+    pub fn destination(&self, activity: Activity, economy: &Economy) -> Option<(i32, i32)> {
+        match activity {
+            Activity::Work => Some(economy.firm(self.employer?)?.coords),
+            Activity::Trade => Some(MARKET_SQUARE),
+            Activity::Eat | Activity::Sleep => None,
+        }
+    }
+
+    /// Live through `hours`, choosing the most useful activity at least
+    /// once an hour and walking across the area's `grid` to do it. Trading
+    /// waits while the market is shut.
+    pub fn update(&mut self, hours: f64, economy: &mut Economy, market_open: bool, grid: &Grid) {
+        let mut remaining = hours;
+        while remaining > 0.0 {
+            let step = remaining.min(MAX_STEP_HOURS);
+            self.step(step, economy, market_open, grid);
+            remaining -= step;
+        }
+    }
+
+    /// Choose the most useful activity, then spend `hours` getting to it
+    /// and doing it
+    fn step(&mut self, hours: f64, economy: &mut Economy, market_open: bool, grid: &Grid) {
+        self.activity = Activity::ALL
+            .into_iter()
+            .max_by(|a, b| {
+                self.utility(*a, economy, market_open)
+                    .total_cmp(&self.utility(*b, economy, market_open))
+            })
+            .unwrap_or(Activity::Work);
+
+        match self.activity {
+            Activity::Sleep => {
+                self.needs.fatigue -= REST_PER_HOUR * hours;
+            }
+            activity => {
+                self.needs.fatigue += FATIGUE_PER_HOUR * hours;
+                self.needs.hunger += HUNGER_PER_HOUR * hours;
+                let destination = self.destination(activity, economy);
+                match (activity, self.walk(destination, hours, grid)) {
+                    (_, None) => {}
+                    (Activity::Work, Some(there)) => self.work(there, economy),
+                    (Activity::Eat, Some(there)) => self.eat(there, economy),
+                    (Activity::Trade, Some(_)) => self.trade(economy),
+                    (Activity::Sleep, Some(_)) => {}
+                }
+            }
+        }
+        self.needs.hunger = self.needs.hunger.clamp(0.0, 1.0);
+        self.needs.fatigue = self.needs.fatigue.clamp(0.0, 1.0);
+    }
+
+    /// Walk towards `destination` for up to `hours`, returning the hours
+    /// left once there, or None while still on the way. Anywhere that
+    /// can't be reached is given up on and the activity done on the spot.
+    fn walk(&mut self, destination: Option<(i32, i32)>, hours: f64, grid: &Grid) -> Option<f64> {
+        let Some(goal) = destination.filter(|&goal| goal != self.coords) else {
+            self.path.clear();
+            self.stride = 0.0;
+            return Some(hours);
+        };
+        let detour = self
+            .path
+            .first()
+            .is_some_and(|&next| next != goal && grid.is_blocked(next));
+        if self.path.last() != Some(&goal) || detour {
+            self.path = grid.find_path(self.coords, goal).unwrap_or_default();
+        }
+        if self.path.is_empty() {
+            self.stride = 0.0;
+            return Some(hours);
+        }
+
+        let mut tiles = self.stride + hours * WALK_TILES_PER_HOUR;
+        while tiles >= 1.0 && !self.path.is_empty() {
+            self.coords = self.path.remove(0);
+            tiles -= 1.0;
+        }
+        if self.path.is_empty() {
+            self.stride = 0.0;
+            Some((tiles / WALK_TILES_PER_HOUR).min(hours))
+        } else {
+            self.stride = tiles;
+            None
+        }
+    }
+
+    /// Take wages in the employer's output from the market's stock, as
+    /// much as the character can carry
+    fn work(&mut self, hours: f64, economy: &mut Economy) {
+        self.needs.fatigue += FATIGUE_PER_HOUR * hours;
+        let Some(good) = self.employer.and_then(|id| employer_output(economy, id)) else {
+            return;
+        };
+        let wage = (WAGE_PER_HOUR * hours).min(self.inventory.room_for(good, economy.catalog()));
+        let Some(market) = economy.market_mut(self.area_id) else {
+            return;
+        };
+        let paid = market.take(good, wage);
+        let catalog = economy.catalog();
+        self.inventory.add(good, paid, catalog);
+    }
+
+    fn eat(&mut self, hours: f64, economy: &Economy) {
+        let mut appetite = FOOD_PER_MEAL_HOUR * hours;
+        let foods: Vec<GoodId> = self
+            .inventory
+            .iter()
+            .map(|(id, _)| id)
+            .filter(|&id| is_food(economy, id))
+            .collect();
+        for food in foods {
+            if appetite <= 0.0 {
+                break;
+            }
+            let eaten = self.inventory.remove(food, appetite);
+            appetite -= eaten;
+            self.needs.hunger -= eaten * HUNGER_PER_FOOD;
+        }
+    }
+
+    /// Swap everything that isn't food for the cheapest food the market
+    /// has, at market prices
+    fn trade(&mut self, economy: &mut Economy) {
+        let Some(market) = economy.market(self.area_id) else {
+            return;
+        };
+        let Some(food) = economy
+            .catalog()
+            .iter()
+            .filter(|(_, good)| good.category == GoodCategory::Food)
+            .map(|(id, _)| id)
+            .filter(|id| market.stock(*id) > 0.0)
+            .min_by(|a, b| {
+                market
+                    .price(*a)
+                    .amount()
+                    .total_cmp(&market.price(*b).amount())
+            })
+        else {
+            return;
+        };
+
+        let wares: Vec<(GoodId, f64)> = self
+            .inventory
+            .iter()
+            .filter(|&(id, _)| id != food)
+            .collect();
+        let value: f64 = wares
+            .iter()
+            .map(|(id, qty)| market.price(*id).amount() * qty)
+            .sum();
+        let price = market.price(food).amount();
+        if value <= 0.0 || price <= 0.0 {
+            return;
+        }
+
+        // Only buy as much as can be carried once the wares are gone
+        let mut unladen = self.inventory.clone();
+        for &(id, qty) in &wares {
+            unladen.remove(id, qty);
+        }
+        let wanted = (value / price).min(unladen.room_for(food, economy.catalog()));
+        let Some(market) = economy.market_mut(self.area_id) else {
+            return;
+        };
+        let bought = market.sell(food, wanted);
+        // Only part with as much as the food obtained was worth
+        let share = bought * price / value;
+        for (id, qty) in wares {
+            let sold = self.inventory.remove(id, qty * share);
+            market.add(id, sold);
+        }
+        let carried = self.inventory.add(food, bought, economy.catalog());
+        if let Some(market) = economy.market_mut(self.area_id) {
+            // Wares that weren't all sold can leave too little room
+            market.add(food, bought - carried);
+        }
+    }
+}
+
+fn is_food(economy: &Economy, id: GoodId) -> bool {
+    economy
+        .catalog()
+        .get(id)
+        .is_some_and(|good| good.category == GoodCategory::Food)
+}
+
+fn employer_output(economy: &Economy, firm_id: EntityId) -> Option<GoodId> {
+    let firm = economy.firm(firm_id)?;
+    let recipe = economy.recipes().get(firm.recipe)?;
+    recipe.outputs.first().map(|(good, _)| *good)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::{Firm, Market};
+
+    fn setup() -> (Economy, Character) {
+        let mut economy = Economy::new();
+        economy.open_market(1);
+        let recipe = economy.recipes().find("ore mining").unwrap();
+        economy.add_firm(Firm::new(10, "Mine", 1, recipe));
+
+        let mut character = Character::new(20, "Ada", 1);
+        character.employer = Some(10);
+        (economy, character)
+    }
+
+    fn market(economy: &Economy) -> &Market {
+        economy.market(1).unwrap()
+    }
+
+    #[test]
+    fn test_rested_character_works_for_wages() {
+        let (mut economy, mut character) = setup();
+        let ore = economy.catalog().id("ore").unwrap();
+        let before = market(&economy).stock(ore);
+
+        character.update(1.0, &mut economy, true, &Grid::new());
+        assert_eq!(character.activity, Activity::Work);
+        assert_eq!(character.inventory.get(ore), WAGE_PER_HOUR);
+        assert_eq!(market(&economy).stock(ore), before - WAGE_PER_HOUR);
+    }
+
+    #[test]
+    fn test_hungry_character_trades_then_eats() {
+        let (mut economy, mut character) = setup();
+        let ore = economy.catalog().id("ore").unwrap();
+        character.inventory.add(ore, 8.0, economy.catalog());
+        character.needs.hunger = 0.9;
+        character.coords = MARKET_SQUARE;
+
+        character.update(0.1, &mut economy, true, &Grid::new());
+        assert_eq!(character.activity, Activity::Trade);
+        assert!(character.food(&economy) > 0.0);
+
+        character.update(1.0, &mut economy, true, &Grid::new());
+        assert_eq!(character.activity, Activity::Eat);
+        assert!(character.needs.hunger < 0.9);
+    }
+
+    #[test]
+    fn test_trade_waits_for_market_to_open() {
+        let (mut economy, mut character) = setup();
+        let ore = economy.catalog().id("ore").unwrap();
+        character.inventory.add(ore, 8.0, economy.catalog());
+        character.needs.hunger = 0.9;
+        character.coords = MARKET_SQUARE;
+
+        character.update(0.1, &mut economy, false, &Grid::new());
+        assert_ne!(character.activity, Activity::Trade);
+        assert_eq!(character.food(&economy), 0.0);
+
+        character.update(0.1, &mut economy, true, &Grid::new());
+        assert_eq!(character.activity, Activity::Trade);
+        assert!(character.food(&economy) > 0.0);
+    }
+
+    #[test]
+    fn test_characters_walk_around_buildings_to_work() {
+        let (mut economy, mut character) = setup();
+        let ore = economy.catalog().id("ore").unwrap();
+        let recipe = economy.recipes().find("ore mining").unwrap();
+        let mut pit = Firm::new(11, "Pit", 1, recipe);
+        pit.coords = (3, 0);
+        economy.add_firm(pit);
+        character.employer = Some(11);
+        let mut grid = Grid::new();
+        grid.block((0, 0));
+        grid.block((3, 0));
+        for y in -1..=1 {
+            grid.block((1, y));
+        }
+
+        // Three tiles round the wall out of seven is a quarter of an hour
+        character.update(0.25, &mut economy, true, &grid);
+        assert_eq!(character.activity, Activity::Work);
+        assert_eq!(character.path.len(), 4);
+        assert!(character.inventory.is_empty());
+        assert!(!grid.is_blocked(character.coords));
+
+        character.update(1.0, &mut economy, true, &grid);
+        assert_eq!(character.coords, (3, 0));
+        assert!(character.path.is_empty());
+        let paid = character.inventory.get(ore);
+        assert!((paid - WAGE_PER_HOUR * (1.0 - 4.0 / WALK_TILES_PER_HOUR)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_wages_stop_once_nothing_more_can_be_carried() {
+        let (mut economy, mut character) = setup();
+        let ore = economy.catalog().id("ore").unwrap();
+        // 7.5 ore weighs 37.5 kg, leaving room for half a unit more
+        character.inventory.add(ore, 7.5, economy.catalog());
+        let before = market(&economy).stock(ore);
+
+        character.update(1.0, &mut economy, false, &Grid::new());
+        character.update(1.0, &mut economy, false, &Grid::new());
+        assert_eq!(character.activity, Activity::Work);
+        assert_eq!(character.inventory.get(ore), 8.0);
+        assert_eq!(character.inventory.mass(economy.catalog()), 40.0);
+        assert_eq!(market(&economy).stock(ore), before - 0.5);
+    }
+
+    #[test]
+    fn test_tired_character_sleeps_until_rested() {
+        let (mut economy, mut character) = setup();
+        character.needs.fatigue = 0.95;
+
+        character.update(1.0, &mut economy, true, &Grid::new());
+        assert_eq!(character.activity, Activity::Sleep);
+        for _ in 0..6 {
+            character.update(1.0, &mut economy, true, &Grid::new());
+        }
+        assert!(character.needs.fatigue < 0.4);
+        assert_eq!(character.activity, Activity::Work);
+    }
+}
@@ -0,0 +1,261 @@
+//! Seasons and weather.
+//!
+//! The year turns through four [`Season`]s, each raising or lowering what
+//! farms, mines, lumber camps and fisheries turn out; over a whole year the
+//! seasons even out. On top of that each region has its own weather. Once
+//! a day a region with fair weather may be struck by a [`WeatherKind`]:
+//! droughts are likeliest in summer and last for weeks, storms come mostly
+//! in autumn and winter and blow over in days. Either cuts the output of
+//! the buildings it hits until it passes, so local prices swing with the
+//! weather.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+use crate::economy::Yields;
+use crate::time::{Calendar, CalendarDate};
+
+use super::rng::Rng;
+
+/// Time between the daily rolls for new weather
+pub const WEATHER_INTERVAL: Duration = Calendar::DEFAULT_DAY_LENGTH;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    /// The season a date falls in: spring from the third month, summer
+    /// from the sixth, autumn from the ninth and winter from the twelfth
+    pub fn of(date: CalendarDate) -> Self {
+        match date.month {
+            3..=5 => Season::Spring,
+            6..=8 => Season::Summer,
+            9..=11 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Season::Spring => "spring",
+            Season::Summer => "summer",
+            Season::Autumn => "autumn",
+            Season::Winter => "winter",
+        }
+    }
+
+    /// Output multipliers for the season; each averages 1 over the year
+    pub fn yields(self) -> Yields {
+        let (farming, mining, logging, fishing) = match self {
+            Season::Spring => (1.0, 1.0, 1.0, 1.1),
+            Season::Summer => (1.2, 1.05, 1.05, 1.1),
+            Season::Autumn => (1.1, 1.0, 1.0, 1.0),
+            Season::Winter => (0.7, 0.95, 0.95, 0.8),
+        };
+        Yields {
+            farming,
+            mining,
+            logging,
+            fishing,
+        }
+    }
+}
+
+impl fmt::Display for Season {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WeatherKind {
+    /// Parches fields and shrinks fishing grounds
+    Drought,
+    /// Floods mine shafts, fells work in the forests and keeps boats in
+    /// harbour
+    Storm,
+}
+
+impl WeatherKind {
+    pub const ALL: [WeatherKind; 2] = [WeatherKind::Drought, WeatherKind::Storm];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WeatherKind::Drought => "drought",
+            WeatherKind::Storm => "storm",
+        }
+    }
+
+    /// Chance of the weather striking a region on a day of the season
+    fn daily_chance(self, season: Season) -> f64 {
+        match (self, season) {
+            (WeatherKind::Drought, Season::Summer) => 0.02,
+            (WeatherKind::Drought, Season::Spring | Season::Autumn) => 0.004,
+            (WeatherKind::Drought, Season::Winter) => 0.0,
+            (WeatherKind::Storm, Season::Autumn) => 0.04,
+            (WeatherKind::Storm, Season::Winter) => 0.03,
+            (WeatherKind::Storm, Season::Spring) => 0.02,
+            (WeatherKind::Storm, Season::Summer) => 0.01,
+        }
+    }
+
+    /// Shortest and longest the weather lasts, in days
+    fn days(self) -> (u64, u64) {
+        match self {
+            WeatherKind::Drought => (10, 40),
+            WeatherKind::Storm => (1, 4),
+        }
+    }
+
+    /// Output multipliers while the weather lasts
+    pub fn yields(self) -> Yields {
+        match self {
+            WeatherKind::Drought => Yields {
+                farming: 0.3,
+                fishing: 0.8,
+                ..Yields::EVEN
+            },
+            WeatherKind::Storm => Yields {
+                farming: 0.8,
+                mining: 0.5,
+                logging: 0.6,
+                fishing: 0.2,
+            },
+        }
+    }
+}
+
+impl fmt::Display for WeatherKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Weather a region is having
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Weather {
+    pub kind: WeatherKind,
+    /// When it passes
+    pub until: Duration,
+}
+
+impl Weather {
+    /// Whole days left at `now`, counting a part day as one
+    pub fn days_left(&self, now: Duration) -> u64 {
+        let left = self.until.saturating_sub(now).as_secs_f64();
+        (left / Calendar::DEFAULT_DAY_LENGTH.as_secs_f64()).ceil() as u64
+    }
+}
+
+/// Roll for weather striking a region in fair weather on a day of
+/// `season`, with each kind's chance scaled by `frequency`, returning what
+/// strikes and for how many days
+pub fn roll(season: Season, frequency: f64, rng: &mut Rng) -> Option<(WeatherKind, u64)> {
+    let mut roll = rng.next_f64();
+    for kind in WeatherKind::ALL {
+        let chance = kind.daily_chance(season) * frequency;
+        if roll < chance {
+            let (shortest, longest) = kind.days();
+            return Some((kind, rng.range(shortest, longest + 1)));
+        }
+        roll -= chance;
+    }
+    None
+}
+
+/// Scale the land's yields by the season and any weather
+pub fn apply(season: Season, weather: Option<WeatherKind>, yields: Yields) -> Yields {
+    let mut scaled = yields;
+    for factor in std::iter::once(season.yields()).chain(weather.map(WeatherKind::yields)) {
+        scaled = Yields {
+            farming: scaled.farming * factor.farming,
+            mining: scaled.mining * factor.mining,
+            logging: scaled.logging * factor.logging,
+            fishing: scaled.fishing * factor.fishing,
+        };
+    }
+    scaled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(month: u32) -> CalendarDate {
+        CalendarDate {
+            year: 1,
+            month,
+            day: 1,
+        }
+    }
+
+    #[test]
+    fn test_seasons_even_out_over_the_year() {
+        assert_eq!(Season::of(date(1)), Season::Winter);
+        assert_eq!(Season::of(date(4)), Season::Spring);
+        assert_eq!(Season::of(date(8)), Season::Summer);
+        assert_eq!(Season::of(date(12)), Season::Winter);
+
+        let seasons = [
+            Season::Spring,
+            Season::Summer,
+            Season::Autumn,
+            Season::Winter,
+        ];
+        let mean =
+            |field: fn(Yields) -> f64| seasons.iter().map(|s| field(s.yields())).sum::<f64>() / 4.0;
+        assert!((mean(|y| y.farming) - 1.0).abs() < 1e-9);
+        assert!((mean(|y| y.mining) - 1.0).abs() < 1e-9);
+        assert!((mean(|y| y.fishing) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_weather_cuts_output() {
+        let land = Yields {
+            farming: 2.0,
+            ..Yields::EVEN
+        };
+        let summer = apply(Season::Summer, None, land);
+        assert!((summer.farming - 2.4).abs() < 1e-9);
+        let drought = apply(Season::Summer, Some(WeatherKind::Drought), land);
+        assert!((drought.farming - 0.72).abs() < 1e-9);
+        assert!((drought.mining - 1.05).abs() < 1e-9);
+
+        let weather = Weather {
+            kind: WeatherKind::Storm,
+            until: Calendar::DEFAULT_DAY_LENGTH * 3,
+        };
+        assert_eq!(weather.days_left(Calendar::DEFAULT_DAY_LENGTH / 2), 3);
+        assert_eq!(weather.days_left(Calendar::DEFAULT_DAY_LENGTH * 4), 0);
+    }
+
+    #[test]
+    fn test_droughts_strike_in_summer_not_winter() {
+        let strikes = |season| {
+            let mut rng = Rng::new(7);
+            (0..10_000)
+                .filter_map(|_| roll(season, 1.0, &mut rng))
+                .filter(|(kind, _)| *kind == WeatherKind::Drought)
+                .count()
+        };
+        assert!(strikes(Season::Summer) > 100);
+        assert_eq!(strikes(Season::Winter), 0);
+
+        let mut rng = Rng::new(7);
+        let storms: Vec<u64> = (0..10_000)
+            .filter_map(|_| roll(Season::Autumn, 1.0, &mut rng))
+            .filter(|(kind, _)| *kind == WeatherKind::Storm)
+            .map(|(_, days)| days)
+            .collect();
+        assert!(storms.iter().all(|days| (1..=4).contains(days)));
+
+        // A world without disasters never rolls any
+        assert!((0..10_000).all(|_| roll(Season::Autumn, 0.0, &mut rng).is_none()));
+    }
+}
@@ -0,0 +1,1288 @@
+//! Interpreter for the developer console.
+//!
+//! Each command takes whitespace-separated arguments and either returns a
+//! message for the event log or a [`CommandError`] describing what went wrong.
+//! [`complete`] finishes a half-typed command, place name, good or keyword,
+//! and [`History`] keeps what was entered between sessions.
+
+use crate::economy::{MAX_POLICY_RATE, Money, Order, Side};
+use crate::modding::Query;
+use crate::save::{SaveGame, compare, data_dir};
+use crate::time::DAYS_PER_YEAR;
+use crate::zoom::{Position, ZoomLevel};
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error as ThisError;
+
+use super::diff::{self, Moment, WorldDiff};
+use super::export;
+use super::schedule::{EventEffect, EventError, ScriptedEvent};
+use super::watches::{self, Aggregate, Watch};
+use super::{Simulation, WorldState};
+
+#[derive(ThisError, Debug, Clone, PartialEq, Eq)]
+pub enum CommandError {
+    #[error("unknown command '{0}' (try 'help')")]
+    UnknownCommand(String),
+    #[error("usage: {0}")]
+    Usage(&'static str),
+    #[error("no entity named '{0}'")]
+    EntityNotFound(String),
+    #[error("invalid value '{0}'")]
+    InvalidValue(String),
+    #[error("{0}")]
+    Event(#[from] EventError),
+    #[error("could not write '{0}'")]
+    Export(String),
+    #[error("{0}")]
+    Query(String),
+    #[error("could not read '{0}'")]
+    Read(String),
+}
+
+/// Longest jump `ff` accepts, in days
+const MAX_FAST_FORWARD_DAYS: f64 = DAYS_PER_YEAR as f64;
+
+/// Levels whose places are completed by name
+const PLACE_LEVELS: [ZoomLevel; 5] = [
+    ZoomLevel::SolarSystem,
+    ZoomLevel::Planet,
+    ZoomLevel::Region,
+    ZoomLevel::LocalArea,
+    ZoomLevel::Room,
+];
+
+/// Commands the console remembers, oldest dropped first
+pub const HISTORY_LENGTH: usize = 100;
+
+/// File in the data directory holding the console's history, one command
+/// a line
+const HISTORY_FILE: &str = "console_history.txt";
+
+pub type CommandResult = std::result::Result<String, CommandError>;
+
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub summary: &'static str,
+}
+
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "goto",
+        usage: "goto <entity name>",
+        summary: "Jump to a named system, planet, region, area or room",
+    },
+    CommandSpec {
+        name: "speed",
+        usage: "speed <multiplier>",
+        summary: "Set the simulation speed (0.1 to 50)",
+    },
+    CommandSpec {
+        name: "ff",
+        usage: "ff <days>",
+        summary: "Fast-forward a number of days, showing progress as it goes",
+    },
+    CommandSpec {
+        name: "spawn",
+        usage: "spawn <system|planet|region|area|room|ship>",
+        summary: "Create a new entity with random attributes, or a ship at the planet in view",
+    },
+    CommandSpec {
+        name: "seed",
+        usage: "seed <number>",
+        summary: "Reseed the world's random generator",
+    },
+    CommandSpec {
+        name: "money",
+        usage: "money [growth %/yr]",
+        summary: "Show the money supply or set its annual growth rate",
+    },
+    CommandSpec {
+        name: "bank",
+        usage: "bank [rate <percent>]",
+        summary: "Show the bank's lending or set the interest rate it follows",
+    },
+    CommandSpec {
+        name: "polity",
+        usage: "polity [tax|sales|tariff|spending <percent>]",
+        summary: "Show or set the taxes and spending of the polity governing the place in view",
+    },
+    CommandSpec {
+        name: "order",
+        usage: "order <buy|sell> <good> <units> <limit>",
+        summary: "Stand an order on the market for the firm under the cursor (0 units cancels)",
+    },
+    CommandSpec {
+        name: "detail",
+        usage: "detail <full|auto>",
+        summary: "Simulate everything in view at full detail, or only nearby places",
+    },
+    CommandSpec {
+        name: "shock",
+        usage: "shock <discovery <good> <units> <area>|collapse <days> <system>|crisis <percent>>",
+        summary: "Strike the economy with a resource discovery, hyperlane collapse or currency crisis",
+    },
+    CommandSpec {
+        name: "export",
+        usage: "export <file.csv|file.json>",
+        summary: "Write market prices as CSV, or markets, population and places as JSON",
+    },
+    CommandSpec {
+        name: "watch",
+        usage: "watch [avg|sum|min|max] <query> [on <place>] | watch every <ticks>",
+        summary: "Pin a query to the debug overlay (F3), worked out every few ticks, or list them",
+    },
+    CommandSpec {
+        name: "unwatch",
+        usage: "unwatch <number|all>",
+        summary: "Unpin a watch by its number in the list, or every one",
+    },
+    CommandSpec {
+        name: "diff",
+        usage: "diff <now|r<n>|s<n>|file.json> [now|r<n>|s<n>|file.json]",
+        summary: "Report what changed between a rewind point (r1 latest), a save (s1 newest) or now",
+    },
+    CommandSpec {
+        name: "help",
+        usage: "help [command]",
+        summary: "List available commands, or show how to use one",
+    },
+];
+
+pub fn execute(input: &str, simulation: &mut Simulation) -> CommandResult {
+    let mut parts = input.split_whitespace();
+    let Some(command) = parts.next() else {
+        return Err(CommandError::Usage("<command> [arguments]"));
+    };
+    let args: Vec<&str> = parts.collect();
+
+    match command.to_ascii_lowercase().as_str() {
+        "goto" => goto(&args, simulation),
+        "speed" => speed(&args, simulation),
+        "ff" => fast_forward(&args, simulation),
+        "spawn" => spawn(&args, simulation),
+        "seed" => seed(&args, simulation),
+        "money" => money(&args, simulation),
+        "bank" => bank(&args, simulation),
+        "polity" => polity(&args, simulation),
+        "order" => order(&args, simulation),
+        "detail" => detail(&args, simulation),
+        "shock" => shock(&args, simulation),
+        "export" => export(&args, simulation),
+        "watch" => watch(&args, simulation),
+        "unwatch" => unwatch(&args, simulation),
+        "diff" => diff(&args, simulation).map(|diff| diff.summary()),
+        "help" => help(&args),
+        other => Err(CommandError::UnknownCommand(other.to_string())),
+    }
+}
+
+fn usage(name: &str) -> &'static str {
+    COMMANDS
+        .iter()
+        .find(|c| c.name == name)
+        .map_or("", |c| c.usage)
+}
+
+fn help(args: &[&str]) -> CommandResult {
+    match args {
+        [] => Ok(COMMANDS
+            .iter()
+            .map(|c| c.name)
+            .collect::<Vec<_>>()
+            .join(", ")),
+        [name] => COMMANDS
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(name))
+            .map(|c| format!("{}: {}", c.usage, c.summary))
+            .ok_or_else(|| CommandError::UnknownCommand(name.to_string())),
+        _ => Err(CommandError::Usage(usage("help"))),
+    }
+}
+
+fn goto(args: &[&str], simulation: &mut Simulation) -> CommandResult {
+    if args.is_empty() {
+        return Err(CommandError::Usage(usage("goto")));
+    }
+
+    let name = args.join(" ");
+    let (level, name) = simulation
+        .goto(&name)
+        .ok_or(CommandError::EntityNotFound(name))?;
+    Ok(format!("Travelled to {} ({})", name, level))
+}
+
+fn speed(args: &[&str], simulation: &mut Simulation) -> CommandResult {
+    let [value] = args else {
+        return Err(CommandError::Usage(usage("speed")));
+    };
+    let multiplier: f64 = value
+        .trim_end_matches('x')
+        .parse()
+        .map_err(|_| CommandError::InvalidValue(value.to_string()))?;
+    if !multiplier.is_finite() || multiplier <= 0.0 {
+        return Err(CommandError::InvalidValue(value.to_string()));
+    }
+
+    let time = simulation.time_mut();
+    time.set_speed(multiplier);
+    Ok(format!("Speed set to {:.1}x", time.speed_multiplier()))
+}
+
+fn spawn(args: &[&str], simulation: &mut Simulation) -> CommandResult {
+    let [kind] = args else {
+        return Err(CommandError::Usage(usage("spawn")));
+    };
+    let level = match kind.to_ascii_lowercase().as_str() {
+        "ship" => return launch_ship(simulation),
+        "system" => ZoomLevel::SolarSystem,
+        "planet" => ZoomLevel::Planet,
+        "region" => ZoomLevel::Region,
+        "area" => ZoomLevel::LocalArea,
+        "room" => ZoomLevel::Room,
+        _ => return Err(CommandError::InvalidValue(kind.to_string())),
+    };
+
+    let world = simulation.world_mut();
+    let id = world
+        .spawn(level)
+        .ok_or_else(|| CommandError::InvalidValue(kind.to_string()))?;
+    let mut position = Position::new();
+    position.set_entity_id(level, Some(id));
+    Ok(format!("Spawned {}", world.location_name(&position, level)))
+}
+
+fn launch_ship(simulation: &mut Simulation) -> CommandResult {
+    let planet = simulation
+        .zoom()
+        .position()
+        .map_owner(ZoomLevel::Planet)
+        .ok_or_else(|| CommandError::InvalidValue(String::from("ship")))?;
+    let world = simulation.world_mut();
+    let ship = world
+        .launch_ship(planet)
+        .and_then(|id| world.ship(id))
+        .ok_or_else(|| CommandError::EntityNotFound(planet.to_string()))?;
+    Ok(format!(
+        "Launched {} at {}",
+        ship.name,
+        world.entity_name(ZoomLevel::Planet, planet)
+    ))
+}
+
+fn fast_forward(args: &[&str], simulation: &mut Simulation) -> CommandResult {
+    let [value] = args else {
+        return Err(CommandError::Usage(usage("ff")));
+    };
+    let days: f64 = value
+        .parse()
+        .map_err(|_| CommandError::InvalidValue(value.to_string()))?;
+    if !days.is_finite() || days <= 0.0 || days > MAX_FAST_FORWARD_DAYS {
+        return Err(CommandError::InvalidValue(value.to_string()));
+    }
+
+    let day = simulation.time().calendar().day_length();
+    simulation.fast_forward(day.mul_f64(days));
+    Ok(format!("Fast-forwarding {} day(s)", days))
+}
+
+fn seed(args: &[&str], simulation: &mut Simulation) -> CommandResult {
+    let [value] = args else {
+        return Err(CommandError::Usage(usage("seed")));
+    };
+    let seed: u64 = value
+        .parse()
+        .map_err(|_| CommandError::InvalidValue(value.to_string()))?;
+
+    simulation.world_mut().reseed(seed);
+    Ok(format!("World seed set to {}", seed))
+}
+
+fn money(args: &[&str], simulation: &mut Simulation) -> CommandResult {
+    match args {
+        [] => {}
+        [value] => {
+            let percent: f64 = value
+                .trim_end_matches('%')
+                .parse()
+                .map_err(|_| CommandError::InvalidValue(value.to_string()))?;
+            if !percent.is_finite() || percent <= -100.0 {
+                return Err(CommandError::InvalidValue(value.to_string()));
+            }
+            simulation
+                .world_mut()
+                .economy_mut()
+                .set_money_growth(percent / 100.0);
+        }
+        _ => return Err(CommandError::Usage(usage("money"))),
+    }
+
+    let economy = simulation.world().economy();
+    let supply = economy.money_supply();
+    Ok(format!(
+        "Money supply {} growing {:+.1}%/yr, price level {:.2}x",
+        economy.currency().format(supply.issued()),
+        supply.growth_rate() * 100.0,
+        supply.price_level()
+    ))
+}
+
+fn bank(args: &[&str], simulation: &mut Simulation) -> CommandResult {
+    match args {
+        [] => {}
+        [setting, value] if setting.eq_ignore_ascii_case("rate") => {
+            let percent: f64 = value
+                .trim_end_matches('%')
+                .parse()
+                .map_err(|_| CommandError::InvalidValue(value.to_string()))?;
+            if !(0.0..=MAX_POLICY_RATE * 100.0).contains(&percent) {
+                return Err(CommandError::InvalidValue(value.to_string()));
+            }
+            simulation
+                .world_mut()
+                .economy_mut()
+                .bank_mut()
+                .set_policy_rate(percent / 100.0);
+        }
+        _ => return Err(CommandError::Usage(usage("bank"))),
+    }
+
+    let economy = simulation.world().economy();
+    let bank = economy.bank();
+    let currency = economy.currency();
+    Ok(format!(
+        "Bank lends at {:.1}% and pays {:.1}% on {} of deposits; {} lent out, {} in reserve",
+        bank.lending_rate() * 100.0,
+        bank.deposit_rate() * 100.0,
+        currency.format(bank.deposits()),
+        currency.format(bank.loans()),
+        currency.format(bank.reserves())
+    ))
+}
+
+fn polity(args: &[&str], simulation: &mut Simulation) -> CommandResult {
+    let Some(id) = simulation.polity_in_view() else {
+        return Ok(String::from("No polity governs this place"));
+    };
+    match args {
+        [] => {}
+        [rate, value] => {
+            let share = value
+                .trim_end_matches('%')
+                .parse::<f64>()
+                .map_err(|_| CommandError::InvalidValue(value.to_string()))?
+                / 100.0;
+            let world = simulation.world_mut();
+            let mut policy = world
+                .polity(id)
+                .map(|polity| polity.policy)
+                .unwrap_or_default();
+            match rate.to_ascii_lowercase().as_str() {
+                "tax" => policy.tax_rate = share,
+                "sales" => policy.sales_tax = share,
+                "tariff" => policy.tariff = share,
+                "spending" => policy.spending = share,
+                _ => return Err(CommandError::InvalidValue(rate.to_string())),
+            }
+            if !world.set_fiscal_policy(id, policy) {
+                return Err(CommandError::InvalidValue(value.to_string()));
+            }
+        }
+        _ => return Err(CommandError::Usage(usage("polity"))),
+    }
+
+    let world = simulation.world();
+    let Some(polity) = world.polity(id) else {
+        return Err(CommandError::EntityNotFound(id.to_string()));
+    };
+    let currency = world.economy().currency();
+    Ok(format!(
+        "{}: {}, {} collected, {} spent, {} in the treasury",
+        polity.name,
+        polity.policy_summary(),
+        currency.format(polity.collected),
+        currency.format(polity.spent),
+        currency.format(world.economy().treasury(id))
+    ))
+}
+
+fn order(args: &[&str], simulation: &mut Simulation) -> CommandResult {
+    let [side, good, units, limit] = args else {
+        return Err(CommandError::Usage(usage("order")));
+    };
+    let side = match side.to_ascii_lowercase().as_str() {
+        "buy" => Side::Buy,
+        "sell" => Side::Sell,
+        _ => return Err(CommandError::InvalidValue(side.to_string())),
+    };
+    let number = |value: &str| {
+        value
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite() && *n >= 0.0)
+            .ok_or_else(|| CommandError::InvalidValue(value.to_string()))
+    };
+    let (quantity, limit) = (number(units)?, Money(number(limit)?));
+    let Some(firm) = simulation.firm_in_view() else {
+        return Ok(String::from("No firm under the cursor"));
+    };
+    let economy = simulation.world_mut().economy_mut();
+    let good = economy
+        .catalog()
+        .id(good)
+        .ok_or_else(|| CommandError::InvalidValue(good.to_string()))?;
+    let order = Order {
+        firm,
+        side,
+        good,
+        quantity,
+        limit,
+    };
+    if !economy.place_order(order) {
+        return Err(CommandError::EntityNotFound(firm.to_string()));
+    }
+
+    let name = economy.firm(firm).map_or("", |f| f.name.as_str());
+    let good = economy.catalog().get(good).map_or("", |g| g.name.as_str());
+    if order.is_filled() {
+        return Ok(format!("{} cancels its order to {} {}", name, side, good));
+    }
+    Ok(format!(
+        "{} will {} {} {} at {} or {}",
+        name,
+        side,
+        quantity,
+        good,
+        economy.currency().format(limit),
+        match side {
+            Side::Buy => "less",
+            Side::Sell => "more",
+        }
+    ))
+}
+
+fn detail(args: &[&str], simulation: &mut Simulation) -> CommandResult {
+    let [mode] = args else {
+        return Err(CommandError::Usage(usage("detail")));
+    };
+    let forced = match mode.to_ascii_lowercase().as_str() {
+        "full" => true,
+        "auto" => false,
+        _ => return Err(CommandError::InvalidValue(mode.to_string())),
+    };
+
+    simulation.force_full_detail(forced);
+    Ok(String::from(if forced {
+        "Simulating everything in view at full detail"
+    } else {
+        "Simulating distant places at reduced detail"
+    }))
+}
+
+fn shock(args: &[&str], simulation: &mut Simulation) -> CommandResult {
+    let number = |value: &str| {
+        value
+            .trim_end_matches('%')
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite() && *n >= 0.0)
+            .ok_or_else(|| CommandError::InvalidValue(value.to_string()))
+    };
+    let (effect, message) = match args {
+        ["discovery", good, units, area @ ..] if !area.is_empty() => {
+            let area = area.join(" ");
+            (
+                EventEffect::Discovery {
+                    area: area.clone(),
+                    good: good.to_string(),
+                    quantity: number(units)?,
+                },
+                format!("A rich seam of {} is found in {}", good, area),
+            )
+        }
+        ["collapse", days, system @ ..] if !system.is_empty() => {
+            let system = system.join(" ");
+            let days = number(days)?;
+            (
+                EventEffect::LaneCollapse {
+                    system: system.clone(),
+                    days,
+                },
+                format!("Hyperlanes to {} collapse for {} day(s)", system, days),
+            )
+        }
+        ["crisis", percent] => {
+            let percent = number(percent)?;
+            (
+                EventEffect::CurrencyCrisis {
+                    share: percent / 100.0,
+                },
+                format!(
+                    "Currency crisis: {}% more money floods the markets",
+                    percent
+                ),
+            )
+        }
+        _ => return Err(CommandError::Usage(usage("shock"))),
+    };
+
+    let event = ScriptedEvent {
+        date: Default::default(),
+        message,
+        effect,
+    };
+    event.apply(simulation.world_mut())?;
+    Ok(event.message)
+}
+
+fn export(args: &[&str], simulation: &mut Simulation) -> CommandResult {
+    let [file] = args else {
+        return Err(CommandError::Usage(usage("export")));
+    };
+    let path = export::export_path(file);
+    let format = export::ExportFormat::of(&path)
+        .ok_or_else(|| CommandError::InvalidValue(file.to_string()))?;
+
+    export::export(simulation, &path, format).map_err(|err| {
+        tracing::error!("Failed to export {}: {:?}", path.display(), err);
+        CommandError::Export(path.display().to_string())
+    })?;
+    Ok(format!("Exported to {}", path.display()))
+}
+
+fn watch(args: &[&str], simulation: &mut Simulation) -> CommandResult {
+    match args {
+        [] => {
+            let watches = simulation.watches();
+            if watches.is_empty() {
+                return Ok(String::from("No watches pinned"));
+            }
+            let list: Vec<String> = watches
+                .iter()
+                .enumerate()
+                .map(|(i, watch)| format!("{} {} = {}", i + 1, watch.label(), watch.reading()))
+                .collect();
+            Ok(format!(
+                "Every {} ticks: {}",
+                watches.interval(),
+                list.join("; ")
+            ))
+        }
+        [every, ticks] if every.eq_ignore_ascii_case("every") => {
+            let ticks = ticks
+                .parse::<u64>()
+                .ok()
+                .filter(|&ticks| ticks > 0)
+                .ok_or_else(|| CommandError::InvalidValue(ticks.to_string()))?;
+            simulation.watches_mut().set_interval(ticks);
+            Ok(format!("Watches are worked out every {} ticks", ticks))
+        }
+        [first, rest @ ..] => {
+            let (aggregate, words) = match Aggregate::parse(first) {
+                Some(aggregate) if !rest.is_empty() => (aggregate, rest),
+                _ => (Aggregate::Avg, args),
+            };
+            let (expression, place) = match words
+                .iter()
+                .rposition(|word| word.eq_ignore_ascii_case("on"))
+            {
+                Some(on) if on > 0 && on + 1 < words.len() => {
+                    let name = words[on + 1..].join(" ");
+                    let world = simulation.world();
+                    let (level, id) = world
+                        .find_entity(&name)
+                        .ok_or(CommandError::EntityNotFound(name))?;
+                    (&words[..on], ((level, id), world.entity_name(level, id)))
+                }
+                _ => (words, (watches::everywhere(), String::from("everywhere"))),
+            };
+            let query = Query::compile(&expression.join(" "))
+                .map_err(|error| CommandError::Query(error.to_string()))?;
+            let watch = Watch::new(aggregate, query, place.0, place.1);
+            let number = simulation.watches().len() + 1;
+            match simulation.add_watch(watch) {
+                Some(watch) => Ok(format!(
+                    "Watch {}: {} = {}",
+                    number,
+                    watch.label(),
+                    watch.reading()
+                )),
+                None => Err(CommandError::InvalidValue(format!(
+                    "more than {} watches",
+                    watches::MAX_WATCHES
+                ))),
+            }
+        }
+    }
+}
+
+fn unwatch(args: &[&str], simulation: &mut Simulation) -> CommandResult {
+    let watches = simulation.watches_mut();
+    match args {
+        [all] if all.eq_ignore_ascii_case("all") => {
+            let count = watches.len();
+            watches.clear();
+            Ok(format!("Unpinned {} watch(es)", count))
+        }
+        [number] => {
+            let watch = number
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_sub(1))
+                .and_then(|index| watches.remove(index))
+                .ok_or_else(|| CommandError::InvalidValue(number.to_string()))?;
+            Ok(format!("Unpinned {}", watch.label()))
+        }
+        _ => Err(CommandError::Usage(usage("unwatch"))),
+    }
+}
+
+/// Compare the world at two moments, the second one now unless given
+pub fn diff(args: &[&str], simulation: &Simulation) -> Result<WorldDiff, CommandError> {
+    let (from, to) = match args {
+        [from] => (*from, "now"),
+        [from, to] => (*from, *to),
+        _ => return Err(CommandError::Usage(usage("diff"))),
+    };
+    let (before, from) = world_at(from, simulation)?;
+    let (after, to) = world_at(to, simulation)?;
+    let now = simulation.world();
+    Ok(WorldDiff::between(
+        before.as_ref().unwrap_or(now),
+        after.as_ref().unwrap_or(now),
+        &from,
+        &to,
+    ))
+}
+
+/// The world at a moment named as [`Moment::parse`] reads it, or None for
+/// the world as it is, with a label naming the moment and its date
+fn world_at(
+    text: &str,
+    simulation: &Simulation,
+) -> Result<(Option<WorldState>, String), CommandError> {
+    let moment = Moment::parse(text).ok_or_else(|| CommandError::InvalidValue(text.to_string()))?;
+    let calendar = simulation.time().calendar();
+    let read = |path: &Path| {
+        let save = SaveGame::read_from(path)
+            .map_err(|_| CommandError::Read(path.display().to_string()))?;
+        let label = diff::save_label(path, &save, calendar);
+        Ok((Some(save.world), label))
+    };
+    match moment {
+        Moment::Now => Ok((None, format!("now ({})", simulation.time().current_date()))),
+        Moment::Rewind(n) => {
+            let snapshot = simulation
+                .snapshot(n - 1)
+                .ok_or_else(|| CommandError::InvalidValue(text.to_string()))?;
+            let world = snapshot
+                .restore()
+                .map_err(|_| CommandError::Read(text.to_string()))?;
+            let label = format!("{} ({})", text, calendar.date_at(snapshot.at));
+            Ok((Some(world), label))
+        }
+        Moment::Save(n) => {
+            let path = compare::recent_saves(&data_dir(), n)
+                .into_iter()
+                .nth(n - 1)
+                .ok_or_else(|| CommandError::InvalidValue(text.to_string()))?;
+            read(&path)
+        }
+        Moment::File(path) if path.is_absolute() => read(&path),
+        Moment::File(path) => read(&data_dir().join(path)),
+    }
+}
+
+/// A console line after pressing Tab
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// The line with its last word filled in as far as every match agrees
+    pub line: String,
+    /// Every match, when more than one is left to choose between
+    pub options: Vec<String>,
+}
+
+/// Complete the word being typed at the end of a console line: a command
+/// name first, then whatever that command takes next, from keywords to
+/// goods and the names of places. A single match is filled in whole.
+pub fn complete(line: &str, simulation: &Simulation) -> Completion {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let (done, partial) = match words.split_last() {
+        Some((last, done)) if !line.ends_with(char::is_whitespace) => (done, *last),
+        _ => (&words[..], ""),
+    };
+    let unchanged = || Completion {
+        line: line.to_string(),
+        options: Vec::new(),
+    };
+
+    // The argument being completed starts `at` words into the line, before
+    // the last word for the names of places, which can run to several
+    let (options, at) = match done {
+        [] => (command_names(), 0),
+        [command, args @ ..] => match argument_options(command, args, simulation) {
+            Some((options, at)) => (options, at + 1),
+            None => return unchanged(),
+        },
+    };
+    let words_before =
+        |words: &[&str]| -> String { words.iter().map(|word| format!("{} ", word)).collect() };
+    let typed = words_before(&done[at..]) + partial;
+
+    let mut matches: Vec<String> = options
+        .into_iter()
+        .filter(|option| starts_with_ignoring_case(option, &typed))
+        .collect();
+    matches.sort();
+    matches.dedup();
+    let head = words_before(&done[..at]);
+    match matches.as_slice() {
+        [] => unchanged(),
+        [only] => Completion {
+            line: format!("{}{} ", head, only),
+            options: Vec::new(),
+        },
+        [first, rest @ ..] => {
+            let shared = rest.iter().fold(first.chars().count(), |shared, option| {
+                first
+                    .chars()
+                    .zip(option.chars())
+                    .take_while(|(a, b)| a.eq_ignore_ascii_case(b))
+                    .count()
+                    .min(shared)
+            });
+            let prefix: String = first.chars().take(shared).collect();
+            Completion {
+                line: format!("{}{}", head, prefix),
+                options: matches,
+            }
+        }
+    }
+}
+
+fn command_names() -> Vec<String> {
+    COMMANDS.iter().map(|c| c.name.to_string()).collect()
+}
+
+fn starts_with_ignoring_case(text: &str, prefix: &str) -> bool {
+    text.to_lowercase().starts_with(&prefix.to_lowercase())
+}
+
+/// What a command takes after the arguments already typed, and which of
+/// them the one being typed starts at. None if it takes nothing more that
+/// can be completed.
+fn argument_options(
+    command: &str,
+    args: &[&str],
+    simulation: &Simulation,
+) -> Option<(Vec<String>, usize)> {
+    let words = |words: &[&str]| words.iter().map(|w| w.to_string()).collect();
+    let next = args.len();
+    if command.eq_ignore_ascii_case("watch")
+        && let Some(on) = args
+            .iter()
+            .rposition(|word| word.eq_ignore_ascii_case("on"))
+    {
+        return Some((place_names(simulation, None), on + 1));
+    }
+    Some(match (command.to_ascii_lowercase().as_str(), args) {
+        ("goto", _) => (place_names(simulation, None), 0),
+        ("help", []) => (command_names(), next),
+        ("spawn", []) => (
+            words(&["system", "planet", "region", "area", "room", "ship"]),
+            next,
+        ),
+        ("detail", []) => (words(&["full", "auto"]), next),
+        ("bank", []) => (words(&["rate"]), next),
+        ("polity", []) => (words(&["tax", "sales", "tariff", "spending"]), next),
+        ("order", []) => (words(&["buy", "sell"]), next),
+        ("order", [_]) => (good_names(simulation), next),
+        ("watch", []) => (words(&["avg", "sum", "min", "max", "every"]), next),
+        ("unwatch", []) => (words(&["all"]), next),
+        ("diff", [] | [_]) => (words(&["now", "r1", "s1"]), next),
+        ("shock", []) => (words(&["discovery", "collapse", "crisis"]), next),
+        ("shock", ["discovery"]) => (good_names(simulation), next),
+        ("shock", ["discovery", _, _, ..]) => {
+            (place_names(simulation, Some(ZoomLevel::LocalArea)), 3)
+        }
+        ("shock", ["collapse", _, ..]) => {
+            (place_names(simulation, Some(ZoomLevel::SolarSystem)), 2)
+        }
+        _ => return None,
+    })
+}
+
+/// Names of every place below the galaxy, or only those at `level`
+fn place_names(simulation: &Simulation, level: Option<ZoomLevel>) -> Vec<String> {
+    let world = simulation.world();
+    PLACE_LEVELS
+        .into_iter()
+        .filter(|l| level.is_none_or(|level| level == *l))
+        .flat_map(|level| {
+            world
+                .ids(level)
+                .into_iter()
+                .map(move |id| world.entity_name(level, id))
+        })
+        .collect()
+}
+
+fn good_names(simulation: &Simulation) -> Vec<String> {
+    simulation
+        .world()
+        .economy()
+        .catalog()
+        .iter()
+        .map(|(_, good)| good.name.clone())
+        .collect()
+}
+
+/// Commands entered in the console, oldest first. Loaded from the data
+/// directory, each new one is written back straight away so the next
+/// session can recall it.
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    entries: Vec<String>,
+    /// Where the history is kept, or None to keep it for this session only
+    path: Option<PathBuf>,
+}
+
+impl History {
+    /// The history kept in `dir`, empty if there is none yet or it can't
+    /// be read
+    pub fn load(dir: &Path) -> Self {
+        let path = dir.join(HISTORY_FILE);
+        let entries = fs::read_to_string(&path)
+            .map(|text| text.lines().map(str::to_string).collect())
+            .unwrap_or_default();
+        Self {
+            entries,
+            path: Some(path),
+        }
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// The entry `back` places before the newest
+    pub fn recall(&self, back: usize) -> Option<&str> {
+        let index = self.entries.len().checked_sub(back + 1)?;
+        self.entries.get(index).map(String::as_str)
+    }
+
+    /// Remember a command, unless it repeats the last one
+    pub fn push(&mut self, line: &str) {
+        let line = line.trim();
+        if line.is_empty() || self.entries.last().is_some_and(|last| last == line) {
+            return;
+        }
+        self.entries.push(line.to_string());
+        let excess = self.entries.len().saturating_sub(HISTORY_LENGTH);
+        self.entries.drain(..excess);
+        if let Some(path) = &self.path {
+            let mut text = self.entries.join("\n");
+            text.push('\n');
+            if let Err(error) = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|()| fs::write(path, text))
+            {
+                tracing::warn!("Console history not saved to {}: {}", path.display(), error);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_goto_moves_view_to_entity() {
+        let mut simulation = Simulation::new();
+        let message = execute("goto terra", &mut simulation).unwrap();
+
+        assert_eq!(message, "Travelled to Terra (Planet)");
+        assert_eq!(simulation.zoom().current_level(), ZoomLevel::Planet);
+        assert_eq!(simulation.zoom().position().current_planet_id, Some(1));
+    }
+
+    #[test]
+    fn test_goto_multi_word_name() {
+        let mut simulation = Simulation::new();
+        execute("goto Market District", &mut simulation).unwrap();
+        assert_eq!(simulation.zoom().current_level(), ZoomLevel::LocalArea);
+    }
+
+    #[test]
+    fn test_speed_is_clamped() {
+        let mut simulation = Simulation::new();
+        execute("speed 10", &mut simulation).unwrap();
+        assert_eq!(simulation.time().speed_multiplier(), 10.0);
+
+        execute("speed 500x", &mut simulation).unwrap();
+        assert_eq!(simulation.time().speed_multiplier(), 50.0);
+
+        assert_eq!(
+            execute("speed fast", &mut simulation),
+            Err(CommandError::InvalidValue(String::from("fast")))
+        );
+    }
+
+    #[test]
+    fn test_spawn_and_seed() {
+        let mut simulation = Simulation::new();
+        assert_eq!(
+            execute("seed 42", &mut simulation).unwrap(),
+            "World seed set to 42"
+        );
+        assert_eq!(simulation.world().seed(), 42);
+
+        let message = execute("spawn planet", &mut simulation).unwrap();
+        assert!(message.starts_with("Spawned Planet "));
+        assert_eq!(simulation.world().entity_count(), 7);
+
+        let message = execute("spawn ship", &mut simulation).unwrap();
+        assert!(message.ends_with(" at Terra"));
+        assert_eq!(simulation.world().ships().count(), 1);
+    }
+
+    #[test]
+    fn test_money_sets_growth_rate() {
+        let mut simulation = Simulation::new();
+        let message = execute("money 5%", &mut simulation).unwrap();
+
+        assert!(message.ends_with("growing +5.0%/yr, price level 1.00x"));
+        let supply = simulation.world().economy().money_supply();
+        assert!((supply.growth_rate() - 0.05).abs() < 1e-12);
+        assert_eq!(
+            execute("money -150", &mut simulation),
+            Err(CommandError::InvalidValue(String::from("-150")))
+        );
+    }
+
+    #[test]
+    fn test_bank_sets_the_policy_rate() {
+        let mut simulation = Simulation::new();
+        let message = execute("bank rate 6%", &mut simulation).unwrap();
+
+        assert!(message.starts_with("Bank lends at 9.0% and pays 3.0%"));
+        assert_eq!(simulation.world().economy().bank().policy_rate(), 0.06);
+        assert_eq!(
+            execute("bank rate 80", &mut simulation),
+            Err(CommandError::InvalidValue(String::from("80")))
+        );
+        assert_eq!(
+            execute("bank 5", &mut simulation),
+            Err(CommandError::Usage("bank [rate <percent>]"))
+        );
+    }
+
+    #[test]
+    fn test_polity_sets_taxes_in_view() {
+        let mut simulation = Simulation::new();
+        assert_eq!(
+            execute("polity", &mut simulation).unwrap(),
+            "No polity governs this place"
+        );
+
+        simulation.goto("Market District").unwrap();
+        let message = execute("polity tariff 12%", &mut simulation).unwrap();
+        assert!(message.starts_with("Highland Compact: tax 10%, sales tax 2%, tariff 12%"));
+        let message = execute("polity spending 20", &mut simulation).unwrap();
+        assert!(message.contains("spending 20%/day"));
+        assert_eq!(
+            execute("polity tax 150", &mut simulation),
+            Err(CommandError::InvalidValue(String::from("150")))
+        );
+        assert_eq!(
+            execute("polity duty 5", &mut simulation),
+            Err(CommandError::InvalidValue(String::from("duty")))
+        );
+    }
+
+    #[test]
+    fn test_order_stands_for_the_firm_under_the_cursor() {
+        let mut simulation = Simulation::new();
+        simulation.goto("Market District").unwrap();
+        assert_eq!(
+            execute("order buy grain 50 0.8", &mut simulation).unwrap(),
+            "No firm under the cursor"
+        );
+
+        // Market Bakery stands at (-3, 1)
+        simulation
+            .zoom_mut()
+            .position_mut()
+            .set_coords_for_level(ZoomLevel::LocalArea, (-3, 1));
+        let message = execute("order buy grain 50 0.8", &mut simulation).unwrap();
+        assert!(message.starts_with("Market Bakery will buy 50 grain at"));
+        let orders = |simulation: &Simulation| {
+            simulation
+                .world()
+                .economy()
+                .market(1)
+                .unwrap()
+                .orders()
+                .to_vec()
+        };
+        assert_eq!(orders(&simulation)[0].firm, 104);
+        assert_eq!(orders(&simulation)[0].side, Side::Buy);
+
+        execute("order buy grain 0 0.8", &mut simulation).unwrap();
+        assert!(orders(&simulation).is_empty());
+        assert_eq!(
+            execute("order lend grain 1 1", &mut simulation),
+            Err(CommandError::InvalidValue(String::from("lend")))
+        );
+        assert_eq!(
+            execute("order sell gold 1 1", &mut simulation),
+            Err(CommandError::InvalidValue(String::from("gold")))
+        );
+    }
+
+    #[test]
+    fn test_detail_toggles_forcing() {
+        let mut simulation = Simulation::new();
+        execute("detail full", &mut simulation).unwrap();
+        assert!(simulation.world().detail().is_forced());
+
+        execute("detail AUTO", &mut simulation).unwrap();
+        assert!(!simulation.world().detail().is_forced());
+        assert_eq!(
+            execute("detail max", &mut simulation),
+            Err(CommandError::InvalidValue(String::from("max")))
+        );
+    }
+
+    #[test]
+    fn test_fast_forward_runs_over_steps() {
+        let mut simulation = Simulation::new();
+        assert_eq!(
+            execute("ff 2", &mut simulation),
+            Ok(String::from("Fast-forwarding 2 day(s)"))
+        );
+        assert!(!simulation.time().is_paused());
+
+        while simulation.is_fast_forwarding() {
+            simulation.step();
+        }
+        assert_eq!(
+            simulation.simulation_time(),
+            Duration::from_secs(2 * 86_400)
+        );
+        assert!(simulation.time().is_paused());
+        assert_eq!(
+            execute("ff 400", &mut simulation),
+            Err(CommandError::InvalidValue(String::from("400")))
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut simulation = Simulation::new();
+        assert_eq!(
+            execute("fly", &mut simulation),
+            Err(CommandError::UnknownCommand(String::from("fly")))
+        );
+        assert_eq!(
+            execute("goto Atlantis", &mut simulation),
+            Err(CommandError::EntityNotFound(String::from("Atlantis")))
+        );
+        assert_eq!(
+            execute("spawn", &mut simulation),
+            Err(CommandError::Usage(
+                "spawn <system|planet|region|area|room|ship>"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_shock_strikes_at_once() {
+        let mut simulation = Simulation::new();
+        assert_eq!(
+            execute("shock collapse 10 Sol System", &mut simulation),
+            Ok(String::from(
+                "Hyperlanes to Sol System collapse for 10 day(s)"
+            ))
+        );
+        assert!(simulation.world().is_isolated(1));
+
+        execute("shock crisis 20%", &mut simulation).unwrap();
+        let level = simulation.world().economy().money_supply().price_level();
+        assert!((level - 1.2).abs() < 1e-9);
+
+        assert_eq!(
+            execute("shock discovery ore 100 Terra", &mut simulation),
+            Err(CommandError::Event(EventError::NotAnArea(String::from(
+                "Terra"
+            ))))
+        );
+        assert_eq!(
+            execute("shock crisis", &mut simulation),
+            Err(CommandError::Usage(usage("shock")))
+        );
+    }
+
+    #[test]
+    fn test_export_writes_by_extension() {
+        let mut simulation = Simulation::new();
+        let path = crate::save::test_dir("export-command").join("prices.csv");
+        let message = execute(&format!("export {}", path.display()), &mut simulation).unwrap();
+
+        assert_eq!(message, format!("Exported to {}", path.display()));
+        assert!(path.exists());
+        assert_eq!(
+            execute("export world.txt", &mut simulation),
+            Err(CommandError::InvalidValue(String::from("world.txt")))
+        );
+        assert_eq!(
+            execute("export", &mut simulation),
+            Err(CommandError::Usage(usage("export")))
+        );
+    }
+
+    #[test]
+    fn test_help_explains_a_command() {
+        let mut simulation = Simulation::new();
+        assert!(execute("help", &mut simulation).unwrap().contains("goto"));
+        assert_eq!(
+            execute("help GOTO", &mut simulation).unwrap(),
+            "goto <entity name>: Jump to a named system, planet, region, area or room"
+        );
+        assert_eq!(
+            execute("help warp", &mut simulation),
+            Err(CommandError::UnknownCommand(String::from("warp")))
+        );
+    }
+
+    #[test]
+    fn test_complete_commands_and_arguments() {
+        let simulation = Simulation::new();
+        let line = |typed: &str| complete(typed, &simulation).line;
+
+        assert_eq!(line("spa"), "spawn ");
+        let completion = complete("sp", &simulation);
+        assert_eq!(completion.line, "sp");
+        assert_eq!(completion.options, ["spawn", "speed"]);
+
+        assert_eq!(line("goto ter"), "goto Terra ");
+        assert_eq!(line("goto market d"), "goto Market District ");
+        assert_eq!(line("spawn pl"), "spawn planet ");
+        assert_eq!(line("order buy gr"), "order buy grain ");
+        assert_eq!(line("help sh"), "help shock ");
+        // Nothing further to complete
+        assert_eq!(line("speed 1"), "speed 1");
+        assert_eq!(line("warp x"), "warp x");
+    }
+
+    #[test]
+    fn test_history_recalls_and_persists() {
+        let dir = crate::save::test_dir("console_history");
+        let mut history = History::load(&dir);
+        history.push("speed 5");
+        history.push("speed 5");
+        history.push(" goto Terra ");
+        history.push("");
+
+        assert_eq!(history.entries(), ["speed 5", "goto Terra"]);
+        assert_eq!(history.recall(0), Some("goto Terra"));
+        assert_eq!(history.recall(1), Some("speed 5"));
+        assert_eq!(history.recall(2), None);
+        assert_eq!(History::load(&dir).entries(), history.entries());
+
+        for n in 0..HISTORY_LENGTH {
+            history.push(&format!("seed {}", n));
+        }
+        assert_eq!(history.entries().len(), HISTORY_LENGTH);
+        assert_eq!(history.entries()[0], "seed 0");
+    }
+
+    #[test]
+    #[cfg(feature = "scripting")]
+    fn test_watch_pins_queries() {
+        let mut simulation = Simulation::new();
+        assert_eq!(
+            execute("watch", &mut simulation).unwrap(),
+            "No watches pinned"
+        );
+        assert_eq!(
+            execute("watch sum firms on terra", &mut simulation).unwrap(),
+            "Watch 1: sum firms on Terra = 6.000"
+        );
+        assert_eq!(
+            execute("watch firms * 2", &mut simulation).unwrap(),
+            "Watch 2: avg firms * 2 on everywhere = 12.000"
+        );
+        execute("watch every 5", &mut simulation).unwrap();
+        assert_eq!(simulation.watches().interval(), 5);
+        assert!(
+            execute("watch", &mut simulation)
+                .unwrap()
+                .starts_with("Every 5 ticks: 1 sum firms on Terra = 6.000; 2 ")
+        );
+
+        assert_eq!(
+            execute("watch firms on Atlantis", &mut simulation),
+            Err(CommandError::EntityNotFound(String::from("Atlantis")))
+        );
+        assert!(matches!(
+            execute("watch price(grain) /", &mut simulation),
+            Err(CommandError::Query(_))
+        ));
+        assert_eq!(
+            execute("unwatch 1", &mut simulation).unwrap(),
+            "Unpinned sum firms on Terra"
+        );
+        assert_eq!(
+            execute("unwatch 7", &mut simulation),
+            Err(CommandError::InvalidValue(String::from("7")))
+        );
+        assert_eq!(
+            execute("unwatch all", &mut simulation).unwrap(),
+            "Unpinned 1 watch(es)"
+        );
+        assert_eq!(
+            complete("watch max firms on ter", &simulation).line,
+            "watch max firms on Terra "
+        );
+    }
+
+    #[test]
+    fn test_diff_compares_with_a_rewind_point() {
+        let mut simulation = Simulation::new();
+        simulation.advance(crate::time::TICK_LENGTH);
+        simulation
+            .world_mut()
+            .set_population(ZoomLevel::Planet, 1, 10);
+
+        let diff = diff(&["r1"], &simulation).unwrap();
+        assert_eq!(
+            diff.title(),
+            "Changes from r1 (Jan 01, Y3000) to now (Jan 01, Y3000)"
+        );
+        assert_eq!(diff.sections[0].changes.len(), 1);
+        assert!(diff.sections[0].changes[0].text.starts_with("Terra: "));
+        assert_eq!(
+            execute("diff now r1", &mut simulation).unwrap(),
+            "Changes from now (Jan 01, Y3000) to r1 (Jan 01, Y3000): \
+             1 population, 0 prices, 0 ownership"
+        );
+
+        assert_eq!(
+            execute("diff r2", &mut simulation),
+            Err(CommandError::InvalidValue(String::from("r2")))
+        );
+        assert_eq!(
+            execute("diff later", &mut simulation),
+            Err(CommandError::InvalidValue(String::from("later")))
+        );
+        assert_eq!(
+            execute("diff", &mut simulation),
+            Err(CommandError::Usage(usage("diff")))
+        );
+    }
+}
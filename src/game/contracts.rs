@@ -0,0 +1,268 @@
+//! Supply contracts between firms.
+//!
+//! Besides buying on the spot, a firm can secure an input ahead of time.
+//! It signs a [`SupplyContract`] with a firm that makes the input: a set
+//! quantity every [`DELIVERY_INTERVAL`] for a number of deliveries, at a
+//! price fixed when they sign. Each delivery waits on the world's timers.
+//! When one falls due, the supplier hands over what it holds of the good
+//! and buys the rest on its own market at whatever the price is by then,
+//! as far as the market's stock goes. The buyer pays the agreed price for
+//! what arrives and keeps it for its own production, so a contract shields
+//! the buyer from price swings and leaves the supplier to bear them. A
+//! supplier with nothing to deliver or a buyer that can't pay breaks the
+//! contract.
+//!
+//! Firms negotiate once a day for any input they have no contract for,
+//! with the maker on the same planet whose market sells it cheapest.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use thiserror::Error as ThisError;
+
+use crate::economy::{Economy, GoodId, Money};
+use crate::time::Calendar;
+
+use super::state::EntityId;
+
+/// Time between a contract's deliveries, and between rounds of negotiation
+pub const DELIVERY_INTERVAL: Duration = Calendar::DEFAULT_DAY_LENGTH;
+
+/// Deliveries a negotiated contract runs for
+pub const CONTRACT_DELIVERIES: u32 = 30;
+
+/// Share of what a buyer would use running every hour of the interval that
+/// it contracts for, leaving the rest to the spot market
+const CONTRACTED_SHARE: f64 = 0.25;
+
+#[derive(ThisError, Debug, Clone, PartialEq)]
+pub enum ContractError {
+    #[error("no firm with id {0}")]
+    UnknownFirm(EntityId),
+    #[error("a firm can't contract with itself")]
+    SameFirm,
+    #[error("the supplier doesn't make that good")]
+    NotMade,
+    #[error("the firms aren't on the same planet")]
+    TooFar,
+    #[error("a contract needs a quantity, a price and deliveries")]
+    BadTerms,
+}
+
+/// Why a delivery fell through
+#[derive(ThisError, Debug, Clone, Copy, PartialEq)]
+pub enum Breach {
+    #[error("one of the firms has closed")]
+    Closed,
+    #[error("the buyer can't pay")]
+    Unpaid,
+    #[error("the supplier had nothing to deliver")]
+    Undelivered,
+}
+
+/// What one delivery of a contract consists of
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Terms {
+    pub supplier: EntityId,
+    pub buyer: EntityId,
+    pub good: GoodId,
+    /// Units handed over each delivery
+    pub quantity: f64,
+    /// Price per unit, fixed at signing
+    pub price: Money,
+}
+
+impl Terms {
+    pub fn is_valid(&self) -> bool {
+        self.quantity.is_finite() && self.quantity > 0.0 && self.price >= Money::ZERO
+    }
+
+    /// What the buyer pays for a delivery
+    pub fn value(&self) -> Money {
+        self.price * self.quantity
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SupplyContract {
+    pub id: EntityId,
+    pub terms: Terms,
+    /// Deliveries still to make
+    pub remaining: u32,
+    /// Units handed over so far
+    #[serde(default)]
+    pub delivered: f64,
+}
+
+impl SupplyContract {
+    pub fn new(id: EntityId, terms: Terms, deliveries: u32) -> Self {
+        Self {
+            id,
+            terms,
+            remaining: deliveries,
+            delivered: 0.0,
+        }
+    }
+
+    pub fn involves(&self, firm: EntityId) -> bool {
+        self.terms.supplier == firm || self.terms.buyer == firm
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.remaining == 0
+    }
+
+    /// Make the next delivery, returning the units handed over, which may
+    /// fall short of the terms
+    pub fn deliver(&mut self, economy: &mut Economy) -> Result<f64, Breach> {
+        let Terms {
+            supplier,
+            buyer,
+            good,
+            quantity,
+            price,
+        } = self.terms;
+        if economy.firm(supplier).is_none() {
+            return Err(Breach::Closed);
+        }
+        let Some(customer) = economy.firm(buyer) else {
+            return Err(Breach::Closed);
+        };
+        if customer.cash < self.terms.value() {
+            return Err(Breach::Unpaid);
+        }
+
+        // No more than the buyer has room to store
+        let room = customer.stock.room_for(good, economy.catalog());
+        let delivered = economy.source(supplier, good, quantity.min(room));
+        if delivered <= 0.0 {
+            return Err(Breach::Undelivered);
+        }
+        let payment = price * delivered;
+        if let Some(customer) = economy.firm_mut(buyer) {
+            customer.cash -= payment;
+        }
+        economy.hold(buyer, good, delivered);
+        if let Some(seller) = economy.firm_mut(supplier) {
+            seller.cash += payment;
+        }
+        self.remaining = self.remaining.saturating_sub(1);
+        self.delivered += delivered;
+        Ok(delivered)
+    }
+}
+
+/// Contracts worth signing: for each input a firm needs and has no contract
+/// for, the cheapest maker of it that `near` allows, at the price on the
+/// supplier's market. `contracted` tells whether a buyer already has a
+/// contract for a good. Firms that couldn't pay for a delivery are left out.
+pub fn negotiate(
+    economy: &Economy,
+    near: impl Fn(EntityId, EntityId) -> bool,
+    contracted: impl Fn(EntityId, GoodId) -> bool,
+) -> Vec<Terms> {
+    let recipes = economy.recipes();
+    let hours = DELIVERY_INTERVAL.as_secs_f64() / 3600.0;
+    let mut deals = Vec::new();
+    for buyer in economy.firms() {
+        let Some(recipe) = recipes.get(buyer.recipe) else {
+            continue;
+        };
+        for &(good, per_hour) in &recipe.inputs {
+            if contracted(buyer.id, good) {
+                continue;
+            }
+            let offers = economy.firms().filter_map(|supplier| {
+                let makes = recipes
+                    .get(supplier.recipe)
+                    .is_some_and(|r| r.outputs.iter().any(|&(made, _)| made == good));
+                if supplier.id == buyer.id || !makes || !near(buyer.area_id, supplier.area_id) {
+                    return None;
+                }
+                let price = economy.market(supplier.area_id)?.price(good);
+                Some((supplier.id, price))
+            });
+            let Some((supplier, price)) =
+                offers.min_by(|a, b| a.1.amount().total_cmp(&b.1.amount()))
+            else {
+                continue;
+            };
+            let terms = Terms {
+                supplier,
+                buyer: buyer.id,
+                good,
+                quantity: per_hour * hours * CONTRACTED_SHARE,
+                price,
+            };
+            if terms.is_valid() && buyer.cash >= terms.value() {
+                deals.push(terms);
+            }
+        }
+    }
+    deals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::Firm;
+
+    fn economy() -> Economy {
+        let mut economy = Economy::new();
+        let farming = economy.recipes().find("grain farming").unwrap();
+        let baking = economy.recipes().find("baking").unwrap();
+        economy.add_firm(Firm::new(1, "Farm", 1, farming));
+        economy.add_firm(Firm::new(2, "Far Farm", 2, farming));
+        economy.add_firm(Firm::new(3, "Bakery", 1, baking));
+        economy
+    }
+
+    #[test]
+    fn test_buyers_contract_with_nearby_makers() {
+        let economy = economy();
+        let grain = economy.catalog().id("grain").unwrap();
+
+        let deals = negotiate(&economy, |a, b| a == b, |_, _| false);
+        assert_eq!(deals.len(), 1);
+        assert_eq!((deals[0].supplier, deals[0].buyer), (1, 3));
+        assert_eq!(deals[0].good, grain);
+        assert_eq!(deals[0].quantity, 60.0);
+        assert_eq!(deals[0].price, economy.market(1).unwrap().price(grain));
+
+        assert!(negotiate(&economy, |a, b| a == b, |_, good| good == grain).is_empty());
+    }
+
+    #[test]
+    fn test_deliveries_move_goods_and_money() {
+        let mut economy = economy();
+        let grain = economy.catalog().id("grain").unwrap();
+        let terms = Terms {
+            supplier: 1,
+            buyer: 3,
+            good: grain,
+            quantity: 60.0,
+            price: Money(2.0),
+        };
+        let mut contract = SupplyContract::new(10, terms, 2);
+        let issued = economy.money_supply().issued();
+        economy.hold(1, grain, 20.0);
+
+        assert_eq!(contract.deliver(&mut economy), Ok(60.0));
+        // 20 came out of the farm's stock and 40 off its market
+        assert_eq!(economy.firm(1).unwrap().held(grain), 0.0);
+        assert_eq!(economy.market(1).unwrap().stock(grain), 60.0);
+        assert_eq!(economy.firm(3).unwrap().held(grain), 60.0);
+        assert_eq!(economy.firm(3).unwrap().cash, Money(5_000.0 - 120.0));
+        assert_eq!(contract.remaining, 1);
+        assert_eq!(economy.total_balances(), issued);
+
+        // The market only has 60 more to give, and then nothing
+        assert_eq!(contract.deliver(&mut economy), Ok(60.0));
+        assert_eq!(contract.delivered, 120.0);
+        assert_eq!(contract.deliver(&mut economy), Err(Breach::Undelivered));
+
+        economy.firm_mut(3).unwrap().cash = Money(100.0);
+        assert_eq!(contract.deliver(&mut economy), Err(Breach::Unpaid));
+        economy.remove_firm(1);
+        assert_eq!(contract.deliver(&mut economy), Err(Breach::Closed));
+    }
+}
@@ -0,0 +1,210 @@
+//! Natural resources that run out.
+//!
+//! Regions hold deposits — ore veins, fertile land and fisheries — sized
+//! when the region is prospected by how much of its map suits each. The
+//! mines, farms and fisheries in a region draw its deposits down as they
+//! work. They produce in full until a deposit is half spent and less and
+//! less after that, so local prices climb as it runs out and untouched
+//! regions become worth opening up. A region without a deposit of the
+//! right kind still supports the building, but only at [`BARREN_YIELD`].
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::economy::{BuildingKind, Yields};
+
+use super::rng::Rng;
+use super::terrain::{Biome, TerrainMap};
+
+/// Output of an extraction building with no deposit to work, or with its
+/// deposit exhausted
+pub const BARREN_YIELD: f64 = 0.25;
+
+/// Share of a deposit left when output starts to fall
+const THINNING: f64 = 0.5;
+
+/// Least share of a region's map that can hold a deposit
+const MIN_SHARE: f64 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum DepositKind {
+    OreVein,
+    FertileLand,
+    Fishery,
+}
+
+impl DepositKind {
+    pub const ALL: [DepositKind; 3] = [
+        DepositKind::OreVein,
+        DepositKind::FertileLand,
+        DepositKind::Fishery,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DepositKind::OreVein => "ore vein",
+            DepositKind::FertileLand => "fertile land",
+            DepositKind::Fishery => "fishery",
+        }
+    }
+
+    /// The kind of deposit a building draws on, if it draws on one
+    pub fn worked_by(building: BuildingKind) -> Option<DepositKind> {
+        match building {
+            BuildingKind::Mine => Some(DepositKind::OreVein),
+            BuildingKind::Farm => Some(DepositKind::FertileLand),
+            BuildingKind::Fishery => Some(DepositKind::Fishery),
+            BuildingKind::LumberCamp | BuildingKind::Factory | BuildingKind::Workshop => None,
+        }
+    }
+
+    /// Units of output a deposit filling a whole region's map holds
+    fn full_reserve(self) -> f64 {
+        match self {
+            DepositKind::OreVein => 600_000.0,
+            DepositKind::FertileLand => 1_500_000.0,
+            DepositKind::Fishery => 900_000.0,
+        }
+    }
+
+    /// Share of a region's map that suits the deposit, from 0 to 1
+    fn share(self, terrain: &TerrainMap) -> f64 {
+        match self {
+            DepositKind::OreVein => {
+                terrain.land_share(Biome::Mountains) + terrain.land_share(Biome::Desert) / 2.0
+            }
+            DepositKind::FertileLand => {
+                terrain.land_share(Biome::Plains) + terrain.land_share(Biome::Coast) / 2.0
+            }
+            DepositKind::Fishery => terrain.water_share(),
+        }
+    }
+}
+
+impl fmt::Display for DepositKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A finite store of a raw resource in one region
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceDeposit {
+    pub kind: DepositKind,
+    /// Units of output still to be had
+    pub reserve: f64,
+    /// Units of output the deposit held when found
+    pub capacity: f64,
+}
+
+impl ResourceDeposit {
+    pub fn new(kind: DepositKind, capacity: f64) -> Self {
+        Self {
+            kind,
+            reserve: capacity,
+            capacity,
+        }
+    }
+
+    /// Share of the deposit left, from 0 to 1
+    pub fn remaining(&self) -> f64 {
+        if self.capacity <= 0.0 {
+            return 0.0;
+        }
+        (self.reserve / self.capacity).clamp(0.0, 1.0)
+    }
+
+    /// Output multiplier for the buildings working the deposit: full until
+    /// it thins, then falling to [`BARREN_YIELD`] as it runs out
+    pub fn output(&self) -> f64 {
+        let richness = (self.remaining() / THINNING).min(1.0);
+        BARREN_YIELD + (1.0 - BARREN_YIELD) * richness
+    }
+
+    pub fn is_thinning(&self) -> bool {
+        self.remaining() < THINNING
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.reserve <= 0.0
+    }
+
+    /// Draw down up to `amount`, returning how much was there to take
+    pub fn extract(&mut self, amount: f64) -> f64 {
+        let taken = amount.clamp(0.0, self.reserve.max(0.0));
+        self.reserve -= taken;
+        taken
+    }
+}
+
+/// Find a region's deposits. The more of the map suits a kind, the likelier
+/// and larger its deposit; `abundance` scales every deposit's size.
+pub fn prospect(terrain: &TerrainMap, rng: &mut Rng, abundance: f64) -> Vec<ResourceDeposit> {
+    let mut deposits = Vec::new();
+    for kind in DepositKind::ALL {
+        let share = kind.share(terrain).min(1.0);
+        let roll = rng.next_f64();
+        if share < MIN_SHARE || roll > share * 2.0 {
+            continue;
+        }
+        let capacity = kind.full_reserve() * share * (0.5 + rng.next_f64()) * abundance.max(0.0);
+        if capacity > 0.0 {
+            deposits.push(ResourceDeposit::new(kind, capacity));
+        }
+    }
+    deposits
+}
+
+/// Scale the land's yields by what its deposits have left
+pub fn apply(deposits: &[ResourceDeposit], yields: Yields) -> Yields {
+    let output = |kind: DepositKind| {
+        deposits
+            .iter()
+            .find(|deposit| deposit.kind == kind)
+            .map_or(BARREN_YIELD, ResourceDeposit::output)
+    };
+    Yields {
+        farming: yields.farming * output(DepositKind::FertileLand),
+        mining: yields.mining * output(DepositKind::OreVein),
+        fishing: yields.fishing * output(DepositKind::Fishery),
+        ..yields
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_falls_as_a_deposit_runs_out() {
+        let mut vein = ResourceDeposit::new(DepositKind::OreVein, 1_000.0);
+        assert_eq!(vein.output(), 1.0);
+        assert_eq!(vein.extract(400.0), 400.0);
+        assert_eq!(vein.output(), 1.0);
+        assert!(!vein.is_thinning());
+
+        vein.extract(350.0);
+        assert!(vein.is_thinning());
+        assert!((vein.output() - (BARREN_YIELD + (1.0 - BARREN_YIELD) * 0.5)).abs() < 1e-9);
+
+        assert_eq!(vein.extract(1_000.0), 250.0);
+        assert!(vein.is_exhausted());
+        assert_eq!(vein.output(), BARREN_YIELD);
+        assert_eq!(vein.extract(10.0), 0.0);
+    }
+
+    #[test]
+    fn test_deposits_follow_the_land() {
+        let mountains = TerrainMap::try_from(String::from("mmm/mmm/mmm")).unwrap();
+        let deposits = prospect(&mountains, &mut Rng::new(1), 1.0);
+        let kinds: Vec<DepositKind> = deposits.iter().map(|d| d.kind).collect();
+        assert_eq!(kinds, vec![DepositKind::OreVein]);
+        assert!(deposits[0].capacity >= 300_000.0);
+        assert!(prospect(&mountains, &mut Rng::new(1), 0.0).is_empty());
+
+        let yields = apply(&deposits, Yields::EVEN);
+        assert_eq!(yields.mining, 1.0);
+        assert_eq!(yields.farming, BARREN_YIELD);
+        assert_eq!(yields.logging, 1.0);
+    }
+}
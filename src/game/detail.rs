@@ -0,0 +1,186 @@
+//! Level-of-detail scheduling for market updates.
+//!
+//! Markets near the player's focus update every tick. Markets elsewhere
+//! bank their elapsed time and update at coarser intervals, covering the
+//! whole banked span in one larger step.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::zoom::ZoomLevel;
+
+use super::state::EntityId;
+
+/// Update interval for markets on the focused planet, away from the
+/// focused region
+pub const REDUCED_INTERVAL: Duration = Duration::from_secs(3_600);
+
+/// Update interval for markets on other planets
+pub const COARSE_INTERVAL: Duration = Duration::from_secs(86_400);
+
+/// How closely a place is being simulated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Detail {
+    /// Every tick
+    Full,
+    /// Hourly
+    Reduced,
+    /// Daily
+    Coarse,
+}
+
+impl Detail {
+    /// Simulation time to bank before an update
+    pub fn interval(self) -> Duration {
+        match self {
+            Detail::Full => Duration::ZERO,
+            Detail::Reduced => REDUCED_INTERVAL,
+            Detail::Coarse => COARSE_INTERVAL,
+        }
+    }
+}
+
+/// Chooses which markets update on a tick and by how much time
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DetailScheduler {
+    /// Ancestry of the entity the player is looking at, innermost first.
+    /// Everything runs at full detail until a focus is set.
+    #[serde(skip)]
+    focus: Option<Vec<(ZoomLevel, EntityId)>>,
+    /// Whether everything inside the focused entity runs at full detail
+    forced: bool,
+    /// Time banked by each area's market since its last update
+    pending: BTreeMap<EntityId, Duration>,
+}
+
+impl DetailScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Focus on the entity at the head of an ancestry chain
+    pub fn set_focus(&mut self, ancestry: Vec<(ZoomLevel, EntityId)>) {
+        self.focus = Some(ancestry);
+    }
+
+    /// Simulate everything at full detail until a focus is set again
+    pub fn clear_focus(&mut self) {
+        self.focus = None;
+    }
+
+    /// Run the whole focused branch at full detail, however far it extends
+    pub fn force_full(&mut self, forced: bool) {
+        self.forced = forced;
+    }
+
+    pub fn is_forced(&self) -> bool {
+        self.forced
+    }
+
+    /// Detail for a place, given its ancestry innermost first
+    pub fn detail(&self, ancestry: &[(ZoomLevel, EntityId)]) -> Detail {
+        let Some(focus) = &self.focus else {
+            return Detail::Full;
+        };
+        if self.forced && focus.first().is_some_and(|head| ancestry.contains(head)) {
+            return Detail::Full;
+        }
+
+        let shared = ancestry
+            .iter()
+            .find(|entry| focus.contains(entry))
+            .map_or(ZoomLevel::Galaxy, |(level, _)| *level);
+        match shared {
+            ZoomLevel::Region | ZoomLevel::LocalArea | ZoomLevel::Room => Detail::Full,
+            ZoomLevel::SolarSystem | ZoomLevel::Planet => Detail::Reduced,
+            ZoomLevel::Galaxy => Detail::Coarse,
+        }
+    }
+
+    /// Bank `delta` for an area. Returns the banked time, clearing it, once
+    /// the area's interval has been reached.
+    pub fn take_due(
+        &mut self,
+        area_id: EntityId,
+        detail: Detail,
+        delta: Duration,
+    ) -> Option<Duration> {
+        let banked = self.pending.entry(area_id).or_default();
+        *banked += delta;
+        if banked.is_zero() || *banked < detail.interval() {
+            return None;
+        }
+        self.pending.remove(&area_id)
+    }
+
+    /// Time an area has banked towards its next update
+    pub fn pending(&self, area_id: EntityId) -> Duration {
+        self.pending.get(&area_id).copied().unwrap_or_default()
+    }
+
+    /// Drop the banked time of an area that no longer exists
+    pub fn forget(&mut self, area_id: EntityId) {
+        self.pending.remove(&area_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ancestry(planet: EntityId, region: EntityId, area: EntityId) -> Vec<(ZoomLevel, EntityId)> {
+        vec![
+            (ZoomLevel::LocalArea, area),
+            (ZoomLevel::Region, region),
+            (ZoomLevel::Planet, planet),
+            (ZoomLevel::SolarSystem, 1),
+            (ZoomLevel::Galaxy, 0),
+        ]
+    }
+
+    #[test]
+    fn test_detail_falls_off_with_distance() {
+        let mut scheduler = DetailScheduler::new();
+        assert_eq!(scheduler.detail(&ancestry(9, 9, 9)), Detail::Full);
+
+        scheduler.set_focus(ancestry(1, 1, 1));
+        assert_eq!(scheduler.detail(&ancestry(1, 1, 2)), Detail::Full);
+        assert_eq!(scheduler.detail(&ancestry(1, 2, 3)), Detail::Reduced);
+
+        let mut elsewhere = ancestry(2, 4, 5);
+        elsewhere[3] = (ZoomLevel::SolarSystem, 2);
+        assert_eq!(scheduler.detail(&elsewhere), Detail::Coarse);
+    }
+
+    #[test]
+    fn test_forcing_covers_focused_branch() {
+        let mut scheduler = DetailScheduler::new();
+        scheduler.set_focus(ancestry(1, 1, 1)[2..].to_vec());
+        assert_eq!(scheduler.detail(&ancestry(1, 2, 3)), Detail::Reduced);
+
+        scheduler.force_full(true);
+        assert_eq!(scheduler.detail(&ancestry(1, 2, 3)), Detail::Full);
+        assert_eq!(scheduler.detail(&ancestry(2, 4, 5)), Detail::Reduced);
+    }
+
+    #[test]
+    fn test_time_is_banked_until_due() {
+        let mut scheduler = DetailScheduler::new();
+        let half_hour = REDUCED_INTERVAL / 2;
+
+        assert_eq!(scheduler.take_due(1, Detail::Reduced, half_hour), None);
+        assert_eq!(scheduler.pending(1), half_hour);
+        assert_eq!(
+            scheduler.take_due(1, Detail::Reduced, half_hour),
+            Some(REDUCED_INTERVAL)
+        );
+        assert_eq!(scheduler.pending(1), Duration::ZERO);
+
+        assert_eq!(scheduler.take_due(2, Detail::Full, Duration::ZERO), None);
+        assert_eq!(
+            scheduler.take_due(2, Detail::Full, Duration::from_secs(1)),
+            Some(Duration::from_secs(1))
+        );
+    }
+}
@@ -0,0 +1,357 @@
+//! What changed in the world between two moments.
+//!
+//! [`WorldDiff::between`] compares two states of the world, such as the
+//! present and an autosave or a rewind snapshot, place by place: how many
+//! people live on each planet and in each area, how each market's prices
+//! moved, and which firms opened, closed or changed hands and which
+//! regions changed polity. The console's `diff` command shows the result
+//! as a report to page through and jump to places from, and `--diff`
+//! prints it for two saves.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::save::SaveGame;
+use crate::time::Calendar;
+use crate::zoom::ZoomLevel;
+
+use super::WorldState;
+use super::state::EntityId;
+
+/// Smallest relative price move the report lists
+pub const PRICE_THRESHOLD: f64 = 0.01;
+
+/// A moment to compare the world at, as the console names it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Moment {
+    /// The world as it is
+    Now,
+    /// A rewind snapshot, counting back from the latest at 1 (`r1`)
+    Rewind(usize),
+    /// A save in the data directory, counting back from the newest at 1
+    /// (`s1`)
+    Save(usize),
+    /// A save file, relative to the data directory unless absolute
+    File(PathBuf),
+}
+
+impl Moment {
+    /// `now`, `r<n>`, `s<n>` or the path of a save file
+    pub fn parse(text: &str) -> Option<Self> {
+        let counted = |prefix: char| {
+            text.strip_prefix(prefix)
+                .and_then(|n| n.parse::<usize>().ok())
+                .filter(|&n| n > 0)
+        };
+        if text.eq_ignore_ascii_case("now") {
+            Some(Self::Now)
+        } else if let Some(n) = counted('r') {
+            Some(Self::Rewind(n))
+        } else if let Some(n) = counted('s') {
+            Some(Self::Save(n))
+        } else if text.ends_with(".json") {
+            Some(Self::File(PathBuf::from(text)))
+        } else {
+            None
+        }
+    }
+}
+
+/// A save as the report names it: its file name and the date it was made
+pub fn save_label(path: &Path, save: &SaveGame, calendar: &Calendar) -> String {
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    format!("{} ({})", name, calendar.date_at(save.simulation_time))
+}
+
+/// One line of the report, with the place it concerns to jump to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub text: String,
+    pub place: Option<(ZoomLevel, EntityId)>,
+}
+
+/// The changes of one kind, largest first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub title: &'static str,
+    pub changes: Vec<Change>,
+}
+
+/// Every change between two states of the world
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorldDiff {
+    /// The moments compared, as the report names them
+    pub from: String,
+    pub to: String,
+    pub sections: Vec<Section>,
+}
+
+impl WorldDiff {
+    pub fn between(before: &WorldState, after: &WorldState, from: &str, to: &str) -> Self {
+        Self {
+            from: from.to_string(),
+            to: to.to_string(),
+            sections: vec![
+                Section {
+                    title: "Population",
+                    changes: population(before, after),
+                },
+                Section {
+                    title: "Prices",
+                    changes: prices(before, after),
+                },
+                Section {
+                    title: "Ownership",
+                    changes: ownership(before, after),
+                },
+            ],
+        }
+    }
+
+    pub fn title(&self) -> String {
+        format!("Changes from {} to {}", self.from, self.to)
+    }
+
+    /// How many changes of each kind, for the event log
+    pub fn summary(&self) -> String {
+        let counts: Vec<String> = self
+            .sections
+            .iter()
+            .map(|section| format!("{} {}", section.changes.len(), section.title.to_lowercase()))
+            .collect();
+        format!("{}: {}", self.title(), counts.join(", "))
+    }
+
+    /// The whole report as plain text, a heading above each section
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![self.title()];
+        for section in &self.sections {
+            lines.push(String::new());
+            lines.push(format!("{} ({})", section.title, section.changes.len()));
+            if section.changes.is_empty() {
+                lines.push(String::from("  No changes"));
+            }
+            lines.extend(
+                section
+                    .changes
+                    .iter()
+                    .map(|change| format!("  {}", change.text)),
+            );
+        }
+        lines
+    }
+}
+
+/// Changes sorted largest first by `size`, ties in the order given
+fn largest_first(mut changes: Vec<(f64, Change)>) -> Vec<Change> {
+    changes.sort_by(|a, b| b.0.abs().total_cmp(&a.0.abs()));
+    changes.into_iter().map(|(_, change)| change).collect()
+}
+
+/// People on each planet and in each local area, where the number moved
+/// or the place came or went
+fn population(before: &WorldState, after: &WorldState) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for level in [ZoomLevel::Planet, ZoomLevel::LocalArea] {
+        for id in after.ids(level) {
+            let name = after.entity_name(level, id);
+            let now = after.population(level, id).unwrap_or(0);
+            let (size, text) = match before.population(level, id) {
+                Some(then) if then == now => continue,
+                Some(then) => (
+                    now as f64 - then as f64,
+                    format!(
+                        "{}: {} -> {} ({:+})",
+                        name,
+                        then,
+                        now,
+                        now as i64 - then as i64
+                    ),
+                ),
+                None if before.placement_of(level, id).is_some() => continue,
+                None => (now as f64, format!("{}: new, {} people", name, now)),
+            };
+            let place = Some((level, id));
+            changes.push((size, Change { text, place }));
+        }
+        for id in before.ids(level) {
+            if after.placement_of(level, id).is_none() {
+                let then = before.population(level, id).unwrap_or(0);
+                let text = format!("{}: gone, had {}", before.entity_name(level, id), then);
+                changes.push((then as f64, Change { text, place: None }));
+            }
+        }
+    }
+    largest_first(changes)
+}
+
+/// Every good whose price moved by at least [`PRICE_THRESHOLD`] on a
+/// market open at both moments
+fn prices(before: &WorldState, after: &WorldState) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for area in after.ids(ZoomLevel::LocalArea) {
+        let (Some(then), Some(now)) = (before.economy().market(area), after.economy().market(area))
+        else {
+            continue;
+        };
+        let area_name = after.entity_name(ZoomLevel::LocalArea, area);
+        for (good, details) in after.economy().catalog().iter() {
+            let (from, to) = (then.price(good), now.price(good));
+            if from.amount() <= 0.0 {
+                continue;
+            }
+            let moved = to.amount() / from.amount() - 1.0;
+            if moved.abs() < PRICE_THRESHOLD {
+                continue;
+            }
+            let text = format!(
+                "{} in {}: {} -> {} ({:+.1}%)",
+                details.name,
+                area_name,
+                from,
+                to,
+                moved * 100.0
+            );
+            let place = Some((ZoomLevel::LocalArea, area));
+            changes.push((moved, Change { text, place }));
+        }
+    }
+    largest_first(changes)
+}
+
+/// Firms opened, closed, taken on or given up by the player, and regions
+/// that changed polity, in that order
+fn ownership(before: &WorldState, after: &WorldState) -> Vec<Change> {
+    let firms = |world: &WorldState| -> BTreeMap<EntityId, (String, EntityId)> {
+        world
+            .economy()
+            .firms()
+            .map(|firm| (firm.id, (firm.name.clone(), firm.area_id)))
+            .collect()
+    };
+    let (then, now) = (firms(before), firms(after));
+    let area = |world: &WorldState, id: EntityId| world.entity_name(ZoomLevel::LocalArea, id);
+    let in_area = |id: EntityId| {
+        after
+            .placement_of(ZoomLevel::LocalArea, id)
+            .map(|_| (ZoomLevel::LocalArea, id))
+    };
+
+    let mut changes = Vec::new();
+    for (id, (name, area_id)) in &now {
+        let text = if !then.contains_key(id) {
+            format!("{} opened in {}", name, area(after, *area_id))
+        } else if after.business().owns(*id) && !before.business().owns(*id) {
+            format!("{} in {} became yours", name, area(after, *area_id))
+        } else if before.business().owns(*id) && !after.business().owns(*id) {
+            format!("{} in {} is no longer yours", name, area(after, *area_id))
+        } else {
+            continue;
+        };
+        let place = in_area(*area_id);
+        changes.push(Change { text, place });
+    }
+    for (id, (name, area_id)) in &then {
+        if !now.contains_key(id) {
+            let text = format!("{} in {} closed", name, area(before, *area_id));
+            let place = in_area(*area_id);
+            changes.push(Change { text, place });
+        }
+    }
+
+    let governing = |world: &WorldState, region: EntityId| {
+        world
+            .polity_of(ZoomLevel::Region, region)
+            .map_or_else(|| String::from("no polity"), |polity| polity.name.clone())
+    };
+    for region in after.ids(ZoomLevel::Region) {
+        if before.placement_of(ZoomLevel::Region, region).is_none() {
+            continue;
+        }
+        let (from, to) = (governing(before, region), governing(after, region));
+        if from != to {
+            let name = after.entity_name(ZoomLevel::Region, region);
+            changes.push(Change {
+                text: format!("{}: {} -> {}", name, from, to),
+                place: Some((ZoomLevel::Region, region)),
+            });
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::{Firm, Money};
+
+    fn texts(section: &Section) -> Vec<&str> {
+        section.changes.iter().map(|c| c.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_moments_parse() {
+        assert_eq!(Moment::parse("now"), Some(Moment::Now));
+        assert_eq!(Moment::parse("r2"), Some(Moment::Rewind(2)));
+        assert_eq!(Moment::parse("s1"), Some(Moment::Save(1)));
+        assert_eq!(
+            Moment::parse("autosave_1.json"),
+            Some(Moment::File(PathBuf::from("autosave_1.json")))
+        );
+        assert_eq!(Moment::parse("r0"), None);
+        assert_eq!(Moment::parse("yesterday"), None);
+    }
+
+    #[test]
+    fn test_diff_lists_changes_by_place() {
+        let before = WorldState::new();
+        let mut after = WorldState::new();
+        after.set_population(ZoomLevel::Planet, 1, 1_000);
+        let catalog = after.economy().catalog().clone();
+        let grain = catalog.id("grain").unwrap();
+        let price = before.economy().market(1).unwrap().price(grain);
+        after.economy_mut().market_mut(1).unwrap().set_price(
+            grain,
+            catalog.get(grain).unwrap(),
+            Money(price.amount() * 1.5),
+        );
+        let recipe = after.economy().firms().next().unwrap().recipe;
+        after
+            .economy_mut()
+            .add_firm(Firm::new(900, "New Mill", 1, recipe));
+
+        let diff = WorldDiff::between(&before, &after, "then", "now");
+        let [population, prices, ownership] = &diff.sections[..] else {
+            panic!("three sections");
+        };
+        let then = before.population(ZoomLevel::Planet, 1).unwrap();
+        assert_eq!(
+            texts(population),
+            [format!(
+                "Terra: {} -> 1000 ({:+})",
+                then,
+                1_000 - then as i64
+            )]
+        );
+        assert_eq!(population.changes[0].place, Some((ZoomLevel::Planet, 1)));
+        assert_eq!(prices.changes.len(), 1);
+        assert!(
+            prices.changes[0]
+                .text
+                .starts_with("grain in Market District: ")
+        );
+        assert!(prices.changes[0].text.ends_with("(+50.0%)"));
+        assert_eq!(texts(ownership), ["New Mill opened in Market District"]);
+
+        assert_eq!(
+            diff.summary(),
+            "Changes from then to now: 1 population, 1 prices, 1 ownership"
+        );
+        let same = WorldDiff::between(&before, &before, "then", "then");
+        assert!(same.sections.iter().all(|s| s.changes.is_empty()));
+        assert!(same.lines().contains(&String::from("  No changes")));
+    }
+}
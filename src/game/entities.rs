@@ -0,0 +1,429 @@
+//! The places the world is made of, kept as entities with components.
+//!
+//! Every place below the galaxy is an entity in an [`EntityStore`]. What
+//! the place is made of lives in component maps keyed by the entity: a
+//! [`Named`] entity has a name, a positioned one has a [`Placement`] on its
+//! parent's map, a [`Populated`] one has people, and a [`MarketHost`]
+//! trades on a market in the economy. Data only one kind of place has, such
+//! as a region's terrain, is a component too. Code that cares about a
+//! component reads its map and never needs to know which kinds of place
+//! carry it, so a new kind of place is a new set of components rather than
+//! a new map and a new arm in every accessor.
+//!
+//! An entity is known by its level and id, as everywhere else in the
+//! world. Ids come from the world in increasing order and are never reused,
+//! so a key kept after its entity is removed finds nothing rather than a
+//! newer entity, as a generational index would.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::economy::Money;
+use crate::zoom::ZoomLevel;
+
+use super::deposits::ResourceDeposit;
+use super::furniture::Furnishing;
+use super::stars::StarClass;
+use super::state::{EntityId, Placement};
+use super::terrain::TerrainMap;
+
+/// How an entity is addressed: its level and its id
+pub type EntityKey = (ZoomLevel, EntityId);
+
+/// One component of every entity that has it
+#[derive(Debug, Clone)]
+pub struct Components<T>(BTreeMap<EntityKey, T>);
+
+impl<T> Components<T> {
+    pub fn get(&self, level: ZoomLevel, id: EntityId) -> Option<&T> {
+        self.0.get(&(level, id))
+    }
+
+    pub fn get_mut(&mut self, level: ZoomLevel, id: EntityId) -> Option<&mut T> {
+        self.0.get_mut(&(level, id))
+    }
+
+    pub fn contains(&self, level: ZoomLevel, id: EntityId) -> bool {
+        self.0.contains_key(&(level, id))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Every entity with the component, by level from the innermost and
+    /// then by id
+    pub fn iter(&self) -> impl Iterator<Item = (EntityKey, &T)> {
+        self.0.iter().map(|(&key, value)| (key, value))
+    }
+
+    /// Entities of one level with the component, in id order
+    pub fn at(&self, level: ZoomLevel) -> impl Iterator<Item = (EntityId, &T)> {
+        self.0
+            .range((level, EntityId::MIN)..=(level, EntityId::MAX))
+            .map(|(&(_, id), value)| (id, value))
+    }
+
+    pub fn at_mut(&mut self, level: ZoomLevel) -> impl Iterator<Item = (EntityId, &mut T)> {
+        self.0
+            .range_mut((level, EntityId::MIN)..=(level, EntityId::MAX))
+            .map(|(&(_, id), value)| (id, value))
+    }
+
+    fn put(&mut self, key: EntityKey, value: Option<T>) {
+        match value {
+            Some(value) => {
+                self.0.insert(key, value);
+            }
+            None => {
+                self.0.remove(&key);
+            }
+        }
+    }
+
+    fn take(&mut self, key: EntityKey) -> Option<T> {
+        self.0.remove(&key)
+    }
+}
+
+impl<T> Default for Components<T> {
+    fn default() -> Self {
+        Self(BTreeMap::new())
+    }
+}
+
+/// Written as a list of `[level, id, component]` entries, since JSON map
+/// keys can only be strings
+impl<T: Serialize> Serialize for Components<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            self.0
+                .iter()
+                .map(|(&(level, id), value)| (level, id, value)),
+        )
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Components<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entries = Vec::<(ZoomLevel, EntityId, T)>::deserialize(deserializer)?;
+        Ok(Self(
+            entries
+                .into_iter()
+                .map(|(level, id, value)| ((level, id), value))
+                .collect(),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Named {
+    pub name: String,
+}
+
+/// People living in a place: a planet's population, or the working
+/// residents of a local area available to staff its firms
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Populated {
+    pub population: u64,
+}
+
+/// The entity can trade on a market in the economy under its id; removing
+/// it closes the market, and snapshots of it carry the market along
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarketHost;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SolarSystemState {
+    pub planet_count: u32,
+    #[serde(default)]
+    pub star: StarClass,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionState {
+    pub terrain_type: String,
+    /// The region's surface, surveyed from its planet's noise; see
+    /// [`WorldState::survey_terrain`](super::WorldState::survey_terrain)
+    #[serde(default)]
+    pub terrain: Option<TerrainMap>,
+    /// Raw resources in the region, or None until it is prospected; see
+    /// [`WorldState::prospect`](super::WorldState::prospect)
+    #[serde(default)]
+    pub deposits: Option<Vec<ResourceDeposit>>,
+    /// What governments have spent building up the region, which makes its
+    /// firms more productive; see
+    /// [`infrastructure_boost`](super::polities::infrastructure_boost)
+    #[serde(default)]
+    pub infrastructure: Money,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalAreaState {
+    pub building_count: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoomState {
+    pub room_type: String,
+    /// Stalls, beds and workbenches on the room's map
+    #[serde(default)]
+    pub furniture: Vec<Furnishing>,
+}
+
+/// Every component of one entity, detached from the store
+#[derive(Debug, Clone)]
+pub struct Entity {
+    pub id: EntityId,
+    pub level: ZoomLevel,
+    pub named: Option<Named>,
+    pub placement: Option<Placement>,
+    pub populated: Option<Populated>,
+    pub market_host: Option<MarketHost>,
+    pub system: Option<SolarSystemState>,
+    pub region: Option<RegionState>,
+    pub area: Option<LocalAreaState>,
+    pub room: Option<RoomState>,
+}
+
+impl Entity {
+    /// An entity with no components yet
+    pub fn new(id: EntityId, level: ZoomLevel) -> Self {
+        Self {
+            id,
+            level,
+            named: None,
+            placement: None,
+            populated: None,
+            market_host: None,
+            system: None,
+            region: None,
+            area: None,
+            room: None,
+        }
+    }
+
+    pub fn named(mut self, name: impl Into<String>) -> Self {
+        self.named = Some(Named { name: name.into() });
+        self
+    }
+
+    pub fn placed(mut self, placement: Placement) -> Self {
+        self.placement = Some(placement);
+        self
+    }
+
+    pub fn populated(mut self, population: u64) -> Self {
+        self.populated = Some(Populated { population });
+        self
+    }
+
+    pub fn with_system(mut self, system: SolarSystemState) -> Self {
+        self.system = Some(system);
+        self
+    }
+
+    pub fn with_region(mut self, region: RegionState) -> Self {
+        self.region = Some(region);
+        self
+    }
+
+    /// A local area, which hosts a market
+    pub fn with_area(mut self, area: LocalAreaState) -> Self {
+        self.area = Some(area);
+        self.market_host = Some(MarketHost);
+        self
+    }
+
+    pub fn with_room(mut self, room: RoomState) -> Self {
+        self.room = Some(room);
+        self
+    }
+}
+
+/// Every entity below the galaxy and their components
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EntityStore {
+    entities: BTreeSet<EntityKey>,
+    pub named: Components<Named>,
+    pub placements: Components<Placement>,
+    pub populated: Components<Populated>,
+    pub market_hosts: Components<MarketHost>,
+    pub systems: Components<SolarSystemState>,
+    pub regions: Components<RegionState>,
+    pub areas: Components<LocalAreaState>,
+    pub rooms: Components<RoomState>,
+}
+
+impl EntityStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn contains(&self, level: ZoomLevel, id: EntityId) -> bool {
+        self.entities.contains(&(level, id))
+    }
+
+    /// Ids of every entity at a level, in ascending order
+    pub fn ids(&self, level: ZoomLevel) -> impl Iterator<Item = EntityId> + '_ {
+        self.entities
+            .range((level, EntityId::MIN)..=(level, EntityId::MAX))
+            .map(|&(_, id)| id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    pub fn name(&self, level: ZoomLevel, id: EntityId) -> Option<&str> {
+        self.named.get(level, id).map(|named| named.name.as_str())
+    }
+
+    pub fn rename(&mut self, level: ZoomLevel, id: EntityId, name: impl Into<String>) {
+        if self.contains(level, id) {
+            let name = name.into();
+            self.named.put((level, id), Some(Named { name }));
+        }
+    }
+
+    /// Add an entity with its components, replacing any with the same key
+    pub fn insert(&mut self, entity: Entity) {
+        let key = (entity.level, entity.id);
+        self.entities.insert(key);
+        self.named.put(key, entity.named);
+        self.placements.put(key, entity.placement);
+        self.populated.put(key, entity.populated);
+        self.market_hosts.put(key, entity.market_host);
+        self.systems.put(key, entity.system);
+        self.regions.put(key, entity.region);
+        self.areas.put(key, entity.area);
+        self.rooms.put(key, entity.room);
+    }
+
+    /// A copy of an entity and all its components
+    pub fn get(&self, level: ZoomLevel, id: EntityId) -> Option<Entity> {
+        if !self.contains(level, id) {
+            return None;
+        }
+        Some(Entity {
+            id,
+            level,
+            named: self.named.get(level, id).cloned(),
+            placement: self.placements.get(level, id).copied(),
+            populated: self.populated.get(level, id).copied(),
+            market_host: self.market_hosts.get(level, id).copied(),
+            system: self.systems.get(level, id).cloned(),
+            region: self.regions.get(level, id).cloned(),
+            area: self.areas.get(level, id).cloned(),
+            room: self.rooms.get(level, id).cloned(),
+        })
+    }
+
+    /// Take an entity and all its components out of the store
+    pub fn remove(&mut self, level: ZoomLevel, id: EntityId) -> Option<Entity> {
+        let key = (level, id);
+        if !self.entities.remove(&key) {
+            return None;
+        }
+        Some(Entity {
+            id,
+            level,
+            named: self.named.take(key),
+            placement: self.placements.take(key),
+            populated: self.populated.take(key),
+            market_host: self.market_hosts.take(key),
+            system: self.systems.take(key),
+            region: self.regions.take(key),
+            area: self.areas.take(key),
+            room: self.rooms.take(key),
+        })
+    }
+
+    /// Entities of a level placed on the given parent's map, in id order
+    pub fn placed_in(
+        &self,
+        level: ZoomLevel,
+        parent: Option<EntityId>,
+    ) -> Vec<(EntityId, (i32, i32))> {
+        self.placements
+            .at(level)
+            .filter(|(_, placement)| placement.parent == parent)
+            .map(|(id, placement)| (id, placement.coords))
+            .collect()
+    }
+
+    /// The first entity named `name`, ignoring case, searching from the
+    /// outermost level in
+    pub fn find(&self, name: &str) -> Option<(ZoomLevel, EntityId)> {
+        self.named
+            .iter()
+            .filter(|(_, named)| named.name.eq_ignore_ascii_case(name))
+            .map(|(key, _)| key)
+            .max_by_key(|&(level, id)| (level, std::cmp::Reverse(id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entities_come_and_go_with_their_components() {
+        let mut store = EntityStore::new();
+        store.insert(
+            Entity::new(7, ZoomLevel::Planet)
+                .named("Terra")
+                .placed(Placement::new(Some(1), (2, 3)))
+                .populated(100),
+        );
+        store.insert(Entity::new(7, ZoomLevel::Region).named("Highlands"));
+        assert_eq!(store.name(ZoomLevel::Planet, 7), Some("Terra"));
+        assert_eq!(
+            store.placed_in(ZoomLevel::Planet, Some(1)),
+            vec![(7, (2, 3))]
+        );
+        assert!(store.placed_in(ZoomLevel::Region, Some(1)).is_empty());
+        assert_eq!(store.find("terra"), Some((ZoomLevel::Planet, 7)));
+
+        let removed = store.remove(ZoomLevel::Planet, 7).unwrap();
+        assert_eq!(removed.populated, Some(Populated { population: 100 }));
+        assert_eq!(store.len(), 1);
+        assert!(store.placements.is_empty() && store.populated.is_empty());
+        assert_eq!(store.name(ZoomLevel::Planet, 7), None);
+        assert_eq!(store.name(ZoomLevel::Region, 7), Some("Highlands"));
+
+        store.insert(removed);
+        let entity = store.get(ZoomLevel::Planet, 7).unwrap();
+        assert_eq!(entity.named.unwrap().name, "Terra");
+    }
+
+    #[test]
+    fn test_find_prefers_outer_levels() {
+        let mut store = EntityStore::new();
+        store.insert(Entity::new(2, ZoomLevel::Room).named("Hub"));
+        store.insert(Entity::new(5, ZoomLevel::LocalArea).named("Hub"));
+        store.insert(Entity::new(9, ZoomLevel::LocalArea).named("hub"));
+        assert_eq!(store.find("HUB"), Some((ZoomLevel::LocalArea, 5)));
+    }
+
+    #[test]
+    fn test_components_round_trip_through_json() {
+        let mut store = EntityStore::new();
+        store.insert(Entity::new(1, ZoomLevel::Planet).named("Terra"));
+        store.insert(Entity::new(1, ZoomLevel::Room).named("Hall"));
+
+        let json = serde_json::to_string(&store).unwrap();
+        let read: EntityStore = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.ids(ZoomLevel::Room).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(read.name(ZoomLevel::Planet, 1), Some("Terra"));
+    }
+}
@@ -0,0 +1,244 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use crate::time::Calendar;
+
+/// Entries kept before the oldest are discarded
+pub const EVENT_LOG_CAPACITY: usize = 200;
+
+/// Game speed from which repeated events are shown as one line per day
+pub const COALESCE_FROM_SPEED: f64 = 5.0;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Simulation time at which the event happened
+    pub time: Duration,
+    pub message: String,
+    /// What the event has in common with others like it, such as "Price
+    /// of grain rose in Market District"; entries sharing a group can be
+    /// shown as one line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+}
+
+/// How much game time repeated events are merged over when the game runs
+/// at a speed: a day once it is fast enough for them to flood the log,
+/// otherwise none
+pub fn coalescing_window(speed: f64) -> Option<Duration> {
+    (speed >= COALESCE_FROM_SPEED).then_some(Calendar::DEFAULT_DAY_LENGTH)
+}
+
+/// Something that happened during an update which the player may want the
+/// game to pause for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alert {
+    /// A scenario's scripted event fired
+    ScriptedEvent,
+    /// A campaign objective or chapter was completed or failed
+    Objective,
+}
+
+/// How much a notice matters, which decides how it is styled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+/// A message worth putting in front of the player straight away rather
+/// than only in the event log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notice {
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Bounded, chronological record of things the player should know about
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventLog {
+    entries: VecDeque<LogEntry>,
+    /// Entries ever pushed, including those since discarded
+    #[serde(default)]
+    pushed: u64,
+}
+
+impl EventLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, time: Duration, message: impl Into<String>) {
+        self.push_entry(LogEntry {
+            time,
+            message: message.into(),
+            group: None,
+        });
+    }
+
+    /// Record an event that may be merged with others in its group when
+    /// shown; see [`EventLog::coalesced`]
+    pub fn push_grouped(
+        &mut self,
+        time: Duration,
+        group: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        self.push_entry(LogEntry {
+            time,
+            message: message.into(),
+            group: Some(group.into()),
+        });
+    }
+
+    fn push_entry(&mut self, entry: LogEntry) {
+        if self.entries.len() == EVENT_LOG_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+        self.pushed += 1;
+    }
+
+    /// How many entries have ever been pushed; a reader that remembers this
+    /// can later ask for only what came after with [`EventLog::since`]
+    pub fn pushed(&self) -> u64 {
+        self.pushed
+    }
+
+    /// Entries pushed after the first `seen`, oldest first, as far back as
+    /// the log still holds
+    pub fn since(&self, seen: u64) -> impl Iterator<Item = &LogEntry> {
+        let newer = self.pushed.saturating_sub(seen);
+        self.recent(usize::try_from(newer).unwrap_or(usize::MAX))
+    }
+
+    /// The newest `count` entries, oldest first
+    pub fn recent(&self, count: usize) -> impl Iterator<Item = &LogEntry> {
+        self.entries
+            .iter()
+            .skip(self.entries.len().saturating_sub(count))
+    }
+
+    /// The newest `count` lines to show, oldest first. Entries of a group
+    /// that fall in the same `window` of game time become one line, placed
+    /// where the latest of them was and counting them all; with no window
+    /// every entry is its own line. The entries themselves are untouched,
+    /// so [`EventLog::since`] still hands out each one.
+    pub fn coalesced(&self, count: usize, window: Option<Duration>) -> Vec<LogEntry> {
+        let Some(window) = window.filter(|w| !w.is_zero()) else {
+            return self.recent(count).cloned().collect();
+        };
+
+        let mut lines: Vec<(LogEntry, usize)> = Vec::new();
+        let mut merged: HashMap<(&str, u128), usize> = HashMap::new();
+        for entry in self.entries.iter().rev() {
+            let Some(group) = entry.group.as_deref() else {
+                lines.push((entry.clone(), 1));
+                continue;
+            };
+            let bucket = entry.time.as_nanos() / window.as_nanos();
+            match merged.get(&(group, bucket)) {
+                Some(&line) => lines[line].1 += 1,
+                None => {
+                    merged.insert((group, bucket), lines.len());
+                    lines.push((entry.clone(), 1));
+                }
+            }
+        }
+
+        lines.truncate(count);
+        lines
+            .into_iter()
+            .rev()
+            .map(|(mut entry, times)| {
+                if let Some(group) = entry.group.as_ref().filter(|_| times > 1) {
+                    entry.message = format!("{} ({} times)", group, times);
+                }
+                entry
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_returns_newest_in_order() {
+        let mut log = EventLog::new();
+        for i in 0..5 {
+            log.push(Duration::from_secs(i), format!("event {}", i));
+        }
+
+        let recent: Vec<&str> = log.recent(2).map(|e| e.message.as_str()).collect();
+        assert_eq!(recent, vec!["event 3", "event 4"]);
+    }
+
+    #[test]
+    fn test_since_skips_entries_already_seen() {
+        let mut log = EventLog::new();
+        log.push(Duration::ZERO, "old");
+        let seen = log.pushed();
+        log.push(Duration::ZERO, "new");
+
+        let newer: Vec<&str> = log.since(seen).map(|e| e.message.as_str()).collect();
+        assert_eq!(newer, vec!["new"]);
+        assert_eq!(log.since(log.pushed()).count(), 0);
+    }
+
+    #[test]
+    fn test_grouped_entries_merge_within_a_day() {
+        let day = Calendar::DEFAULT_DAY_LENGTH;
+        let mut log = EventLog::new();
+        for hour in 0..14 {
+            let time = Duration::from_secs(hour * 3600);
+            log.push_grouped(time, "Price of grain rose in Market District", "grain up");
+        }
+        log.push(Duration::from_secs(20 * 3600), "Festival begins");
+        log.push_grouped(day, "Price of grain rose in Market District", "grain up");
+
+        let lines: Vec<String> = log
+            .coalesced(10, coalescing_window(50.0))
+            .into_iter()
+            .map(|entry| entry.message)
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Price of grain rose in Market District (14 times)",
+                "Festival begins",
+                "grain up",
+            ]
+        );
+
+        // Slow games and the export stream see every entry
+        assert_eq!(coalescing_window(1.0), None);
+        assert_eq!(log.coalesced(100, None).len(), 16);
+        assert_eq!(log.since(0).count(), 16);
+    }
+
+    #[test]
+    fn test_capacity_is_bounded() {
+        let mut log = EventLog::new();
+        for i in 0..(EVENT_LOG_CAPACITY + 10) {
+            log.push(Duration::ZERO, format!("event {}", i));
+        }
+
+        assert_eq!(log.len(), EVENT_LOG_CAPACITY);
+        assert_eq!(
+            log.recent(EVENT_LOG_CAPACITY).next().unwrap().message,
+            "event 10"
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! Dumping the world to files for analysis outside the game.
+//!
+//! A CSV export is one row per good on every market, ready for a
+//! spreadsheet or a dataframe. A JSON export holds the same markets along
+//! with galaxy-wide figures and every place as a tree from the solar systems
+//! down to the rooms, each with its population and, for local areas, the
+//! firms working there. Exporting the same world again after a few days
+//! gives a second sample to compare against.
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::result::{Error, Result};
+use crate::save::{data_dir, ensure_dir};
+use crate::zoom::ZoomLevel;
+
+use super::Simulation;
+use super::state::{EntityId, WorldState};
+
+/// Directory in the data directory exports with relative paths go to
+pub const EXPORT_DIR: &str = "exports";
+
+const CSV_HEADER: &str = "tick,date,area_id,area,good,price,stock,volume";
+
+/// What kind of file to write, going by its extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn of(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "csv" => Some(Self::Csv),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Everything a JSON export holds
+#[derive(Debug, Clone, Serialize)]
+pub struct WorldExport {
+    pub tick: u64,
+    pub date: String,
+    pub seed: u64,
+    pub money_supply: f64,
+    pub price_level: f64,
+    pub population: u64,
+    pub markets: Vec<MarketRecord>,
+    /// Solar systems, each holding the places inside it
+    pub places: Vec<PlaceRecord>,
+}
+
+/// One local area's market
+#[derive(Debug, Clone, Serialize)]
+pub struct MarketRecord {
+    pub area_id: EntityId,
+    pub area: String,
+    pub goods: Vec<GoodRecord>,
+}
+
+/// One good on a market
+#[derive(Debug, Clone, Serialize)]
+pub struct GoodRecord {
+    pub good: String,
+    pub price: f64,
+    pub stock: f64,
+    /// Units sold per hour over the latest hour recorded
+    pub volume: f64,
+}
+
+/// A place and everything placed inside it
+#[derive(Debug, Clone, Serialize)]
+pub struct PlaceRecord {
+    pub id: EntityId,
+    pub level: ZoomLevel,
+    pub name: String,
+    pub population: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub firms: Vec<FirmRecord>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<PlaceRecord>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FirmRecord {
+    pub id: EntityId,
+    pub name: String,
+    pub recipe: String,
+    pub workers: u32,
+    pub cash: f64,
+}
+
+impl WorldExport {
+    pub fn of(simulation: &Simulation) -> Self {
+        let world = simulation.world();
+        let economy = world.economy();
+        WorldExport {
+            tick: world.tick_count(),
+            date: simulation.time().current_date().to_string(),
+            seed: world.seed(),
+            money_supply: economy.money_supply().issued().amount(),
+            price_level: economy.money_supply().price_level(),
+            population: world.census().totals().population,
+            markets: markets(world),
+            places: places(world, ZoomLevel::SolarSystem, None),
+        }
+    }
+}
+
+fn markets(world: &WorldState) -> Vec<MarketRecord> {
+    let economy = world.economy();
+    economy
+        .markets()
+        .map(|(area_id, market)| MarketRecord {
+            area_id,
+            area: world.entity_name(ZoomLevel::LocalArea, area_id),
+            goods: economy
+                .catalog()
+                .iter()
+                .map(|(good, info)| GoodRecord {
+                    good: info.name.clone(),
+                    price: market.price(good).amount(),
+                    stock: market.stock(good),
+                    volume: economy
+                        .history()
+                        .series(area_id, good)
+                        .and_then(|series| series.latest())
+                        .map_or(0.0, |point| point.volume / point.hours.max(1) as f64),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn places(world: &WorldState, level: ZoomLevel, parent: Option<EntityId>) -> Vec<PlaceRecord> {
+    world
+        .placed_in(level, parent)
+        .into_iter()
+        .map(|(id, _)| {
+            let firms = if level == ZoomLevel::LocalArea {
+                let economy = world.economy();
+                economy
+                    .firms_in_area(id)
+                    .map(|firm| FirmRecord {
+                        id: firm.id,
+                        name: firm.name.clone(),
+                        recipe: economy
+                            .recipes()
+                            .get(firm.recipe)
+                            .map_or_else(String::new, |recipe| recipe.name.clone()),
+                        workers: firm.workers,
+                        cash: firm.cash.amount(),
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            PlaceRecord {
+                id,
+                level,
+                name: world.entity_name(level, id),
+                population: world.population(level, id),
+                firms,
+                children: level
+                    .zoom_in()
+                    .map_or_else(Vec::new, |inner| places(world, inner, Some(id))),
+            }
+        })
+        .collect()
+}
+
+/// Where an export named `file` goes: as given if absolute, otherwise in
+/// the exports directory
+pub fn export_path(file: &str) -> PathBuf {
+    let path = Path::new(file);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        data_dir().join(EXPORT_DIR).join(path)
+    }
+}
+
+/// Market prices as CSV, one row per good on each market
+pub fn prices_csv(simulation: &Simulation) -> String {
+    let export = WorldExport::of(simulation);
+    let mut lines = vec![String::from(CSV_HEADER)];
+    for market in &export.markets {
+        for good in &market.goods {
+            lines.push(
+                [
+                    export.tick.to_string(),
+                    csv_field(&export.date),
+                    market.area_id.to_string(),
+                    csv_field(&market.area),
+                    csv_field(&good.good),
+                    good.price.to_string(),
+                    good.stock.to_string(),
+                    good.volume.to_string(),
+                ]
+                .join(","),
+            );
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Quote a field if it holds anything CSV treats specially
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Write the world to `path` in the given format, creating the directory
+/// it goes in if needed
+pub fn export(simulation: &Simulation, path: &Path, format: ExportFormat) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        ensure_dir(dir)?;
+    }
+    let contents = match format {
+        ExportFormat::Csv => prices_csv(simulation),
+        ExportFormat::Json => serde_json::to_string_pretty(&WorldExport::of(simulation))?,
+    };
+    fs::write(path, contents).map_err(|source| Error::SaveFileError {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::save::test_dir;
+
+    #[test]
+    fn test_exports_hold_every_market_and_place() {
+        let simulation = Simulation::new();
+        let dir = test_dir("export");
+
+        let csv_path = dir.join("prices.csv");
+        export(&simulation, &csv_path, ExportFormat::Csv).unwrap();
+        let csv = fs::read_to_string(&csv_path).unwrap();
+        let mut rows = csv.lines();
+        assert_eq!(rows.next(), Some(CSV_HEADER));
+        let world = simulation.world();
+        let goods = world.economy().catalog().iter().count();
+        assert_eq!(rows.count(), world.economy().markets().count() * goods);
+
+        let json_path = dir.join("nested/world.json");
+        export(&simulation, &json_path, ExportFormat::Json).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(json["tick"], world.tick_count());
+        let systems = json["places"].as_array().unwrap();
+        assert_eq!(systems.len(), world.ids(ZoomLevel::SolarSystem).len());
+        assert_eq!(systems[0]["level"], "SolarSystem");
+        assert!(
+            systems[0]["children"]
+                .as_array()
+                .is_some_and(|c| !c.is_empty())
+        );
+    }
+
+    #[test]
+    fn test_formats_follow_the_extension() {
+        assert_eq!(
+            ExportFormat::of(Path::new("prices.CSV")),
+            Some(ExportFormat::Csv)
+        );
+        assert_eq!(
+            ExportFormat::of(Path::new("runs/world.json")),
+            Some(ExportFormat::Json)
+        );
+        assert_eq!(ExportFormat::of(Path::new("world.txt")), None);
+        assert_eq!(ExportFormat::of(Path::new("world")), None);
+        assert_eq!(
+            csv_field("Port \"Blue\", east"),
+            "\"Port \"\"Blue\"\", east\""
+        );
+    }
+}
@@ -0,0 +1,186 @@
+//! Seasonal festivals that change what people buy.
+//!
+//! A festival falls on the same dates every year, either on one planet or
+//! on all of them. While it lasts, households in the planet's local areas
+//! take extra feast goods from their market on top of their usual food,
+//! so feast goods run short and their prices spike.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::economy::Economy;
+use crate::time::{Calendar, CalendarDate, DAYS_PER_YEAR};
+
+use super::state::EntityId;
+
+/// A yearly festival
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Festival {
+    pub name: String,
+    /// The planet that celebrates, or None if every planet does
+    #[serde(default)]
+    pub planet: Option<EntityId>,
+    /// First day, 1-based
+    pub month: u32,
+    pub day: u32,
+    /// How many days it lasts
+    pub days: u32,
+    /// Names of the goods households feast on
+    pub goods: Vec<String>,
+    /// Extra units of each feast good every worker takes per hour
+    pub demand: f64,
+}
+
+impl Festival {
+    /// Festivals every new world celebrates
+    pub fn defaults() -> Vec<Festival> {
+        vec![
+            Festival {
+                name: String::from("Landing Day"),
+                planet: Some(1),
+                month: 4,
+                day: 5,
+                days: 2,
+                goods: vec![String::from("bread")],
+                demand: 0.1,
+            },
+            Festival {
+                name: String::from("Harvest Feast"),
+                planet: None,
+                month: 9,
+                day: 10,
+                days: 3,
+                goods: vec![String::from("bread"), String::from("grain")],
+                demand: 0.2,
+            },
+            Festival {
+                name: String::from("Midwinter Lights"),
+                planet: None,
+                month: 12,
+                day: 26,
+                days: 7,
+                goods: vec![String::from("furniture")],
+                demand: 0.05,
+            },
+        ]
+    }
+
+    /// Whether a planet takes part
+    pub fn is_celebrated_on(&self, planet: Option<EntityId>) -> bool {
+        match self.planet {
+            Some(id) => planet == Some(id),
+            None => planet.is_some(),
+        }
+    }
+
+    fn first_day(&self) -> CalendarDate {
+        CalendarDate {
+            year: 0,
+            month: self.month,
+            day: self.day,
+        }
+    }
+
+    /// Whether the festival is on during a date, including ones that carry
+    /// over into the next year
+    pub fn is_on(&self, date: CalendarDate) -> bool {
+        let start = self.first_day().day_of_year();
+        let since = (date.day_of_year() + DAYS_PER_YEAR - start) % DAYS_PER_YEAR;
+        since < self.days
+    }
+
+    /// When the festival next begins after `now`
+    pub fn next_start(&self, now: Duration, calendar: &Calendar) -> Duration {
+        let this_year = calendar.start_of(CalendarDate {
+            year: calendar.date_at(now).year,
+            ..self.first_day()
+        });
+        if this_year > now {
+            this_year
+        } else {
+            this_year + calendar.day_length() * DAYS_PER_YEAR
+        }
+    }
+
+    /// Take a feast's worth of goods for `workers` over `hours` from an
+    /// area's market, as far as its stock goes
+    pub fn feast(&self, economy: &mut Economy, area_id: EntityId, workers: u32, hours: f64) {
+        let goods: Vec<_> = self
+            .goods
+            .iter()
+            .filter_map(|name| economy.catalog().id(name))
+            .collect();
+        let Some(market) = economy.market_mut(area_id) else {
+            return;
+        };
+        let wanted = workers as f64 * self.demand * hours;
+        for id in goods {
+            market.sell(id, wanted);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(month: u32, day: u32) -> CalendarDate {
+        CalendarDate {
+            year: 3000,
+            month,
+            day,
+        }
+    }
+
+    #[test]
+    fn test_festival_spans_its_days() {
+        let harvest = &Festival::defaults()[1];
+        assert!(!harvest.is_on(date(9, 9)));
+        assert!(harvest.is_on(date(9, 10)) && harvest.is_on(date(9, 12)));
+        assert!(!harvest.is_on(date(9, 13)));
+
+        // Midwinter runs from Dec 26 into the new year
+        let midwinter = &Festival::defaults()[2];
+        assert!(midwinter.is_on(date(12, 30)) && midwinter.is_on(date(1, 2)));
+        assert!(!midwinter.is_on(date(1, 3)));
+    }
+
+    #[test]
+    fn test_next_start_rolls_into_next_year() {
+        let calendar = Calendar::default();
+        let harvest = &Festival::defaults()[1];
+        let start = calendar.start_of(date(9, 10));
+        assert_eq!(harvest.next_start(Duration::ZERO, &calendar), start);
+        assert_eq!(
+            calendar.date_at(harvest.next_start(start, &calendar)),
+            CalendarDate {
+                year: 3001,
+                month: 9,
+                day: 10
+            }
+        );
+    }
+
+    #[test]
+    fn test_planet_festivals_stay_local() {
+        let landing = &Festival::defaults()[0];
+        assert!(landing.is_celebrated_on(Some(1)));
+        assert!(!landing.is_celebrated_on(Some(2)));
+        assert!(Festival::defaults()[1].is_celebrated_on(Some(2)));
+    }
+
+    #[test]
+    fn test_feast_draws_down_feast_goods() {
+        let mut economy = Economy::new();
+        economy.open_market(1);
+        let bread = economy.catalog().id("bread").unwrap();
+        let ore = economy.catalog().id("ore").unwrap();
+        let market = economy.market(1).unwrap();
+        let (bread_before, ore_before) = (market.stock(bread), market.stock(ore));
+
+        Festival::defaults()[1].feast(&mut economy, 1, 10, 2.0);
+        let market = economy.market(1).unwrap();
+        assert!((market.stock(bread) - (bread_before - 4.0)).abs() < 1e-9);
+        assert_eq!(market.stock(ore), ore_before);
+    }
+}
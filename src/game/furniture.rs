@@ -0,0 +1,187 @@
+//! Furniture in rooms and what the player can do with it.
+//!
+//! A room is furnished for its type when it is spawned: market stalls in a
+//! commercial room, beds in a residential one and workbenches in an
+//! industrial one, each on its own spot of the room's map. Pressing ENTER
+//! over a piece opens a menu of what it offers. At a stall the player buys
+//! lots of goods from the area's market into their pack, paying from their
+//! purse, or sells back what they carry. A bed passes the night. A
+//! workbench passes a shift, paid at the area's wage.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error as ThisError;
+
+use crate::economy::{Capacity, GoodId, Inventory};
+use crate::zoom::Bounds;
+
+use super::rng::Rng;
+
+/// Units bought or sold at a stall at a time
+pub const TRADE_LOT: f64 = 10.0;
+
+/// Hours a night in a bed lasts
+pub const REST_HOURS: u32 = 8;
+
+/// Hours a shift at a workbench lasts
+pub const SHIFT_HOURS: u32 = 8;
+
+/// What the player can carry in their pack
+pub const PACK: Capacity = Capacity {
+    mass: Some(200.0),
+    volume: Some(300.0),
+};
+
+/// Most pieces of furniture a spawned room gets
+const MAX_PIECES: u64 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FurnitureKind {
+    MarketStall,
+    Bed,
+    Workbench,
+}
+
+impl FurnitureKind {
+    pub const ALL: [FurnitureKind; 3] = [
+        FurnitureKind::MarketStall,
+        FurnitureKind::Bed,
+        FurnitureKind::Workbench,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FurnitureKind::MarketStall => "market stall",
+            FurnitureKind::Bed => "bed",
+            FurnitureKind::Workbench => "workbench",
+        }
+    }
+
+    /// The furniture a type of room is furnished with, if any
+    pub fn for_room(room_type: &str) -> Option<FurnitureKind> {
+        match room_type {
+            "Commercial" => Some(FurnitureKind::MarketStall),
+            "Residential" => Some(FurnitureKind::Bed),
+            "Industrial" => Some(FurnitureKind::Workbench),
+            _ => None,
+        }
+    }
+}
+
+/// A piece of furniture on a room's map
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Furnishing {
+    pub kind: FurnitureKind,
+    pub coords: (i32, i32),
+}
+
+impl Furnishing {
+    pub fn new(kind: FurnitureKind, coords: (i32, i32)) -> Self {
+        Self { kind, coords }
+    }
+}
+
+/// Furnish a room of a type with up to [`MAX_PIECES`] pieces on random
+/// spots of a map with `bounds`, leaving its center, where the player
+/// walks in, clear
+pub fn furnish(room_type: &str, bounds: Bounds, rng: &mut Rng) -> Vec<Furnishing> {
+    let Some(kind) = FurnitureKind::for_room(room_type) else {
+        return Vec::new();
+    };
+    let (min, max) = (bounds.min(), bounds.max());
+    let mut free: Vec<(i32, i32)> = (min.1..=max.1)
+        .flat_map(|y| (min.0..=max.0).map(move |x| (x, y)))
+        .filter(|&spot| spot != (0, 0))
+        .collect();
+    let mut pieces = Vec::new();
+    for _ in 0..rng.range(1, MAX_PIECES + 1) {
+        if free.is_empty() {
+            break;
+        }
+        let spot = free.swap_remove(rng.range(0, free.len() as u64) as usize);
+        pieces.push(Furnishing::new(kind, spot));
+    }
+    pieces
+}
+
+/// An empty pack the size of [`PACK`]
+pub fn player_pack() -> Inventory {
+    Inventory::new(PACK)
+}
+
+/// Something the player does at a piece of furniture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interaction {
+    /// Buy a lot of a good at a stall
+    Buy(GoodId),
+    /// Sell a lot of a good from the pack at a stall
+    Sell(GoodId),
+    /// Sleep in a bed through the night
+    Rest,
+    /// Work a shift at a workbench for the area's wage
+    Work,
+}
+
+impl Interaction {
+    /// The furniture the interaction needs
+    pub fn furniture(self) -> FurnitureKind {
+        match self {
+            Interaction::Buy(_) | Interaction::Sell(_) => FurnitureKind::MarketStall,
+            Interaction::Rest => FurnitureKind::Bed,
+            Interaction::Work => FurnitureKind::Workbench,
+        }
+    }
+
+    /// Game hours the interaction takes
+    pub fn hours(self) -> u32 {
+        match self {
+            Interaction::Buy(_) | Interaction::Sell(_) => 0,
+            Interaction::Rest => REST_HOURS,
+            Interaction::Work => SHIFT_HOURS,
+        }
+    }
+}
+
+#[derive(ThisError, Debug, Clone, PartialEq)]
+pub enum InteractionError {
+    #[error("there is no furniture here")]
+    NoFurniture,
+    #[error("a {0} can't be used that way")]
+    WrongFurniture(&'static str),
+    #[error("this room's area has no market")]
+    NoMarket,
+    #[error("the market has no {0} to sell")]
+    OutOfStock(String),
+    #[error("you can't afford any {0}")]
+    CannotAfford(String),
+    #[error("your pack has no room for {0}")]
+    PackFull(String),
+    #[error("you carry no {0}")]
+    NothingToSell(String),
+    #[error("the market can't pay for {0}")]
+    MarketBroke(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zoom::Edge;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_rooms_are_furnished_for_their_type() {
+        let bounds = Bounds::new(9, 5, Edge::Stop);
+        let mut rng = Rng::new(5);
+        for _ in 0..50 {
+            let pieces = furnish("Residential", bounds, &mut rng);
+            assert!((1..=MAX_PIECES as usize).contains(&pieces.len()));
+            assert!(pieces.iter().all(|piece| piece.kind == FurnitureKind::Bed
+                && piece.coords != (0, 0)
+                && bounds.contains(piece.coords)));
+            let spots: HashSet<_> = pieces.iter().map(|piece| piece.coords).collect();
+            assert_eq!(spots.len(), pieces.len());
+        }
+        assert!(furnish("Storage", bounds, &mut rng).is_empty());
+        // A room too small for anything but its center stays empty
+        assert!(furnish("Commercial", Bounds::new(1, 1, Edge::Stop), &mut rng).is_empty());
+    }
+}
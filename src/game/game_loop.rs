@@ -1,274 +1,3543 @@
-use crate::input::{InputAction, InputHandler};
-use crate::render::{Canvas, RenderEngine};
+#[cfg(feature = "serve")]
+use crate::api::Observer;
+use crate::economy::{FiscalPolicy, MAX_POLICY_RATE, Money};
+use crate::editor::{AttributeForm, Editor, Inspector};
+use crate::input::{InputAction, InputHandler, InputMode};
+use crate::logging::LogGuard;
+use crate::modding::{ModHost, Query};
+use crate::render::charts;
+use crate::render::notifications::Notifications;
+use crate::render::{
+    GlyphSet, Glyphs, Layout, Line, MIN_HEIGHT, MIN_WIDTH, Preset, Rect, Renderer, Tone, Trend,
+    spans,
+};
 use crate::result::Result;
-use crate::time::TimeController;
-use crate::zoom::{Direction, Position, ZoomLevel, ZoomManager};
+use crate::save::compare;
+use crate::save::{CustomOverlay, SaveGame, SaveManager, Scenario, Settings, SettingsOverrides};
+use crate::time::{FrameBreakdown, FramePacer, MAX_SPEED, Profiler};
+use crate::ui::{Clock, Field, Form, Progress};
+use crate::zoom::{Direction, Position, ZoomLevel};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
 use std::thread::sleep;
+use std::time::{Duration, Instant};
 
-use super::WorldState;
+use super::bookmarks::BOOKMARK_SLOTS;
+use super::campaign::Interstitial;
+use super::commands::History;
+use super::events::coalescing_window;
+use super::menus::{MenuChoice, MenuScreen};
+use super::polities;
+use super::presets::{GamePreset, PRESETS};
+use super::screens::{
+    self, Context, Handled, Placement, Screen, ScreenKind, ScreenStack, draw_panel,
+};
+use super::search::Search;
+use super::shutdown::ShutdownReport;
+use super::stars;
+use super::state::{DEFAULT_SEED, EntityId};
+use super::stats::GALAXY_ID;
+use super::terrain::{Biome, REGION_MAP_HEIGHT, REGION_MAP_WIDTH};
+use super::travel::{Journey, TravelError};
+use super::tutorial::Tutorial;
+use super::{Alert, DEFAULT_TARGET_FPS, Severity, Simulation, WorldState};
+
+/// Saves offered by the load menu, newest first
+const LOADABLE_SAVES: usize = 9;
+
+/// Event log lines shown beside the zoom view
+const EVENT_PANEL_ROWS: usize = 8;
+
+/// Size of the editor's map grid, in characters, inside the zoom view box
+const EDITOR_MAP_WIDTH: usize = 33;
+const EDITOR_MAP_ROWS: usize = 7;
+
+/// Columns between the zoom view box's borders
+const VIEW_INNER_WIDTH: usize = 36;
+
+/// Characters listed beside the local area and room art
+const CHARACTER_ROWS: usize = 5;
+
+/// How many cells of space around a system on the galaxy map its polity
+/// holds
+const SYSTEM_REACH: i32 = 2;
 
 struct RenderState<'a> {
     fps: f32,
-    show_help: bool,
+    /// Frames are taking longer than the frame rate allows
+    is_behind: bool,
+    /// Whether the last frame had more ticks due than it could run
+    is_lagging: bool,
     time_str: String,
+    date_str: String,
     is_paused: bool,
+    is_turbo: bool,
     speed: f64,
     zoom_level: ZoomLevel,
     position: Position,
+    /// Where a free camera has the map centered
+    camera: Option<(i32, i32)>,
     tick_count: u64,
     entity_name: String,
+    /// Names from the galaxy down to the place in view
+    location_path: Vec<String>,
     entity_count: usize,
+    money_summary: Line,
+    market_summary: Option<String>,
+    /// Banner for the festivals under way on the planet in view
+    festival: Option<Line>,
+    recent_events: Vec<String>,
+    quit_prompt: bool,
+    /// Title and field lines of the open form
+    form: Option<(String, Vec<String>)>,
+    /// Bookmark slots, when the list is open
+    bookmarks: Option<Vec<String>>,
+    /// The query and its matches, while the search box is open
+    search: Option<Vec<String>>,
+    /// Map grid and palette line while the sandbox editor is open
+    editor_map: Option<Vec<String>>,
+    palette: Option<String>,
+    /// Help, the dashboard and the other screens open over the game
+    screens: &'a ScreenStack,
+    /// Colored terrain tiles, at planet and region zoom
+    surface: Option<Vec<Line>>,
+    /// What the overlay on the planet map shows and the range it spans
+    legend: Option<String>,
+    /// Current campaign objective
+    objective: Option<String>,
+    /// Time left to meet the objective, when its chapter has a deadline
+    deadline: Option<Progress>,
+    /// How far an ongoing fast-forward has got
+    fast_forward: Option<Progress>,
+    /// How far the player's journey has got, while they are in transit
+    journey: Option<Progress>,
+    interstitial: Option<&'a Interstitial>,
+    /// What the characters in view are doing
+    characters: Vec<String>,
+    /// Notices still on screen, newest first
+    toasts: Vec<Line>,
+    /// The tutorial's current step, until it is finished or skipped
+    tutorial: Option<String>,
     _phantom: std::marker::PhantomData<&'a ()>,
 }
 
+/// A form on screen and what committing it changes
+enum ActiveForm {
+    Attributes(AttributeForm),
+    /// Money growth, and the taxes of the polity in view if one governs it
+    Policy(Form, Option<EntityId>),
+    /// This game's settings, or every game's if `profile`
+    Settings {
+        form: Form,
+        profile: bool,
+    },
+    Business(Form),
+    Inspector(Inspector),
+    Overlay(Form),
+}
+
+/// A profile overlay drawn over the planet map
+struct ShownOverlay {
+    /// Its place among the profile's overlays
+    index: usize,
+    name: String,
+    query: Query,
+}
+
+impl ActiveForm {
+    fn form(&self) -> &Form {
+        match self {
+            ActiveForm::Attributes(attributes) => attributes.form(),
+            ActiveForm::Inspector(inspector) => inspector.form(),
+            ActiveForm::Policy(form, _)
+            | ActiveForm::Settings { form, .. }
+            | ActiveForm::Business(form)
+            | ActiveForm::Overlay(form) => form,
+        }
+    }
+
+    fn form_mut(&mut self) -> &mut Form {
+        match self {
+            ActiveForm::Attributes(attributes) => attributes.form_mut(),
+            ActiveForm::Inspector(inspector) => inspector.form_mut(),
+            ActiveForm::Policy(form, _)
+            | ActiveForm::Settings { form, .. }
+            | ActiveForm::Business(form)
+            | ActiveForm::Overlay(form) => form,
+        }
+    }
+}
+
 pub struct GameLoop<'a> {
-    render_engine: RenderEngine<'a>,
-    time_controller: TimeController,
-    zoom_manager: ZoomManager,
-    world_state: WorldState,
+    renderer: Box<dyn Renderer + 'a>,
+    simulation: Simulation,
     input_handler: InputHandler,
+    saves: SaveManager,
+    editor: Option<Editor>,
+    form: Option<ActiveForm>,
+    /// Help, the dashboard, the console and the other screens now open
+    screens: ScreenStack,
+    /// The search box, while it is open
+    search: Option<Search>,
+    overlay: Option<ShownOverlay>,
+    /// While the camera is free of the player, the zoom level it was
+    /// freed at and the spot the map is centered on there
+    camera: Option<(ZoomLevel, (i32, i32))>,
+    /// The player's progress through the tutorial, as kept in the profile
+    tutorial: Tutorial,
+    mods: ModHost,
+    /// Map symbols, from the profile's glyph set and any mod overrides
+    glyphs: Glyphs,
+    pacer: FramePacer,
+    /// Frame and tick timings for the session
+    profiler: Profiler,
+    /// The profile's settings with the current save's overrides applied
+    settings: Settings,
+    /// Chapter screen on display, and whether the clock was running before
+    /// it paused the game
+    interstitial: Option<(Interstitial, bool)>,
+    /// Notices on screen for a few seconds after they are raised
+    notifications: Notifications,
+    /// Least severe notice shown as a toast, or None to show none
+    toast_level: Option<Severity>,
+    /// Seed new games are generated from
+    new_game_seed: u64,
+    /// Whether the clock was running when the pause menu stopped it
+    resume_on_close: bool,
+    /// Whether a game has been started or loaded, rather than the main
+    /// menu showing over the sample world
+    playing: bool,
+    /// Where the world is streamed each tick, when serving
+    #[cfg(feature = "serve")]
+    observer: Option<Observer>,
+    /// Keeps the log file's writer running until shutdown flushes it
+    log_guard: Option<LogGuard>,
+    target_fps: u32,
+    /// Whether anything changed since the last save
+    dirty: bool,
+    save_on_exit: bool,
 }
 
 impl<'a> GameLoop<'a> {
-    pub fn new(render_engine: RenderEngine<'a>) -> Self {
-        Self {
-            render_engine,
-            time_controller: TimeController::new(30),
-            zoom_manager: ZoomManager::new(),
-            world_state: WorldState::new(),
+    pub fn new(renderer: impl Renderer + 'a, saves: SaveManager) -> Self {
+        let glyphs = Glyphs::new(saves.profile().glyphs);
+        let tutorial = saves.profile().tutorial;
+        let mut game_loop = Self {
+            renderer: Box::new(renderer),
+            simulation: Simulation::new(),
             input_handler: InputHandler::new(),
+            saves,
+            editor: None,
+            form: None,
+            screens: ScreenStack::new(),
+            search: None,
+            overlay: None,
+            camera: None,
+            tutorial,
+            mods: ModHost::new(),
+            glyphs,
+            pacer: FramePacer::new(),
+            profiler: Profiler::new(),
+            settings: Settings::default(),
+            interstitial: None,
+            notifications: Notifications::new(),
+            toast_level: Some(Severity::Info),
+            new_game_seed: DEFAULT_SEED,
+            resume_on_close: false,
+            playing: false,
+            #[cfg(feature = "serve")]
+            observer: None,
+            log_guard: None,
+            target_fps: DEFAULT_TARGET_FPS,
+            dirty: false,
+            save_on_exit: false,
+        };
+        game_loop.apply_settings();
+        game_loop
+    }
+
+    /// Add a message to the in-game event log
+    pub fn log(&mut self, message: impl Into<String>) {
+        self.simulation.world_mut().log(message);
+    }
+
+    /// Log a message and show it as a toast
+    pub fn notify(&mut self, severity: Severity, message: impl Into<String>) {
+        self.simulation.world_mut().notify(severity, message);
+        self.show_notices();
+    }
+
+    /// Move notices the world has raised onto the screen
+    fn show_notices(&mut self) {
+        let now = Instant::now();
+        for notice in self.simulation.world_mut().take_notices() {
+            if self
+                .toast_level
+                .is_some_and(|level| notice.severity >= level)
+            {
+                self.notifications.push(notice, now);
+            }
+        }
+    }
+
+    /// Load the mod scripts in a directory, reporting the outcome in the
+    /// event log
+    pub fn load_mods(&mut self, dir: &Path) {
+        let world = self.simulation.world_mut();
+        for error in self.mods.load_dir(dir, world) {
+            world.log(error.to_string());
+        }
+        if !self.mods.is_empty() {
+            world.log(format!("Loaded {} mod(s)", self.mods.len()));
+        }
+        self.apply_mod_glyphs();
+    }
+
+    fn apply_mod_glyphs(&mut self) {
+        for (key, glyph) in self.mods.take_glyphs() {
+            self.glyphs.override_glyph(&key, glyph);
+        }
+    }
+
+    /// Start a fresh session in a scenario's world
+    pub fn load_scenario(&mut self, scenario: Scenario) {
+        self.begin(Simulation::from_scenario(scenario));
+    }
+
+    /// Start a fresh session in a world
+    pub fn start(&mut self, world: WorldState) {
+        self.begin(Simulation::with_world(world));
+    }
+
+    fn begin(&mut self, simulation: Simulation) {
+        self.simulation = simulation;
+        self.camera = None;
+        self.saves.reset_autosave(self.simulation.simulation_time());
+        self.apply_settings();
+        self.dirty = false;
+        self.playing = true;
+    }
+
+    /// Show the main menu in place of the game, generating new games from
+    /// `seed`
+    pub fn open_main_menu(&mut self, seed: u64) {
+        self.new_game_seed = seed;
+        self.open_screen(Box::new(self.main_menu()));
+    }
+
+    /// The main menu, offering to continue from the latest save if there
+    /// is one
+    fn main_menu(&self) -> MenuScreen {
+        MenuScreen::main(self.playing, self.saves.has_last_save())
+    }
+
+    /// Ask which preset to start a new game in, generating its world from
+    /// `seed` once one is picked
+    pub fn choose_preset(&mut self, seed: u64) {
+        self.new_game_seed = seed;
+        let mut screen = MenuScreen::presets(MenuChoice::MainMenu);
+        screen.select(&MenuChoice::Preset(
+            PRESETS
+                .iter()
+                .position(|preset| *preset == GamePreset::default())
+                .unwrap_or_default(),
+        ));
+        self.open_screen(Box::new(screen));
+    }
+
+    /// Stop the clock behind the pause menu
+    fn open_pause_menu(&mut self) {
+        self.resume_on_close = !self.simulation.time().is_paused();
+        if self.resume_on_close {
+            self.simulation.time_mut().toggle_pause();
+        }
+        self.open_screen(Box::new(MenuScreen::pause()));
+    }
+
+    /// Offer what the piece of furniture under the cursor can be used for
+    fn open_furnishing(&mut self) {
+        if let Some((_, piece)) = self.simulation.furnishing_in_view() {
+            self.open_screen(Box::new(MenuScreen::furnishing(&self.simulation, piece)));
+        }
+    }
+
+    /// Keys while a menu is open. Returns true once the player has chosen
+    /// to quit.
+    fn handle_menu(&mut self, action: InputAction) -> bool {
+        match self.offer_screens(action) {
+            Handled::Picked(choice) => self.pick(choice),
+            _ => false,
+        }
+    }
+
+    /// Carry out a menu choice. Returns true if it quits the game.
+    fn pick(&mut self, choice: MenuChoice) -> bool {
+        match choice {
+            MenuChoice::Resume => self.resume(),
+            MenuChoice::MainMenu => self.open_screen(Box::new(self.main_menu())),
+            MenuChoice::NewGame => self.choose_preset(self.new_game_seed),
+            MenuChoice::Preset(index) => {
+                let preset = PRESETS[index.min(PRESETS.len() - 1)];
+                self.resume_on_close = false;
+                self.start(preset.generate(self.new_game_seed));
+                self.log(format!("Started a {} game", preset.name));
+            }
+            MenuChoice::Load => {
+                let saves = compare::recent_saves(self.saves.dir(), LOADABLE_SAVES);
+                if saves.is_empty() {
+                    self.notify(Severity::Info, "No saved games to load");
+                    self.pick(MenuChoice::MainMenu);
+                } else {
+                    let back = MenuChoice::MainMenu;
+                    self.open_screen(Box::new(MenuScreen::saves(saves, back)));
+                }
+            }
+            MenuChoice::Continue => match self.saves.load_last() {
+                Some(Ok(save)) => {
+                    self.resume_on_close = false;
+                    self.load(save);
+                    self.log("Resumed saved game");
+                }
+                Some(Err(error)) => {
+                    self.notify(Severity::Warning, format!("Load failed: {}", error));
+                    self.pick(MenuChoice::MainMenu);
+                }
+                None => {
+                    self.pick(MenuChoice::MainMenu);
+                }
+            },
+            MenuChoice::LoadSave(path) => match SaveGame::read_from(&path) {
+                Ok(save) => {
+                    self.resume_on_close = false;
+                    self.load(save);
+                    self.log(format!("Loaded {}", path.display()));
+                }
+                Err(error) => {
+                    self.notify(Severity::Warning, format!("Load failed: {}", error));
+                    self.pick(MenuChoice::Load);
+                }
+            },
+            MenuChoice::Save => {
+                let message = match self.save_game() {
+                    Ok(()) => String::from("Game saved"),
+                    Err(error) => format!("Save failed: {}", error),
+                };
+                self.log(message);
+                self.resume();
+            }
+            MenuChoice::GameSettings => {
+                self.resume();
+                self.open_settings_form(false);
+            }
+            MenuChoice::Settings => self.open_settings_form(true),
+            MenuChoice::Interact(interaction) => match self.simulation.interact(interaction) {
+                Ok(done) => {
+                    self.dirty = true;
+                    self.log(done);
+                }
+                Err(error) => self.notify(Severity::Warning, format!("Can't do that: {}", error)),
+            },
+            MenuChoice::ShowDiff(diff) => {
+                self.open_screen(Box::new(screens::DiffReport::new(diff)))
+            }
+            MenuChoice::Quit => {
+                if !self.dirty {
+                    return true;
+                }
+                self.resume();
+                self.input_handler.set_mode(InputMode::Prompt);
+            }
+        }
+        false
+    }
+
+    /// Start the clock again if the pause menu stopped it
+    fn resume(&mut self) {
+        if std::mem::take(&mut self.resume_on_close) && self.simulation.time().is_paused() {
+            self.simulation.time_mut().toggle_pause();
         }
     }
 
-    pub fn run(mut self) -> Result<()> {
+    /// Frames per second to draw at, kept across loads
+    pub fn set_target_fps(&mut self, fps: u32) {
+        self.target_fps = fps;
+        self.simulation.time_mut().set_target_fps(fps);
+    }
+
+    /// Cells the cursor moves per second while an arrow key is held
+    pub fn set_move_rate(&mut self, rate: f64) {
+        self.input_handler.set_move_rate(rate);
+    }
+
+    /// Least severe notice to show as a toast, or None for no toasts.
+    /// Every notice is still logged.
+    pub fn set_toast_level(&mut self, level: Option<Severity>) {
+        self.toast_level = level;
+    }
+
+    /// Keep the log file writer alive until shutdown, which flushes it
+    pub fn keep_log(&mut self, guard: LogGuard) {
+        self.log_guard = Some(guard);
+    }
+
+    /// Stream the world to an observer's clients after every tick
+    #[cfg(feature = "serve")]
+    pub fn serve(&mut self, observer: Observer) {
+        self.observer = Some(observer);
+    }
+
+    /// Draw map symbols from this set rather than the profile's, as when
+    /// the terminal can't show the Unicode ones
+    pub fn set_glyph_set(&mut self, set: GlyphSet) {
+        self.glyphs.set_glyph_set(set);
+    }
+
+    /// Replace the running session with a saved one
+    pub fn load(&mut self, save: SaveGame) {
+        self.saves.reset_autosave(save.simulation_time);
+        self.simulation = Simulation::from_save(save);
+        self.camera = None;
+        self.apply_settings();
+        self.dirty = false;
+        self.playing = true;
+    }
+
+    /// Resolve the session's settings and push them to the parts of the
+    /// game they control
+    fn apply_settings(&mut self) {
+        let profile = self.saves.profile().settings;
+        self.settings = self.simulation.world().settings().apply(&profile);
+        self.simulation
+            .world_mut()
+            .economy_mut()
+            .set_difficulty(self.settings.difficulty());
+        self.simulation
+            .world_mut()
+            .economy_mut()
+            .set_history_retention(self.settings.history_retention());
+        self.saves
+            .set_autosave_interval(self.settings.autosave_interval());
+        self.simulation.time_mut().set_target_fps(self.target_fps);
+    }
+
+    /// Play until the player quits, returning the session's frame and tick
+    /// timings. Shutdown runs however the game ends, and an error that
+    /// ended it is returned once the terminal is restored.
+    pub fn run(mut self) -> Result<Profiler> {
+        let outcome = self.play();
+        self.shutdown(outcome)
+    }
+
+    /// Run frames until the player quits or one fails
+    fn play(&mut self) -> Result<()> {
         loop {
+            let frame_start = Instant::now();
             if self.handle_input()? {
                 break;
             }
+            let input = frame_start.elapsed();
 
-            if !self.time_controller.is_paused() {
-                self.update();
-            }
+            let ticks_before = self.simulation.world().tick_count();
+            let update_start = Instant::now();
+            self.update();
+            let update = update_start.elapsed();
+            let ticks = self.simulation.world().tick_count() - ticks_before;
+
+            let render_start = Instant::now();
+            let present = if self.pacer.should_render() {
+                self.render()?
+            } else {
+                Duration::ZERO
+            };
+            let render = render_start.elapsed().saturating_sub(present);
+
+            let budget = self.simulation.time().target_frame_duration();
+            let work = frame_start.elapsed();
+            self.profiler.record_frame(work);
+            self.profiler.record_breakdown(FrameBreakdown {
+                input,
+                update,
+                render,
+                present,
+                ticks: ticks.min(u32::MAX as u64) as u32,
+            });
+            sleep(self.pacer.finish_frame(work, budget));
+        }
+
+        Ok(())
+    }
 
-            self.render()?;
+    /// Run each shutdown stage in order, show any failures, then restore the
+    /// terminal. New subsystems that need cleanup add a stage here.
+    fn shutdown(mut self, outcome: Result<()>) -> Result<Profiler> {
+        let mut report = ShutdownReport::new();
 
-            sleep(self.time_controller.target_frame_duration());
+        if let Err(error) = &outcome {
+            tracing::error!("Game loop failed: {}", error);
+            report.record_error("Game loop", error);
+            // Nobody was asked, so keep the game in an autosave slot
+            // rather than over the quit save
+            if self.dirty {
+                let save = self.simulation.to_save();
+                report.record("Autosave", self.saves.autosave(&save).map(|_| ()));
+            }
+        } else if self.save_on_exit {
+            report.record("Save game", self.save_game());
+        }
+        #[cfg(feature = "serve")]
+        if let Some(observer) = self.observer.take() {
+            report.record("Close observer", observer.close().map_err(Into::into));
         }
+        if let Some(guard) = self.log_guard.take() {
+            // Dropping the guard writes out whatever is still buffered
+            drop(guard);
+            report.record("Flush log", Ok(()));
+        }
+
+        let shown = if report.is_clean() {
+            Ok(())
+        } else {
+            self.show_shutdown_errors(&report)
+        };
+        let exited = self.renderer.exit();
+        outcome?;
+        shown?;
+        exited?;
+        Ok(self.profiler)
+    }
+
+    /// Hold the screen on the shutdown failures until a key is pressed
+    fn show_shutdown_errors(&mut self, report: &ShutdownReport) -> Result<()> {
+        self.renderer.begin_frame()?;
+        Self::draw_shutdown_errors(self.renderer.as_mut(), report);
+        self.renderer.end_frame()?;
+        self.input_handler.wait_for_key()
+    }
 
-        self.render_engine.exit()?;
+    fn save_game(&mut self) -> Result<()> {
+        self.saves.save(&self.simulation.to_save())?;
+        self.dirty = false;
         Ok(())
     }
 
+    /// Act on every input that arrived since the last frame. Returns true
+    /// once the player has chosen to quit.
     fn handle_input(&mut self) -> Result<bool> {
-        let action = self.input_handler.poll()?;
+        self.input_handler.poll()?;
+        self.handle_pending()
+    }
+
+    fn handle_pending(&mut self) -> Result<bool> {
+        while let Some(action) = self.input_handler.next_action() {
+            if self.handle_action(action)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Act on one input in the current mode. Returns true once the player
+    /// has chosen to quit.
+    fn handle_action(&mut self, action: InputAction) -> Result<bool> {
+        let _span = tracing::debug_span!("input", ?action).entered();
+        if let InputAction::Resize(width, height) = action {
+            self.renderer.resize(width, height)?;
+            return Ok(false);
+        }
+
+        match self.input_handler.mode() {
+            InputMode::Prompt => return Ok(self.handle_quit_prompt(action)),
+            InputMode::Console => {
+                if let Handled::Picked(choice) = self.offer_screens(action) {
+                    self.pick(choice);
+                }
+                return Ok(false);
+            }
+            InputMode::Editor => {
+                if !self.handle_screens(action) {
+                    self.handle_editor(action);
+                }
+                return Ok(false);
+            }
+            InputMode::Form => {
+                self.handle_form(action);
+                return Ok(false);
+            }
+            InputMode::Interstitial => {
+                if action == InputAction::Confirm {
+                    self.close_interstitial();
+                }
+                return Ok(false);
+            }
+            InputMode::Bookmarks => {
+                self.handle_bookmarks(action);
+                return Ok(false);
+            }
+            InputMode::Search => {
+                self.handle_search(action);
+                return Ok(false);
+            }
+            InputMode::Menu => return Ok(self.handle_menu(action)),
+            InputMode::Normal => {}
+        }
+
+        self.follow_tutorial(action);
+        if self.handle_screens(action) || self.pan_camera(action) || self.navigate(action) {
+            return Ok(false);
+        }
 
         match action {
-            InputAction::Quit => return Ok(true),
-            InputAction::TogglePause => self.time_controller.toggle_pause(),
-            InputAction::IncreaseSpeed => self.time_controller.increase_speed(),
-            InputAction::DecreaseSpeed => self.time_controller.decrease_speed(),
-            InputAction::ZoomIn => {
-                self.zoom_manager.zoom_in();
+            InputAction::Quit => {
+                if !self.dirty {
+                    return Ok(true);
+                }
+                self.input_handler.set_mode(InputMode::Prompt);
             }
-            InputAction::ZoomOut => {
-                self.zoom_manager.zoom_out();
+            InputAction::OpenMenu => self.open_pause_menu(),
+            InputAction::TogglePause if self.simulation.is_fast_forwarding() => {
+                self.simulation.cancel_fast_forward();
+                self.log("Fast-forward stopped");
             }
-            InputAction::MoveUp => {
-                self.zoom_manager.move_in_direction(Direction::Up);
+            InputAction::TogglePause => self.simulation.time_mut().toggle_pause(),
+            InputAction::IncreaseSpeed => self.simulation.time_mut().increase_speed(),
+            InputAction::DecreaseSpeed => self.simulation.time_mut().decrease_speed(),
+            InputAction::SetSpeed(multiplier) => self.simulation.time_mut().set_speed(multiplier),
+            InputAction::ToggleTurbo => {
+                let time = self.simulation.time_mut();
+                let turbo = !time.is_turbo();
+                time.set_turbo(turbo);
             }
-            InputAction::MoveDown => {
-                self.zoom_manager.move_in_direction(Direction::Down);
+            InputAction::Rewind => match self.simulation.rewind() {
+                Some(date) => {
+                    let left = self.simulation.snapshots();
+                    self.log(format!("Rewound to {} ({} snapshots left)", date, left));
+                    self.dirty = true;
+                }
+                None => self.log("No earlier snapshot to rewind to"),
+            },
+            InputAction::OpenConsole => {
+                let history = History::load(self.saves.dir());
+                self.open_screen(Box::new(screens::Console::new(history)))
             }
-            InputAction::MoveLeft => {
-                self.zoom_manager.move_in_direction(Direction::Left);
+            InputAction::ToggleHelp => {
+                self.open_screen(Box::new(screens::Help::new(InputMode::Normal)))
             }
-            InputAction::MoveRight => {
-                self.zoom_manager.move_in_direction(Direction::Right);
+            InputAction::ToggleDashboard => {
+                self.open_screen(Box::new(screens::EconomyDashboard::default()))
+            }
+            InputAction::ToggleCensus => {
+                // Catch up with anything edited since the last tick
+                self.simulation.world_mut().refresh_census();
+                self.open_screen(Box::new(screens::Census::default()));
+            }
+            InputAction::ToggleFinances => self.open_screen(Box::new(screens::Finances::default())),
+            InputAction::ToggleTechnology => {
+                self.open_screen(Box::new(screens::Technology::default()))
+            }
+            InputAction::ToggleMonetary => {
+                self.open_screen(Box::new(screens::MonetaryDashboard::default()))
+            }
+            InputAction::ToggleProfiler => {
+                self.open_screen(Box::new(screens::ProfilerReport::default()))
+            }
+            InputAction::ToggleDebugOverlay => {
+                self.open_screen(Box::new(screens::DebugOverlay::default()))
+            }
+            InputAction::ToggleSidebar => self.open_screen(Box::new(screens::Inspector::default())),
+            InputAction::ToggleMinimap => self.open_screen(Box::new(screens::Minimap::default())),
+            InputAction::ToggleCamera => self.toggle_camera(),
+            InputAction::CenterCamera => self.center_camera(),
+            InputAction::ToggleEditor => self.open_editor(),
+            InputAction::OpenPolicy => self.open_policy_form(),
+            InputAction::OpenSettings => self.open_settings_form(false),
+            InputAction::OpenBusiness => self.open_business_form(),
+            InputAction::EditAttributes => self.open_inspector(),
+            InputAction::TogglePin => self.toggle_pin(),
+            InputAction::SetBookmark(slot) => {
+                if let Some(name) = self.simulation.bookmark(slot) {
+                    self.log(format!("Bookmarked {} as {}", name, slot));
+                    self.dirty = true;
+                }
             }
-            InputAction::Enter => {
-                // For now, just attempt to zoom in
-                // Later this will be "enter current entity"
-                self.zoom_manager.zoom_in();
+            InputAction::OpenBookmarks => self.input_handler.push_mode(InputMode::Bookmarks),
+            InputAction::CycleOverlay => self.cycle_overlay(),
+            InputAction::NewOverlay => self.open_overlay_form(),
+            InputAction::OpenSearch => {
+                self.search = Some(Search::new());
+                self.input_handler.push_mode(InputMode::Search);
             }
-            InputAction::ToggleHelp | InputAction::None => {}
+            _ => {}
         }
 
         Ok(false)
     }
 
-    fn update(&mut self) {
-        let delta = self.time_controller.step();
-        self.world_state.update(delta);
+    fn open_screen(&mut self, screen: Box<dyn Screen>) {
+        if let Some(mode) = screen.input_mode() {
+            self.input_handler.push_mode(mode);
+        }
+        self.screens.push(screen);
     }
 
-    fn render(&mut self) -> Result<()> {
-        self.render_engine.begin_frame()?;
-
-        let zoom_level = self.zoom_manager.current_level();
-        let state = RenderState {
-            fps: self.render_engine.fps(),
-            show_help: self.input_handler.is_help_visible(),
-            time_str: self.time_controller.format_time(),
-            is_paused: self.time_controller.is_paused(),
-            speed: self.time_controller.speed_multiplier(),
-            zoom_level,
-            position: *self.zoom_manager.position(),
-            tick_count: self.world_state.tick_count(),
-            entity_name: self.world_state.get_current_entity_name(zoom_level),
-            entity_count: self.world_state.entity_count(),
-            _phantom: std::marker::PhantomData,
-        };
+    /// Offer an input to the open screens. Returns whether one acted on it.
+    fn handle_screens(&mut self, action: InputAction) -> bool {
+        self.offer_screens(action) != Handled::Ignored
+    }
 
-        Self::draw_game(self.render_engine.canvas_mut(), &state);
+    /// Offer an input to the open screens, handing the keys back to the
+    /// game once the screen that took them closes
+    fn offer_screens(&mut self, action: InputAction) -> Handled {
+        let mode = self.screens.input_mode();
+        let handled = self.screens.handle(action, &mut self.simulation);
+        if let Handled::Closed { .. } | Handled::Picked(_) = handled
+            && mode.is_some()
+            && self.screens.input_mode() != mode
+        {
+            self.input_handler.pop_mode();
+        }
+        if let Handled::Closed { changed } = handled {
+            self.dirty |= changed;
+        }
+        handled
+    }
 
-        self.render_engine.end_frame()?;
-        Ok(())
+    /// Keys while the bookmark list is open: a digit jumps to that slot
+    fn handle_bookmarks(&mut self, action: InputAction) {
+        match action {
+            InputAction::JumpToBookmark(slot) => {
+                let place = self
+                    .simulation
+                    .world()
+                    .bookmarks()
+                    .get(slot)
+                    .map(|bookmark| {
+                        let level = bookmark.level;
+                        (
+                            level,
+                            bookmark.position.map_owner(level).unwrap_or(GALAXY_ID),
+                        )
+                    });
+                let Some((level, id)) = place else {
+                    self.log(format!("No bookmark in slot {}", slot));
+                    return;
+                };
+                // A bookmark elsewhere is travelled to rather than jumped to
+                match self.simulation.world_mut().depart(level, id) {
+                    Ok(None) => {
+                        if let Some(name) = self.simulation.jump_to_bookmark(slot) {
+                            self.log(format!("Jumped to {}", name));
+                        }
+                    }
+                    departed => self.report_departure(departed),
+                }
+                self.input_handler.pop_mode();
+                self.dirty = true;
+            }
+            InputAction::Cancel => self.input_handler.pop_mode(),
+            _ => {}
+        }
     }
 
-    fn draw_game(canvas: &mut Canvas, state: &RenderState) {
-        let (width, height) = (canvas.width(), canvas.height());
+    /// Keys while the search box is open: typing narrows the matches, the
+    /// arrows choose one and Enter jumps to it
+    fn handle_search(&mut self, action: InputAction) {
+        let Some(search) = self.search.as_mut() else {
+            self.input_handler.pop_mode();
+            return;
+        };
+        match action {
+            InputAction::Edit(edit) => search.edit(edit),
+            InputAction::MoveUp => search.select_previous(),
+            InputAction::MoveDown => {
+                let count = search.hits(self.simulation.world()).len();
+                search.select_next(count);
+            }
+            InputAction::Confirm => {
+                let Some(hit) = search.chosen(self.simulation.world()) else {
+                    return;
+                };
+                match self.simulation.travel_to(hit.level, hit.id) {
+                    Ok(None) => {
+                        let position = *self.simulation.zoom().position();
+                        let name = self.simulation.world().location_name(&position, hit.level);
+                        self.log(format!("Jumped to {}", name));
+                        self.dirty = true;
+                    }
+                    departed => self.report_departure(departed),
+                }
+                self.search = None;
+                self.input_handler.pop_mode();
+            }
+            InputAction::Cancel => {
+                self.search = None;
+                self.input_handler.pop_mode();
+            }
+            _ => {}
+        }
+    }
 
-        canvas.draw_box(0, 0, width, 3);
-        let pause_indicator = if state.is_paused {
-            "[PAUSED]"
+    /// Pin the entity in view to the watchlist, or unpin it
+    fn toggle_pin(&mut self) {
+        let level = self.simulation.zoom().current_level();
+        let id = self
+            .simulation
+            .zoom()
+            .position()
+            .map_owner(level)
+            .unwrap_or(GALAXY_ID);
+        let world = self.simulation.world_mut();
+        let name = world.entity_name(level, id);
+        let message = if world.toggle_watch(level, id) {
+            format!("Pinned {} to the watchlist", name)
         } else {
-            "[PLAYING]"
+            format!("Unpinned {}", name)
         };
-        let status_text = format!(
-            "Econogenesis v0.1.0 | {} | {} {:.1}x | FPS: {:.1}",
-            state.zoom_level, pause_indicator, state.speed, state.fps
+        world.log(message);
+        self.dirty = true;
+    }
+
+    /// Tell the player about a journey they set out on, or why they
+    /// couldn't
+    fn report_departure(&mut self, departed: std::result::Result<Option<Journey>, TravelError>) {
+        match departed {
+            Ok(Some(journey)) => {
+                let fare = match journey.fare {
+                    fare if fare > Money::ZERO => {
+                        let currency = self.simulation.world().economy().currency();
+                        format!(" for {}", currency.format(fare))
+                    }
+                    _ => String::new(),
+                };
+                self.log(format!(
+                    "Set out for {} {}{}",
+                    journey.destination, journey.route, fare
+                ));
+                self.dirty = true;
+            }
+            Ok(None) => {}
+            Err(error) => self.notify(Severity::Warning, format!("Can't travel: {}", error)),
+        }
+    }
+
+    /// Movement and zoom, shared by normal play and the editor. Going into
+    /// a place in normal play travels there; while the player is in
+    /// transit the view stays put. Returns true if the action was one of
+    /// these.
+    fn navigate(&mut self, action: InputAction) -> bool {
+        let moves = matches!(
+            action,
+            InputAction::ZoomIn
+                | InputAction::ZoomOut
+                | InputAction::Enter
+                | InputAction::MoveUp
+                | InputAction::MoveDown
+                | InputAction::MoveLeft
+                | InputAction::MoveRight
         );
-        canvas.draw_text(2, 1, &status_text);
+        if moves && let Some(journey) = self.simulation.world().journey() {
+            let message = format!(
+                "In transit to {}; [C] frees the camera to look around",
+                journey.destination
+            );
+            self.notify(Severity::Info, message);
+            return true;
+        }
+        match action {
+            InputAction::Enter if self.simulation.furnishing_in_view().is_some() => {
+                self.open_furnishing();
+            }
+            InputAction::ZoomIn | InputAction::Enter if self.editor.is_none() => {
+                let departed = self.simulation.travel_in();
+                self.report_departure(departed);
+            }
+            InputAction::ZoomIn | InputAction::Enter => {
+                self.simulation.zoom_in();
+            }
+            InputAction::ZoomOut => {
+                self.simulation.zoom_out();
+            }
+            InputAction::MoveUp => {
+                self.simulation.move_cursor(Direction::Up);
+            }
+            InputAction::MoveDown => {
+                self.simulation.move_cursor(Direction::Down);
+            }
+            InputAction::MoveLeft => {
+                self.simulation.move_cursor(Direction::Left);
+            }
+            InputAction::MoveRight => {
+                self.simulation.move_cursor(Direction::Right);
+            }
+            _ => return false,
+        }
+
+        self.dirty = true;
+        true
+    }
 
-        let content_y = 4;
-        let content_height = height - content_y - 2;
-        canvas.draw_box(0, content_y, width, content_height);
+    /// Where the map is centered at `level` when the camera follows the
+    /// player: on the player, except on a region's map, which always shows
+    /// the whole region, and a room's, which shows the whole room at the
+    /// left of the view, clear of the characters listed beside it
+    fn player_center(simulation: &Simulation, level: ZoomLevel) -> (i32, i32) {
+        match level {
+            ZoomLevel::Region => (0, 0),
+            ZoomLevel::Room => {
+                let room = simulation.world().map_bounds(ZoomLevel::Room);
+                (REGION_MAP_WIDTH as i32 / 2 - 1 + room.min().0, 0)
+            }
+            level => simulation.zoom().position().coords_for_level(level),
+        }
+    }
 
-        if state.show_help {
-            Self::draw_help_overlay(canvas, content_y);
+    /// Where a free camera is centered at the zoom level in view, or None
+    /// if the camera follows the player. Zooming in or out brings a free
+    /// camera back to the player on the new map.
+    fn camera_center(&self) -> Option<(i32, i32)> {
+        let (freed_at, center) = self.camera?;
+        let level = self.simulation.zoom().current_level();
+        Some(if freed_at == level {
+            center
         } else {
-            Self::draw_zoom_view(canvas, content_y, state.zoom_level);
+            Self::player_center(&self.simulation, level)
+        })
+    }
 
-            let info_y = content_y + 2;
-            canvas.draw_text(2, info_y, &format!("Simulation Time: {}", state.time_str));
-            canvas.draw_text(2, info_y + 1, &format!("Location: {}", state.entity_name));
-            let coords = state.position.coords_for_level(state.zoom_level);
-            canvas.draw_text(
-                2,
-                info_y + 2,
-                &format!("Position: ({}, {})", coords.0, coords.1),
-            );
-            canvas.draw_text(
-                2,
-                info_y + 3,
-                &format!(
-                    "World: {} entities | Tick: {}",
-                    state.entity_count, state.tick_count
-                ),
-            );
+    fn toggle_camera(&mut self) {
+        if self.camera.take().is_some() {
+            self.log("The camera follows you again");
+        } else {
+            self.center_camera();
+            self.log("Free camera: the arrows pan the map, HOME brings it back to you");
         }
+    }
 
-        let status_y = height - 2;
-        canvas.draw_box(0, status_y, width, 2);
-        let controls_text = "[ARROWS] Move | [ENTER] Enter | [Z/X] Zoom | [H/?] Help | [Q] Quit";
-        canvas.draw_text(2, status_y + 1, controls_text);
+    /// Center a free camera on the player, and free the camera if it isn't
+    fn center_camera(&mut self) {
+        let level = self.simulation.zoom().current_level();
+        self.camera = Some((level, Self::player_center(&self.simulation, level)));
     }
 
-    fn draw_help_overlay(canvas: &mut Canvas, content_y: u16) {
-        let help_y = content_y + 2;
+    /// Move a free camera one spot with the arrow keys, as far as the edge
+    /// of the map. Returns true if the action panned the camera.
+    fn pan_camera(&mut self, action: InputAction) -> bool {
+        let step = match action {
+            InputAction::MoveUp => (0, -1),
+            InputAction::MoveDown => (0, 1),
+            InputAction::MoveLeft => (-1, 0),
+            InputAction::MoveRight => (1, 0),
+            _ => return false,
+        };
+        let Some((x, y)) = self.camera_center() else {
+            return false;
+        };
+        let level = self.simulation.zoom().current_level();
+        let bounds = self.simulation.world().map_bounds(level);
+        self.camera = Some((level, bounds.clamp((x + step.0, y + step.1))));
+        true
+    }
 
-        canvas.draw_text(2, help_y, "╔══════════════════════════════════════╗");
-        canvas.draw_text(2, help_y + 1, "║          KEYBOARD CONTROLS           ║");
-        canvas.draw_text(2, help_y + 2, "╠══════════════════════════════════════╣");
-        canvas.draw_text(2, help_y + 3, "║  SPACE     Play/Pause simulation     ║");
-        canvas.draw_text(2, help_y + 4, "║  +/=       Increase time speed       ║");
-        canvas.draw_text(2, help_y + 5, "║  -/_       Decrease time speed       ║");
-        canvas.draw_text(2, help_y + 6, "║  Z         Zoom in                   ║");
-        canvas.draw_text(2, help_y + 7, "║  X         Zoom out                  ║");
-        canvas.draw_text(2, help_y + 8, "║  ↑↓←→      Navigate within level     ║");
-        canvas.draw_text(2, help_y + 9, "║  ENTER     Enter current entity      ║");
-        canvas.draw_text(2, help_y + 10, "║  H/?       Toggle this help          ║");
-        canvas.draw_text(2, help_y + 11, "║  Q/ESC     Quit application          ║");
-        canvas.draw_text(2, help_y + 12, "╠══════════════════════════════════════╣");
-        canvas.draw_text(2, help_y + 13, "║  Press H or ? to close this help     ║");
-        canvas.draw_text(2, help_y + 14, "╚══════════════════════════════════════╝");
+    /// Enter sandbox mode; the clock stops so edits land on a still world
+    fn open_editor(&mut self) {
+        if !self.simulation.time().is_paused() {
+            self.simulation.time_mut().toggle_pause();
+        }
+        self.editor = Some(Editor::new());
+        self.input_handler.set_mode(InputMode::Editor);
+        self.log("Sandbox editor opened");
     }
 
-    fn draw_zoom_view(canvas: &mut Canvas, content_y: u16, level: ZoomLevel) {
-        let view_y = content_y + 6;
+    fn handle_editor(&mut self, action: InputAction) {
+        if self.navigate(action) {
+            return;
+        }
+        let Some(editor) = self.editor.as_mut() else {
+            self.input_handler.set_mode(InputMode::Normal);
+            return;
+        };
 
-        match level {
-            ZoomLevel::Galaxy => {
-                canvas.draw_text(2, view_y, "╔════════════════════════════════════╗");
-                canvas.draw_text(2, view_y + 1, "║      GALAXY VIEW                   ║");
-                canvas.draw_text(2, view_y + 2, "║                                    ║");
-                canvas.draw_text(2, view_y + 3, "║        *   ·    *                  ║");
-                canvas.draw_text(2, view_y + 4, "║    ·       ⊙        ·              ║");
-                canvas.draw_text(2, view_y + 5, "║  *    ·  YOU   *    ·    *         ║");
-                canvas.draw_text(2, view_y + 6, "║         *       ·                  ║");
-                canvas.draw_text(2, view_y + 7, "║    ·               *    ·          ║");
-                canvas.draw_text(2, view_y + 8, "║                                    ║");
-                canvas.draw_text(2, view_y + 9, "╚════════════════════════════════════╝");
+        let outcome = match action {
+            InputAction::PaletteNext => {
+                editor.select_next(&self.simulation);
+                return;
             }
-            ZoomLevel::SolarSystem => {
-                canvas.draw_text(2, view_y, "╔════════════════════════════════════╗");
-                canvas.draw_text(2, view_y + 1, "║    SOLAR SYSTEM VIEW               ║");
-                canvas.draw_text(2, view_y + 2, "║                                    ║");
-                canvas.draw_text(2, view_y + 3, "║              ☉                     ║");
-                canvas.draw_text(2, view_y + 4, "║         o                          ║");
-                canvas.draw_text(2, view_y + 5, "║     o       YOU   O                ║");
-                canvas.draw_text(2, view_y + 6, "║   o                    o           ║");
-                canvas.draw_text(2, view_y + 7, "║                                    ║");
-                canvas.draw_text(2, view_y + 8, "║                  O                 ║");
-                canvas.draw_text(2, view_y + 9, "╚════════════════════════════════════╝");
+            InputAction::PalettePrevious => {
+                editor.select_previous(&self.simulation);
+                return;
             }
-            ZoomLevel::Planet => {
-                canvas.draw_text(2, view_y, "╔════════════════════════════════════╗");
-                canvas.draw_text(2, view_y + 1, "║      PLANET VIEW                   ║");
-                canvas.draw_text(2, view_y + 2, "║                                    ║");
-                canvas.draw_text(2, view_y + 3, "║        ~~~~~  ~~~~                 ║");
-                canvas.draw_text(2, view_y + 4, "║    ~~~~       ^^^^  ~~~            ║");
-                canvas.draw_text(2, view_y + 5, "║  ~~~    ^^^^ YOU ^^^^   ~~~        ║");
-                canvas.draw_text(2, view_y + 6, "║    ^^^^       ~~~~                 ║");
-                canvas.draw_text(2, view_y + 7, "║       ^^^^  ~~~~~   ^^^^           ║");
-                canvas.draw_text(2, view_y + 8, "║                                    ║");
-                canvas.draw_text(2, view_y + 9, "╚════════════════════════════════════╝");
+            InputAction::Place => editor.place(&mut self.simulation),
+            InputAction::Remove => editor.remove(&mut self.simulation),
+            InputAction::EditAttributes => match editor.attribute_form(&self.simulation) {
+                Ok(attributes) => {
+                    self.open_form(ActiveForm::Attributes(attributes));
+                    return;
+                }
+                Err(error) => Err(error),
+            },
+            InputAction::MarkSelection => {
+                editor.toggle_mark(&self.simulation);
+                return;
             }
-            ZoomLevel::Region => {
-                canvas.draw_text(2, view_y, "╔════════════════════════════════════╗");
-                canvas.draw_text(2, view_y + 1, "║      REGION VIEW                   ║");
-                canvas.draw_text(2, view_y + 2, "║                                    ║");
-                canvas.draw_text(2, view_y + 3, "║   ♣  ♠  ♣                          ║");
-                canvas.draw_text(2, view_y + 4, "║  ♠ ♣    ♠  ♣                       ║");
-                canvas.draw_text(2, view_y + 5, "║   ♣  ♠ YOU  ♣  ♠                   ║");
-                canvas.draw_text(2, view_y + 6, "║  ♠    ♣  ♠    ♣                    ║");
-                canvas.draw_text(2, view_y + 7, "║   ♣  ♠    ♣  ♠                     ║");
-                canvas.draw_text(2, view_y + 8, "║                                    ║");
-                canvas.draw_text(2, view_y + 9, "╚════════════════════════════════════╝");
+            InputAction::Copy => editor.copy(&self.simulation),
+            InputAction::Paste => editor.paste(&mut self.simulation),
+            InputAction::Undo => editor.undo(&mut self.simulation),
+            InputAction::SaveScenario => {
+                self.save_scenario();
+                return;
             }
-            ZoomLevel::LocalArea => {
-                canvas.draw_text(2, view_y, "╔════════════════════════════════════╗");
-                canvas.draw_text(2, view_y + 1, "║    LOCAL AREA VIEW                 ║");
-                canvas.draw_text(2, view_y + 2, "║                                    ║");
-                canvas.draw_text(2, view_y + 3, "║   ▓▓▓▓     ▓▓▓                     ║");
-                canvas.draw_text(2, view_y + 4, "║   ▓  ▓     ▓ ▓                     ║");
-                canvas.draw_text(2, view_y + 5, "║   ▓  ▓  @ YOU                      ║");
-                canvas.draw_text(2, view_y + 6, "║   ▓▓▓▓     ▓▓▓                     ║");
-                canvas.draw_text(2, view_y + 7, "║            ▓ ▓                     ║");
-                canvas.draw_text(2, view_y + 8, "║                                    ║");
-                canvas.draw_text(2, view_y + 9, "╚════════════════════════════════════╝");
+            InputAction::ToggleHelp => {
+                self.open_screen(Box::new(screens::Help::new(InputMode::Editor)));
+                return;
             }
-            ZoomLevel::Room => {
-                canvas.draw_text(2, view_y, "╔════════════════════════════════════╗");
-                canvas.draw_text(2, view_y + 1, "║       ROOM VIEW                    ║");
-                canvas.draw_text(2, view_y + 2, "║  ┌──────────────────┐              ║");
-                canvas.draw_text(2, view_y + 3, "║  │                  │              ║");
-                canvas.draw_text(2, view_y + 4, "║  │  [Table]         │              ║");
-                canvas.draw_text(2, view_y + 5, "║  │         @ YOU    │              ║");
-                canvas.draw_text(2, view_y + 6, "║  │                  │              ║");
-                canvas.draw_text(2, view_y + 7, "║  │      [Chair]     │              ║");
-                canvas.draw_text(2, view_y + 8, "║  └──────────────────┘              ║");
-                canvas.draw_text(2, view_y + 9, "╚════════════════════════════════════╝");
+            InputAction::ToggleEditor => {
+                self.editor = None;
+                self.input_handler.set_mode(InputMode::Normal);
+                self.log("Sandbox editor closed");
+                return;
+            }
+            _ => return,
+        };
+
+        match outcome {
+            Ok(message) => {
+                self.dirty = true;
+                self.log(message);
+            }
+            Err(error) => self.log(format!("Error: {}", error)),
+        }
+    }
+
+    fn open_form(&mut self, form: ActiveForm) {
+        self.form = Some(form);
+        self.input_handler.push_mode(InputMode::Form);
+    }
+
+    fn close_form(&mut self) {
+        let form = self.form.take();
+        self.input_handler.pop_mode();
+        // Settings opened from the title menu lead back to it
+        if matches!(form, Some(ActiveForm::Settings { profile: true, .. })) && !self.playing {
+            self.open_screen(Box::new(self.main_menu()));
+        }
+    }
+
+    fn open_policy_form(&mut self) {
+        let growth = self
+            .simulation
+            .world()
+            .economy()
+            .money_supply()
+            .growth_rate();
+        let interest = self.simulation.world().economy().bank().policy_rate();
+        let polity = self
+            .simulation
+            .polity_in_view()
+            .and_then(|id| self.simulation.world().polity(id));
+        let mut form = Form::new(&polity.map_or(String::from("Monetary Policy"), |polity| {
+            format!("Policy: {}", polity.name)
+        }));
+        let percent = |share: f64| (share * 1000.0).round() / 10.0;
+        form.add_field(Field::number(
+            "growth",
+            "Money growth (%/yr)",
+            percent(growth),
+            -50.0,
+            100.0,
+        ));
+        form.add_field(Field::number(
+            "interest",
+            "Interest rate (%/yr)",
+            percent(interest),
+            0.0,
+            MAX_POLICY_RATE * 100.0,
+        ));
+        if let Some(polity) = polity {
+            let most = polities::MAX_RATE * 100.0;
+            form.add_field(Field::number(
+                "tax",
+                "Tax on takings (%)",
+                percent(polity.policy.tax_rate),
+                0.0,
+                most,
+            ));
+            form.add_field(Field::number(
+                "sales",
+                "Sales tax (%)",
+                percent(polity.policy.sales_tax),
+                0.0,
+                most,
+            ));
+            form.add_field(Field::number(
+                "tariff",
+                "Tariff (%)",
+                percent(polity.policy.tariff),
+                0.0,
+                most,
+            ));
+            form.add_field(Field::number(
+                "spending",
+                "Budget spent (%/day)",
+                percent(polity.policy.spending),
+                0.0,
+                most,
+            ));
+        }
+        let polity = polity.map(|polity| polity.id);
+        self.open_form(ActiveForm::Policy(form, polity));
+    }
+
+    /// This game's settings, each label naming where its value currently
+    /// comes from; committing stores whatever differs from the profile in
+    /// this save. With `profile`, the profile's own settings instead.
+    fn open_settings_form(&mut self, profile: bool) {
+        let defaults = self.saves.profile().settings;
+        let sources = self.simulation.world().settings().sources(&defaults);
+        let settings = if profile { defaults } else { self.settings };
+        let label = |name: &str, source| match profile {
+            true => name.to_string(),
+            false => format!("{} ({})", name, source),
+        };
+
+        let mut form = Form::new(if profile {
+            "Settings for every game"
+        } else {
+            "Settings for this game"
+        });
+        form.add_field(Field::number(
+            "productivity",
+            &label("Productivity", sources.productivity),
+            settings.productivity,
+            0.1,
+            10.0,
+        ));
+        form.add_field(Field::number(
+            "appetite",
+            &label("Appetite", sources.appetite),
+            settings.appetite,
+            0.1,
+            10.0,
+        ));
+        form.add_field(Field::flag(
+            "pause_on_events",
+            &label("Pause on events", sources.pause_on_events),
+            settings.pause_on_events,
+        ));
+        form.add_field(Field::flag(
+            "pause_on_objectives",
+            &label("Pause on objectives", sources.pause_on_objectives),
+            settings.pause_on_objectives,
+        ));
+        form.add_field(Field::integer(
+            "autosave_minutes",
+            &label("Autosave minutes", sources.autosave_minutes),
+            i64::from(settings.autosave_minutes),
+            1,
+            24 * 60,
+        ));
+        form.add_field(Field::integer(
+            "price_history_days",
+            &label("Price history days", sources.price_history_days),
+            i64::from(settings.price_history_days),
+            0,
+            365,
+        ));
+        self.open_form(ActiveForm::Settings { form, profile });
+    }
+
+    /// Founding a firm takes a local area in view to put it in
+    fn open_business_form(&mut self) {
+        if self.simulation.zoom().current_level() > ZoomLevel::LocalArea {
+            self.log("Zoom into a local area to found a business");
+            return;
+        }
+        let purse = self.simulation.world().economy().purse().amount();
+        let mut form = Form::new("Found a business");
+        form.add_field(Field::text("name", "Name", "Player Works"));
+        form.add_field(Field::text("recipe", "Recipe", "baking"));
+        form.add_field(Field::integer("workers", "Most workers", 5, 1, 100));
+        form.add_field(Field::number(
+            "capital",
+            "Capital",
+            (purse / 2.0).floor(),
+            0.0,
+            purse,
+        ));
+        form.add_field(Field::flag("build", "Build it first", true));
+        self.open_form(ActiveForm::Business(form));
+    }
+
+    /// Commission the building from the business form, saying how it went
+    fn commission_building(
+        &mut self,
+        name: &str,
+        recipe: &str,
+        workers: u32,
+        capital: Money,
+    ) -> String {
+        let id = match self
+            .simulation
+            .commission_building(name, recipe, workers, capital)
+        {
+            Ok(id) => id,
+            Err(error) => return format!("Error: {}", error),
+        };
+        let economy = self.simulation.world().economy();
+        let Some(site) = economy.site(id) else {
+            return format!("Commissioned {}", name);
+        };
+        format!(
+            "Building {} in {} with {}; it opens after {:.0} days of work once its materials are in",
+            name,
+            self.simulation
+                .world()
+                .entity_name(ZoomLevel::LocalArea, site.area_id),
+            economy.currency().format(capital),
+            site.hours / 24.0
+        )
+    }
+
+    /// Live editing of the entity in view and the firm under the cursor,
+    /// for testing economy behavior by hand
+    fn open_inspector(&mut self) {
+        if !cfg!(debug_assertions) {
+            self.log("Live editing is only available in debug builds");
+            return;
+        }
+        let level = self.simulation.zoom().current_level();
+        let position = *self.simulation.zoom().position();
+        let Some(id) = position.map_owner(level) else {
+            self.log("Nothing to edit here");
+            return;
+        };
+        let world = self.simulation.world();
+        let firm = match level {
+            ZoomLevel::LocalArea => world
+                .economy()
+                .firm_at(id, position.coords_for_level(level))
+                .map(|firm| firm.id),
+            _ => None,
+        };
+        match Inspector::new(world, level, id, firm) {
+            Some(inspector) => self.open_form(ActiveForm::Inspector(inspector)),
+            None => self.log(format!("Nothing to edit at {} level", level)),
+        }
+    }
+
+    /// Move the tutorial on when the player does what it asks, or end it
+    /// when they skip it
+    fn follow_tutorial(&mut self, action: InputAction) {
+        if self.tutorial.is_finished() {
+            return;
+        }
+        let skipped = action == InputAction::SkipTutorial;
+        if skipped {
+            self.tutorial.skip();
+        } else if !self.tutorial.observe(action) {
+            return;
+        }
+        if let Err(error) = self.saves.save_tutorial(self.tutorial) {
+            self.log(format!("Tutorial progress not saved: {}", error));
+        }
+        if skipped {
+            self.log("Tutorial skipped; press H or ? to see every control");
+        } else if self.tutorial.is_finished() {
+            self.log("Tutorial complete; press H or ? to see every control");
+        }
+    }
+
+    /// Opens on the overlay in view so it can be changed, as saving under
+    /// the same name replaces it
+    fn open_overlay_form(&mut self) {
+        let (name, expression) = match &self.overlay {
+            Some(shown) => (shown.name.as_str(), shown.query.expression()),
+            None => ("Cost of grain", "price(grain) / wage"),
+        };
+        let mut form = Form::new("Define a map overlay");
+        form.add_field(Field::text("name", "Name", name));
+        form.add_field(Field::text("expression", "Query", expression));
+        self.open_form(ActiveForm::Overlay(form));
+    }
+
+    /// Show the profile's next overlay that compiles, turning overlays off
+    /// after the last
+    fn cycle_overlay(&mut self) {
+        let overlays = self.saves.profile().overlays.clone();
+        if overlays.is_empty() {
+            self.log("No overlays defined; press V to define one");
+            return;
+        }
+        let start = self.overlay.as_ref().map_or(0, |shown| shown.index + 1);
+        self.overlay = None;
+        for (index, overlay) in overlays.into_iter().enumerate().skip(start) {
+            match Query::compile(&overlay.expression) {
+                Ok(query) => {
+                    self.log(format!("Overlay {} shown on the planet map", overlay.name));
+                    self.overlay = Some(ShownOverlay {
+                        index,
+                        name: overlay.name,
+                        query,
+                    });
+                    return;
+                }
+                Err(error) => self.log(format!("Overlay {} skipped: {}", overlay.name, error)),
+            }
+        }
+        self.log("Overlays off");
+    }
+
+    /// Check an overlay against the planet in view, then store it in the
+    /// profile and show it
+    fn define_overlay(&mut self, overlay: CustomOverlay) -> String {
+        if overlay.name.is_empty() {
+            return String::from("Overlay not saved: it needs a name");
+        }
+        let query = match Query::compile(&overlay.expression) {
+            Ok(query) => query,
+            Err(error) => return format!("Overlay not saved: {}", error),
+        };
+        let world = self.simulation.world();
+        let planet = self
+            .simulation
+            .zoom()
+            .position()
+            .map_owner(ZoomLevel::Planet)
+            .or_else(|| world.ids(ZoomLevel::Planet).first().copied());
+        if let Some(Err(error)) = planet.map(|planet| query.evaluate_planet(world, planet)) {
+            return format!("Overlay not saved: {}", error);
+        }
+        if let Err(error) = self.saves.save_overlay(overlay.clone()) {
+            return format!("Overlay not saved: {}", error);
+        }
+        let index = self
+            .saves
+            .profile()
+            .overlays
+            .iter()
+            .position(|saved| saved.name == overlay.name)
+            .unwrap_or_default();
+        let message = format!("Overlay {} saved and shown on the planet map", overlay.name);
+        self.overlay = Some(ShownOverlay {
+            index,
+            name: overlay.name,
+            query,
+        });
+        message
+    }
+
+    fn handle_form(&mut self, action: InputAction) {
+        let Some(active) = self.form.as_mut() else {
+            self.input_handler.pop_mode();
+            return;
+        };
+
+        match action {
+            InputAction::NextField => active.form_mut().focus_next(),
+            InputAction::PreviousField => active.form_mut().focus_previous(),
+            InputAction::Edit(edit) => active.form_mut().edit(edit),
+            InputAction::Cancel => self.close_form(),
+            InputAction::Confirm => {
+                // The profile's settings are stored on their own, not in
+                // the game
+                let changes_game = !matches!(active, ActiveForm::Settings { profile: true, .. });
+                let message = match active {
+                    ActiveForm::Attributes(attributes) => match self.editor.as_mut() {
+                        Some(editor) => editor.commit_attributes(attributes, &mut self.simulation),
+                        None => attributes.commit(self.simulation.world_mut()),
+                    },
+                    ActiveForm::Inspector(inspector) => {
+                        inspector.commit(self.simulation.world_mut())
+                    }
+                    ActiveForm::Policy(form, polity) => form.commit().map(|values| {
+                        let percent = values.number("growth").unwrap_or_default();
+                        let interest = values.number("interest").unwrap_or_default();
+                        let world = self.simulation.world_mut();
+                        world.economy_mut().set_money_growth(percent / 100.0);
+                        world
+                            .economy_mut()
+                            .bank_mut()
+                            .set_policy_rate(interest / 100.0);
+                        let mut message = format!(
+                            "Money growth set to {:+.1}%/yr, interest to {:.1}%",
+                            percent, interest
+                        );
+                        if let Some(id) = *polity {
+                            let policy = FiscalPolicy {
+                                tax_rate: values.number("tax").unwrap_or_default() / 100.0,
+                                tariff: values.number("tariff").unwrap_or_default() / 100.0,
+                                sales_tax: values.number("sales").unwrap_or_default() / 100.0,
+                                spending: values.number("spending").unwrap_or_default() / 100.0,
+                            };
+                            if world.set_fiscal_policy(id, policy)
+                                && let Some(polity) = world.polity(id)
+                            {
+                                message = format!("{}, {}", message, polity.policy_summary());
+                            }
+                        }
+                        message
+                    }),
+                    ActiveForm::Settings { form, profile } => {
+                        let profile = *profile;
+                        form.commit().map(|values| {
+                            let current = match profile {
+                                true => self.saves.profile().settings,
+                                false => self.settings,
+                            };
+                            let desired = Settings {
+                                productivity: values
+                                    .number("productivity")
+                                    .unwrap_or(current.productivity),
+                                appetite: values.number("appetite").unwrap_or(current.appetite),
+                                pause_on_events: values
+                                    .flag("pause_on_events")
+                                    .unwrap_or(current.pause_on_events),
+                                pause_on_objectives: values
+                                    .flag("pause_on_objectives")
+                                    .unwrap_or(current.pause_on_objectives),
+                                autosave_minutes: values
+                                    .integer("autosave_minutes")
+                                    .map_or(current.autosave_minutes, |m| m as u32),
+                                price_history_days: values
+                                    .integer("price_history_days")
+                                    .map_or(current.price_history_days, |d| d as u32),
+                            };
+                            if profile {
+                                let saved = self.saves.save_settings(desired);
+                                self.apply_settings();
+                                return match saved {
+                                    Ok(()) => String::from("Settings saved for every game"),
+                                    Err(error) => format!("Settings not saved: {}", error),
+                                };
+                            }
+                            let profile = self.saves.profile().settings;
+                            let overrides = SettingsOverrides::between(&profile, &desired);
+                            self.simulation.world_mut().set_settings(overrides);
+                            self.apply_settings();
+                            String::from("Settings saved for this game")
+                        })
+                    }
+                    ActiveForm::Overlay(form) => form.commit().map(|values| {
+                        let overlay = CustomOverlay {
+                            name: values.text("name").unwrap_or_default().trim().to_string(),
+                            expression: values.text("expression").unwrap_or_default().to_string(),
+                        };
+                        self.define_overlay(overlay)
+                    }),
+                    ActiveForm::Business(form) => form.commit().map(|values| {
+                        let name = values.text("name").unwrap_or_default().to_string();
+                        let recipe = values.text("recipe").unwrap_or_default().to_string();
+                        let workers = values.integer("workers").unwrap_or(1) as u32;
+                        let capital = Money(values.number("capital").unwrap_or_default());
+                        if values.flag("build").unwrap_or(false) {
+                            return self.commission_building(&name, &recipe, workers, capital);
+                        }
+                        match self
+                            .simulation
+                            .found_business(&name, &recipe, workers, capital)
+                        {
+                            Ok(id) => {
+                                let world = self.simulation.world();
+                                let area = world
+                                    .economy()
+                                    .firm(id)
+                                    .map(|firm| {
+                                        world.entity_name(ZoomLevel::LocalArea, firm.area_id)
+                                    })
+                                    .unwrap_or_default();
+                                let capital = world.economy().currency().format(capital);
+                                format!("Founded {} in {} with {}", name, area, capital)
+                            }
+                            Err(error) => format!("Error: {}", error),
+                        }
+                    }),
+                };
+                // Invalid fields keep the form open with their errors shown
+                if let Some(message) = message {
+                    self.close_form();
+                    self.dirty |= changes_game;
+                    self.log(message);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Write the edited world to a scenario file named after the galaxy
+    fn save_scenario(&mut self) {
+        let world = self.simulation.world();
+        let scenario = Scenario::new(world.galaxy().name.clone(), world.clone());
+        let message = match scenario.store(self.saves.dir()) {
+            Ok(path) => format!("Scenario saved to {}", path.display()),
+            Err(e) => format!("Scenario save failed: {}", e),
+        };
+        self.log(message);
+    }
+
+    /// Answer to "save before quitting?"; returns true to quit
+    fn handle_quit_prompt(&mut self, action: InputAction) -> bool {
+        match action {
+            InputAction::Confirm => {
+                self.save_on_exit = true;
+                true
+            }
+            InputAction::Deny => true,
+            InputAction::Cancel => {
+                self.input_handler.set_mode(InputMode::Normal);
+                false
+            }
+            _ => false,
+        }
+    }
+
+    fn update(&mut self) {
+        for message in self.mods.run(self.simulation.world_mut()) {
+            self.simulation
+                .world_mut()
+                .log(format!("Victory: {}", message));
+            if self.interstitial.is_none() {
+                self.open_interstitial(Interstitial {
+                    title: String::from("Victory"),
+                    text: message,
+                });
+            }
+        }
+        self.apply_mod_glyphs();
+
+        let level = self.simulation.zoom().current_level();
+        let position = *self.simulation.zoom().position();
+        self.simulation.world_mut().explore(
+            level,
+            position.map_owner(level),
+            position.coords_for_level(level),
+        );
+
+        if self.interstitial.is_none()
+            && let Some(screen) = self.simulation.world_mut().take_interstitial()
+        {
+            self.open_interstitial(screen);
+        }
+        self.show_notices();
+        #[cfg(feature = "serve")]
+        if let Some(observer) = &mut self.observer {
+            observer.publish(&self.simulation);
+        }
+
+        if self.simulation.time().is_paused() {
+            return;
+        }
+
+        // Faster speeds run more ticks a frame, each handled as it happens
+        // so an event that pauses the game stops the rest
+        for _ in 0..self.simulation.due_ticks() {
+            if self.simulation.time().is_paused() {
+                break;
+            }
+            self.simulation.tick();
+            self.dirty = true;
+            let world = self.simulation.world();
+            self.profiler
+                .record_tick(world.tick_count(), world.timings());
+
+            let alerts = self.simulation.world_mut().take_alerts();
+            let pause = alerts.iter().any(|alert| match alert {
+                Alert::ScriptedEvent => self.settings.pause_on_events,
+                Alert::Objective => self.settings.pause_on_objectives,
+            });
+            if pause {
+                self.simulation.cancel_fast_forward();
+                if !self.simulation.time().is_paused() {
+                    self.simulation.time_mut().toggle_pause();
+                }
+                self.log("Auto-paused");
+            }
+        }
+
+        if self
+            .saves
+            .is_autosave_due(self.simulation.simulation_time())
+        {
+            self.run_autosave();
+        }
+    }
+
+    /// Pause for a chapter screen until the player dismisses it
+    fn open_interstitial(&mut self, screen: Interstitial) {
+        let was_running = !self.simulation.time().is_paused();
+        if was_running {
+            self.simulation.time_mut().toggle_pause();
+        }
+        self.interstitial = Some((screen, was_running));
+        self.input_handler.push_mode(InputMode::Interstitial);
+    }
+
+    fn close_interstitial(&mut self) {
+        if let Some((_, was_running)) = self.interstitial.take()
+            && was_running
+            && self.simulation.time().is_paused()
+        {
+            self.simulation.time_mut().toggle_pause();
+        }
+        self.input_handler.pop_mode();
+    }
+
+    /// Autosave failures are reported in the UI rather than ending the session
+    fn run_autosave(&mut self) {
+        let save = self.simulation.to_save();
+        match self.saves.autosave(&save) {
+            Ok(_) => {
+                self.dirty = false;
+                self.notify(Severity::Info, "Autosaved");
+            }
+            Err(e) => self.notify(Severity::Critical, format!("Autosave failed: {}", e)),
+        }
+    }
+
+    /// Draw a frame and write it to the terminal, returning how long the
+    /// writing took
+    fn render(&mut self) -> Result<Duration> {
+        let _span = tracing::debug_span!("render").entered();
+        self.renderer.begin_frame()?;
+
+        let zoom_level = self.simulation.zoom().current_level();
+        let toasts = self.notifications.lines(Instant::now());
+        let (heat, legend) =
+            Self::overlay_heat(&self.simulation, self.overlay.as_ref(), zoom_level).unzip();
+        let legend = legend
+            .or_else(|| Self::star_legend(&self.simulation, zoom_level))
+            .or_else(|| Self::furnishing_legend(&self.simulation));
+        self.screens.refresh(&Context {
+            simulation: &self.simulation,
+            glyphs: &self.glyphs,
+            profiler: &self.profiler,
+        });
+        let state = RenderState {
+            fps: self.renderer.fps(),
+            // Turbo frames have no budget, so they are always behind
+            is_behind: self.pacer.is_behind() && !self.simulation.time().is_turbo(),
+            is_lagging: self.simulation.time().is_lagging(),
+            time_str: self.simulation.time().format_time(),
+            date_str: self.simulation.time().current_date().to_string(),
+            is_paused: self.simulation.time().is_paused(),
+            is_turbo: self.simulation.time().is_turbo(),
+            speed: self.simulation.time().speed_multiplier(),
+            zoom_level,
+            position: *self.simulation.zoom().position(),
+            tick_count: self.simulation.world().tick_count(),
+            entity_name: self
+                .simulation
+                .world()
+                .location_name(self.simulation.zoom().position(), zoom_level),
+            location_path: self
+                .simulation
+                .world()
+                .location_path(self.simulation.zoom().position(), zoom_level),
+            entity_count: self.simulation.world().entity_count(),
+            money_summary: Self::money_summary(self.simulation.world()),
+            market_summary: Self::market_summary(
+                self.simulation.world(),
+                self.simulation.zoom().position(),
+                zoom_level,
+            ),
+            festival: Self::festival_banner(&self.simulation, &self.glyphs, zoom_level),
+            recent_events: self.recent_events(EVENT_PANEL_ROWS),
+            quit_prompt: self.input_handler.mode() == InputMode::Prompt,
+            form: self
+                .form
+                .as_ref()
+                .map(|active| (active.form().title().to_string(), active.form().lines())),
+            bookmarks: (self.input_handler.mode() == InputMode::Bookmarks)
+                .then(|| Self::bookmark_lines(self.simulation.world())),
+            search: self
+                .search
+                .as_ref()
+                .map(|search| Self::search_lines(self.simulation.world(), search)),
+            editor_map: self.editor.as_ref().map(|editor| {
+                editor.map_rows(
+                    &self.simulation,
+                    &self.glyphs,
+                    EDITOR_MAP_WIDTH,
+                    EDITOR_MAP_ROWS,
+                )
+            }),
+            palette: self
+                .editor
+                .as_ref()
+                .map(|editor| editor.palette_label(&self.simulation)),
+            screens: &self.screens,
+            surface: Self::surface_lines(
+                &self.simulation,
+                &self.glyphs,
+                zoom_level,
+                heat.as_ref(),
+                self.camera_center(),
+            ),
+            camera: self.camera_center(),
+            legend,
+            objective: self
+                .simulation
+                .world()
+                .campaign()
+                .and_then(|campaign| campaign.objective())
+                .map(|objective| objective.description.clone()),
+            deadline: self.deadline_progress(),
+            fast_forward: self.simulation.fast_forward_progress(),
+            journey: self.simulation.journey_progress(),
+            interstitial: self.interstitial.as_ref().map(|(screen, _)| screen),
+            characters: Self::character_lines(&self.simulation, &self.glyphs, zoom_level),
+            toasts,
+            tutorial: (self.input_handler.mode() == InputMode::Normal
+                && !self.screens.is_open(ScreenKind::Help))
+            .then(|| self.tutorial.prompt())
+            .flatten(),
+            _phantom: std::marker::PhantomData,
+        };
+
+        Self::draw_game(self.renderer.as_mut(), &state);
+
+        let present_start = Instant::now();
+        self.renderer.end_frame()?;
+        Ok(present_start.elapsed())
+    }
+
+    /// Time used and left in the current chapter, if it has a deadline
+    fn deadline_progress(&self) -> Option<Progress> {
+        let campaign = self.simulation.world().campaign()?;
+        let (start, end) = campaign.deadline()?;
+        let now = self.simulation.simulation_time();
+        let done = now.saturating_sub(start).as_secs_f64();
+        let total = (end - start).as_secs_f64();
+        Some(
+            Progress::new("Time", done, total).with_remaining(end.saturating_sub(now), Clock::Game),
+        )
+    }
+
+    fn recent_events(&self, count: usize) -> Vec<String> {
+        let time = self.simulation.time();
+        let calendar = time.calendar();
+        // Turbo and fast-forwards run as fast as the top speed or faster
+        let speed = if time.is_turbo() || self.simulation.is_fast_forwarding() {
+            MAX_SPEED
+        } else {
+            time.speed_multiplier()
+        };
+        self.simulation
+            .world()
+            .events()
+            .coalesced(count, coalescing_window(speed))
+            .into_iter()
+            .map(|entry| format!("{} {}", calendar.date_at(entry.time), entry.message))
+            .collect()
+    }
+
+    /// Money in circulation, its growth rate and the resulting price level,
+    /// with the price level's recent trend
+    fn money_summary(world_state: &WorldState) -> Line {
+        let economy = world_state.economy();
+        let supply = economy.money_supply();
+        let prices: Vec<f64> = world_state
+            .stats()
+            .trends(ZoomLevel::Galaxy, GALAXY_ID)
+            .map(|trends| trends.prices.iter().copied().collect())
+            .unwrap_or_default();
+        Line::plain(format!(
+            "Money: {} ({:+.1}%/yr) | Prices: ",
+            economy.currency().format(supply.issued()),
+            supply.growth_rate() * 100.0,
+        ))
+        .value(format!("{:.2}x", supply.price_level()), Trend::of(&prices))
+    }
+
+    /// One line per character in the area or room in view
+    fn character_lines(
+        simulation: &Simulation,
+        glyphs: &Glyphs,
+        zoom_level: ZoomLevel,
+    ) -> Vec<String> {
+        let world = simulation.world();
+        let owner = simulation.zoom().position().map_owner(zoom_level);
+        let characters: Vec<_> = match (zoom_level, owner) {
+            (ZoomLevel::LocalArea, Some(id)) => world.characters_in_area(id).collect(),
+            (ZoomLevel::Room, Some(id)) => world.characters_in_room(id).collect(),
+            _ => return Vec::new(),
+        };
+        characters
+            .into_iter()
+            .take(CHARACTER_ROWS)
+            .map(|c| {
+                let first_name = c.name.split_whitespace().next().unwrap_or_default();
+                format!("{} {:<5.5} {}", glyphs.character(), first_name, c.activity)
+            })
+            .collect()
+    }
+
+    /// The heat of each region on the planet in view under the shown
+    /// overlay, scaled from its lowest value to its highest, and a legend
+    /// naming that range
+    fn overlay_heat(
+        simulation: &Simulation,
+        overlay: Option<&ShownOverlay>,
+        zoom_level: ZoomLevel,
+    ) -> Option<(BTreeMap<EntityId, Tone>, String)> {
+        let overlay = overlay?;
+        if zoom_level != ZoomLevel::Planet {
+            return None;
+        }
+        let planet = simulation.zoom().position().map_owner(zoom_level)?;
+        let values = match overlay.query.evaluate_planet(simulation.world(), planet) {
+            Ok(values) => values,
+            Err(error) => return Some((BTreeMap::new(), format!("{}: {}", overlay.name, error))),
+        };
+        let low = values
+            .iter()
+            .map(|&(_, value)| value)
+            .fold(f64::INFINITY, f64::min);
+        let high = values
+            .iter()
+            .map(|&(_, value)| value)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let heat = values
+            .into_iter()
+            .map(|(region, value)| {
+                let share = if high > low {
+                    (value - low) / (high - low)
+                } else {
+                    0.5
+                };
+                (region, Tone::heat(share))
+            })
+            .collect::<BTreeMap<_, _>>();
+        let legend = if heat.is_empty() {
+            format!("{}: no values here", overlay.name)
+        } else {
+            format!("{}: {:.2} to {:.2}", overlay.name, low, high)
+        };
+        Some((heat, legend))
+    }
+
+    /// The star under the cursor on the galaxy map, named with its class
+    /// and population in place of the view's title
+    fn star_legend(simulation: &Simulation, zoom_level: ZoomLevel) -> Option<String> {
+        if zoom_level != ZoomLevel::Galaxy {
+            return None;
+        }
+        let world = simulation.world();
+        let coords = simulation.zoom().position().coords_for_level(zoom_level);
+        let system = world.entity_at(ZoomLevel::SolarSystem, None, coords)?;
+        let star = world.get_system(system)?.star;
+        let population = world
+            .census()
+            .figures(ZoomLevel::SolarSystem, system)
+            .map_or(0, |figures| figures.population);
+        let people = match population {
+            0 => String::from("uninhabited"),
+            people => format!("{} people", charts::compact(people as f64)),
+        };
+        Some(format!(
+            "{}: class {}, {}",
+            world.entity_name(ZoomLevel::SolarSystem, system),
+            star.letter(),
+            people
+        ))
+    }
+
+    /// The furniture under the cursor in a room and how to use it, in place
+    /// of the view's title
+    fn furnishing_legend(simulation: &Simulation) -> Option<String> {
+        let (_, piece) = simulation.furnishing_in_view()?;
+        Some(format!("[ENTER] Use the {}", piece.kind.name()))
+    }
+
+    /// The land around the player as colored tiles two columns wide. The
+    /// galaxy shows each system as its star, glyph by class and colored by
+    /// how many live there, with the one under the cursor highlighted, and
+    /// the space each polity holds around them; a system shows its planets; a
+    /// planet shows each region's most common biome and the bare surface
+    /// between regions; a region shows its own terrain map with its areas
+    /// marked on it; a room shows its furniture, the piece under the cursor
+    /// highlighted. Ships under way are marked on the galaxy and system maps.
+    /// On the galaxy and planets a border glyph takes the place of the gap
+    /// between tiles held by different polities.
+    fn surface_lines(
+        simulation: &Simulation,
+        glyphs: &Glyphs,
+        zoom_level: ZoomLevel,
+        heat: Option<&BTreeMap<EntityId, Tone>>,
+        camera: Option<(i32, i32)>,
+    ) -> Option<Vec<Line>> {
+        let world = simulation.world();
+        let position = simulation.zoom().position();
+        let (x, y) = position.coords_for_level(zoom_level);
+        let (cx, cy) = camera.unwrap_or_else(|| Self::player_center(simulation, zoom_level));
+        let (half_width, half_height) = (REGION_MAP_WIDTH as i32 / 2, REGION_MAP_HEIGHT as i32 / 2);
+        let tile = |biome: Biome| (glyphs.biome(biome), biome_tone(biome), None);
+        let around = |cell: &dyn Fn(i32, i32) -> (char, Tone, Option<EntityId>)| {
+            (cy - half_height..=cy + half_height)
+                .map(|row| {
+                    (cx - half_width..cx + half_width)
+                        .map(|column| cell(column, row))
+                        .collect()
+                })
+                .collect()
+        };
+
+        let rows: Vec<Vec<(char, Tone, Option<EntityId>)>> = match zoom_level {
+            ZoomLevel::Galaxy => {
+                let systems = world.placed_in(ZoomLevel::SolarSystem, None);
+                let ships = world.ships_on_map(ZoomLevel::Galaxy, None);
+                let population = |system: EntityId| {
+                    world
+                        .census()
+                        .figures(ZoomLevel::SolarSystem, system)
+                        .map_or(0, |figures| figures.population)
+                };
+                let most = systems
+                    .iter()
+                    .map(|&(id, _)| population(id))
+                    .max()
+                    .unwrap_or(0);
+                let star = |system: EntityId| {
+                    let class = world.get_system(system).map(|s| s.star).unwrap_or_default();
+                    let tone = stars::population_share(population(system), most)
+                        .map_or(Tone::Steady, Tone::heat);
+                    (glyphs.star(class), tone)
+                };
+                let holder = |system: EntityId| {
+                    world
+                        .polity_of(ZoomLevel::SolarSystem, system)
+                        .map(|polity| polity.id)
+                };
+                around(&|column, row| {
+                    let nearest = systems
+                        .iter()
+                        .map(|&(id, (sx, sy))| ((sx - column).abs().max((sy - row).abs()), id))
+                        .filter(|&(distance, _)| distance <= SYSTEM_REACH)
+                        .min();
+                    let held = nearest.and_then(|(_, id)| holder(id));
+                    let here = (column, row) == (x, y);
+                    if let Some((0, system)) = nearest {
+                        let (glyph, tone) = star(system);
+                        (glyph, if here { Tone::Selected } else { tone }, held)
+                    } else if here {
+                        (glyphs.you(), Tone::Plain, held)
+                    } else if ships.iter().any(|&(_, at)| at == (column, row)) {
+                        (glyphs.ship(), Tone::Plain, held)
+                    } else if held.is_some() {
+                        (glyphs.empty(), Tone::Border, held)
+                    } else {
+                        (' ', Tone::Plain, None)
+                    }
+                })
+            }
+            ZoomLevel::SolarSystem => {
+                let owner = position.map_owner(zoom_level)?;
+                let ships = world.ships_on_map(ZoomLevel::SolarSystem, Some(owner));
+                around(&|column, row| {
+                    if (column, row) == (x, y) {
+                        (glyphs.you(), Tone::Plain, None)
+                    } else if ships.iter().any(|&(_, at)| at == (column, row)) {
+                        (glyphs.ship(), Tone::Plain, None)
+                    } else if world
+                        .entity_at(ZoomLevel::Planet, Some(owner), (column, row))
+                        .is_some()
+                    {
+                        (glyphs.level(ZoomLevel::Planet), Tone::Festive, None)
+                    } else {
+                        (' ', Tone::Plain, None)
+                    }
+                })
+            }
+            ZoomLevel::Planet => {
+                let owner = position.map_owner(zoom_level)?;
+                let surface = world.surface(owner);
+                let planet_holder = world
+                    .polity_of(ZoomLevel::Planet, owner)
+                    .map(|polity| polity.id);
+                around(&|column, row| {
+                    let region = world.entity_at(ZoomLevel::Region, Some(owner), (column, row));
+                    let held = match region {
+                        Some(id) => world
+                            .polity_of(ZoomLevel::Region, id)
+                            .map(|polity| polity.id),
+                        None => planet_holder,
+                    };
+                    if (column, row) == (x, y) {
+                        return (glyphs.you(), Tone::Plain, held);
+                    }
+                    if let Some(&tone) = region.and_then(|id| heat.and_then(|heat| heat.get(&id))) {
+                        return (glyphs.heat(), tone, held);
+                    }
+                    let terrain = region
+                        .and_then(|id| world.get_region(id))
+                        .and_then(|region| region.terrain.as_ref());
+                    let (glyph, tone, _) = tile(match terrain {
+                        Some(map) => map.dominant(),
+                        None => surface.biome(column as f64, row as f64, None),
+                    });
+                    (glyph, tone, held)
+                })
+            }
+            ZoomLevel::Region => {
+                let owner = position.map_owner(zoom_level)?;
+                let map = world.get_region(owner)?.terrain.as_ref()?;
+                around(&|column, row| {
+                    let biome = usize::try_from(column + half_width)
+                        .ok()
+                        .zip(usize::try_from(row + half_height).ok())
+                        .and_then(|(tx, ty)| map.get(tx, ty));
+                    if (column, row) == (x, y) {
+                        (glyphs.you(), Tone::Plain, None)
+                    } else if world
+                        .entity_at(ZoomLevel::LocalArea, Some(owner), (column, row))
+                        .is_some()
+                    {
+                        (glyphs.level(ZoomLevel::LocalArea), Tone::Plain, None)
+                    } else if let Some(biome) = biome {
+                        tile(biome)
+                    } else {
+                        (' ', Tone::Plain, None)
+                    }
+                })
+            }
+            ZoomLevel::LocalArea => {
+                let owner = position.map_owner(zoom_level)?;
+                let economy = world.economy();
+                let walking: BTreeSet<(i32, i32)> =
+                    world.characters_in_area(owner).map(|c| c.coords).collect();
+                around(&|column, row| {
+                    let coords = (column, row);
+                    if coords == (x, y) {
+                        (glyphs.you(), Tone::Plain, None)
+                    } else if let Some(firm) = economy.firm_at(owner, coords) {
+                        let kind = economy.recipes().get(firm.recipe).map(|r| r.building);
+                        let glyph =
+                            kind.map_or(glyphs.get("building"), |kind| glyphs.building(kind));
+                        (glyph, Tone::Plain, None)
+                    } else if let Some(site) = economy.site_at(owner, coords) {
+                        (glyphs.site(site.progress()), Tone::Plain, None)
+                    } else if world
+                        .entity_at(ZoomLevel::Room, Some(owner), coords)
+                        .is_some()
+                    {
+                        (glyphs.level(ZoomLevel::Room), Tone::Plain, None)
+                    } else if walking.contains(&coords) {
+                        (glyphs.character(), Tone::Plain, None)
+                    } else {
+                        (glyphs.empty(), Tone::Plain, None)
+                    }
+                })
+            }
+            ZoomLevel::Room => {
+                let owner = position.map_owner(zoom_level)?;
+                let furniture = &world.get_room(owner)?.furniture;
+                let bounds = world.map_bounds(ZoomLevel::Room);
+                around(&|column, row| {
+                    let coords = (column, row);
+                    let here = coords == (x, y);
+                    if let Some(piece) = furniture.iter().find(|piece| piece.coords == coords) {
+                        let tone = if here { Tone::Selected } else { Tone::Festive };
+                        (glyphs.furniture(piece.kind), tone, None)
+                    } else if here {
+                        (glyphs.you(), Tone::Plain, None)
+                    } else if bounds.contains(coords) {
+                        (glyphs.empty(), Tone::Plain, None)
+                    } else {
+                        (' ', Tone::Plain, None)
+                    }
+                })
+            }
+        };
+
+        Some(
+            rows.into_iter()
+                .map(|tiles| {
+                    let mut line = Line::default();
+                    let mut tiles = tiles.into_iter().peekable();
+                    while let Some((glyph, tone, held)) = tiles.next() {
+                        line = line.push(glyph.to_string(), tone);
+                        line = match tiles.peek() {
+                            Some(&(_, _, next)) if next != held => {
+                                line.push(glyphs.border().to_string(), Tone::Border)
+                            }
+                            _ => line.push(" ", Tone::Plain),
+                        };
+                    }
+                    line
+                })
+                .collect(),
+        )
+    }
+
+    /// One-line price board for the market of the area the player is in,
+    /// shown at area and room zoom
+    fn market_summary(
+        world_state: &WorldState,
+        position: &Position,
+        zoom_level: ZoomLevel,
+    ) -> Option<String> {
+        if zoom_level > ZoomLevel::LocalArea {
+            return None;
+        }
+
+        let area_id = position.current_entity_id(ZoomLevel::LocalArea)?;
+        let economy = world_state.economy();
+        let market = economy.market(area_id)?;
+        let prices: Vec<String> = economy
+            .catalog()
+            .iter()
+            .map(|(id, good)| format!("{} {:.2}", good.name, market.price(id)))
+            .collect();
+        let workers: u32 = economy.firms_in_area(area_id).map(|f| f.workers).sum();
+        let status = match world_state.market_status(area_id) {
+            Some(status) if !status.is_open() => format!(", {}", status),
+            _ => String::new(),
+        };
+
+        Some(format!(
+            "Market ({} workers{}): {}",
+            workers,
+            status,
+            prices.join(" | ")
+        ))
+    }
+
+    /// One line per bookmark slot, empty slots included
+    fn bookmark_lines(world: &WorldState) -> Vec<String> {
+        (1..=BOOKMARK_SLOTS)
+            .map(|slot| match world.bookmarks().get(slot) {
+                Some(bookmark) => format!("{}  {} ({})", slot, bookmark.name, bookmark.level),
+                None => format!("{}  -", slot),
+            })
+            .collect()
+    }
+
+    /// The query being typed, then its matches with the chosen one marked
+    fn search_lines(world: &WorldState, search: &Search) -> Vec<String> {
+        let mut lines = vec![format!("/{}", search.query().with_cursor()), String::new()];
+        let hits = search.hits(world);
+        if hits.is_empty() && !search.query().is_empty() {
+            lines.push(String::from("  No matches"));
+        }
+        for (index, hit) in hits.iter().enumerate() {
+            let marker = if index == search.selected() { '>' } else { ' ' };
+            lines.push(format!("{} {} ({})", marker, hit.name, hit.level));
+        }
+        lines
+    }
+
+    /// The festivals under way on the planet in view, framed by the
+    /// festival glyph
+    fn festival_banner(
+        simulation: &Simulation,
+        glyphs: &Glyphs,
+        zoom_level: ZoomLevel,
+    ) -> Option<Line> {
+        if zoom_level > ZoomLevel::Planet {
+            return None;
+        }
+        let planet = simulation.zoom().position().current_planet_id?;
+        let names: Vec<&str> = simulation
+            .world()
+            .festivals_on(planet)
+            .map(|festival| festival.name.as_str())
+            .collect();
+        if names.is_empty() {
+            return None;
+        }
+        let glyph = glyphs.festival();
+        Some(Line::default().push(
+            format!(" {} {} {} ", glyph, names.join(" & "), glyph),
+            Tone::Festive,
+        ))
+    }
+
+    /// Location names joined outermost first, dropping the outermost ones
+    /// behind an ellipsis when the whole path is wider than `width`
+    fn breadcrumb(path: &[String], width: usize) -> String {
+        const SEPARATOR: &str = " > ";
+        let full = path.join(SEPARATOR);
+        if full.chars().count() <= width {
+            return full;
+        }
+        for start in 1..path.len() {
+            let line = format!("…{}{}", SEPARATOR, path[start..].join(SEPARATOR));
+            if line.chars().count() <= width {
+                return line;
+            }
+        }
+        full
+    }
+
+    fn draw_game(canvas: &mut dyn Renderer, state: &RenderState) {
+        let Some(mut layout) = Layout::compute(canvas.width(), canvas.height()) else {
+            Self::draw_too_small(canvas);
+            return;
+        };
+
+        layout = state.screens.arrange(layout);
+
+        let header = layout.header;
+        canvas.draw_box(header.x, header.y, header.width, header.height);
+        let (pause_indicator, pause_tone) = if state.is_paused {
+            ("[PAUSED]", Tone::Paused)
+        } else if state.is_turbo {
+            ("[TURBO]", Tone::Warning)
+        } else {
+            ("[PLAYING]", Tone::Plain)
+        };
+        let speed = if state.is_turbo {
+            String::from("max")
+        } else {
+            format!("{:.1}x", state.speed)
+        };
+        // The clock can't keep up with the speed set
+        let lagging = if state.is_lagging { " (lagging)" } else { "" };
+        let status = match layout.preset {
+            Preset::Minimal => spans![
+                format!("{} | {} | ", state.zoom_level, state.date_str),
+                pause_indicator => pause_tone,
+                format!(" {}", speed),
+                lagging => Tone::Warning,
+            ],
+            Preset::Full | Preset::Compact => spans![
+                format!(
+                    "Econogenesis v0.1.0 | {} | {} | ",
+                    state.zoom_level, state.date_str
+                ),
+                pause_indicator => pause_tone,
+                format!(" {}", speed),
+                lagging => Tone::Warning,
+                " | ",
+                format!("FPS: {:.1}", state.fps) => match state.is_behind {
+                    true => Tone::Warning,
+                    false => Tone::Healthy,
+                },
+                if state.is_behind { " (behind)" } else { "" } => Tone::Warning,
+            ],
+        };
+        let status = status.truncated(header.width.saturating_sub(4) as usize);
+        canvas.draw_line(header.x + 2, header.y + 1, &status);
+        if let Some(banner) = &state.festival {
+            let x = (header.x + header.width).saturating_sub(banner.width() as u16 + 2);
+            if x > header.x + 1 {
+                canvas.draw_line(x, header.y, banner);
+            }
+        }
+        let breadcrumb = layout.breadcrumb;
+        canvas.draw_text(
+            breadcrumb.x,
+            breadcrumb.y,
+            &Self::breadcrumb(&state.location_path, breadcrumb.width as usize),
+        );
+
+        let content = layout.content;
+        canvas.draw_box(content.x, content.y, content.width, content.height);
+
+        if state.screens.covers_view() {
+            state.screens.draw(canvas, &layout, Placement::Overlay);
+        } else {
+            match &state.editor_map {
+                Some(rows) => Self::draw_editor_map(canvas, layout.view, rows),
+                None => {
+                    Self::draw_zoom_view(
+                        canvas,
+                        layout.view,
+                        state.zoom_level,
+                        state.surface.as_deref(),
+                        state.legend.as_deref(),
+                    );
+                    Self::draw_characters(canvas, layout.view, &state.characters);
+                }
+            }
+
+            let (info_x, info_y) = (layout.info.x, layout.info.y);
+            let coords = state.position.coords_for_level(state.zoom_level);
+            let camera = state
+                .camera
+                .map(|(x, y)| format!(" | Camera: ({}, {})", x, y))
+                .unwrap_or_default();
+            let mut info_lines = match layout.preset {
+                Preset::Full => vec![
+                    Line::plain(format!("Simulation Time: {}", state.time_str)),
+                    Line::plain(format!("Location: {}", state.entity_name)),
+                    Line::plain(format!("Position: ({}, {}){}", coords.0, coords.1, camera)),
+                ],
+                // The breadcrumb already names the location
+                Preset::Compact | Preset::Minimal => vec![Line::plain(format!(
+                    "Time: {} | Position: ({}, {}){}",
+                    state.time_str, coords.0, coords.1, camera
+                ))],
+            };
+            info_lines.push(
+                Line::plain(format!(
+                    "World: {} entities | Tick: {} | ",
+                    state.entity_count, state.tick_count
+                ))
+                .append(state.money_summary.clone()),
+            );
+            if let Some(palette) = &state.palette {
+                info_lines.push(Line::plain(palette.clone()));
+            } else if let Some(objective) = &state.objective {
+                let deadline = state
+                    .deadline
+                    .as_ref()
+                    .map(|progress| format!(" | {}", progress.render()))
+                    .unwrap_or_default();
+                info_lines.push(Line::plain(format!("Objective: {}{}", objective, deadline)));
+            } else if let Some(summary) = &state.market_summary {
+                info_lines.push(Line::plain(summary.clone()));
+            }
+            for (i, line) in info_lines.into_iter().enumerate() {
+                let line = line.truncated(layout.info.width as usize);
+                canvas.draw_line(info_x, info_y + i as u16, &line);
+            }
+            if let Some(events) = layout.events {
+                Self::draw_event_log(canvas, events, &state.recent_events);
+            }
+            state.screens.draw(canvas, &layout, Placement::Beside);
+        }
+
+        let footer = layout.footer;
+        canvas.draw_box(footer.x, footer.y, footer.width, footer.height);
+        let status_y = footer.y + 1;
+        // Stop short of the bottom-right corner so the box stays closed
+        let status_width = footer.width.saturating_sub(4) as usize;
+        let status = if state.screens.draw(canvas, &layout, Placement::Footer) {
+            // The console has the status line
+            None
+        } else if let Some(progress) = &state.fast_forward {
+            Some(Line::hotkeys(&format!(
+                "{} | [SPACE] Stop",
+                progress.render()
+            )))
+        } else if let Some(progress) = &state.journey {
+            Some(Line::hotkeys(&format!(
+                "{} | [SPACE] Play/Pause | [C] Free camera",
+                progress.render()
+            )))
+        } else if state.editor_map.is_some() {
+            let controls_text = "[ARROWS] Move | [Z/X] Zoom | [[/]] Palette | [SPACE] Place | [DEL] Remove | [V] Mark | [C] Copy | [P] Paste | [U] Undo | [A] Attributes | [S] Save | [E] Exit";
+            Some(Line::hotkeys(controls_text))
+        } else if layout.preset == Preset::Minimal {
+            Some(Line::hotkeys("[H/?] Help | [:] Console | [Q] Quit"))
+        } else {
+            let controls_text = "[ARROWS] Move | [ENTER] Enter | [Z/X] Zoom | [:] Console | [H/?] Help | [ESC] Menu";
+            Some(Line::hotkeys(controls_text))
+        };
+        if let Some(status) = status {
+            canvas.draw_line(2, status_y, &status.truncated(status_width));
+        }
+
+        if let Some((title, lines)) = &state.form {
+            Self::draw_form(canvas, layout.overlay, title, lines);
+        }
+
+        if let Some(lines) = &state.bookmarks {
+            draw_panel(
+                canvas,
+                layout.overlay,
+                "Bookmarks",
+                lines,
+                "[1-9] Jump  [ESC] Close  Shift+1-9 bookmarks the view",
+            );
+        }
+
+        if let Some(lines) = &state.search {
+            draw_panel(
+                canvas,
+                layout.overlay,
+                "Search",
+                lines,
+                "[UP/DOWN] Choose  [ENTER] Jump  [ESC] Close",
+            );
+        }
+
+        if let Some(screen) = state.interstitial {
+            Self::draw_interstitial(canvas, layout.overlay, screen);
+        }
+
+        if let Some(prompt) = &state.tutorial {
+            Self::draw_tutorial(canvas, layout.content, prompt);
+        }
+
+        if state.quit_prompt {
+            Self::draw_quit_prompt(canvas, layout.overlay);
+        }
+
+        state.screens.draw(canvas, &layout, Placement::Corner);
+        Self::draw_toasts(canvas, layout.content, &state.toasts);
+    }
+
+    /// Notices stacked down the top-right corner of the content box, over
+    /// whatever is beneath them
+    fn draw_toasts(canvas: &mut dyn Renderer, area: Rect, toasts: &[Line]) {
+        let width = area.width.saturating_sub(4) as usize;
+        let rows = area.height.saturating_sub(2) as usize;
+        for (i, toast) in toasts.iter().take(rows).enumerate() {
+            let toast = toast.clone().truncated(width);
+            let x = (area.x + area.width).saturating_sub(toast.width() as u16 + 2);
+            canvas.draw_line(x, area.y + 1 + i as u16, &toast);
+        }
+    }
+
+    /// The tutorial's step in a box along the bottom of the content box,
+    /// over whatever is beneath it
+    fn draw_tutorial(canvas: &mut dyn Renderer, area: Rect, prompt: &str) {
+        const SKIP: &str = "  [S] Skip";
+        if area.height < 5 || area.width < 6 {
+            return;
+        }
+        let width = (area.width - 4) as usize;
+        let text: String = format!(" {}{} ", prompt, SKIP)
+            .chars()
+            .take(width - 2)
+            .collect();
+        let box_width = text.chars().count() as u16 + 2;
+        let (x, y) = (area.x + 2, area.y + area.height - 4);
+        canvas.draw_box(x, y, box_width, 3);
+        canvas.draw_text(x + 1, y + 1, &text);
+    }
+
+    /// A chapter's title and introduction, wrapped to fit the overlay
+    fn draw_interstitial(canvas: &mut dyn Renderer, area: Rect, screen: &Interstitial) {
+        const FOOTER: &str = "[ENTER] Continue";
+        let width = (area.width as usize).saturating_sub(4).min(60);
+        let mut lines = vec![String::new()];
+        for word in screen.text.split_whitespace() {
+            let line = lines.last_mut().expect("lines starts non-empty");
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+                lines.push(word.to_string());
+            } else {
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                line.push_str(word);
+            }
+        }
+        let rows = (area.height as usize).saturating_sub(6);
+        lines.truncate(rows);
+
+        let (x, y) = (area.x, area.y);
+        let border = "═".repeat(width + 2);
+        canvas.draw_text(x, y, &format!("╔{}╗", border));
+        canvas.draw_text(x, y + 1, &format!("║ {:<width$} ║", screen.title));
+        canvas.draw_text(x, y + 2, &format!("╠{}╣", border));
+        for (i, line) in lines.iter().enumerate() {
+            canvas.draw_text(x, y + 3 + i as u16, &format!("║ {:<width$} ║", line));
+        }
+        let footer_y = y + 3 + lines.len() as u16;
+        canvas.draw_text(x, footer_y, &format!("║ {:<width$} ║", FOOTER));
+        canvas.draw_text(x, footer_y + 1, &format!("╚{}╝", border));
+    }
+
+    /// Recent event log lines, drawn beside the zoom view
+    fn draw_event_log(canvas: &mut dyn Renderer, area: Rect, events: &[String]) {
+        let max_len = area.width as usize;
+        let rows = area.height.saturating_sub(1) as usize;
+
+        canvas.draw_text(area.x, area.y, "Recent Events");
+        let skip = events.len().saturating_sub(rows);
+        for (i, line) in events.iter().skip(skip).enumerate() {
+            let line: String = line.chars().take(max_len).collect();
+            canvas.draw_text(area.x, area.y + 1 + i as u16, &line);
+        }
+    }
+
+    /// A form as a box sized to its longest line, over the info panel
+    fn draw_form(canvas: &mut dyn Renderer, area: Rect, title: &str, lines: &[String]) {
+        const FOOTER: &str = "[ENTER] Save  [TAB] Next field  [ESC] Cancel";
+        draw_panel(canvas, area, title, lines, FOOTER);
+    }
+
+    fn draw_quit_prompt(canvas: &mut dyn Renderer, area: Rect) {
+        let (x, prompt_y) = (area.x, area.y);
+
+        canvas.draw_text(x, prompt_y, "╔══════════════════════════════════════╗");
+        canvas.draw_text(x, prompt_y + 1, "║    Save your game before quitting?   ║");
+        canvas.draw_text(x, prompt_y + 2, "║                                      ║");
+        canvas.draw_text(x, prompt_y + 3, "║    [Y] Save  [N] Discard  [C] Stay   ║");
+        canvas.draw_text(x, prompt_y + 4, "╚══════════════════════════════════════╝");
+    }
+
+    fn draw_shutdown_errors(canvas: &mut dyn Renderer, report: &ShutdownReport) {
+        let width = canvas.width();
+        canvas.draw_box(0, 0, width, report.failures().len() as u16 + 6);
+        canvas.draw_text(2, 1, "Problems occurred while shutting down:");
+
+        for (i, failure) in report.failures().iter().enumerate() {
+            canvas.draw_text(
+                4,
+                3 + i as u16,
+                &format!("{}: {}", failure.stage, failure.message),
+            );
+        }
+
+        let footer_y = 4 + report.failures().len() as u16;
+        canvas.draw_text(2, footer_y, "Press any key to exit.");
+    }
+
+    /// Shown instead of the game until the terminal is big enough for it,
+    /// centered in whatever room there is
+    fn draw_too_small(canvas: &mut dyn Renderer) {
+        let (width, height) = (canvas.width(), canvas.height());
+        let lines = [
+            String::from("Terminal too small"),
+            format!("Resize to at least {}×{}", MIN_WIDTH, MIN_HEIGHT),
+            format!("Now {}×{}", width, height),
+        ];
+        let top = height.saturating_sub(lines.len() as u16) / 2;
+        for (i, line) in lines.iter().enumerate() {
+            let line: String = line.chars().take(width as usize).collect();
+            let x = width.saturating_sub(line.chars().count() as u16) / 2;
+            canvas.draw_text(x, top + i as u16, &line);
+        }
+    }
+
+    /// The sandbox editor's grid, drawn in place of the zoom view art
+    fn draw_editor_map(canvas: &mut dyn Renderer, view: Rect, rows: &[String]) {
+        let (x, view_y) = (view.x, view.y);
+
+        canvas.draw_text(x, view_y, "╔════════════════════════════════════╗");
+        canvas.draw_text(x, view_y + 1, "║    SANDBOX EDITOR                  ║");
+        for (i, row) in rows.iter().enumerate() {
+            let y = view_y + 2 + i as u16;
+            canvas.draw_text(x, y, &format!("║ {:<34} ║", row));
+        }
+        let bottom = view_y + 2 + rows.len() as u16;
+        canvas.draw_text(x, bottom, "╚════════════════════════════════════╝");
+    }
+
+    /// Characters listed in the empty right half of the zoom view
+    fn draw_characters(canvas: &mut dyn Renderer, view: Rect, lines: &[String]) {
+        for (i, line) in lines.iter().enumerate() {
+            canvas.draw_text(view.x + 22, view.y + 3 + i as u16, line);
+        }
+    }
+
+    fn draw_zoom_view(
+        canvas: &mut dyn Renderer,
+        view: Rect,
+        level: ZoomLevel,
+        surface: Option<&[Line]>,
+        legend: Option<&str>,
+    ) {
+        let (x, view_y) = (view.x, view.y);
+
+        match level {
+            ZoomLevel::Galaxy => {
+                canvas.draw_text(x, view_y, "╔════════════════════════════════════╗");
+                canvas.draw_text(x, view_y + 1, "║      GALAXY VIEW                   ║");
+                for row in view_y + 2..view_y + 9 {
+                    canvas.draw_text(x, row, "║                                    ║");
+                }
+                canvas.draw_text(x, view_y + 9, "╚════════════════════════════════════╝");
+            }
+            ZoomLevel::SolarSystem => {
+                canvas.draw_text(x, view_y, "╔════════════════════════════════════╗");
+                canvas.draw_text(x, view_y + 1, "║    SOLAR SYSTEM VIEW               ║");
+                canvas.draw_text(x, view_y + 2, "║                                    ║");
+                canvas.draw_text(x, view_y + 3, "║              ☉                     ║");
+                canvas.draw_text(x, view_y + 4, "║         o                          ║");
+                canvas.draw_text(x, view_y + 5, "║     o       YOU   O                ║");
+                canvas.draw_text(x, view_y + 6, "║   o                    o           ║");
+                canvas.draw_text(x, view_y + 7, "║                                    ║");
+                canvas.draw_text(x, view_y + 8, "║                  O                 ║");
+                canvas.draw_text(x, view_y + 9, "╚════════════════════════════════════╝");
+            }
+            ZoomLevel::Planet => {
+                canvas.draw_text(x, view_y, "╔════════════════════════════════════╗");
+                canvas.draw_text(x, view_y + 1, "║      PLANET VIEW                   ║");
+                canvas.draw_text(x, view_y + 2, "║                                    ║");
+                canvas.draw_text(x, view_y + 3, "║        ~~~~~  ~~~~                 ║");
+                canvas.draw_text(x, view_y + 4, "║    ~~~~       ^^^^  ~~~            ║");
+                canvas.draw_text(x, view_y + 5, "║  ~~~    ^^^^ YOU ^^^^   ~~~        ║");
+                canvas.draw_text(x, view_y + 6, "║    ^^^^       ~~~~                 ║");
+                canvas.draw_text(x, view_y + 7, "║       ^^^^  ~~~~~   ^^^^           ║");
+                canvas.draw_text(x, view_y + 8, "║                                    ║");
+                canvas.draw_text(x, view_y + 9, "╚════════════════════════════════════╝");
+            }
+            ZoomLevel::Region => {
+                canvas.draw_text(x, view_y, "╔════════════════════════════════════╗");
+                canvas.draw_text(x, view_y + 1, "║      REGION VIEW                   ║");
+                canvas.draw_text(x, view_y + 2, "║                                    ║");
+                canvas.draw_text(x, view_y + 3, "║   ♣  ♠  ♣                          ║");
+                canvas.draw_text(x, view_y + 4, "║  ♠ ♣    ♠  ♣                       ║");
+                canvas.draw_text(x, view_y + 5, "║   ♣  ♠ YOU  ♣  ♠                   ║");
+                canvas.draw_text(x, view_y + 6, "║  ♠    ♣  ♠    ♣                    ║");
+                canvas.draw_text(x, view_y + 7, "║   ♣  ♠    ♣  ♠                     ║");
+                canvas.draw_text(x, view_y + 8, "║                                    ║");
+                canvas.draw_text(x, view_y + 9, "╚════════════════════════════════════╝");
+            }
+            ZoomLevel::LocalArea => {
+                canvas.draw_text(x, view_y, "╔════════════════════════════════════╗");
+                canvas.draw_text(x, view_y + 1, "║    LOCAL AREA VIEW                 ║");
+                canvas.draw_text(x, view_y + 2, "║                                    ║");
+                canvas.draw_text(x, view_y + 3, "║   ▓▓▓▓     ▓▓▓                     ║");
+                canvas.draw_text(x, view_y + 4, "║   ▓  ▓     ▓ ▓                     ║");
+                canvas.draw_text(x, view_y + 5, "║   ▓  ▓  @ YOU                      ║");
+                canvas.draw_text(x, view_y + 6, "║   ▓▓▓▓     ▓▓▓                     ║");
+                canvas.draw_text(x, view_y + 7, "║            ▓ ▓                     ║");
+                canvas.draw_text(x, view_y + 8, "║                                    ║");
+                canvas.draw_text(x, view_y + 9, "╚════════════════════════════════════╝");
+            }
+            ZoomLevel::Room => {
+                canvas.draw_text(x, view_y, "╔════════════════════════════════════╗");
+                canvas.draw_text(x, view_y + 1, "║       ROOM VIEW                    ║");
+                canvas.draw_text(x, view_y + 2, "║ ┌─────────────────┐                ║");
+                canvas.draw_text(x, view_y + 3, "║ │                 │                ║");
+                canvas.draw_text(x, view_y + 4, "║ │  [Table]        │                ║");
+                canvas.draw_text(x, view_y + 5, "║ │         @ YOU   │                ║");
+                canvas.draw_text(x, view_y + 6, "║ │                 │                ║");
+                canvas.draw_text(x, view_y + 7, "║ │      [Chair]    │                ║");
+                canvas.draw_text(x, view_y + 8, "║ └─────────────────┘                ║");
+                canvas.draw_text(x, view_y + 9, "╚════════════════════════════════════╝");
+            }
+        }
+
+        // The map replaces the sketch inside each box
+        for (row, line) in surface.unwrap_or_default().iter().enumerate() {
+            canvas.draw_line(x + 1, view_y + 2 + row as u16, line);
+        }
+        // An overlay's legend takes the title's place
+        if let Some(legend) = legend {
+            let legend: String = legend.chars().take(VIEW_INNER_WIDTH - 2).collect();
+            canvas.draw_text(
+                x + 1,
+                view_y + 1,
+                &format!(" {:<width$} ", legend, width = VIEW_INNER_WIDTH - 2),
+            );
+        }
+    }
+}
+
+/// How a biome is colored on the map
+fn biome_tone(biome: Biome) -> Tone {
+    match biome {
+        Biome::Ocean => Tone::Water,
+        Biome::Coast => Tone::Shallows,
+        Biome::Plains => Tone::Grassland,
+        Biome::Forest => Tone::Woodland,
+        Biome::Desert => Tone::Arid,
+        Biome::Mountains => Tone::Rocky,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::TradingHours;
+    use crate::game::furniture;
+    use crate::game::ships::{Course, Voyage};
+    use crate::game::simulation::FAST_FORWARD_STEP;
+    use crate::game::stars::StarClass;
+    use crate::game::state::Placement;
+    use crate::game::worldgen::WorldConfig;
+    use crate::render::{FrameCapture, HEAT_LEVELS, snapshot};
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use std::time::Duration;
+
+    /// Sizes around every layout threshold, from nothing at all to huge
+    const WIDTHS: [u16; 19] = [
+        0, 1, 2, 5, 12, 30, 38, 43, 44, 45, 59, 60, 79, 80, 81, 119, 120, 121, 300,
+    ];
+    const HEIGHTS: [u16; 13] = [0, 1, 2, 3, 5, 12, 20, 23, 24, 25, 30, 40, 100];
+
+    fn game() -> GameLoop<'static> {
+        let dir =
+            std::env::temp_dir().join(format!("econogenesis-test-screens-{}", std::process::id()));
+        GameLoop::new(FrameCapture::new(80, 24), SaveManager::new(&dir))
+    }
+
+    /// A session with a data directory of its own, so no other test's
+    /// saves show up in its menus
+    fn game_in(name: &str) -> GameLoop<'static> {
+        let saves = SaveManager::new(&crate::save::test_dir(name));
+        GameLoop::new(FrameCapture::new(80, 24), saves)
+    }
+
+    fn press(game: &mut GameLoop, keys: &str) {
+        for key in keys.chars() {
+            let action = game
+                .input_handler
+                .press(KeyCode::Char(key), KeyModifiers::NONE);
+            game.handle_action(action).unwrap();
+        }
+    }
+
+    fn render_at_every_size(game: &mut GameLoop) {
+        for width in WIDTHS {
+            for height in HEIGHTS {
+                game.renderer.resize(width, height).unwrap();
+                game.render().unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_every_screen_renders_at_any_size() {
+        // Keys that open each screen: help, dashboard, census, profiler,
+        // sidebar and map, console, search, forms, bookmarks, editor, and
+        // the local area views
+        let screens = [
+            "", "h", "d", "C", "g", "k", "l", "r", "im", ":", "/ter", "/zzz", "O", "M", "b", "e",
+            "zzzz", "zzzzf", "zza", "zzzza", "zzzzzim",
+        ];
+        for keys in screens {
+            let mut game = game();
+            press(&mut game, keys);
+            render_at_every_size(&mut game);
+        }
+
+        let mut prompt = game();
+        prompt.dirty = true;
+        press(&mut prompt, "q");
+        assert_eq!(prompt.input_handler.mode(), InputMode::Prompt);
+        render_at_every_size(&mut prompt);
+
+        let mut story = game();
+        story.notify(Severity::Critical, "Famine in the Northern Highlands");
+        story.open_interstitial(Interstitial {
+            title: String::from("Chapter one"),
+            text: String::from("A long introduction. ").repeat(40),
+        });
+        render_at_every_size(&mut story);
+    }
+
+    #[test]
+    fn test_help_pages_through_every_key_on_small_terminals() {
+        fn draw(help: &screens::Help, width: u16, height: u16) -> String {
+            let mut capture = FrameCapture::new(width, height);
+            capture.begin_frame().unwrap();
+            help.draw(&mut capture, &Layout::compute(width, height).unwrap());
+            capture.end_frame().unwrap();
+            capture.last_frame().unwrap().to_string()
+        }
+        let mut simulation = Simulation::new();
+
+        let mut help = screens::Help::new(InputMode::Normal);
+        let first = draw(&help, 80, 24);
+        assert!(first.contains("Page 1/"));
+        assert!(first.contains("Play/Pause"));
+
+        // The arrows turn the page while there are pages to turn
+        let mut seen = first;
+        while !seen.contains("Quit") {
+            let turned = help.handle(InputAction::MoveRight, &mut simulation);
+            assert_eq!(turned, Handled::Taken);
+            let frame = draw(&help, 80, 24);
+            assert_ne!(frame, seen, "ran out of pages before the last key");
+            seen = frame;
+        }
+        assert!(!seen.contains("Play/Pause"));
+        help.handle(InputAction::MoveLeft, &mut simulation);
+        assert!(!draw(&help, 80, 24).contains("Quit"));
+
+        // The editor's keys fit on one page of a tall terminal, leaving the
+        // arrows to move the cursor
+        let mut editor = screens::Help::new(InputMode::Editor);
+        let frame = draw(&editor, 120, 60);
+        assert!(frame.contains("Leave the editor") && !frame.contains("Page"));
+        assert_eq!(
+            editor.handle(InputAction::MoveRight, &mut simulation),
+            Handled::Ignored
+        );
+    }
+
+    #[test]
+    fn test_screens_stack_and_leave_other_keys_to_the_game() {
+        let mut game = game();
+        press(&mut game, "hd");
+        assert!(game.screens.is_open(ScreenKind::Help));
+        assert!(game.screens.covers_view());
+
+        // Zooming still works under the dashboard, and closing help leaves
+        // it open
+        press(&mut game, "zh");
+        assert_eq!(
+            game.simulation.zoom().current_level(),
+            ZoomLevel::SolarSystem
+        );
+        assert!(!game.screens.is_open(ScreenKind::Help));
+        assert!(game.screens.is_open(ScreenKind::EconomyDashboard));
+        render_at_every_size(&mut game);
+
+        press(&mut game, ":speed 2");
+        assert_eq!(game.input_handler.mode(), InputMode::Console);
+        let action = game.input_handler.press(KeyCode::Enter, KeyModifiers::NONE);
+        game.handle_action(action).unwrap();
+        assert_eq!(game.input_handler.mode(), InputMode::Normal);
+        assert!(!game.screens.is_open(ScreenKind::Console));
+        assert!(game.dirty);
+
+        press(&mut game, "d");
+        assert!(!game.screens.covers_view());
+    }
+
+    fn key(game: &mut GameLoop, code: KeyCode) -> bool {
+        let action = game.input_handler.press(code, KeyModifiers::NONE);
+        game.handle_action(action).unwrap()
+    }
+
+    #[test]
+    fn test_f3_shows_the_debug_overlay_over_the_view() {
+        let mut game = game();
+        key(&mut game, KeyCode::F(3));
+        assert!(game.screens.is_open(ScreenKind::DebugOverlay));
+        assert!(!game.screens.covers_view());
+        game.profiler.record_breakdown(FrameBreakdown {
+            update: Duration::from_millis(3),
+            ticks: 1,
+            ..FrameBreakdown::default()
+        });
+        render_at_every_size(&mut game);
+
+        // The overlay stays up under other screens and leaves keys to them
+        press(&mut game, "h");
+        assert!(game.screens.is_open(ScreenKind::Help));
+        render_at_every_size(&mut game);
+        key(&mut game, KeyCode::F(3));
+        assert!(!game.screens.is_open(ScreenKind::DebugOverlay));
+        assert!(game.screens.is_open(ScreenKind::Help));
+    }
+
+    #[test]
+    #[cfg(feature = "scripting")]
+    fn test_watches_pinned_from_the_console_show_on_the_debug_overlay() {
+        let mut capture = FrameCapture::new(100, 30);
+        let saves = SaveManager::new(&crate::save::test_dir("watch_overlay"));
+        let mut game = GameLoop::new(&mut capture, saves);
+        press(&mut game, ":watch sum firms on Terra");
+        key(&mut game, KeyCode::Enter);
+        assert_eq!(game.simulation.watches().len(), 1);
+        key(&mut game, KeyCode::F(3));
+        game.render().unwrap();
+        drop(game);
+
+        let frame = capture.rows().join("\n");
+        assert!(frame.contains("Watches, every 20 ticks"), "{}", frame);
+        assert!(frame.contains("1 sum firms on Terra = 6.000"), "{}", frame);
+    }
+
+    #[test]
+    fn test_diff_opens_a_report_to_jump_from() {
+        let mut game = game_in("diff_report");
+        // Terra had fewer people when the game was saved
+        let mut world = WorldState::new();
+        world.set_population(ZoomLevel::Planet, 1, 10);
+        let path = crate::save::test_dir("diff_report_save").join("before.json");
+        SaveGame::new(Duration::ZERO, ZoomLevel::Galaxy, Position::new(), world)
+            .write_to(&path)
+            .unwrap();
+
+        press(&mut game, &format!(":diff {}", path.display()));
+        key(&mut game, KeyCode::Enter);
+        assert!(game.screens.is_open(ScreenKind::DiffReport));
+        assert_eq!(game.input_handler.mode(), InputMode::Menu);
+        render_at_every_size(&mut game);
+
+        // Enter goes to the place the change is about
+        key(&mut game, KeyCode::Enter);
+        assert!(!game.screens.is_open(ScreenKind::DiffReport));
+        assert_eq!(game.input_handler.mode(), InputMode::Normal);
+        assert_eq!(game.simulation.zoom().current_level(), ZoomLevel::Planet);
+    }
+
+    #[test]
+    fn test_free_camera_pans_without_moving_the_player() {
+        let mut game = game();
+        let glyphs = Glyphs::default();
+        // The player starts over Sol, which stands in for the player's glyph
+        let you = glyphs.star(StarClass::G).to_string();
+        let at = |game: &GameLoop, level| game.simulation.zoom().position().coords_for_level(level);
+        let (x, y) = at(&game, ZoomLevel::Galaxy);
+        let row_of_you = |game: &GameLoop| {
+            GameLoop::surface_lines(
+                &game.simulation,
+                &glyphs,
+                ZoomLevel::Galaxy,
+                None,
+                game.camera_center(),
+            )
+            .unwrap()
+            .iter()
+            .position(|line| line.to_string().contains(&you))
+        };
+        assert_eq!(row_of_you(&game), Some(REGION_MAP_HEIGHT / 2));
+
+        press(&mut game, "c");
+        key(&mut game, KeyCode::Up);
+        key(&mut game, KeyCode::Left);
+        assert_eq!(at(&game, ZoomLevel::Galaxy), (x, y));
+        assert_eq!(game.camera_center(), Some((x - 1, y - 1)));
+        assert_eq!(row_of_you(&game), Some(REGION_MAP_HEIGHT / 2 + 1));
+        render_at_every_size(&mut game);
+
+        key(&mut game, KeyCode::Home);
+        assert_eq!(game.camera_center(), Some((x, y)));
+        // Zooming in brings the camera back to the player on the new map
+        key(&mut game, KeyCode::Down);
+        press(&mut game, "z");
+        let system = at(&game, ZoomLevel::SolarSystem);
+        assert_eq!(game.camera_center(), Some(system));
+
+        press(&mut game, "c");
+        assert_eq!(game.camera_center(), None);
+        key(&mut game, KeyCode::Right);
+        assert_ne!(at(&game, ZoomLevel::SolarSystem), system);
+    }
+
+    #[test]
+    fn test_main_menu_starts_the_chosen_preset() {
+        let mut game = game_in("main-menu");
+        game.open_main_menu(7);
+        assert_eq!(game.input_handler.mode(), InputMode::Menu);
+        render_at_every_size(&mut game);
+
+        // New Game, then down from Standard to Frontier
+        key(&mut game, KeyCode::Enter);
+        assert!(game.screens.is_open(ScreenKind::MainMenu));
+        key(&mut game, KeyCode::Down);
+        key(&mut game, KeyCode::Enter);
+
+        assert_eq!(game.input_handler.mode(), InputMode::Normal);
+        assert!(!game.screens.is_open(ScreenKind::MainMenu));
+        let world = game.simulation.world();
+        assert_eq!(world.rules().preset, "Frontier");
+        assert_eq!(world.seed(), 7);
+
+        game.choose_preset(7);
+        press(&mut game, "4");
+        assert_eq!(game.simulation.world().rules().preset, "Hardship");
+
+        // Settings from the title menu lead back to it, whether committed
+        // or cancelled
+        let mut game = game_in("title-settings");
+        game.open_main_menu(7);
+        press(&mut game, "3");
+        assert!(matches!(
+            game.form,
+            Some(ActiveForm::Settings { profile: true, .. })
+        ));
+        assert!(!game.screens.is_open(ScreenKind::MainMenu));
+        key(&mut game, KeyCode::Esc);
+        assert!(game.form.is_none());
+        assert!(game.screens.is_open(ScreenKind::MainMenu));
+        assert_eq!(game.input_handler.mode(), InputMode::Menu);
+        press(&mut game, "3");
+        key(&mut game, KeyCode::Enter);
+        assert!(game.form.is_none());
+        assert!(game.screens.is_open(ScreenKind::MainMenu));
+        assert_eq!(game.input_handler.mode(), InputMode::Menu);
+        assert!(!game.dirty);
+
+        // Leaving the main menu at startup quits
+        let mut game = game_in("title-quit");
+        game.open_main_menu(7);
+        assert!(key(&mut game, KeyCode::Esc));
+    }
+
+    #[test]
+    fn test_title_menu_continues_the_latest_save() {
+        let dir = crate::save::test_dir("title-continue");
+        let mut game = GameLoop::new(FrameCapture::new(80, 24), SaveManager::new(&dir));
+        game.start(WorldState::new());
+        game.simulation
+            .world_mut()
+            .set_population(ZoomLevel::Planet, 1, 1234);
+        game.save_game().unwrap();
+
+        let mut game = GameLoop::new(FrameCapture::new(80, 24), SaveManager::new(&dir));
+        game.open_main_menu(7);
+        render_at_every_size(&mut game);
+        key(&mut game, KeyCode::Enter);
+        assert_eq!(game.input_handler.mode(), InputMode::Normal);
+        assert!(!game.screens.is_open(ScreenKind::MainMenu));
+        assert!(game.playing);
+        let world = game.simulation.world();
+        assert_eq!(world.population(ZoomLevel::Planet, 1).unwrap(), 1234);
+
+        // With no previous save the menu leads with New Game
+        let mut game = game_in("title-no-continue");
+        game.open_main_menu(7);
+        key(&mut game, KeyCode::Enter);
+        assert!(game.screens.is_open(ScreenKind::MainMenu));
+        assert!(!game.playing);
+    }
+
+    #[test]
+    fn test_market_summary_follows_the_players_area() {
+        let mut world = WorldState::new();
+        let economy = world.economy_mut();
+        economy.open_market(2);
+        economy
+            .market_mut(1)
+            .unwrap()
+            .set_hours(TradingHours::ALWAYS);
+        economy
+            .market_mut(2)
+            .unwrap()
+            .set_hours(TradingHours { open: 3, close: 3 });
+        let mut position = Position::new();
+        let summary =
+            |position: &Position| GameLoop::market_summary(&world, position, ZoomLevel::LocalArea);
+
+        // Not in any area, nothing to show
+        assert_eq!(summary(&position), None);
+
+        position.set_entity_id(ZoomLevel::LocalArea, Some(1));
+        let open = summary(&position).unwrap();
+        assert!(!open.contains("closed"), "{}", open);
+
+        // Area 2's market is closed whatever area 1's is doing
+        position.set_entity_id(ZoomLevel::LocalArea, Some(2));
+        let closed = summary(&position).unwrap();
+        assert!(closed.contains("closed until 03:00"), "{}", closed);
+
+        // An area without a market shows no board
+        position.set_entity_id(ZoomLevel::LocalArea, Some(99));
+        assert_eq!(summary(&position), None);
+    }
+
+    #[test]
+    fn test_pause_menu_stops_the_clock_until_resumed() {
+        let mut game = game_in("pause-menu");
+        game.start(WorldState::new());
+        press(&mut game, " ");
+        assert!(!game.simulation.time().is_paused());
+
+        key(&mut game, KeyCode::Esc);
+        assert!(game.screens.is_open(ScreenKind::PauseMenu));
+        assert!(game.simulation.time().is_paused());
+        render_at_every_size(&mut game);
+        // Keys go to the menu rather than the game
+        press(&mut game, "z");
+        assert_eq!(game.simulation.zoom().current_level(), ZoomLevel::Galaxy);
+
+        key(&mut game, KeyCode::Esc);
+        assert_eq!(game.input_handler.mode(), InputMode::Normal);
+        assert!(!game.simulation.time().is_paused());
+
+        // Main Menu, then Back to the game
+        key(&mut game, KeyCode::Esc);
+        press(&mut game, "4");
+        assert!(game.screens.is_open(ScreenKind::MainMenu));
+        press(&mut game, "4");
+        assert_eq!(game.input_handler.mode(), InputMode::Normal);
+        assert!(!game.simulation.time().is_paused());
+    }
+
+    #[test]
+    fn test_search_jumps_to_the_chosen_place() {
+        let mut game = game();
+        press(&mut game, "/trading");
+        let action = game.input_handler.press(KeyCode::Enter, KeyModifiers::NONE);
+        game.handle_action(action).unwrap();
+
+        assert_eq!(game.input_handler.mode(), InputMode::Normal);
+        let zoom = game.simulation.zoom();
+        assert_eq!(zoom.current_level(), ZoomLevel::Room);
+        assert_eq!(zoom.position().current_room_id, Some(1));
+        assert_eq!(zoom.position().current_planet_id, Some(1));
+        assert_eq!(zoom.position().current_area_id, Some(1));
+    }
+
+    #[test]
+    fn test_enter_uses_the_furniture_under_the_cursor() {
+        let mut game = game();
+        press(&mut game, "/trading");
+        key(&mut game, KeyCode::Enter);
+        assert_eq!(GameLoop::furnishing_legend(&game.simulation), None);
+
+        key(&mut game, KeyCode::Left);
+        key(&mut game, KeyCode::Left);
+        key(&mut game, KeyCode::Up);
+        assert_eq!(
+            GameLoop::furnishing_legend(&game.simulation).as_deref(),
+            Some("[ENTER] Use the market stall")
+        );
+        key(&mut game, KeyCode::Enter);
+        assert!(game.screens.is_open(ScreenKind::Furniture));
+        assert_eq!(game.simulation.zoom().current_level(), ZoomLevel::Room);
+        render_at_every_size(&mut game);
+
+        // The first lot offered is bought into the pack
+        key(&mut game, KeyCode::Enter);
+        assert!(!game.screens.is_open(ScreenKind::Furniture));
+        let world = game.simulation.world();
+        let grain = world.economy().catalog().id("grain").unwrap();
+        assert_eq!(world.pack().get(grain), furniture::TRADE_LOT);
+        assert!(game.dirty);
+    }
+
+    #[test]
+    fn test_going_into_a_far_system_travels_there() {
+        let mut game = game();
+        let config = WorldConfig {
+            systems: 1,
+            ..WorldConfig::default()
+        };
+        game.start(config.generate());
+        let world = game.simulation.world();
+        let far = *world.ids(ZoomLevel::SolarSystem).last().unwrap();
+        let coords = world
+            .placement_of(ZoomLevel::SolarSystem, far)
+            .unwrap()
+            .coords;
+        game.simulation
+            .zoom_mut()
+            .position_mut()
+            .set_coords_for_level(ZoomLevel::Galaxy, coords);
+
+        key(&mut game, KeyCode::Enter);
+        let journey = game.simulation.world().journey().cloned().unwrap();
+        assert_eq!(game.simulation.zoom().current_level(), ZoomLevel::Galaxy);
+        key(&mut game, KeyCode::Right);
+        assert_eq!(
+            game.simulation
+                .zoom()
+                .position()
+                .coords_for_level(ZoomLevel::Galaxy),
+            coords
+        );
+        let progress = game.simulation.journey_progress().unwrap().render();
+        assert!(progress.starts_with(&format!("To {} by ship [", journey.destination)));
+        render_at_every_size(&mut game);
+
+        while game.simulation.world().journey().is_some() {
+            game.simulation.advance(FAST_FORWARD_STEP);
+        }
+        assert_eq!(
+            game.simulation.zoom().current_level(),
+            ZoomLevel::SolarSystem
+        );
+        assert_eq!(
+            game.simulation.zoom().position().current_system_id,
+            Some(far)
+        );
+    }
+
+    #[test]
+    fn test_keys_queued_in_one_frame_follow_mode_changes() {
+        let mut game = game();
+        for key in ":q".chars() {
+            game.input_handler
+                .queue(KeyCode::Char(key), KeyModifiers::NONE);
+        }
+        game.input_handler.queue(KeyCode::Esc, KeyModifiers::NONE);
+        game.input_handler
+            .queue(KeyCode::Char(' '), KeyModifiers::NONE);
+        let paused = game.simulation.time().is_paused();
+
+        // The 'q' goes to the console rather than quitting, and the space
+        // after the console closes toggles the pause
+        assert!(!game.handle_pending().unwrap());
+        assert_eq!(game.input_handler.mode(), InputMode::Normal);
+        assert_ne!(game.simulation.time().is_paused(), paused);
+        assert_eq!(game.input_handler.next_action(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "scripting")]
+    fn test_overlays_are_defined_and_cycled() {
+        let dir = crate::save::test_dir("overlays");
+        let mut game = GameLoop::new(FrameCapture::new(80, 24), SaveManager::new(&dir));
+        game.simulation.jump_to(ZoomLevel::Planet, 1);
+        press(&mut game, "V");
+        let action = game.input_handler.press(KeyCode::Enter, KeyModifiers::NONE);
+        game.handle_action(action).unwrap();
+
+        assert_eq!(game.saves.profile().overlays.len(), 1);
+        let (heat, legend) =
+            GameLoop::overlay_heat(&game.simulation, game.overlay.as_ref(), ZoomLevel::Planet)
+                .unwrap();
+        // A lone region sits in the middle of the scale
+        assert_eq!(heat.get(&1), Some(&Tone::Heat(2)));
+        assert!(legend.starts_with("Cost of grain: "));
+        assert!(
+            GameLoop::overlay_heat(&game.simulation, game.overlay.as_ref(), ZoomLevel::Region)
+                .is_none()
+        );
+
+        let bad = CustomOverlay {
+            name: String::from("Broken"),
+            expression: String::from("price(grain) +"),
+        };
+        assert!(game.define_overlay(bad).starts_with("Overlay not saved"));
+        assert_eq!(game.saves.profile().overlays.len(), 1);
+
+        press(&mut game, "v");
+        assert!(game.overlay.is_none());
+        press(&mut game, "v");
+        assert_eq!(game.overlay.as_ref().map(|shown| shown.index), Some(0));
+    }
+
+    #[test]
+    fn test_galaxy_map_shows_stars_by_class_and_population() {
+        let mut game = game();
+        let glyphs = Glyphs::default();
+        let world = game.simulation.world_mut();
+        let system = world
+            .spawn_at(ZoomLevel::SolarSystem, Placement::new(None, (2, 0)))
+            .unwrap();
+        world.get_system_mut(system).unwrap().star = StarClass::M;
+        let planet = world
+            .spawn_at(ZoomLevel::Planet, Placement::new(Some(system), (0, 0)))
+            .unwrap();
+        world.set_population(ZoomLevel::Planet, planet, 1_000);
+        world.refresh_census();
+
+        let star_tones = |game: &GameLoop| {
+            let galaxy =
+                GameLoop::surface_lines(&game.simulation, &glyphs, ZoomLevel::Galaxy, None, None)
+                    .unwrap();
+            let middle = galaxy[REGION_MAP_HEIGHT / 2].clone();
+            let tone_of = |glyph: char| {
+                middle
+                    .spans()
+                    .iter()
+                    .find(|span| span.text == glyph.to_string())
+                    .map(|span| span.tone)
+            };
+            (
+                tone_of(glyphs.star(StarClass::G)),
+                tone_of(glyphs.star(StarClass::M)),
+            )
+        };
+        // The cursor starts on Sol; the small colony is colored low on the
+        // scale
+        assert_eq!(
+            star_tones(&game),
+            (Some(Tone::Selected), Some(Tone::Heat(1)))
+        );
+        let legend = GameLoop::star_legend(&game.simulation, ZoomLevel::Galaxy).unwrap();
+        assert!(legend.starts_with("Sol System: class G, "), "{}", legend);
+
+        key(&mut game, KeyCode::Right);
+        assert_eq!(
+            GameLoop::star_legend(&game.simulation, ZoomLevel::Galaxy),
+            None
+        );
+        key(&mut game, KeyCode::Right);
+        assert_eq!(
+            star_tones(&game),
+            (Some(Tone::Heat(HEAT_LEVELS - 1)), Some(Tone::Selected))
+        );
+        assert_eq!(
+            GameLoop::star_legend(&game.simulation, ZoomLevel::Galaxy),
+            Some(format!("System {}: class M, 1.00K people", system))
+        );
+        assert_eq!(
+            GameLoop::star_legend(&game.simulation, ZoomLevel::Planet),
+            None
+        );
+        render_at_every_size(&mut game);
+    }
+
+    #[test]
+    fn test_borders_divide_polities_on_the_map() {
+        let mut game = game();
+        let glyphs = Glyphs::default();
+        let border = glyphs.border().to_string();
+        let galaxy =
+            GameLoop::surface_lines(&game.simulation, &glyphs, ZoomLevel::Galaxy, None, None)
+                .unwrap();
+        // The home system's polity holds the space around it
+        let middle = galaxy[REGION_MAP_HEIGHT / 2].to_string();
+        assert_eq!(middle.matches(border.as_str()).count(), 2);
+        assert!(galaxy.iter().all(|line| line.width() == VIEW_INNER_WIDTH));
+
+        // Only the sample region is held on the home planet
+        game.simulation.jump_to(ZoomLevel::Planet, 1);
+        let planet =
+            GameLoop::surface_lines(&game.simulation, &glyphs, ZoomLevel::Planet, None, None)
+                .unwrap();
+        assert!(planet.iter().any(|line| line.to_string().contains(&border)));
+
+        let rival = game
+            .simulation
+            .world_mut()
+            .found_polity("Rival", Default::default());
+        game.simulation
+            .world_mut()
+            .claim(rival, ZoomLevel::Planet, 1);
+        let planet =
+            GameLoop::surface_lines(&game.simulation, &glyphs, ZoomLevel::Planet, None, None)
+                .unwrap();
+        assert!(
+            planet
+                .iter()
+                .all(|line| !line.to_string().contains(&border))
+        );
+        render_at_every_size(&mut game);
+    }
+
+    #[test]
+    fn test_ships_under_way_are_marked_on_the_system_map() {
+        let mut game = game();
+        let glyphs = Glyphs::default();
+        let world = game.simulation.world_mut();
+        let moon = world
+            .spawn_at(ZoomLevel::Planet, Placement::new(Some(1), (4, 0)))
+            .unwrap();
+        let ship = world.launch_ship(1).unwrap();
+        world.update(Duration::from_secs(3600));
+        world.ship_mut(ship).unwrap().course = Course::Underway(Voyage {
+            from: 1,
+            to: moon,
+            departed: Duration::ZERO,
+            arrives: Duration::from_secs(7200),
+        });
+        assert_eq!(
+            world.ships_on_map(ZoomLevel::SolarSystem, Some(1)),
+            vec![(ship, (2, 0))]
+        );
+        assert!(world.ships_on_map(ZoomLevel::Galaxy, None).is_empty());
+
+        game.simulation.jump_to(ZoomLevel::SolarSystem, 1);
+        let system = GameLoop::surface_lines(
+            &game.simulation,
+            &glyphs,
+            ZoomLevel::SolarSystem,
+            None,
+            None,
+        )
+        .unwrap();
+        let middle = system[REGION_MAP_HEIGHT / 2].to_string();
+        let (you, ship, planet) = (glyphs.you(), glyphs.ship(), glyphs.level(ZoomLevel::Planet));
+        // Every tile is two columns wide, with one empty tile between each
+        assert!(middle.contains(&format!("{}   {}   {}", you, ship, planet)));
+        render_at_every_size(&mut game);
+    }
+
+    /// Draw a fresh session after pressing `keys`, as rows of text
+    fn frame(name: &str, keys: &str, width: u16, height: u16) -> Vec<String> {
+        let mut capture = FrameCapture::new(width, height);
+        let saves = SaveManager::new(&crate::save::test_dir(name));
+        let mut game = GameLoop::new(&mut capture, saves);
+        press(&mut game, keys);
+        game.render().unwrap();
+        drop(game);
+        capture.rows()
+    }
+
+    fn assert_frame(name: &str, keys: &str, width: u16, height: u16) {
+        let rows = frame(name, keys, width, height);
+        let faults = snapshot::box_faults(&rows, width as usize);
+        assert!(
+            faults.is_empty(),
+            "{} has broken boxes:\n{}\n{}",
+            name,
+            faults.join("\n"),
+            rows.join("\n")
+        );
+        snapshot::assert_snapshot(name, &rows);
+    }
+
+    #[test]
+    fn test_every_zoom_level_matches_its_snapshot() {
+        let levels = [
+            ("galaxy", ""),
+            ("solar_system", "z"),
+            ("planet", "zz"),
+            ("region", "zzz"),
+            ("local_area", "zzzz"),
+            ("room", "zzzzz"),
+        ];
+        for (name, keys) in levels {
+            assert_frame(&format!("{}_80x24", name), keys, 80, 24);
+            assert_frame(&format!("{}_120x40", name), keys, 120, 40);
+        }
+    }
+
+    #[test]
+    fn test_every_screen_matches_its_snapshot() {
+        // Timings on the profiler and debug overlay change from run to run,
+        // so they are left to the render-at-any-size test
+        let screens = [
+            ("help", "h"),
+            ("dashboard", "d"),
+            ("market_dashboard", "zzzzd"),
+            ("census", "C"),
+            ("finances", "g"),
+            ("technology", "k"),
+            ("monetary", "l"),
+            ("sidebar", "i"),
+            ("minimap", "m"),
+            ("console", ":speed"),
+            ("search", "/ter"),
+            ("bookmarks", "b"),
+            ("settings", "O"),
+            ("policy", "M"),
+            ("editor", "e"),
+        ];
+        for (name, keys) in screens {
+            assert_frame(&format!("screen_{}", name), keys, 100, 30);
+        }
+    }
+
+    #[test]
+    fn test_tutorial_progress_is_kept_in_the_profile() {
+        let dir = crate::save::test_dir("tutorial");
+        let mut game = GameLoop::new(FrameCapture::new(80, 24), SaveManager::new(&dir));
+        press(&mut game, "zz ");
+        assert_eq!(
+            game.tutorial.prompt().as_deref(),
+            Some("Tutorial 2/6: Press SPACE again to pause it")
+        );
+        render_at_every_size(&mut game);
+
+        let mut game = GameLoop::new(FrameCapture::new(80, 24), SaveManager::new(&dir));
+        assert!(game.tutorial.prompt().unwrap().starts_with("Tutorial 2/6"));
+        press(&mut game, "s");
+        assert!(game.tutorial.is_finished());
+        assert!(SaveManager::new(&dir).profile().tutorial.is_finished());
+    }
+
+    #[test]
+    fn test_small_terminal_asks_for_more_room() {
+        let mut capture = FrameCapture::new(30, 5);
+        capture.begin_frame().unwrap();
+        GameLoop::draw_too_small(&mut capture);
+        capture.end_frame().unwrap();
+        assert_eq!(
+            capture.last_frame(),
+            Some("\n      Terminal too small\n   Resize to at least 44×24\n           Now 30×5\n")
+        );
+
+        let mut tiny = FrameCapture::new(8, 1);
+        tiny.begin_frame().unwrap();
+        GameLoop::draw_too_small(&mut tiny);
+        tiny.end_frame().unwrap();
+        assert_eq!(tiny.last_frame(), Some("Terminal"));
+    }
+
+    #[cfg(feature = "serve")]
+    #[test]
+    fn test_shutdown_hangs_up_on_observers() {
+        use std::io::Read;
+
+        let mut game = game();
+        let observer = Observer::bind("127.0.0.1:0").unwrap();
+        let mut client = std::net::TcpStream::connect(observer.local_addr().unwrap()).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        game.serve(observer);
+        game.update();
+        game.shutdown(Ok(())).unwrap();
+
+        // The stream ends after the observations already sent
+        let mut received = String::new();
+        client.read_to_string(&mut received).unwrap();
+        assert!(received.ends_with('\n'));
+    }
+
+    #[test]
+    fn test_shutdown_errors_render_at_any_size() {
+        let mut report = ShutdownReport::new();
+        report.record("Save game", Err(std::io::Error::other("disk full").into()));
+        for width in WIDTHS {
+            for height in HEIGHTS {
+                let mut capture = FrameCapture::new(width, height);
+                GameLoop::draw_shutdown_errors(&mut capture, &report);
             }
         }
     }
@@ -0,0 +1,56 @@
+//! Changes made by hand to a running world.
+//!
+//! Values edited through the inspector in debug builds are written here
+//! with the time they changed and what they changed from, and the journal
+//! is saved with the game, so a save that was adjusted while testing says
+//! exactly how.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// One value changed by hand
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Simulation time of the change
+    pub at: Duration,
+    /// The entity changed, by name
+    pub target: String,
+    pub field: String,
+    pub from: String,
+    pub to: String,
+}
+
+impl fmt::Display for JournalEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: {} → {}",
+            self.target, self.field, self.from, self.to
+        )
+    }
+}
+
+/// Every hand edit, oldest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Journal {
+    entries: Vec<JournalEntry>,
+}
+
+impl Journal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, entry: JournalEntry) {
+        self.entries.push(entry);
+    }
+
+    pub fn entries(&self) -> &[JournalEntry] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,297 @@
+//! The main menu, the pause menu and the menus they lead to, and the menu
+//! a piece of furniture opens.
+//!
+//! Each is a [`MenuScreen`] on the screen stack that takes the keyboard
+//! while it is open. Picking an option closes the menu and hands a
+//! [`MenuChoice`] to the game loop to carry out, since most of them (loading
+//! a save, starting a new game, quitting) reach beyond the simulation.
+
+use std::path::PathBuf;
+
+use crate::input::{InputAction, InputMode};
+use crate::render::{Layout, Line, Renderer, Tone};
+use crate::ui::Menu;
+use crate::zoom::ZoomLevel;
+
+use super::Simulation;
+use super::diff::WorldDiff;
+use super::furniture::{self, Furnishing, FurnitureKind, Interaction};
+use super::presets::PRESETS;
+use super::screens::{Handled, Screen, ScreenKind, draw_panel};
+
+/// What the game loop does when a menu option is picked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MenuChoice {
+    /// Close the menu and carry on playing
+    Resume,
+    /// Show the main menu
+    MainMenu,
+    /// Choose a preset for a new game
+    NewGame,
+    /// Start a new game in one of the [`PRESETS`]
+    Preset(usize),
+    /// Load the save written most recently
+    Continue,
+    /// Choose a save to load
+    Load,
+    LoadSave(PathBuf),
+    Save,
+    /// Edit the settings every game follows
+    Settings,
+    /// Edit the settings of the game in progress
+    GameSettings,
+    Quit,
+    /// Use the piece of furniture under the cursor
+    Interact(Interaction),
+    /// Show what changed between two moments, as the console's `diff`
+    /// found it
+    ShowDiff(WorldDiff),
+}
+
+/// A menu on the screen stack
+pub struct MenuScreen {
+    kind: ScreenKind,
+    menu: Menu,
+    choices: Vec<MenuChoice>,
+    /// What leaving the menu with ESC does
+    cancel: MenuChoice,
+}
+
+impl MenuScreen {
+    pub fn new(kind: ScreenKind, title: &str, cancel: MenuChoice) -> Self {
+        Self {
+            kind,
+            menu: Menu::new(title),
+            choices: Vec::new(),
+            cancel,
+        }
+    }
+
+    pub fn add(&mut self, label: &str, description: &str, choice: MenuChoice) {
+        self.menu.add_option(label, description);
+        self.choices.push(choice);
+    }
+
+    /// Start with the option for `choice` selected
+    pub fn select(&mut self, choice: &MenuChoice) {
+        if let Some(index) = self.choices.iter().position(|c| c == choice) {
+            self.menu.select(index);
+        }
+    }
+
+    /// New Game, Load, Settings and Quit, led by Continue when there is a
+    /// previous save. Opened over a game in progress it can be left for
+    /// that game; at startup leaving it quits.
+    pub fn main(in_game: bool, can_continue: bool) -> Self {
+        let cancel = if in_game {
+            MenuChoice::Resume
+        } else {
+            MenuChoice::Quit
+        };
+        let mut screen = Self::new(ScreenKind::MainMenu, "Econogenesis", cancel);
+        if can_continue {
+            screen.add("Continue", "Resume the latest save", MenuChoice::Continue);
+        }
+        screen.add("New Game", "Start a new galaxy", MenuChoice::NewGame);
+        screen.add("Load", "Pick up a saved game", MenuChoice::Load);
+        screen.add("Settings", "Settings for every game", MenuChoice::Settings);
+        if in_game {
+            screen.add("Back", "Return to the game", MenuChoice::Resume);
+        }
+        screen.add("Quit", "Leave Econogenesis", MenuChoice::Quit);
+        screen
+    }
+
+    /// Opened with ESC during play, with the clock stopped
+    pub fn pause() -> Self {
+        let mut screen = Self::new(ScreenKind::PauseMenu, "Paused", MenuChoice::Resume);
+        screen.add("Resume", "Carry on playing", MenuChoice::Resume);
+        screen.add("Save", "Save the game", MenuChoice::Save);
+        screen.add(
+            "Settings",
+            "Settings for this game",
+            MenuChoice::GameSettings,
+        );
+        screen.add("Main Menu", "New game, load or quit", MenuChoice::MainMenu);
+        screen.add("Quit", "Leave Econogenesis", MenuChoice::Quit);
+        screen
+    }
+
+    /// Every preset a new game can start from
+    pub fn presets(back: MenuChoice) -> Self {
+        let mut screen = Self::new(ScreenKind::MainMenu, "Start a new game", back);
+        for (index, preset) in PRESETS.iter().enumerate() {
+            screen.add(preset.name, preset.description, MenuChoice::Preset(index));
+        }
+        screen
+    }
+
+    /// The saves in the data directory, newest first, each labelled with
+    /// its file name
+    pub fn saves(paths: Vec<PathBuf>, back: MenuChoice) -> Self {
+        let mut screen = Self::new(ScreenKind::MainMenu, "Load a game", back);
+        for path in paths {
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            screen.add(
+                &name,
+                &path.display().to_string(),
+                MenuChoice::LoadSave(path),
+            );
+        }
+        screen
+    }
+
+    /// What a piece of furniture in a room offers: at a stall, a lot of
+    /// each good the player carries to sell and of each good the room's
+    /// market has in stock to buy; in a bed, the night; at a workbench, a
+    /// shift at the area's wage
+    pub fn furnishing(simulation: &Simulation, piece: Furnishing) -> Self {
+        let world = simulation.world();
+        let economy = world.economy();
+        let currency = economy.currency();
+        let title = format!("The {}", piece.kind.name());
+        let mut screen = Self::new(ScreenKind::Furniture, &title, MenuChoice::Resume);
+        let market = simulation
+            .zoom()
+            .position()
+            .map_owner(ZoomLevel::LocalArea)
+            .and_then(|area| economy.market(area));
+        match (piece.kind, market) {
+            (FurnitureKind::MarketStall, Some(market)) => {
+                let lot = furniture::TRADE_LOT;
+                for (good, held) in world.pack().iter() {
+                    let name = economy.catalog().get(good).map_or("", |g| &g.name);
+                    screen.add(
+                        &format!("Sell {:.0} {}", lot.min(held), name),
+                        &format!(
+                            "{} each, {:.0} carried",
+                            currency.format(market.price(good)),
+                            held
+                        ),
+                        MenuChoice::Interact(Interaction::Sell(good)),
+                    );
+                }
+                for (good, info) in economy.catalog().iter() {
+                    if market.stock(good) >= 1.0 {
+                        screen.add(
+                            &format!("Buy {} {}", lot, info.name),
+                            &format!("{} each", currency.format(market.price(good))),
+                            MenuChoice::Interact(Interaction::Buy(good)),
+                        );
+                    }
+                }
+            }
+            (FurnitureKind::Bed, _) => screen.add(
+                "Rest",
+                &format!("Sleep for {} hours", furniture::REST_HOURS),
+                MenuChoice::Interact(Interaction::Rest),
+            ),
+            (FurnitureKind::Workbench, Some(market)) => screen.add(
+                "Work a shift",
+                &format!(
+                    "{} hours at {} an hour",
+                    furniture::SHIFT_HOURS,
+                    currency.format(market.wage())
+                ),
+                MenuChoice::Interact(Interaction::Work),
+            ),
+            _ => {}
+        }
+        screen.add("Leave", "Step away", MenuChoice::Resume);
+        screen
+    }
+
+    fn pick(&self) -> Handled {
+        match self.choices.get(self.menu.selected()) {
+            Some(choice) => Handled::Picked(choice.clone()),
+            None => Handled::Picked(self.cancel.clone()),
+        }
+    }
+}
+
+impl Screen for MenuScreen {
+    fn kind(&self) -> ScreenKind {
+        self.kind
+    }
+
+    fn input_mode(&self) -> Option<InputMode> {
+        Some(InputMode::Menu)
+    }
+
+    /// Takes every key, so the game behind never acts on one
+    fn handle(&mut self, action: InputAction, _simulation: &mut Simulation) -> Handled {
+        match action {
+            InputAction::MoveUp => self.menu.select_previous(),
+            InputAction::MoveDown => self.menu.select_next(),
+            InputAction::Choose(number) if (1..=self.choices.len()).contains(&number.into()) => {
+                self.menu.select(usize::from(number) - 1);
+                return self.pick();
+            }
+            InputAction::Confirm => return self.pick(),
+            InputAction::Cancel => return Handled::Picked(self.cancel.clone()),
+            _ => {}
+        }
+        Handled::Taken
+    }
+
+    /// The option lines in a panel, the selected one highlighted
+    fn draw(&self, canvas: &mut dyn Renderer, layout: &Layout) {
+        const FOOTER: &str = "[UP/DOWN] Choose  [ENTER] Select  [ESC] Back";
+        let lines = self.menu.lines();
+        draw_panel(canvas, layout.overlay, self.menu.title(), &lines, FOOTER);
+        if let Some(selected) = lines.get(self.menu.selected()) {
+            let (x, y) = (layout.overlay.x + 2, layout.overlay.y + 3);
+            let row = y + self.menu.selected() as u16;
+            canvas.draw_line(x, row, &Line::plain("").push(selected, Tone::Selected));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_menus_hand_back_the_choice() {
+        let mut simulation = Simulation::new();
+        let mut menu = MenuScreen::pause();
+        assert_eq!(
+            menu.handle(InputAction::MoveDown, &mut simulation),
+            Handled::Taken
+        );
+        assert_eq!(
+            menu.handle(InputAction::Confirm, &mut simulation),
+            Handled::Picked(MenuChoice::Save)
+        );
+        assert_eq!(
+            menu.handle(InputAction::Choose(5), &mut simulation),
+            Handled::Picked(MenuChoice::Quit)
+        );
+        assert_eq!(
+            menu.handle(InputAction::Choose(9), &mut simulation),
+            Handled::Taken
+        );
+        assert_eq!(
+            menu.handle(InputAction::Cancel, &mut simulation),
+            Handled::Picked(MenuChoice::Resume)
+        );
+
+        let mut main = MenuScreen::main(false, false);
+        assert_eq!(
+            main.handle(InputAction::Confirm, &mut simulation),
+            Handled::Picked(MenuChoice::NewGame)
+        );
+        assert_eq!(
+            main.handle(InputAction::Cancel, &mut simulation),
+            Handled::Picked(MenuChoice::Quit)
+        );
+        let mut main = MenuScreen::main(false, true);
+        assert_eq!(
+            main.handle(InputAction::Confirm, &mut simulation),
+            Handled::Picked(MenuChoice::Continue)
+        );
+    }
+}
@@ -0,0 +1,233 @@
+//! People moving towards better places to live.
+//!
+//! Once a day every local area with a market is scored on what a day's
+//! wage buys in food there and how much housing it has free. Workers leave
+//! each area for the best-scoring area on the same planet, and planets lose
+//! people to whichever planet's areas score best on average. Moves are
+//! proportional to how much better the destination is, so populations
+//! settle as scores even out.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::time::Calendar;
+use crate::zoom::ZoomLevel;
+
+use super::state::EntityId;
+
+/// Simulation time between migrations
+pub const MIGRATION_INTERVAL: Duration = Calendar::DEFAULT_DAY_LENGTH;
+
+/// Residents each building in an area can house
+pub const RESIDENTS_PER_BUILDING: u32 = 2;
+
+/// Share of an area's workforce that leaves per day when a destination
+/// scores twice as well
+const AREA_RATE: f64 = 0.05;
+/// Share of a planet's population that leaves per day when a destination
+/// scores twice as well
+const PLANET_RATE: f64 = 0.001;
+/// Score advantage a destination needs before anyone bothers to move
+const MIN_ADVANTAGE: f64 = 0.05;
+
+/// What makes a local area worth moving to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Appeal {
+    /// Hourly wage for one worker
+    pub wage: f64,
+    /// Price of the cheapest food on sale
+    pub food_price: f64,
+    pub workforce: u32,
+    pub buildings: u32,
+}
+
+impl Appeal {
+    /// Residents the area has room for
+    pub fn housing(&self) -> u32 {
+        self.buildings.saturating_mul(RESIDENTS_PER_BUILDING)
+    }
+
+    /// Homes not yet taken
+    pub fn vacancies(&self) -> u32 {
+        self.housing().saturating_sub(self.workforce)
+    }
+
+    /// Food a wage buys, halved in an area with no room left
+    pub fn score(&self) -> f64 {
+        if self.food_price <= 0.0 {
+            return 0.0;
+        }
+        let housing = self.housing().max(1) as f64;
+        let vacancy = (1.0 - self.workforce as f64 / housing).max(0.0);
+        self.wage / self.food_price * (0.5 + 0.5 * vacancy)
+    }
+}
+
+/// A local area as seen by migration
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Area {
+    pub id: EntityId,
+    /// The planet the area is on, if it is placed on one
+    pub planet: Option<EntityId>,
+    pub appeal: Appeal,
+}
+
+/// People moving from one place to another at the same level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub level: ZoomLevel,
+    pub from: EntityId,
+    pub to: EntityId,
+    pub people: u64,
+}
+
+/// Decide a day's moves between the given areas, and between the planets
+/// with the given populations
+pub fn plan(areas: &[Area], planets: &BTreeMap<EntityId, u64>) -> Vec<Move> {
+    let mut moves = Vec::new();
+
+    let mut vacancies: BTreeMap<EntityId, u64> = areas
+        .iter()
+        .map(|area| (area.id, area.appeal.vacancies() as u64))
+        .collect();
+    for area in areas {
+        let best = areas
+            .iter()
+            .filter(|other| other.id != area.id && other.planet == area.planet)
+            .max_by(|a, b| a.appeal.score().total_cmp(&b.appeal.score()));
+        let Some(best) = best else {
+            continue;
+        };
+        let room = vacancies.get(&best.id).copied().unwrap_or(0);
+        let people = leaving(
+            area.appeal.workforce as u64,
+            area.appeal.score(),
+            best.appeal.score(),
+            AREA_RATE,
+        )
+        .min(room);
+        if people > 0 {
+            vacancies.insert(best.id, room - people);
+            moves.push(Move {
+                level: ZoomLevel::LocalArea,
+                from: area.id,
+                to: best.id,
+                people,
+            });
+        }
+    }
+
+    let mut totals: BTreeMap<EntityId, (f64, u32)> = BTreeMap::new();
+    for area in areas {
+        if let Some(planet) = area.planet {
+            let total = totals.entry(planet).or_default();
+            total.0 += area.appeal.score();
+            total.1 += 1;
+        }
+    }
+    let scores: BTreeMap<EntityId, f64> = totals
+        .into_iter()
+        .filter(|(id, _)| planets.contains_key(id))
+        .map(|(id, (sum, count))| (id, sum / count as f64))
+        .collect();
+    let best = scores
+        .iter()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(&id, &score)| (id, score));
+    if let Some((to, best_score)) = best {
+        for (&from, &score) in &scores {
+            let people = leaving(planets[&from], score, best_score, PLANET_RATE);
+            if from != to && people > 0 {
+                moves.push(Move {
+                    level: ZoomLevel::Planet,
+                    from,
+                    to,
+                    people,
+                });
+            }
+        }
+    }
+    moves
+}
+
+/// People leaving a place of `population` scoring `score` for one scoring
+/// `destination`
+fn leaving(population: u64, score: f64, destination: f64, rate: f64) -> u64 {
+    if destination <= score * (1.0 + MIN_ADVANTAGE) {
+        return 0;
+    }
+    let advantage = if score > 0.0 {
+        (destination / score - 1.0).min(1.0)
+    } else {
+        1.0
+    };
+    (population as f64 * rate * advantage).round() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(id: EntityId, planet: EntityId, food_price: f64, workforce: u32) -> Area {
+        Area {
+            id,
+            planet: Some(planet),
+            appeal: Appeal {
+                wage: 0.5,
+                food_price,
+                workforce,
+                buildings: 100,
+            },
+        }
+    }
+
+    #[test]
+    fn test_scarce_housing_lowers_score() {
+        let roomy = area(1, 1, 1.0, 0).appeal;
+        let full = area(2, 1, 1.0, 200).appeal;
+        assert_eq!(roomy.score(), 0.5);
+        assert_eq!(full.score(), 0.25);
+        assert_eq!(full.vacancies(), 0);
+    }
+
+    #[test]
+    fn test_workers_move_to_cheaper_food() {
+        let areas = [area(1, 1, 2.0, 100), area(2, 1, 1.0, 100)];
+        let moves = plan(&areas, &BTreeMap::new());
+        assert_eq!(
+            moves,
+            vec![Move {
+                level: ZoomLevel::LocalArea,
+                from: 1,
+                to: 2,
+                people: 5,
+            }]
+        );
+
+        let settled = [area(1, 1, 1.0, 100), area(2, 1, 1.0, 100)];
+        assert!(plan(&settled, &BTreeMap::new()).is_empty());
+    }
+
+    #[test]
+    fn test_moves_stay_within_housing() {
+        let areas = [area(1, 1, 4.0, 100), area(2, 1, 1.0, 198)];
+        let moves = plan(&areas, &BTreeMap::new());
+        assert_eq!(moves[0].people, 2);
+    }
+
+    #[test]
+    fn test_planets_lose_people_to_better_planets() {
+        let areas = [area(1, 1, 2.0, 100), area(2, 2, 1.0, 100)];
+        let planets = BTreeMap::from([(1, 1_000_000), (2, 1_000_000)]);
+        let moves = plan(&areas, &planets);
+        assert_eq!(
+            moves,
+            vec![Move {
+                level: ZoomLevel::Planet,
+                from: 1,
+                to: 2,
+                people: 1_000,
+            }]
+        );
+    }
+}
@@ -1,5 +1,50 @@
+pub mod audit;
+pub mod bookmarks;
+pub mod builder;
+pub mod business;
+pub mod campaign;
+pub mod census;
+pub mod characters;
+pub mod climate;
+pub mod commands;
+pub mod contracts;
+pub mod deposits;
+pub mod detail;
+pub mod diff;
+pub mod entities;
+pub mod events;
+pub mod export;
+pub mod festivals;
+pub mod furniture;
 mod game_loop;
+pub mod journal;
+mod menus;
+pub mod migration;
+pub mod names;
+pub mod pathfinding;
+pub mod polities;
+pub mod presets;
+pub mod random_events;
+pub mod rewind;
+pub mod rng;
+pub mod schedule;
+mod screens;
+pub mod search;
+pub mod ships;
+mod shutdown;
+mod simulation;
+pub mod soak;
+pub mod stars;
 pub mod state;
+pub mod stats;
+pub mod terrain;
+pub mod travel;
+pub mod tutorial;
+pub mod watches;
+pub mod worldgen;
 
+pub use builder::WorldStateBuilder;
+pub use events::{Alert, EventLog, Notice, Severity};
 pub use game_loop::GameLoop;
+pub use simulation::{DEFAULT_TARGET_FPS, Simulation};
 pub use state::WorldState;
@@ -0,0 +1,321 @@
+//! Names for generated places and people, in the style of a culture.
+//!
+//! The cultures are content, read from `data/names.toml` compiled into the
+//! game. Each gives the syllables its words are built from and, for every
+//! [`NameKind`], the patterns those words go into, so one culture's stars
+//! and settlers sound alike and unlike another's. A [`NameGenerator`] is
+//! made from a seed, which picks its culture and everything it names, so
+//! the same seed always gives the same names.
+
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::OnceLock;
+use thiserror::Error as ThisError;
+
+use super::rng::Rng;
+
+const BUILT_IN: &str = include_str!("../../data/names.toml");
+
+/// Stands for a root in a name's pattern
+const ROOT: &str = "{}";
+
+/// Most middle syllables a root takes
+const MAX_MIDDLE: u64 = 1;
+
+/// Names tried for one not yet taken before numbering it instead
+const ATTEMPTS: usize = 20;
+
+#[derive(ThisError, Debug)]
+pub enum NamesDataError {
+    #[error("invalid names file")]
+    Parse(#[from] toml::de::Error),
+    #[error("no cultures are defined")]
+    NoCultures,
+    #[error("culture '{0}' is defined twice")]
+    DuplicateCulture(String),
+    #[error("culture '{culture}' has no {field}")]
+    Empty {
+        culture: String,
+        field: &'static str,
+    },
+    #[error("culture '{culture}' has a {kind} pattern with no {{}} for a root")]
+    NoRoot { culture: String, kind: NameKind },
+}
+
+/// What is being named
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameKind {
+    /// A solar system, after its star
+    Star,
+    Planet,
+    Region,
+    /// A local area
+    Settlement,
+    Person,
+}
+
+impl NameKind {
+    pub const ALL: [NameKind; 5] = [
+        NameKind::Star,
+        NameKind::Planet,
+        NameKind::Region,
+        NameKind::Settlement,
+        NameKind::Person,
+    ];
+}
+
+impl fmt::Display for NameKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            NameKind::Star => "star",
+            NameKind::Planet => "planet",
+            NameKind::Region => "region",
+            NameKind::Settlement => "settlement",
+            NameKind::Person => "person",
+        })
+    }
+}
+
+/// A people's way of naming things
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Culture {
+    pub name: String,
+    /// Syllables a root starts with
+    first: Vec<String>,
+    /// Syllables a root may have between its first and last
+    middle: Vec<String>,
+    /// Syllables a root ends with
+    last: Vec<String>,
+    star: Vec<String>,
+    planet: Vec<String>,
+    region: Vec<String>,
+    settlement: Vec<String>,
+    person: Vec<String>,
+}
+
+impl Culture {
+    fn patterns(&self, kind: NameKind) -> &[String] {
+        match kind {
+            NameKind::Star => &self.star,
+            NameKind::Planet => &self.planet,
+            NameKind::Region => &self.region,
+            NameKind::Settlement => &self.settlement,
+            NameKind::Person => &self.person,
+        }
+    }
+}
+
+/// Every culture, as read from a names file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NamesData {
+    cultures: Vec<Culture>,
+}
+
+impl NamesData {
+    /// Read cultures from TOML, checking that each has syllables to build
+    /// roots from and a pattern with a root in it for every kind of name
+    pub fn parse(text: &str) -> Result<Self, NamesDataError> {
+        let data: Self = toml::from_str(text)?;
+        data.validate()?;
+        Ok(data)
+    }
+
+    /// The cultures in `data/names.toml` at build time
+    pub fn built_in() -> &'static Self {
+        static BUILT_IN_DATA: OnceLock<NamesData> = OnceLock::new();
+        BUILT_IN_DATA.get_or_init(|| Self::parse(BUILT_IN).expect("built-in names file is invalid"))
+    }
+
+    fn validate(&self) -> Result<(), NamesDataError> {
+        if self.cultures.is_empty() {
+            return Err(NamesDataError::NoCultures);
+        }
+        let mut names = HashSet::new();
+        for culture in &self.cultures {
+            if !names.insert(culture.name.as_str()) {
+                return Err(NamesDataError::DuplicateCulture(culture.name.clone()));
+            }
+            let empty = |field| NamesDataError::Empty {
+                culture: culture.name.clone(),
+                field,
+            };
+            if culture.first.is_empty() {
+                return Err(empty("first syllables"));
+            }
+            if culture.last.is_empty() {
+                return Err(empty("last syllables"));
+            }
+            for kind in NameKind::ALL {
+                let patterns = culture.patterns(kind);
+                if patterns.is_empty() {
+                    return Err(empty("name patterns for every kind"));
+                }
+                if patterns.iter().any(|pattern| !pattern.contains(ROOT)) {
+                    return Err(NamesDataError::NoRoot {
+                        culture: culture.name.clone(),
+                        kind,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn cultures(&self) -> &[Culture] {
+        &self.cultures
+    }
+}
+
+/// Makes names in one culture's style
+#[derive(Debug, Clone)]
+pub struct NameGenerator<'a> {
+    culture: &'a Culture,
+    rng: Rng,
+}
+
+impl NameGenerator<'static> {
+    /// A generator for the built-in culture `seed` picks
+    pub fn new(seed: u64) -> Self {
+        Self::with_data(NamesData::built_in(), seed)
+    }
+}
+
+impl<'a> NameGenerator<'a> {
+    /// A generator for the culture of `data` that `seed` picks
+    pub fn with_data(data: &'a NamesData, seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        let culture = rng
+            .choose(&data.cultures)
+            .expect("names data has a culture");
+        Self { culture, rng }
+    }
+
+    pub fn culture(&self) -> &'a Culture {
+        self.culture
+    }
+
+    /// A word of the culture's syllables
+    fn root(&mut self) -> String {
+        let mut root = self.pick(|c| &c.first);
+        for _ in 0..self.rng.range(0, MAX_MIDDLE + 1) {
+            root.push_str(&self.pick(|c| &c.middle));
+        }
+        root.push_str(&self.pick(|c| &c.last));
+        root
+    }
+
+    fn pick(&mut self, syllables: impl Fn(&Culture) -> &Vec<String>) -> String {
+        self.rng
+            .choose(syllables(self.culture))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// A name for something of a kind
+    pub fn name(&mut self, kind: NameKind) -> String {
+        let pattern = self.rng.choose(self.culture.patterns(kind)).cloned();
+        let mut name = pattern.unwrap_or_else(|| String::from(ROOT));
+        while name.contains(ROOT) {
+            let root = self.root();
+            name = name.replacen(ROOT, &root, 1);
+        }
+        name
+    }
+
+    /// A name for something of a kind that isn't in `taken` yet, which it
+    /// is added to. If every name tried is taken the last is numbered.
+    pub fn unique(&mut self, kind: NameKind, taken: &mut HashSet<String>) -> String {
+        let mut name = self.name(kind);
+        for _ in 1..ATTEMPTS {
+            if !taken.contains(&name) {
+                break;
+            }
+            name = self.name(kind);
+        }
+        let base = name.clone();
+        let mut number = 2;
+        while taken.contains(&name) {
+            name = format!("{} {}", base, number);
+            number += 1;
+        }
+        taken.insert(name.clone());
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE_CULTURE: &str = r#"
+        [[cultures]]
+        name = "Test"
+        first = ["Ka"]
+        middle = []
+        last = ["ro"]
+        star = ["{}"]
+        planet = ["{} Prime"]
+        region = ["{} Reach"]
+        settlement = ["Port {}"]
+        person = ["{} {}"]
+    "#;
+
+    #[test]
+    fn test_names_follow_their_culture() {
+        let data = NamesData::parse(ONE_CULTURE).unwrap();
+        let mut names = NameGenerator::with_data(&data, 1);
+        assert_eq!(names.culture().name, "Test");
+        assert_eq!(names.name(NameKind::Star), "Karo");
+        assert_eq!(names.name(NameKind::Settlement), "Port Karo");
+        assert_eq!(names.name(NameKind::Person), "Karo Karo");
+
+        // A name already given is numbered once there is no other
+        let mut taken = HashSet::new();
+        assert_eq!(names.unique(NameKind::Planet, &mut taken), "Karo Prime");
+        assert_eq!(names.unique(NameKind::Planet, &mut taken), "Karo Prime 2");
+        assert_eq!(names.unique(NameKind::Planet, &mut taken), "Karo Prime 3");
+    }
+
+    #[test]
+    fn test_same_seed_same_names() {
+        let names = |seed| {
+            let mut generator = NameGenerator::new(seed);
+            let culture = generator.culture().name.clone();
+            let names: Vec<String> = NameKind::ALL.iter().map(|&k| generator.name(k)).collect();
+            (culture, names)
+        };
+        assert_eq!(names(7), names(7));
+        // Between them the seeds reach every built-in culture
+        let cultures: HashSet<String> = (0..50).map(|seed| names(seed).0).collect();
+        assert_eq!(cultures.len(), NamesData::built_in().cultures().len());
+    }
+
+    #[test]
+    fn test_bad_names_data_is_rejected() {
+        assert!(matches!(
+            NamesData::parse("cultures = []"),
+            Err(NamesDataError::NoCultures)
+        ));
+        let twice = format!("{}{}", ONE_CULTURE, ONE_CULTURE);
+        assert!(matches!(
+            NamesData::parse(&twice),
+            Err(NamesDataError::DuplicateCulture(_))
+        ));
+        let no_root = ONE_CULTURE.replace("{} Reach", "The Reach");
+        assert!(matches!(
+            NamesData::parse(&no_root),
+            Err(NamesDataError::NoRoot {
+                kind: NameKind::Region,
+                ..
+            })
+        ));
+        let no_syllables = ONE_CULTURE.replace(r#"last = ["ro"]"#, "last = []");
+        assert!(matches!(
+            NamesData::parse(&no_syllables),
+            Err(NamesDataError::Empty { .. })
+        ));
+    }
+}
@@ -0,0 +1,135 @@
+//! Finding a way across a local area's map.
+//!
+//! A [`Grid`] marks the tiles taken up by buildings, which nobody can walk
+//! through, and [`Grid::find_path`] runs A* over the rest, moving one tile
+//! at a time up, down, left or right. The tile a path ends on may itself be
+//! a building, since going somewhere usually means going inside. Searches
+//! keep to a margin around the start and goal so an unreachable tile gives
+//! up quickly instead of wandering off across an endless map.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap};
+
+/// How far beyond the start and goal a search may stray to get around
+/// whatever is in the way
+const SEARCH_MARGIN: i32 = 8;
+
+/// Tiles on a map and which of them are blocked
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Grid {
+    blocked: BTreeSet<(i32, i32)>,
+}
+
+impl Grid {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark a tile as impassable
+    pub fn block(&mut self, coords: (i32, i32)) {
+        self.blocked.insert(coords);
+    }
+
+    pub fn is_blocked(&self, coords: (i32, i32)) -> bool {
+        self.blocked.contains(&coords)
+    }
+
+    /// The tiles to step through to get from `from` to `to`, ending on `to`
+    /// and not including `from`, or None if there is no way through. Ties
+    /// between equally short paths always break the same way.
+    pub fn find_path(&self, from: (i32, i32), to: (i32, i32)) -> Option<Vec<(i32, i32)>> {
+        if from == to {
+            return Some(Vec::new());
+        }
+
+        let (min_x, max_x) = (
+            from.0.min(to.0) - SEARCH_MARGIN,
+            from.0.max(to.0) + SEARCH_MARGIN,
+        );
+        let (min_y, max_y) = (
+            from.1.min(to.1) - SEARCH_MARGIN,
+            from.1.max(to.1) + SEARCH_MARGIN,
+        );
+        let in_bounds =
+            |(x, y): (i32, i32)| (min_x..=max_x).contains(&x) && (min_y..=max_y).contains(&y);
+        let estimate = |(x, y): (i32, i32)| (x - to.0).abs() + (y - to.1).abs();
+
+        let mut open = BinaryHeap::new();
+        let mut came_from = BTreeMap::new();
+        let mut cost = BTreeMap::from([(from, 0)]);
+        open.push(Reverse((estimate(from), 0, from)));
+
+        while let Some(Reverse((_, walked, tile))) = open.pop() {
+            if tile == to {
+                let mut path = vec![to];
+                let mut at = to;
+                while let Some(&previous) = came_from.get(&at) {
+                    if previous == from {
+                        break;
+                    }
+                    path.push(previous);
+                    at = previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if cost.get(&tile).is_some_and(|&best| walked > best) {
+                continue;
+            }
+
+            let (x, y) = tile;
+            for next in [(x, y - 1), (x + 1, y), (x, y + 1), (x - 1, y)] {
+                if !in_bounds(next) || (next != to && self.is_blocked(next)) {
+                    continue;
+                }
+                let walked = walked + 1;
+                if cost.get(&next).is_none_or(|&best| walked < best) {
+                    cost.insert(next, walked);
+                    came_from.insert(next, tile);
+                    open.push(Reverse((walked + estimate(next), walked, next)));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths_go_around_walls() {
+        let mut grid = Grid::new();
+        for y in -2..=2 {
+            grid.block((2, y));
+        }
+
+        let path = grid.find_path((0, 0), (4, 0)).unwrap();
+        assert_eq!(path.last(), Some(&(4, 0)));
+        assert_eq!(path.len(), 10);
+        assert!(path.iter().all(|&tile| !grid.is_blocked(tile)));
+        let mut at = (0, 0);
+        for &(x, y) in &path {
+            assert_eq!((x - at.0).abs() + (y - at.1).abs(), 1);
+            at = (x, y);
+        }
+        assert_eq!(grid.find_path((0, 0), (4, 0)), Some(path));
+    }
+
+    #[test]
+    fn test_blocked_goals_can_be_entered_but_not_enclosed_ones() {
+        let mut grid = Grid::new();
+        grid.block((3, 0));
+        assert_eq!(
+            grid.find_path((0, 0), (3, 0)),
+            Some(vec![(1, 0), (2, 0), (3, 0)])
+        );
+        assert_eq!(grid.find_path((3, 0), (3, 0)), Some(Vec::new()));
+
+        for tile in [(5, 4), (6, 5), (5, 6), (4, 5)] {
+            grid.block(tile);
+        }
+        assert_eq!(grid.find_path((0, 0), (5, 5)), None);
+    }
+}
@@ -0,0 +1,153 @@
+//! Nations and empires that govern parts of the galaxy.
+//!
+//! A [`Polity`] holds whole planets and single regions on planets it doesn't
+//! hold whole. Every market in its territory answers to its
+//! [`FiscalPolicy`]: firms there pay tax on their takings, every sale pays
+//! sales tax, and its tariff raises every price. What the taxes raise goes
+//! into the polity's budget, held in the economy as its treasury, and a
+//! share of that is spent each day building infrastructure in the regions
+//! it governs, which makes their firms more productive; see
+//! [`infrastructure_boost`]. Each polity also researches technologies, as
+//! fast as the number of markets it governs allows, and its firms work with
+//! whatever it knows; see [`RESEARCH_PER_MARKET`]. Places no polity holds
+//! are ungoverned, untaxed and stuck with the technology they started with.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+use crate::economy::{FiscalPolicy, Money, Research};
+
+use super::state::EntityId;
+
+/// Highest tax rate and tariff a polity can set
+pub const MAX_RATE: f64 = 1.0;
+
+/// Days of research a polity does each day for every market it governs
+pub const RESEARCH_PER_MARKET: f64 = 1.0;
+
+/// Most infrastructure can add to a region's productivity
+const MAX_BOOST: f64 = 0.5;
+
+/// Infrastructure spending that gives a region half of [`MAX_BOOST`]
+const HALF_BOOST: Money = Money(20_000.0);
+
+/// Multiplier on the productivity of firms in a region that has had
+/// `invested` spent on its infrastructure. Each extra credit adds less than
+/// the last.
+pub fn infrastructure_boost(invested: Money) -> f64 {
+    let invested = invested.max(Money::ZERO);
+    1.0 + MAX_BOOST * (invested / (invested + HALF_BOOST))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Polity {
+    pub id: EntityId,
+    pub name: String,
+    /// Planets held whole, every region on them included
+    pub planets: BTreeSet<EntityId>,
+    /// Regions held on planets the polity doesn't hold whole
+    pub regions: BTreeSet<EntityId>,
+    pub policy: FiscalPolicy,
+    /// Tax collected since the polity was founded
+    #[serde(default)]
+    pub collected: Money,
+    /// Budget spent on infrastructure since the polity was founded
+    #[serde(default)]
+    pub spent: Money,
+    /// Technologies known and the one being researched
+    #[serde(default)]
+    pub research: Research,
+}
+
+impl Polity {
+    pub fn new(id: EntityId, name: &str, policy: FiscalPolicy) -> Self {
+        Self {
+            id,
+            name: name.to_string(),
+            planets: BTreeSet::new(),
+            regions: BTreeSet::new(),
+            policy,
+            collected: Money::ZERO,
+            spent: Money::ZERO,
+            research: Research::new(),
+        }
+    }
+
+    /// Whether the polity governs a region on a planet
+    pub fn governs(&self, region: EntityId, planet: Option<EntityId>) -> bool {
+        self.regions.contains(&region) || planet.is_some_and(|p| self.planets.contains(&p))
+    }
+
+    /// Whether a policy's rates are ones a polity can set
+    pub fn is_valid_policy(policy: FiscalPolicy) -> bool {
+        [
+            policy.tax_rate,
+            policy.tariff,
+            policy.sales_tax,
+            policy.spending,
+        ]
+        .iter()
+        .all(|rate| (0.0..=MAX_RATE).contains(rate))
+    }
+
+    /// The policy in a few words, for panels and the log
+    pub fn policy_summary(&self) -> String {
+        format!(
+            "tax {:.0}%, sales tax {:.0}%, tariff {:.0}%, spending {:.0}%/day",
+            self.policy.tax_rate * 100.0,
+            self.policy.sales_tax * 100.0,
+            self.policy.tariff * 100.0,
+            self.policy.spending * 100.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_territory_and_policy() {
+        let mut polity = Polity::new(
+            1,
+            "Accord",
+            FiscalPolicy {
+                tax_rate: 0.1,
+                tariff: 0.05,
+                sales_tax: 0.02,
+                spending: 0.1,
+            },
+        );
+        polity.planets.insert(10);
+        polity.regions.insert(20);
+        assert!(polity.governs(11, Some(10)));
+        assert!(polity.governs(20, Some(12)));
+        assert!(!polity.governs(21, Some(12)));
+        assert!(!polity.governs(21, None));
+        assert_eq!(
+            polity.policy_summary(),
+            "tax 10%, sales tax 2%, tariff 5%, spending 10%/day"
+        );
+
+        assert!(Polity::is_valid_policy(polity.policy));
+        assert!(!Polity::is_valid_policy(FiscalPolicy {
+            tax_rate: -0.1,
+            ..FiscalPolicy::NONE
+        }));
+        assert!(!Polity::is_valid_policy(FiscalPolicy {
+            spending: f64::NAN,
+            ..FiscalPolicy::NONE
+        }));
+    }
+
+    #[test]
+    fn test_infrastructure_boost_levels_off() {
+        assert_eq!(infrastructure_boost(Money::ZERO), 1.0);
+        assert_eq!(infrastructure_boost(Money(-5.0)), 1.0);
+        assert_eq!(infrastructure_boost(HALF_BOOST), 1.0 + MAX_BOOST / 2.0);
+        let first = infrastructure_boost(HALF_BOOST) - 1.0;
+        let second = infrastructure_boost(HALF_BOOST * 2.0) - 1.0 - first;
+        assert!(second < first);
+        assert!(infrastructure_boost(Money(1e12)) < 1.0 + MAX_BOOST);
+    }
+}
@@ -0,0 +1,175 @@
+//! Difficulty presets a new game is started from.
+//!
+//! A [`GamePreset`] says how much money the player starts with, how big a
+//! world to generate, how sharply prices react to shortages and gluts and
+//! how often droughts, storms and random events such as plagues strike. The [`Rules`] it sets are kept in
+//! the world, so a saved game plays by the same rules when it is resumed.
+
+use serde::{Deserialize, Serialize};
+
+use crate::economy::Money;
+
+use super::WorldState;
+use super::business::STARTING_PURSE;
+use super::worldgen::WorldConfig;
+
+/// The rules a world is simulated under, fixed when it is created
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rules {
+    /// Name of the preset the world was started from
+    pub preset: String,
+    /// Cash in the player's purse at the start
+    pub starting_money: Money,
+    /// Multiplier on how far prices move as stock runs short or piles up
+    pub volatility: f64,
+    /// Multiplier on the daily chance of droughts and storms
+    pub disaster_frequency: f64,
+    /// Multiplier on the daily chance of asteroid strikes, plagues, gold
+    /// rushes and breakthroughs
+    pub event_frequency: f64,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        STANDARD.rules()
+    }
+}
+
+/// A kind of game to start, chosen from the startup menu
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GamePreset {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub starting_money: Money,
+    /// Systems generated around the home system
+    pub systems: u32,
+    /// How far from the galaxy's center systems are placed
+    pub galaxy_radius: i32,
+    pub volatility: f64,
+    pub disaster_frequency: f64,
+    pub event_frequency: f64,
+}
+
+const STANDARD: GamePreset = GamePreset {
+    name: "Standard",
+    description: "A modest purse in a settled galaxy",
+    starting_money: STARTING_PURSE,
+    systems: 4,
+    galaxy_radius: 6,
+    volatility: 1.0,
+    disaster_frequency: 1.0,
+    event_frequency: 1.0,
+};
+
+/// Every preset, easiest first
+pub const PRESETS: [GamePreset; 4] = [
+    GamePreset {
+        name: "Sandbox",
+        description: "Deep pockets, steady prices and fair weather",
+        starting_money: Money(50_000.0),
+        systems: 2,
+        galaxy_radius: 4,
+        volatility: 0.5,
+        disaster_frequency: 0.0,
+        event_frequency: 0.0,
+    },
+    STANDARD,
+    GamePreset {
+        name: "Frontier",
+        description: "A sprawling galaxy with restless markets",
+        starting_money: Money(1_500.0),
+        systems: 10,
+        galaxy_radius: 10,
+        volatility: 1.5,
+        disaster_frequency: 1.5,
+        event_frequency: 1.5,
+    },
+    GamePreset {
+        name: "Hardship",
+        description: "Little cash, wild prices and frequent disasters",
+        starting_money: Money(500.0),
+        systems: 6,
+        galaxy_radius: 8,
+        volatility: 2.0,
+        disaster_frequency: 3.0,
+        event_frequency: 2.0,
+    },
+];
+
+impl GamePreset {
+    /// The preset with this name, ignoring case
+    pub fn find(name: &str) -> Option<GamePreset> {
+        PRESETS
+            .into_iter()
+            .find(|preset| preset.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn rules(&self) -> Rules {
+        Rules {
+            preset: self.name.to_string(),
+            starting_money: self.starting_money,
+            volatility: self.volatility,
+            disaster_frequency: self.disaster_frequency,
+            event_frequency: self.event_frequency,
+        }
+    }
+
+    /// Parameters for generating the preset's world
+    pub fn world_config(&self, seed: u64) -> WorldConfig {
+        WorldConfig {
+            seed,
+            systems: self.systems,
+            galaxy_radius: self.galaxy_radius,
+            ..WorldConfig::default()
+        }
+    }
+
+    /// Generate a world to play the preset in
+    pub fn generate(&self, seed: u64) -> WorldState {
+        let mut world = self.world_config(seed).generate();
+        world.set_rules(self.rules());
+        world
+    }
+}
+
+impl Default for GamePreset {
+    fn default() -> Self {
+        STANDARD
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::state::DEFAULT_SEED;
+    use crate::zoom::ZoomLevel;
+
+    #[test]
+    fn test_presets_are_found_by_name() {
+        assert_eq!(GamePreset::find("hardship").unwrap().name, "Hardship");
+        assert!(GamePreset::find("Impossible").is_none());
+        assert_eq!(Rules::default(), GamePreset::default().rules());
+    }
+
+    #[test]
+    fn test_generated_worlds_follow_the_preset() {
+        let small = GamePreset::find("Sandbox").unwrap().generate(DEFAULT_SEED);
+        let large = GamePreset::find("Frontier").unwrap().generate(DEFAULT_SEED);
+        assert!(small.ids(ZoomLevel::SolarSystem).len() < large.ids(ZoomLevel::SolarSystem).len());
+        assert_eq!(small.economy().purse(), Money(50_000.0));
+        assert_eq!(large.rules().preset, "Frontier");
+        assert_eq!(large.economy().volatility(), 1.5);
+        let json = serde_json::to_string(&large).unwrap();
+        let resumed: WorldState = serde_json::from_str(&json).unwrap();
+        assert_eq!(resumed.rules(), large.rules());
+        assert_eq!(resumed.economy().volatility(), 1.5);
+
+        for world in [&small, &large] {
+            assert_eq!(
+                world.economy().total_balances(),
+                world.economy().money_supply().issued()
+            );
+        }
+    }
+}
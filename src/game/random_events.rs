@@ -0,0 +1,245 @@
+//! Shocks nobody scheduled.
+//!
+//! Once a day the galaxy rolls for a [`RandomEvent`]: an asteroid strike
+//! that kills people in a local area and buries much of its market's stock,
+//! a plague that sweeps a planet, a gold rush that floods an area's market
+//! with ore and draws people in, or a breakthrough that lifts the output of
+//! a region's firms as new infrastructure would. At most one strikes a day,
+//! on a place picked at random, and the player is told what happened. The
+//! chance of each scales with the world's [`Rules::event_frequency`], which
+//! presets and scenarios set; at 0 nothing ever happens.
+//!
+//! [`Rules::event_frequency`]: super::presets::Rules::event_frequency
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::economy::Money;
+use crate::time::Calendar;
+use crate::zoom::ZoomLevel;
+
+use super::events::Severity;
+use super::polities;
+use super::rng::Rng;
+use super::state::{EntityId, WorldState};
+
+/// Time between the daily rolls for a random event
+pub const RANDOM_EVENT_INTERVAL: Duration = Calendar::DEFAULT_DAY_LENGTH;
+
+/// Good a gold rush strikes
+const RUSH_GOOD: &str = "ore";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RandomEvent {
+    /// Kills part of a local area's workforce and buries part of its
+    /// market's stock
+    AsteroidStrike,
+    /// Kills part of a planet's residents
+    Plague,
+    /// Floods a local area's market with ore and draws workers to it
+    GoldRush,
+    /// Raises a region's productivity as infrastructure does
+    Breakthrough,
+}
+
+impl RandomEvent {
+    pub const ALL: [RandomEvent; 4] = [
+        RandomEvent::AsteroidStrike,
+        RandomEvent::Plague,
+        RandomEvent::GoldRush,
+        RandomEvent::Breakthrough,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RandomEvent::AsteroidStrike => "asteroid strike",
+            RandomEvent::Plague => "plague",
+            RandomEvent::GoldRush => "gold rush",
+            RandomEvent::Breakthrough => "breakthrough",
+        }
+    }
+
+    /// Chance of the event happening somewhere in the galaxy on a day
+    fn daily_chance(self) -> f64 {
+        match self {
+            RandomEvent::AsteroidStrike => 0.002,
+            RandomEvent::Plague => 0.003,
+            RandomEvent::GoldRush => 0.004,
+            RandomEvent::Breakthrough => 0.004,
+        }
+    }
+
+    /// Strike a place picked at random, returning how much it matters and
+    /// the message to tell the player, or None if there was nowhere for
+    /// the event to happen
+    pub fn strike(self, world: &mut WorldState, rng: &mut Rng) -> Option<(Severity, String)> {
+        match self {
+            RandomEvent::AsteroidStrike => asteroid_strike(world, rng),
+            RandomEvent::Plague => plague(world, rng),
+            RandomEvent::GoldRush => gold_rush(world, rng),
+            RandomEvent::Breakthrough => breakthrough(world, rng),
+        }
+    }
+}
+
+impl fmt::Display for RandomEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Roll for a day's random event, with each kind's chance scaled by
+/// `frequency`
+pub fn roll(frequency: f64, rng: &mut Rng) -> Option<RandomEvent> {
+    let mut roll = rng.next_f64();
+    for event in RandomEvent::ALL {
+        let chance = event.daily_chance() * frequency.max(0.0);
+        if roll < chance {
+            return Some(event);
+        }
+        roll -= chance;
+    }
+    None
+}
+
+/// A share between `low` and `high` percent
+fn share(rng: &mut Rng, low: u64, high: u64) -> f64 {
+    rng.range(low, high + 1) as f64 / 100.0
+}
+
+/// A local area with a market, picked at random
+fn market_area(world: &WorldState, rng: &mut Rng) -> Option<EntityId> {
+    let areas: Vec<EntityId> = world.economy().markets().map(|(id, _)| id).collect();
+    rng.choose(&areas).copied()
+}
+
+fn asteroid_strike(world: &mut WorldState, rng: &mut Rng) -> Option<(Severity, String)> {
+    let area = market_area(world, rng)?;
+    let killed = share(rng, 10, 30);
+    let buried = share(rng, 20, 50);
+
+    let workforce = world.population(ZoomLevel::LocalArea, area).unwrap_or(0);
+    let dead = (workforce as f64 * killed) as u64;
+    world.set_population(ZoomLevel::LocalArea, area, workforce - dead);
+    let economy = world.economy_mut();
+    let goods: Vec<_> = economy.catalog().iter().map(|(good, _)| good).collect();
+    if let Some(market) = economy.market_mut(area) {
+        for good in goods {
+            market.take(good, market.stock(good) * buried);
+        }
+    }
+
+    let name = world.entity_name(ZoomLevel::LocalArea, area);
+    Some((
+        Severity::Critical,
+        format!(
+            "An asteroid strikes {}, killing {} and burying {:.0}% of its market's stock",
+            name,
+            dead,
+            buried * 100.0
+        ),
+    ))
+}
+
+fn plague(world: &mut WorldState, rng: &mut Rng) -> Option<(Severity, String)> {
+    let planets: Vec<EntityId> = world
+        .ids(ZoomLevel::Planet)
+        .into_iter()
+        .filter(|&id| world.population(ZoomLevel::Planet, id).unwrap_or(0) > 0)
+        .collect();
+    let planet = *rng.choose(&planets)?;
+    let killed = share(rng, 5, 20);
+
+    let residents = world.population(ZoomLevel::Planet, planet).unwrap_or(0);
+    let dead = (residents as f64 * killed) as u64;
+    world.set_population(ZoomLevel::Planet, planet, residents - dead);
+    let name = world.entity_name(ZoomLevel::Planet, planet);
+    Some((
+        Severity::Warning,
+        format!("Plague sweeps {}, killing {}", name, dead),
+    ))
+}
+
+fn gold_rush(world: &mut WorldState, rng: &mut Rng) -> Option<(Severity, String)> {
+    let area = market_area(world, rng)?;
+    let quantity = rng.range(500, 2_001) as f64;
+    let influx = share(rng, 10, 25);
+
+    let economy = world.economy_mut();
+    let good = economy.catalog().id(RUSH_GOOD)?;
+    economy.market_mut(area)?.add(good, quantity);
+    let workforce = world.population(ZoomLevel::LocalArea, area).unwrap_or(0);
+    let arrivals = (workforce as f64 * influx).round() as u64;
+    world.set_population(ZoomLevel::LocalArea, area, workforce + arrivals);
+
+    let name = world.entity_name(ZoomLevel::LocalArea, area);
+    Some((
+        Severity::Info,
+        format!(
+            "Gold rush in {}: prospectors strike {:.0} {} and {} people pour in",
+            name, quantity, RUSH_GOOD, arrivals
+        ),
+    ))
+}
+
+fn breakthrough(world: &mut WorldState, rng: &mut Rng) -> Option<(Severity, String)> {
+    let regions = world.ids(ZoomLevel::Region);
+    let region_id = *rng.choose(&regions)?;
+    let worth = Money(rng.range(5_000, 15_001) as f64);
+
+    let region = world.get_region_mut(region_id)?;
+    let before = polities::infrastructure_boost(region.infrastructure);
+    region.infrastructure += worth;
+    let after = polities::infrastructure_boost(region.infrastructure);
+
+    let name = world.entity_name(ZoomLevel::Region, region_id);
+    Some((
+        Severity::Info,
+        format!(
+            "Engineers in {} make a breakthrough, lifting its firms' output by {:.1}%",
+            name,
+            (after / before - 1.0) * 100.0
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rolls_scale_with_frequency() {
+        let days = 20_000;
+        let count = |frequency: f64| {
+            let mut rng = Rng::new(7);
+            (0..days)
+                .filter(|_| roll(frequency, &mut rng).is_some())
+                .count()
+        };
+        let chance: f64 = RandomEvent::ALL.iter().map(|e| e.daily_chance()).sum();
+
+        assert_eq!(count(0.0), 0);
+        let expected = chance * days as f64;
+        assert!((count(1.0) as f64 - expected).abs() < expected * 0.25);
+        assert!(count(3.0) > count(1.0) * 2);
+    }
+
+    #[test]
+    fn test_every_event_strikes_the_default_world() {
+        let mut world = WorldState::new();
+        let population = world.census().totals().population;
+        let ore = world.economy().catalog().id(RUSH_GOOD).unwrap();
+        let stock = world.economy().market(1).unwrap().stock(ore);
+        let infrastructure = world.infrastructure(1);
+
+        for event in RandomEvent::ALL {
+            let (_, message) = event.strike(&mut world, &mut Rng::new(3)).unwrap();
+            assert!(!message.is_empty(), "{} said nothing", event);
+        }
+        world.refresh_census();
+        assert_ne!(world.census().totals().population, population);
+        assert_ne!(world.economy().market(1).unwrap().stock(ore), stock);
+        assert!(world.infrastructure(1) > infrastructure);
+        assert!(world.audit().is_empty());
+    }
+}
@@ -0,0 +1,160 @@
+//! Going back in time to an earlier state of the world.
+//!
+//! Every [`SNAPSHOT_INTERVAL`] of simulation time the world is serialized and
+//! deflated into a ring of [`SNAPSHOT_SLOTS`] snapshots, so trying out a
+//! policy change costs nothing: rewind and try something else. Snapshots
+//! live only for the session; saves don't keep them.
+
+use flate2::Compression;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::result::Result;
+
+use super::WorldState;
+
+/// Simulation time between snapshots
+pub const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(86_400);
+
+/// Snapshots kept before the oldest is dropped
+pub const SNAPSHOT_SLOTS: usize = 30;
+
+/// How recent a snapshot can be and still count as the present, so that
+/// rewinding again shortly after a rewind goes further back
+pub const REWIND_GRACE: Duration = Duration::from_secs(3_600);
+
+/// The world at one moment, compressed
+pub struct Snapshot {
+    /// Simulation time the snapshot was taken at
+    pub at: Duration,
+    data: Vec<u8>,
+}
+
+impl Snapshot {
+    pub fn take(world: &WorldState, at: Duration) -> Result<Self> {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+        serde_json::to_writer(&mut encoder, world)?;
+        Ok(Self {
+            at,
+            data: encoder.finish()?,
+        })
+    }
+
+    pub fn restore(&self) -> Result<WorldState> {
+        Ok(serde_json::from_reader(DeflateDecoder::new(
+            self.data.as_slice(),
+        ))?)
+    }
+
+    /// Compressed size in bytes
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+}
+
+/// The most recent snapshots, oldest first
+#[derive(Default)]
+pub struct Rewind {
+    snapshots: VecDeque<Snapshot>,
+}
+
+impl Rewind {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether a snapshot is due at `now`: none has been taken yet, or the
+    /// latest is at least an interval old
+    pub fn is_due(&self, now: Duration) -> bool {
+        self.snapshots
+            .back()
+            .is_none_or(|latest| now.saturating_sub(latest.at) >= SNAPSHOT_INTERVAL)
+    }
+
+    /// Keep a snapshot, dropping the oldest once every slot is full
+    pub fn push(&mut self, snapshot: Snapshot) {
+        if self.snapshots.len() == SNAPSHOT_SLOTS {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+    }
+
+    /// The latest snapshot taken more than [`REWIND_GRACE`] before `now`,
+    /// dropping any taken since. It stays in the ring as the latest
+    /// snapshot, so rewinding again goes back to the one before it.
+    pub fn restore_point(&mut self, now: Duration) -> Option<&Snapshot> {
+        let keep = self
+            .snapshots
+            .iter()
+            .rposition(|snapshot| snapshot.at + REWIND_GRACE < now)?;
+        self.snapshots.truncate(keep + 1);
+        self.snapshots.back()
+    }
+
+    /// The snapshot `back` places before the latest
+    pub fn back(&self, back: usize) -> Option<&Snapshot> {
+        let index = self.snapshots.len().checked_sub(back + 1)?;
+        self.snapshots.get(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zoom::ZoomLevel;
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let mut world = WorldState::new();
+        world.set_population(ZoomLevel::Planet, 1, 1234);
+        let snapshot = Snapshot::take(&world, Duration::from_secs(60)).unwrap();
+        let json = serde_json::to_vec(&world).unwrap();
+        assert!(snapshot.size() < json.len() / 2);
+
+        let restored = snapshot.restore().unwrap();
+        assert_eq!(restored.population(ZoomLevel::Planet, 1).unwrap(), 1234);
+        assert_eq!(restored.seed(), world.seed());
+    }
+
+    #[test]
+    fn test_ring_keeps_the_latest_snapshots() {
+        let world = WorldState::new();
+        let mut rewind = Rewind::new();
+        let day = |n: u64| SNAPSHOT_INTERVAL * n as u32;
+        for n in 0..SNAPSHOT_SLOTS as u64 + 5 {
+            assert!(rewind.is_due(day(n)));
+            rewind.push(Snapshot::take(&world, day(n)).unwrap());
+            assert!(!rewind.is_due(day(n) + Duration::from_secs(1)));
+        }
+        assert_eq!(rewind.len(), SNAPSHOT_SLOTS);
+        assert_eq!(rewind.back(0).unwrap().at, day(SNAPSHOT_SLOTS as u64 + 4));
+        assert_eq!(rewind.back(SNAPSHOT_SLOTS - 1).unwrap().at, day(5));
+        assert!(rewind.back(SNAPSHOT_SLOTS).is_none());
+
+        // Snapshots at or just before the present are skipped, and the one
+        // restored stays in the ring
+        let latest = day(SNAPSHOT_SLOTS as u64 + 4);
+        let restored = rewind.restore_point(latest).unwrap().at;
+        assert_eq!(restored, latest - SNAPSHOT_INTERVAL);
+        assert_eq!(rewind.len(), SNAPSHOT_SLOTS - 1);
+        assert!(!rewind.is_due(restored));
+
+        let restored = rewind
+            .restore_point(restored + Duration::from_secs(1))
+            .unwrap()
+            .at;
+        assert_eq!(restored, latest - SNAPSHOT_INTERVAL * 2);
+        assert_eq!(rewind.len(), SNAPSHOT_SLOTS - 2);
+        assert!(rewind.restore_point(day(0) + REWIND_GRACE).is_none());
+    }
+}
@@ -0,0 +1,388 @@
+//! Authored events that a scenario schedules for specific dates.
+//!
+//! A scenario lists [`ScriptedEvent`]s with dates counted from its start;
+//! when the scenario begins they are queued on the world's [`Schedule`],
+//! which fires each one as the clock passes its time. The console's `shock`
+//! command applies the economic shocks among them straight away.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use thiserror::Error as ThisError;
+
+use crate::economy::Firm;
+use crate::time::{Calendar, DAYS_PER_MONTH, DAYS_PER_YEAR, Scheduler};
+use crate::zoom::ZoomLevel;
+
+use super::WorldState;
+use super::state::EntityId;
+
+#[derive(ThisError, Debug, Clone, PartialEq, Eq)]
+pub enum EventError {
+    #[error("no place named '{0}'")]
+    UnknownPlace(String),
+    #[error("'{0}' is not a planet or local area")]
+    NotPopulated(String),
+    #[error("'{0}' is not a local area")]
+    NotAnArea(String),
+    #[error("no recipe named '{0}'")]
+    UnknownRecipe(String),
+    #[error("no good named '{0}'")]
+    UnknownGood(String),
+    #[error("'{0}' has no market")]
+    NoMarket(String),
+    #[error("'{0}' is not a solar system")]
+    NotASystem(String),
+}
+
+/// When an event happens, as a 1-based date counted from the start of the
+/// scenario. Omitted fields default to 1, so `{"year": 3}` is the first day
+/// of the scenario's third year and `{"month": 6}` the first day of its
+/// sixth month.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventDate {
+    pub year: u32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl EventDate {
+    /// Simulation time from the scenario's start to this date
+    pub fn offset(&self, calendar: &Calendar) -> Duration {
+        let days = self.year.saturating_sub(1) * DAYS_PER_YEAR
+            + self.month.saturating_sub(1) * DAYS_PER_MONTH
+            + self.day.saturating_sub(1);
+        calendar.day_length() * days
+    }
+}
+
+impl Default for EventDate {
+    fn default() -> Self {
+        Self {
+            year: 1,
+            month: 1,
+            day: 1,
+        }
+    }
+}
+
+/// What a scripted event does to the world
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EventEffect {
+    /// Nothing beyond the message in the event log
+    Announcement,
+    /// Kill a share of a planet's population or an area's workforce
+    Plague { target: String, share: f64 },
+    /// A new firm opens in a local area
+    Arrival {
+        area: String,
+        name: String,
+        recipe: String,
+    },
+    /// A rich deposit is found in a local area, flooding its market with a
+    /// good
+    Discovery {
+        area: String,
+        good: String,
+        quantity: f64,
+    },
+    /// A solar system's hyperlanes collapse, cutting it off from the rest
+    /// of the galaxy for a number of days
+    LaneCollapse { system: String, days: f64 },
+    /// The money in circulation grows by a share overnight
+    CurrencyCrisis { share: f64 },
+}
+
+/// An authored event: a date, the message logged when it fires, and its
+/// effect
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptedEvent {
+    #[serde(default)]
+    pub date: EventDate,
+    pub message: String,
+    #[serde(flatten)]
+    pub effect: EventEffect,
+}
+
+impl ScriptedEvent {
+    pub fn apply(&self, world: &mut WorldState) -> Result<(), EventError> {
+        match &self.effect {
+            EventEffect::Announcement => Ok(()),
+            EventEffect::Plague { target, share } => plague(world, target, *share),
+            EventEffect::Arrival { area, name, recipe } => arrival(world, area, name, recipe),
+            EventEffect::Discovery {
+                area,
+                good,
+                quantity,
+            } => discovery(world, area, good, *quantity),
+            EventEffect::LaneCollapse { system, days } => lane_collapse(world, system, *days),
+            EventEffect::CurrencyCrisis { share } => {
+                world.economy_mut().debase(share.max(0.0));
+                Ok(())
+            }
+        }
+    }
+}
+
+fn plague(world: &mut WorldState, target: &str, share: f64) -> Result<(), EventError> {
+    let (level, id) = world
+        .find_entity(target)
+        .ok_or_else(|| EventError::UnknownPlace(target.to_string()))?;
+    let population = world
+        .population(level, id)
+        .ok_or_else(|| EventError::NotPopulated(target.to_string()))?;
+
+    let survivors = 1.0 - share.clamp(0.0, 1.0);
+    world.set_population(level, id, (population as f64 * survivors) as u64);
+    Ok(())
+}
+
+fn local_area(world: &WorldState, area: &str) -> Result<EntityId, EventError> {
+    match world.find_entity(area) {
+        Some((ZoomLevel::LocalArea, id)) => Ok(id),
+        Some(_) => Err(EventError::NotAnArea(area.to_string())),
+        None => Err(EventError::UnknownPlace(area.to_string())),
+    }
+}
+
+fn arrival(world: &mut WorldState, area: &str, name: &str, recipe: &str) -> Result<(), EventError> {
+    let area_id = local_area(world, area)?;
+    let recipe_id = world
+        .economy()
+        .recipes()
+        .find(recipe)
+        .ok_or_else(|| EventError::UnknownRecipe(recipe.to_string()))?;
+
+    let mut firm = Firm::new(world.next_id(), name, area_id, recipe_id);
+    firm.coords = world.free_firm_spot(area_id);
+    world.economy_mut().add_firm(firm);
+    Ok(())
+}
+
+fn discovery(
+    world: &mut WorldState,
+    area: &str,
+    good: &str,
+    quantity: f64,
+) -> Result<(), EventError> {
+    let area_id = local_area(world, area)?;
+    let economy = world.economy_mut();
+    let good_id = economy
+        .catalog()
+        .id(good)
+        .ok_or_else(|| EventError::UnknownGood(good.to_string()))?;
+    let market = economy
+        .market_mut(area_id)
+        .ok_or_else(|| EventError::NoMarket(area.to_string()))?;
+    market.add(good_id, quantity.max(0.0));
+    Ok(())
+}
+
+fn lane_collapse(world: &mut WorldState, system: &str, days: f64) -> Result<(), EventError> {
+    let system_id = match world.find_entity(system) {
+        Some((ZoomLevel::SolarSystem, id)) => id,
+        Some(_) => return Err(EventError::NotASystem(system.to_string())),
+        None => return Err(EventError::UnknownPlace(system.to_string())),
+    };
+    let duration = Calendar::default().day_length().mul_f64(days.max(0.0));
+    world.collapse_lanes(system_id, duration);
+    Ok(())
+}
+
+/// Scripted events waiting for their time
+pub type Schedule = Scheduler<ScriptedEvent>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn announcement(message: &str) -> ScriptedEvent {
+        ScriptedEvent {
+            date: EventDate::default(),
+            message: message.to_string(),
+            effect: EventEffect::Announcement,
+        }
+    }
+
+    #[test]
+    fn test_dates_count_from_scenario_start() {
+        let calendar = Calendar::default();
+        let day = Calendar::DEFAULT_DAY_LENGTH;
+        assert_eq!(EventDate::default().offset(&calendar), Duration::ZERO);
+
+        let june = EventDate {
+            month: 6,
+            ..EventDate::default()
+        };
+        assert_eq!(june.offset(&calendar), day * 150);
+
+        let third_year = EventDate {
+            year: 3,
+            ..EventDate::default()
+        };
+        assert_eq!(third_year.offset(&calendar), day * 720);
+    }
+
+    #[test]
+    fn test_schedule_releases_due_events_in_order() {
+        let mut schedule = Schedule::new();
+        schedule.schedule_at(Duration::from_secs(20), announcement("second"));
+        schedule.schedule_at(Duration::from_secs(10), announcement("first"));
+        schedule.schedule_at(Duration::from_secs(30), announcement("third"));
+
+        let due: Vec<String> = schedule
+            .take_due(Duration::from_secs(20))
+            .into_iter()
+            .map(|e| e.message)
+            .collect();
+        assert_eq!(due, vec!["first", "second"]);
+        assert_eq!(schedule.len(), 1);
+    }
+
+    #[test]
+    fn test_events_parse_from_json() {
+        let json = r#"[
+            {"date": {"year": 3}, "message": "A plague hits Terra",
+             "kind": "plague", "target": "Terra", "share": 0.25},
+            {"date": {"month": 6}, "message": "A rival trader arrives",
+             "kind": "arrival", "area": "Market District", "name": "Rival Bakery",
+             "recipe": "baking"},
+            {"message": "Welcome", "kind": "announcement"}
+        ]"#;
+        let events: Vec<ScriptedEvent> = serde_json::from_str(json).unwrap();
+
+        assert_eq!(events[0].date.year, 3);
+        assert_eq!(
+            events[0].effect,
+            EventEffect::Plague {
+                target: String::from("Terra"),
+                share: 0.25
+            }
+        );
+        assert_eq!(events[1].date.month, 6);
+        assert_eq!(events[2].date, EventDate::default());
+    }
+
+    #[test]
+    fn test_plague_reduces_population() {
+        let mut world = WorldState::new();
+        let before = world.population(ZoomLevel::Planet, 1).unwrap();
+        let event = ScriptedEvent {
+            effect: EventEffect::Plague {
+                target: String::from("Terra"),
+                share: 0.5,
+            },
+            ..announcement("Plague")
+        };
+
+        event.apply(&mut world).unwrap();
+        assert_eq!(world.population(ZoomLevel::Planet, 1).unwrap(), before / 2);
+    }
+
+    #[test]
+    fn test_arrival_opens_firm() {
+        let mut world = WorldState::new();
+        let area = world.entity_name(ZoomLevel::LocalArea, 1);
+        let firms = world.economy().firm_count();
+        let event = ScriptedEvent {
+            effect: EventEffect::Arrival {
+                area,
+                name: String::from("Rival Bakery"),
+                recipe: String::from("baking"),
+            },
+            ..announcement("A rival arrives")
+        };
+
+        event.apply(&mut world).unwrap();
+        assert_eq!(world.economy().firm_count(), firms + 1);
+
+        let bad = ScriptedEvent {
+            effect: EventEffect::Arrival {
+                area: String::from("Terra"),
+                name: String::from("Nowhere"),
+                recipe: String::from("baking"),
+            },
+            ..announcement("Misplaced")
+        };
+        assert_eq!(
+            bad.apply(&mut world),
+            Err(EventError::NotAnArea(String::from("Terra")))
+        );
+    }
+
+    #[test]
+    fn test_discovery_floods_market() {
+        let mut world = WorldState::new();
+        let area = world.entity_name(ZoomLevel::LocalArea, 1);
+        let ore = world.economy().catalog().id("ore").unwrap();
+        let before = world.economy().market(1).unwrap().stock(ore);
+        let event = ScriptedEvent {
+            effect: EventEffect::Discovery {
+                area: area.clone(),
+                good: String::from("ore"),
+                quantity: 500.0,
+            },
+            ..announcement("Ore strike")
+        };
+
+        event.apply(&mut world).unwrap();
+        assert_eq!(
+            world.economy().market(1).unwrap().stock(ore),
+            before + 500.0
+        );
+
+        let bad = ScriptedEvent {
+            effect: EventEffect::Discovery {
+                area,
+                good: String::from("gold"),
+                quantity: 1.0,
+            },
+            ..announcement("Gold strike")
+        };
+        assert_eq!(
+            bad.apply(&mut world),
+            Err(EventError::UnknownGood(String::from("gold")))
+        );
+    }
+
+    #[test]
+    fn test_lane_collapse_isolates_system_for_a_while() {
+        let mut world = WorldState::new();
+        let event = ScriptedEvent {
+            effect: EventEffect::LaneCollapse {
+                system: String::from("Sol System"),
+                days: 2.0,
+            },
+            ..announcement("The lanes to Sol collapse")
+        };
+
+        event.apply(&mut world).unwrap();
+        assert!(world.is_isolated(1));
+        world.update(Calendar::DEFAULT_DAY_LENGTH * 2);
+        assert!(!world.is_isolated(1));
+    }
+
+    #[test]
+    fn test_shocks_parse_from_json() {
+        let json = r#"[
+            {"message": "Ore strike", "kind": "discovery",
+             "area": "Market District", "good": "ore", "quantity": 400},
+            {"message": "Lanes down", "kind": "lane_collapse",
+             "system": "Sol System", "days": 30},
+            {"message": "Run on the credit", "kind": "currency_crisis", "share": 0.5}
+        ]"#;
+        let events: Vec<ScriptedEvent> = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            events[1].effect,
+            EventEffect::LaneCollapse {
+                system: String::from("Sol System"),
+                days: 30.0
+            }
+        );
+        let mut world = WorldState::new();
+        events[2].apply(&mut world).unwrap();
+        assert!((world.economy().money_supply().price_level() - 1.5).abs() < 1e-9);
+    }
+}
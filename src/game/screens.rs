@@ -0,0 +1,1554 @@
+//! Screens opened over or beside the game.
+//!
+//! Help, the economy and monetary dashboards, the census, polity finances,
+//! technology, the profiler, the debug overlay, the inspector sidebar, the
+//! mini-map and the console are each a [`Screen`] with their own input
+//! handling and drawing. The game loop keeps the open ones on a
+//! [`ScreenStack`], which offers input to the topmost screen first and
+//! lets each draw itself, so a new screen doesn't touch the game's own
+//! drawing.
+
+use std::time::Duration;
+
+use crate::economy::{BankSample, Builder, Money, PriceSeries};
+use crate::input::{InputAction, InputHandler, InputMode};
+use crate::render::minimap::{self, Cell};
+use crate::render::{Glyphs, Layout, Line, Rect, Renderer, Trend, charts};
+use crate::time::{Profiler, millis, resident_memory};
+use crate::ui::TextInput;
+use crate::zoom::ZoomLevel;
+
+use super::census::Figures;
+use super::commands::{self, History};
+use super::diff::{Change, WorldDiff};
+use super::menus::MenuChoice;
+use super::polities;
+use super::state::EntityId;
+use super::stats::GALAXY_ID;
+use super::watches::Watches;
+use super::{Simulation, WorldState};
+
+/// Days of history shown by each dashboard sparkline
+const DASHBOARD_CHART_WIDTH: usize = 16;
+/// Length of a full-scale price bar on the dashboard
+const DASHBOARD_BAR_WIDTH: usize = 14;
+/// Levels of each side of a good's book counted in the dashboard's depth
+const DASHBOARD_BOOK_LEVELS: usize = 5;
+
+/// Systems, planets and regions listed on the census screen
+const CENSUS_ROWS: usize = 14;
+
+/// Regions listed under each polity on the finances screen
+const FINANCE_REGIONS: usize = 4;
+
+/// Length of a research progress bar on the technology screen
+const RESEARCH_BAR_WIDTH: usize = 10;
+
+/// Every screen there is. Screens that make room in the layout do so in
+/// this order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ScreenKind {
+    Inspector,
+    Minimap,
+    Help,
+    EconomyDashboard,
+    MonetaryDashboard,
+    Census,
+    Finances,
+    Technology,
+    Profiler,
+    DebugOverlay,
+    Console,
+    PauseMenu,
+    MainMenu,
+    /// What a piece of furniture offers
+    Furniture,
+    DiffReport,
+}
+
+/// Where on screen a screen is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    /// Over the view and info lines, hiding the game beneath
+    Overlay,
+    /// In room the layout makes for it beside the view
+    Beside,
+    /// On the footer's status line
+    Footer,
+    /// In the top-left corner of the view, over everything but notices
+    Corner,
+}
+
+/// What a screen made of an input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Handled {
+    /// Not the screen's to act on, so it goes on to the game
+    Ignored,
+    Taken,
+    /// The screen is done and comes off the stack, having changed the
+    /// world if `changed`
+    Closed {
+        changed: bool,
+    },
+    /// A menu option was picked, closing the menu, for the game loop to
+    /// carry out
+    Picked(MenuChoice),
+}
+
+/// What screens read to bring themselves up to date
+pub struct Context<'a> {
+    pub simulation: &'a Simulation,
+    pub glyphs: &'a Glyphs,
+    pub profiler: &'a Profiler,
+}
+
+pub trait Screen {
+    fn kind(&self) -> ScreenKind;
+
+    fn placement(&self) -> Placement {
+        Placement::Overlay
+    }
+
+    /// The input mode the screen takes the keyboard over with, if any
+    fn input_mode(&self) -> Option<InputMode> {
+        None
+    }
+
+    fn handle(&mut self, action: InputAction, simulation: &mut Simulation) -> Handled;
+
+    /// Make room for the screen in the layout
+    fn arrange(&self, layout: Layout) -> Layout {
+        layout
+    }
+
+    /// Catch up with the world before the screen is drawn
+    fn refresh(&mut self, _context: &Context) {}
+
+    fn draw(&self, canvas: &mut dyn Renderer, layout: &Layout);
+}
+
+/// The open screens, most recently opened last
+#[derive(Default)]
+pub struct ScreenStack {
+    screens: Vec<Box<dyn Screen>>,
+}
+
+impl ScreenStack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, screen: Box<dyn Screen>) {
+        self.screens.push(screen);
+    }
+
+    pub fn is_open(&self, kind: ScreenKind) -> bool {
+        self.screens.iter().any(|screen| screen.kind() == kind)
+    }
+
+    /// The input mode of the topmost screen that has one
+    pub fn input_mode(&self) -> Option<InputMode> {
+        self.screens
+            .iter()
+            .rev()
+            .find_map(|screen| screen.input_mode())
+    }
+
+    /// Offer an input to each screen from the top down until one acts on
+    /// it, taking the screen off the stack if that closes it
+    pub fn handle(&mut self, action: InputAction, simulation: &mut Simulation) -> Handled {
+        for index in (0..self.screens.len()).rev() {
+            match self.screens[index].handle(action, simulation) {
+                Handled::Ignored => continue,
+                Handled::Taken => return Handled::Taken,
+                closed @ (Handled::Closed { .. } | Handled::Picked(_)) => {
+                    self.screens.remove(index);
+                    return closed;
+                }
+            }
+        }
+        Handled::Ignored
+    }
+
+    pub fn refresh(&mut self, context: &Context) {
+        for screen in &mut self.screens {
+            screen.refresh(context);
+        }
+    }
+
+    pub fn arrange(&self, layout: Layout) -> Layout {
+        let mut screens: Vec<&dyn Screen> = self.screens.iter().map(AsRef::as_ref).collect();
+        screens.sort_by_key(|screen| screen.kind());
+        screens
+            .into_iter()
+            .fold(layout, |layout, screen| screen.arrange(layout))
+    }
+
+    /// Whether an overlay hides the view
+    pub fn covers_view(&self) -> bool {
+        self.screens
+            .iter()
+            .any(|screen| screen.placement() == Placement::Overlay)
+    }
+
+    /// Draw the screens in one placement: just the topmost overlay, since
+    /// they all fill the same space, or every other screen. Returns whether
+    /// any were drawn.
+    pub fn draw(&self, canvas: &mut dyn Renderer, layout: &Layout, placement: Placement) -> bool {
+        let mut placed = self
+            .screens
+            .iter()
+            .filter(|screen| screen.placement() == placement);
+        if placement == Placement::Overlay {
+            return match placed.next_back() {
+                Some(screen) => {
+                    screen.draw(canvas, layout);
+                    true
+                }
+                None => false,
+            };
+        }
+        let mut drawn = false;
+        for screen in placed {
+            screen.draw(canvas, layout);
+            drawn = true;
+        }
+        drawn
+    }
+}
+
+/// Close on the key that opened the screen, leaving every other key to
+/// the game
+fn close_on(action: InputAction, toggle: InputAction) -> Handled {
+    if action == toggle {
+        Handled::Closed { changed: false }
+    } else {
+        Handled::Ignored
+    }
+}
+
+/// The keys bound in the mode help was opened from, a page at a time
+pub struct Help {
+    mode: InputMode,
+    page: usize,
+    /// Pages the help took up when last drawn
+    pages: std::cell::Cell<usize>,
+}
+
+impl Help {
+    pub fn new(mode: InputMode) -> Self {
+        Self {
+            mode,
+            page: 0,
+            pages: std::cell::Cell::new(1),
+        }
+    }
+}
+
+impl Screen for Help {
+    fn kind(&self) -> ScreenKind {
+        ScreenKind::Help
+    }
+
+    /// Left and right turn the page when there is more than one, leaving
+    /// them to move the cursor otherwise
+    fn handle(&mut self, action: InputAction, _simulation: &mut Simulation) -> Handled {
+        let pages = self.pages.get();
+        match action {
+            InputAction::MoveRight if pages > 1 => {
+                self.page = (self.page + 1).min(pages - 1);
+                Handled::Taken
+            }
+            InputAction::MoveLeft if pages > 1 => {
+                self.page = self.page.saturating_sub(1);
+                Handled::Taken
+            }
+            action => close_on(action, InputAction::ToggleHelp),
+        }
+    }
+
+    fn draw(&self, canvas: &mut dyn Renderer, layout: &Layout) {
+        // The box's borders, title and footer take five rows
+        let pages = help_pages(self.mode, layout.overlay.height.saturating_sub(5) as usize);
+        self.pages.set(pages.len());
+        let page = self.page.min(pages.len() - 1);
+        let footer = if pages.len() > 1 {
+            format!("[←/→] Page {}/{} | [H/?] Close help", page + 1, pages.len())
+        } else {
+            String::from("Press H or ? to close this help")
+        };
+        draw_panel(
+            canvas,
+            layout.overlay,
+            "KEYBOARD CONTROLS",
+            &pages[page],
+            &footer,
+        );
+    }
+}
+
+/// The help for a mode split into pages of at most `rows` lines, each
+/// category headed by its name. A heading never ends a page, so it always
+/// sits above its first key.
+fn help_pages(mode: InputMode, rows: usize) -> Vec<Vec<String>> {
+    let help = InputHandler::help(mode);
+    let key_width = help
+        .iter()
+        .map(|row| row.keys.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let rows = rows.max(2);
+    let mut pages = vec![Vec::new()];
+    let mut category = None;
+    for row in &help {
+        let heading = category != Some(row.category);
+        let needed = if heading { 2 } else { 1 };
+        if pages.last().is_some_and(|page| page.len() + needed > rows) {
+            pages.push(Vec::new());
+        }
+        let page = pages.last_mut().expect("there is always a page");
+        if heading {
+            page.push(row.category.name().to_ascii_uppercase());
+            category = Some(row.category);
+        }
+        page.push(format!("  {:<key_width$}  {}", row.keys, row.description));
+    }
+    pages
+}
+
+/// Trend charts and prices for the place in view
+#[derive(Default)]
+pub struct EconomyDashboard {
+    lines: Vec<Line>,
+}
+
+impl Screen for EconomyDashboard {
+    fn kind(&self) -> ScreenKind {
+        ScreenKind::EconomyDashboard
+    }
+
+    fn handle(&mut self, action: InputAction, _simulation: &mut Simulation) -> Handled {
+        close_on(action, InputAction::ToggleDashboard)
+    }
+
+    fn refresh(&mut self, context: &Context) {
+        let zoom_level = context.simulation.zoom().current_level();
+        self.lines = dashboard_lines(context.simulation, context.glyphs, zoom_level);
+    }
+
+    /// Drawn over the info panel and zoom view
+    fn draw(&self, canvas: &mut dyn Renderer, layout: &Layout) {
+        draw_chart_panel(canvas, layout.overlay, &self.lines, "[D] Close dashboard");
+    }
+}
+
+/// Money supply, bank lending and interest rates across the galaxy
+#[derive(Default)]
+pub struct MonetaryDashboard {
+    lines: Vec<Line>,
+}
+
+impl Screen for MonetaryDashboard {
+    fn kind(&self) -> ScreenKind {
+        ScreenKind::MonetaryDashboard
+    }
+
+    fn handle(&mut self, action: InputAction, _simulation: &mut Simulation) -> Handled {
+        close_on(action, InputAction::ToggleMonetary)
+    }
+
+    fn refresh(&mut self, context: &Context) {
+        self.lines = monetary_lines(context.simulation.world());
+    }
+
+    fn draw(&self, canvas: &mut dyn Renderer, layout: &Layout) {
+        draw_chart_panel(
+            canvas,
+            layout.overlay,
+            &self.lines,
+            "[L] Close monetary dashboard",
+        );
+    }
+}
+
+/// Population, wealth and output across the galaxy
+#[derive(Default)]
+pub struct Census {
+    title: String,
+    lines: Vec<String>,
+}
+
+impl Screen for Census {
+    fn kind(&self) -> ScreenKind {
+        ScreenKind::Census
+    }
+
+    fn handle(&mut self, action: InputAction, _simulation: &mut Simulation) -> Handled {
+        close_on(action, InputAction::ToggleCensus)
+    }
+
+    fn refresh(&mut self, context: &Context) {
+        let world = context.simulation.world();
+        self.title = format!("Census at tick {}", world.tick_count());
+        self.lines = census_lines(world);
+    }
+
+    fn draw(&self, canvas: &mut dyn Renderer, layout: &Layout) {
+        draw_panel(
+            canvas,
+            layout.overlay,
+            &self.title,
+            &self.lines,
+            "[C] Close census  Made counts goods produced last tick",
+        );
+    }
+}
+
+/// Every polity's budget and what its spending has built
+#[derive(Default)]
+pub struct Finances {
+    lines: Vec<String>,
+}
+
+impl Screen for Finances {
+    fn kind(&self) -> ScreenKind {
+        ScreenKind::Finances
+    }
+
+    fn handle(&mut self, action: InputAction, _simulation: &mut Simulation) -> Handled {
+        close_on(action, InputAction::ToggleFinances)
+    }
+
+    fn refresh(&mut self, context: &Context) {
+        self.lines = finance_lines(context.simulation.world());
+    }
+
+    fn draw(&self, canvas: &mut dyn Renderer, layout: &Layout) {
+        draw_panel(
+            canvas,
+            layout.overlay,
+            "Polity finances",
+            &self.lines,
+            "[G] Close finances  Boost is extra output from infrastructure",
+        );
+    }
+}
+
+/// What each polity knows and is researching, and the whole tree
+#[derive(Default)]
+pub struct Technology {
+    lines: Vec<String>,
+}
+
+impl Screen for Technology {
+    fn kind(&self) -> ScreenKind {
+        ScreenKind::Technology
+    }
+
+    fn handle(&mut self, action: InputAction, _simulation: &mut Simulation) -> Handled {
+        close_on(action, InputAction::ToggleTechnology)
+    }
+
+    fn refresh(&mut self, context: &Context) {
+        self.lines = technology_lines(context.simulation.world());
+    }
+
+    fn draw(&self, canvas: &mut dyn Renderer, layout: &Layout) {
+        draw_panel(
+            canvas,
+            layout.overlay,
+            "Technology",
+            &self.lines,
+            "[K] Close technology  Polities research faster with more markets",
+        );
+    }
+}
+
+/// Frame and tick timings
+#[derive(Default)]
+pub struct ProfilerReport {
+    lines: Vec<String>,
+}
+
+impl Screen for ProfilerReport {
+    fn kind(&self) -> ScreenKind {
+        ScreenKind::Profiler
+    }
+
+    fn handle(&mut self, action: InputAction, _simulation: &mut Simulation) -> Handled {
+        close_on(action, InputAction::ToggleProfiler)
+    }
+
+    fn refresh(&mut self, context: &Context) {
+        self.lines = context.profiler.report();
+    }
+
+    fn draw(&self, canvas: &mut dyn Renderer, layout: &Layout) {
+        draw_panel(
+            canvas,
+            layout.overlay,
+            "Profiler",
+            &self.lines,
+            "[R] Close profiler  Times cover this session",
+        );
+    }
+}
+
+/// The watches pinned from the console, then where the latest frame's
+/// time went, how many ticks it ran, how many entities there are and how
+/// much memory the game holds
+#[derive(Default)]
+pub struct DebugOverlay {
+    lines: Vec<String>,
+}
+
+impl Screen for DebugOverlay {
+    fn kind(&self) -> ScreenKind {
+        ScreenKind::DebugOverlay
+    }
+
+    fn placement(&self) -> Placement {
+        Placement::Corner
+    }
+
+    fn handle(&mut self, action: InputAction, _simulation: &mut Simulation) -> Handled {
+        close_on(action, InputAction::ToggleDebugOverlay)
+    }
+
+    fn refresh(&mut self, context: &Context) {
+        // Watches first, as the ones the player asked for
+        self.lines = watch_lines(context.simulation.watches());
+        self.lines
+            .extend(debug_lines(context.simulation.world(), context.profiler));
+    }
+
+    /// The lines in a box, over whatever is beneath them
+    fn draw(&self, canvas: &mut dyn Renderer, layout: &Layout) {
+        let area = layout.view;
+        let width = self
+            .lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(area.width.saturating_sub(2) as usize);
+        let rows = self.lines.len().min(area.height.saturating_sub(2) as usize);
+        canvas.draw_box(area.x, area.y, width as u16 + 2, rows as u16 + 2);
+        canvas.draw_text(area.x + 2, area.y, " Debug ");
+        for (i, line) in self.lines.iter().take(rows).enumerate() {
+            let line: String = format!("{:<width$}", line).chars().take(width).collect();
+            canvas.draw_text(area.x + 1, area.y + 1 + i as u16, &line);
+        }
+    }
+}
+
+/// The debug overlay: the latest frame's stages against the average of
+/// the recent ones, then the world's size
+fn debug_lines(world: &WorldState, profiler: &Profiler) -> Vec<String> {
+    const LEVELS: [(ZoomLevel, &str); 5] = [
+        (ZoomLevel::SolarSystem, "Systems"),
+        (ZoomLevel::Planet, "Planets"),
+        (ZoomLevel::Region, "Regions"),
+        (ZoomLevel::LocalArea, "Areas"),
+        (ZoomLevel::Room, "Rooms"),
+    ];
+    let last = profiler.last_frame().unwrap_or_default();
+    let recent = profiler.recent_frames();
+    let stage = |name: &str, last: Duration, recent: Duration| {
+        format!("{:<7}{:>9} {:>9}", name, millis(last), millis(recent))
+    };
+    let economy = world.economy();
+    let memory = match resident_memory() {
+        Some(bytes) => format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0)),
+        None => String::from("unknown"),
+    };
+
+    let mut lines = vec![
+        format!("{:<7}{:>9} {:>9}", "", "last", "average"),
+        stage("Input", last.input, recent.input),
+        stage("Update", last.update, recent.update),
+        stage("Render", last.render, recent.render),
+        stage("Apply", last.present, recent.present),
+        stage("Frame", last.total(), recent.total()),
+        format!(
+            "Ticks   {} this frame, {} in {}",
+            last.ticks,
+            recent.ticks,
+            profiler.recent_len()
+        ),
+        format!("Entities {}", world.entity_count()),
+    ];
+    lines.extend(
+        LEVELS
+            .iter()
+            .map(|&(level, name)| format!("  {:<8}{:>8}", name, world.ids(level).len())),
+    );
+    lines.push(format!(
+        "Firms {}  Markets {}",
+        economy.firms().count(),
+        economy.markets().count()
+    ));
+    lines.push(format!(
+        "Characters {}  Ships {}",
+        world.characters().count(),
+        world.ships().count()
+    ));
+    lines.push(format!("Memory {}", memory));
+    lines
+}
+
+/// The watches pinned from the console, numbered as `unwatch` takes them
+fn watch_lines(watches: &Watches) -> Vec<String> {
+    if watches.is_empty() {
+        return Vec::new();
+    }
+    let mut lines = vec![format!("Watches, every {} ticks", watches.interval())];
+    lines.extend(
+        watches
+            .iter()
+            .enumerate()
+            .map(|(i, watch)| format!("{} {} = {}", i + 1, watch.label(), watch.reading())),
+    );
+    lines
+}
+
+/// Details of the place in view, the player's business and the
+/// watchlist, in a sidebar
+#[derive(Default)]
+pub struct Inspector {
+    lines: Vec<Line>,
+}
+
+impl Screen for Inspector {
+    fn kind(&self) -> ScreenKind {
+        ScreenKind::Inspector
+    }
+
+    fn placement(&self) -> Placement {
+        Placement::Beside
+    }
+
+    fn handle(&mut self, action: InputAction, _simulation: &mut Simulation) -> Handled {
+        close_on(action, InputAction::ToggleSidebar)
+    }
+
+    fn arrange(&self, layout: Layout) -> Layout {
+        layout.with_sidebar()
+    }
+
+    fn refresh(&mut self, context: &Context) {
+        let zoom_level = context.simulation.zoom().current_level();
+        self.lines = sidebar_lines(context.simulation, zoom_level);
+    }
+
+    /// Down the right edge, behind a divider
+    fn draw(&self, canvas: &mut dyn Renderer, layout: &Layout) {
+        let Some(area) = layout.sidebar else {
+            return;
+        };
+        for row in 0..area.height {
+            canvas.draw_text(area.x, area.y + row, "│");
+        }
+        let width = area.width.saturating_sub(2) as usize;
+        for (i, line) in self.lines.iter().take(area.height as usize).enumerate() {
+            let line = line.clone().truncated(width);
+            canvas.draw_line(area.x + 2, area.y + i as u16, &line);
+        }
+    }
+}
+
+/// An overview of the map in view
+#[derive(Default)]
+pub struct Minimap {
+    rows: Vec<String>,
+}
+
+impl Screen for Minimap {
+    fn kind(&self) -> ScreenKind {
+        ScreenKind::Minimap
+    }
+
+    fn placement(&self) -> Placement {
+        Placement::Beside
+    }
+
+    fn handle(&mut self, action: InputAction, _simulation: &mut Simulation) -> Handled {
+        close_on(action, InputAction::ToggleMinimap)
+    }
+
+    fn arrange(&self, layout: Layout) -> Layout {
+        layout.with_minimap()
+    }
+
+    fn refresh(&mut self, context: &Context) {
+        let zoom_level = context.simulation.zoom().current_level();
+        self.rows = minimap_lines(context.simulation, context.glyphs, zoom_level);
+    }
+
+    /// The rows inside a titled box
+    fn draw(&self, canvas: &mut dyn Renderer, layout: &Layout) {
+        let Some(area) = layout.minimap else {
+            return;
+        };
+        canvas.draw_box(area.x, area.y, area.width, area.height);
+        canvas.draw_text(area.x + 2, area.y, " Map ");
+        for (i, row) in self.rows.iter().enumerate() {
+            canvas.draw_text(area.x + 1, area.y + 1 + i as u16, row);
+        }
+    }
+}
+
+/// A command line on the footer, run against the simulation on Enter.
+/// Tab completes the word being typed, and the up and down arrows step
+/// through the commands entered before.
+#[derive(Default)]
+pub struct Console {
+    input: TextInput,
+    history: History,
+    /// The history entry shown, counting back from the newest
+    recalled: Option<usize>,
+    /// What was typed before stepping back through the history
+    draft: String,
+    /// Completions left to choose between after the last Tab
+    options: Vec<String>,
+}
+
+impl Console {
+    pub fn new(history: History) -> Self {
+        Self {
+            history,
+            ..Self::default()
+        }
+    }
+
+    /// Show the history entry `back` places before the newest, or what was
+    /// being typed for None
+    fn recall(&mut self, back: Option<usize>) {
+        if self.recalled.is_none() {
+            self.draft = self.input.text().to_string();
+        }
+        let text = match back {
+            Some(back) => match self.history.recall(back) {
+                Some(entry) => entry.to_string(),
+                None => return,
+            },
+            None => std::mem::take(&mut self.draft),
+        };
+        self.recalled = back;
+        self.input = TextInput::with_text(&text);
+    }
+}
+
+impl Screen for Console {
+    fn kind(&self) -> ScreenKind {
+        ScreenKind::Console
+    }
+
+    fn placement(&self) -> Placement {
+        Placement::Footer
+    }
+
+    fn input_mode(&self) -> Option<InputMode> {
+        Some(InputMode::Console)
+    }
+
+    fn handle(&mut self, action: InputAction, simulation: &mut Simulation) -> Handled {
+        match action {
+            InputAction::Edit(edit) => {
+                self.input.apply(edit);
+                self.options.clear();
+                Handled::Taken
+            }
+            InputAction::Complete => {
+                let completion = commands::complete(self.input.text(), simulation);
+                self.input = TextInput::with_text(&completion.line);
+                self.options = completion.options;
+                Handled::Taken
+            }
+            InputAction::MoveUp => {
+                self.recall(Some(self.recalled.map_or(0, |back| back + 1)));
+                Handled::Taken
+            }
+            InputAction::MoveDown => {
+                if let Some(back) = self.recalled {
+                    self.recall(back.checked_sub(1));
+                }
+                Handled::Taken
+            }
+            InputAction::SubmitCommand => {
+                let line = self.input.take();
+                if line.trim().is_empty() {
+                    return Handled::Closed { changed: false };
+                }
+
+                self.history.push(&line);
+                // A diff is shown as a report to read through, rather than
+                // only summed up in the log
+                let words: Vec<&str> = line.split_whitespace().collect();
+                if let Some((command, args)) = words.split_first()
+                    && command.eq_ignore_ascii_case("diff")
+                    && let Ok(diff) = commands::diff(args, simulation)
+                {
+                    let world = simulation.world_mut();
+                    world.log(format!("> {}", line.trim()));
+                    world.log(diff.summary());
+                    return Handled::Picked(MenuChoice::ShowDiff(diff));
+                }
+
+                let outcome = commands::execute(&line, simulation);
+                let world = simulation.world_mut();
+                world.log(format!("> {}", line.trim()));
+                match outcome {
+                    Ok(message) => world.log(message),
+                    Err(error) => world.log(format!("Error: {}", error)),
+                }
+                Handled::Closed { changed: true }
+            }
+            InputAction::Cancel => Handled::Closed { changed: false },
+            _ => Handled::Ignored,
+        }
+    }
+
+    fn draw(&self, canvas: &mut dyn Renderer, layout: &Layout) {
+        let status_y = layout.footer.y + 1;
+        let mut line = format!(":{}", self.input.with_cursor());
+        if !self.options.is_empty() {
+            line = format!("{}   {}", line, self.options.join("  "));
+        }
+        let width = layout.footer.width.saturating_sub(4) as usize;
+        canvas.draw_text(2, status_y, &line.chars().take(width).collect::<String>());
+    }
+}
+
+/// What changed between two moments, one section at a time. Enter jumps
+/// to the place the selected change concerns.
+pub struct DiffReport {
+    diff: WorldDiff,
+    section: usize,
+    selected: usize,
+    /// Changes that fitted on the screen when last drawn
+    rows: std::cell::Cell<usize>,
+}
+
+impl DiffReport {
+    pub fn new(diff: WorldDiff) -> Self {
+        Self {
+            diff,
+            section: 0,
+            selected: 0,
+            rows: std::cell::Cell::new(1),
+        }
+    }
+
+    fn changes(&self) -> &[Change] {
+        self.diff
+            .sections
+            .get(self.section)
+            .map_or(&[], |section| section.changes.as_slice())
+    }
+}
+
+impl Screen for DiffReport {
+    fn kind(&self) -> ScreenKind {
+        ScreenKind::DiffReport
+    }
+
+    fn input_mode(&self) -> Option<InputMode> {
+        Some(InputMode::Menu)
+    }
+
+    fn handle(&mut self, action: InputAction, simulation: &mut Simulation) -> Handled {
+        let last = self.changes().len().saturating_sub(1);
+        match action {
+            InputAction::MoveUp => self.selected = self.selected.saturating_sub(1),
+            InputAction::MoveDown => self.selected = (self.selected + 1).min(last),
+            InputAction::Choose(number) if usize::from(number) <= self.diff.sections.len() => {
+                self.section = usize::from(number) - 1;
+                self.selected = 0;
+            }
+            InputAction::Confirm => {
+                let Some(&(level, id)) = self
+                    .changes()
+                    .get(self.selected)
+                    .and_then(|change| change.place.as_ref())
+                else {
+                    return Handled::Taken;
+                };
+                simulation.jump_to(level, id);
+                return Handled::Closed { changed: false };
+            }
+            InputAction::Cancel => return Handled::Closed { changed: false },
+            _ => {}
+        }
+        Handled::Taken
+    }
+
+    /// The sections' names, then a window of the chosen one's changes
+    /// that keeps the selected one in view
+    fn draw(&self, canvas: &mut dyn Renderer, layout: &Layout) {
+        const FOOTER: &str = "[1-3] Section  [UP/DOWN] Choose  [ENTER] Go there  [ESC] Close";
+        let area = layout.overlay;
+        // The box's borders, title, footer and the section names take
+        // seven rows
+        let rows = (area.height as usize).saturating_sub(7).max(1);
+        self.rows.set(rows);
+        let tabs: Vec<String> = self
+            .diff
+            .sections
+            .iter()
+            .enumerate()
+            .map(|(i, section)| {
+                let label = format!("{} {} ({})", i + 1, section.title, section.changes.len());
+                if i == self.section {
+                    format!("[{}]", label)
+                } else {
+                    format!(" {} ", label)
+                }
+            })
+            .collect();
+
+        let changes = self.changes();
+        let first = (self.selected + 1).saturating_sub(rows);
+        let mut lines = vec![tabs.join(" "), String::new()];
+        if changes.is_empty() {
+            lines.push(String::from("  No changes"));
+        }
+        lines.extend(
+            changes
+                .iter()
+                .enumerate()
+                .skip(first)
+                .take(rows)
+                .map(|(i, change)| {
+                    let marker = if i == self.selected { '>' } else { ' ' };
+                    format!("{} {}", marker, change.text)
+                }),
+        );
+        let width = (area.width as usize).saturating_sub(4);
+        let fit = |line: &str| line.chars().take(width).collect::<String>();
+        let lines: Vec<String> = lines.iter().map(|line| fit(line)).collect();
+        draw_panel(canvas, area, &fit(&self.diff.title()), &lines, &fit(FOOTER));
+    }
+}
+
+/// Trend charts for the place in view, followed by its current prices.
+/// Rooms report on the area they are in.
+fn dashboard_lines(simulation: &Simulation, glyphs: &Glyphs, zoom_level: ZoomLevel) -> Vec<Line> {
+    let world = simulation.world();
+    let position = simulation.zoom().position();
+    let (level, id) = match zoom_level {
+        ZoomLevel::Galaxy => (ZoomLevel::Galaxy, GALAXY_ID),
+        ZoomLevel::Room => (
+            ZoomLevel::LocalArea,
+            position.map_owner(ZoomLevel::LocalArea).unwrap_or(1),
+        ),
+        level => (level, position.map_owner(level).unwrap_or(1)),
+    };
+    let economy = world.economy();
+    let currency = economy.currency();
+
+    let mut lines = vec![Line::plain(format!(
+        "{} ({})",
+        world.location_name(position, level),
+        level
+    ))];
+    let chart = |label: &str, series: &[f64], value: String| {
+        Line::plain(format!(
+            "{:<11}{:<width$} ",
+            label,
+            charts::sparkline(series, DASHBOARD_CHART_WIDTH),
+            width = DASHBOARD_CHART_WIDTH
+        ))
+        .value(value, Trend::of(series))
+    };
+    match world.stats().trends(level, id) {
+        Some(trends) => {
+            let prices: Vec<f64> = trends.prices.iter().copied().collect();
+            let gdp: Vec<f64> = trends.gdp.iter().copied().collect();
+            let population: Vec<f64> = trends.population.iter().copied().collect();
+            let harvest: Vec<f64> = trends.harvest.iter().copied().collect();
+            let last = |series: &[f64]| series.last().copied().unwrap_or_default();
+            lines.push(chart("Prices", &prices, format!("{:.2}x", last(&prices))));
+            if !harvest.is_empty() {
+                lines.push(chart(
+                    "Harvest",
+                    &harvest,
+                    format!("{:.2}x", last(&harvest)),
+                ));
+            }
+            lines.push(chart("GDP/day", &gdp, currency.format(Money(last(&gdp)))));
+            lines.push(chart(
+                "Population",
+                &population,
+                charts::compact(last(&population)),
+            ));
+        }
+        None => lines.push(Line::plain("No history yet; figures are recorded daily")),
+    }
+
+    // What is left of a region's deposits
+    if level == ZoomLevel::Region
+        && let Some(deposits) = world.get_region(id).and_then(|r| r.deposits.as_ref())
+    {
+        lines.push(Line::default());
+        if deposits.is_empty() {
+            lines.push(Line::plain("No deposits worth working"));
+        }
+        for deposit in deposits {
+            let line = Line::plain(format!(
+                "{:<13}{} ",
+                deposit.kind,
+                charts::bar(deposit.remaining(), 1.0, DASHBOARD_BAR_WIDTH)
+            ));
+            let trend = if deposit.is_thinning() {
+                Trend::Falling
+            } else {
+                Trend::Steady
+            };
+            let left = format!("{:.0}% left", deposit.remaining() * 100.0);
+            lines.push(line.value(left, trend));
+        }
+    }
+
+    // Current prices averaged over every market in the place
+    let markets: Vec<_> = economy
+        .markets()
+        .filter(|&(area_id, _)| {
+            world
+                .ancestry(ZoomLevel::LocalArea, area_id)
+                .contains(&(level, id))
+        })
+        .map(|(_, market)| market)
+        .collect();
+    if markets.is_empty() {
+        return lines;
+    }
+    let prices: Vec<(String, f64)> = economy
+        .catalog()
+        .iter()
+        .map(|(good, info)| {
+            let total: f64 = markets.iter().map(|m| m.price(good).amount()).sum();
+            let label = format!("{} {}", glyphs.category(info.category), info.name);
+            (label, total / markets.len() as f64)
+        })
+        .collect();
+    let highest = prices.iter().map(|&(_, p)| p).fold(0.0, f64::max);
+
+    // A single market charts each good's recent prices instead, beside
+    // the best of its book and the units on the first levels of each side
+    if level == ZoomLevel::LocalArea {
+        let history = economy.history();
+        let market = markets[0];
+        let quote =
+            |price: Option<Money>| price.map_or_else(|| String::from("-"), |p| currency.format(p));
+        lines.push(Line::default());
+        lines.push(Line::plain(format!(
+            "{:<width$}{:<8}{:<8}{:<8}Price",
+            "Price history",
+            "Bid",
+            "Ask",
+            "Depth",
+            width = 12 + DASHBOARD_CHART_WIDTH
+        )));
+        for ((good, info), (name, price)) in economy.catalog().iter().zip(prices) {
+            let series = history
+                .series(id, good)
+                .map(PriceSeries::prices)
+                .unwrap_or_default();
+            let depth = market.depth(good, info, DASHBOARD_BOOK_LEVELS);
+            let line = Line::plain(format!(
+                "{:<11}{:<width$} {:<8}{:<8}{:<8}",
+                name,
+                charts::sparkline(&series, DASHBOARD_CHART_WIDTH),
+                quote(depth.best_bid()),
+                quote(depth.best_ask()),
+                format!("{:.0}/{:.0}", depth.bid_depth(), depth.ask_depth()),
+                width = DASHBOARD_CHART_WIDTH
+            ));
+            lines.push(line.value(currency.format(Money(price)), Trend::of(&series)));
+        }
+        return lines;
+    }
+
+    lines.push(Line::default());
+    lines.push(Line::plain(format!(
+        "Prices across {} market(s)",
+        markets.len()
+    )));
+    for (name, price) in prices {
+        lines.push(Line::plain(format!(
+            "{:<11}{} {}",
+            name,
+            charts::bar(price, highest, DASHBOARD_BAR_WIDTH),
+            currency.format(Money(price))
+        )));
+    }
+    lines
+}
+
+/// Galaxy totals, then each system, planet and region indented under
+/// its parent
+fn monetary_lines(world: &WorldState) -> Vec<Line> {
+    let economy = world.economy();
+    let currency = economy.currency();
+    let bank = economy.bank();
+    let supply = economy.money_supply();
+
+    let mut lines = vec![Line::plain("Money and banking")];
+    let chart = |label: &str, series: &[f64]| {
+        let last = series.last().copied().unwrap_or_default();
+        Line::plain(format!(
+            "{:<11}{:<width$} ",
+            label,
+            charts::sparkline(series, DASHBOARD_CHART_WIDTH),
+            width = DASHBOARD_CHART_WIDTH
+        ))
+        .value(currency.format(Money(last)), Trend::of(series))
+    };
+    let series = |figure: fn(&BankSample) -> Money| -> Vec<f64> {
+        bank.history().map(|day| figure(day).amount()).collect()
+    };
+    if bank.history().next().is_some() {
+        lines.push(chart("Money", &series(|day| day.money_supply)));
+        lines.push(chart("Deposits", &series(|day| day.deposits)));
+        lines.push(chart("Loans", &series(|day| day.loans)));
+        lines.push(chart("Lent/day", &series(|day| day.lent)));
+        lines.push(chart("Defaults", &series(|day| day.written_off)));
+    } else {
+        lines.push(Line::plain("No history yet; figures are recorded daily"));
+    }
+
+    lines.push(Line::default());
+    lines.push(Line::plain(format!(
+        "Money supply {} growing {:+.1}%/yr, prices {:.2}x",
+        currency.format(supply.issued()),
+        supply.growth_rate() * 100.0,
+        supply.price_level()
+    )));
+    lines.push(Line::plain(format!(
+        "Policy rate {:.1}%  Lending {:.1}%  Deposits {:.1}%",
+        bank.policy_rate() * 100.0,
+        bank.lending_rate() * 100.0,
+        bank.deposit_rate() * 100.0
+    )));
+    lines.push(Line::plain(format!(
+        "Reserves {}  Owed to savers {}  Lent out {}",
+        currency.format(bank.reserves()),
+        currency.format(bank.deposits()),
+        currency.format(bank.loans())
+    )));
+    lines
+}
+
+fn finance_lines(world: &WorldState) -> Vec<String> {
+    let currency = world.economy().currency();
+    let mut lines = vec![format!(
+        "{:<24} {:>9} {:>9} {:>9}",
+        "", "Budget", "Collected", "Spent"
+    )];
+    for polity in world.polities() {
+        lines.push(format!(
+            "{:<24.24} {:>9} {:>9} {:>9}",
+            polity.name,
+            currency.format(world.economy().treasury(polity.id)),
+            currency.format(polity.collected),
+            currency.format(polity.spent),
+        ));
+        lines.push(format!("  {}", polity.policy_summary()));
+
+        let mut regions: Vec<EntityId> = polity
+            .planets
+            .iter()
+            .flat_map(|&planet| world.placed_in(ZoomLevel::Region, Some(planet)))
+            .map(|(region, _)| region)
+            .chain(polity.regions.iter().copied())
+            .collect();
+        regions.sort_unstable();
+        for &region in regions.iter().take(FINANCE_REGIONS) {
+            let built = world
+                .get_region(region)
+                .map_or(Money::ZERO, |region| region.infrastructure);
+            lines.push(format!(
+                "  {:<22.22} {:>9} boost {:+.0}%",
+                world.entity_name(ZoomLevel::Region, region),
+                currency.format(built),
+                (polities::infrastructure_boost(built) - 1.0) * 100.0
+            ));
+        }
+        if regions.len() > FINANCE_REGIONS {
+            lines.push(format!(
+                "  ...and {} more regions",
+                regions.len() - FINANCE_REGIONS
+            ));
+        }
+    }
+    if lines.len() == 1 {
+        lines.push(String::from("No polity governs any of the galaxy"));
+    }
+    lines
+}
+
+fn technology_lines(world: &WorldState) -> Vec<String> {
+    let economy = world.economy();
+    let techs = economy.techs();
+    let name = |id| techs.get(id).map_or("?", |tech| tech.name.as_str());
+    let mut lines = Vec::new();
+    for polity in world.polities() {
+        let research = &polity.research;
+        lines.push(match research.current(techs) {
+            Some((tech, done)) => format!(
+                "{:<24.24} {:<18.18} {} {:>3.0}%",
+                polity.name,
+                name(tech),
+                charts::bar(done, 1.0, RESEARCH_BAR_WIDTH),
+                done * 100.0
+            ),
+            None => format!("{:<24.24} nothing left to research", polity.name),
+        });
+        let known: Vec<&str> = research.known().map(name).collect();
+        if !known.is_empty() {
+            lines.push(format!("  Knows {}", known.join(", ")));
+        }
+    }
+    if lines.is_empty() {
+        lines.push(String::from("No polity is researching anything"));
+    }
+
+    lines.push(String::new());
+    lines.push(format!(
+        "{:<16} {:>4}  {:<14}  Effect",
+        "Technology", "Days", "Needs"
+    ));
+    for (_, tech) in techs.iter() {
+        let needs: Vec<&str> = tech.requires.iter().map(|&id| name(id)).collect();
+        let boosts: Vec<String> = tech
+            .boosts
+            .iter()
+            .map(|(building, share)| format!("{} +{:.0}%", building, share * 100.0))
+            .collect();
+        lines.push(format!(
+            "{:<16.16} {:>4.0}  {:<14.14}  {}",
+            tech.name,
+            tech.days,
+            needs.join(", "),
+            boosts.join(", ")
+        ));
+        for recipe in tech
+            .unlocks
+            .iter()
+            .filter_map(|&id| economy.recipes().get(id))
+        {
+            lines.push(format!("{:<39}unlocks {}", "", recipe.name));
+        }
+    }
+    lines
+}
+
+fn census_lines(world: &WorldState) -> Vec<String> {
+    let census = world.census();
+    let currency = world.economy().currency();
+    let row = |name: String, figures: Figures| {
+        format!(
+            "{:<24.24} {:>8} {:>9} {:>8} {:>6}",
+            name,
+            charts::compact(figures.population as f64),
+            currency.format(figures.wealth),
+            charts::compact(figures.produced),
+            figures
+                .price_index
+                .map_or(String::from("-"), |index| format!("{:.2}x", index)),
+        )
+    };
+
+    let mut places = Vec::new();
+    let mut stack: Vec<(ZoomLevel, EntityId, usize)> = world
+        .ids(ZoomLevel::SolarSystem)
+        .into_iter()
+        .rev()
+        .map(|id| (ZoomLevel::SolarSystem, id, 0))
+        .collect();
+    while let Some((level, id, depth)) = stack.pop() {
+        places.push((level, id, depth));
+        if let Some(child) = level.zoom_in().filter(|&l| l >= ZoomLevel::Region) {
+            let mut children: Vec<EntityId> = world
+                .placed_in(child, Some(id))
+                .into_iter()
+                .map(|(child_id, _)| child_id)
+                .collect();
+            children.sort_unstable();
+            stack.extend(children.into_iter().rev().map(|c| (child, c, depth + 1)));
+        }
+    }
+
+    let mut lines = vec![
+        format!(
+            "{:<24} {:>8} {:>9} {:>8} {:>6}",
+            "", "People", "Wealth", "Made", "Prices"
+        ),
+        row(String::from("Galaxy"), census.totals()),
+    ];
+    for &(level, id, depth) in places.iter().take(CENSUS_ROWS) {
+        let name = format!("{}{}", "  ".repeat(depth + 1), world.entity_name(level, id));
+        lines.push(row(name, census.figures(level, id).unwrap_or_default()));
+    }
+    if places.len() > CENSUS_ROWS {
+        lines.push(format!("  ...and {} more", places.len() - CENSUS_ROWS));
+    }
+    lines
+}
+
+/// The map in view around the player, hiding what hasn't been explored
+fn minimap_lines(simulation: &Simulation, glyphs: &Glyphs, zoom_level: ZoomLevel) -> Vec<String> {
+    let world = simulation.world();
+    let position = simulation.zoom().position();
+    let owner = position.map_owner(zoom_level);
+    let child_level = zoom_level.zoom_in();
+    minimap::minimap_rows(position.coords_for_level(zoom_level), glyphs, |coords| {
+        if !world.is_explored(zoom_level, owner, coords) {
+            return Cell::Unexplored;
+        }
+        if zoom_level == ZoomLevel::LocalArea
+            && let Some(firm) = owner.and_then(|area| world.economy().firm_at(area, coords))
+        {
+            let kind = world
+                .economy()
+                .recipes()
+                .get(firm.recipe)
+                .map(|r| r.building);
+            return Cell::Occupied(
+                kind.map_or(glyphs.get("building"), |kind| glyphs.building(kind)),
+            );
+        }
+        if zoom_level == ZoomLevel::LocalArea
+            && let Some(site) = owner.and_then(|area| world.economy().site_at(area, coords))
+        {
+            return Cell::Occupied(glyphs.site(site.progress()));
+        }
+        let Some(level) = child_level else {
+            return Cell::Empty;
+        };
+        match world.entity_at(level, owner, coords) {
+            Some(id) => Cell::Occupied(match world.get_region(id) {
+                Some(region) if level == ZoomLevel::Region => glyphs.terrain(&region.terrain_type),
+                _ => glyphs.level(level),
+            }),
+            None => Cell::Empty,
+        }
+    })
+}
+
+/// Details of the entity in view, the player's purse and the profit of
+/// each firm they own, then a line per pinned entity
+fn sidebar_lines(simulation: &Simulation, zoom_level: ZoomLevel) -> Vec<Line> {
+    let world = simulation.world();
+    let id = simulation
+        .zoom()
+        .position()
+        .map_owner(zoom_level)
+        .unwrap_or(GALAXY_ID);
+    let price_trend = |level: ZoomLevel, id: EntityId| {
+        let prices: Vec<f64> = world
+            .stats()
+            .trends(level, id)
+            .map(|trends| trends.prices.iter().copied().collect())
+            .unwrap_or_default();
+        let last = prices.last().copied()?;
+        Some(Line::default().value(format!("{:.2}x", last), Trend::of(&prices)))
+    };
+
+    let mut lines = vec![
+        Line::plain("Inspector"),
+        Line::plain(world.entity_name(zoom_level, id)),
+        Line::plain(format!("  {}", zoom_level)),
+    ];
+    if let Some(polity) = world.polity_of(zoom_level, id) {
+        lines.push(Line::plain(format!("  Held by {}", polity.name)));
+        lines.push(Line::plain(format!("  {}", polity.policy_summary())));
+    }
+    let region = match zoom_level {
+        ZoomLevel::Region => Some(id),
+        ZoomLevel::LocalArea => world
+            .placement_of(zoom_level, id)
+            .and_then(|area| area.parent),
+        _ => None,
+    };
+    if let Some(region) = region {
+        let mut climate = world.season().name().to_string();
+        climate[..1].make_ascii_uppercase();
+        if let Some(weather) = world.weather(region) {
+            let left = weather.days_left(world.elapsed());
+            climate.push_str(&format!(", {} ({}d left)", weather.kind, left));
+        }
+        lines.push(Line::plain(format!("  {}", climate)));
+    }
+    if let Some(population) = world.population(zoom_level, id) {
+        lines.push(Line::plain(format!(
+            "  Population {}",
+            charts::compact(population as f64)
+        )));
+    }
+    let areas: Vec<EntityId> = world
+        .ids(ZoomLevel::LocalArea)
+        .into_iter()
+        .filter(|&area| {
+            world
+                .ancestry(ZoomLevel::LocalArea, area)
+                .contains(&(zoom_level, id))
+        })
+        .collect();
+    let firms: usize = areas
+        .iter()
+        .map(|&area| world.economy().firms_in_area(area).count())
+        .sum();
+    if firms > 0 {
+        lines.push(Line::plain(format!("  Firms {}", firms)));
+    }
+    let sites: usize = areas
+        .iter()
+        .map(|&area| world.economy().sites_in_area(area).count())
+        .sum();
+    if sites > 0 {
+        lines.push(Line::plain(format!("  Going up {}", sites)));
+    }
+    if let Some(trend) = price_trend(zoom_level, id) {
+        lines.push(Line::plain("  Prices ").append(trend));
+    }
+    let people = match zoom_level {
+        ZoomLevel::LocalArea => world.characters_in_area(id).count(),
+        ZoomLevel::Room => world.characters_in_room(id).count(),
+        _ => 0,
+    };
+    if people > 0 {
+        lines.push(Line::plain(format!("  People {}", people)));
+    }
+    let contracts = world
+        .contracts()
+        .filter(|contract| {
+            zoom_level == ZoomLevel::LocalArea
+                && [contract.terms.supplier, contract.terms.buyer]
+                    .iter()
+                    .any(|&firm| world.economy().firm(firm).is_some_and(|f| f.area_id == id))
+        })
+        .count();
+    if contracts > 0 {
+        lines.push(Line::plain(format!("  Contracts {}", contracts)));
+    }
+    let in_port = world
+        .ships()
+        .filter(|ship| zoom_level == ZoomLevel::Planet && ship.docked_at() == Some(id))
+        .count();
+    if in_port > 0 {
+        lines.push(Line::plain(format!("  Ships in port {}", in_port)));
+    }
+    if cfg!(debug_assertions) {
+        lines.push(Line::plain("  [A] edits live"));
+    }
+
+    let economy = world.economy();
+    lines.push(Line::default());
+    lines.push(Line::plain("Business"));
+    lines.push(Line::plain(format!(
+        "  Purse {}",
+        economy.currency().format(economy.purse())
+    )));
+    if world.business().holdings().is_empty() {
+        lines.push(Line::plain("  [F] founds a firm here"));
+    }
+    for holding in world.business().holdings() {
+        let Some(firm) = economy.firm(holding.firm) else {
+            continue;
+        };
+        let name: String = firm.name.chars().take(14).collect();
+        let profit = holding.profit(economy).unwrap_or_default();
+        let trend = match profit.amount() {
+            p if p > 0.0 => Trend::Rising,
+            p if p < 0.0 => Trend::Falling,
+            _ => Trend::Steady,
+        };
+        lines.push(
+            Line::plain(format!("  {:<14} ", name)).value(economy.currency().format(profit), trend),
+        );
+    }
+    for site in economy
+        .sites()
+        .filter(|site| site.builder == Builder::Player)
+    {
+        let name: String = site.name.chars().take(14).collect();
+        lines.push(Line::plain(format!(
+            "  {:<14} {:.0}% built",
+            name,
+            site.progress() * 100.0
+        )));
+    }
+
+    lines.push(Line::default());
+    lines.push(Line::plain("Watchlist"));
+    if world.watchlist().is_empty() {
+        lines.push(Line::plain("  [P] pins what's in view"));
+    }
+    for &(level, id) in world.watchlist() {
+        let name: String = world.entity_name(level, id).chars().take(14).collect();
+        let line = Line::plain(format!("  {:<14} ", name));
+        lines.push(match price_trend(level, id) {
+            Some(trend) => line.append(trend),
+            None => line,
+        });
+    }
+    lines
+}
+
+/// A panel of styled lines, the first of them its title
+fn draw_chart_panel(canvas: &mut dyn Renderer, area: Rect, lines: &[Line], footer: &str) {
+    let (x, y) = (area.x, area.y);
+    let width = lines
+        .iter()
+        .map(Line::width)
+        .chain([footer.len()])
+        .max()
+        .unwrap_or(0);
+    let border = "═".repeat(width + 2);
+    let Some((title, body)) = lines.split_first() else {
+        return;
+    };
+
+    canvas.draw_text(x, y, &format!("╔{}╗", border));
+    canvas.draw_text(x, y + 1, &format!("║ {:<width$} ║", title.to_string()));
+    canvas.draw_text(x, y + 2, &format!("╠{}╣", border));
+    for (i, line) in body.iter().enumerate() {
+        let row = y + 3 + i as u16;
+        canvas.draw_text(x, row, &format!("║ {:<width$} ║", ""));
+        canvas.draw_line(x + 2, row, line);
+    }
+    let footer_y = y + 3 + body.len() as u16;
+    canvas.draw_text(x, footer_y, &format!("║ {:<width$} ║", footer));
+    canvas.draw_text(x, footer_y + 1, &format!("╚{}╝", border));
+}
+
+/// A titled box sized to its longest line, with a footer of keys
+pub(super) fn draw_panel(
+    canvas: &mut dyn Renderer,
+    area: Rect,
+    title: &str,
+    lines: &[String],
+    footer: &str,
+) {
+    let (x, panel_y) = (area.x, area.y);
+    let width = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .chain([title.chars().count(), footer.chars().count()])
+        .max()
+        .unwrap_or(0);
+    let border = "═".repeat(width + 2);
+
+    canvas.draw_text(x, panel_y, &format!("╔{}╗", border));
+    canvas.draw_text(x, panel_y + 1, &format!("║ {:<width$} ║", title));
+    canvas.draw_text(x, panel_y + 2, &format!("╠{}╣", border));
+    for (i, line) in lines.iter().enumerate() {
+        canvas.draw_text(x, panel_y + 3 + i as u16, &format!("║ {:<width$} ║", line));
+    }
+    let footer_y = panel_y + 3 + lines.len() as u16;
+    canvas.draw_text(x, footer_y, &format!("║ {:<width$} ║", footer));
+    canvas.draw_text(x, footer_y + 1, &format!("╚{}╝", border));
+}
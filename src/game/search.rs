@@ -0,0 +1,193 @@
+//! Finding places by part of their name.
+//!
+//! A query matches a name when its letters appear in the name in order,
+//! ignoring case, so "ter" finds Terra and "mkt" finds Market District.
+//! Matches are ranked so that names starting with the query come first,
+//! then letters typed together that run together in the name, then
+//! letters that begin words, with shorter names breaking ties.
+
+use crate::ui::{TextEdit, TextInput};
+use crate::zoom::ZoomLevel;
+
+use super::WorldState;
+use super::state::EntityId;
+use super::stats::GALAXY_ID;
+
+/// Most matches listed for a query
+pub const SEARCH_RESULTS: usize = 8;
+
+const EXACT_BONUS: i32 = 1_000;
+const PREFIX_BONUS: i32 = 100;
+const RUN_BONUS: i32 = 10;
+const WORD_START_BONUS: i32 = 5;
+
+/// A place whose name matches a query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+    pub level: ZoomLevel,
+    pub id: EntityId,
+    pub name: String,
+    pub score: i32,
+}
+
+/// How well a name matches a query, or None if it doesn't. Higher is
+/// better.
+pub fn fuzzy_score(query: &str, name: &str) -> Option<i32> {
+    let query: Vec<char> = query.trim().to_lowercase().chars().collect();
+    let name_chars: Vec<char> = name.to_lowercase().chars().collect();
+    if query.is_empty() {
+        return None;
+    }
+
+    let mut score = 0;
+    let mut wanted = query.iter().peekable();
+    let mut previous_match = None;
+    for (at, &c) in name_chars.iter().enumerate() {
+        let Some(&&next) = wanted.peek() else {
+            break;
+        };
+        if c != next {
+            continue;
+        }
+        wanted.next();
+        score += 1;
+        if previous_match.is_some_and(|previous| previous + 1 == at) {
+            score += RUN_BONUS;
+        }
+        if at == 0 || !name_chars[at - 1].is_alphanumeric() {
+            score += WORD_START_BONUS;
+        }
+        previous_match = Some(at);
+    }
+    if wanted.peek().is_some() {
+        return None;
+    }
+
+    if name_chars == query {
+        score += EXACT_BONUS;
+    } else if name_chars.starts_with(&query) {
+        score += PREFIX_BONUS;
+    }
+    Some(score - name_chars.len() as i32)
+}
+
+impl WorldState {
+    /// Places at every zoom level matching a query, best first
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+        let levels = [
+            ZoomLevel::Galaxy,
+            ZoomLevel::SolarSystem,
+            ZoomLevel::Planet,
+            ZoomLevel::Region,
+            ZoomLevel::LocalArea,
+            ZoomLevel::Room,
+        ];
+        let mut hits: Vec<SearchHit> = levels
+            .into_iter()
+            .flat_map(|level| {
+                let ids = match level {
+                    ZoomLevel::Galaxy => vec![GALAXY_ID],
+                    level => self.ids(level),
+                };
+                ids.into_iter().map(move |id| (level, id))
+            })
+            .filter_map(|(level, id)| {
+                let name = self.entity_name(level, id);
+                let score = fuzzy_score(query, &name)?;
+                Some(SearchHit {
+                    level,
+                    id,
+                    name,
+                    score,
+                })
+            })
+            .collect();
+        // Larger places first among equal matches
+        hits.sort_by(|a, b| b.score.cmp(&a.score).then(b.level.cmp(&a.level)));
+        hits.truncate(limit);
+        hits
+    }
+}
+
+/// The search box: a query and which of its matches is chosen
+#[derive(Debug, Clone, Default)]
+pub struct Search {
+    query: TextInput,
+    selected: usize,
+}
+
+impl Search {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn query(&self) -> &TextInput {
+        &self.query
+    }
+
+    /// Change the query, going back to its best match
+    pub fn edit(&mut self, edit: TextEdit) {
+        self.query.apply(edit);
+        self.selected = 0;
+    }
+
+    /// Choose the next match down, stopping at the last of `count`
+    pub fn select_next(&mut self, count: usize) {
+        self.selected = (self.selected + 1).min(count.saturating_sub(1));
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// The query's matches in the world
+    pub fn hits(&self, world: &WorldState) -> Vec<SearchHit> {
+        world.search(self.query.text(), SEARCH_RESULTS)
+    }
+
+    /// The match that is chosen, if any
+    pub fn chosen(&self, world: &WorldState) -> Option<SearchHit> {
+        self.hits(world).into_iter().nth(self.selected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_letters_must_appear_in_order() {
+        assert!(fuzzy_score("ter", "Terra").is_some());
+        assert!(fuzzy_score("mkt", "Market District").is_some());
+        assert!(fuzzy_score("tkm", "Market District").is_none());
+        assert!(fuzzy_score("", "Terra").is_none());
+        assert!(fuzzy_score("terra", "Terra") > fuzzy_score("ter", "Terra"));
+        assert!(fuzzy_score("ter", "Terra") > fuzzy_score("ter", "Outer Rim"));
+        assert!(fuzzy_score("md", "Market District") > fuzzy_score("md", "Homeland"));
+    }
+
+    #[test]
+    fn test_search_ranks_across_levels() {
+        let world = WorldState::new();
+        let hits = world.search("Ter", SEARCH_RESULTS);
+        assert_eq!(hits[0].name, "Terra");
+        assert_eq!((hits[0].level, hits[0].id), (ZoomLevel::Planet, 1));
+
+        let mut search = Search::new();
+        for c in "hall".chars() {
+            search.edit(TextEdit::Insert(c));
+        }
+        search.select_next(5);
+        search.select_previous();
+        let chosen = search.chosen(&world).unwrap();
+        assert_eq!(
+            (chosen.level, chosen.name.as_str()),
+            (ZoomLevel::Room, "Trading Hall")
+        );
+        assert!(world.search("qqq", SEARCH_RESULTS).is_empty());
+    }
+}
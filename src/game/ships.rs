@@ -0,0 +1,262 @@
+//! Ships that carry goods and people between planets.
+//!
+//! A docked [`Ship`] looks over the markets of its planet and every planet
+//! it can reach for the good whose price gap pays best, loads what the
+//! cheap market has over [`TARGET_STOCK`] and sails for the dear one, with
+//! as many passengers as want to leave. The voyage takes as long as the
+//! distance at the ship's speed: the gap between two planets of a system is
+//! measured on the system map, and a hop between systems costs
+//! [`INTERSTELLAR_SCALE`] system cells for each cell of the galaxy map. On
+//! arrival the buying market pays the selling one what the cargo cost, so
+//! goods move between planets only by ship.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::economy::{Capacity, GoodId, Money, TARGET_STOCK};
+use crate::time::Calendar;
+
+use super::state::EntityId;
+
+/// System map cells a cell of the galaxy map spans
+pub const INTERSTELLAR_SCALE: f64 = 10.0;
+
+/// Time a ship spends in port between voyages, or before looking again
+/// when there is no trade worth making
+pub const PORT_TIME: Duration = Duration::from_secs(6 * 3600);
+
+/// Least a destination price must exceed the origin's by, as a share of
+/// it, before a cargo is worth carrying
+const MIN_MARGIN: f64 = 0.1;
+
+/// Share of a planet's population that books passage on a departing ship
+const PASSENGER_SHARE: f64 = 0.0001;
+
+/// Shortest voyage, however close two planets are
+const MIN_VOYAGE: Duration = Duration::from_secs(3600);
+
+/// What a new ship's hold takes
+pub const HOLD: Capacity = Capacity {
+    mass: Some(1_000.0),
+    volume: Some(300.0),
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Voyage {
+    pub from: EntityId,
+    pub to: EntityId,
+    pub departed: Duration,
+    pub arrives: Duration,
+}
+
+impl Voyage {
+    /// Share of the way to the destination covered by `now`, from 0 to 1
+    pub fn progress(&self, now: Duration) -> f64 {
+        let total = self.arrives.saturating_sub(self.departed).as_secs_f64();
+        if total <= 0.0 {
+            return 1.0;
+        }
+        (now.saturating_sub(self.departed).as_secs_f64() / total).clamp(0.0, 1.0)
+    }
+}
+
+/// Where a ship is
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Course {
+    /// In port at a planet, ready to sail once `until` has passed
+    Docked {
+        planet: EntityId,
+        until: Duration,
+    },
+    Underway(Voyage),
+}
+
+/// Goods bought in one market for another
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Cargo {
+    pub good: GoodId,
+    pub quantity: f64,
+    /// What the goods cost where they were loaded, paid on delivery
+    pub value: Money,
+    /// Local area whose market sold the goods
+    pub seller: EntityId,
+    /// Local area whose market takes them
+    pub buyer: EntityId,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ship {
+    pub id: EntityId,
+    pub name: String,
+    /// System map cells covered in a day
+    pub speed: f64,
+    /// What the hold takes; a ship carries one cargo at a time
+    pub hold: Capacity,
+    /// Passengers the ship has room for
+    pub berths: u64,
+    pub course: Course,
+    pub cargo: Option<Cargo>,
+    pub passengers: u64,
+}
+
+impl Ship {
+    /// A ship in port at `planet`, ready to sail at once
+    pub fn new(id: EntityId, name: &str, planet: EntityId) -> Self {
+        Self {
+            id,
+            name: name.to_string(),
+            speed: 3.0,
+            hold: HOLD,
+            berths: 50,
+            course: Course::Docked {
+                planet,
+                until: Duration::ZERO,
+            },
+            cargo: None,
+            passengers: 0,
+        }
+    }
+
+    /// The planet the ship is in port at, if it is
+    pub fn docked_at(&self) -> Option<EntityId> {
+        match self.course {
+            Course::Docked { planet, .. } => Some(planet),
+            Course::Underway(_) => None,
+        }
+    }
+
+    pub fn voyage(&self) -> Option<&Voyage> {
+        match &self.course {
+            Course::Underway(voyage) => Some(voyage),
+            Course::Docked { .. } => None,
+        }
+    }
+
+    /// How long the ship takes to cover `distance` system map cells
+    pub fn travel_time(&self, distance: f64) -> Duration {
+        let days = distance.max(0.0) / self.speed.max(f64::EPSILON);
+        Duration::from_secs_f64(days * Calendar::DEFAULT_DAY_LENGTH.as_secs_f64()).max(MIN_VOYAGE)
+    }
+
+    /// Passengers that board at a planet of `population`
+    pub fn boarding(&self, population: u64) -> u64 {
+        ((population as f64 * PASSENGER_SHARE) as u64).min(self.berths)
+    }
+}
+
+/// A good on sale in one market, as shippers see it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Listing {
+    pub area: EntityId,
+    pub planet: EntityId,
+    pub good: GoodId,
+    pub price: Money,
+    pub stock: f64,
+}
+
+/// The cargo from `planet` that pays best for a hold taking `room` units
+/// of each good: a good with stock to spare there that sells for enough
+/// more on another planet. Listings should only include planets the ship
+/// can reach.
+pub fn best_trade(
+    planet: EntityId,
+    room: impl Fn(GoodId) -> f64,
+    listings: &[Listing],
+) -> Option<Cargo> {
+    let mut best: Option<(f64, Cargo)> = None;
+    for origin in listings.iter().filter(|l| l.planet == planet) {
+        let quantity = (origin.stock - TARGET_STOCK).min(room(origin.good));
+        if quantity <= 0.0 {
+            continue;
+        }
+        let cost = origin.price.amount();
+        for destination in listings
+            .iter()
+            .filter(|l| l.planet != planet && l.good == origin.good)
+        {
+            let gain = (destination.price.amount() - cost) * quantity;
+            if destination.price.amount() < cost * (1.0 + MIN_MARGIN)
+                || best.as_ref().is_some_and(|(best, _)| *best >= gain)
+            {
+                continue;
+            }
+            let cargo = Cargo {
+                good: origin.good,
+                quantity,
+                value: origin.price * quantity,
+                seller: origin.area,
+                buyer: destination.area,
+            };
+            best = Some((gain, cargo));
+        }
+    }
+    best.map(|(_, cargo)| cargo)
+}
+
+/// Straight-line distance between two map cells
+pub fn distance(from: (i32, i32), to: (i32, i32)) -> f64 {
+    f64::from(to.0 - from.0).hypot(f64::from(to.1 - from.1))
+}
+
+/// The map cell a voyage has reached, between where it left and where it
+/// is bound
+pub fn between(from: (i32, i32), to: (i32, i32), progress: f64) -> (i32, i32) {
+    let lerp = |a: i32, b: i32| (f64::from(a) + f64::from(b - a) * progress).round() as i32;
+    (lerp(from.0, to.0), lerp(from.1, to.1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listing(area: EntityId, planet: EntityId, good: u16, price: f64, stock: f64) -> Listing {
+        Listing {
+            area,
+            planet,
+            good: GoodId(good),
+            price: Money(price),
+            stock,
+        }
+    }
+
+    #[test]
+    fn test_ships_carry_the_best_paying_cargo() {
+        let listings = [
+            listing(1, 10, 0, 1.0, 400.0),
+            listing(1, 10, 1, 2.0, 150.0),
+            listing(2, 20, 0, 1.05, 50.0),
+            listing(3, 30, 0, 1.5, 50.0),
+            listing(3, 30, 1, 3.0, 50.0),
+        ];
+        let cargo = best_trade(10, |_| 200.0, &listings).unwrap();
+        // 200 of good 0 at 0.5 a unit beats 50 of good 1 at 1 a unit
+        assert_eq!(cargo.good, GoodId(0));
+        assert_eq!(cargo.quantity, 200.0);
+        assert_eq!((cargo.seller, cargo.buyer), (1, 3));
+        assert_eq!(cargo.value, Money(200.0));
+
+        // Nothing is worth carrying from a planet without spare stock
+        assert_eq!(best_trade(20, |_| 200.0, &listings), None);
+    }
+
+    #[test]
+    fn test_voyages_take_distance_over_speed() {
+        let ship = Ship::new(1, "Wayfarer", 10);
+        assert_eq!(ship.docked_at(), Some(10));
+        assert_eq!(ship.travel_time(6.0), Calendar::DEFAULT_DAY_LENGTH * 2);
+        assert_eq!(ship.travel_time(0.0), MIN_VOYAGE);
+        assert_eq!(ship.boarding(1_000_000), 50);
+        assert_eq!(ship.boarding(100_000), 10);
+
+        let voyage = Voyage {
+            from: 10,
+            to: 20,
+            departed: Duration::from_secs(100),
+            arrives: Duration::from_secs(300),
+        };
+        assert_eq!(voyage.progress(Duration::from_secs(200)), 0.5);
+        assert_eq!(voyage.progress(Duration::from_secs(900)), 1.0);
+        assert_eq!(distance((0, 0), (3, 4)), 5.0);
+        assert_eq!(between((0, 0), (4, -2), 0.5), (2, -1));
+    }
+}
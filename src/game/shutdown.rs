@@ -0,0 +1,84 @@
+use crate::result::{Error, Result};
+
+/// A shutdown step that did not complete
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageFailure {
+    pub stage: &'static str,
+    pub message: String,
+}
+
+/// Outcome of the shutdown pipeline.
+///
+/// Every stage runs even if an earlier one failed; failures are collected
+/// here so they can be shown to the player before the terminal is restored.
+#[derive(Debug, Default)]
+pub struct ShutdownReport {
+    failures: Vec<StageFailure>,
+}
+
+impl ShutdownReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, stage: &'static str, result: Result<()>) {
+        if let Err(error) = result {
+            self.record_error(stage, &error);
+        }
+    }
+
+    /// Note a failure the caller still needs, such as the error that ended
+    /// the game loop
+    pub fn record_error(&mut self, stage: &'static str, error: &Error) {
+        let mut message = error.to_string();
+        let mut source = std::error::Error::source(error);
+        while let Some(cause) = source {
+            message.push_str(&format!(": {}", cause));
+            source = cause.source();
+        }
+        self.failures.push(StageFailure { stage, message });
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    pub fn failures(&self) -> &[StageFailure] {
+        &self.failures
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_successful_stages_are_clean() {
+        let mut report = ShutdownReport::new();
+        report.record("Save game", Ok(()));
+        assert!(report.is_clean());
+    }
+
+    #[test]
+    fn test_failures_include_their_cause() {
+        let mut report = ShutdownReport::new();
+        report.record(
+            "Save game",
+            Err(Error::SaveFileError {
+                path: PathBuf::from("/tmp/save.json"),
+                source: std::io::Error::other("disk full"),
+            }),
+        );
+        report.record("Flush", Ok(()));
+
+        assert!(!report.is_clean());
+        assert_eq!(
+            report.failures(),
+            &[StageFailure {
+                stage: "Save game",
+                message: String::from("save file error at /tmp/save.json: disk full"),
+            }]
+        );
+    }
+}
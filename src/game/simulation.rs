@@ -0,0 +1,735 @@
+use crate::economy::Money;
+use crate::save::{SaveGame, Scenario};
+use crate::time::{CalendarDate, TICK_LENGTH, TimeController};
+use crate::ui::{Clock, Progress};
+use crate::zoom::{Direction, ZoomLevel, ZoomManager};
+use std::time::{Duration, Instant};
+
+use super::WorldState;
+use super::bookmarks::Bookmark;
+use super::business::BusinessError;
+use super::furniture::{Furnishing, Interaction, InteractionError};
+use super::rewind::{Rewind, Snapshot};
+use super::state::EntityId;
+use super::stats::GALAXY_ID;
+use super::travel::{Journey, TravelError};
+use super::watches::{Watch, Watches};
+
+/// Frame rate the simulation clock paces itself against by default
+pub const DEFAULT_TARGET_FPS: u32 = 30;
+
+/// Simulation time covered by each step of a fast-forward
+pub const FAST_FORWARD_STEP: Duration = Duration::from_secs(3_600);
+
+/// A jump ahead in time that runs over many frames
+struct FastForward {
+    from: Duration,
+    to: Duration,
+    /// Wall-clock start, for estimating how long is left
+    started: Instant,
+    /// Whether the clock was paused before, to restore afterwards
+    was_paused: bool,
+}
+
+/// The simulation core: world state, the clock that drives it, and the
+/// player's view into it.
+///
+/// Frontends own a `Simulation` and call [`Simulation::step`] once per frame;
+/// tests and tools can call [`Simulation::advance`] to move time forward
+/// deterministically without a terminal.
+pub struct Simulation {
+    time: TimeController,
+    zoom: ZoomManager,
+    world: WorldState,
+    fast_forward: Option<FastForward>,
+    /// Recent snapshots of the world to rewind to
+    rewind: Rewind,
+    /// Queries pinned from the console, worked out every few ticks
+    watches: Watches,
+}
+
+impl Simulation {
+    pub fn new() -> Self {
+        Self::with_world(WorldState::new())
+    }
+
+    pub fn with_world(mut world: WorldState) -> Self {
+        world.survey_terrain();
+        Self {
+            time: TimeController::new(DEFAULT_TARGET_FPS),
+            zoom: ZoomManager::new(),
+            world,
+            fast_forward: None,
+            rewind: Rewind::new(),
+            watches: Watches::default(),
+        }
+    }
+
+    pub fn from_save(save: SaveGame) -> Self {
+        let mut simulation = Self::with_world(save.world);
+        simulation.time.set_simulation_time(save.simulation_time);
+        simulation.zoom.restore(save.zoom_level, save.position);
+        simulation
+    }
+
+    /// Begin a session in a scenario's world, with the clock picking up
+    /// wherever the world was when the scenario was authored and the
+    /// scenario's events and campaign starting from that point
+    pub fn from_scenario(scenario: Scenario) -> Self {
+        let mut simulation = Self::with_world(scenario.world);
+        let elapsed = simulation.world.elapsed();
+        simulation.time.set_simulation_time(elapsed);
+
+        let calendar = *simulation.time.calendar();
+        for event in scenario.events {
+            let at = elapsed + event.date.offset(&calendar);
+            simulation.world.schedule(at, event);
+        }
+        if let Some(campaign) = scenario.campaign {
+            simulation.world.start_campaign(campaign);
+        }
+        if let Some(frequency) = scenario.event_frequency {
+            simulation.world.set_event_frequency(frequency);
+        }
+        simulation
+    }
+
+    pub fn to_save(&self) -> SaveGame {
+        SaveGame::new(
+            self.time.simulation_time(),
+            self.zoom.current_level(),
+            *self.zoom.position(),
+            self.world.clone(),
+        )
+    }
+
+    pub fn time(&self) -> &TimeController {
+        &self.time
+    }
+
+    pub fn time_mut(&mut self) -> &mut TimeController {
+        &mut self.time
+    }
+
+    pub fn zoom(&self) -> &ZoomManager {
+        &self.zoom
+    }
+
+    pub fn zoom_mut(&mut self) -> &mut ZoomManager {
+        &mut self.zoom
+    }
+
+    /// Descend into the entity under the cursor; see [`ZoomManager::zoom_in`].
+    /// The view can't leave where it is while the player is in transit.
+    pub fn zoom_in(&mut self) -> bool {
+        self.world.journey().is_none() && self.zoom.zoom_in(&self.world)
+    }
+
+    pub fn zoom_out(&mut self) -> bool {
+        self.world.journey().is_none() && self.zoom.zoom_out(&self.world)
+    }
+
+    /// Move the cursor a spot, within the bounds of the map in view
+    pub fn move_cursor(&mut self, direction: Direction) -> bool {
+        self.world.journey().is_none() && self.zoom.move_in_direction(&self.world, direction)
+    }
+
+    /// Go into the entity under the cursor as [`Simulation::zoom_in`] does,
+    /// if the player is there already; a system, planet or region elsewhere
+    /// is set out for instead. Returns the journey begun, if any.
+    pub fn travel_in(&mut self) -> Result<Option<Journey>, TravelError> {
+        let level = self.zoom.current_level();
+        let Some(child) = level.zoom_in() else {
+            return Ok(None);
+        };
+        let position = self.zoom.position();
+        let coords = position.coords_for_level(level);
+        let Some(id) = self
+            .world
+            .entity_at(child, position.map_owner(level), coords)
+        else {
+            return Ok(None);
+        };
+        let journey = self.world.depart(child, id)?;
+        if journey.is_none() {
+            self.zoom_in();
+        }
+        Ok(journey)
+    }
+
+    /// Move the view into an entity as [`Simulation::jump_to`] does, if the
+    /// player is there already, or set out for it. Returns the journey
+    /// begun, if any.
+    pub fn travel_to(
+        &mut self,
+        level: ZoomLevel,
+        id: EntityId,
+    ) -> Result<Option<Journey>, TravelError> {
+        let journey = self.world.depart(level, id)?;
+        if journey.is_none() {
+            self.jump_to(level, id);
+        }
+        Ok(journey)
+    }
+
+    /// How far the player's journey has got, with the game time left
+    pub fn journey_progress(&self) -> Option<Progress> {
+        let journey = self.world.journey()?;
+        let now = self.world.elapsed();
+        let label = format!("To {} {}", journey.destination, journey.route);
+        Some(
+            Progress::new(label, journey.progress(now), 1.0)
+                .with_remaining(journey.arrives.saturating_sub(now), Clock::Game),
+        )
+    }
+
+    /// Bookmark the view in a slot from 1 to 9, returning the name of the
+    /// place bookmarked, or None for a slot out of range
+    pub fn bookmark(&mut self, slot: u8) -> Option<String> {
+        let level = self.zoom.current_level();
+        let position = *self.zoom.position();
+        let name = self.world.location_name(&position, level);
+        let bookmark = Bookmark {
+            level,
+            position,
+            name: name.clone(),
+        };
+        self.world
+            .bookmarks_mut()
+            .set(slot, bookmark)
+            .then_some(name)
+    }
+
+    /// Return the view to a bookmark, returning its name, or None if the
+    /// slot is empty
+    pub fn jump_to_bookmark(&mut self, slot: u8) -> Option<String> {
+        let bookmark = self.world.bookmarks().get(slot)?;
+        self.zoom.restore(bookmark.level, bookmark.position);
+        Some(bookmark.name.clone())
+    }
+
+    /// Move the view to a named place, returning its level and full name,
+    /// or None if nothing has that name
+    pub fn goto(&mut self, name: &str) -> Option<(ZoomLevel, String)> {
+        let (level, id) = self.world.find_entity(name)?;
+        let name = self.jump_to(level, id)?;
+        Some((level, name))
+    }
+
+    /// Move the view into an entity at any level, with each place above
+    /// it as the current one at its level and the cursor over where it
+    /// sits on its parent's map. Returns its full name, or None if there
+    /// is no such entity.
+    pub fn jump_to(&mut self, level: ZoomLevel, id: EntityId) -> Option<String> {
+        if level != ZoomLevel::Galaxy && self.world.placement_of(level, id).is_none() {
+            return None;
+        }
+        let mut position = *self.zoom.position();
+        let mut below = level.zoom_in();
+        while let Some(inner) = below {
+            position.set_entity_id(inner, None);
+            below = inner.zoom_in();
+        }
+        for (at, entity) in self.world.ancestry(level, id) {
+            position.set_entity_id(at, Some(entity));
+            if let (Some(parent), Some(placement)) =
+                (at.zoom_out(), self.world.placement_of(at, entity))
+            {
+                position.set_coords_for_level(parent, placement.coords);
+            }
+        }
+        self.zoom.restore(level, position);
+        Some(self.world.location_name(&position, level))
+    }
+
+    /// Found a firm for the player in the local area in view, or the one
+    /// holding the room in view
+    pub fn found_business(
+        &mut self,
+        name: &str,
+        recipe: &str,
+        workers: u32,
+        capital: Money,
+    ) -> Result<EntityId, BusinessError> {
+        let area = self.area_in_view().ok_or(BusinessError::NotInArea)?;
+        self.world
+            .found_business(area, name, recipe, workers, capital)
+    }
+
+    /// Commission a building for the player in the local area in view, or
+    /// the one holding the room in view
+    pub fn commission_building(
+        &mut self,
+        name: &str,
+        recipe: &str,
+        workers: u32,
+        capital: Money,
+    ) -> Result<EntityId, BusinessError> {
+        let area = self.area_in_view().ok_or(BusinessError::NotInArea)?;
+        self.world
+            .commission_building(area, name, recipe, workers, capital)
+    }
+
+    /// The local area in view, or the one holding the room in view
+    fn area_in_view(&self) -> Option<EntityId> {
+        if self.zoom.current_level() > ZoomLevel::LocalArea {
+            return None;
+        }
+        self.zoom.position().map_owner(ZoomLevel::LocalArea)
+    }
+
+    /// The firm whose building is under the cursor, when a local area is
+    /// in view
+    pub fn firm_in_view(&self) -> Option<EntityId> {
+        if self.zoom.current_level() != ZoomLevel::LocalArea {
+            return None;
+        }
+        let position = self.zoom.position();
+        let area = position.map_owner(ZoomLevel::LocalArea)?;
+        self.world
+            .economy()
+            .firm_at(area, position.coords_for_level(ZoomLevel::LocalArea))
+            .map(|firm| firm.id)
+    }
+
+    /// The room in view and the piece of furniture under the cursor in it
+    pub fn furnishing_in_view(&self) -> Option<(EntityId, Furnishing)> {
+        if self.zoom.current_level() != ZoomLevel::Room {
+            return None;
+        }
+        let position = self.zoom.position();
+        let room = position.map_owner(ZoomLevel::Room)?;
+        let coords = position.coords_for_level(ZoomLevel::Room);
+        Some((room, self.world.furnishing_at(room, coords)?))
+    }
+
+    /// Use the furniture under the cursor, fast-forwarding through the
+    /// hours it takes. Returns what the player did.
+    pub fn interact(&mut self, interaction: Interaction) -> Result<String, InteractionError> {
+        let (room, piece) = self
+            .furnishing_in_view()
+            .ok_or(InteractionError::NoFurniture)?;
+        let done = self.world.interact(room, piece.coords, interaction)?;
+        let hours = interaction.hours();
+        if hours > 0 {
+            let hour = self.time.calendar().day_length() / 24;
+            self.fast_forward(hour * hours);
+        }
+        Ok(done)
+    }
+
+    /// The polity governing the place in view, if any
+    pub fn polity_in_view(&self) -> Option<EntityId> {
+        let level = self.zoom.current_level();
+        let id = self.zoom.position().map_owner(level)?;
+        self.world.polity_of(level, id).map(|polity| polity.id)
+    }
+
+    pub fn world(&self) -> &WorldState {
+        &self.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut WorldState {
+        &mut self.world
+    }
+
+    pub fn watches(&self) -> &Watches {
+        &self.watches
+    }
+
+    pub fn watches_mut(&mut self) -> &mut Watches {
+        &mut self.watches
+    }
+
+    /// Pin a watch, worked out against the world as it is now. Returns
+    /// it, or None if there is no room for another.
+    pub fn add_watch(&mut self, watch: Watch) -> Option<&Watch> {
+        self.watches.add(watch, &self.world)
+    }
+
+    pub fn simulation_time(&self) -> Duration {
+        self.time.simulation_time()
+    }
+
+    /// Advance by the real time elapsed since the last step, scaled by the
+    /// current speed, in as many ticks as are due, or by the next chunk of
+    /// a fast-forward. Does nothing while paused.
+    pub fn step(&mut self) -> Duration {
+        let mut delta = Duration::ZERO;
+        for _ in 0..self.due_ticks() {
+            delta += self.tick();
+        }
+        delta
+    }
+
+    /// Ticks to run this frame: one per [`TICK_LENGTH`] of scaled time
+    /// built up since the last frame, at most [`MAX_TICKS_PER_FRAME`], or
+    /// one chunk of a fast-forward. None while paused.
+    ///
+    /// [`MAX_TICKS_PER_FRAME`]: crate::time::MAX_TICKS_PER_FRAME
+    pub fn due_ticks(&mut self) -> u32 {
+        if self.time.is_paused() {
+            0
+        } else if self.fast_forward.is_some() {
+            1
+        } else {
+            self.time.take_ticks()
+        }
+    }
+
+    /// Run one tick: the next chunk of a fast-forward, or [`TICK_LENGTH`].
+    /// Does nothing while paused, as when a tick earlier in the frame
+    /// paused the game.
+    pub fn tick(&mut self) -> Duration {
+        if self.time.is_paused() {
+            return Duration::ZERO;
+        }
+        if let Some(to) = self.fast_forward.as_ref().map(|ff| ff.to) {
+            let delta = to
+                .saturating_sub(self.time.simulation_time())
+                .min(FAST_FORWARD_STEP);
+            self.advance(delta);
+            if self.time.simulation_time() >= to {
+                let date = self.time.current_date();
+                self.cancel_fast_forward();
+                self.world.log(format!("Fast-forwarded to {}", date));
+            }
+            return delta;
+        }
+
+        self.advance(TICK_LENGTH);
+        TICK_LENGTH
+    }
+
+    /// Advance by an exact amount of simulation time, regardless of pause
+    /// state or speed
+    pub fn advance(&mut self, delta: Duration) {
+        self.time.advance(delta);
+        self.sync_focus();
+        self.world.update(delta);
+        self.watches.update(&self.world);
+        if let Some(journey) = self.world.take_arrival() {
+            self.jump_to(journey.level, journey.to);
+        }
+        self.record_snapshot();
+    }
+
+    /// Snapshots held to rewind to
+    pub fn snapshots(&self) -> usize {
+        self.rewind.len()
+    }
+
+    /// The rewind snapshot `back` places before the latest
+    pub fn snapshot(&self, back: usize) -> Option<&Snapshot> {
+        self.rewind.back(back)
+    }
+
+    /// Go back to the latest snapshot from before now (ignoring any taken
+    /// within [`super::rewind::REWIND_GRACE`]), with the world and
+    /// clock as they were then. Returns the date rewound to, or None if
+    /// there is no earlier snapshot.
+    pub fn rewind(&mut self) -> Option<CalendarDate> {
+        let snapshot = self.rewind.restore_point(self.time.simulation_time())?;
+        let at = snapshot.at;
+        let world = match snapshot.restore() {
+            Ok(world) => world,
+            Err(e) => {
+                self.world.log(format!("Rewind failed: {}", e));
+                return None;
+            }
+        };
+        self.cancel_fast_forward();
+        self.world = world;
+        self.world.survey_terrain();
+        self.time.set_simulation_time(at);
+        self.sync_focus();
+        Some(self.time.current_date())
+    }
+
+    /// Keep a snapshot of the world if one is due
+    fn record_snapshot(&mut self) {
+        let now = self.time.simulation_time();
+        if !self.rewind.is_due(now) {
+            return;
+        }
+        match Snapshot::take(&self.world, now) {
+            Ok(snapshot) => self.rewind.push(snapshot),
+            Err(e) => self.world.log(format!("Snapshot failed: {}", e)),
+        }
+    }
+
+    /// Jump `span` ahead over the coming frames, one [`FAST_FORWARD_STEP`]
+    /// per frame, running the clock until it is done
+    pub fn fast_forward(&mut self, span: Duration) {
+        let now = self.time.simulation_time();
+        let was_paused = self.time.is_paused();
+        if was_paused {
+            self.time.toggle_pause();
+        }
+        self.fast_forward = Some(FastForward {
+            from: now,
+            to: now + span,
+            started: Instant::now(),
+            was_paused,
+        });
+    }
+
+    pub fn is_fast_forwarding(&self) -> bool {
+        self.fast_forward.is_some()
+    }
+
+    /// Stop a fast-forward where it is, pausing again if the clock was
+    /// paused when it began
+    pub fn cancel_fast_forward(&mut self) {
+        if let Some(fast_forward) = self.fast_forward.take()
+            && fast_forward.was_paused
+            && !self.time.is_paused()
+        {
+            self.time.toggle_pause();
+        }
+    }
+
+    /// How far the current fast-forward has got, with a real-time estimate
+    /// of how long it will take
+    pub fn fast_forward_progress(&self) -> Option<Progress> {
+        let fast_forward = self.fast_forward.as_ref()?;
+        let done = self
+            .time
+            .simulation_time()
+            .saturating_sub(fast_forward.from);
+        let total = fast_forward.to - fast_forward.from;
+        Some(
+            Progress::new("Fast-forward", done.as_secs_f64(), total.as_secs_f64())
+                .with_estimate(fast_forward.started.elapsed(), Clock::Real),
+        )
+    }
+
+    /// Simulate everything inside the viewed entity at full detail, rather
+    /// than only its immediate surroundings
+    pub fn force_full_detail(&mut self, forced: bool) {
+        self.world.detail_mut().force_full(forced);
+    }
+
+    /// Point the world's level-of-detail scheduling at the viewed entity
+    fn sync_focus(&mut self) {
+        let level = self.zoom.current_level();
+        let id = self.zoom.position().map_owner(level).unwrap_or(GALAXY_ID);
+        self.world.set_focus(level, id);
+    }
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_does_nothing_while_paused() {
+        let mut simulation = Simulation::new();
+        assert_eq!(simulation.step(), Duration::ZERO);
+        assert_eq!(simulation.world().tick_count(), 0);
+    }
+
+    #[test]
+    fn test_advance_moves_clock_and_world() {
+        let mut simulation = Simulation::new();
+        simulation.advance(Duration::from_secs(90));
+
+        assert_eq!(simulation.simulation_time(), Duration::from_secs(90));
+        assert_eq!(simulation.world().tick_count(), 1);
+    }
+
+    #[test]
+    fn test_scenario_events_fire_on_their_date() {
+        use crate::game::schedule::{EventDate, EventEffect, ScriptedEvent};
+
+        let mut scenario = Scenario::new("Plague Year", WorldState::new());
+        scenario.events.push(ScriptedEvent {
+            date: EventDate {
+                day: 3,
+                ..EventDate::default()
+            },
+            message: String::from("A plague hits Terra"),
+            effect: EventEffect::Plague {
+                target: String::from("Terra"),
+                share: 0.1,
+            },
+        });
+        let mut simulation = Simulation::from_scenario(scenario);
+        let population = simulation.world().population(ZoomLevel::Planet, 1).unwrap();
+        let day = Duration::from_secs(86_400);
+
+        simulation.advance(day);
+        assert_eq!(simulation.world().pending_events(), 1);
+
+        simulation.advance(day);
+        assert_eq!(simulation.world().pending_events(), 0);
+        assert!(simulation.world().population(ZoomLevel::Planet, 1).unwrap() < population);
+        let last = simulation.world().events().recent(1).next().unwrap();
+        assert_eq!(last.message, "A plague hits Terra");
+    }
+
+    #[test]
+    fn test_scenarios_set_how_often_random_events_strike() {
+        let mut scenario = Scenario::new("Cursed", WorldState::new());
+        scenario.event_frequency = Some(100.0);
+        let mut simulation = Simulation::from_scenario(scenario);
+        assert_eq!(simulation.world().rules().event_frequency, 100.0);
+
+        simulation.advance(Duration::from_secs(86_400));
+        let struck = simulation.world().events().recent(50).any(|entry| {
+            ["asteroid", "Plague", "Gold rush", "breakthrough"]
+                .iter()
+                .any(|word| entry.message.contains(word))
+        });
+        assert!(struck);
+
+        let mut calm = Scenario::new("Calm", WorldState::new());
+        calm.event_frequency = Some(0.0);
+        let calm = Simulation::from_scenario(calm);
+        assert_eq!(calm.world().rules().event_frequency, 0.0);
+    }
+
+    #[test]
+    fn test_bookmarks_survive_a_save() {
+        let mut simulation = Simulation::new();
+        simulation.zoom_in();
+        simulation.zoom_in();
+        let name = simulation.bookmark(2).unwrap();
+        assert!(simulation.bookmark(10).is_none());
+        simulation.zoom_out();
+        simulation.zoom_out();
+
+        let mut restored = Simulation::from_save(simulation.to_save());
+        assert_eq!(restored.jump_to_bookmark(2), Some(name));
+        assert_eq!(restored.zoom().current_level(), ZoomLevel::Planet);
+        assert!(restored.jump_to_bookmark(3).is_none());
+    }
+
+    #[test]
+    fn test_travel_takes_time_and_moves_the_view_on_arrival() {
+        use crate::game::travel::Route;
+        use crate::game::worldgen::WorldConfig;
+
+        let config = WorldConfig {
+            systems: 1,
+            ..WorldConfig::default()
+        };
+        let mut simulation = Simulation::with_world(config.generate());
+        let far = *simulation
+            .world()
+            .ids(ZoomLevel::SolarSystem)
+            .last()
+            .unwrap();
+        let purse = simulation.world().economy().purse();
+
+        // The region the player is in needs no journey
+        assert_eq!(simulation.travel_to(ZoomLevel::Region, 1), Ok(None));
+        assert_eq!(simulation.zoom().current_level(), ZoomLevel::Region);
+
+        let day = Duration::from_secs(86_400);
+        simulation.world_mut().collapse_lanes(far, day);
+        assert!(matches!(
+            simulation.travel_to(ZoomLevel::SolarSystem, far),
+            Err(TravelError::LanesClosed(_))
+        ));
+        simulation.advance(day);
+
+        let journey = simulation
+            .travel_to(ZoomLevel::SolarSystem, far)
+            .unwrap()
+            .unwrap();
+        assert_eq!(journey.route, Route::Interstellar);
+        assert!(journey.fare > Money::ZERO);
+        assert_eq!(simulation.world().economy().purse(), purse - journey.fare);
+        assert!(simulation.journey_progress().is_some());
+
+        // Until the player arrives the view stays where they set out
+        assert!(!simulation.zoom_out());
+        assert!(!simulation.move_cursor(Direction::Left));
+        assert_eq!(
+            simulation.travel_to(ZoomLevel::Region, 1),
+            Err(TravelError::InTransit(journey.destination.clone()))
+        );
+        assert_eq!(simulation.zoom().current_level(), ZoomLevel::Region);
+
+        while simulation.world().journey().is_some() {
+            simulation.advance(FAST_FORWARD_STEP);
+        }
+        assert!(simulation.world().elapsed() >= journey.arrives);
+        assert_eq!(simulation.zoom().current_level(), ZoomLevel::SolarSystem);
+        assert_eq!(simulation.zoom().position().current_system_id, Some(far));
+        let whereabouts = simulation.world().player_position();
+        assert_eq!(whereabouts.current_system_id, Some(far));
+        assert_eq!(whereabouts.current_planet_id, None);
+        assert!(simulation.world().audit().is_empty());
+    }
+
+    #[test]
+    fn test_business_is_founded_in_area_in_view() {
+        let mut simulation = Simulation::new();
+        assert_eq!(
+            simulation.found_business("Corner Bakery", "baking", 3, Money(500.0)),
+            Err(BusinessError::NotInArea)
+        );
+        for _ in 0..4 {
+            simulation.zoom_in();
+        }
+        let id = simulation
+            .found_business("Corner Bakery", "baking", 3, Money(500.0))
+            .unwrap();
+
+        let firm = simulation.world().economy().firm(id).unwrap();
+        assert_eq!(firm.area_id, 1);
+        assert_eq!(firm.max_workers, Some(3));
+        let restored = Simulation::from_save(simulation.to_save());
+        assert!(restored.world().business().owns(id));
+    }
+
+    #[test]
+    fn test_save_round_trip_restores_view() {
+        let mut simulation = Simulation::new();
+        simulation.zoom_in();
+        simulation.advance(Duration::from_secs(60));
+
+        let restored = Simulation::from_save(simulation.to_save());
+        assert_eq!(restored.zoom().current_level(), ZoomLevel::SolarSystem);
+        assert_eq!(restored.simulation_time(), Duration::from_secs(60));
+        assert_eq!(restored.world().tick_count(), 1);
+    }
+
+    #[test]
+    fn test_rewind_restores_an_earlier_day() {
+        let mut simulation = Simulation::new();
+        assert_eq!(simulation.rewind(), None);
+        let hour = Duration::from_secs(3_600);
+        for _ in 0..30 {
+            simulation.advance(hour);
+        }
+        // Snapshots after the first hour and the first full day later
+        assert_eq!(simulation.snapshots(), 2);
+        simulation
+            .world_mut()
+            .set_population(ZoomLevel::Planet, 1, 1);
+
+        simulation.rewind().unwrap();
+        assert_eq!(simulation.simulation_time(), hour * 25);
+        assert_ne!(
+            simulation.world().population(ZoomLevel::Planet, 1).unwrap(),
+            1
+        );
+        // A tick later the restored snapshot is still the latest, so no new
+        // one is taken and rewinding again goes back a day
+        simulation.advance(TICK_LENGTH);
+        assert_eq!(simulation.snapshots(), 2);
+        simulation.rewind().unwrap();
+        assert_eq!(simulation.simulation_time(), hour);
+        assert_eq!(simulation.snapshots(), 1);
+        assert_eq!(simulation.rewind(), None);
+    }
+}
@@ -0,0 +1,166 @@
+//! Long headless runs that audit the world after every simulated hour.
+//!
+//! A soak run advances the whole simulation at full detail for as many hours
+//! as asked, checking [`WorldState::audit`] before the first hour and after
+//! each one. It stops at the first violation and keeps the world as it was an
+//! hour earlier, so loading that save and running one more hour reproduces
+//! the fault.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::result::{Error, Result};
+use crate::save::{SaveGame, ensure_dir};
+
+use super::Simulation;
+use super::audit::Violation;
+
+/// Directory in the data directory soak failures are written to
+pub const SOAK_DIR: &str = "soak";
+
+const REPORT_FILE: &str = "report.txt";
+const REPRO_FILE: &str = "repro.json";
+
+const HOUR: Duration = Duration::from_secs(3600);
+
+/// How a soak run went
+pub struct SoakReport {
+    /// Hours asked for
+    pub hours: u64,
+    /// Hours that passed the audit
+    pub completed: u64,
+    /// What the first failing audit found; empty if every audit passed
+    pub violations: Vec<Violation>,
+    /// The world an hour before the first failing audit, or at the start
+    /// if the world was broken from the outset
+    pub repro: Option<SaveGame>,
+    /// Real time the run took
+    pub elapsed: Duration,
+    seed: u64,
+}
+
+impl SoakReport {
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let rate = self.completed as f64 / self.elapsed.as_secs_f64().max(1e-3);
+        let mut lines = vec![format!(
+            "Soaked {} of {} hours with seed {} in {:.1}s ({:.0} hours/s)",
+            self.completed,
+            self.hours,
+            self.seed,
+            self.elapsed.as_secs_f64(),
+            rate
+        )];
+        if self.passed() {
+            lines.push(String::from("Every audit passed"));
+            return lines;
+        }
+        let when = match self.completed {
+            0 => String::from("before the first hour"),
+            hours => format!("after hour {}", hours + 1),
+        };
+        lines.push(format!("{} violation(s) {}:", self.violations.len(), when));
+        lines.extend(self.violations.iter().map(|v| format!("  {}", v)));
+        lines
+    }
+
+    /// Write the report and, if the run failed, the save to reproduce it
+    /// from. Returns the paths written.
+    pub fn store(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        ensure_dir(dir)?;
+        let mut written = Vec::new();
+        let mut lines = self.lines();
+        if let Some(repro) = &self.repro {
+            let path = dir.join(REPRO_FILE);
+            repro.write_to(&path)?;
+            lines.push(format!(
+                "Reproduce with --load {} --soak hours=1",
+                path.display()
+            ));
+            written.push(path);
+        }
+
+        let path = dir.join(REPORT_FILE);
+        fs::write(&path, lines.join("\n") + "\n").map_err(|source| Error::SaveFileError {
+            path: path.clone(),
+            source,
+        })?;
+        written.insert(0, path);
+        Ok(written)
+    }
+}
+
+/// Run the simulation for `hours`, auditing as it goes
+pub fn soak(simulation: &mut Simulation, hours: u64) -> SoakReport {
+    let started = Instant::now();
+    simulation.force_full_detail(true);
+    let seed = simulation.world().seed();
+
+    let mut completed = 0;
+    let mut violations = simulation.world().audit();
+    // A world that starts out broken is its own repro
+    let mut repro = (!violations.is_empty()).then(|| simulation.to_save());
+    while violations.is_empty() && completed < hours {
+        let before = simulation.to_save();
+        simulation.advance(HOUR);
+        violations = simulation.world().audit();
+        if violations.is_empty() {
+            completed += 1;
+        } else {
+            for violation in &violations {
+                tracing::error!(hour = completed + 1, "{}", violation);
+            }
+            repro = Some(before);
+        }
+    }
+
+    SoakReport {
+        hours,
+        completed,
+        violations,
+        repro,
+        elapsed: started.elapsed(),
+        seed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::Money;
+    use crate::save::test_dir;
+
+    #[test]
+    fn test_sound_world_soaks_cleanly() {
+        let mut simulation = Simulation::new();
+        let report = soak(&mut simulation, 72);
+        assert!(report.passed(), "{:?}", report.lines());
+        assert_eq!(report.completed, 72);
+        assert!(report.repro.is_none());
+    }
+
+    #[test]
+    fn test_first_violation_is_kept_for_repro() {
+        let mut simulation = Simulation::new();
+        simulation
+            .world_mut()
+            .economy_mut()
+            .market_mut(1)
+            .unwrap()
+            .deposit(Money(100.0));
+        let report = soak(&mut simulation, 10);
+        assert!(!report.passed());
+        assert_eq!(report.completed, 0);
+
+        let dir = test_dir("soak");
+        let written = report.store(&dir).unwrap();
+        let text = fs::read_to_string(&written[0]).unwrap();
+        assert!(text.contains("before the first hour"));
+        assert!(text.contains("money issued"));
+        assert_eq!(written.len(), 2);
+    }
+}
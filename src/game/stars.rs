@@ -0,0 +1,158 @@
+//! The stars at the heart of each solar system.
+//!
+//! Every system has a [`StarClass`], from the hot blue O stars down to the
+//! cool red M dwarfs, picked when the system is spawned with the dim classes
+//! far more common than the bright ones, as they are in the sky. The galaxy
+//! map draws each system at its own coordinates as its star: the glyph
+//! tells how bright the star burns and the color how many people live
+//! around it.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::rng::Rng;
+
+/// Spectral class of a system's star, hottest first
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub enum StarClass {
+    O,
+    B,
+    A,
+    F,
+    #[default]
+    G,
+    K,
+    M,
+}
+
+/// How bright a star looks on the galaxy map
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Brightness {
+    /// O, B and A stars
+    Bright,
+    /// F and G stars, like the sun
+    Sunlike,
+    /// K and M dwarfs
+    Dim,
+}
+
+impl StarClass {
+    pub const ALL: [StarClass; 7] = [
+        StarClass::O,
+        StarClass::B,
+        StarClass::A,
+        StarClass::F,
+        StarClass::G,
+        StarClass::K,
+        StarClass::M,
+    ];
+
+    pub fn letter(self) -> char {
+        match self {
+            StarClass::O => 'O',
+            StarClass::B => 'B',
+            StarClass::A => 'A',
+            StarClass::F => 'F',
+            StarClass::G => 'G',
+            StarClass::K => 'K',
+            StarClass::M => 'M',
+        }
+    }
+
+    /// What the star looks like, e.g. "yellow dwarf"
+    pub fn description(self) -> &'static str {
+        match self {
+            StarClass::O => "blue giant",
+            StarClass::B => "blue-white giant",
+            StarClass::A => "white star",
+            StarClass::F => "yellow-white star",
+            StarClass::G => "yellow dwarf",
+            StarClass::K => "orange dwarf",
+            StarClass::M => "red dwarf",
+        }
+    }
+
+    pub fn brightness(self) -> Brightness {
+        match self {
+            StarClass::O | StarClass::B | StarClass::A => Brightness::Bright,
+            StarClass::F | StarClass::G => Brightness::Sunlike,
+            StarClass::K | StarClass::M => Brightness::Dim,
+        }
+    }
+
+    /// How often the class turns up relative to the others
+    fn weight(self) -> u64 {
+        match self {
+            StarClass::O => 1,
+            StarClass::B => 2,
+            StarClass::A => 4,
+            StarClass::F => 8,
+            StarClass::G => 12,
+            StarClass::K => 20,
+            StarClass::M => 40,
+        }
+    }
+
+    /// A class picked at random, weighted by how common each is
+    pub fn pick(rng: &mut Rng) -> StarClass {
+        let total: u64 = Self::ALL.iter().map(|class| class.weight()).sum();
+        let mut roll = rng.range(0, total);
+        for class in Self::ALL {
+            if roll < class.weight() {
+                return class;
+            }
+            roll -= class.weight();
+        }
+        StarClass::M
+    }
+}
+
+impl fmt::Display for StarClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.letter(), self.description())
+    }
+}
+
+/// Where a system's population puts it between none and the most any
+/// system holds, on a log scale so that small colonies still show, or
+/// None for an empty system
+pub fn population_share(population: u64, most: u64) -> Option<f64> {
+    if population == 0 {
+        return None;
+    }
+    let most = most.max(population) as f64;
+    Some(if most > 1.0 {
+        (population as f64).ln() / most.ln()
+    } else {
+        1.0
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dim_stars_are_the_most_common() {
+        let mut rng = Rng::new(11);
+        let mut counts = [0; 7];
+        for _ in 0..5_000 {
+            counts[StarClass::pick(&mut rng) as usize] += 1;
+        }
+        assert!(counts.iter().all(|&count| count > 0), "{:?}", counts);
+        assert!(counts[StarClass::M as usize] > counts[StarClass::G as usize]);
+        assert!(counts[StarClass::G as usize] > counts[StarClass::O as usize]);
+        assert_eq!(StarClass::G.to_string(), "G yellow dwarf");
+    }
+
+    #[test]
+    fn test_population_share_is_logarithmic() {
+        assert_eq!(population_share(0, 1_000), None);
+        assert_eq!(population_share(1_000, 1_000), Some(1.0));
+        assert_eq!(population_share(1, 1), Some(1.0));
+        let share = population_share(1_000, 1_000_000).unwrap();
+        assert!((share - 0.5).abs() < 1e-9);
+    }
+}
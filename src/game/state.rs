@@ -1,274 +1,3816 @@
-use crate::zoom::{Position, ZoomLevel};
-use std::collections::HashMap;
+use super::bookmarks::Bookmarks;
+use super::builder::WorldStateBuilder;
+use super::business::{Business, BusinessError, STARTING_PURSE};
+use super::campaign::{Campaign, CampaignProgress, Interstitial};
+use super::census::Census;
+use super::characters::Character;
+use super::climate::{self, Season, Weather, WeatherKind};
+use super::contracts::{self, ContractError, SupplyContract, Terms};
+use super::deposits::{self, DepositKind, ResourceDeposit};
+use super::detail::DetailScheduler;
+use super::entities::{
+    Entity, EntityStore, LocalAreaState, RegionState, RoomState, SolarSystemState,
+};
+use super::events::{Alert, EventLog, Notice, Severity};
+use super::festivals::Festival;
+use super::furniture::{self, Furnishing, FurnitureKind, Interaction, InteractionError};
+use super::journal::{Journal, JournalEntry};
+use super::migration::{self, Move};
+use super::pathfinding::Grid;
+use super::polities::{self, Polity};
+use super::presets::Rules;
+use super::random_events;
+use super::rng::Rng;
+use super::schedule::{Schedule, ScriptedEvent};
+use super::ships::{self, Course, Listing, Ship, Voyage};
+use super::stars::StarClass;
+use super::stats::{self, EconomyStats, GALAXY_ID, Sample};
+use super::terrain::{Biome, Surface, TerrainMap};
+use super::travel::{Journey, Route, TravelError};
+use crate::economy::{
+    Builder, EXPANSION_BUDGET, EXPANSION_INTERVAL, Economy, Firm, FiscalPolicy, GoodCategory,
+    GoodId, Holiday, Inventory, Market, MarketStatus, MarketTick, Money, RecipeId, Site,
+    TechBoosts, Yields,
+};
+use crate::save::SettingsOverrides;
+use crate::time::{Calendar, Scheduler, TickTimings, next_multiple};
+use crate::zoom::{Bounds, MapBounds, Position, ZoomLevel};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use std::time::Duration;
 
 pub type EntityId = u64;
 
-#[derive(Debug, Clone)]
-pub struct GalaxyState {
-    pub name: String,
-    #[allow(dead_code)]
-    pub star_count: u64,
+/// Seed used when no other seed is supplied
+pub const DEFAULT_SEED: u64 = 0x00EC_0DE5_1500;
+
+/// First id handed out to entities created after the sample data
+const FIRST_DYNAMIC_ID: EntityId = 1000;
+
+/// Units of food below which a market counts as empty of it
+const FAMINE_STOCK: f64 = 1.0;
+
+/// Change in a market price since it was last reported that is worth
+/// reporting again
+const PRICE_MOVE_REPORTED: f64 = 0.05;
+
+/// How many spots around the player count as explored
+const SIGHT_RADIUS: i32 = 1;
+
+/// Local hour in regions at column 0 of their planet when the simulation
+/// starts, so the home region's market is open from the first tick. Local
+/// time runs an hour later for each column further east.
+const LOCAL_HOUR_AT_START: i64 = 8;
+
+/// Recurring upkeep the world schedules for itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Timer {
+    /// Move people towards better-off places
+    Migrate,
+    /// Record a day of economic trends
+    SampleStats,
+    /// The festival at this index in the world's list begins
+    FestivalBegins(usize),
+    /// Hyperlanes to a solar system that collapsed are open again
+    LanesReopen(EntityId),
+    /// Firms sign supply contracts for inputs they have none for
+    Negotiate,
+    /// The supply contract with this id makes its next delivery
+    Deliver(EntityId),
+    /// Weather passes, and may strike regions with fair weather
+    Weather,
+    /// Busy firms with money to spare put up new buildings
+    Expand,
+    /// Roll for an asteroid strike, plague, gold rush or breakthrough
+    RandomEvent,
+    /// The player's journey ends
+    Arrive,
 }
 
-#[derive(Debug, Clone)]
-pub struct SolarSystemState {
-    #[allow(dead_code)]
-    pub id: EntityId,
-    pub name: String,
-    #[allow(dead_code)]
-    pub planet_count: u32,
+impl Timer {
+    /// Daily upkeep first due a day after the start, and the first day of
+    /// each default festival
+    fn initial() -> Scheduler<Timer> {
+        let mut timers = Scheduler::new();
+        timers.schedule_at(migration::MIGRATION_INTERVAL, Timer::Migrate);
+        timers.schedule_at(stats::SAMPLE_INTERVAL, Timer::SampleStats);
+        timers.schedule_at(contracts::DELIVERY_INTERVAL, Timer::Negotiate);
+        timers.schedule_at(climate::WEATHER_INTERVAL, Timer::Weather);
+        timers.schedule_at(EXPANSION_INTERVAL, Timer::Expand);
+        timers.schedule_at(random_events::RANDOM_EVENT_INTERVAL, Timer::RandomEvent);
+        let calendar = Calendar::default();
+        for (index, festival) in Festival::defaults().iter().enumerate() {
+            timers.schedule_at(
+                festival.next_start(Duration::ZERO, &calendar),
+                Timer::FestivalBegins(index),
+            );
+        }
+        timers
+    }
 }
 
-#[derive(Debug, Clone)]
-pub struct PlanetState {
-    #[allow(dead_code)]
-    pub id: EntityId,
-    pub name: String,
-    #[allow(dead_code)]
-    pub population: u64,
+/// Where an entity sits on its parent's map
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Placement {
+    /// The entity one zoom level up; None for systems, which sit in the galaxy
+    pub parent: Option<EntityId>,
+    pub coords: (i32, i32),
 }
 
-#[derive(Debug, Clone)]
-pub struct RegionState {
-    #[allow(dead_code)]
-    pub id: EntityId,
-    pub name: String,
-    #[allow(dead_code)]
-    pub terrain_type: String,
+impl Placement {
+    pub fn new(parent: Option<EntityId>, coords: (i32, i32)) -> Self {
+        Self { parent, coords }
+    }
 }
 
-#[derive(Debug, Clone)]
-pub struct LocalAreaState {
-    #[allow(dead_code)]
-    pub id: EntityId,
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GalaxyState {
     pub name: String,
-    #[allow(dead_code)]
-    pub building_count: u32,
+    pub star_count: u64,
 }
 
+/// A detached copy of one entity, used to undo edits and to stamp copies.
+///
+/// An area carries its market, firms and characters so that removing it can
+/// be undone.
 #[derive(Debug, Clone)]
-pub struct RoomState {
-    #[allow(dead_code)]
-    pub id: EntityId,
-    pub name: String,
-    #[allow(dead_code)]
-    pub room_type: String,
+pub struct EntitySnapshot {
+    entity: Box<Entity>,
+    market: Option<Market>,
+    firms: Vec<Firm>,
+    characters: Vec<Character>,
+}
+
+impl EntitySnapshot {
+    pub fn level(&self) -> ZoomLevel {
+        self.entity.level
+    }
+
+    pub fn id(&self) -> EntityId {
+        self.entity.id
+    }
+
+    pub fn placement(&self) -> Placement {
+        self.entity.placement.unwrap_or_default()
+    }
+
+    /// Re-key the snapshot as a new entity at another spot. Economic state
+    /// is not copied; a stamped area starts without a market.
+    pub fn duplicate(&self, id: EntityId, placement: Placement) -> Self {
+        let mut entity = self.entity.clone();
+        entity.id = id;
+        entity.placement = Some(placement);
+        Self {
+            entity,
+            market: None,
+            firms: Vec::new(),
+            characters: Vec::new(),
+        }
+    }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorldState {
     tick_count: u64,
-    #[allow(dead_code)]
+    /// Total simulation time the world has been advanced by
+    elapsed: Duration,
+    seed: u64,
+    rng: Rng,
+    next_entity_id: EntityId,
+    events: EventLog,
+    /// Where the player is: the system, planet and region they last
+    /// arrived in
     player_position: Position,
+    /// The player's journey, while they are in transit
+    #[serde(default)]
+    journey: Option<Journey>,
+    /// A journey that has ended, until the view is taken to where it went
+    #[serde(default)]
+    arrival: Option<Journey>,
     galaxy: GalaxyState,
-    systems: HashMap<EntityId, SolarSystemState>,
-    planets: HashMap<EntityId, PlanetState>,
-    regions: HashMap<EntityId, RegionState>,
-    areas: HashMap<EntityId, LocalAreaState>,
-    rooms: HashMap<EntityId, RoomState>,
+    /// Every place below the galaxy
+    entities: EntityStore,
+    economy: Economy,
+    #[serde(default)]
+    stats: EconomyStats,
+    #[serde(default)]
+    schedule: Schedule,
+    #[serde(default)]
+    campaign: Option<CampaignProgress>,
+    #[serde(default)]
+    characters: BTreeMap<EntityId, Character>,
+    /// Nations and empires, and the planets and regions they govern
+    #[serde(default)]
+    polities: BTreeMap<EntityId, Polity>,
+    /// Ships in port or under way between planets
+    #[serde(default)]
+    ships: BTreeMap<EntityId, Ship>,
+    /// Supply contracts between firms with deliveries still to make
+    #[serde(default)]
+    contracts: BTreeMap<EntityId, SupplyContract>,
+    /// Regions having a drought or storm
+    #[serde(default)]
+    weather: BTreeMap<EntityId, Weather>,
+    #[serde(default)]
+    detail: DetailScheduler,
+    /// Upkeep due at future times
+    #[serde(default = "Timer::initial")]
+    timers: Scheduler<Timer>,
+    /// Yearly festivals and who celebrates them
+    #[serde(default = "Festival::defaults")]
+    festivals: Vec<Festival>,
+    /// Days of the year every market closes
+    #[serde(default = "Holiday::defaults")]
+    holidays: Vec<Holiday>,
+    /// Solar systems cut off from the rest of the galaxy by collapsed
+    /// hyperlanes
+    #[serde(default)]
+    isolated: BTreeSet<EntityId>,
+    /// Local areas whose markets have run out of food
+    #[serde(default)]
+    famished: BTreeSet<EntityId>,
+    /// Each market's prices as last reported in the log
+    #[serde(skip)]
+    reported_prices: BTreeMap<(EntityId, GoodId), f64>,
+    /// Settings this world changes from the player's profile
+    #[serde(default)]
+    settings: SettingsOverrides,
+    /// The preset's rules the world was started under
+    #[serde(default)]
+    rules: Rules,
+    /// Entities the player has pinned to keep an eye on, in pin order
+    #[serde(default)]
+    watchlist: Vec<(ZoomLevel, EntityId)>,
+    /// Views the player saved to jump back to
+    #[serde(default)]
+    bookmarks: Bookmarks,
+    /// Firms the player has founded
+    #[serde(default)]
+    business: Business,
+    /// Goods the player carries, bought and sold at market stalls
+    #[serde(default = "furniture::player_pack")]
+    pack: Inventory,
+    /// Values changed by hand through the inspector
+    #[serde(default)]
+    journal: Journal,
+    /// Spots the player has been near, by the level and owner of the map
+    #[serde(default)]
+    explored: BTreeSet<(ZoomLevel, Option<EntityId>, (i32, i32))>,
+    /// How far the cursor can move on each level's maps
+    #[serde(default)]
+    map_bounds: MapBounds,
+    /// Figures taken at the end of the latest tick
+    #[serde(skip)]
+    census: Census,
+    /// How long each system took during the latest tick
+    #[serde(skip)]
+    timings: TickTimings,
+    /// Alerts raised since they were last taken
+    #[serde(skip)]
+    alerts: Vec<Alert>,
+    /// Notices raised since they were last taken
+    #[serde(skip)]
+    notices: Vec<Notice>,
+    /// Entities spawned since the journal was last taken
+    #[serde(skip)]
+    spawned: Vec<(ZoomLevel, EntityId)>,
 }
 
 impl WorldState {
     pub fn new() -> Self {
-        let mut state = Self {
+        let mut state = Self::empty();
+        state.initialize_sample_data();
+        state.survey_terrain();
+        state.refresh_census();
+        state
+    }
+
+    /// A galaxy with nothing in it yet, for building a world up piece by
+    /// piece; see [`WorldStateBuilder`]
+    pub fn empty() -> Self {
+        Self {
             tick_count: 0,
+            elapsed: Duration::ZERO,
+            seed: DEFAULT_SEED,
+            rng: Rng::new(DEFAULT_SEED),
+            next_entity_id: FIRST_DYNAMIC_ID,
+            events: EventLog::new(),
             player_position: Position::new(),
+            journey: None,
+            arrival: None,
             galaxy: GalaxyState {
                 name: String::from("Andromeda Prime"),
                 star_count: 1_000_000_000,
             },
-            systems: HashMap::new(),
-            planets: HashMap::new(),
-            regions: HashMap::new(),
-            areas: HashMap::new(),
-            rooms: HashMap::new(),
-        };
+            entities: EntityStore::new(),
+            economy: Economy::new(),
+            stats: EconomyStats::new(),
+            schedule: Schedule::new(),
+            campaign: None,
+            characters: BTreeMap::new(),
+            polities: BTreeMap::new(),
+            ships: BTreeMap::new(),
+            contracts: BTreeMap::new(),
+            weather: BTreeMap::new(),
+            detail: DetailScheduler::new(),
+            timers: Timer::initial(),
+            festivals: Festival::defaults(),
+            holidays: Holiday::defaults(),
+            isolated: BTreeSet::new(),
+            famished: BTreeSet::new(),
+            reported_prices: BTreeMap::new(),
+            settings: SettingsOverrides::default(),
+            rules: Rules::default(),
+            watchlist: Vec::new(),
+            bookmarks: Bookmarks::new(),
+            business: Business::new(),
+            pack: furniture::player_pack(),
+            journal: Journal::new(),
+            explored: BTreeSet::new(),
+            map_bounds: MapBounds::default(),
+            census: Census::default(),
+            timings: TickTimings::default(),
+            alerts: Vec::new(),
+            notices: Vec::new(),
+            spawned: Vec::new(),
+        }
+    }
 
-        state.initialize_sample_data();
-        state
+    pub fn builder() -> WorldStateBuilder {
+        WorldStateBuilder::new()
     }
 
     fn initialize_sample_data(&mut self) {
-        self.systems.insert(
-            1,
-            SolarSystemState {
-                id: 1,
-                name: String::from("Sol System"),
-                planet_count: 8,
-            },
+        let entities = &mut self.entities;
+        entities.insert(
+            Entity::new(1, ZoomLevel::SolarSystem)
+                .named("Sol System")
+                .placed(Placement::default())
+                .with_system(SolarSystemState {
+                    planet_count: 8,
+                    star: StarClass::G,
+                }),
         );
-
-        self.planets.insert(
-            1,
-            PlanetState {
-                id: 1,
-                name: String::from("Terra"),
-                population: 7_800_000_000,
-            },
+        entities.insert(
+            Entity::new(1, ZoomLevel::Planet)
+                .named("Terra")
+                .placed(Placement::new(Some(1), (0, 0)))
+                .populated(7_800_000_000),
         );
-
-        self.regions.insert(
-            1,
-            RegionState {
-                id: 1,
-                name: String::from("Northern Highlands"),
-                terrain_type: String::from("Mountains"),
-            },
+        entities.insert(
+            Entity::new(1, ZoomLevel::Region)
+                .named("Northern Highlands")
+                .placed(Placement::new(Some(1), (0, 0)))
+                .with_region(RegionState {
+                    terrain_type: String::from("Mountains"),
+                    terrain: None,
+                    deposits: Some(vec![
+                        ResourceDeposit::new(DepositKind::OreVein, 600_000.0),
+                        ResourceDeposit::new(DepositKind::FertileLand, 1_500_000.0),
+                    ]),
+                    infrastructure: Money::ZERO,
+                }),
+        );
+        entities.insert(
+            Entity::new(1, ZoomLevel::LocalArea)
+                .named("Market District")
+                .placed(Placement::new(Some(1), (0, 0)))
+                .populated(60)
+                .with_area(LocalAreaState { building_count: 47 }),
         );
 
-        self.areas.insert(
-            1,
-            LocalAreaState {
-                id: 1,
-                name: String::from("Market District"),
-                building_count: 47,
-            },
+        let firms = [
+            (101, "Highland Farm", "grain farming", (-3, -1)),
+            (102, "Deepvein Mine", "ore mining", (-1, -1)),
+            (103, "Pinecrest Lumber", "logging", (1, -1)),
+            (104, "Market Bakery", "baking", (-3, 1)),
+            (105, "District Smelter", "smelting", (-1, 1)),
+            (106, "Anvil & Sons", "toolmaking", (1, 1)),
+        ];
+        for (id, name, recipe, coords) in firms {
+            if let Some(recipe) = self.economy.recipes().find(recipe) {
+                let mut firm = Firm::new(id, name, 1, recipe);
+                firm.coords = coords;
+                self.economy.add_firm(firm);
+            }
+        }
+
+        self.entities.insert(
+            Entity::new(1, ZoomLevel::Room)
+                .named("Trading Hall")
+                .placed(Placement::new(Some(1), (0, 0)))
+                .with_room(RoomState {
+                    room_type: String::from("Commercial"),
+                    furniture: vec![
+                        Furnishing::new(FurnitureKind::MarketStall, (-2, -1)),
+                        Furnishing::new(FurnitureKind::MarketStall, (2, -1)),
+                    ],
+                }),
         );
 
-        self.rooms.insert(
-            1,
-            RoomState {
-                id: 1,
-                name: String::from("Trading Hall"),
-                room_type: String::from("Commercial"),
+        let characters = [
+            (201, "Ada Fenwick", Some(1), 104),
+            (202, "Bram Holt", None, 101),
+            (203, "Cleo Marsh", Some(1), 106),
+        ];
+        for (id, name, room_id, employer) in characters {
+            let mut character = Character::new(id, name, 1);
+            character.room_id = room_id;
+            character.employer = Some(employer);
+            self.characters.insert(id, character);
+        }
+
+        let mut compact = Polity::new(
+            301,
+            "Highland Compact",
+            FiscalPolicy {
+                tax_rate: 0.1,
+                tariff: 0.05,
+                sales_tax: 0.02,
+                spending: 0.05,
             },
         );
+        compact.regions.insert(1);
+        self.polities.insert(compact.id, compact);
+
+        self.economy.endow_purse(STARTING_PURSE);
+        for level in [ZoomLevel::SolarSystem, ZoomLevel::Planet, ZoomLevel::Region] {
+            self.player_position.set_entity_id(level, Some(1));
+        }
     }
 
-    pub fn update(&mut self, _delta: Duration) {
+    pub fn update(&mut self, delta: Duration) {
         self.tick_count += 1;
+        self.elapsed += delta;
+        let _span = tracing::info_span!("update", tick = self.tick_count).entered();
+        let mut timings = TickTimings::start();
+
+        let calendar = Calendar::default();
+        let mut elapsed = Vec::new();
+        for (area_id, _) in self.economy.markets() {
+            let detail = self
+                .detail
+                .detail(&self.ancestry(ZoomLevel::LocalArea, area_id));
+            if let Some(area_elapsed) = self.detail.take_due(area_id, detail, delta) {
+                elapsed.push((area_id, area_elapsed));
+            }
+        }
+        // Working out each market's conditions only reads the world, so
+        // every market's is found at once
+        let due: BTreeMap<EntityId, MarketTick> = elapsed
+            .into_par_iter()
+            .map(|(area_id, elapsed)| (area_id, self.market_tick(area_id, elapsed, &calendar)))
+            .collect();
+
+        let entities = &self.entities;
+        self.economy.update_scheduled(
+            delta,
+            |area_id| workforce(entities, area_id),
+            |area_id| due.get(&area_id).copied(),
+        );
+        self.collect_taxes(due.keys().copied().collect());
+        timings.lap("markets");
+
+        self.fund_infrastructure(delta);
+        self.research(delta);
+        timings.lap("budgets");
+
+        for firm in self.economy.bank_mut().take_defaults() {
+            let name = self
+                .economy
+                .firm(firm)
+                .map_or_else(|| format!("Firm {}", firm), |firm| firm.name.clone());
+            self.notify(
+                Severity::Warning,
+                format!("{} defaulted on its bank loan", name),
+            );
+        }
+
+        self.open_finished_buildings();
+        timings.lap("construction");
+
+        self.deplete_deposits();
+        timings.lap("deposits");
+
+        self.sail();
+        timings.lap("ships");
+
+        let today = calendar.date_at(self.elapsed);
+        for (&area_id, tick) in &due {
+            let planet = self.planet_of(area_id);
+            let workers = workforce(&self.entities, area_id);
+            let hours = tick.elapsed.as_secs_f64() / 3600.0;
+            for festival in &self.festivals {
+                if festival.is_on(today) && festival.is_celebrated_on(planet) {
+                    festival.feast(&mut self.economy, area_id, workers, hours);
+                }
+            }
+        }
+
+        timings.lap("festivals");
+
+        self.watch_for_famine(due.keys().copied().collect());
+        timings.lap("famine");
+
+        self.watch_prices(due.keys().copied().collect());
+        timings.lap("prices");
+
+        // Prices are recorded once each hour is over
+        let finished = (self.elapsed.as_secs() / 3600).checked_sub(1);
+        if let Some(hour) = finished
+            && self
+                .economy
+                .history()
+                .last_hour()
+                .is_none_or(|last| hour > last)
+        {
+            self.economy.record_history(hour);
+        }
+        timings.lap("history");
+
+        // Characters keep to the same level of detail as their area's
+        // market, and hold on to their wares while it is shut
+        let grids: BTreeMap<EntityId, Grid> =
+            due.keys().map(|&id| (id, self.area_grid(id))).collect();
+        for character in self.characters.values_mut() {
+            if let (Some(tick), Some(grid)) =
+                (due.get(&character.area_id), grids.get(&character.area_id))
+            {
+                character.update(
+                    tick.elapsed.as_secs_f64() / 3600.0,
+                    &mut self.economy,
+                    tick.open_share > 0.0,
+                    grid,
+                );
+            }
+        }
+        timings.lap("characters");
+
+        for timer in self.timers.take_due(self.elapsed) {
+            let next = match timer {
+                Timer::Migrate => {
+                    self.migrate();
+                    Some(next_multiple(self.elapsed, migration::MIGRATION_INTERVAL))
+                }
+                Timer::SampleStats => {
+                    self.sample_stats();
+                    Some(next_multiple(self.elapsed, stats::SAMPLE_INTERVAL))
+                }
+                Timer::FestivalBegins(index) => self.begin_festival(index),
+                Timer::LanesReopen(system) => {
+                    self.isolated.remove(&system);
+                    let name = self.entity_name(ZoomLevel::SolarSystem, system);
+                    self.notify(Severity::Info, format!("Hyperlanes to {} reopen", name));
+                    None
+                }
+                Timer::Negotiate => {
+                    self.negotiate();
+                    Some(next_multiple(self.elapsed, contracts::DELIVERY_INTERVAL))
+                }
+                Timer::Deliver(id) => self.deliver(id),
+                Timer::Weather => {
+                    self.change_weather();
+                    Some(next_multiple(self.elapsed, climate::WEATHER_INTERVAL))
+                }
+                Timer::Expand => {
+                    self.expand_firms();
+                    Some(next_multiple(self.elapsed, EXPANSION_INTERVAL))
+                }
+                Timer::RandomEvent => {
+                    self.roll_random_event();
+                    Some(next_multiple(
+                        self.elapsed,
+                        random_events::RANDOM_EVENT_INTERVAL,
+                    ))
+                }
+                Timer::Arrive => {
+                    self.arrive();
+                    None
+                }
+            };
+            if let Some(at) = next {
+                self.timers.schedule_at(at, timer);
+            }
+        }
+        timings.lap("timers");
+
+        for event in self.schedule.take_due(self.elapsed) {
+            match event.apply(self) {
+                Ok(()) => {
+                    self.notify(Severity::Warning, event.message);
+                    self.alerts.push(Alert::ScriptedEvent);
+                }
+                Err(error) => self.log(format!("Skipped '{}': {}", event.message, error)),
+            }
+        }
+        timings.lap("events");
+
+        if let Some(mut campaign) = self.campaign.take() {
+            let messages = campaign.update(self, self.elapsed);
+            if !messages.is_empty() {
+                self.alerts.push(Alert::Objective);
+            }
+            for message in messages {
+                self.notify(Severity::Info, message);
+            }
+            self.campaign = Some(campaign);
+        }
+        timings.lap("campaign");
+
+        self.refresh_census();
+        timings.lap("census");
+        self.timings = timings;
     }
 
-    pub fn tick_count(&self) -> u64 {
-        self.tick_count
+    /// How long each system took during the latest tick
+    pub fn timings(&self) -> &TickTimings {
+        &self.timings
     }
 
-    #[allow(dead_code)]
-    pub fn player_position(&self) -> &Position {
-        &self.player_position
+    /// Totals for the galaxy and each place, as of the latest tick
+    pub fn census(&self) -> &Census {
+        &self.census
     }
 
-    #[allow(dead_code)]
-    pub fn galaxy(&self) -> &GalaxyState {
-        &self.galaxy
+    /// Take the census again now, to catch up with changes made between
+    /// ticks
+    pub fn refresh_census(&mut self) {
+        self.census = Census::take(self);
     }
 
-    pub fn get_system(&self, id: EntityId) -> Option<&SolarSystemState> {
-        self.systems.get(&id)
+    /// Create a character living in a local area. Returns None if there is
+    /// no such area.
+    pub fn add_character(&mut self, name: &str, area_id: EntityId) -> Option<EntityId> {
+        if !self.entities.contains(ZoomLevel::LocalArea, area_id) {
+            return None;
+        }
+        let id = self.allocate_id();
+        self.characters
+            .insert(id, Character::new(id, name, area_id));
+        Some(id)
     }
 
-    pub fn get_planet(&self, id: EntityId) -> Option<&PlanetState> {
-        self.planets.get(&id)
+    pub fn character(&self, id: EntityId) -> Option<&Character> {
+        self.characters.get(&id)
     }
 
-    pub fn get_region(&self, id: EntityId) -> Option<&RegionState> {
-        self.regions.get(&id)
+    pub fn character_mut(&mut self, id: EntityId) -> Option<&mut Character> {
+        self.characters.get_mut(&id)
     }
 
-    pub fn get_area(&self, id: EntityId) -> Option<&LocalAreaState> {
-        self.areas.get(&id)
+    /// Every character, in id order
+    pub fn characters(&self) -> impl Iterator<Item = &Character> {
+        self.characters.values()
     }
 
-    pub fn get_room(&self, id: EntityId) -> Option<&RoomState> {
-        self.rooms.get(&id)
+    /// Characters living in a local area, in id order
+    pub fn characters_in_area(&self, area_id: EntityId) -> impl Iterator<Item = &Character> {
+        self.characters
+            .values()
+            .filter(move |c| c.area_id == area_id)
     }
 
-    pub fn get_current_entity_name(&self, zoom_level: ZoomLevel) -> String {
-        match zoom_level {
-            ZoomLevel::Galaxy => self.galaxy.name.clone(),
-            ZoomLevel::SolarSystem => self
-                .get_system(1)
-                .map(|s| s.name.clone())
-                .unwrap_or_else(|| String::from("Unknown System")),
-            ZoomLevel::Planet => self
-                .get_planet(1)
-                .map(|p| p.name.clone())
-                .unwrap_or_else(|| String::from("Unknown Planet")),
-            ZoomLevel::Region => self
-                .get_region(1)
-                .map(|r| r.name.clone())
-                .unwrap_or_else(|| String::from("Unknown Region")),
-            ZoomLevel::LocalArea => self
-                .get_area(1)
-                .map(|a| a.name.clone())
-                .unwrap_or_else(|| String::from("Unknown Area")),
-            ZoomLevel::Room => self
-                .get_room(1)
-                .map(|r| r.name.clone())
-                .unwrap_or_else(|| String::from("Unknown Room")),
+    /// Characters spending their time in a room, in id order
+    pub fn characters_in_room(&self, room_id: EntityId) -> impl Iterator<Item = &Character> {
+        self.characters
+            .values()
+            .filter(move |c| c.room_id == Some(room_id))
+    }
+
+    pub fn business(&self) -> &Business {
+        &self.business
+    }
+
+    /// Goods the player carries
+    pub fn pack(&self) -> &Inventory {
+        &self.pack
+    }
+
+    /// The piece of furniture on a spot of a room's map
+    pub fn furnishing_at(&self, room: EntityId, coords: (i32, i32)) -> Option<Furnishing> {
+        self.get_room(room)?
+            .furniture
+            .iter()
+            .find(|piece| piece.coords == coords)
+            .copied()
+    }
+
+    /// Use the furniture on a spot of a room's map: trade a lot of a good
+    /// with the room's market at a stall, or be paid for a shift at a
+    /// workbench. Passing the hours a night's rest or a shift takes is up
+    /// to the caller. Returns what the player did.
+    pub fn interact(
+        &mut self,
+        room: EntityId,
+        coords: (i32, i32),
+        interaction: Interaction,
+    ) -> Result<String, InteractionError> {
+        let piece = self
+            .furnishing_at(room, coords)
+            .ok_or(InteractionError::NoFurniture)?;
+        if piece.kind != interaction.furniture() {
+            return Err(InteractionError::WrongFurniture(piece.kind.name()));
+        }
+        if interaction == Interaction::Rest {
+            return Ok(format!(
+                "You sleep in the bed for {} hours",
+                furniture::REST_HOURS
+            ));
+        }
+
+        let area = self
+            .placement_of(ZoomLevel::Room, room)
+            .and_then(|placement| placement.parent)
+            .ok_or(InteractionError::NoMarket)?;
+        let economy = &mut self.economy;
+        let market = economy.market(area).ok_or(InteractionError::NoMarket)?;
+        let name = |good: GoodId| {
+            economy
+                .catalog()
+                .get(good)
+                .map_or_else(String::new, |good| good.name.clone())
+        };
+        match interaction {
+            Interaction::Buy(good) => {
+                let (price, stock) = (market.price(good), market.stock(good));
+                let room_for = self.pack.room_for(good, economy.catalog()).floor();
+                let affordable = (economy.purse() / price).floor();
+                let lot = furniture::TRADE_LOT.min(room_for);
+                if lot < 1.0 {
+                    return Err(InteractionError::PackFull(name(good)));
+                }
+                if stock < 1.0 {
+                    return Err(InteractionError::OutOfStock(name(good)));
+                }
+                if affordable < 1.0 {
+                    return Err(InteractionError::CannotAfford(name(good)));
+                }
+                let lot = lot.min(stock.floor()).min(affordable);
+                let name = name(good);
+                let (bought, cost) = economy
+                    .buy_for_player(area, good, lot)
+                    .ok_or(InteractionError::NoMarket)?;
+                self.pack.add(good, bought, economy.catalog());
+                Ok(format!(
+                    "Bought {:.0} {} for {}",
+                    bought,
+                    name,
+                    economy.currency().format(cost)
+                ))
+            }
+            Interaction::Sell(good) => {
+                let held = self.pack.get(good).min(furniture::TRADE_LOT);
+                if held <= 0.0 {
+                    return Err(InteractionError::NothingToSell(name(good)));
+                }
+                let payable = (market.cash() / market.price(good)).floor();
+                if payable < 1.0 {
+                    return Err(InteractionError::MarketBroke(name(good)));
+                }
+                let name = name(good);
+                let (sold, paid) = economy
+                    .sell_for_player(area, good, held.min(payable))
+                    .ok_or(InteractionError::NoMarket)?;
+                self.pack.remove(good, sold);
+                Ok(format!(
+                    "Sold {:.0} {} for {}",
+                    sold,
+                    name,
+                    economy.currency().format(paid)
+                ))
+            }
+            Interaction::Work => {
+                let paid = economy
+                    .pay_player_wage(area, f64::from(furniture::SHIFT_HOURS))
+                    .ok_or(InteractionError::NoMarket)?;
+                Ok(format!(
+                    "You work an {}-hour shift at the workbench and earn {}",
+                    furniture::SHIFT_HOURS,
+                    economy.currency().format(paid)
+                ))
+            }
+            Interaction::Rest => unreachable!(),
         }
     }
 
-    pub fn entity_count(&self) -> usize {
-        1 + self.systems.len()
-            + self.planets.len()
-            + self.regions.len()
-            + self.areas.len()
-            + self.rooms.len()
+    /// Hand a firm to the player, counting the cash it holds now as their
+    /// capital, or take it back. Returns false if there is no such firm.
+    pub fn set_owned(&mut self, firm: EntityId, owned: bool) -> bool {
+        let Some(cash) = self.economy.firm(firm).map(|f| f.cash) else {
+            return false;
+        };
+        if owned && !self.business.owns(firm) {
+            self.business.add(firm, cash);
+        } else if !owned {
+            self.business.remove(firm);
+        }
+        true
     }
-}
 
-impl Default for WorldState {
-    fn default() -> Self {
-        Self::new()
+    pub fn journal(&self) -> &Journal {
+        &self.journal
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Note a value changed by hand in the journal and the event log
+    pub fn record_edit(
+        &mut self,
+        target: impl Into<String>,
+        field: impl Into<String>,
+        from: impl Into<String>,
+        to: impl Into<String>,
+    ) {
+        let entry = JournalEntry {
+            at: self.elapsed,
+            target: target.into(),
+            field: field.into(),
+            from: from.into(),
+            to: to.into(),
+        };
+        self.log(format!("Edited {}", entry));
+        self.journal.record(entry);
+    }
 
-    #[test]
-    fn test_world_state_initialization() {
-        let state = WorldState::new();
-        assert_eq!(state.tick_count(), 0);
-        assert_eq!(state.galaxy().name, "Andromeda Prime");
-        assert_eq!(state.entity_count(), 6);
+    /// Found a firm for the player in a local area, paid for from their
+    /// purse. It hires no more than `workers` and sells on the area's
+    /// market.
+    pub fn found_business(
+        &mut self,
+        area_id: EntityId,
+        name: &str,
+        recipe: &str,
+        workers: u32,
+        capital: Money,
+    ) -> Result<EntityId, BusinessError> {
+        let recipe_id = self.check_business(area_id, recipe, workers, capital)?;
+        let mut firm = Firm::new(self.next_id(), name, area_id, recipe_id);
+        firm.coords = self.free_firm_spot(area_id);
+        firm.max_workers = Some(workers);
+        let id = firm.id;
+        self.economy.invest(firm, capital);
+        self.business.add(id, capital);
+        Ok(id)
     }
 
-    #[test]
-    fn test_world_state_update() {
-        let mut state = WorldState::new();
-        state.update(Duration::from_secs(1));
-        assert_eq!(state.tick_count(), 1);
-        state.update(Duration::from_secs(1));
-        assert_eq!(state.tick_count(), 2);
+    /// Commission a building for the player in a local area, paid for
+    /// from their purse. It opens as a firm like [`WorldState::found_business`]
+    /// would found once its materials are bought and the work is done, with
+    /// what is left of `capital` as its cash. Returns the id the firm will
+    /// have.
+    pub fn commission_building(
+        &mut self,
+        area_id: EntityId,
+        name: &str,
+        recipe: &str,
+        workers: u32,
+        capital: Money,
+    ) -> Result<EntityId, BusinessError> {
+        let recipe_id = self.check_business(area_id, recipe, workers, capital)?;
+        let cost = self
+            .economy
+            .building_cost(area_id, recipe_id)
+            .unwrap_or_default();
+        if capital < cost {
+            return Err(BusinessError::CannotBuild { capital, cost });
+        }
+
+        let id = self.next_id();
+        let Some(mut site) = Site::new(
+            id,
+            name,
+            area_id,
+            recipe_id,
+            self.economy.recipes(),
+            self.economy.catalog(),
+        ) else {
+            return Err(BusinessError::UnknownRecipe(recipe.to_string()));
+        };
+        site.coords = self.free_firm_spot(area_id);
+        site.max_workers = Some(workers);
+        self.economy.commission(site, capital);
+        Ok(id)
     }
 
-    #[test]
-    fn test_sample_data_exists() {
-        let state = WorldState::new();
-        assert!(state.get_system(1).is_some());
-        assert!(state.get_planet(1).is_some());
-        assert!(state.get_region(1).is_some());
-        assert!(state.get_area(1).is_some());
-        assert!(state.get_room(1).is_some());
+    /// The recipe a player's firm would run, once the area, workers and
+    /// capital have been checked
+    fn check_business(
+        &self,
+        area_id: EntityId,
+        recipe: &str,
+        workers: u32,
+        capital: Money,
+    ) -> Result<RecipeId, BusinessError> {
+        if self.get_area(area_id).is_none() {
+            return Err(BusinessError::NotInArea);
+        }
+        let recipe_id = self
+            .economy
+            .recipes()
+            .find(recipe)
+            .ok_or_else(|| BusinessError::UnknownRecipe(recipe.to_string()))?;
+        if !self.has_unlocked(area_id, recipe_id) {
+            return Err(BusinessError::NotResearched(recipe.to_string()));
+        }
+        if workers == 0 {
+            return Err(BusinessError::NoWorkers);
+        }
+        let purse = self.economy.purse();
+        if capital > purse || capital < Money::ZERO {
+            return Err(BusinessError::NotEnoughCash { capital, purse });
+        }
+        Ok(recipe_id)
     }
 
-    #[test]
-    fn test_current_entity_name() {
-        let state = WorldState::new();
-        assert_eq!(
-            state.get_current_entity_name(ZoomLevel::Galaxy),
-            "Andromeda Prime"
-        );
-        assert_eq!(
-            state.get_current_entity_name(ZoomLevel::SolarSystem),
-            "Sol System"
-        );
-        assert_eq!(state.get_current_entity_name(ZoomLevel::Planet), "Terra");
-        assert_eq!(
-            state.get_current_entity_name(ZoomLevel::Region),
-            "Northern Highlands"
-        );
-        assert_eq!(
-            state.get_current_entity_name(ZoomLevel::LocalArea),
-            "Market District"
-        );
-        assert_eq!(
-            state.get_current_entity_name(ZoomLevel::Room),
-            "Trading Hall"
+    /// Hand the player the firms their finished buildings open as, and
+    /// announce every building finished
+    fn open_finished_buildings(&mut self) {
+        for site in self.economy.take_finished() {
+            let area = self.entity_name(ZoomLevel::LocalArea, site.area_id);
+            match site.builder {
+                Builder::Player => {
+                    self.business.add(site.id, site.budget);
+                    self.notify(
+                        Severity::Info,
+                        format!("{} is finished and open in {}", site.name, area),
+                    );
+                }
+                Builder::Firm(_) => {
+                    self.log_grouped("construction", format!("{} opens in {}", site.name, area))
+                }
+            }
+        }
+    }
+
+    /// Have each firm ready to grow put up another building like its own,
+    /// with [`EXPANSION_BUDGET`] from its cash
+    fn expand_firms(&mut self) {
+        let entities = &self.entities;
+        let expanding = self
+            .economy
+            .expansions(|area_id| workforce(entities, area_id));
+        for firm_id in expanding {
+            let Some(firm) = self.economy.firm(firm_id) else {
+                continue;
+            };
+            let (area_id, recipe, owner) = (firm.area_id, firm.recipe, firm.name.clone());
+            let Some(kind) = self.economy.recipes().get(recipe).map(|r| r.building) else {
+                continue;
+            };
+            let area = self.entity_name(ZoomLevel::LocalArea, area_id);
+            let id = self.next_id();
+            let name = format!("{} {}", area, kind);
+            let Some(mut site) = Site::new(
+                id,
+                &name,
+                area_id,
+                recipe,
+                self.economy.recipes(),
+                self.economy.catalog(),
+            ) else {
+                continue;
+            };
+            site.coords = self.free_firm_spot(area_id);
+            site.builder = Builder::Firm(firm_id);
+            if self.economy.commission(site, EXPANSION_BUDGET) {
+                let message = format!("{} is putting up a new {} in {}", owner, kind, area);
+                self.log_grouped("construction", message);
+            }
+        }
+    }
+
+    /// The first spot along an area's top row with no firm, building site
+    /// or room on it
+    pub fn free_firm_spot(&self, area_id: EntityId) -> (i32, i32) {
+        (0..)
+            .map(|x| (x, 0))
+            .find(|&coords| {
+                self.economy.firm_at(area_id, coords).is_none()
+                    && self.economy.site_at(area_id, coords).is_none()
+                    && self
+                        .entity_at(ZoomLevel::Room, Some(area_id), coords)
+                        .is_none()
+            })
+            .unwrap_or_default()
+    }
+
+    /// A local area's map with its firms, building sites and rooms walled
+    /// off
+    pub fn area_grid(&self, area_id: EntityId) -> Grid {
+        let mut grid = Grid::new();
+        for firm in self.economy.firms_in_area(area_id) {
+            grid.block(firm.coords);
+        }
+        for site in self.economy.sites_in_area(area_id) {
+            grid.block(site.coords);
+        }
+        for (_, coords) in self.placed_in(ZoomLevel::Room, Some(area_id)) {
+            grid.block(coords);
+        }
+        grid
+    }
+
+    pub fn bookmarks(&self) -> &Bookmarks {
+        &self.bookmarks
+    }
+
+    pub fn bookmarks_mut(&mut self) -> &mut Bookmarks {
+        &mut self.bookmarks
+    }
+
+    pub fn watchlist(&self) -> &[(ZoomLevel, EntityId)] {
+        &self.watchlist
+    }
+
+    /// Pin an entity to the watchlist, or unpin it if it is already there.
+    /// Returns whether it is now pinned.
+    pub fn toggle_watch(&mut self, level: ZoomLevel, id: EntityId) -> bool {
+        if let Some(index) = self
+            .watchlist
+            .iter()
+            .position(|&entry| entry == (level, id))
+        {
+            self.watchlist.remove(index);
+            false
+        } else {
+            self.watchlist.push((level, id));
+            true
+        }
+    }
+
+    /// The extent of every map at a level
+    pub fn map_bounds(&self, level: ZoomLevel) -> Bounds {
+        self.map_bounds.get(level)
+    }
+
+    pub fn set_map_bounds(&mut self, level: ZoomLevel, bounds: Bounds) {
+        self.map_bounds.set(level, bounds);
+    }
+
+    /// Mark the spots within sight of `coords` on a map as explored
+    pub fn explore(&mut self, level: ZoomLevel, owner: Option<EntityId>, coords: (i32, i32)) {
+        for dy in -SIGHT_RADIUS..=SIGHT_RADIUS {
+            for dx in -SIGHT_RADIUS..=SIGHT_RADIUS {
+                self.explored
+                    .insert((level, owner, (coords.0 + dx, coords.1 + dy)));
+            }
+        }
+    }
+
+    pub fn is_explored(
+        &self,
+        level: ZoomLevel,
+        owner: Option<EntityId>,
+        coords: (i32, i32),
+    ) -> bool {
+        self.explored.contains(&(level, owner, coords))
+    }
+
+    /// Alerts raised since the last call, oldest first
+    pub fn take_alerts(&mut self) -> Vec<Alert> {
+        std::mem::take(&mut self.alerts)
+    }
+
+    /// Log a message and also raise it as a notice for the player
+    pub fn notify(&mut self, severity: Severity, message: impl Into<String>) {
+        let message = message.into();
+        match severity {
+            Severity::Critical => tracing::error!("{}", message),
+            Severity::Warning => tracing::warn!("{}", message),
+            Severity::Info => tracing::info!("{}", message),
+        }
+        self.events.push(self.elapsed, message.clone());
+        self.notices.push(Notice { severity, message });
+    }
+
+    /// Notices raised since the last call, oldest first
+    pub fn take_notices(&mut self) -> Vec<Notice> {
+        std::mem::take(&mut self.notices)
+    }
+
+    pub fn settings(&self) -> &SettingsOverrides {
+        &self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: SettingsOverrides) {
+        self.settings = settings;
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// Play by a preset's rules from now on, replacing the player's purse
+    /// with its starting money
+    pub fn set_rules(&mut self, rules: Rules) {
+        self.economy.replace_purse(rules.starting_money);
+        self.economy.set_volatility(rules.volatility);
+        self.rules = rules;
+    }
+
+    /// Scale the chance of random events from now on, leaving the rest of
+    /// the rules alone
+    pub fn set_event_frequency(&mut self, frequency: f64) {
+        self.rules.event_frequency = frequency.max(0.0);
+    }
+
+    /// Simulate markets near an entity in full detail and those further away
+    /// at coarser intervals
+    pub fn set_focus(&mut self, level: ZoomLevel, id: EntityId) {
+        let ancestry = self.ancestry(level, id);
+        self.detail.set_focus(ancestry);
+    }
+
+    pub fn detail(&self) -> &DetailScheduler {
+        &self.detail
+    }
+
+    pub fn detail_mut(&mut self) -> &mut DetailScheduler {
+        &mut self.detail
+    }
+
+    /// Open the first chapter of a campaign, replacing any in progress
+    pub fn start_campaign(&mut self, campaign: Campaign) {
+        self.campaign = Some(CampaignProgress::start(campaign, self.elapsed));
+    }
+
+    pub fn campaign(&self) -> Option<&CampaignProgress> {
+        self.campaign.as_ref()
+    }
+
+    /// Next chapter screen waiting to be shown
+    pub fn take_interstitial(&mut self) -> Option<Interstitial> {
+        self.campaign.as_mut()?.take_interstitial()
+    }
+
+    /// Queue an authored event to fire once the world reaches `at`
+    pub fn schedule(&mut self, at: Duration, event: ScriptedEvent) {
+        self.schedule.schedule_at(at, event);
+    }
+
+    /// Number of scheduled events still to fire
+    pub fn pending_events(&self) -> usize {
+        self.schedule.len()
+    }
+
+    /// Record a day of figures for every place with markets or residents,
+    /// rolled up from local areas and planets to the galaxy. The harvest is
+    /// what the season and weather make of farm output, averaged over
+    /// markets like prices. Population is
+    /// the area workforce at region level and below, and planet population
+    /// above it.
+    fn sample_stats(&mut self) {
+        #[derive(Default)]
+        struct Tally {
+            price_total: f64,
+            harvest_total: f64,
+            markets: u32,
+            gdp: f64,
+            population: f64,
+        }
+
+        let season = self.season();
+
+        let mut tallies: BTreeMap<(ZoomLevel, EntityId), Tally> = BTreeMap::new();
+
+        let market_ids: Vec<EntityId> = self.economy.markets().map(|(id, _)| id).collect();
+        for area_id in market_ids {
+            let gdp = self.economy.take_output(area_id).amount();
+            let economy = &self.economy;
+            let price_index = economy
+                .market(area_id)
+                .map_or(1.0, |m| m.price_index(economy.catalog()));
+            let weather = self
+                .placement_of(ZoomLevel::LocalArea, area_id)
+                .and_then(|area| self.weather(area.parent?))
+                .map(|weather| weather.kind);
+            let harvest = climate::apply(season, weather, Yields::EVEN).farming;
+            for scope in self.ancestry(ZoomLevel::LocalArea, area_id) {
+                let tally = tallies.entry(scope).or_default();
+                tally.price_total += price_index;
+                tally.harvest_total += harvest;
+                tally.markets += 1;
+                tally.gdp += gdp;
+            }
+        }
+
+        for (area_id, people) in self.entities.populated.at(ZoomLevel::LocalArea) {
+            for scope in self.ancestry(ZoomLevel::LocalArea, area_id) {
+                if scope.0 <= ZoomLevel::Region {
+                    tallies.entry(scope).or_default().population += people.population as f64;
+                }
+            }
+        }
+        for (planet_id, people) in self.entities.populated.at(ZoomLevel::Planet) {
+            for scope in self.ancestry(ZoomLevel::Planet, planet_id) {
+                tallies.entry(scope).or_default().population += people.population as f64;
+            }
+        }
+
+        self.stats.record(tallies.into_iter().map(|(scope, tally)| {
+            let sample = Sample {
+                price_index: (tally.markets > 0).then(|| tally.price_total / tally.markets as f64),
+                harvest: (tally.markets > 0).then(|| tally.harvest_total / tally.markets as f64),
+                gdp: tally.gdp,
+                population: tally.population,
+            };
+            (scope, sample)
+        }));
+        self.economy.record_banking();
+    }
+
+    fn foods(&self) -> Vec<GoodId> {
+        self.economy
+            .catalog()
+            .iter()
+            .filter(|(_, good)| good.category == GoodCategory::Food)
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Raise a notice when an area's market runs out of every food, and
+    /// another once some is back on its shelves
+    fn watch_for_famine(&mut self, areas: Vec<EntityId>) {
+        let foods = self.foods();
+        if foods.is_empty() {
+            return;
+        }
+        for area_id in areas {
+            let Some(market) = self.economy.market(area_id) else {
+                continue;
+            };
+            let empty = foods.iter().all(|&good| market.stock(good) < FAMINE_STOCK);
+            let name = self.entity_name(ZoomLevel::LocalArea, area_id);
+            if empty && self.famished.insert(area_id) {
+                self.notify(Severity::Critical, format!("Famine in {}", name));
+            } else if !empty && self.famished.remove(&area_id) {
+                self.notify(Severity::Info, format!("Food is back on sale in {}", name));
+            }
+        }
+    }
+
+    /// Log each price that has moved far enough since it was last logged.
+    /// Prices move every tick, so these are grouped to be shown as one
+    /// line a day when the game runs fast.
+    fn watch_prices(&mut self, areas: Vec<EntityId>) {
+        for area_id in areas {
+            let Some(market) = self.economy.market(area_id) else {
+                continue;
+            };
+            let mut moves = Vec::new();
+            for (good_id, good) in self.economy.catalog().iter() {
+                let price = market.price(good_id).amount();
+                let reported = *self
+                    .reported_prices
+                    .entry((area_id, good_id))
+                    .or_insert(price);
+                if reported <= 0.0 || (price / reported - 1.0).abs() < PRICE_MOVE_REPORTED {
+                    continue;
+                }
+                self.reported_prices.insert((area_id, good_id), price);
+                moves.push((good.name.clone(), price, price / reported - 1.0));
+            }
+
+            let name = self.entity_name(ZoomLevel::LocalArea, area_id);
+            for (good, price, change) in moves {
+                let direction = if change > 0.0 { "rose" } else { "fell" };
+                let price = self.economy.currency().format(Money(price));
+                self.log_grouped(
+                    format!("Price of {} {} in {}", good, direction, name),
+                    format!(
+                        "Price of {} {} {:.0}% to {} in {}",
+                        good,
+                        direction,
+                        change.abs() * 100.0,
+                        price,
+                        name
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Move a day's worth of people towards better-off areas and planets,
+    /// see [`Migration`]
+    fn migrate(&mut self) {
+        let foods = self.foods();
+        let economy = &self.economy;
+        let mut areas = Vec::new();
+        for (area_id, market) in economy.markets() {
+            let Some(area) = self.entities.areas.get(ZoomLevel::LocalArea, area_id) else {
+                continue;
+            };
+            let food_price = foods
+                .iter()
+                .map(|&id| market.price(id).amount())
+                .filter(|&price| price > 0.0)
+                .min_by(f64::total_cmp)
+                .unwrap_or(0.0);
+            let planet = self.planet_of(area_id);
+            areas.push(migration::Area {
+                id: area_id,
+                planet,
+                appeal: migration::Appeal {
+                    wage: market.wage().amount(),
+                    food_price,
+                    workforce: workforce(&self.entities, area_id),
+                    buildings: area.building_count,
+                },
+            });
+        }
+        // Nobody emigrates to or from a planet whose system is cut off
+        let planets = self
+            .entities
+            .populated
+            .at(ZoomLevel::Planet)
+            .filter(|&(planet, _)| {
+                self.system_of(planet)
+                    .is_none_or(|system| !self.isolated.contains(&system))
+            })
+            .map(|(planet, people)| (planet, people.population))
+            .collect();
+
+        for Move {
+            level,
+            from,
+            to,
+            people,
+        } in migration::plan(&areas, &planets)
+        {
+            let (Some(left), Some(arrived)) =
+                (self.population(level, from), self.population(level, to))
+            else {
+                continue;
+            };
+            let people = people.min(left);
+            self.set_population(level, from, left - people);
+            self.set_population(level, to, arrived + people);
+            let (from, to) = (self.entity_name(level, from), self.entity_name(level, to));
+            match level {
+                ZoomLevel::LocalArea => self.log_grouped(
+                    format!("Workers moved from {} to {}", from, to),
+                    format!("{} workers moved from {} to {}", people, from, to),
+                ),
+                _ => self.log_grouped(
+                    format!("People emigrated from {} to {}", from, to),
+                    format!("{} people emigrated from {} to {}", people, from, to),
+                ),
+            }
+        }
+    }
+
+    /// Daily economic history, see [`EconomyStats`]
+    pub fn stats(&self) -> &EconomyStats {
+        &self.stats
+    }
+
+    /// An entity followed by each of its parents, ending with the galaxy
+    pub fn ancestry(&self, level: ZoomLevel, id: EntityId) -> Vec<(ZoomLevel, EntityId)> {
+        let mut chain = Vec::new();
+        let mut current = Some((level, id));
+        while let Some((level, id)) = current {
+            if level == ZoomLevel::Galaxy {
+                break;
+            }
+            chain.push((level, id));
+            current = level.zoom_out().zip(
+                self.placement_of(level, id)
+                    .and_then(|placement| placement.parent),
+            );
+        }
+        chain.push((ZoomLevel::Galaxy, GALAXY_ID));
+        chain
+    }
+
+    pub fn tick_count(&self) -> u64 {
+        self.tick_count
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Local time in an area, which runs ahead of simulation time by an
+    /// hour for every column its region sits east of its planet's first
+    pub fn local_time(&self, area_id: EntityId) -> Duration {
+        let column = self
+            .placement_of(ZoomLevel::LocalArea, area_id)
+            .and_then(|area| area.parent)
+            .and_then(|region| self.placement_of(ZoomLevel::Region, region))
+            .map_or(0, |region| region.coords.0);
+        let offset = (LOCAL_HOUR_AT_START + i64::from(column)).rem_euclid(24) as u64;
+        self.elapsed + Duration::from_secs(offset * 3600)
+    }
+
+    /// The planet a local area is on, if it is placed on one
+    fn planet_of(&self, area_id: EntityId) -> Option<EntityId> {
+        self.ancestry(ZoomLevel::LocalArea, area_id)
+            .into_iter()
+            .find(|(level, _)| *level == ZoomLevel::Planet)
+            .map(|(_, id)| id)
+    }
+
+    /// The solar system a planet is in, if it is placed in one
+    fn system_of(&self, planet_id: EntityId) -> Option<EntityId> {
+        self.placement_of(ZoomLevel::Planet, planet_id)
+            .and_then(|placement| placement.parent)
+    }
+
+    /// Cut a solar system off from the rest of the galaxy until `duration`
+    /// has passed, replacing when any earlier collapse would have ended.
+    /// Returns false if there is no such system.
+    pub fn collapse_lanes(&mut self, system: EntityId, duration: Duration) -> bool {
+        if !self.entities.contains(ZoomLevel::SolarSystem, system) {
+            return false;
+        }
+        self.isolated.insert(system);
+        self.timers
+            .retain(|timer| *timer != Timer::LanesReopen(system));
+        self.timers
+            .schedule_in(self.elapsed, duration, Timer::LanesReopen(system));
+        true
+    }
+
+    /// Whether a solar system's hyperlanes have collapsed
+    pub fn is_isolated(&self, system: EntityId) -> bool {
+        self.isolated.contains(&system)
+    }
+
+    pub fn festivals(&self) -> &[Festival] {
+        &self.festivals
+    }
+
+    /// Festivals under way today on a planet
+    pub fn festivals_on(&self, planet: EntityId) -> impl Iterator<Item = &Festival> {
+        let today = Calendar::default().date_at(self.elapsed);
+        self.festivals
+            .iter()
+            .filter(move |f| f.is_on(today) && f.is_celebrated_on(Some(planet)))
+    }
+
+    /// Announce a festival on every planet that celebrates it, returning
+    /// when it next begins. A festival whose start was missed, as when a
+    /// save from before it existed is loaded, begins quietly.
+    fn begin_festival(&mut self, index: usize) -> Option<Duration> {
+        let calendar = Calendar::default();
+        let festival = self.festivals.get(index)?.clone();
+        if festival.is_on(calendar.date_at(self.elapsed)) {
+            let message = match festival.planet {
+                Some(planet) => format!(
+                    "{} begins on {}",
+                    festival.name,
+                    self.entity_name(ZoomLevel::Planet, planet)
+                ),
+                None => format!("{} begins across the galaxy", festival.name),
+            };
+            self.notify(Severity::Info, message);
+        }
+        Some(festival.next_start(self.elapsed, &calendar))
+    }
+
+    pub fn holidays(&self) -> &[Holiday] {
+        &self.holidays
+    }
+
+    pub fn set_holidays(&mut self, holidays: Vec<Holiday>) {
+        self.holidays = holidays;
+    }
+
+    /// Whether an area's market is trading right now. None if the area has
+    /// no market.
+    pub fn market_status(&self, area_id: EntityId) -> Option<MarketStatus> {
+        let market = self.economy.market(area_id)?;
+        Some(market.hours().status(
+            self.local_time(area_id),
+            &Calendar::default(),
+            &self.holidays,
+        ))
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Restart the world's random stream from a new seed
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = Rng::new(seed);
+        for (_, region) in self.entities.regions.at_mut(ZoomLevel::Region) {
+            region.terrain = None;
+        }
+        self.survey_terrain();
+    }
+
+    /// The noise a planet's surface is generated from
+    pub fn surface(&self, planet: EntityId) -> Surface {
+        Surface::new(self.seed, planet)
+    }
+
+    /// Survey every region that has no terrain map yet, such as those in
+    /// worlds saved before terrain was generated, and prospect every region
+    /// that has not been
+    pub fn survey_terrain(&mut self) {
+        let regions = &self.entities.regions;
+        let unsurveyed: Vec<EntityId> = regions
+            .at(ZoomLevel::Region)
+            .filter(|(_, region)| region.terrain.is_none())
+            .map(|(id, _)| id)
+            .collect();
+        for id in unsurveyed {
+            self.survey_region(id);
+        }
+
+        let regions = &self.entities.regions;
+        let unprospected: Vec<EntityId> = regions
+            .at(ZoomLevel::Region)
+            .filter(|(_, region)| region.deposits.is_none())
+            .map(|(id, _)| id)
+            .collect();
+        for id in unprospected {
+            self.prospect(id, 1.0);
+        }
+    }
+
+    /// Find a region's deposits afresh from its terrain, replacing any it
+    /// had. `abundance` scales their size. The same seed and terrain always
+    /// find the same deposits.
+    pub fn prospect(&mut self, region: EntityId, abundance: f64) {
+        let mut rng = Rng::new(self.seed ^ region.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        if let Some(state) = self.get_region_mut(region) {
+            state.deposits = Some(
+                state
+                    .terrain
+                    .as_ref()
+                    .map(|terrain| deposits::prospect(terrain, &mut rng, abundance))
+                    .unwrap_or_default(),
+            );
+        }
+    }
+
+    /// Change a region's terrain and survey its surface again to match
+    pub fn set_terrain(&mut self, region: EntityId, terrain_type: &str) {
+        if let Some(state) = self.get_region_mut(region) {
+            state.terrain_type = terrain_type.to_string();
+            self.survey_region(region);
+        }
+    }
+
+    fn survey_region(&mut self, id: EntityId) {
+        let seed = self.seed;
+        let placement = self.placement_of(ZoomLevel::Region, id).unwrap_or_default();
+        if let Some(region) = self.get_region_mut(id) {
+            let surface = Surface::new(seed, placement.parent.unwrap_or(GALAXY_ID));
+            let terrain = Biome::from_terrain(&region.terrain_type);
+            region.terrain = Some(TerrainMap::generate(&surface, placement.coords, terrain));
+        }
+    }
+
+    /// Every polity, in id order
+    pub fn polities(&self) -> impl Iterator<Item = &Polity> {
+        self.polities.values()
+    }
+
+    pub fn polity(&self, id: EntityId) -> Option<&Polity> {
+        self.polities.get(&id)
+    }
+
+    pub fn polity_mut(&mut self, id: EntityId) -> Option<&mut Polity> {
+        self.polities.get_mut(&id)
+    }
+
+    /// Found a polity that holds nothing yet
+    pub fn found_polity(&mut self, name: &str, policy: FiscalPolicy) -> EntityId {
+        let id = self.allocate_id();
+        self.polities.insert(id, Polity::new(id, name, policy));
+        id
+    }
+
+    /// Hand a planet or region to a polity, taking it from whoever held it.
+    /// A region taken from a polity that held its planet whole leaves that
+    /// polity the planet's other regions. Returns false for an unknown
+    /// polity or place, or a place at another level.
+    pub fn claim(&mut self, polity: EntityId, level: ZoomLevel, id: EntityId) -> bool {
+        if !self.polities.contains_key(&polity) || self.placement_of(level, id).is_none() {
+            return false;
+        }
+        let regions_on = |planet: EntityId| -> Vec<EntityId> {
+            self.placed_in(ZoomLevel::Region, Some(planet))
+                .into_iter()
+                .map(|(region, _)| region)
+                .collect()
+        };
+        match level {
+            ZoomLevel::Planet => {
+                let regions = regions_on(id);
+                for other in self.polities.values_mut() {
+                    other.planets.remove(&id);
+                    other.regions.retain(|region| !regions.contains(region));
+                }
+                if let Some(holder) = self.polities.get_mut(&polity) {
+                    holder.planets.insert(id);
+                }
+            }
+            ZoomLevel::Region => {
+                let planet = self
+                    .placement_of(ZoomLevel::Region, id)
+                    .and_then(|placement| placement.parent);
+                let siblings = planet.map(regions_on).unwrap_or_default();
+                for other in self.polities.values_mut() {
+                    other.regions.remove(&id);
+                    if let Some(planet) = planet
+                        && other.planets.remove(&planet)
+                    {
+                        other
+                            .regions
+                            .extend(siblings.iter().filter(|&&region| region != id));
+                    }
+                }
+                if let Some(holder) = self.polities.get_mut(&polity) {
+                    holder.regions.insert(id);
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Change a polity's taxes. Returns false for an unknown polity or
+    /// rates out of range.
+    pub fn set_fiscal_policy(&mut self, polity: EntityId, policy: FiscalPolicy) -> bool {
+        match self.polities.get_mut(&polity) {
+            Some(polity) if Polity::is_valid_policy(policy) => {
+                polity.policy = policy;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The polity governing a place, if any. A solar system counts as
+    /// governed by whoever governs the first of its planets, or failing
+    /// that the first of their regions, that anyone governs.
+    pub fn polity_of(&self, level: ZoomLevel, id: EntityId) -> Option<&Polity> {
+        let children = |level: ZoomLevel, parent: EntityId| {
+            let mut ids: Vec<EntityId> = self
+                .placed_in(level, Some(parent))
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect();
+            ids.sort_unstable();
+            ids
+        };
+        match level {
+            ZoomLevel::Galaxy => None,
+            ZoomLevel::SolarSystem => {
+                let planets = children(ZoomLevel::Planet, id);
+                planets
+                    .iter()
+                    .find_map(|&planet| self.polity_of(ZoomLevel::Planet, planet))
+                    .or_else(|| {
+                        planets.iter().find_map(|&planet| {
+                            children(ZoomLevel::Region, planet)
+                                .into_iter()
+                                .find_map(|region| self.polity_of(ZoomLevel::Region, region))
+                        })
+                    })
+            }
+            ZoomLevel::Planet => self
+                .polities
+                .values()
+                .find(|polity| polity.planets.contains(&id)),
+            ZoomLevel::Region => {
+                let planet = self
+                    .placement_of(ZoomLevel::Region, id)
+                    .and_then(|placement| placement.parent);
+                self.polities
+                    .values()
+                    .find(|polity| polity.governs(id, planet))
+            }
+            ZoomLevel::LocalArea | ZoomLevel::Room => self
+                .ancestry(level, id)
+                .into_iter()
+                .find(|(level, _)| *level == ZoomLevel::Region)
+                .and_then(|(_, region)| self.polity_of(ZoomLevel::Region, region)),
+        }
+    }
+
+    /// How an area's market advances over `elapsed`: how long it trades
+    /// and what the land, weather, government and infrastructure there
+    /// make of it
+    fn market_tick(&self, area_id: EntityId, elapsed: Duration, calendar: &Calendar) -> MarketTick {
+        let open_share = self.economy.market(area_id).map_or(0.0, |market| {
+            market
+                .hours()
+                .open_share(self.local_time(area_id), elapsed, calendar, &self.holidays)
+        });
+        MarketTick {
+            elapsed,
+            open_share,
+            yields: self.yields(area_id),
+            policy: self.fiscal_policy(area_id),
+            infrastructure: self.infrastructure(area_id),
+            technology: self.technology(area_id),
+        }
+    }
+
+    /// Taxes levied on an area's market by the polity governing it
+    pub fn fiscal_policy(&self, area_id: EntityId) -> FiscalPolicy {
+        self.polity_of(ZoomLevel::LocalArea, area_id)
+            .map_or(FiscalPolicy::NONE, |polity| polity.policy)
+    }
+
+    /// Move the tax paid in each area into the budget of the polity
+    /// governing it
+    fn collect_taxes(&mut self, areas: Vec<EntityId>) {
+        for area_id in areas {
+            let governor = self.polity_of(ZoomLevel::LocalArea, area_id).map(|p| p.id);
+            let taxes = self.economy.levy(area_id, governor);
+            if let Some(polity) = governor.and_then(|id| self.polities.get_mut(&id)) {
+                polity.collected += taxes;
+            }
+        }
+    }
+
+    /// Each polity spends its daily share of its budget on infrastructure,
+    /// split evenly across the markets it governs. The money goes to the
+    /// households there; the region keeps what was built.
+    fn fund_infrastructure(&mut self, delta: Duration) {
+        let days = delta.as_secs_f64() / 86_400.0;
+        for (polity_id, areas) in self.governed_markets() {
+            let Some(polity) = self.polities.get(&polity_id) else {
+                continue;
+            };
+            let share = (polity.policy.spending * days).clamp(0.0, 1.0);
+            let each = self.economy.treasury(polity_id) * share / areas.len() as f64;
+            if each <= Money::ZERO {
+                continue;
+            }
+            let mut spent = Money::ZERO;
+            for area_id in areas {
+                let paid = self.economy.spend_treasury(polity_id, area_id, each);
+                let region = self
+                    .placement_of(ZoomLevel::LocalArea, area_id)
+                    .and_then(|area| area.parent);
+                if let Some(region) = region.and_then(|id| self.get_region_mut(id)) {
+                    region.infrastructure += paid;
+                }
+                spent += paid;
+            }
+            if let Some(polity) = self.polities.get_mut(&polity_id) {
+                polity.spent += spent;
+            }
+        }
+    }
+
+    /// The markets each polity governs, by the polity's id
+    fn governed_markets(&self) -> BTreeMap<EntityId, Vec<EntityId>> {
+        let mut governed: BTreeMap<EntityId, Vec<EntityId>> = BTreeMap::new();
+        for (area_id, _) in self.economy.markets() {
+            if let Some(polity) = self.polity_of(ZoomLevel::LocalArea, area_id) {
+                governed.entry(polity.id).or_default().push(area_id);
+            }
+        }
+        governed
+    }
+
+    /// Each polity puts [`polities::RESEARCH_PER_MARKET`] days of research
+    /// a day into its next technology for every market it governs, and
+    /// announces what it discovers
+    fn research(&mut self, delta: Duration) {
+        let days = delta.as_secs_f64() / 86_400.0;
+        let mut discoveries = Vec::new();
+        for (polity_id, areas) in self.governed_markets() {
+            let Some(polity) = self.polities.get_mut(&polity_id) else {
+                continue;
+            };
+            let done = days * polities::RESEARCH_PER_MARKET * areas.len() as f64;
+            for tech in polity.research.advance(self.economy.techs(), done) {
+                discoveries.push((polity.name.clone(), tech));
+            }
+        }
+
+        for (polity, tech) in discoveries {
+            let techs = self.economy.techs();
+            let Some(tech) = techs.get(tech) else {
+                continue;
+            };
+            let unlocks: Vec<&str> = tech
+                .unlocks
+                .iter()
+                .filter_map(|&recipe| self.economy.recipes().get(recipe))
+                .map(|recipe| recipe.name.as_str())
+                .collect();
+            let message = if unlocks.is_empty() {
+                format!("{} discovers {}", polity, tech.name)
+            } else {
+                format!(
+                    "{} discovers {}; its firms can now take up {}",
+                    polity,
+                    tech.name,
+                    unlocks.join(", ")
+                )
+            };
+            self.notify(Severity::Info, message);
+        }
+    }
+
+    /// Multipliers on the output of an area's firms from the technologies
+    /// known by the polity governing it
+    pub fn technology(&self, area_id: EntityId) -> TechBoosts {
+        self.polity_of(ZoomLevel::LocalArea, area_id)
+            .map_or(TechBoosts::NONE, |polity| {
+                polity.research.boosts(self.economy.techs())
+            })
+    }
+
+    /// Whether firms in an area can take up a recipe: ungoverned areas only
+    /// have the recipes no technology unlocks
+    pub fn has_unlocked(&self, area_id: EntityId, recipe: RecipeId) -> bool {
+        let techs = self.economy.techs();
+        match self.polity_of(ZoomLevel::LocalArea, area_id) {
+            Some(polity) => polity.research.has_unlocked(techs, recipe),
+            None => techs.unlocking(recipe).next().is_none(),
+        }
+    }
+
+    /// Multiplier on the productivity of an area's firms from the
+    /// infrastructure built in its region
+    pub fn infrastructure(&self, area_id: EntityId) -> f64 {
+        self.placement_of(ZoomLevel::LocalArea, area_id)
+            .and_then(|area| area.parent)
+            .and_then(|region| self.get_region(region))
+            .map_or(1.0, |region| {
+                polities::infrastructure_boost(region.infrastructure)
+            })
+    }
+
+    /// Every ship, in id order
+    pub fn ships(&self) -> impl Iterator<Item = &Ship> {
+        self.ships.values()
+    }
+
+    pub fn ship(&self, id: EntityId) -> Option<&Ship> {
+        self.ships.get(&id)
+    }
+
+    pub fn ship_mut(&mut self, id: EntityId) -> Option<&mut Ship> {
+        self.ships.get_mut(&id)
+    }
+
+    /// Put a new ship in port at a planet, returning its id, or None if
+    /// there is no such planet
+    pub fn launch_ship(&mut self, planet: EntityId) -> Option<EntityId> {
+        if !self.entities.contains(ZoomLevel::Planet, planet) {
+            return None;
+        }
+        let id = self.allocate_id();
+        let mut ship = Ship::new(id, &format!("Ship {}", id), planet);
+        ship.course = Course::Docked {
+            planet,
+            until: self.elapsed,
+        };
+        self.ships.insert(id, ship);
+        Some(id)
+    }
+
+    /// How far apart two planets are in system map cells, counting each
+    /// cell between their systems as [`ships::INTERSTELLAR_SCALE`] of them.
+    /// None if either planet isn't placed in a placed system.
+    pub fn planet_distance(&self, from: EntityId, to: EntityId) -> Option<f64> {
+        let from = self.placement_of(ZoomLevel::Planet, from)?;
+        let to = self.placement_of(ZoomLevel::Planet, to)?;
+        if from.parent == to.parent {
+            return Some(ships::distance(from.coords, to.coords));
+        }
+        let system = |parent: Option<EntityId>| {
+            self.placement_of(ZoomLevel::SolarSystem, parent?)
+                .map(|placement| placement.coords)
+        };
+        let hop = ships::distance(system(from.parent)?, system(to.parent)?);
+        Some(
+            ships::distance(from.coords, (0, 0))
+                + hop * ships::INTERSTELLAR_SCALE
+                + ships::distance((0, 0), to.coords),
+        )
+    }
+
+    /// Whether a ship can sail between two planets: both are placed in
+    /// systems, and a voyage between systems needs both of their
+    /// hyperlanes open
+    fn is_reachable(&self, from: EntityId, to: EntityId) -> bool {
+        match (self.system_of(from), self.system_of(to)) {
+            (Some(a), Some(b)) if a == b => true,
+            (Some(a), Some(b)) => !self.is_isolated(a) && !self.is_isolated(b),
+            _ => false,
+        }
+    }
+
+    /// Ships under way across a map, with the cell each has reached: ships
+    /// between systems on the galaxy map, and ships between the planets of
+    /// a system on its map
+    pub fn ships_on_map(
+        &self,
+        level: ZoomLevel,
+        owner: Option<EntityId>,
+    ) -> Vec<(EntityId, (i32, i32))> {
+        let coords = |level: ZoomLevel, id: EntityId| {
+            self.placement_of(level, id)
+                .map(|placement| placement.coords)
+        };
+        self.ships
+            .values()
+            .filter_map(|ship| {
+                let voyage = ship.voyage()?;
+                let (from, to) = (self.system_of(voyage.from)?, self.system_of(voyage.to)?);
+                let ends = match level {
+                    ZoomLevel::Galaxy if from != to => (
+                        coords(ZoomLevel::SolarSystem, from)?,
+                        coords(ZoomLevel::SolarSystem, to)?,
+                    ),
+                    ZoomLevel::SolarSystem if from == to && owner == Some(from) => (
+                        coords(ZoomLevel::Planet, voyage.from)?,
+                        coords(ZoomLevel::Planet, voyage.to)?,
+                    ),
+                    _ => return None,
+                };
+                let progress = voyage.progress(self.elapsed);
+                Some((ship.id, ships::between(ends.0, ends.1, progress)))
+            })
+            .collect()
+    }
+
+    /// Bring in ships whose voyages are over and send off those ready to
+    /// sail again
+    fn sail(&mut self) {
+        let ready: Vec<(EntityId, Course)> = self
+            .ships
+            .values()
+            .map(|ship| (ship.id, ship.course))
+            .collect();
+        for (id, course) in ready {
+            match course {
+                Course::Underway(voyage) if voyage.arrives <= self.elapsed => {
+                    self.dock(id, voyage.to);
+                }
+                Course::Docked { planet, until } if until <= self.elapsed => {
+                    self.set_sail(id, planet);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Unload a ship at the end of its voyage. The buying market pays the
+    /// selling one for the cargo, as far as its cash goes.
+    fn dock(&mut self, id: EntityId, planet: EntityId) {
+        let until = self.elapsed + ships::PORT_TIME;
+        let Some(ship) = self.ships.get_mut(&id) else {
+            return;
+        };
+        ship.course = Course::Docked { planet, until };
+        let cargo = ship.cargo.take();
+        let passengers = std::mem::take(&mut ship.passengers);
+        let name = ship.name.clone();
+
+        if let Some(people) = self.entities.populated.get_mut(ZoomLevel::Planet, planet) {
+            people.population += passengers;
+        }
+        let Some(cargo) = cargo else {
+            return;
+        };
+        let Some(buyer) = self.economy.market_mut(cargo.buyer) else {
+            return;
+        };
+        buyer.add(cargo.good, cargo.quantity);
+        if self.economy.market(cargo.seller).is_some() {
+            let paid = self
+                .economy
+                .market_mut(cargo.buyer)
+                .map_or(Money::ZERO, |buyer| buyer.withdraw(cargo.value));
+            if let Some(seller) = self.economy.market_mut(cargo.seller) {
+                seller.deposit(paid);
+            }
+        }
+        let good = self
+            .economy
+            .catalog()
+            .get(cargo.good)
+            .map_or_else(String::new, |good| good.name.clone());
+        let message = format!(
+            "{} docks at {} with {:.0} {}",
+            name,
+            self.entity_name(ZoomLevel::Planet, planet),
+            cargo.quantity,
+            good
+        );
+        self.log_grouped("shipping", message);
+    }
+
+    /// Load a docked ship with the best paying cargo it can reach and send
+    /// it off with whoever wants passage, or keep it in port a while longer
+    /// if no trade is worth making
+    fn set_sail(&mut self, id: EntityId, planet: EntityId) {
+        let Some(ship) = self.ships.get(&id) else {
+            return;
+        };
+        let catalog = self.economy.catalog();
+        let mut listings = Vec::new();
+        for (area, market) in self.economy.markets() {
+            let Some(at) = self.planet_of(area) else {
+                continue;
+            };
+            if at != planet && !self.is_reachable(planet, at) {
+                continue;
+            }
+            listings.extend(catalog.iter().map(|(good, _)| Listing {
+                area,
+                planet: at,
+                good,
+                price: market.price(good),
+                stock: market.stock(good),
+            }));
+        }
+        let room = |good| {
+            catalog
+                .get(good)
+                .map_or(0.0, |good| ship.hold.units_of(good))
+        };
+        let trade = ships::best_trade(planet, room, &listings).and_then(|cargo| {
+            let to = self.planet_of(cargo.buyer)?;
+            let distance = self.planet_distance(planet, to)?;
+            Some((cargo, to, ship.travel_time(distance)))
+        });
+        let Some((mut cargo, to, travel)) = trade else {
+            let until = self.elapsed + ships::PORT_TIME;
+            if let Some(ship) = self.ships.get_mut(&id) {
+                ship.course = Course::Docked { planet, until };
+            }
+            return;
+        };
+
+        if let Some(seller) = self.economy.market_mut(cargo.seller) {
+            let loaded = seller.sell(cargo.good, cargo.quantity);
+            cargo.value = cargo.value * (loaded / cargo.quantity);
+            cargo.quantity = loaded;
+        }
+        let populated = &mut self.entities.populated;
+        let passengers = populated
+            .get_mut(ZoomLevel::Planet, planet)
+            .map_or(0, |people| {
+                let boarding = ship.boarding(people.population);
+                people.population -= boarding;
+                boarding
+            });
+        let voyage = Voyage {
+            from: planet,
+            to,
+            departed: self.elapsed,
+            arrives: self.elapsed + travel,
+        };
+        if let Some(ship) = self.ships.get_mut(&id) {
+            ship.course = Course::Underway(voyage);
+            ship.cargo = Some(cargo);
+            ship.passengers = passengers;
+        }
+    }
+
+    /// Supply contracts in force, oldest first
+    pub fn contracts(&self) -> impl Iterator<Item = &SupplyContract> {
+        self.contracts.values()
+    }
+
+    pub fn contract(&self, id: EntityId) -> Option<&SupplyContract> {
+        self.contracts.get(&id)
+    }
+
+    /// Contracts a firm supplies or buys under
+    pub fn contracts_of(&self, firm: EntityId) -> impl Iterator<Item = &SupplyContract> {
+        self.contracts
+            .values()
+            .filter(move |contract| contract.involves(firm))
+    }
+
+    /// Sign a supply contract for a number of deliveries, the first due a
+    /// [`contracts::DELIVERY_INTERVAL`] from now
+    pub fn sign_contract(
+        &mut self,
+        terms: Terms,
+        deliveries: u32,
+    ) -> Result<EntityId, ContractError> {
+        if terms.supplier == terms.buyer {
+            return Err(ContractError::SameFirm);
+        }
+        let firm = |id| self.economy.firm(id).ok_or(ContractError::UnknownFirm(id));
+        let (supplier, buyer) = (firm(terms.supplier)?, firm(terms.buyer)?);
+        let makes = self
+            .economy
+            .recipes()
+            .get(supplier.recipe)
+            .is_some_and(|recipe| recipe.outputs.iter().any(|&(good, _)| good == terms.good));
+        if !makes {
+            return Err(ContractError::NotMade);
+        }
+        if !self.same_planet(supplier.area_id, buyer.area_id) {
+            return Err(ContractError::TooFar);
+        }
+        if !terms.is_valid() || deliveries == 0 {
+            return Err(ContractError::BadTerms);
+        }
+
+        let id = self.allocate_id();
+        self.contracts
+            .insert(id, SupplyContract::new(id, terms, deliveries));
+        self.timers.schedule_at(
+            self.elapsed + contracts::DELIVERY_INTERVAL,
+            Timer::Deliver(id),
+        );
+        Ok(id)
+    }
+
+    /// Whether two local areas are on the same planet
+    fn same_planet(&self, a: EntityId, b: EntityId) -> bool {
+        let planet = self.planet_of(a);
+        planet.is_some() && planet == self.planet_of(b)
+    }
+
+    /// Sign every contract firms want for inputs they have none for
+    fn negotiate(&mut self) {
+        let deals = contracts::negotiate(
+            &self.economy,
+            |a, b| self.same_planet(a, b),
+            |buyer, good| {
+                self.contracts_of(buyer)
+                    .any(|c| c.terms.buyer == buyer && c.terms.good == good)
+            },
+        );
+        for terms in deals {
+            if self
+                .sign_contract(terms, contracts::CONTRACT_DELIVERIES)
+                .is_err()
+            {
+                continue;
+            }
+            let name = |id| self.economy.firm(id).map_or("", |firm| firm.name.as_str());
+            let message = format!(
+                "{} contracts {} for {:.0} {} a day at {}",
+                name(terms.buyer),
+                name(terms.supplier),
+                terms.quantity,
+                self.economy
+                    .catalog()
+                    .get(terms.good)
+                    .map_or("goods", |good| good.name.as_str()),
+                self.economy.currency().format(terms.price)
+            );
+            self.log_grouped("contracts", message);
+        }
+    }
+
+    /// Make a contract's next delivery, returning when the one after is
+    /// due. A breached or completed contract ends.
+    fn deliver(&mut self, id: EntityId) -> Option<Duration> {
+        let mut contract = self.contracts.remove(&id)?;
+        let result = contract.deliver(&mut self.economy);
+        let terms = contract.terms;
+        let name = |id| self.economy.firm(id).map(|firm| firm.name.clone());
+        match result {
+            Ok(_) if !contract.is_complete() => {
+                self.contracts.insert(id, contract);
+                return Some(self.elapsed + contracts::DELIVERY_INTERVAL);
+            }
+            Ok(_) => {
+                let message = format!(
+                    "{} fulfils its contract with {}",
+                    name(terms.supplier).unwrap_or_default(),
+                    name(terms.buyer).unwrap_or_default()
+                );
+                self.log_grouped("contracts", message);
+            }
+            // Nobody is left to mind a contract with a closed firm
+            Err(contracts::Breach::Closed) => {}
+            Err(breach) => {
+                let message = format!(
+                    "Contract between {} and {} is broken: {}",
+                    name(terms.supplier).unwrap_or_default(),
+                    name(terms.buyer).unwrap_or_default(),
+                    breach
+                );
+                self.notify(Severity::Warning, message);
+            }
+        }
+        None
+    }
+
+    /// How the land around a local area, the season and the region's
+    /// weather suit its resource buildings
+    pub fn yields(&self, area_id: EntityId) -> Yields {
+        let region = self
+            .placement_of(ZoomLevel::LocalArea, area_id)
+            .and_then(|area| area.parent);
+        let land = region.map_or(Yields::EVEN, |region| self.region_yields(region));
+        let weather = region.and_then(|region| self.weather(region));
+        climate::apply(self.season(), weather.map(|w| w.kind), land)
+    }
+
+    /// The season it is now
+    pub fn season(&self) -> Season {
+        Season::of(Calendar::default().date_at(self.elapsed))
+    }
+
+    /// The drought or storm a region is having, if any
+    pub fn weather(&self, region: EntityId) -> Option<&Weather> {
+        self.weather.get(&region)
+    }
+
+    /// Strike a region with weather lasting `duration`, replacing any it
+    /// was having. Returns false if there is no such region.
+    pub fn set_weather(&mut self, region: EntityId, kind: WeatherKind, duration: Duration) -> bool {
+        if !self.entities.contains(ZoomLevel::Region, region) {
+            return false;
+        }
+        let until = self.elapsed + duration;
+        self.weather.insert(region, Weather { kind, until });
+        true
+    }
+
+    /// Let weather that has run its course pass, then roll for new weather
+    /// in every region with fair skies. Each region's roll depends only on
+    /// the seed and the day, so it doesn't disturb the world's other
+    /// random draws.
+    fn change_weather(&mut self) {
+        let now = self.elapsed;
+        let passed: Vec<(EntityId, WeatherKind)> = self
+            .weather
+            .iter()
+            .filter(|(_, weather)| weather.until <= now)
+            .map(|(&region, weather)| (region, weather.kind))
+            .collect();
+        for (region, kind) in passed {
+            self.weather.remove(&region);
+            let name = self.entity_name(ZoomLevel::Region, region);
+            self.log_grouped("weather", format!("The {} in {} passes", kind, name));
+        }
+
+        let season = self.season();
+        let day = Calendar::default().days_elapsed(now);
+        let regions: Vec<EntityId> = self.entities.ids(ZoomLevel::Region).collect();
+        for region in regions {
+            if self.weather.contains_key(&region) {
+                continue;
+            }
+            let mut rng = Rng::new(
+                self.seed ^ region.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ day.rotate_left(32),
+            );
+            let Some((kind, days)) = climate::roll(season, self.rules.disaster_frequency, &mut rng)
+            else {
+                continue;
+            };
+            self.set_weather(region, kind, Calendar::DEFAULT_DAY_LENGTH * days as u32);
+            let name = self.entity_name(ZoomLevel::Region, region);
+            let spell = match days {
+                1 => String::from("a day"),
+                days => format!("{} days", days),
+            };
+            let message = match kind {
+                WeatherKind::Drought => format!("Drought grips {} for {}", name, spell),
+                WeatherKind::Storm => format!("Storms lash {} for {}", name, spell),
+            };
+            self.notify(Severity::Warning, message);
+        }
+    }
+
+    /// Roll for the day's random event and strike with it. The roll
+    /// depends only on the seed and the day, like the weather's.
+    fn roll_random_event(&mut self) {
+        let day = Calendar::default().days_elapsed(self.elapsed);
+        let mut rng = Rng::new(self.seed ^ 0xD1B5_4A32_D192_ED03 ^ day.rotate_left(32));
+        let Some(event) = random_events::roll(self.rules.event_frequency, &mut rng) else {
+            return;
+        };
+        if let Some((severity, message)) = event.strike(self, &mut rng) {
+            self.notify(severity, message);
+        }
+    }
+
+    /// How a region's land, and what is left of its deposits, suit
+    /// resource buildings
+    pub fn region_yields(&self, region: EntityId) -> Yields {
+        let Some(region) = self.get_region(region) else {
+            return Yields::EVEN;
+        };
+        let land = region
+            .terrain
+            .as_ref()
+            .map_or(Yields::EVEN, TerrainMap::yields);
+        match &region.deposits {
+            Some(found) => deposits::apply(found, land),
+            None => land,
+        }
+    }
+
+    /// Draw down the deposits under every extraction building that worked
+    /// this tick, warning as each thins and when it runs out
+    fn deplete_deposits(&mut self) {
+        let mut extracted: BTreeMap<(EntityId, DepositKind), f64> = BTreeMap::new();
+        for firm in self.economy.firms().filter(|firm| firm.runs > 0.0) {
+            let Some(recipe) = self.economy.recipes().get(firm.recipe) else {
+                continue;
+            };
+            let Some(kind) = DepositKind::worked_by(recipe.building) else {
+                continue;
+            };
+            let Some(region) = self
+                .placement_of(ZoomLevel::LocalArea, firm.area_id)
+                .and_then(|area| area.parent)
+            else {
+                continue;
+            };
+            let made: f64 = recipe.outputs.iter().map(|&(_, qty)| qty * firm.runs).sum();
+            *extracted.entry((region, kind)).or_default() += made;
+        }
+
+        for ((region, kind), amount) in extracted {
+            let name = self.entity_name(ZoomLevel::Region, region);
+            let Some(state) = self.get_region_mut(region) else {
+                continue;
+            };
+            let Some(deposit) = state
+                .deposits
+                .iter_mut()
+                .flatten()
+                .find(|deposit| deposit.kind == kind)
+            else {
+                continue;
+            };
+            let (was_thinning, was_exhausted) = (deposit.is_thinning(), deposit.is_exhausted());
+            deposit.extract(amount);
+            let news = if deposit.is_exhausted() && !was_exhausted {
+                "is exhausted"
+            } else if deposit.is_thinning() && !was_thinning {
+                "is running thin"
+            } else {
+                continue;
+            };
+            let message = format!("The {} in {} {}", kind, name, news);
+            self.notify(Severity::Warning, message);
+        }
+    }
+
+    pub fn events(&self) -> &EventLog {
+        &self.events
+    }
+
+    /// Record an event at the current simulation time
+    pub fn log(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        tracing::debug!("{}", message);
+        self.events.push(self.elapsed, message);
+    }
+
+    /// Record an event that may be shown merged with others of its group;
+    /// see [`EventLog::coalesced`]
+    pub fn log_grouped(&mut self, group: impl Into<String>, message: impl Into<String>) {
+        let message = message.into();
+        tracing::debug!("{}", message);
+        self.events.push_grouped(self.elapsed, group, message);
+    }
+
+    fn allocate_id(&mut self) -> EntityId {
+        let id = self.next_entity_id;
+        self.next_entity_id += 1;
+        id
+    }
+
+    /// Create a new entity at the given level with randomized attributes.
+    /// Returns None for the galaxy, which cannot be spawned.
+    pub fn spawn(&mut self, level: ZoomLevel) -> Option<EntityId> {
+        self.spawn_at(level, Placement::default())
+    }
+
+    /// Like [`WorldState::spawn`], but placed on a specific spot of its
+    /// parent's map
+    pub fn spawn_at(&mut self, level: ZoomLevel, placement: Placement) -> Option<EntityId> {
+        if level == ZoomLevel::Galaxy {
+            return None;
+        }
+
+        let id = self.allocate_id();
+        let entity = Entity::new(id, level).placed(placement);
+        let entity = match level {
+            ZoomLevel::Galaxy => unreachable!(),
+            ZoomLevel::SolarSystem => {
+                let planet_count = self.rng.range(1, 12) as u32;
+                let star = StarClass::pick(&mut self.rng);
+                entity
+                    .named(format!("System {}", id))
+                    .with_system(SolarSystemState { planet_count, star })
+            }
+            ZoomLevel::Planet => {
+                let population = self.rng.range(0, 10_000_000_000);
+                entity.named(format!("Planet {}", id)).populated(population)
+            }
+            ZoomLevel::Region => {
+                let terrain = ["Plains", "Mountains", "Forest", "Desert", "Coast"];
+                let terrain_type = self.rng.choose(&terrain).unwrap_or(&"Plains");
+                entity
+                    .named(format!("Region {}", id))
+                    .with_region(RegionState {
+                        terrain_type: terrain_type.to_string(),
+                        terrain: None,
+                        deposits: None,
+                        infrastructure: Money::ZERO,
+                    })
+            }
+            ZoomLevel::LocalArea => {
+                let building_count = self.rng.range(1, 100) as u32;
+                let workforce = self.rng.range(10, 200);
+                entity
+                    .named(format!("Area {}", id))
+                    .populated(workforce)
+                    .with_area(LocalAreaState { building_count })
+            }
+            ZoomLevel::Room => {
+                let kinds = ["Commercial", "Residential", "Industrial", "Storage"];
+                let room_type = self.rng.choose(&kinds).unwrap_or(&"Storage");
+                let bounds = self.map_bounds(ZoomLevel::Room);
+                let furniture = furniture::furnish(room_type, bounds, &mut self.rng);
+                entity.named(format!("Room {}", id)).with_room(RoomState {
+                    room_type: room_type.to_string(),
+                    furniture,
+                })
+            }
+        };
+        self.entities.insert(entity);
+        if level == ZoomLevel::Region {
+            self.survey_region(id);
+        }
+
+        self.spawned.push((level, id));
+        Some(id)
+    }
+
+    /// Entities spawned since the last call, oldest first
+    pub fn take_spawned(&mut self) -> Vec<(ZoomLevel, EntityId)> {
+        std::mem::take(&mut self.spawned)
+    }
+
+    /// Allocate an id for a record kept outside the world's entity maps,
+    /// such as a firm
+    pub fn next_id(&mut self) -> EntityId {
+        self.allocate_id()
+    }
+
+    /// Delete an entity. Removing an area also closes its market and firms.
+    /// Children are left in place and become unreachable until re-parented.
+    pub fn remove_entity(&mut self, level: ZoomLevel, id: EntityId) -> bool {
+        self.stats.forget(level, id);
+        self.watchlist.retain(|&entry| entry != (level, id));
+        let Some(entity) = self.entities.remove(level, id) else {
+            return false;
+        };
+        if entity.market_host.is_some() {
+            self.economy.close_market(id);
+            self.detail.forget(id);
+            self.characters.retain(|_, c| c.area_id != id);
+        }
+        match level {
+            ZoomLevel::Planet => {
+                // Ships in its port or bound to or from it are lost with it
+                self.ships.retain(|_, ship| match ship.course {
+                    Course::Docked { planet, .. } => planet != id,
+                    Course::Underway(voyage) => voyage.from != id && voyage.to != id,
+                });
+            }
+            ZoomLevel::Region => {
+                self.weather.remove(&id);
+            }
+            ZoomLevel::Room => {
+                for character in self.characters.values_mut() {
+                    if character.room_id == Some(id) {
+                        character.room_id = None;
+                    }
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
+    /// Copy an entity, including a market host's market and firms
+    pub fn snapshot(&self, level: ZoomLevel, id: EntityId) -> Option<EntitySnapshot> {
+        let entity = self.entities.get(level, id)?;
+        let hosted = entity.market_host.is_some();
+        Some(EntitySnapshot {
+            market: self.economy.market(id).filter(|_| hosted).cloned(),
+            firms: match hosted {
+                true => self.economy.firms_in_area(id).cloned().collect(),
+                false => Vec::new(),
+            },
+            characters: match hosted {
+                true => self.characters_in_area(id).cloned().collect(),
+                false => Vec::new(),
+            },
+            entity: Box::new(entity),
+        })
+    }
+
+    /// Put a snapshot back under its own id, replacing any entity there.
+    /// An area's market and firms are reopened with their saved balances.
+    pub fn restore(&mut self, snapshot: EntitySnapshot) {
+        let id = snapshot.id();
+        if let Some(market) = snapshot.market {
+            self.economy.restore_market(id, market);
+        }
+        for firm in snapshot.firms {
+            self.economy.restore_firm(firm);
+        }
+        for character in snapshot.characters {
+            self.characters.insert(character.id, character);
+        }
+        self.entities.insert(*snapshot.entity);
+    }
+
+    /// Replace an entity's own attributes with a snapshot's, leaving an
+    /// area's market and firms as they are
+    pub fn restore_attributes(&mut self, snapshot: EntitySnapshot) {
+        self.entities.insert(*snapshot.entity);
+    }
+
+    /// Entities of a level placed on the given parent's map, in id order
+    pub fn placed_in(
+        &self,
+        level: ZoomLevel,
+        parent: Option<EntityId>,
+    ) -> Vec<(EntityId, (i32, i32))> {
+        self.entities.placed_in(level, parent)
+    }
+
+    /// Ids of every entity at a level, in ascending order
+    pub fn ids(&self, level: ZoomLevel) -> Vec<EntityId> {
+        self.entities.ids(level).collect()
+    }
+
+    /// People living on a planet, or working in a local area or across a
+    /// region's areas
+    pub fn population(&self, level: ZoomLevel, id: EntityId) -> Option<u64> {
+        let populated = &self.entities.populated;
+        match level {
+            ZoomLevel::Region => self.entities.contains(level, id).then(|| {
+                self.entities
+                    .placed_in(ZoomLevel::LocalArea, Some(id))
+                    .into_iter()
+                    .filter_map(|(area, _)| populated.get(ZoomLevel::LocalArea, area))
+                    .map(|people| people.population)
+                    .sum()
+            }),
+            level => populated.get(level, id).map(|people| people.population),
+        }
+    }
+
+    /// Change a planet's population or an area's workforce, or scale the
+    /// workforce of a region's areas to a new total. Returns false for any
+    /// other kind of entity.
+    pub fn set_population(&mut self, level: ZoomLevel, id: EntityId, population: u64) -> bool {
+        if level != ZoomLevel::Region {
+            return self
+                .entities
+                .populated
+                .get_mut(level, id)
+                .map(|people| people.population = population)
+                .is_some();
+        }
+
+        let Some(current) = self.population(level, id) else {
+            return false;
+        };
+        let scale = if current > 0 {
+            population as f64 / current as f64
+        } else {
+            0.0
+        };
+        let areas = self.entities.placed_in(ZoomLevel::LocalArea, Some(id));
+        for (area, _) in areas {
+            if let Some(people) = self.entities.populated.get_mut(ZoomLevel::LocalArea, area) {
+                people.population = (people.population as f64 * scale).round() as u64;
+            }
+        }
+        true
+    }
+
+    /// Where an entity sits on its parent's map
+    pub fn placement_of(&self, level: ZoomLevel, id: EntityId) -> Option<Placement> {
+        self.entities.placements.get(level, id).copied()
+    }
+
+    /// The entity of a level occupying a spot on its parent's map
+    pub fn entity_at(
+        &self,
+        level: ZoomLevel,
+        parent: Option<EntityId>,
+        coords: (i32, i32),
+    ) -> Option<EntityId> {
+        self.placed_in(level, parent)
+            .into_iter()
+            .find(|&(_, c)| c == coords)
+            .map(|(id, _)| id)
+    }
+
+    /// Find an entity by name, ignoring case, preferring the outermost.
+    /// The galaxy is reported with id 0.
+    pub fn find_entity(&self, name: &str) -> Option<(ZoomLevel, EntityId)> {
+        if self.galaxy.name.eq_ignore_ascii_case(name) {
+            return Some((ZoomLevel::Galaxy, 0));
+        }
+        self.entities.find(name)
+    }
+
+    pub fn player_position(&self) -> &Position {
+        &self.player_position
+    }
+
+    /// The player's journey, while they are in transit
+    pub fn journey(&self) -> Option<&Journey> {
+        self.journey.as_ref()
+    }
+
+    /// The journey that has just ended, if one has since this was last
+    /// called
+    pub fn take_arrival(&mut self) -> Option<Journey> {
+        self.arrival.take()
+    }
+
+    /// Set out for a place at `level`. A place where the player already is
+    /// needs no journey: they are there at once and this returns None.
+    /// Otherwise the player pays any fare to the port they sail from, or
+    /// the one they sail to if theirs has no market, and is in transit
+    /// until the journey returned arrives.
+    pub fn depart(
+        &mut self,
+        level: ZoomLevel,
+        id: EntityId,
+    ) -> Result<Option<Journey>, TravelError> {
+        let destination = self.entity_name(level, id);
+        if let Some(journey) = &self.journey {
+            return Err(TravelError::InTransit(journey.destination.clone()));
+        }
+        let mut bound_for = Position::new();
+        for (at, entity) in self.ancestry(level, id) {
+            if matches!(
+                at,
+                ZoomLevel::SolarSystem | ZoomLevel::Planet | ZoomLevel::Region
+            ) {
+                bound_for.set_entity_id(at, Some(entity));
+            }
+        }
+        let Some(route) = Route::between(&self.player_position, &bound_for) else {
+            if self.player_position.current_system_id.is_none() {
+                self.player_position = bound_for;
+            }
+            return Ok(None);
+        };
+
+        let from = self.player_position;
+        if route == Route::Interstellar
+            && let Some(closed) = [from.current_system_id, bound_for.current_system_id]
+                .into_iter()
+                .flatten()
+                .find(|&system| self.is_isolated(system))
+        {
+            let name = self.entity_name(ZoomLevel::SolarSystem, closed);
+            return Err(TravelError::LanesClosed(name));
+        }
+        let distance = self
+            .journey_distance(&from, &bound_for, route)
+            .ok_or_else(|| TravelError::Unreachable(destination.clone()))?;
+        let fare = route.fare(distance);
+        if fare > Money::ZERO {
+            if fare > self.economy.purse() {
+                let cost = self.economy.currency().format(fare);
+                return Err(TravelError::CannotAfford(cost));
+            }
+            let port = [from.current_planet_id, bound_for.current_planet_id]
+                .into_iter()
+                .flatten()
+                .find_map(|planet| self.port_of(planet))
+                .ok_or(TravelError::NoPort)?;
+            self.economy.pay_from_purse(port, fare);
+        }
+
+        let journey = Journey {
+            level,
+            to: id,
+            destination,
+            route,
+            bound_for,
+            fare,
+            departed: self.elapsed,
+            arrives: self.elapsed + route.duration(distance),
+        };
+        self.timers.schedule_at(journey.arrives, Timer::Arrive);
+        self.journey = Some(journey.clone());
+        Ok(Some(journey))
+    }
+
+    /// End the player's journey where it was bound
+    fn arrive(&mut self) {
+        let Some(journey) = self.journey.take() else {
+            return;
+        };
+        self.player_position = journey.bound_for;
+        self.notify(
+            Severity::Info,
+            format!("You arrive at {}", journey.destination),
+        );
+        self.arrival = Some(journey);
+    }
+
+    /// Map cells between two of the player's whereabouts along a route:
+    /// system map cells by ship, counting each galaxy cell between systems
+    /// as [`ships::INTERSTELLAR_SCALE`] of them, and planet map cells
+    /// overland. A place not yet chosen at a level counts as the center
+    /// of its parent's map.
+    fn journey_distance(&self, from: &Position, to: &Position, route: Route) -> Option<f64> {
+        let coords = |position: &Position, level: ZoomLevel| match position.current_entity_id(level)
+        {
+            Some(id) => self
+                .placement_of(level, id)
+                .map(|placement| placement.coords),
+            None => Some((0, 0)),
+        };
+        Some(match route {
+            Route::Interstellar => {
+                let hop = ships::distance(
+                    coords(from, ZoomLevel::SolarSystem)?,
+                    coords(to, ZoomLevel::SolarSystem)?,
+                );
+                ships::distance(coords(from, ZoomLevel::Planet)?, (0, 0))
+                    + hop * ships::INTERSTELLAR_SCALE
+                    + ships::distance((0, 0), coords(to, ZoomLevel::Planet)?)
+            }
+            Route::Interplanetary => ships::distance(
+                coords(from, ZoomLevel::Planet)?,
+                coords(to, ZoomLevel::Planet)?,
+            ),
+            Route::Overland => ships::distance(
+                coords(from, ZoomLevel::Region)?,
+                coords(to, ZoomLevel::Region)?,
+            ),
+        })
+    }
+
+    /// A local area on a planet with a market to book passage at
+    fn port_of(&self, planet: EntityId) -> Option<EntityId> {
+        self.economy
+            .markets()
+            .map(|(area, _)| area)
+            .find(|&area| self.planet_of(area) == Some(planet))
+    }
+
+    pub fn galaxy(&self) -> &GalaxyState {
+        &self.galaxy
+    }
+
+    pub fn galaxy_mut(&mut self) -> &mut GalaxyState {
+        &mut self.galaxy
+    }
+
+    pub fn economy(&self) -> &Economy {
+        &self.economy
+    }
+
+    pub fn economy_mut(&mut self) -> &mut Economy {
+        &mut self.economy
+    }
+
+    /// Every place below the galaxy and its components
+    pub fn entities(&self) -> &EntityStore {
+        &self.entities
+    }
+
+    pub fn entities_mut(&mut self) -> &mut EntityStore {
+        &mut self.entities
+    }
+
+    pub fn get_system(&self, id: EntityId) -> Option<&SolarSystemState> {
+        self.entities.systems.get(ZoomLevel::SolarSystem, id)
+    }
+
+    pub fn get_system_mut(&mut self, id: EntityId) -> Option<&mut SolarSystemState> {
+        self.entities.systems.get_mut(ZoomLevel::SolarSystem, id)
+    }
+
+    pub fn get_region(&self, id: EntityId) -> Option<&RegionState> {
+        self.entities.regions.get(ZoomLevel::Region, id)
+    }
+
+    pub fn get_region_mut(&mut self, id: EntityId) -> Option<&mut RegionState> {
+        self.entities.regions.get_mut(ZoomLevel::Region, id)
+    }
+
+    pub fn get_area(&self, id: EntityId) -> Option<&LocalAreaState> {
+        self.entities.areas.get(ZoomLevel::LocalArea, id)
+    }
+
+    pub fn get_area_mut(&mut self, id: EntityId) -> Option<&mut LocalAreaState> {
+        self.entities.areas.get_mut(ZoomLevel::LocalArea, id)
+    }
+
+    pub fn get_room(&self, id: EntityId) -> Option<&RoomState> {
+        self.entities.rooms.get(ZoomLevel::Room, id)
+    }
+
+    pub fn get_room_mut(&mut self, id: EntityId) -> Option<&mut RoomState> {
+        self.entities.rooms.get_mut(ZoomLevel::Room, id)
+    }
+
+    pub fn get_current_entity_name(&self, zoom_level: ZoomLevel) -> String {
+        self.location_name(&Position::new(), zoom_level)
+    }
+
+    /// Name of the entity the position is in at the given level, defaulting
+    /// to the starting location when no entity has been selected
+    pub fn location_name(&self, position: &Position, zoom_level: ZoomLevel) -> String {
+        let id = position.current_entity_id(zoom_level).unwrap_or(1);
+        self.entity_name(zoom_level, id)
+    }
+
+    /// Names of the places containing the position at the given level,
+    /// from the galaxy down to the entity itself
+    pub fn location_path(&self, position: &Position, zoom_level: ZoomLevel) -> Vec<String> {
+        let Some(id) = position.map_owner(zoom_level) else {
+            return vec![self.galaxy.name.clone()];
+        };
+        self.ancestry(zoom_level, id)
+            .into_iter()
+            .rev()
+            .map(|(level, id)| self.entity_name(level, id))
+            .collect()
+    }
+
+    /// Name of an entity, or a placeholder naming its kind if it is missing.
+    /// The galaxy's name is returned for any id.
+    pub fn entity_name(&self, level: ZoomLevel, id: EntityId) -> String {
+        if level == ZoomLevel::Galaxy {
+            return self.galaxy.name.clone();
+        }
+        match (self.entities.name(level, id), level) {
+            (Some(name), _) => name.to_string(),
+            (None, ZoomLevel::SolarSystem) => String::from("Unknown System"),
+            (None, ZoomLevel::LocalArea) => String::from("Unknown Area"),
+            (None, level) => format!("Unknown {}", level),
+        }
+    }
+
+    pub fn entity_count(&self) -> usize {
+        1 + self.entities.len()
+    }
+}
+
+/// Working residents of a local area, as firms count them
+fn workforce(entities: &EntityStore, area_id: EntityId) -> u32 {
+    entities
+        .populated
+        .get(ZoomLevel::LocalArea, area_id)
+        .map_or(0, |people| people.population.min(u32::MAX as u64) as u32)
+}
+
+impl Default for WorldState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::characters::Activity;
+    use crate::game::worldgen::WorldConfig;
+
+    #[test]
+    fn test_ancestry_climbs_to_galaxy() {
+        let world = WorldState::new();
+        assert_eq!(
+            world.ancestry(ZoomLevel::LocalArea, 1),
+            vec![
+                (ZoomLevel::LocalArea, 1),
+                (ZoomLevel::Region, 1),
+                (ZoomLevel::Planet, 1),
+                (ZoomLevel::SolarSystem, 1),
+                (ZoomLevel::Galaxy, GALAXY_ID),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_daily_stats_roll_up() {
+        let mut world = WorldState::new();
+        for _ in 0..24 {
+            world.update(Duration::from_secs(3_600));
+        }
+
+        let stats = world.stats();
+        let area = stats.trends(ZoomLevel::LocalArea, 1).unwrap();
+        let galaxy = stats.trends(ZoomLevel::Galaxy, GALAXY_ID).unwrap();
+        assert_eq!(area.gdp.len(), 1);
+        assert!(area.gdp[0] > 0.0);
+        assert_eq!(galaxy.gdp, area.gdp);
+        assert_eq!(area.prices.len(), 1);
+
+        let planet = world.population(ZoomLevel::Planet, 1).unwrap() as f64;
+        assert_eq!(galaxy.population[0], planet);
+        assert_eq!(
+            area.population[0],
+            world.population(ZoomLevel::LocalArea, 1).unwrap() as f64
+        );
+    }
+
+    #[test]
+    fn test_world_state_initialization() {
+        let state = WorldState::new();
+        assert_eq!(state.tick_count(), 0);
+        assert_eq!(state.galaxy().name, "Andromeda Prime");
+        assert_eq!(state.entity_count(), 6);
+    }
+
+    #[test]
+    fn test_world_state_update() {
+        let mut state = WorldState::new();
+        state.update(Duration::from_secs(1));
+        assert_eq!(state.tick_count(), 1);
+        state.update(Duration::from_secs(1));
+        assert_eq!(state.tick_count(), 2);
+    }
+
+    #[test]
+    fn test_sample_data_exists() {
+        let state = WorldState::new();
+        assert!(state.get_system(1).is_some());
+        assert!(state.entities().contains(ZoomLevel::Planet, 1));
+        assert!(state.get_region(1).is_some());
+        assert!(state.get_area(1).is_some());
+        assert!(state.get_room(1).is_some());
+        assert!(state.economy().market(1).is_some());
+        assert_eq!(state.economy().firms_in_area(1).count(), 6);
+    }
+
+    #[test]
+    fn test_update_runs_production() {
+        let mut state = WorldState::new();
+        let grain = state.economy().catalog().id("grain").unwrap();
+        let before = state.economy().market(1).unwrap().stock(grain);
+
+        state.update(Duration::from_secs(3600));
+
+        assert_ne!(state.economy().market(1).unwrap().stock(grain), before);
+    }
+
+    #[test]
+    fn test_current_entity_name() {
+        let state = WorldState::new();
+        assert_eq!(
+            state.get_current_entity_name(ZoomLevel::Galaxy),
+            "Andromeda Prime"
+        );
+        assert_eq!(
+            state.get_current_entity_name(ZoomLevel::SolarSystem),
+            "Sol System"
+        );
+        assert_eq!(state.get_current_entity_name(ZoomLevel::Planet), "Terra");
+        assert_eq!(
+            state.get_current_entity_name(ZoomLevel::Region),
+            "Northern Highlands"
+        );
+        assert_eq!(
+            state.get_current_entity_name(ZoomLevel::LocalArea),
+            "Market District"
+        );
+        assert_eq!(
+            state.get_current_entity_name(ZoomLevel::Room),
+            "Trading Hall"
+        );
+    }
+
+    #[test]
+    fn test_spawn_and_find() {
+        let mut state = WorldState::new();
+        let id = state.spawn(ZoomLevel::Planet).unwrap();
+
+        assert_eq!(state.entity_count(), 7);
+        assert_eq!(
+            state.find_entity(&format!("planet {}", id)),
+            Some((ZoomLevel::Planet, id))
+        );
+        assert_eq!(state.find_entity("terra"), Some((ZoomLevel::Planet, 1)));
+        assert_eq!(
+            state.find_entity("andromeda prime"),
+            Some((ZoomLevel::Galaxy, 0))
+        );
+        assert_eq!(state.find_entity("Atlantis"), None);
+        assert!(state.spawn(ZoomLevel::Galaxy).is_none());
+    }
+
+    #[test]
+    fn test_reseed_makes_spawns_reproducible() {
+        let mut a = WorldState::new();
+        let mut b = WorldState::new();
+        a.reseed(42);
+        b.reseed(42);
+
+        let a_id = a.spawn(ZoomLevel::Planet).unwrap();
+        let b_id = b.spawn(ZoomLevel::Planet).unwrap();
+        assert_eq!(
+            a.population(ZoomLevel::Planet, a_id).unwrap(),
+            b.population(ZoomLevel::Planet, b_id).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_regions_are_surveyed_from_the_seed() {
+        let mut state = WorldState::new();
+        let map = state.get_region(1).unwrap().terrain.clone().unwrap();
+        // The sample region's deposits are untouched, and it has no fishery
+        let land = map.yields();
+        assert_eq!(
+            state.region_yields(1),
+            Yields {
+                fishing: land.fishing * deposits::BARREN_YIELD,
+                ..land
+            }
+        );
+        // The world starts in winter
+        assert_eq!(
+            state.yields(1),
+            climate::apply(Season::Winter, None, state.region_yields(1))
+        );
+
+        state.set_terrain(1, "Forest");
+        let forest = state.get_region(1).unwrap().terrain.clone().unwrap();
+        assert!(forest.land_share(Biome::Forest) >= map.land_share(Biome::Forest));
+
+        state.reseed(42);
+        let reseeded = state.get_region(1).unwrap().terrain.clone().unwrap();
+        assert_ne!(reseeded, forest);
+        assert_eq!(state.get_region(1).unwrap().terrain_type, "Forest");
+    }
+
+    #[test]
+    fn test_spawn_at_places_on_parent_map() {
+        let mut state = WorldState::new();
+        let id = state
+            .spawn_at(ZoomLevel::Planet, Placement::new(Some(1), (3, -2)))
+            .unwrap();
+
+        assert_eq!(
+            state.entity_at(ZoomLevel::Planet, Some(1), (3, -2)),
+            Some(id)
+        );
+        assert_eq!(state.entity_at(ZoomLevel::Planet, Some(1), (0, 0)), Some(1));
+        assert_eq!(state.entity_at(ZoomLevel::Planet, Some(2), (3, -2)), None);
+        assert_eq!(
+            state.placed_in(ZoomLevel::Planet, Some(1)),
+            vec![(1, (0, 0)), (id, (3, -2))]
+        );
+
+        assert!(state.remove_entity(ZoomLevel::Planet, id));
+        assert_eq!(state.entity_at(ZoomLevel::Planet, Some(1), (3, -2)), None);
+    }
+
+    #[test]
+    fn test_snapshot_restores_removed_area() {
+        let mut state = WorldState::new();
+        let issued = state.economy().money_supply().issued();
+        let snapshot = state.snapshot(ZoomLevel::LocalArea, 1).unwrap();
+
+        state.remove_entity(ZoomLevel::LocalArea, 1);
+        state.restore(snapshot);
+
+        assert_eq!(
+            state.entity_name(ZoomLevel::LocalArea, 1),
+            "Market District"
+        );
+        assert!(state.economy().market(1).is_some());
+        assert_eq!(state.economy().firms_in_area(1).count(), 6);
+        assert_eq!(state.characters_in_area(1).count(), 3);
+        assert!(
+            (state.economy().money_supply().issued() - issued)
+                .amount()
+                .abs()
+                < 1e-6
+        );
+    }
+
+    #[test]
+    fn test_characters_live_in_areas_and_rooms() {
+        let mut state = WorldState::new();
+        assert_eq!(state.characters_in_room(1).count(), 2);
+        assert_eq!(state.add_character("Dov", 999), None);
+
+        let id = state.add_character("Dov", 1).unwrap();
+        assert_eq!(state.characters_in_area(1).count(), 4);
+        state.character_mut(id).unwrap().employer = Some(104);
+
+        state.update(Duration::from_secs(3_600));
+        let dov = state.character(id).unwrap();
+        assert_eq!(dov.activity, Activity::Work);
+        assert!(!dov.inventory.is_empty());
+
+        state.remove_entity(ZoomLevel::Room, 1);
+        assert_eq!(state.characters_in_room(1).count(), 0);
+    }
+
+    #[test]
+    fn test_duplicate_rekeys_snapshot() {
+        let state = WorldState::new();
+        let snapshot = state.snapshot(ZoomLevel::LocalArea, 1).unwrap();
+        let copy = snapshot.duplicate(2000, Placement::new(Some(1), (4, 4)));
+
+        assert_eq!(copy.id(), 2000);
+        assert_eq!(copy.placement().coords, (4, 4));
+        assert!(copy.market.is_none());
+    }
+
+    #[test]
+    fn test_removing_area_closes_market() {
+        let mut state = WorldState::new();
+        assert!(state.remove_entity(ZoomLevel::LocalArea, 1));
+
+        assert!(state.economy().market(1).is_none());
+        assert_eq!(state.economy().firm_count(), 0);
+        assert_eq!(state.characters_in_area(1).count(), 0);
+        assert!(!state.remove_entity(ZoomLevel::Galaxy, 0));
+    }
+
+    #[test]
+    fn test_location_name_follows_position() {
+        let mut state = WorldState::new();
+        let id = state.spawn(ZoomLevel::Region).unwrap();
+        let mut position = Position::new();
+        position.current_region_id = Some(id);
+
+        assert_eq!(
+            state.location_name(&position, ZoomLevel::Region),
+            format!("Region {}", id)
+        );
+    }
+
+    #[test]
+    fn test_watchlist_pins_and_unpins() {
+        let mut state = WorldState::new();
+        assert!(state.toggle_watch(ZoomLevel::Planet, 1));
+        assert!(state.toggle_watch(ZoomLevel::LocalArea, 1));
+        assert_eq!(
+            state.watchlist(),
+            &[(ZoomLevel::Planet, 1), (ZoomLevel::LocalArea, 1)]
+        );
+
+        assert!(!state.toggle_watch(ZoomLevel::Planet, 1));
+        state.remove_entity(ZoomLevel::LocalArea, 1);
+        assert!(state.watchlist().is_empty());
+    }
+
+    #[test]
+    fn test_workers_migrate_to_roomier_areas() {
+        let mut state = WorldState::builder()
+            .system("Kepler")
+            .planet("Verdant", 1_000)
+            .region("Lowlands", "Plains")
+            .area("Crowded", 100)
+            .area("Roomy", 0)
+            .market("Crowded")
+            .market("Roomy")
+            .build()
+            .unwrap();
+        let (_, crowded) = state.find_entity("Crowded").unwrap();
+        let (_, roomy) = state.find_entity("Roomy").unwrap();
+        state.get_area_mut(crowded).unwrap().building_count = 25;
+        state.get_area_mut(roomy).unwrap().building_count = 50;
+        let (_, region) = state.find_entity("Lowlands").unwrap();
+        let before = state.population(ZoomLevel::Region, region).unwrap();
+
+        state.update(migration::MIGRATION_INTERVAL);
+        assert!(state.population(ZoomLevel::LocalArea, roomy).unwrap() > 0);
+        assert_eq!(state.population(ZoomLevel::Region, region), Some(before));
+        let log = state.events();
+        assert!(
+            log.recent(log.len())
+                .any(|e| e.message.ends_with("from Crowded to Roomy"))
+        );
+    }
+
+    #[test]
+    fn test_collapsed_lanes_stop_emigration() {
+        let mut state = WorldState::builder()
+            .system("Kepler")
+            .planet("Verdant", 1_000_000)
+            .region("Lowlands", "Plains")
+            .area("Dearfood", 10)
+            .stock("Dearfood", "grain", 10.0)
+            .stock("Dearfood", "bread", 10.0)
+            .system("Vega")
+            .planet("Plenty", 1_000_000)
+            .region("Fields", "Plains")
+            .area("Cheapfood", 10)
+            .market("Cheapfood")
+            .build()
+            .unwrap();
+        let (_, kepler) = state.find_entity("Kepler").unwrap();
+        let (_, verdant) = state.find_entity("Verdant").unwrap();
+        let day = migration::MIGRATION_INTERVAL;
+
+        state.collapse_lanes(kepler, day * 2);
+        state.update(day);
+        assert_eq!(
+            state.population(ZoomLevel::Planet, verdant).unwrap(),
+            1_000_000
+        );
+
+        state.update(day);
+        assert!(!state.is_isolated(kepler));
+        assert!(state.population(ZoomLevel::Planet, verdant).unwrap() < 1_000_000);
+    }
+
+    #[test]
+    fn test_player_business_trades_on_local_market() {
+        let mut state = WorldState::builder()
+            .system("Kepler")
+            .planet("Verdant", 1_000)
+            .region("Lowlands", "Plains")
+            .area("Millbrook", 20)
+            .market("Millbrook")
+            .build()
+            .unwrap();
+        state.economy_mut().endow_purse(Money(1_000.0));
+        let (_, millbrook) = state.find_entity("Millbrook").unwrap();
+        let (_, lowlands) = state.find_entity("Lowlands").unwrap();
+
+        assert_eq!(
+            state.found_business(lowlands, "Mill", "grain farming", 4, Money(100.0)),
+            Err(BusinessError::NotInArea)
+        );
+        assert_eq!(
+            state.found_business(millbrook, "Mill", "grain farming", 4, Money(5_000.0)),
+            Err(BusinessError::NotEnoughCash {
+                capital: Money(5_000.0),
+                purse: Money(1_000.0)
+            })
+        );
+
+        let id = state
+            .found_business(millbrook, "Mill", "grain farming", 4, Money(600.0))
+            .unwrap();
+        assert_eq!(state.economy().purse(), Money(400.0));
+        assert!(state.business().owns(id));
+
+        let grain = state.economy().catalog().id("grain").unwrap();
+        let stock = state.economy().market(millbrook).unwrap().stock(grain);
+        state.update(Duration::from_secs(3600));
+        let firm = state.economy().firm(id).unwrap();
+        assert_eq!(firm.workers, 4);
+        assert!(state.economy().market(millbrook).unwrap().stock(grain) > stock);
+        assert_eq!(
+            state.economy().money_supply().issued(),
+            state.economy().total_balances()
+        );
+    }
+
+    #[test]
+    fn test_commissioned_buildings_open_for_the_player() {
+        let mut state = WorldState::builder()
+            .system("Kepler")
+            .planet("Verdant", 1_000)
+            .region("Lowlands", "Plains")
+            .area("Millbrook", 20)
+            .market("Millbrook")
+            .build()
+            .unwrap();
+        state.economy_mut().endow_purse(Money(3_000.0));
+        let (_, millbrook) = state.find_entity("Millbrook").unwrap();
+
+        assert!(matches!(
+            state.commission_building(millbrook, "Camp", "logging", 4, Money(10.0)),
+            Err(BusinessError::CannotBuild { .. })
+        ));
+        let id = state
+            .commission_building(millbrook, "Camp", "logging", 4, Money(2_000.0))
+            .unwrap();
+        assert!(!state.business().owns(id));
+        assert_eq!(state.free_firm_spot(millbrook), (1, 0));
+
+        let day = Duration::from_secs(86_400);
+        for _ in 0..4 {
+            state.update(day);
+        }
+        assert!(state.business().owns(id));
+        assert_eq!(state.business().holdings()[0].capital, Money(2_000.0));
+        assert_eq!(state.economy().firm(id).unwrap().max_workers, Some(4));
+        assert!(
+            state
+                .take_notices()
+                .iter()
+                .any(|notice| notice.message == "Camp is finished and open in Millbrook")
+        );
+        assert_eq!(
+            state.economy().money_supply().issued(),
+            state.economy().total_balances()
+        );
+    }
+
+    #[test]
+    fn test_price_moves_are_logged_in_groups() {
+        let mut state = WorldState::builder()
+            .system("Kepler")
+            .planet("Verdant", 1_000)
+            .region("Lowlands", "Plains")
+            .area("Millbrook", 20)
+            .market("Millbrook")
+            .build()
+            .unwrap();
+        let hour = Duration::from_secs(3600);
+        state.update(hour);
+        let logged = state.events().pushed();
+
+        // Emptying the shelves sends the price of bread soaring
+        let (_, millbrook) = state.find_entity("Millbrook").unwrap();
+        let bread = state.economy().catalog().id("bread").unwrap();
+        let market = state.economy_mut().market_mut(millbrook).unwrap();
+        market.take(bread, f64::MAX);
+        state.update(hour);
+
+        let entry = state
+            .events()
+            .since(logged)
+            .find(|entry| entry.message.starts_with("Price of bread rose"))
+            .unwrap();
+        assert_eq!(
+            entry.group.as_deref(),
+            Some("Price of bread rose in Millbrook")
+        );
+    }
+
+    #[test]
+    fn test_mining_exhausts_the_vein() {
+        let mut state = WorldState::new();
+        state.get_region_mut(1).unwrap().deposits =
+            Some(vec![ResourceDeposit::new(DepositKind::OreVein, 100.0)]);
+        let rich = state.yields(1).mining;
+
+        for _ in 0..48 {
+            state.update(Duration::from_secs(3600));
+        }
+        let vein = &state.get_region(1).unwrap().deposits.as_ref().unwrap()[0];
+        assert!(vein.is_exhausted());
+        assert_eq!(state.yields(1).mining, rich * deposits::BARREN_YIELD);
+
+        let messages: Vec<String> = state
+            .take_notices()
+            .into_iter()
+            .map(|n| n.message)
+            .collect();
+        assert!(messages.contains(&String::from(
+            "The ore vein in Northern Highlands is running thin"
+        )));
+        assert!(messages.contains(&String::from(
+            "The ore vein in Northern Highlands is exhausted"
+        )));
+    }
+
+    #[test]
+    fn test_updates_come_out_the_same_on_any_number_of_threads() {
+        let world = WorldConfig {
+            systems: 12,
+            galaxy_radius: 10,
+            ..WorldConfig::default()
+        }
+        .generate();
+        let run = |threads: usize| {
+            let mut world = world.clone();
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            pool.install(|| {
+                for _ in 0..48 {
+                    world.update(Duration::from_secs(3600));
+                }
+            });
+            // Through a value, since hash maps are written in any order
+            serde_json::to_value(&world).unwrap()
+        };
+        assert!(world.economy().markets().count() > 12);
+        assert_eq!(run(1), run(4));
+    }
+
+    #[test]
+    fn test_polities_govern_and_tax_their_markets() {
+        let mut state = WorldState::new();
+        let governor = |state: &WorldState, level| state.polity_of(level, 1).map(|p| p.id);
+        assert_eq!(governor(&state, ZoomLevel::Room), Some(301));
+        assert_eq!(governor(&state, ZoomLevel::SolarSystem), Some(301));
+        assert_eq!(governor(&state, ZoomLevel::Planet), None);
+        assert_eq!(state.fiscal_policy(1).tax_rate, 0.1);
+
+        for _ in 0..24 {
+            state.update(Duration::from_secs(3600));
+        }
+        let compact = state.polity(301).unwrap();
+        assert!(compact.collected > Money::ZERO);
+        assert!(compact.spent > Money::ZERO);
+        assert!(
+            (state.economy().treasury(301) - (compact.collected - compact.spent))
+                .amount()
+                .abs()
+                < 1e-9
+        );
+        assert_eq!(state.get_region(1).unwrap().infrastructure, compact.spent);
+        assert!(state.infrastructure(1) > 1.0);
+        assert!(
+            (state.economy().total_balances() - state.economy().money_supply().issued())
+                .amount()
+                .abs()
+                < 1e-3
+        );
+
+        let rival = state.found_polity("Rival", FiscalPolicy::NONE);
+        assert!(state.claim(rival, ZoomLevel::Planet, 1));
+        assert_eq!(governor(&state, ZoomLevel::LocalArea), Some(rival));
+        assert!(state.polity(301).unwrap().regions.is_empty());
+        assert_eq!(state.fiscal_policy(1), FiscalPolicy::NONE);
+        assert!(!state.claim(rival, ZoomLevel::LocalArea, 1));
+
+        let raised = FiscalPolicy {
+            tax_rate: 0.2,
+            ..FiscalPolicy::NONE
+        };
+        assert!(state.set_fiscal_policy(rival, raised));
+        assert!(!state.set_fiscal_policy(
+            rival,
+            FiscalPolicy {
+                tax_rate: 1.5,
+                ..raised
+            }
+        ));
+        assert_eq!(state.fiscal_policy(1), raised);
+    }
+
+    #[test]
+    fn test_polities_research_technology_for_their_markets() {
+        let mut state = WorldState::new();
+        state.economy_mut().endow_purse(Money(1_000.0));
+        let techs = state.economy().techs().clone();
+        let rotation = techs.find("crop rotation").unwrap();
+        let ploughs = techs.find("iron ploughs").unwrap();
+        let ploughed = state.economy().recipes().find("ploughed farming").unwrap();
+        assert_eq!(state.technology(1), TechBoosts::NONE);
+        assert!(!state.has_unlocked(1, ploughed));
+        assert_eq!(
+            state.found_business(1, "Ploughs", "ploughed farming", 4, Money(100.0)),
+            Err(BusinessError::NotResearched(String::from(
+                "ploughed farming"
+            )))
+        );
+
+        let markets = state.governed_markets()[&301].len() as f64;
+        // Quicker technologies come first, so research long enough for all
+        let total: f64 = techs.iter().map(|(_, tech)| tech.days).sum();
+        let days = total / (markets * polities::RESEARCH_PER_MARKET);
+        state.research(Duration::from_secs_f64(days * 86_400.0 + 1.0));
+        let research = &state.polity(301).unwrap().research;
+        assert!(research.knows(rotation) && research.knows(ploughs));
+        assert!(
+            state
+                .technology(1)
+                .for_building(crate::economy::BuildingKind::Farm)
+                > 1.1
+        );
+        assert_eq!(
+            state
+                .market_tick(1, Duration::ZERO, &Calendar::default())
+                .technology,
+            state.technology(1)
+        );
+        assert!(state.has_unlocked(1, ploughed));
+        assert!(
+            state
+                .found_business(1, "Ploughs", "ploughed farming", 4, Money(100.0))
+                .is_ok()
+        );
+        assert!(
+            state
+                .take_notices()
+                .iter()
+                .any(|notice| notice.message.contains("discovers iron ploughs"))
+        );
+    }
+
+    #[test]
+    fn test_ships_carry_surplus_to_dearer_markets() {
+        let mut state = WorldState::builder()
+            .system("Kepler")
+            .planet("Verdant", 1_000_000)
+            .region("Lowlands", "Plains")
+            .area("Millbrook", 0)
+            .stock("Millbrook", "grain", 600.0)
+            .planet("Dust", 1_000)
+            .region("Flats", "Desert")
+            .area("Dunes", 0)
+            .stock("Dunes", "grain", 0.0)
+            .build()
+            .unwrap();
+        let (_, verdant) = state.find_entity("Verdant").unwrap();
+        let (_, dust) = state.find_entity("Dust").unwrap();
+        let (_, millbrook) = state.find_entity("Millbrook").unwrap();
+        let (_, dunes) = state.find_entity("Dunes").unwrap();
+        let grain = state.economy().catalog().id("grain").unwrap();
+        let ship = state.launch_ship(verdant).unwrap();
+        let hour = Duration::from_secs(3600);
+
+        state.update(hour);
+        let voyage = *state.ship(ship).unwrap().voyage().unwrap();
+        assert_eq!((voyage.from, voyage.to), (verdant, dust));
+        let distance = state.planet_distance(verdant, dust).unwrap();
+        assert_eq!(
+            voyage.arrives - voyage.departed,
+            state.ship(ship).unwrap().travel_time(distance)
+        );
+        assert_eq!(state.ship(ship).unwrap().passengers, 50);
+        assert_eq!(
+            state.population(ZoomLevel::Planet, verdant).unwrap(),
+            999_950
+        );
+        let market = |state: &WorldState, area| state.economy().market(area).unwrap().stock(grain);
+        assert_eq!(market(&state, millbrook), 400.0);
+
+        while state.ship(ship).unwrap().docked_at().is_none() {
+            state.update(hour);
+        }
+        assert_eq!(state.ship(ship).unwrap().docked_at(), Some(dust));
+        assert_eq!(state.population(ZoomLevel::Planet, dust).unwrap(), 1_050);
+        assert!(market(&state, dunes) > 100.0);
+        assert!(state.economy().market(millbrook).unwrap().cash() > Money::ZERO);
+        assert_eq!(state.audit(), Vec::new());
+        let docked = format!("Ship {} docks at Dust with 200 grain", ship);
+        assert!(
+            state
+                .events()
+                .recent(10)
+                .any(|event| event.message == docked)
+        );
+    }
+
+    #[test]
+    fn test_firms_sign_and_honour_supply_contracts() {
+        let mut world = WorldState::new();
+        let hour = Duration::from_secs(3600);
+        for _ in 0..24 {
+            world.update(hour);
+        }
+        let grain = world.economy().catalog().id("grain").unwrap();
+        let bread = world
+            .contracts_of(104)
+            .find(|c| c.terms.good == grain)
+            .unwrap()
+            .clone();
+        assert_eq!(bread.terms.supplier, 101);
+        assert_eq!(bread.remaining, contracts::CONTRACT_DELIVERIES);
+        // The smelter and the toolmaker contract for their inputs as well
+        assert_eq!(world.contracts().count(), 5);
+
+        for _ in 0..24 {
+            world.update(hour);
+        }
+        let contract = world.contract(bread.id).unwrap();
+        assert_eq!(contract.remaining, contracts::CONTRACT_DELIVERIES - 1);
+        assert_eq!(world.contracts().count(), 5);
+        assert_eq!(world.audit(), Vec::new());
+
+        world.economy_mut().firm_mut(104).unwrap().cash = Money::ZERO;
+        world.take_notices();
+        for _ in 0..24 {
+            world.update(hour);
+        }
+        assert!(world.contract(bread.id).is_none());
+        assert!(world.take_notices().iter().any(|notice| {
+            notice.message
+                == "Contract between Highland Farm and Market Bakery is broken: the buyer can't pay"
+        }));
+
+        assert_eq!(
+            world.sign_contract(
+                Terms {
+                    supplier: 106,
+                    ..bread.terms
+                },
+                1
+            ),
+            Err(ContractError::NotMade)
+        );
+    }
+
+    #[test]
+    fn test_weather_strikes_regions_and_passes() {
+        let mut state = WorldState::new();
+        let day = Calendar::DEFAULT_DAY_LENGTH;
+        let fair = state.yields(1);
+        assert!(state.set_weather(1, WeatherKind::Drought, day * 2));
+        assert!(!state.set_weather(99, WeatherKind::Storm, day));
+        assert!((state.yields(1).farming - fair.farming * 0.3).abs() < 1e-9);
+        assert_eq!(state.yields(1).mining, fair.mining);
+
+        state.update(day * 3);
+        assert!(state.weather(1).is_none_or(|w| w.until > day * 2));
+        assert!(
+            state
+                .events()
+                .recent(10)
+                .any(|entry| entry.message == "The drought in Northern Highlands passes")
+        );
+        // By summer the rolls have brought weather of their own, and the
+        // harvest has climbed out of winter
+        state.take_notices();
+        for _ in 0..180 {
+            state.update(day);
+        }
+        assert!(state.take_notices().iter().any(|notice| {
+            notice.message.starts_with("Drought grips") || notice.message.starts_with("Storms lash")
+        }));
+        let harvest = &state.stats().trends(ZoomLevel::Region, 1).unwrap().harvest;
+        assert!(harvest.iter().any(|&h| h > 1.0));
+    }
+
+    #[test]
+    fn test_famine_is_announced_once() {
+        let mut state = WorldState::builder()
+            .system("Kepler")
+            .planet("Verdant", 1_000)
+            .region("Lowlands", "Plains")
+            .area("Millbrook", 20)
+            .stock("Millbrook", "grain", 0.0)
+            .stock("Millbrook", "bread", 0.0)
+            .stock("Millbrook", "fish", 0.0)
+            .build()
+            .unwrap();
+        let hour = Duration::from_secs(3600);
+
+        state.update(hour);
+        state.update(hour);
+        let notices = state.take_notices();
+        assert_eq!(notices.len(), 1);
+        assert_eq!(notices[0].severity, Severity::Critical);
+        assert_eq!(notices[0].message, "Famine in Millbrook");
+        assert_eq!(
+            state.events().recent(1).next().unwrap().message,
+            "Famine in Millbrook"
+        );
+
+        let (_, millbrook) = state.find_entity("Millbrook").unwrap();
+        let bread = state.economy().catalog().id("bread").unwrap();
+        state
+            .economy_mut()
+            .market_mut(millbrook)
+            .unwrap()
+            .add(bread, 500.0);
+        state.update(hour);
+        assert_eq!(state.take_notices()[0].severity, Severity::Info);
+    }
+
+    #[test]
+    fn test_daily_upkeep_fires_once_per_day() {
+        let mut state = WorldState::new();
+        let samples = |state: &WorldState| {
+            state
+                .stats()
+                .trends(ZoomLevel::Galaxy, GALAXY_ID)
+                .map_or(0, |t| t.gdp.len())
+        };
+        let day = stats::SAMPLE_INTERVAL;
+
+        state.update(day * 3);
+        assert_eq!(samples(&state), 1);
+        state.update(day / 2);
+        assert_eq!(samples(&state), 1);
+        state.update(day / 2);
+        assert_eq!(samples(&state), 2);
+    }
+
+    #[test]
+    fn test_festivals_are_announced_on_their_day() {
+        let mut state = WorldState::new();
+        let day = Calendar::DEFAULT_DAY_LENGTH;
+        state.update(day * 94 - Duration::from_secs(1));
+        assert_eq!(state.festivals_on(1).count(), 0);
+
+        state.update(Duration::from_secs(1));
+        let names: Vec<&str> = state.festivals_on(1).map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["Landing Day"]);
+        let log = state.events();
+        assert!(
+            log.recent(log.len())
+                .any(|e| e.message == "Landing Day begins on Terra")
+        );
+    }
+
+    #[test]
+    fn test_region_population_scales_its_areas() {
+        let mut state = WorldState::new();
+        assert_eq!(state.population(ZoomLevel::Region, 1), Some(60));
+        assert!(state.set_population(ZoomLevel::Region, 1, 30));
+        assert_eq!(state.population(ZoomLevel::LocalArea, 1).unwrap(), 30);
+    }
+
+    #[test]
+    fn test_home_market_keeps_daytime_hours() {
+        let mut state = WorldState::new();
+        assert_eq!(state.market_status(1), Some(MarketStatus::Open));
+        assert_eq!(state.market_status(999), None);
+
+        state.update(Duration::from_secs(3600 * 15));
+        assert_eq!(
+            state.market_status(1),
+            Some(MarketStatus::Closed { opens: 6 })
+        );
+    }
+
+    #[test]
+    fn test_exploring_reveals_neighbours() {
+        let mut state = WorldState::new();
+        assert!(!state.is_explored(ZoomLevel::Planet, Some(1), (0, 0)));
+
+        state.explore(ZoomLevel::Planet, Some(1), (0, 0));
+        assert!(state.is_explored(ZoomLevel::Planet, Some(1), (1, -1)));
+        assert!(!state.is_explored(ZoomLevel::Planet, Some(1), (2, 0)));
+        assert!(!state.is_explored(ZoomLevel::Planet, Some(2), (0, 0)));
+    }
+
+    #[test]
+    fn test_location_path_lists_every_level() {
+        let state = WorldState::new();
+        let position = Position::new();
+
+        assert_eq!(
+            state.location_path(&position, ZoomLevel::Room),
+            vec![
+                "Andromeda Prime",
+                "Sol System",
+                "Terra",
+                "Northern Highlands",
+                "Market District",
+                "Trading Hall",
+            ]
+        );
+        assert_eq!(
+            state.location_path(&position, ZoomLevel::Galaxy),
+            vec!["Andromeda Prime"]
+        );
+    }
+
+    #[test]
+    fn test_player_trades_and_works_at_furniture() {
+        let mut state = WorldState::new();
+        let grain = state.economy().catalog().id("grain").unwrap();
+        let purse = state.economy().purse();
+        let stock = state.economy().market(1).unwrap().stock(grain);
+
+        assert_eq!(
+            state.interact(1, (0, 0), Interaction::Rest),
+            Err(InteractionError::NoFurniture)
+        );
+        assert_eq!(
+            state.interact(1, (-2, -1), Interaction::Rest),
+            Err(InteractionError::WrongFurniture("market stall"))
+        );
+        assert!(matches!(
+            state.interact(1, (-2, -1), Interaction::Sell(grain)),
+            Err(InteractionError::NothingToSell(_))
+        ));
+
+        let message = state
+            .interact(1, (-2, -1), Interaction::Buy(grain))
+            .unwrap();
+        assert!(message.starts_with("Bought 10 grain for "), "{}", message);
+        assert_eq!(state.pack().get(grain), furniture::TRADE_LOT);
+        assert_eq!(
+            state.economy().market(1).unwrap().stock(grain),
+            stock - furniture::TRADE_LOT
+        );
+        assert!(state.economy().purse() < purse);
+        assert!(state.audit().is_empty());
+
+        state
+            .interact(1, (2, -1), Interaction::Sell(grain))
+            .unwrap();
+        assert_eq!(state.pack().get(grain), 0.0);
+        assert!(state.audit().is_empty());
+
+        let purse = state.economy().purse();
+        state.get_room_mut(1).unwrap().furniture =
+            vec![Furnishing::new(FurnitureKind::Workbench, (1, 1))];
+        let message = state.interact(1, (1, 1), Interaction::Work).unwrap();
+        assert!(
+            message.starts_with("You work an 8-hour shift"),
+            "{}",
+            message
         );
+        assert!(state.economy().purse() > purse);
+        assert!(state.audit().is_empty());
     }
 
     #[test]
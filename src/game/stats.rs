@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+
+use crate::time::Calendar;
+use crate::zoom::ZoomLevel;
+
+use super::state::EntityId;
+
+/// Daily samples kept per series before the oldest are discarded
+pub const HISTORY_DAYS: usize = 60;
+
+/// Simulation time between samples
+pub const SAMPLE_INTERVAL: Duration = Calendar::DEFAULT_DAY_LENGTH;
+
+/// Id the galaxy is recorded under, matching [`super::WorldState::find_entity`]
+pub const GALAXY_ID: EntityId = 0;
+
+/// One day's economic figures for a place
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Sample {
+    /// Mean price relative to base value across the place's markets, or
+    /// None where there are no markets
+    pub price_index: Option<f64>,
+    /// Farm output the season and weather allow, relative to a mild day,
+    /// or None where there are no markets
+    pub harvest: Option<f64>,
+    /// Value of goods produced during the day
+    pub gdp: f64,
+    pub population: f64,
+}
+
+/// Recent daily history for one place, oldest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Trends {
+    pub prices: VecDeque<f64>,
+    pub gdp: VecDeque<f64>,
+    pub population: VecDeque<f64>,
+    #[serde(default)]
+    pub harvest: VecDeque<f64>,
+}
+
+impl Trends {
+    fn push(&mut self, sample: Sample) {
+        if let Some(index) = sample.price_index {
+            push_bounded(&mut self.prices, index);
+        }
+        if let Some(harvest) = sample.harvest {
+            push_bounded(&mut self.harvest, harvest);
+        }
+        push_bounded(&mut self.gdp, sample.gdp);
+        push_bounded(&mut self.population, sample.population);
+    }
+}
+
+fn push_bounded(series: &mut VecDeque<f64>, value: f64) {
+    if series.len() == HISTORY_DAYS {
+        series.pop_front();
+    }
+    series.push_back(value);
+}
+
+/// Daily trend history for the galaxy and every entity down to local areas
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EconomyStats {
+    scopes: BTreeMap<ZoomLevel, BTreeMap<EntityId, Trends>>,
+}
+
+impl EconomyStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store one day's samples, keyed by level and entity
+    pub fn record(&mut self, samples: impl IntoIterator<Item = ((ZoomLevel, EntityId), Sample)>) {
+        for ((level, id), sample) in samples {
+            self.scopes
+                .entry(level)
+                .or_default()
+                .entry(id)
+                .or_default()
+                .push(sample);
+        }
+    }
+
+    pub fn trends(&self, level: ZoomLevel, id: EntityId) -> Option<&Trends> {
+        self.scopes.get(&level)?.get(&id)
+    }
+
+    /// Forget the history of an entity that no longer exists
+    pub fn forget(&mut self, level: ZoomLevel, id: EntityId) {
+        if let Some(scope) = self.scopes.get_mut(&level) {
+            scope.remove(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(gdp: f64) -> Sample {
+        Sample {
+            price_index: Some(1.0),
+            harvest: Some(1.0),
+            gdp,
+            population: 10.0,
+        }
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut stats = EconomyStats::new();
+        for day in 0..HISTORY_DAYS + 5 {
+            stats.record([((ZoomLevel::Planet, 1), sample(day as f64))]);
+        }
+
+        let trends = stats.trends(ZoomLevel::Planet, 1).unwrap();
+        assert_eq!(trends.gdp.len(), HISTORY_DAYS);
+        assert_eq!(trends.gdp.front(), Some(&5.0));
+        assert!(stats.trends(ZoomLevel::Region, 1).is_none());
+    }
+
+    #[test]
+    fn test_missing_price_index_is_skipped() {
+        let mut stats = EconomyStats::new();
+        let no_markets = Sample {
+            price_index: None,
+            harvest: None,
+            ..sample(0.0)
+        };
+        stats.record([((ZoomLevel::Region, 2), no_markets)]);
+
+        let trends = stats.trends(ZoomLevel::Region, 2).unwrap();
+        assert!(trends.prices.is_empty());
+        assert!(trends.harvest.is_empty());
+        assert_eq!(trends.population.len(), 1);
+    }
+}
@@ -0,0 +1,405 @@
+//! Planet surfaces generated from noise.
+//!
+//! Every planet has a height field and a moisture field, both smooth value
+//! noise seeded from the world and the planet, spread over the planet's
+//! map so that neighboring regions meet without seams. Each region keeps a
+//! grid of biomes sampled from the stretch of surface it covers. The
+//! terrain a region is known for shapes that grid: mountainous regions
+//! raise more peaks, coastal ones let the sea in further while inland
+//! ones hold it back, and otherwise the named terrain takes over the
+//! middling ground. The share of each
+//! biome then decides how well the region's farms, mines, lumber camps and
+//! fisheries do; see [`TerrainMap::yields`].
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::economy::Yields;
+
+use super::state::EntityId;
+
+/// Tiles across a region's map
+pub const REGION_MAP_WIDTH: usize = 18;
+/// Tiles down a region's map
+pub const REGION_MAP_HEIGHT: usize = 7;
+
+/// Noise cycles across one spot of a planet's map, for the broadest layer
+const BASE_FREQUENCY: f64 = 2.0;
+const OCTAVES: u32 = 4;
+
+/// Heights below this are sea
+const SEA_LEVEL: f64 = 0.42;
+/// Land this close above the sea is shore
+const SHORE_BAND: f64 = 0.04;
+/// Heights above this are mountains
+const PEAK_LEVEL: f64 = 0.64;
+/// Moisture above this grows forest, below its complement makes desert
+const WET: f64 = 0.6;
+
+/// Output of a resource building with no suitable land around it
+const POOR_YIELD: f64 = 0.5;
+/// Output with only suitable land around it
+const RICH_YIELD: f64 = 2.0;
+
+/// What covers one tile of a planet's surface
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Biome {
+    Ocean,
+    Coast,
+    Plains,
+    Forest,
+    Desert,
+    Mountains,
+}
+
+impl Biome {
+    pub const ALL: [Biome; 6] = [
+        Biome::Ocean,
+        Biome::Coast,
+        Biome::Plains,
+        Biome::Forest,
+        Biome::Desert,
+        Biome::Mountains,
+    ];
+
+    /// The biome a region's terrain name stands for, ignoring case
+    pub fn from_terrain(terrain: &str) -> Option<Biome> {
+        Self::ALL
+            .into_iter()
+            .find(|biome| biome.name().eq_ignore_ascii_case(terrain))
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Biome::Ocean => "Ocean",
+            Biome::Coast => "Coast",
+            Biome::Plains => "Plains",
+            Biome::Forest => "Forest",
+            Biome::Desert => "Desert",
+            Biome::Mountains => "Mountains",
+        }
+    }
+
+    pub fn is_land(self) -> bool {
+        self != Biome::Ocean
+    }
+
+    /// Character standing for the biome in saved maps
+    fn code(self) -> char {
+        match self {
+            Biome::Ocean => '~',
+            Biome::Coast => 'c',
+            Biome::Plains => 'p',
+            Biome::Forest => 'f',
+            Biome::Desert => 'd',
+            Biome::Mountains => 'm',
+        }
+    }
+
+    fn from_code(code: char) -> Option<Biome> {
+        Self::ALL.into_iter().find(|biome| biome.code() == code)
+    }
+}
+
+impl fmt::Display for Biome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A planet's height and moisture fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Surface {
+    seed: u64,
+}
+
+impl Surface {
+    /// The surface of one planet in a world
+    pub fn new(world_seed: u64, planet: EntityId) -> Self {
+        Self {
+            seed: mix(world_seed ^ mix(planet)),
+        }
+    }
+
+    /// Height and moisture, each from 0 to 1, at a point on the planet's
+    /// map; spot `(x, y)` is centered on the point `(x, y)`
+    pub fn sample(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            fractal(self.seed, x, y),
+            fractal(mix(self.seed ^ 0x5EA5_0A75), x, y),
+        )
+    }
+
+    /// The biome at a point, shaped by a region's named terrain if given
+    pub fn biome(&self, x: f64, y: f64, terrain: Option<Biome>) -> Biome {
+        let (height, moisture) = self.sample(x, y);
+        let sea = match terrain {
+            Some(Biome::Coast | Biome::Ocean) => SEA_LEVEL + 0.06,
+            Some(_) => SEA_LEVEL - 0.08,
+            None => SEA_LEVEL,
+        };
+        let shore = match terrain {
+            Some(Biome::Coast) => SHORE_BAND * 2.5,
+            _ => SHORE_BAND,
+        };
+        let peaks = match terrain {
+            Some(Biome::Mountains) => PEAK_LEVEL - 0.1,
+            _ => PEAK_LEVEL,
+        };
+        if height < sea {
+            return Biome::Ocean;
+        }
+        if height < sea + shore {
+            return Biome::Coast;
+        }
+        if height > peaks {
+            return Biome::Mountains;
+        }
+        let (wet, dry) = match terrain {
+            Some(Biome::Forest) => (WET - 0.15, 1.0 - WET),
+            Some(Biome::Desert) => (WET, 1.0 - WET + 0.15),
+            Some(Biome::Plains) => (WET + 0.1, 1.0 - WET - 0.1),
+            _ => (WET, 1.0 - WET),
+        };
+        if moisture > wet {
+            Biome::Forest
+        } else if moisture < dry {
+            Biome::Desert
+        } else {
+            Biome::Plains
+        }
+    }
+}
+
+/// The biomes across one region, row by row
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct TerrainMap {
+    width: usize,
+    tiles: Vec<Biome>,
+}
+
+impl TerrainMap {
+    /// Survey the region centered on a spot of its planet's map
+    pub fn generate(surface: &Surface, center: (i32, i32), terrain: Option<Biome>) -> Self {
+        let mut tiles = Vec::with_capacity(REGION_MAP_WIDTH * REGION_MAP_HEIGHT);
+        for row in 0..REGION_MAP_HEIGHT {
+            for column in 0..REGION_MAP_WIDTH {
+                let x = center.0 as f64 - 0.5 + (column as f64 + 0.5) / REGION_MAP_WIDTH as f64;
+                let y = center.1 as f64 - 0.5 + (row as f64 + 0.5) / REGION_MAP_HEIGHT as f64;
+                tiles.push(surface.biome(x, y, terrain));
+            }
+        }
+        Self {
+            width: REGION_MAP_WIDTH,
+            tiles,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.tiles.len().checked_div(self.width).unwrap_or(0)
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<Biome> {
+        if x >= self.width {
+            return None;
+        }
+        self.tiles.get(y * self.width + x).copied()
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[Biome]> {
+        self.tiles.chunks(self.width.max(1))
+    }
+
+    /// Share of the region's land covered by a biome, from 0 to 1
+    pub fn land_share(&self, biome: Biome) -> f64 {
+        let land = self.tiles.iter().filter(|b| b.is_land()).count();
+        if land == 0 {
+            return 0.0;
+        }
+        self.tiles.iter().filter(|&&b| b == biome).count() as f64 / land as f64
+    }
+
+    /// Share of the whole map that is sea or shore, from 0 to 1
+    pub fn water_share(&self) -> f64 {
+        if self.tiles.is_empty() {
+            return 0.0;
+        }
+        let water = self
+            .tiles
+            .iter()
+            .filter(|&&b| matches!(b, Biome::Ocean | Biome::Coast))
+            .count();
+        water as f64 / self.tiles.len() as f64
+    }
+
+    /// The land biome covering the most tiles, or Ocean if there is no land
+    pub fn dominant(&self) -> Biome {
+        Biome::ALL
+            .into_iter()
+            .filter(|b| b.is_land())
+            .map(|biome| (self.tiles.iter().filter(|&&b| b == biome).count(), biome))
+            .filter(|&(count, _)| count > 0)
+            .max()
+            .map_or(Biome::Ocean, |(_, biome)| biome)
+    }
+
+    /// How the land suits farming, mining, logging and fishing. Farms want
+    /// plains and some shore, mines want mountains and some desert, lumber
+    /// camps want forest and fisheries want water. A third of the land (or
+    /// for fisheries, of the map) suiting a building gives the usual output.
+    pub fn yields(&self) -> Yields {
+        let output = |share: f64| {
+            (POOR_YIELD + share * 3.0 * (1.0 - POOR_YIELD)).clamp(POOR_YIELD, RICH_YIELD)
+        };
+        Yields {
+            farming: output(self.land_share(Biome::Plains) + self.land_share(Biome::Coast) / 2.0),
+            mining: output(
+                self.land_share(Biome::Mountains) + self.land_share(Biome::Desert) / 2.0,
+            ),
+            logging: output(self.land_share(Biome::Forest)),
+            fishing: output(self.water_share()),
+        }
+    }
+}
+
+impl From<TerrainMap> for String {
+    /// Rows of biome codes separated by slashes
+    fn from(map: TerrainMap) -> String {
+        map.rows()
+            .map(|row| row.iter().map(|b| b.code()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+impl TryFrom<String> for TerrainMap {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, String> {
+        let mut width = None;
+        let mut tiles = Vec::new();
+        for row in text.split('/') {
+            let count = row.chars().count();
+            if *width.get_or_insert(count) != count {
+                return Err(String::from("terrain rows differ in length"));
+            }
+            for code in row.chars() {
+                tiles.push(
+                    Biome::from_code(code)
+                        .ok_or_else(|| format!("unknown terrain code '{}'", code))?,
+                );
+            }
+        }
+        Ok(Self {
+            width: width.unwrap_or(0),
+            tiles,
+        })
+    }
+}
+
+/// SplitMix64's finalizer, spreading any change in the input over every bit
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// A value from 0 to 1 fixed for each lattice point
+fn lattice(seed: u64, x: i64, y: i64) -> f64 {
+    let hash = mix(seed ^ mix(x as u64 ^ mix(y as u64)));
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Lattice values blended smoothly between the points around `(x, y)`
+fn value_noise(seed: u64, x: f64, y: f64) -> f64 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let smooth = |t: f64| t * t * (3.0 - 2.0 * t);
+    let (tx, ty) = (smooth(x - x0), smooth(y - y0));
+    let (x0, y0) = (x0 as i64, y0 as i64);
+    let top = lerp(lattice(seed, x0, y0), lattice(seed, x0 + 1, y0), tx);
+    let bottom = lerp(lattice(seed, x0, y0 + 1), lattice(seed, x0 + 1, y0 + 1), tx);
+    lerp(top, bottom, ty)
+}
+
+/// Layers of noise, each twice as fine and half as strong as the last,
+/// scaled back to 0 to 1
+fn fractal(seed: u64, x: f64, y: f64) -> f64 {
+    let (mut total, mut weight, mut frequency, mut amplitude) = (0.0, 0.0, BASE_FREQUENCY, 1.0);
+    for octave in 0..OCTAVES {
+        let layer = mix(seed.wrapping_add(octave as u64));
+        total += value_noise(layer, x * frequency, y * frequency) * amplitude;
+        weight += amplitude;
+        frequency *= 2.0;
+        amplitude /= 2.0;
+    }
+    total / weight
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(map: &TerrainMap, biome: Biome) -> usize {
+        map.rows().flatten().filter(|&&b| b == biome).count()
+    }
+
+    #[test]
+    fn test_same_seed_same_surface() {
+        let surface = Surface::new(7, 1);
+        let map = TerrainMap::generate(&surface, (0, 0), None);
+        assert_eq!(map, TerrainMap::generate(&Surface::new(7, 1), (0, 0), None));
+        assert_ne!(map, TerrainMap::generate(&Surface::new(7, 2), (0, 0), None));
+        assert_eq!(
+            (map.width(), map.height()),
+            (REGION_MAP_WIDTH, REGION_MAP_HEIGHT)
+        );
+
+        let (height, moisture) = surface.sample(0.3, -1.7);
+        assert!((0.0..=1.0).contains(&height) && (0.0..=1.0).contains(&moisture));
+    }
+
+    #[test]
+    fn test_named_terrain_shapes_the_map() {
+        for planet in 1..20 {
+            let surface = Surface::new(42, planet);
+            let plain = TerrainMap::generate(&surface, (0, 0), None);
+            let mountains = TerrainMap::generate(&surface, (0, 0), Some(Biome::Mountains));
+            let forest = TerrainMap::generate(&surface, (0, 0), Some(Biome::Forest));
+            let coast = TerrainMap::generate(&surface, (0, 0), Some(Biome::Coast));
+            assert!(count(&mountains, Biome::Mountains) >= count(&plain, Biome::Mountains));
+            assert!(count(&forest, Biome::Forest) >= count(&plain, Biome::Forest));
+            assert!(count(&coast, Biome::Ocean) >= count(&plain, Biome::Ocean));
+        }
+    }
+
+    #[test]
+    fn test_yields_follow_the_land() {
+        let map = TerrainMap::try_from(String::from("mmm/mmf/~~~")).unwrap();
+        assert_eq!((map.width(), map.height()), (3, 3));
+        assert_eq!(map.get(2, 1), Some(Biome::Forest));
+        assert_eq!(map.dominant(), Biome::Mountains);
+        let yields = map.yields();
+        assert!((yields.mining - 1.75).abs() < 1e-9, "{}", yields.mining);
+        assert_eq!(yields.farming, POOR_YIELD);
+        assert!(yields.logging > POOR_YIELD && yields.logging < 1.0);
+        assert!((yields.fishing - 1.0).abs() < 1e-9);
+
+        // A third of the land suiting each keeps every yield even
+        let balanced = TerrainMap::try_from(String::from("mmf/fpp/~~~")).unwrap();
+        assert_eq!(balanced.yields(), Yields::EVEN);
+
+        assert_eq!(String::from(map), "mmm/mmf/~~~");
+        assert!(TerrainMap::try_from(String::from("mm/m")).is_err());
+        assert!(TerrainMap::try_from(String::from("mx")).is_err());
+    }
+}
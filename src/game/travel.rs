@@ -0,0 +1,194 @@
+//! The player's journeys between places.
+//!
+//! Going into a solar system, planet or region other than the one the
+//! player is in takes time rather than happening at once. Between planets,
+//! and between systems, the player books passage on a ship, paying a fare
+//! by the distance to the port they sail from, and goes as fast as a new
+//! ship does; between the regions of a planet they go overland, more slowly
+//! but for nothing. The world schedules the arrival with its other timers.
+//! Until it comes the player is in transit: the view stays where they set
+//! out and can't be moved from place to place.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+use thiserror::Error as ThisError;
+
+use crate::economy::Money;
+use crate::time::Calendar;
+use crate::zoom::{Position, ZoomLevel};
+
+use super::state::EntityId;
+
+/// System map cells a passage covers in a day, as fast as a new ship
+pub const PASSAGE_SPEED: f64 = 3.0;
+
+/// Planet map cells the player covers overland in a day
+pub const OVERLAND_SPEED: f64 = 4.0;
+
+/// Fare for each system map cell of a passage
+pub const FARE_PER_CELL: Money = Money(2.0);
+
+/// Shortest journey, however close two places are
+const MIN_JOURNEY: Duration = Duration::from_secs(3600);
+
+/// How a journey is made
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Route {
+    /// By ship to another solar system
+    Interstellar,
+    /// By ship to another planet of the same system
+    Interplanetary,
+    /// On foot to another region of the same planet
+    Overland,
+}
+
+impl Route {
+    /// The route from where the player is to a place, or None if the
+    /// place is where they already are, or they haven't been anywhere yet
+    pub fn between(from: &Position, to: &Position) -> Option<Route> {
+        let differs = |level| {
+            to.current_entity_id(level)
+                .is_some_and(|id| from.current_entity_id(level) != Some(id))
+        };
+        if from.current_system_id.is_none() {
+            None
+        } else if differs(ZoomLevel::SolarSystem) {
+            Some(Route::Interstellar)
+        } else if differs(ZoomLevel::Planet) {
+            Some(Route::Interplanetary)
+        } else if differs(ZoomLevel::Region) {
+            Some(Route::Overland)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the route is sailed, and paid for
+    pub fn by_ship(self) -> bool {
+        self != Route::Overland
+    }
+
+    /// How long the route takes over `distance` map cells
+    pub fn duration(self, distance: f64) -> Duration {
+        let speed = if self.by_ship() {
+            PASSAGE_SPEED
+        } else {
+            OVERLAND_SPEED
+        };
+        let days = distance.max(0.0) / speed;
+        Duration::from_secs_f64(days * Calendar::DEFAULT_DAY_LENGTH.as_secs_f64()).max(MIN_JOURNEY)
+    }
+
+    /// What passage over `distance` system map cells costs; nothing
+    /// overland
+    pub fn fare(self, distance: f64) -> Money {
+        if self.by_ship() {
+            FARE_PER_CELL * distance.max(0.0)
+        } else {
+            Money::ZERO
+        }
+    }
+}
+
+impl fmt::Display for Route {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.by_ship() {
+            "by ship"
+        } else {
+            "overland"
+        })
+    }
+}
+
+/// The player on the way to a place
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Journey {
+    /// Level of the place bound for, where the view goes on arrival
+    pub level: ZoomLevel,
+    pub to: EntityId,
+    pub destination: String,
+    pub route: Route,
+    /// Where the player will be once they arrive
+    pub bound_for: Position,
+    pub fare: Money,
+    pub departed: Duration,
+    pub arrives: Duration,
+}
+
+impl Journey {
+    /// Share of the way covered by `now`, from 0 to 1
+    pub fn progress(&self, now: Duration) -> f64 {
+        let total = self.arrives.saturating_sub(self.departed).as_secs_f64();
+        if total <= 0.0 {
+            return 1.0;
+        }
+        (now.saturating_sub(self.departed).as_secs_f64() / total).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(ThisError, Debug, Clone, PartialEq)]
+pub enum TravelError {
+    #[error("you are already on your way to {0}")]
+    InTransit(String),
+    #[error("the hyperlanes to {0} have collapsed")]
+    LanesClosed(String),
+    #[error("there is no port to sail from or to")]
+    NoPort,
+    #[error("passage costs {0}, more than your purse holds")]
+    CannotAfford(String),
+    #[error("{0} can't be reached from here")]
+    Unreachable(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(system: EntityId, planet: Option<EntityId>, region: Option<EntityId>) -> Position {
+        Position {
+            current_system_id: Some(system),
+            current_planet_id: planet,
+            current_region_id: region,
+            ..Position::new()
+        }
+    }
+
+    #[test]
+    fn test_routes_go_by_the_outermost_place_that_differs() {
+        let home = at(1, Some(1), Some(1));
+        assert_eq!(
+            Route::between(&home, &at(2, Some(5), None)),
+            Some(Route::Interstellar)
+        );
+        assert_eq!(
+            Route::between(&home, &at(1, Some(2), None)),
+            Some(Route::Interplanetary)
+        );
+        assert_eq!(
+            Route::between(&home, &at(1, Some(1), Some(3))),
+            Some(Route::Overland)
+        );
+        // Going into the system or planet the player is in costs nothing
+        assert_eq!(Route::between(&home, &at(1, None, None)), None);
+        assert_eq!(Route::between(&home, &at(1, Some(1), None)), None);
+        // Nor does a first step anywhere
+        assert_eq!(Route::between(&Position::new(), &at(2, None, None)), None);
+        // A system's planets are a passage away from the system itself
+        assert_eq!(
+            Route::between(&at(2, None, None), &at(2, Some(5), None)),
+            Some(Route::Interplanetary)
+        );
+    }
+
+    #[test]
+    fn test_passage_is_quicker_but_paid_for() {
+        let day = Calendar::DEFAULT_DAY_LENGTH;
+        assert_eq!(Route::Interplanetary.duration(6.0), day * 2);
+        assert_eq!(Route::Overland.duration(6.0), day * 3 / 2);
+        assert_eq!(Route::Overland.duration(0.0), MIN_JOURNEY);
+        assert_eq!(Route::Interstellar.fare(10.0), Money(20.0));
+        assert_eq!(Route::Overland.fare(10.0), Money::ZERO);
+        assert_eq!(Route::Interplanetary.to_string(), "by ship");
+    }
+}
@@ -0,0 +1,123 @@
+//! A first-run walkthrough of the basic controls.
+//!
+//! Each step asks the player to press one key and moves on once they do.
+//! Progress is kept in the profile, so the tutorial is only seen once and a
+//! player who quits partway picks up where they left off.
+
+use serde::{Deserialize, Serialize};
+
+use crate::input::InputAction;
+
+struct Step {
+    prompt: &'static str,
+    done_by: fn(InputAction) -> bool,
+}
+
+const STEPS: [Step; 6] = [
+    Step {
+        prompt: "Press SPACE to start the clock",
+        done_by: |action| action == InputAction::TogglePause,
+    },
+    Step {
+        prompt: "Press SPACE again to pause it",
+        done_by: |action| action == InputAction::TogglePause,
+    },
+    Step {
+        prompt: "Press Z to zoom in on the place under you",
+        done_by: |action| action == InputAction::ZoomIn,
+    },
+    Step {
+        prompt: "Use the arrow keys to move around the map",
+        done_by: |action| {
+            matches!(
+                action,
+                InputAction::MoveUp
+                    | InputAction::MoveDown
+                    | InputAction::MoveLeft
+                    | InputAction::MoveRight
+            )
+        },
+    },
+    Step {
+        prompt: "Press X to zoom back out",
+        done_by: |action| action == InputAction::ZoomOut,
+    },
+    Step {
+        prompt: "Press I to open the inspector for what's in view",
+        done_by: |action| action == InputAction::ToggleSidebar,
+    },
+];
+
+/// How far the player has got through the tutorial
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tutorial {
+    /// Steps done, or all of them once skipped
+    completed: usize,
+}
+
+impl Tutorial {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.completed >= STEPS.len()
+    }
+
+    /// What the current step asks for, numbered, or None once finished
+    pub fn prompt(&self) -> Option<String> {
+        let step = STEPS.get(self.completed)?;
+        Some(format!(
+            "Tutorial {}/{}: {}",
+            self.completed + 1,
+            STEPS.len(),
+            step.prompt
+        ))
+    }
+
+    /// Move on if the action is what the current step asks for. Returns
+    /// whether it did.
+    pub fn observe(&mut self, action: InputAction) -> bool {
+        match STEPS.get(self.completed) {
+            Some(step) if (step.done_by)(action) => {
+                self.completed += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn skip(&mut self) {
+        self.completed = STEPS.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_steps_follow_the_right_keys() {
+        let mut tutorial = Tutorial::new();
+        assert!(tutorial.prompt().unwrap().starts_with("Tutorial 1/6"));
+        assert!(!tutorial.observe(InputAction::ZoomIn));
+        for action in [
+            InputAction::TogglePause,
+            InputAction::TogglePause,
+            InputAction::ZoomIn,
+            InputAction::MoveLeft,
+            InputAction::ZoomOut,
+        ] {
+            assert!(tutorial.observe(action));
+        }
+        assert!(!tutorial.is_finished());
+        assert!(tutorial.observe(InputAction::ToggleSidebar));
+        assert!(tutorial.is_finished());
+        assert_eq!(tutorial.prompt(), None);
+
+        let mut skipped = Tutorial::new();
+        skipped.skip();
+        assert!(skipped.is_finished());
+        assert!(!skipped.observe(InputAction::TogglePause));
+    }
+}
@@ -0,0 +1,313 @@
+//! Watch expressions pinned from the console.
+//!
+//! A watch is a [`Query`] worked out for every region in a place and
+//! folded into one number, such as `avg price(grain) on Terra`. The
+//! simulation re-evaluates its watches every few ticks and the debug
+//! overlay lists their latest values.
+
+use crate::modding::{Query, QueryError};
+use crate::zoom::ZoomLevel;
+
+use super::WorldState;
+use super::state::EntityId;
+use super::stats::GALAXY_ID;
+
+/// Ticks between evaluations of the watches, unless changed
+pub const WATCH_INTERVAL: u64 = 20;
+
+/// Most watches that can be pinned at once
+pub const MAX_WATCHES: usize = 8;
+
+/// How a watch folds its regions' values into one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Avg,
+    Sum,
+    Min,
+    Max,
+}
+
+impl Aggregate {
+    pub const ALL: [Aggregate; 4] = [Self::Avg, Self::Sum, Self::Min, Self::Max];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Avg => "avg",
+            Self::Sum => "sum",
+            Self::Min => "min",
+            Self::Max => "max",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|aggregate| aggregate.name().eq_ignore_ascii_case(name))
+    }
+
+    /// The values folded together, or None if there are none
+    fn fold(self, values: &[f64]) -> Option<f64> {
+        if values.is_empty() {
+            return None;
+        }
+        Some(match self {
+            Self::Avg => values.iter().sum::<f64>() / values.len() as f64,
+            Self::Sum => values.iter().sum(),
+            Self::Min => values.iter().copied().fold(f64::INFINITY, f64::min),
+            Self::Max => values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        })
+    }
+}
+
+/// A query pinned to a place
+pub struct Watch {
+    aggregate: Aggregate,
+    query: Query,
+    level: ZoomLevel,
+    place: EntityId,
+    /// Where the watch looks, as the player named it
+    place_name: String,
+    /// The latest value; None where no region had one
+    value: Result<Option<f64>, QueryError>,
+}
+
+impl Watch {
+    pub fn new(
+        aggregate: Aggregate,
+        query: Query,
+        (level, place): (ZoomLevel, EntityId),
+        place_name: String,
+    ) -> Self {
+        Self {
+            aggregate,
+            query,
+            level,
+            place,
+            place_name,
+            value: Ok(None),
+        }
+    }
+
+    /// What the watch shows, as it would be typed after `watch`
+    pub fn label(&self) -> String {
+        format!(
+            "{} {} on {}",
+            self.aggregate.name(),
+            self.query.expression(),
+            self.place_name
+        )
+    }
+
+    pub fn value(&self) -> &Result<Option<f64>, QueryError> {
+        &self.value
+    }
+
+    /// The latest value as the debug overlay shows it
+    pub fn reading(&self) -> String {
+        match &self.value {
+            Ok(Some(value)) => format!("{:.3}", value),
+            Ok(None) => String::from("n/a"),
+            Err(error) => error.to_string(),
+        }
+    }
+
+    /// Work the query out for each region in the place and fold the
+    /// results together
+    fn evaluate(&mut self, world: &WorldState) {
+        self.value = regions_in(world, self.level, self.place)
+            .into_iter()
+            .filter_map(|region| self.query.evaluate(world, region).transpose())
+            .collect::<Result<Vec<f64>, _>>()
+            .map(|values| self.aggregate.fold(&values));
+    }
+}
+
+/// The watches pinned so far, and how often they are worked out
+pub struct Watches {
+    watches: Vec<Watch>,
+    interval: u64,
+    /// The world's tick count when they were last worked out
+    evaluated_at: u64,
+}
+
+impl Default for Watches {
+    fn default() -> Self {
+        Self {
+            watches: Vec::new(),
+            interval: WATCH_INTERVAL,
+            evaluated_at: 0,
+        }
+    }
+}
+
+impl Watches {
+    pub fn iter(&self) -> impl Iterator<Item = &Watch> {
+        self.watches.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.watches.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.watches.is_empty()
+    }
+
+    pub fn interval(&self) -> u64 {
+        self.interval
+    }
+
+    /// Work the watches out every `ticks` ticks
+    pub fn set_interval(&mut self, ticks: u64) {
+        self.interval = ticks.max(1);
+    }
+
+    /// Pin a watch, worked out straight away. Returns it, or None if
+    /// [`MAX_WATCHES`] are already pinned.
+    pub fn add(&mut self, mut watch: Watch, world: &WorldState) -> Option<&Watch> {
+        if self.watches.len() >= MAX_WATCHES {
+            return None;
+        }
+        watch.evaluate(world);
+        self.watches.push(watch);
+        self.watches.last()
+    }
+
+    /// Unpin the watch at `index`, counting from zero
+    pub fn remove(&mut self, index: usize) -> Option<Watch> {
+        (index < self.watches.len()).then(|| self.watches.remove(index))
+    }
+
+    pub fn clear(&mut self) {
+        self.watches.clear();
+    }
+
+    /// Work the watches out again if the interval has passed since last
+    /// time. A rewind counts as time passing.
+    pub fn update(&mut self, world: &WorldState) {
+        let ticks = world.tick_count();
+        if ticks.abs_diff(self.evaluated_at) < self.interval {
+            return;
+        }
+        self.evaluated_at = ticks;
+        for watch in &mut self.watches {
+            watch.evaluate(world);
+        }
+    }
+}
+
+/// Every region in a place: those beneath it, or the one it lies in
+fn regions_in(world: &WorldState, level: ZoomLevel, id: EntityId) -> Vec<EntityId> {
+    if level == ZoomLevel::Galaxy {
+        return world.ids(ZoomLevel::Region);
+    }
+    if let Some(&(_, region)) = world
+        .ancestry(level, id)
+        .iter()
+        .find(|(at, _)| *at == ZoomLevel::Region)
+    {
+        return vec![region];
+    }
+
+    let mut places = vec![id];
+    let mut at = level;
+    while at != ZoomLevel::Region {
+        let Some(inner) = at.zoom_in() else {
+            return Vec::new();
+        };
+        places = places
+            .into_iter()
+            .flat_map(|place| world.placed_in(inner, Some(place)))
+            .map(|(id, _)| id)
+            .collect();
+        at = inner;
+    }
+    places
+}
+
+/// The galaxy as a place to watch, for watches not pinned anywhere else
+pub fn everywhere() -> (ZoomLevel, EntityId) {
+    (ZoomLevel::Galaxy, GALAXY_ID)
+}
+
+#[cfg(all(test, feature = "scripting"))]
+mod tests {
+    use super::*;
+    use crate::time::TICK_LENGTH;
+
+    fn watch(expression: &str, aggregate: Aggregate, place: (ZoomLevel, EntityId)) -> Watch {
+        let query = Query::compile(expression).unwrap();
+        Watch::new(aggregate, query, place, String::from("there"))
+    }
+
+    #[test]
+    fn test_watches_fold_their_regions() {
+        let mut world = WorldState::new();
+        let second = world.spawn(ZoomLevel::Region).unwrap();
+        let mut watches = Watches::default();
+        let firms = |watch: &Watch| watch.value().clone().unwrap();
+
+        let all = watches
+            .add(watch("firms", Aggregate::Sum, everywhere()), &world)
+            .unwrap();
+        assert_eq!(firms(all), Some(6.0));
+        let terra = watches
+            .add(
+                watch("firms", Aggregate::Min, (ZoomLevel::Planet, 1)),
+                &world,
+            )
+            .unwrap();
+        assert_eq!(firms(terra), Some(6.0));
+        let area = watches
+            .add(
+                watch("firms", Aggregate::Avg, (ZoomLevel::LocalArea, 1)),
+                &world,
+            )
+            .unwrap();
+        assert_eq!(firms(area), Some(6.0));
+        let empty = watches
+            .add(
+                watch("firms", Aggregate::Max, (ZoomLevel::Region, second)),
+                &world,
+            )
+            .unwrap();
+        assert_eq!(firms(empty), Some(0.0));
+        assert_eq!(watches.len(), 4);
+
+        assert!(watches.remove(4).is_none());
+        watches.remove(0).unwrap();
+        assert_eq!(watches.iter().next().unwrap().label(), "min firms on there");
+    }
+
+    #[test]
+    fn test_watches_refresh_every_interval() {
+        let mut world = WorldState::new();
+        let mut watches = Watches::default();
+        watches.set_interval(4);
+        // One for each region in the galaxy
+        watches.add(watch("1", Aggregate::Sum, everywhere()), &world);
+        watches.add(watch("wage / 0.0", Aggregate::Avg, everywhere()), &world);
+        let readings =
+            |watches: &Watches| -> Vec<String> { watches.iter().map(Watch::reading).collect() };
+        assert_eq!(readings(&watches), ["1.000", "n/a"]);
+
+        world.spawn(ZoomLevel::Region).unwrap();
+        for _ in 0..3 {
+            world.update(TICK_LENGTH);
+            watches.update(&world);
+        }
+        assert_eq!(readings(&watches)[0], "1.000");
+        world.update(TICK_LENGTH);
+        watches.update(&world);
+        assert_eq!(readings(&watches)[0], "2.000");
+
+        while watches.len() < MAX_WATCHES {
+            watches.add(watch("1", Aggregate::Sum, everywhere()), &world);
+        }
+        assert!(
+            watches
+                .add(watch("1", Aggregate::Sum, everywhere()), &world)
+                .is_none()
+        );
+    }
+}
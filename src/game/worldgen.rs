@@ -0,0 +1,542 @@
+//! Generating a world from a parameters file.
+//!
+//! A [`WorldConfig`] is read from TOML and says how many systems to scatter
+//! around the home system, how many planets, regions, local areas and rooms
+//! each holds, how often each terrain occurs, how plentiful raw resources and
+//! deposits are, how many polities divide the new systems between them and
+//! how many ships trade between the planets. Every system, the home system
+//! included, is then settled by a culture of its own, which names its
+//! star, planets, regions, settlements and people. Every field is optional:
+//!
+//! ```toml
+//! seed = 42
+//! systems = 12
+//! galaxy_radius = 10
+//! planets_per_system = { min = 2, max = 6 }
+//! resource_abundance = 0.5
+//! polities = 3
+//! ships = 8
+//!
+//! [terrain]
+//! Desert = 3.0
+//! Coast = 1.0
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use thiserror::Error as ThisError;
+
+use crate::economy::{FiscalPolicy, GoodCategory, TARGET_STOCK};
+use crate::zoom::{Bounds, ZoomLevel};
+
+use super::names::{NameGenerator, NameKind};
+use super::rng::Rng;
+use super::state::{DEFAULT_SEED, EntityId, Placement, WorldState};
+
+/// How far from its parent's center a planet, region, area or room is placed
+const CHILD_RADIUS: i32 = 3;
+
+/// Highest tax rate a generated polity starts with
+const MAX_START_TAX: f64 = 0.3;
+
+/// Highest tariff a generated polity starts with
+const MAX_START_TARIFF: f64 = 0.2;
+
+/// Highest sales tax a generated polity starts with
+const MAX_START_SALES_TAX: f64 = 0.1;
+
+/// Highest share of its budget a generated polity starts out spending a day
+const MAX_START_SPENDING: f64 = 0.2;
+
+#[derive(ThisError, Debug)]
+pub enum WorldConfigError {
+    #[error("could not read world config {path}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("invalid world config {path}")]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+}
+
+/// An inclusive range of how many of something to generate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Count {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl Count {
+    pub const fn new(min: u32, max: u32) -> Self {
+        Self { min, max }
+    }
+
+    fn pick(self, rng: &mut Rng) -> u32 {
+        rng.range(self.min as u64, self.max.max(self.min) as u64 + 1) as u32
+    }
+}
+
+/// Parameters for generating a world around the home system
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorldConfig {
+    /// Seed for the layout and every generated attribute
+    pub seed: u64,
+    /// Stars the galaxy is said to contain
+    pub star_count: u64,
+    /// Systems generated besides the home system
+    pub systems: u32,
+    /// How far from the galaxy's center systems are placed
+    pub galaxy_radius: i32,
+    pub planets_per_system: Count,
+    pub regions_per_planet: Count,
+    pub areas_per_region: Count,
+    /// Rooms in each local area, furnished for their type
+    pub rooms_per_area: Count,
+    /// Relative chance of each terrain for a generated region
+    pub terrain: BTreeMap<String, f64>,
+    /// Multiplier on the raw goods a generated area's market starts with
+    /// and on the size of each generated region's deposits
+    pub resource_abundance: f64,
+    /// Polities founded among the generated systems, each holding every
+    /// planet of one of them
+    pub polities: u32,
+    /// Ships put in port at random planets
+    pub ships: u32,
+}
+
+impl Default for WorldConfig {
+    fn default() -> Self {
+        let terrain = ["Plains", "Mountains", "Forest", "Desert", "Coast"]
+            .into_iter()
+            .map(|name| (name.to_string(), 1.0))
+            .collect();
+        Self {
+            seed: DEFAULT_SEED,
+            star_count: 1_000_000_000,
+            systems: 4,
+            galaxy_radius: 6,
+            planets_per_system: Count::new(1, 4),
+            regions_per_planet: Count::new(1, 3),
+            areas_per_region: Count::new(1, 2),
+            rooms_per_area: Count::new(1, 2),
+            terrain,
+            resource_abundance: 1.0,
+            polities: 2,
+            ships: 4,
+        }
+    }
+}
+
+impl WorldConfig {
+    pub fn parse(text: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    pub fn read_from(path: &Path) -> Result<Self, WorldConfigError> {
+        let text = std::fs::read_to_string(path).map_err(|source| WorldConfigError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&text).map_err(|source| WorldConfigError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Build a world: the home system, then everything the config asks for
+    /// around it. The same config always generates the same world.
+    pub fn generate(&self) -> WorldState {
+        let mut world = WorldState::new();
+        world.reseed(self.seed);
+        world.galaxy_mut().star_count = self.star_count;
+        self.size_maps(&mut world);
+        let mut rng = Rng::new(self.seed);
+
+        let mut taken: HashSet<(i32, i32)> = world
+            .placed_in(ZoomLevel::SolarSystem, None)
+            .into_iter()
+            .map(|(_, coords)| coords)
+            .collect();
+        let mut systems = Vec::new();
+        for _ in 0..self.systems {
+            let Some(coords) = free_spot(&mut rng, self.galaxy_radius, &mut taken) else {
+                break;
+            };
+            let Some(system) = world.spawn_at(ZoomLevel::SolarSystem, Placement::new(None, coords))
+            else {
+                break;
+            };
+            let planets = self.populate(&mut world, &mut rng, ZoomLevel::Planet, system);
+            if let Some(state) = world.get_system_mut(system) {
+                state.planet_count = planets;
+            }
+            systems.push(system);
+        }
+        self.name_places(&mut world);
+        self.found_polities(&mut world, &mut rng, &systems);
+        self.launch_ships(&mut world, &mut rng);
+
+        // Generated entities are part of the starting world, not arrivals
+        world.take_spawned();
+        world
+    }
+
+    /// Fit the galaxy's map around every system, and the maps below it to
+    /// the grid children are placed on
+    fn size_maps(&self, world: &mut WorldState) {
+        let galaxy = world.map_bounds(ZoomLevel::Galaxy);
+        let reach = self.galaxy_radius.max(0) as u32 * 2 + 1;
+        world.set_map_bounds(
+            ZoomLevel::Galaxy,
+            Bounds::new(
+                galaxy.width.max(reach),
+                galaxy.height.max(reach),
+                galaxy.edge,
+            ),
+        );
+        for level in [ZoomLevel::SolarSystem, ZoomLevel::Planet, ZoomLevel::Region] {
+            let edge = world.map_bounds(level).edge;
+            world.set_map_bounds(level, Bounds::around(CHILD_RADIUS as u32, edge));
+        }
+    }
+
+    /// Spawn `level` children under `parent`, and theirs in turn, returning
+    /// how many were placed
+    fn populate(
+        &self,
+        world: &mut WorldState,
+        rng: &mut Rng,
+        level: ZoomLevel,
+        parent: EntityId,
+    ) -> u32 {
+        let count = match level {
+            ZoomLevel::Planet => self.planets_per_system,
+            ZoomLevel::Region => self.regions_per_planet,
+            ZoomLevel::LocalArea => self.areas_per_region,
+            ZoomLevel::Room => self.rooms_per_area,
+            _ => return 0,
+        }
+        .pick(rng);
+
+        let mut taken = HashSet::new();
+        let mut placed = 0;
+        for _ in 0..count {
+            let Some(coords) = free_spot(rng, CHILD_RADIUS, &mut taken) else {
+                break;
+            };
+            let Some(id) = world.spawn_at(level, Placement::new(Some(parent), coords)) else {
+                break;
+            };
+            placed += 1;
+            match level {
+                ZoomLevel::Region => {
+                    if let Some(terrain) = self.pick_terrain(rng) {
+                        world.set_terrain(id, &terrain);
+                    }
+                    world.prospect(id, self.resource_abundance);
+                }
+                ZoomLevel::LocalArea => self.open_market(world, id),
+                _ => {}
+            }
+            if let Some(child) = level.zoom_in() {
+                self.populate(world, rng, child, id);
+            }
+        }
+        placed
+    }
+
+    /// Name every system and what is in it, each system in the style of a
+    /// culture picked for it, and the people living there. Names come from
+    /// a stream of their own, so they don't shift the layout.
+    fn name_places(&self, world: &mut WorldState) {
+        let mut rng = Rng::new(self.seed);
+        let mut taken = HashSet::new();
+        for system in world.ids(ZoomLevel::SolarSystem) {
+            let mut names = NameGenerator::new(rng.next_u64());
+            let mut places = vec![(ZoomLevel::SolarSystem, system)];
+            while let Some((level, id)) = places.pop() {
+                let kind = match level {
+                    ZoomLevel::SolarSystem => NameKind::Star,
+                    ZoomLevel::Planet => NameKind::Planet,
+                    ZoomLevel::Region => NameKind::Region,
+                    ZoomLevel::LocalArea => NameKind::Settlement,
+                    _ => continue,
+                };
+                let name = names.unique(kind, &mut taken);
+                world.entities_mut().rename(level, id, name);
+                if let Some(child) = level.zoom_in() {
+                    places.extend(
+                        world
+                            .placed_in(child, Some(id))
+                            .into_iter()
+                            .map(|(child_id, _)| (child, child_id)),
+                    );
+                }
+                if level != ZoomLevel::LocalArea {
+                    continue;
+                }
+                let people: Vec<EntityId> = world
+                    .characters_in_area(id)
+                    .map(|character| character.id)
+                    .collect();
+                for person in people {
+                    let name = names.unique(NameKind::Person, &mut taken);
+                    if let Some(character) = world.character_mut(person) {
+                        character.name = name;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Found the configured polities, each in a different generated system
+    /// and with its own taxes, until the systems run out
+    fn found_polities(&self, world: &mut WorldState, rng: &mut Rng, systems: &[EntityId]) {
+        for &system in systems.iter().take(self.polities as usize) {
+            let policy = FiscalPolicy {
+                tax_rate: (rng.next_f64() * MAX_START_TAX * 100.0).round() / 100.0,
+                tariff: (rng.next_f64() * MAX_START_TARIFF * 100.0).round() / 100.0,
+                sales_tax: (rng.next_f64() * MAX_START_SALES_TAX * 100.0).round() / 100.0,
+                spending: (rng.next_f64() * MAX_START_SPENDING * 100.0).round() / 100.0,
+            };
+            let name = world
+                .entities()
+                .name(ZoomLevel::SolarSystem, system)
+                .map(|name| format!("Dominion of {}", name))
+                .unwrap_or_default();
+            let polity = world.found_polity(&name, policy);
+            for (planet, _) in world.placed_in(ZoomLevel::Planet, Some(system)) {
+                world.claim(polity, ZoomLevel::Planet, planet);
+            }
+        }
+    }
+
+    /// Launch the configured ships, each in port at a random planet and a
+    /// little faster or slower than the usual
+    fn launch_ships(&self, world: &mut WorldState, rng: &mut Rng) {
+        let planets = world.ids(ZoomLevel::Planet);
+        for _ in 0..self.ships {
+            let Some(&planet) = rng.choose(&planets) else {
+                break;
+            };
+            let speed = 0.75 + rng.next_f64() / 2.0;
+            if let Some(ship) = world.launch_ship(planet).and_then(|id| world.ship_mut(id)) {
+                ship.speed *= speed;
+            }
+        }
+    }
+
+    fn pick_terrain(&self, rng: &mut Rng) -> Option<String> {
+        let total: f64 = self.terrain.values().map(|w| w.max(0.0)).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut roll = rng.next_f64() * total;
+        for (name, weight) in &self.terrain {
+            roll -= weight.max(0.0);
+            if roll < 0.0 {
+                return Some(name.clone());
+            }
+        }
+        self.terrain.keys().next_back().cloned()
+    }
+
+    /// Open an area's market with raw goods stocked by abundance
+    fn open_market(&self, world: &mut WorldState, area_id: EntityId) {
+        let economy = world.economy_mut();
+        economy.open_market(area_id);
+        let raw: Vec<_> = economy
+            .catalog()
+            .iter()
+            .filter(|(_, good)| good.category == GoodCategory::Raw)
+            .map(|(id, _)| id)
+            .collect();
+        let stock = TARGET_STOCK * self.resource_abundance.max(0.0);
+        if let Some(market) = economy.market_mut(area_id) {
+            for id in raw {
+                market.take(id, TARGET_STOCK);
+                market.add(id, stock);
+            }
+        }
+    }
+}
+
+/// A random unused spot within `radius` of the center, marking it taken.
+/// Returns None once every spot is used.
+fn free_spot(rng: &mut Rng, radius: i32, taken: &mut HashSet<(i32, i32)>) -> Option<(i32, i32)> {
+    let radius = radius.max(0);
+    let free: Vec<(i32, i32)> = (-radius..=radius)
+        .flat_map(|y| (-radius..=radius).map(move |x| (x, y)))
+        .filter(|spot| !taken.contains(spot))
+        .collect();
+    let spot = *rng.choose(&free)?;
+    taken.insert(spot);
+    Some(spot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let config = WorldConfig::parse("systems = 2\n[terrain]\nDesert = 1.0").unwrap();
+        assert_eq!(config.systems, 2);
+        assert_eq!(config.planets_per_system, Count::new(1, 4));
+        assert_eq!(config.terrain.len(), 1);
+        assert!(WorldConfig::parse("sistems = 2").is_err());
+    }
+
+    #[test]
+    fn test_generates_requested_counts() {
+        let config = WorldConfig {
+            systems: 3,
+            planets_per_system: Count::new(2, 2),
+            regions_per_planet: Count::new(1, 1),
+            areas_per_region: Count::new(0, 0),
+            terrain: BTreeMap::from([(String::from("Desert"), 1.0)]),
+            ..WorldConfig::default()
+        };
+        let world = config.generate();
+        assert_eq!(world.ids(ZoomLevel::SolarSystem).len(), 4);
+        assert_eq!(world.ids(ZoomLevel::Planet).len(), 7);
+        let regions = world.ids(ZoomLevel::Region);
+        assert_eq!(regions.len(), 7);
+        assert!(
+            regions[1..]
+                .iter()
+                .all(|&id| world.get_region(id).unwrap().terrain_type == "Desert")
+        );
+    }
+
+    #[test]
+    fn test_tiny_galaxy_stops_when_full() {
+        let config = WorldConfig {
+            systems: 50,
+            galaxy_radius: 1,
+            ..WorldConfig::default()
+        };
+        assert_eq!(config.generate().ids(ZoomLevel::SolarSystem).len(), 9);
+    }
+
+    #[test]
+    fn test_abundance_scales_raw_stock() {
+        let config = WorldConfig {
+            systems: 1,
+            areas_per_region: Count::new(1, 1),
+            resource_abundance: 0.5,
+            ..WorldConfig::default()
+        };
+        let world = config.generate();
+        let area = *world.ids(ZoomLevel::LocalArea).last().unwrap();
+        let ore = world.economy().catalog().id("ore").unwrap();
+        let market = world.economy().market(area).unwrap();
+        assert_eq!(market.stock(ore), TARGET_STOCK / 2.0);
+        let region = *world.ids(ZoomLevel::Region).last().unwrap();
+        assert!(world.get_region(region).unwrap().deposits.is_some());
+        assert!(world.clone().take_spawned().is_empty());
+    }
+
+    #[test]
+    fn test_polities_hold_generated_systems() {
+        let config = WorldConfig {
+            systems: 3,
+            polities: 2,
+            planets_per_system: Count::new(1, 1),
+            ..WorldConfig::default()
+        };
+        let world = config.generate();
+        let generated: Vec<_> = world.polities().filter(|polity| polity.id != 301).collect();
+        assert_eq!(generated.len(), 2);
+        let system_names: Vec<&str> = world
+            .ids(ZoomLevel::SolarSystem)
+            .into_iter()
+            .filter_map(|id| world.entities().name(ZoomLevel::SolarSystem, id))
+            .collect();
+        for polity in generated {
+            let system = polity.name.strip_prefix("Dominion of ").unwrap();
+            assert!(system_names.contains(&system), "{}", polity.name);
+            assert_eq!(polity.planets.len(), 1);
+            assert!(polity.policy.tax_rate <= MAX_START_TAX);
+            assert!(polity.policy.tariff <= MAX_START_TARIFF);
+            assert!(polity.policy.sales_tax <= MAX_START_SALES_TAX);
+            assert!(polity.policy.spending <= MAX_START_SPENDING);
+        }
+        assert!(world.audit().is_empty());
+    }
+
+    #[test]
+    fn test_ships_start_in_port() {
+        let config = WorldConfig {
+            systems: 2,
+            ships: 3,
+            ..WorldConfig::default()
+        };
+        let world = config.generate();
+        assert_eq!(world.ships().count(), 3);
+        assert!(world.ships().all(|ship| {
+            ship.docked_at()
+                .is_some_and(|planet| world.entities().contains(ZoomLevel::Planet, planet))
+        }));
+    }
+
+    #[test]
+    fn test_same_seed_same_world() {
+        let config = WorldConfig::default();
+        let names = |world: &WorldState| {
+            world
+                .ids(ZoomLevel::Region)
+                .into_iter()
+                .map(|id| world.get_region(id).unwrap().terrain_type.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&config.generate()), names(&config.generate()));
+    }
+
+    #[test]
+    fn test_places_and_people_get_distinct_names() {
+        let config = WorldConfig {
+            systems: 6,
+            ..WorldConfig::default()
+        };
+        let world = config.generate();
+        let mut names = Vec::new();
+        for level in [
+            ZoomLevel::SolarSystem,
+            ZoomLevel::Planet,
+            ZoomLevel::Region,
+            ZoomLevel::LocalArea,
+        ] {
+            for id in world.ids(level) {
+                names.push(world.entities().name(level, id).unwrap().to_string());
+            }
+        }
+        names.extend(world.characters().map(|character| character.name.clone()));
+        // The home system is renamed along with everything else
+        assert!(
+            !names
+                .iter()
+                .any(|name| name == "Sol System" || name == "Terra")
+        );
+        assert!(!names.iter().any(|name| name.starts_with("Planet ")));
+        let distinct: HashSet<&String> = names.iter().collect();
+        assert_eq!(distinct.len(), names.len(), "{:?}", names);
+
+        let again = config.generate();
+        let home = |world: &WorldState| {
+            world
+                .entities()
+                .name(ZoomLevel::Planet, 1)
+                .map(str::to_string)
+        };
+        assert_eq!(home(&world), home(&again));
+    }
+}
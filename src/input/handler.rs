@@ -1,71 +1,926 @@
 use crate::result::Result;
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
-use std::time::Duration;
+use crate::time::SPEED_PRESETS;
+use crate::ui::TextEdit;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use super::repeat::KeyRepeat;
+use KeyCategory::{Editing, Game, Map, Places, Screens, Time};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum InputAction {
     Quit,
+    /// Stop the clock and show the pause menu
+    OpenMenu,
     TogglePause,
     IncreaseSpeed,
     DecreaseSpeed,
+    /// Jump straight to a speed multiplier
+    SetSpeed(f64),
+    ToggleTurbo,
+    /// Go back to the latest snapshot of the world
+    Rewind,
     ZoomIn,
     ZoomOut,
     ToggleHelp,
+    ToggleDashboard,
+    ToggleCensus,
+    ToggleFinances,
+    ToggleTechnology,
+    ToggleMonetary,
+    ToggleProfiler,
+    /// Show or hide frame timings and world counts over the view
+    ToggleDebugOverlay,
+    ToggleSidebar,
+    ToggleMinimap,
+    /// Let the arrow keys pan the map without moving the player, or stop
+    ToggleCamera,
+    /// Bring a panned map back to the player
+    CenterCamera,
+    /// Pin or unpin the entity in view on the watchlist
+    TogglePin,
+    /// Save the view in a bookmark slot from 1 to 9
+    SetBookmark(u8),
+    /// Return to the view in a bookmark slot
+    JumpToBookmark(u8),
+    OpenBookmarks,
+    OpenSearch,
+    /// Show the profile's next map overlay, or none after the last
+    CycleOverlay,
+    /// Define a map overlay from a query
+    NewOverlay,
+    SkipTutorial,
     MoveUp,
     MoveDown,
     MoveLeft,
     MoveRight,
     Enter,
+    OpenConsole,
+    SubmitCommand,
+    /// Finish the word being typed into the console
+    Complete,
+    Confirm,
+    Deny,
+    Cancel,
+    ToggleEditor,
+    PaletteNext,
+    PalettePrevious,
+    Place,
+    Remove,
+    SaveScenario,
+    EditAttributes,
+    MarkSelection,
+    Copy,
+    Paste,
+    Undo,
+    OpenPolicy,
+    OpenSettings,
+    OpenBusiness,
+    NextField,
+    PreviousField,
+    /// Pick a menu's option by its number, counting from 1
+    Choose(u8),
+    Edit(TextEdit),
+    /// The terminal changed to the given width and height
+    Resize(u16, u16),
     None,
 }
 
+/// Determines how key presses are interpreted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputMode {
+    Normal,
+    /// A yes/no/cancel question is on screen
+    Prompt,
+    /// Keys are typed into the command console
+    Console,
+    /// The sandbox editor is placing and removing entities
+    Editor,
+    /// Keys edit the fields of an open form
+    Form,
+    /// A story screen waits to be dismissed
+    Interstitial,
+    /// The bookmark list is open, waiting for a slot to jump to
+    Bookmarks,
+    /// Keys are typed into the search box
+    Search,
+    /// A menu waits for an option to be picked
+    Menu,
+}
+
+/// What a key is for, grouping the keys listed in the help
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum KeyCategory {
+    Time,
+    Map,
+    Places,
+    Screens,
+    Editing,
+    Game,
+}
+
+impl KeyCategory {
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyCategory::Time => "Time",
+            KeyCategory::Map => "Map",
+            KeyCategory::Places => "Places",
+            KeyCategory::Screens => "Screens",
+            KeyCategory::Editing => "Editing",
+            KeyCategory::Game => "Game",
+        }
+    }
+}
+
+/// Keys that carry out an action in one input mode
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyBinding {
+    pub keys: &'static [KeyCode],
+    /// Modifiers that must be held; capitals need no Shift here, since the
+    /// character already says which case was typed
+    pub modifiers: KeyModifiers,
+    pub action: InputAction,
+    pub category: KeyCategory,
+    /// What the keys do, as the help lists them
+    pub description: &'static str,
+}
+
+impl KeyBinding {
+    const fn new(
+        category: KeyCategory,
+        keys: &'static [KeyCode],
+        action: InputAction,
+        description: &'static str,
+    ) -> Self {
+        Self {
+            keys,
+            modifiers: KeyModifiers::NONE,
+            action,
+            category,
+            description,
+        }
+    }
+
+    const fn shifted(mut self) -> Self {
+        self.modifiers = KeyModifiers::SHIFT;
+        self
+    }
+
+    fn matches(&self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        self.keys.contains(&code) && modifiers.contains(self.modifiers)
+    }
+}
+
+/// One row of the help: keys bound alike and what they do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpRow {
+    pub category: KeyCategory,
+    /// The keys as typed, such as "Z", "+/=" or "Shift+1-9"
+    pub keys: String,
+    pub description: &'static str,
+}
+
+const NORMAL_KEYS: &[KeyBinding] = &[
+    KeyBinding::new(
+        Time,
+        &[KeyCode::Char(' ')],
+        InputAction::TogglePause,
+        "Play/Pause",
+    ),
+    KeyBinding::new(
+        Time,
+        &[KeyCode::Char('+'), KeyCode::Char('=')],
+        InputAction::IncreaseSpeed,
+        "Increase speed",
+    ),
+    KeyBinding::new(
+        Time,
+        &[KeyCode::Char('-'), KeyCode::Char('_')],
+        InputAction::DecreaseSpeed,
+        "Decrease speed",
+    ),
+    KeyBinding::new(
+        Time,
+        &[KeyCode::Char('1')],
+        InputAction::SetSpeed(SPEED_PRESETS[0]),
+        "Jump to a preset speed",
+    ),
+    KeyBinding::new(
+        Time,
+        &[KeyCode::Char('2')],
+        InputAction::SetSpeed(SPEED_PRESETS[1]),
+        "Jump to a preset speed",
+    ),
+    KeyBinding::new(
+        Time,
+        &[KeyCode::Char('3')],
+        InputAction::SetSpeed(SPEED_PRESETS[2]),
+        "Jump to a preset speed",
+    ),
+    KeyBinding::new(
+        Time,
+        &[KeyCode::Char('4')],
+        InputAction::SetSpeed(SPEED_PRESETS[3]),
+        "Jump to a preset speed",
+    ),
+    KeyBinding::new(
+        Time,
+        &[KeyCode::Char('5')],
+        InputAction::SetSpeed(SPEED_PRESETS[4]),
+        "Jump to a preset speed",
+    ),
+    KeyBinding::new(
+        Time,
+        &[KeyCode::Char('t'), KeyCode::Char('T')],
+        InputAction::ToggleTurbo,
+        "Turbo",
+    ),
+    KeyBinding::new(
+        Time,
+        &[KeyCode::Char('u'), KeyCode::Char('U')],
+        InputAction::Rewind,
+        "Rewind to the latest snapshot",
+    ),
+    KeyBinding::new(
+        Map,
+        &[KeyCode::Char('z'), KeyCode::Char('Z')],
+        InputAction::ZoomIn,
+        "Zoom in",
+    ),
+    KeyBinding::new(
+        Map,
+        &[KeyCode::Char('x'), KeyCode::Char('X')],
+        InputAction::ZoomOut,
+        "Zoom out",
+    ),
+    KeyBinding::new(Map, &[KeyCode::Up], InputAction::MoveUp, "Move"),
+    KeyBinding::new(Map, &[KeyCode::Down], InputAction::MoveDown, "Move"),
+    KeyBinding::new(Map, &[KeyCode::Left], InputAction::MoveLeft, "Move"),
+    KeyBinding::new(Map, &[KeyCode::Right], InputAction::MoveRight, "Move"),
+    KeyBinding::new(
+        Map,
+        &[KeyCode::Enter],
+        InputAction::Enter,
+        "Enter entity or use furniture",
+    ),
+    KeyBinding::new(
+        Map,
+        &[KeyCode::Char('c')],
+        InputAction::ToggleCamera,
+        "Free camera",
+    ),
+    KeyBinding::new(
+        Map,
+        &[KeyCode::Home],
+        InputAction::CenterCamera,
+        "Camera back to you",
+    ),
+    KeyBinding::new(
+        Map,
+        &[KeyCode::Char('m')],
+        InputAction::ToggleMinimap,
+        "Mini-map",
+    ),
+    KeyBinding::new(
+        Map,
+        &[KeyCode::Char('v')],
+        InputAction::CycleOverlay,
+        "Next map overlay",
+    ),
+    KeyBinding::new(
+        Map,
+        &[KeyCode::Char('V')],
+        InputAction::NewOverlay,
+        "New map overlay",
+    ),
+    KeyBinding::new(
+        Places,
+        &[KeyCode::Char('/')],
+        InputAction::OpenSearch,
+        "Search by name",
+    ),
+    KeyBinding::new(
+        Places,
+        &[KeyCode::Char('i'), KeyCode::Char('I')],
+        InputAction::ToggleSidebar,
+        "Sidebar",
+    ),
+    KeyBinding::new(
+        Places,
+        &[KeyCode::Char('p'), KeyCode::Char('P')],
+        InputAction::TogglePin,
+        "Pin to watchlist",
+    ),
+    KeyBinding::new(
+        Places,
+        &[KeyCode::Char('1')],
+        InputAction::SetBookmark(1),
+        "Bookmark",
+    )
+    .shifted(),
+    KeyBinding::new(
+        Places,
+        &[KeyCode::Char('2')],
+        InputAction::SetBookmark(2),
+        "Bookmark",
+    )
+    .shifted(),
+    KeyBinding::new(
+        Places,
+        &[KeyCode::Char('3')],
+        InputAction::SetBookmark(3),
+        "Bookmark",
+    )
+    .shifted(),
+    KeyBinding::new(
+        Places,
+        &[KeyCode::Char('4')],
+        InputAction::SetBookmark(4),
+        "Bookmark",
+    )
+    .shifted(),
+    KeyBinding::new(
+        Places,
+        &[KeyCode::Char('5')],
+        InputAction::SetBookmark(5),
+        "Bookmark",
+    )
+    .shifted(),
+    KeyBinding::new(
+        Places,
+        &[KeyCode::Char('6')],
+        InputAction::SetBookmark(6),
+        "Bookmark",
+    )
+    .shifted(),
+    KeyBinding::new(
+        Places,
+        &[KeyCode::Char('7')],
+        InputAction::SetBookmark(7),
+        "Bookmark",
+    )
+    .shifted(),
+    KeyBinding::new(
+        Places,
+        &[KeyCode::Char('8')],
+        InputAction::SetBookmark(8),
+        "Bookmark",
+    )
+    .shifted(),
+    KeyBinding::new(
+        Places,
+        &[KeyCode::Char('9')],
+        InputAction::SetBookmark(9),
+        "Bookmark",
+    )
+    .shifted(),
+    KeyBinding::new(
+        Places,
+        &[KeyCode::Char('b'), KeyCode::Char('B')],
+        InputAction::OpenBookmarks,
+        "Bookmarks",
+    ),
+    KeyBinding::new(
+        Places,
+        &[KeyCode::Char('a'), KeyCode::Char('A')],
+        InputAction::EditAttributes,
+        "Edit live (debug builds)",
+    ),
+    KeyBinding::new(
+        Screens,
+        &[KeyCode::Char('d'), KeyCode::Char('D')],
+        InputAction::ToggleDashboard,
+        "Economy dashboard",
+    ),
+    KeyBinding::new(
+        Screens,
+        &[KeyCode::Char('C')],
+        InputAction::ToggleCensus,
+        "Census",
+    ),
+    KeyBinding::new(
+        Screens,
+        &[KeyCode::Char('g'), KeyCode::Char('G')],
+        InputAction::ToggleFinances,
+        "Polity finances",
+    ),
+    KeyBinding::new(
+        Screens,
+        &[KeyCode::Char('k'), KeyCode::Char('K')],
+        InputAction::ToggleTechnology,
+        "Technology",
+    ),
+    KeyBinding::new(
+        Screens,
+        &[KeyCode::Char('l'), KeyCode::Char('L')],
+        InputAction::ToggleMonetary,
+        "Banking",
+    ),
+    KeyBinding::new(
+        Screens,
+        &[KeyCode::Char('r'), KeyCode::Char('R')],
+        InputAction::ToggleProfiler,
+        "Profiler",
+    ),
+    KeyBinding::new(
+        Screens,
+        &[KeyCode::F(3)],
+        InputAction::ToggleDebugOverlay,
+        "Debug overlay",
+    ),
+    KeyBinding::new(
+        Screens,
+        &[KeyCode::Char('h'), KeyCode::Char('H'), KeyCode::Char('?')],
+        InputAction::ToggleHelp,
+        "This help",
+    ),
+    KeyBinding::new(
+        Game,
+        &[KeyCode::Char(':')],
+        InputAction::OpenConsole,
+        "Console",
+    ),
+    KeyBinding::new(
+        Game,
+        &[KeyCode::Char('e'), KeyCode::Char('E')],
+        InputAction::ToggleEditor,
+        "Sandbox editor",
+    ),
+    KeyBinding::new(
+        Game,
+        &[KeyCode::Char('f'), KeyCode::Char('F')],
+        InputAction::OpenBusiness,
+        "Found a business",
+    ),
+    KeyBinding::new(
+        Game,
+        &[KeyCode::Char('M')],
+        InputAction::OpenPolicy,
+        "Policy",
+    ),
+    KeyBinding::new(
+        Game,
+        &[KeyCode::Char('o'), KeyCode::Char('O')],
+        InputAction::OpenSettings,
+        "Settings",
+    ),
+    KeyBinding::new(
+        Game,
+        &[KeyCode::Char('s'), KeyCode::Char('S')],
+        InputAction::SkipTutorial,
+        "Skip the tutorial",
+    ),
+    KeyBinding::new(Game, &[KeyCode::Esc], InputAction::OpenMenu, "Pause menu"),
+    KeyBinding::new(
+        Game,
+        &[KeyCode::Char('q'), KeyCode::Char('Q')],
+        InputAction::Quit,
+        "Quit",
+    ),
+];
+
+const EDITOR_KEYS: &[KeyBinding] = &[
+    KeyBinding::new(
+        Map,
+        &[KeyCode::Char('z'), KeyCode::Char('Z')],
+        InputAction::ZoomIn,
+        "Zoom in",
+    ),
+    KeyBinding::new(
+        Map,
+        &[KeyCode::Char('x'), KeyCode::Char('X')],
+        InputAction::ZoomOut,
+        "Zoom out",
+    ),
+    KeyBinding::new(Map, &[KeyCode::Up], InputAction::MoveUp, "Move"),
+    KeyBinding::new(Map, &[KeyCode::Down], InputAction::MoveDown, "Move"),
+    KeyBinding::new(Map, &[KeyCode::Left], InputAction::MoveLeft, "Move"),
+    KeyBinding::new(Map, &[KeyCode::Right], InputAction::MoveRight, "Move"),
+    KeyBinding::new(
+        Map,
+        &[KeyCode::Enter],
+        InputAction::Enter,
+        "Enter entity or use furniture",
+    ),
+    KeyBinding::new(
+        Editing,
+        &[KeyCode::Char(']'), KeyCode::Tab],
+        InputAction::PaletteNext,
+        "Next in the palette",
+    ),
+    KeyBinding::new(
+        Editing,
+        &[KeyCode::Char('['), KeyCode::BackTab],
+        InputAction::PalettePrevious,
+        "Previous in the palette",
+    ),
+    KeyBinding::new(Editing, &[KeyCode::Char(' ')], InputAction::Place, "Place"),
+    KeyBinding::new(
+        Editing,
+        &[KeyCode::Delete, KeyCode::Backspace],
+        InputAction::Remove,
+        "Remove",
+    ),
+    KeyBinding::new(
+        Editing,
+        &[KeyCode::Char('a'), KeyCode::Char('A')],
+        InputAction::EditAttributes,
+        "Edit attributes",
+    ),
+    KeyBinding::new(
+        Editing,
+        &[KeyCode::Char('v'), KeyCode::Char('V')],
+        InputAction::MarkSelection,
+        "Mark a selection",
+    ),
+    KeyBinding::new(
+        Editing,
+        &[KeyCode::Char('c'), KeyCode::Char('C')],
+        InputAction::Copy,
+        "Copy",
+    ),
+    KeyBinding::new(
+        Editing,
+        &[KeyCode::Char('p'), KeyCode::Char('P')],
+        InputAction::Paste,
+        "Paste",
+    ),
+    KeyBinding::new(
+        Editing,
+        &[KeyCode::Char('u'), KeyCode::Char('U')],
+        InputAction::Undo,
+        "Undo",
+    ),
+    KeyBinding::new(
+        Editing,
+        &[KeyCode::Char('s'), KeyCode::Char('S')],
+        InputAction::SaveScenario,
+        "Save as a scenario",
+    ),
+    KeyBinding::new(
+        Screens,
+        &[KeyCode::Char('h'), KeyCode::Char('H'), KeyCode::Char('?')],
+        InputAction::ToggleHelp,
+        "This help",
+    ),
+    KeyBinding::new(
+        Game,
+        &[KeyCode::Char('e'), KeyCode::Char('E'), KeyCode::Esc],
+        InputAction::ToggleEditor,
+        "Leave the editor",
+    ),
+];
+
+/// Input read from the terminal and waiting to be acted on
+#[derive(Debug, Clone, Copy)]
+enum Pending {
+    Key(KeyCode, KeyModifiers),
+    Resize(u16, u16),
+}
+
 pub struct InputHandler {
-    show_help: bool,
+    /// Active modes, innermost last. The bottom is always `Normal`.
+    modes: Vec<InputMode>,
+    /// Input read but not yet acted on, oldest first
+    pending: VecDeque<Pending>,
+    repeat: KeyRepeat,
 }
 
 impl InputHandler {
     pub fn new() -> Self {
-        Self { show_help: false }
+        Self {
+            modes: vec![InputMode::Normal],
+            pending: VecDeque::new(),
+            repeat: KeyRepeat::default(),
+        }
+    }
+
+    /// Cells moved per second while an arrow key is held
+    pub fn set_move_rate(&mut self, rate: f64) {
+        self.repeat.set_rate(rate);
     }
 
-    pub fn poll(&mut self) -> Result<InputAction> {
-        if event::poll(Duration::ZERO)?
-            && let Event::Key(KeyEvent {
-                code,
+    pub fn mode(&self) -> InputMode {
+        self.modes.last().copied().unwrap_or(InputMode::Normal)
+    }
+
+    /// Replace the innermost mode
+    pub fn set_mode(&mut self, mode: InputMode) {
+        if self.modes.len() > 1 {
+            self.modes.pop();
+        }
+        self.push_mode(mode);
+    }
+
+    /// Enter a mode on top of the current one, returning to it on
+    /// [`InputHandler::pop_mode`]
+    pub fn push_mode(&mut self, mode: InputMode) {
+        if mode == InputMode::Normal {
+            self.modes.truncate(1);
+        } else {
+            self.modes.push(mode);
+        }
+    }
+
+    /// Leave the innermost mode; the base `Normal` mode is never removed
+    pub fn pop_mode(&mut self) {
+        if self.modes.len() > 1 {
+            self.modes.pop();
+        }
+    }
+
+    /// Read everything the terminal has waiting without blocking, so
+    /// keys pressed during a long frame are all kept, then add the presses
+    /// a held arrow key is owed. Returns how many inputs are now waiting
+    /// for [`InputHandler::next_action`].
+    pub fn poll(&mut self) -> Result<usize> {
+        while event::poll(Duration::ZERO)? {
+            match event::read()? {
+                Event::Key(KeyEvent {
+                    code,
+                    modifiers,
+                    kind,
+                    ..
+                }) if self.repeat.observe(code, modifiers, kind, Instant::now()) => {
+                    self.queue(code, modifiers)
+                }
+                Event::Resize(width, height) => {
+                    self.pending.push_back(Pending::Resize(width, height))
+                }
+                _ => {}
+            }
+        }
+        if let Some((code, modifiers, count)) = self.repeat.due(Instant::now()) {
+            for _ in 0..count {
+                self.queue(code, modifiers);
+            }
+        }
+        Ok(self.pending.len())
+    }
+
+    /// Add a key press to the back of the queue
+    pub fn queue(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        self.pending.push_back(Pending::Key(code, modifiers));
+    }
+
+    /// The action for the oldest waiting input, or None once all have been
+    /// taken. Keys are interpreted in the mode current when they are taken,
+    /// so acting on each action before taking the next lets a key that
+    /// opens the console send the keys after it to the console.
+    pub fn next_action(&mut self) -> Option<InputAction> {
+        Some(match self.pending.pop_front()? {
+            Pending::Key(code, modifiers) => self.press(code, modifiers),
+            Pending::Resize(width, height) => InputAction::Resize(width, height),
+        })
+    }
+
+    /// Interpret a key pressed in the current mode
+    pub fn press(&mut self, code: KeyCode, modifiers: KeyModifiers) -> InputAction {
+        match self.mode() {
+            InputMode::Normal => Self::map_normal(code, modifiers),
+            InputMode::Form => Self::map_form(code),
+            InputMode::Prompt => Self::map_prompt(code),
+            InputMode::Console => Self::map_console(code),
+            InputMode::Editor => Self::map_editor(code),
+            InputMode::Interstitial => Self::map_interstitial(code),
+            InputMode::Bookmarks => Self::map_bookmarks(code),
+            InputMode::Search => Self::map_search(code),
+            InputMode::Menu => Self::map_menu(code),
+        }
+    }
+
+    /// Block until any key is pressed
+    pub fn wait_for_key(&mut self) -> Result<()> {
+        loop {
+            if let Event::Key(KeyEvent {
                 kind: KeyEventKind::Press,
                 ..
             }) = event::read()?
-        {
-            let action = match code {
-                KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => InputAction::Quit,
-                KeyCode::Char(' ') => InputAction::TogglePause,
-                KeyCode::Char('+') | KeyCode::Char('=') => InputAction::IncreaseSpeed,
-                KeyCode::Char('-') | KeyCode::Char('_') => InputAction::DecreaseSpeed,
-                KeyCode::Char('z') | KeyCode::Char('Z') => InputAction::ZoomIn,
-                KeyCode::Char('x') | KeyCode::Char('X') => InputAction::ZoomOut,
-                KeyCode::Char('h') | KeyCode::Char('H') | KeyCode::Char('?') => {
-                    InputAction::ToggleHelp
+            {
+                return Ok(());
+            }
+        }
+    }
+
+    /// The keys bound in a mode, for the modes whose keys are listed in
+    /// the help
+    pub fn bindings(mode: InputMode) -> &'static [KeyBinding] {
+        match mode {
+            InputMode::Normal => NORMAL_KEYS,
+            InputMode::Editor => EDITOR_KEYS,
+            _ => &[],
+        }
+    }
+
+    /// The help for a mode: one row for each run of keys that do alike, in
+    /// category order and then in the order they are bound
+    pub fn help(mode: InputMode) -> Vec<HelpRow> {
+        let mut groups: Vec<(&KeyBinding, Vec<KeyCode>)> = Vec::new();
+        for binding in Self::bindings(mode) {
+            match groups.last_mut() {
+                Some((first, keys))
+                    if first.description == binding.description
+                        && first.category == binding.category
+                        && first.modifiers == binding.modifiers =>
+                {
+                    keys.extend_from_slice(binding.keys)
                 }
-                KeyCode::Up => InputAction::MoveUp,
-                KeyCode::Down => InputAction::MoveDown,
-                KeyCode::Left => InputAction::MoveLeft,
-                KeyCode::Right => InputAction::MoveRight,
-                KeyCode::Enter => InputAction::Enter,
-                _ => InputAction::None,
-            };
-
-            if action == InputAction::ToggleHelp {
-                self.show_help = !self.show_help;
+                _ => groups.push((binding, binding.keys.to_vec())),
             }
+        }
+        groups.sort_by_key(|(binding, _)| binding.category);
+        groups
+            .into_iter()
+            .map(|(binding, keys)| HelpRow {
+                category: binding.category,
+                keys: Self::key_names(&keys, binding.modifiers),
+                description: binding.description,
+            })
+            .collect()
+    }
+
+    /// Keys as the help shows them. A letter bound in both cases is shown
+    /// as the capital, and runs of three or more characters in a row
+    /// shorten to the first and last, so 1 to 5 reads "1-5".
+    fn key_names(keys: &[KeyCode], modifiers: KeyModifiers) -> String {
+        let shown: Vec<KeyCode> = keys
+            .iter()
+            .copied()
+            .filter(|&key| match key {
+                KeyCode::Char(c) => {
+                    !c.is_ascii_lowercase()
+                        || !keys.contains(&KeyCode::Char(c.to_ascii_uppercase()))
+                }
+                _ => true,
+            })
+            .collect();
 
-            Ok(action)
+        let mut names = Vec::new();
+        let mut start = 0;
+        while start < shown.len() {
+            let mut end = start;
+            while let (KeyCode::Char(a), Some(&KeyCode::Char(b))) = (shown[end], shown.get(end + 1))
+            {
+                if b as u32 != a as u32 + 1 {
+                    break;
+                }
+                end += 1;
+            }
+            if end - start >= 2 {
+                names.push(format!(
+                    "{}-{}",
+                    Self::key_name(shown[start]),
+                    Self::key_name(shown[end])
+                ));
+            } else {
+                names.extend(shown[start..=end].iter().map(|&key| Self::key_name(key)));
+            }
+            start = end + 1;
+        }
+
+        let prefix = if modifiers.contains(KeyModifiers::SHIFT) {
+            "Shift+"
         } else {
-            Ok(InputAction::None)
+            ""
+        };
+        format!("{}{}", prefix, names.join("/"))
+    }
+
+    fn key_name(key: KeyCode) -> String {
+        match key {
+            KeyCode::Char(' ') => String::from("SPACE"),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Up => String::from("↑"),
+            KeyCode::Down => String::from("↓"),
+            KeyCode::Left => String::from("←"),
+            KeyCode::Right => String::from("→"),
+            KeyCode::F(n) => format!("F{}", n),
+            KeyCode::BackTab => String::from("Shift+TAB"),
+            KeyCode::Backspace => String::from("BKSP"),
+            KeyCode::Delete => String::from("DEL"),
+            other => format!("{:?}", other).to_uppercase(),
+        }
+    }
+
+    /// The action a mode's bindings give a key, preferring a binding that
+    /// needs the modifiers held over one that needs none
+    fn lookup(bindings: &[KeyBinding], code: KeyCode, modifiers: KeyModifiers) -> InputAction {
+        let bound = |modified: bool| {
+            bindings.iter().find(|binding| {
+                binding.modifiers.is_empty() != modified && binding.matches(code, modifiers)
+            })
+        };
+        bound(true)
+            .or_else(|| bound(false))
+            .map_or(InputAction::None, |binding| binding.action)
+    }
+
+    fn map_normal(code: KeyCode, modifiers: KeyModifiers) -> InputAction {
+        match Self::lookup(NORMAL_KEYS, code, modifiers) {
+            InputAction::None => {
+                Self::bookmark_slot(code).map_or(InputAction::None, InputAction::SetBookmark)
+            }
+            action => action,
+        }
+    }
+
+    /// The slot a shifted digit bookmarks on terminals that report the
+    /// symbol Shift gives the digit rather than the digit itself, going by
+    /// the US layout
+    fn bookmark_slot(code: KeyCode) -> Option<u8> {
+        const SHIFTED_DIGITS: [char; 9] = ['!', '@', '#', '$', '%', '^', '&', '*', '('];
+        match code {
+            KeyCode::Char(symbol) => SHIFTED_DIGITS
+                .iter()
+                .position(|&c| c == symbol)
+                .map(|index| index as u8 + 1),
+            _ => None,
+        }
+    }
+
+    fn map_bookmarks(code: KeyCode) -> InputAction {
+        match code {
+            KeyCode::Char(digit @ '1'..='9') => InputAction::JumpToBookmark(digit as u8 - b'0'),
+            KeyCode::Char('b') | KeyCode::Char('B') | KeyCode::Esc => InputAction::Cancel,
+            _ => InputAction::None,
+        }
+    }
+
+    fn map_editor(code: KeyCode) -> InputAction {
+        Self::lookup(EDITOR_KEYS, code, KeyModifiers::NONE)
+    }
+
+    fn map_console(code: KeyCode) -> InputAction {
+        match code {
+            KeyCode::Enter => InputAction::SubmitCommand,
+            KeyCode::Esc => InputAction::Cancel,
+            KeyCode::Tab => InputAction::Complete,
+            KeyCode::Up => InputAction::MoveUp,
+            KeyCode::Down => InputAction::MoveDown,
+            code => Self::text_edit(code).map_or(InputAction::None, InputAction::Edit),
+        }
+    }
+
+    fn map_search(code: KeyCode) -> InputAction {
+        match code {
+            KeyCode::Enter => InputAction::Confirm,
+            KeyCode::Esc => InputAction::Cancel,
+            KeyCode::Up => InputAction::MoveUp,
+            KeyCode::Down | KeyCode::Tab => InputAction::MoveDown,
+            code => Self::text_edit(code).map_or(InputAction::None, InputAction::Edit),
+        }
+    }
+
+    fn map_menu(code: KeyCode) -> InputAction {
+        match code {
+            KeyCode::Up => InputAction::MoveUp,
+            KeyCode::Down | KeyCode::Tab => InputAction::MoveDown,
+            KeyCode::Enter | KeyCode::Char(' ') => InputAction::Confirm,
+            KeyCode::Char(digit @ '1'..='9') => InputAction::Choose(digit as u8 - b'0'),
+            KeyCode::Esc => InputAction::Cancel,
+            _ => InputAction::None,
+        }
+    }
+
+    fn map_interstitial(code: KeyCode) -> InputAction {
+        match code {
+            KeyCode::Enter | KeyCode::Char(' ') | KeyCode::Esc => InputAction::Confirm,
+            _ => InputAction::None,
+        }
+    }
+
+    fn map_form(code: KeyCode) -> InputAction {
+        match code {
+            KeyCode::Enter => InputAction::Confirm,
+            KeyCode::Esc => InputAction::Cancel,
+            KeyCode::Tab | KeyCode::Down => InputAction::NextField,
+            KeyCode::BackTab | KeyCode::Up => InputAction::PreviousField,
+            code => Self::text_edit(code).map_or(InputAction::None, InputAction::Edit),
+        }
+    }
+
+    /// Keys shared by every text input
+    fn text_edit(code: KeyCode) -> Option<TextEdit> {
+        match code {
+            KeyCode::Char(c) => Some(TextEdit::Insert(c)),
+            KeyCode::Backspace => Some(TextEdit::Backspace),
+            KeyCode::Delete => Some(TextEdit::Delete),
+            KeyCode::Left => Some(TextEdit::Left),
+            KeyCode::Right => Some(TextEdit::Right),
+            KeyCode::Home => Some(TextEdit::Home),
+            KeyCode::End => Some(TextEdit::End),
+            _ => None,
         }
     }
 
-    pub fn is_help_visible(&self) -> bool {
-        self.show_help
+    fn map_prompt(code: KeyCode) -> InputAction {
+        match code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => InputAction::Confirm,
+            KeyCode::Char('n') | KeyCode::Char('N') => InputAction::Deny,
+            KeyCode::Char('c') | KeyCode::Char('C') | KeyCode::Esc => InputAction::Cancel,
+            _ => InputAction::None,
+        }
     }
 }
 
@@ -74,3 +929,70 @@ impl Default for InputHandler {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_key_is_bound_twice() {
+        for bindings in [NORMAL_KEYS, EDITOR_KEYS] {
+            for (i, binding) in bindings.iter().enumerate() {
+                for other in &bindings[i + 1..] {
+                    assert!(
+                        binding.modifiers != other.modifiers
+                            || binding.keys.iter().all(|key| !other.keys.contains(key)),
+                        "{:?} and {:?} share a key",
+                        binding.action,
+                        other.action
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_help_lists_keys_as_typed() {
+        let help = InputHandler::help(InputMode::Normal);
+        let keys = |description: &str| {
+            help.iter()
+                .find(|row| row.description == description)
+                .map(|row| row.keys.as_str())
+        };
+        assert_eq!(keys("Jump to a preset speed"), Some("1-5"));
+        assert_eq!(keys("Bookmark"), Some("Shift+1-9"));
+        assert_eq!(keys("Move"), Some("↑/↓/←/→"));
+        assert_eq!(keys("This help"), Some("H/?"));
+        assert_eq!(keys("Free camera"), Some("c"));
+        assert_eq!(keys("Census"), Some("C"));
+        assert!(help.is_sorted_by_key(|row| row.category));
+
+        let editor = InputHandler::help(InputMode::Editor);
+        assert!(
+            editor
+                .iter()
+                .any(|row| row.keys == "E/ESC" && row.description == "Leave the editor")
+        );
+    }
+
+    #[test]
+    fn test_shifted_digits_bookmark_and_plain_ones_set_speed() {
+        let press = |code, modifiers| InputHandler::new().press(code, modifiers);
+        assert_eq!(
+            press(KeyCode::Char('3'), KeyModifiers::SHIFT),
+            InputAction::SetBookmark(3)
+        );
+        assert_eq!(
+            press(KeyCode::Char('#'), KeyModifiers::SHIFT),
+            InputAction::SetBookmark(3)
+        );
+        assert_eq!(
+            press(KeyCode::Char('3'), KeyModifiers::NONE),
+            InputAction::SetSpeed(SPEED_PRESETS[2])
+        );
+        assert_eq!(
+            press(KeyCode::Char('C'), KeyModifiers::SHIFT),
+            InputAction::ToggleCensus
+        );
+    }
+}
@@ -1,3 +1,5 @@
 mod handler;
+mod repeat;
 
-pub use handler::{InputAction, InputHandler};
+pub use handler::{HelpRow, InputAction, InputHandler, InputMode, KeyBinding, KeyCategory};
+pub use repeat::{DEFAULT_MOVE_RATE, KeyRepeat};
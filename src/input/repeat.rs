@@ -0,0 +1,212 @@
+use crossterm::event::{KeyCode, KeyEventKind, KeyModifiers};
+use std::time::{Duration, Instant};
+
+/// Cells moved per second while an arrow key is held
+pub const DEFAULT_MOVE_RATE: f64 = 15.0;
+
+/// How long a key must be held before it starts repeating, on terminals
+/// that report releases
+const REPEAT_DELAY: Duration = Duration::from_millis(250);
+
+/// Presses of the same key closer together than this are the terminal
+/// repeating a held key rather than separate taps
+const REPEAT_GAP: Duration = Duration::from_millis(150);
+
+/// A key being held down
+#[derive(Debug, Clone, Copy)]
+struct Held {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+    since: Instant,
+    /// The latest sign the key is still down
+    seen: Instant,
+    /// Repeats already sent
+    repeats: u64,
+}
+
+/// Turns a held arrow key into a steady stream of presses at the movement
+/// rate, whatever rate the terminal repeats keys at.
+///
+/// Terminals that report key releases say exactly when a key is let go,
+/// and the key repeats from [`REPEAT_DELAY`] after the press until then.
+/// Most only send the press again at the keyboard's own repeat rate; there
+/// the key counts as held for as long as those keep coming, so a key let go
+/// never carries the cursor on past where the terminal's repeats stopped.
+#[derive(Debug, Clone)]
+pub struct KeyRepeat {
+    /// Presses per second while held
+    rate: f64,
+    held: Option<Held>,
+    /// Whether the terminal has been seen reporting releases
+    reports_releases: bool,
+}
+
+impl KeyRepeat {
+    pub fn new(rate: f64) -> Self {
+        Self {
+            rate,
+            held: None,
+            reports_releases: false,
+        }
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    pub fn set_rate(&mut self, rate: f64) {
+        self.rate = rate.max(0.0);
+    }
+
+    /// Whether holding a key moves continuously
+    pub fn repeats(code: KeyCode) -> bool {
+        matches!(
+            code,
+            KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right
+        )
+    }
+
+    /// Take note of a key event, returning whether it should be acted on
+    /// as a press. Repeats of a held key are swallowed and replaced by the
+    /// ones [`KeyRepeat::due`] hands out.
+    pub fn observe(
+        &mut self,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+        kind: KeyEventKind,
+        now: Instant,
+    ) -> bool {
+        let holding = self.held.filter(|held| held.code == code);
+        match kind {
+            KeyEventKind::Release => {
+                self.reports_releases = true;
+                if holding.is_some() {
+                    self.held = None;
+                }
+                false
+            }
+            KeyEventKind::Repeat => {
+                self.reports_releases = true;
+                match holding {
+                    Some(_) => {
+                        self.refresh(now);
+                        false
+                    }
+                    // An arrow pressed before another key was let go
+                    None if Self::repeats(code) => self.press(code, modifiers, now),
+                    None => false,
+                }
+            }
+            KeyEventKind::Press => match holding {
+                Some(held) if !self.reports_releases && now - held.seen < REPEAT_GAP => {
+                    self.refresh(now);
+                    false
+                }
+                _ => self.press(code, modifiers, now),
+            },
+        }
+    }
+
+    fn press(&mut self, code: KeyCode, modifiers: KeyModifiers, now: Instant) -> bool {
+        self.held = Self::repeats(code).then_some(Held {
+            code,
+            modifiers,
+            since: now,
+            seen: now,
+            repeats: 0,
+        });
+        true
+    }
+
+    fn refresh(&mut self, now: Instant) {
+        if let Some(held) = &mut self.held {
+            held.seen = now;
+        }
+    }
+
+    /// The held key and how many more presses of it are owed by `now`
+    pub fn due(&mut self, now: Instant) -> Option<(KeyCode, KeyModifiers, u64)> {
+        let held = self.held.as_mut()?;
+        let (delay, until) = if self.reports_releases {
+            (REPEAT_DELAY, now)
+        } else {
+            // The terminal's own repeat delay has already passed
+            (Duration::ZERO, held.seen)
+        };
+        let holding = until.saturating_duration_since(held.since + delay);
+        let owed = (holding.as_secs_f64() * self.rate) as u64;
+        let count = owed.saturating_sub(held.repeats);
+        held.repeats = held.repeats.max(owed);
+        (count > 0).then_some((held.code, held.modifiers, count))
+    }
+}
+
+impl Default for KeyRepeat {
+    fn default() -> Self {
+        Self::new(DEFAULT_MOVE_RATE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NONE: KeyModifiers = KeyModifiers::NONE;
+
+    fn after(start: Instant, millis: u64) -> Instant {
+        start + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_terminal_repeats_are_paced_by_the_move_rate() {
+        let mut repeat = KeyRepeat::new(10.0);
+        let start = Instant::now();
+        assert!(repeat.observe(KeyCode::Right, NONE, KeyEventKind::Press, start));
+
+        // The keyboard's first repeat comes after its own delay, then
+        // every 30ms; none of them are acted on directly
+        assert!(repeat.observe(KeyCode::Right, NONE, KeyEventKind::Press, after(start, 500)));
+        for millis in (530..=1000).step_by(30) {
+            let press = KeyEventKind::Press;
+            assert!(!repeat.observe(KeyCode::Right, NONE, press, after(start, millis)));
+        }
+        // Held from 500ms to 980ms: four repeats at ten a second
+        assert_eq!(
+            repeat.due(after(start, 2000)),
+            Some((KeyCode::Right, NONE, 4))
+        );
+        assert_eq!(repeat.due(after(start, 3000)), None);
+
+        // A separate tap later is its own press
+        assert!(repeat.observe(
+            KeyCode::Right,
+            NONE,
+            KeyEventKind::Press,
+            after(start, 3000)
+        ));
+    }
+
+    #[test]
+    fn test_reported_releases_end_the_hold() {
+        let mut repeat = KeyRepeat::new(10.0);
+        let start = Instant::now();
+        assert!(repeat.observe(KeyCode::Up, NONE, KeyEventKind::Press, start));
+        assert!(!repeat.observe(KeyCode::Up, NONE, KeyEventKind::Repeat, after(start, 300)));
+        assert_eq!(repeat.due(after(start, 200)), None);
+        assert_eq!(repeat.due(after(start, 750)), Some((KeyCode::Up, NONE, 5)));
+
+        assert!(!repeat.observe(KeyCode::Up, NONE, KeyEventKind::Release, after(start, 800)));
+        assert_eq!(repeat.due(after(start, 5000)), None);
+    }
+
+    #[test]
+    fn test_only_arrows_repeat() {
+        let mut repeat = KeyRepeat::default();
+        let start = Instant::now();
+        let quit = KeyCode::Char('q');
+        assert!(repeat.observe(quit, NONE, KeyEventKind::Press, start));
+        assert!(repeat.observe(quit, NONE, KeyEventKind::Press, after(start, 10)));
+        assert!(!repeat.observe(quit, NONE, KeyEventKind::Repeat, after(start, 20)));
+        assert_eq!(repeat.due(after(start, 5000)), None);
+    }
+}
@@ -0,0 +1,25 @@
+//! Econogenesis simulation core.
+//!
+//! The terminal game in `main.rs` is one frontend over this library; other
+//! frontends and integration tests drive the same [`Simulation`] directly.
+
+pub mod api;
+pub mod economy;
+pub mod editor;
+pub mod game;
+pub mod input;
+pub mod logging;
+pub mod modding;
+pub mod render;
+pub mod result;
+pub mod save;
+pub mod time;
+pub mod ui;
+pub mod zoom;
+
+pub use game::{GameLoop, Simulation, WorldState};
+pub use result::{Error, Result};
+pub use zoom::ZoomManager;
+
+/// The complete simulated universe
+pub type World = WorldState;
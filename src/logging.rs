@@ -0,0 +1,91 @@
+//! Diagnostics written to a log file.
+//!
+//! The terminal belongs to the game, and anything printed to it vanishes
+//! with the alternate screen. Spans around every tick, frame and key
+//! press, the world's log and notices, and panics go to a file under the
+//! data directory's [`LOG_DIR`] instead. A new file is started each day and
+//! only the latest [`KEPT_LOGS`] are kept.
+//!
+//! Builds without the `telemetry` feature write no log file: [`init`]
+//! does nothing, and the spans go nowhere.
+
+#[cfg(feature = "telemetry")]
+use std::backtrace::Backtrace;
+use std::path::Path;
+use tracing::level_filters::LevelFilter;
+#[cfg(feature = "telemetry")]
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+
+use crate::result::Result;
+
+/// Keeps the log file's writer going; dropping it flushes the file
+#[cfg(feature = "telemetry")]
+pub use tracing_appender::non_blocking::WorkerGuard as LogGuard;
+
+/// Stands in for the log file's writer in builds without telemetry
+#[cfg(not(feature = "telemetry"))]
+pub struct LogGuard;
+
+// Nothing to flush, but dropped the same way as the real guard
+#[cfg(not(feature = "telemetry"))]
+impl Drop for LogGuard {
+    fn drop(&mut self) {}
+}
+
+/// Directory under the data directory that holds the log files
+pub const LOG_DIR: &str = "logs";
+
+/// Daily log files kept before the oldest is deleted
+pub const KEPT_LOGS: usize = 7;
+
+/// Start writing everything at `level` or above to the log file, and log
+/// panics before the default hook reports them. Does nothing at
+/// [`LevelFilter::OFF`]. Hold on to the returned guard until exit: dropping
+/// it flushes what is still buffered.
+#[cfg(feature = "telemetry")]
+pub fn init(data_dir: &Path, level: LevelFilter) -> Result<Option<LogGuard>> {
+    if level == LevelFilter::OFF {
+        return Ok(None);
+    }
+    let dir = data_dir.join(LOG_DIR);
+    std::fs::create_dir_all(&dir)?;
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix("econogenesis")
+        .filename_suffix("log")
+        .max_log_files(KEPT_LOGS)
+        .build(dir)?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+    let installed = tracing_subscriber::fmt()
+        .with_writer(writer)
+        .with_max_level(level)
+        .with_ansi(false)
+        .try_init()
+        .is_ok();
+    if installed {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            tracing::error!("{}\n{}", info, Backtrace::force_capture());
+            previous(info);
+        }));
+    }
+    Ok(Some(guard))
+}
+
+/// Write no log file, without the `telemetry` feature
+#[cfg(not(feature = "telemetry"))]
+pub fn init(_data_dir: &Path, _level: LevelFilter) -> Result<Option<LogGuard>> {
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nothing_is_logged_when_off() {
+        let dir = std::env::temp_dir().join(format!("econogenesis-logging-{}", std::process::id()));
+        assert!(init(&dir, LevelFilter::OFF).unwrap().is_none());
+        assert!(!dir.exists());
+    }
+}
@@ -1,29 +1,515 @@
-mod game;
-mod input;
-mod render;
-mod result;
-mod time;
-mod zoom;
-
-use game::GameLoop;
-use render::RenderEngine;
-use result::Result;
-use std::io::stdout;
+use clap::{Parser, ValueEnum};
+use econogenesis::api::{Command, Core, Event};
+#[cfg(feature = "serve")]
+use econogenesis::api::{DEFAULT_SERVE_ADDR, Observer};
+use econogenesis::economy::data::{self, GOODS_FILE, GoodsData};
+use econogenesis::game::diff::{self, WorldDiff};
+use econogenesis::game::presets::{GamePreset, PRESETS};
+use econogenesis::game::soak::{self, SOAK_DIR};
+use econogenesis::game::state::DEFAULT_SEED;
+use econogenesis::game::worldgen::WorldConfig;
+use econogenesis::game::{DEFAULT_TARGET_FPS, Severity};
+use econogenesis::input::DEFAULT_MOVE_RATE;
+use econogenesis::logging;
+use econogenesis::modding::MOD_DIR;
+use econogenesis::render::{Capabilities, GlyphSet, RenderEngine};
+use econogenesis::save::compare::{self, Run};
+use econogenesis::save::{self, Autosave, SaveGame, SaveManager, Scenario, SessionLock};
+use econogenesis::time::Calendar;
+use econogenesis::{GameLoop, Result, Simulation, WorldState};
+use std::io::{BufRead, Stdout, Write, stdin, stdout};
+use std::path::{Path, PathBuf};
+use tracing::level_filters::LevelFilter;
+
+/// A galaxy-spanning economic simulation
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Cli {
+    /// Seed for the new world's random stream
+    #[arg(long, conflicts_with_all = ["load", "continue_session", "scenario"])]
+    seed: Option<u64>,
+
+    /// Start a new game from a preset (sandbox, standard, frontier or
+    /// hardship) rather than choosing one from the startup menu
+    #[arg(long, value_name = "NAME", value_parser = parse_preset,
+          conflicts_with_all = ["load", "continue_session", "scenario", "world_config"])]
+    preset: Option<GamePreset>,
+
+    /// Generate the world from a parameters file
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["load", "continue_session", "scenario"])]
+    world_config: Option<PathBuf>,
+
+    /// Start from a scenario built in the sandbox editor
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["load", "continue_session"])]
+    scenario: Option<PathBuf>,
+
+    /// Resume a save file
+    #[arg(long, value_name = "SAVE", conflicts_with = "continue_session")]
+    load: Option<PathBuf>,
+
+    /// Pick up the most recent save
+    #[arg(long = "continue")]
+    continue_session: bool,
+
+    /// Run the simulation without a terminal interface and print a summary
+    #[arg(long)]
+    headless: bool,
+
+    /// Hours to simulate in headless mode
+    #[arg(long, default_value_t = 24, requires = "headless")]
+    ticks: u64,
+
+    /// Frames drawn per second
+    #[arg(long, default_value_t = DEFAULT_TARGET_FPS,
+          value_parser = clap::value_parser!(u32).range(1..=240))]
+    fps: u32,
+
+    /// Cells moved per second while an arrow key is held
+    #[arg(long, default_value_t = DEFAULT_MOVE_RATE, value_parser = parse_move_rate)]
+    move_rate: f64,
+
+    /// Draw with plain ASCII instead of box drawing and Unicode symbols
+    #[arg(long)]
+    ascii: bool,
+
+    /// Draw without colour
+    #[arg(long)]
+    no_color: bool,
+
+    /// Least severe notice to report, and least severe entry to write to
+    /// the log file
+    #[arg(long, value_enum, default_value_t = LogLevel::Info)]
+    log_level: LogLevel,
+
+    /// Run without a terminal interface for hours=N simulated hours, auditing
+    /// the world after each and stopping at the first violation
+    #[arg(long, value_name = "hours=N", value_parser = parse_soak,
+          conflicts_with_all = ["headless", "compare"])]
+    soak: Option<u64>,
+
+    /// Stream the world as JSON lines to clients connecting to this local
+    /// address, once a tick
+    #[cfg(feature = "serve")]
+    #[arg(long, value_name = "ADDR", num_args = 0..=1,
+          default_missing_value = DEFAULT_SERVE_ADDR,
+          conflicts_with_all = ["soak", "compare"])]
+    serve: Option<String>,
+
+    /// Chart the galaxy's trends across saves, or the most recent ones
+    #[arg(long, value_name = "SAVE", num_args = 0..)]
+    compare: Option<Vec<PathBuf>>,
+
+    /// Report what changed between two saves, or between one and the
+    /// newest save
+    #[arg(long, value_name = "SAVE", num_args = 1..=2, conflicts_with = "compare")]
+    diff: Option<Vec<PathBuf>>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum LogLevel {
+    /// Nothing
+    Off,
+    Critical,
+    Warning,
+    Info,
+    /// Every log entry as well as notices; shown headless only, and
+    /// written to the log file along with each tick, frame and key press
+    Debug,
+}
+
+impl LogLevel {
+    /// The least severe notice shown at this level
+    fn threshold(self) -> Option<Severity> {
+        match self {
+            LogLevel::Off => None,
+            LogLevel::Critical => Some(Severity::Critical),
+            LogLevel::Warning => Some(Severity::Warning),
+            LogLevel::Info | LogLevel::Debug => Some(Severity::Info),
+        }
+    }
+
+    /// The least severe entry written to the log file at this level
+    fn filter(self) -> LevelFilter {
+        match self {
+            LogLevel::Off => LevelFilter::OFF,
+            LogLevel::Critical => LevelFilter::ERROR,
+            LogLevel::Warning => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+        }
+    }
+}
+
+/// Replace the built-in goods and recipes with the data directory's goods
+/// file, if there is one, before any world is made
+fn load_goods(data_dir: &Path) -> Result<()> {
+    let path = data_dir.join(GOODS_FILE);
+    if path.exists() {
+        data::install(GoodsData::read_from(&path)?)?;
+    }
+    Ok(())
+}
+
+/// What the terminal is taken to support: detected from the environment,
+/// less whatever the command line turns off
+fn capabilities(cli: &Cli) -> Capabilities {
+    let mut capabilities = Capabilities::detect();
+    capabilities.unicode &= !cli.ascii;
+    capabilities.color &= !cli.no_color;
+    capabilities
+}
+
+/// Listen for observers if the command line asks for it
+#[cfg(feature = "serve")]
+fn observer(cli: &Cli) -> Result<Option<Observer>> {
+    let Some(addr) = &cli.serve else {
+        return Ok(None);
+    };
+    let observer = Observer::bind(addr.as_str())?;
+    let addr = observer.local_addr()?;
+    tracing::info!("Serving the world on {}", addr);
+    if cli.headless {
+        eprintln!("Serving the world on {}", addr);
+    }
+    Ok(Some(observer))
+}
+
+/// What a session starts from
+enum Start {
+    /// A new game in the named preset's world, or a preset to be chosen,
+    /// generated from the seed given
+    New(Option<GamePreset>, Option<u64>),
+    Scenario(Scenario),
+    Save(SaveGame, &'static str),
+}
 
 fn main() {
-    if let Err(e) = run() {
+    if let Err(e) = run(Cli::parse()) {
         eprintln!("Error: {:?}", e);
         std::process::exit(1);
     }
 }
 
-fn run() -> Result<()> {
+fn run(cli: Cli) -> Result<()> {
+    let data_dir = save::data_dir();
+    let log = logging::init(&data_dir, cli.log_level.filter())?;
+    tracing::info!("Econogenesis {} starting", env!("CARGO_PKG_VERSION"));
+    load_goods(&data_dir)?;
+    if let Some(paths) = cli.compare {
+        return compare_saves(&data_dir, paths);
+    }
+    if let Some(paths) = cli.diff {
+        return diff_saves(&data_dir, paths);
+    }
+    if let Some(hours) = cli.soak {
+        if !run_soak(&cli, &data_dir, hours)? {
+            // Exiting skips destructors, so flush the log first
+            drop(log);
+            std::process::exit(2);
+        }
+        return Ok(());
+    }
+    if cli.headless {
+        return run_headless(&cli, &data_dir);
+    }
+
+    let session = SessionLock::acquire(&data_dir)?;
     let mut device = stdout();
-    let engine = RenderEngine::new(&mut device)?;
-    let game_loop = GameLoop::new(engine);
+    let mut game_loop = match start_up(&cli, &data_dir, &session, &mut device) {
+        Ok(game_loop) => game_loop,
+        Err(error) => {
+            // Nothing was played, so leave the lock as it was found
+            session.abandon()?;
+            return Err(error);
+        }
+    };
+    if let Some(guard) = log {
+        game_loop.keep_log(guard);
+    }
 
-    game_loop.run()?;
+    let profiler = game_loop.run()?;
+    session.release()?;
 
     println!("Econogenesis exited successfully!");
+    println!();
+    for line in profiler.report() {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// Everything before the game loop runs that can fail: choosing the world,
+/// taking over the terminal and listening for observers
+fn start_up<'a>(
+    cli: &Cli,
+    data_dir: &Path,
+    session: &SessionLock,
+    device: &'a mut Stdout,
+) -> Result<GameLoop<'a>> {
+    let saves = SaveManager::new(data_dir);
+
+    let mut notice = None;
+    let start = if session.previous_crashed() {
+        match prompt_recovery(data_dir)? {
+            Some(save) => Start::Save(save, "Recovered from autosave"),
+            None => choose_start(cli, &saves, &mut notice)?,
+        }
+    } else {
+        choose_start(cli, &saves, &mut notice)?
+    };
+
+    let mut engine = RenderEngine::new(device)?;
+    let capabilities = capabilities(cli);
+    engine.set_capabilities(capabilities);
+    let mut game_loop = GameLoop::new(engine, saves);
+    if !capabilities.unicode {
+        game_loop.set_glyph_set(GlyphSet::Ascii);
+    }
+    game_loop.set_target_fps(cli.fps);
+    game_loop.set_move_rate(cli.move_rate);
+    game_loop.set_toast_level(cli.log_level.threshold());
+    #[cfg(feature = "serve")]
+    if let Some(observer) = observer(cli)? {
+        game_loop.log(format!("Serving the world on {}", observer.local_addr()?));
+        game_loop.serve(observer);
+    }
+    match start {
+        Start::New(Some(preset), seed) => {
+            game_loop.start(preset.generate(seed.unwrap_or(DEFAULT_SEED)));
+            game_loop.log(format!("Started a {} game", preset.name));
+        }
+        Start::New(None, seed) => game_loop.open_main_menu(seed.unwrap_or(DEFAULT_SEED)),
+        Start::Scenario(scenario) => {
+            let message = format!("Started scenario {}", scenario.name);
+            game_loop.load_scenario(scenario);
+            game_loop.log(message);
+        }
+        Start::Save(save, message) => {
+            game_loop.load(save);
+            game_loop.log(message);
+        }
+    }
+    if let Some(notice) = notice {
+        game_loop.log(notice);
+    }
+    game_loop.load_mods(&data_dir.join(MOD_DIR));
+    Ok(game_loop)
+}
+
+/// Pick the world to start in from the command line
+fn choose_start(
+    cli: &Cli,
+    saves: &SaveManager,
+    notice: &mut Option<&'static str>,
+) -> Result<Start> {
+    if let Some(path) = &cli.scenario {
+        return Ok(Start::Scenario(Scenario::read_from(path)?));
+    }
+    if let Some(path) = &cli.world_config {
+        let mut config = WorldConfig::read_from(path)?;
+        if let Some(seed) = cli.seed {
+            config.seed = seed;
+        }
+        return Ok(Start::Scenario(Scenario::new(
+            "Generated world",
+            config.generate(),
+        )));
+    }
+    if let Some(path) = &cli.load {
+        return Ok(Start::Save(SaveGame::read_from(path)?, "Loaded saved game"));
+    }
+    if cli.continue_session {
+        match saves.load_last() {
+            Some(save) => return Ok(Start::Save(save?, "Resumed saved game")),
+            None => *notice = Some("No previous save to continue; starting a new game"),
+        }
+    }
+    Ok(Start::New(cli.preset, cli.seed))
+}
+
+/// The world for a new game without a terminal to pick a preset in: the
+/// named preset's, or else the sample world
+fn new_world(preset: Option<GamePreset>, seed: Option<u64>) -> WorldState {
+    if let Some(preset) = preset {
+        return preset.generate(seed.unwrap_or(DEFAULT_SEED));
+    }
+    let mut world = WorldState::new();
+    if let Some(seed) = seed {
+        world.reseed(seed);
+    }
+    world
+}
+
+/// Advance the world an hour at a time, reporting notices as they are
+/// raised, then print where it ended up. Saves nothing.
+fn run_headless(cli: &Cli, data_dir: &Path) -> Result<()> {
+    let saves = SaveManager::new(data_dir);
+    let mut notice = None;
+    let mut core = match choose_start(cli, &saves, &mut notice)? {
+        Start::New(preset, seed) => Core::with_world(new_world(preset, seed)),
+        Start::Scenario(scenario) => Core::wrap(Simulation::from_scenario(scenario)),
+        Start::Save(save, _) => Core::from_save(save),
+    };
+    if let Some(notice) = notice {
+        eprintln!("{}", notice);
+    }
+
+    #[cfg(feature = "serve")]
+    let mut observer = observer(cli)?;
+    let threshold = cli.log_level.threshold();
+    for _ in 0..cli.ticks {
+        let _ = core.apply(Command::Advance { seconds: 3600 });
+        #[cfg(feature = "serve")]
+        if let Some(observer) = &mut observer {
+            core.publish(observer);
+        }
+        for event in core.poll_events() {
+            match event {
+                Event::Notice { severity, message }
+                    if threshold.is_some_and(|level| severity >= level) =>
+                {
+                    println!("[{:?}] {}", severity, message);
+                }
+                Event::Logged { at, message } if cli.log_level == LogLevel::Debug => {
+                    println!("{:>10.0}s {}", at, message);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let snapshot = core.snapshot();
+    let economy = &snapshot.economy;
+    println!("{} at tick {}", snapshot.date, snapshot.tick);
+    println!("  population    {}", economy.population);
+    println!(
+        "  money supply  {}{:.2}",
+        economy.currency_symbol, economy.money_supply
+    );
+    println!("  price level   {:.3}", economy.price_level);
+    println!("  firms         {}", economy.firms);
     Ok(())
 }
+
+/// The hours in a `--soak` argument, written `hours=N`
+fn parse_soak(value: &str) -> std::result::Result<u64, String> {
+    value
+        .strip_prefix("hours=")
+        .and_then(|hours| hours.parse().ok())
+        .ok_or_else(|| format!("expected hours=N, not '{}'", value))
+}
+
+fn parse_preset(value: &str) -> std::result::Result<GamePreset, String> {
+    GamePreset::find(value).ok_or_else(|| {
+        let names: Vec<&str> = PRESETS.iter().map(|preset| preset.name).collect();
+        format!("expected one of {}, not '{}'", names.join(", "), value)
+    })
+}
+
+fn parse_move_rate(value: &str) -> std::result::Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(rate) if rate > 0.0 && rate <= 240.0 => Ok(rate),
+        _ => Err(format!(
+            "expected cells per second up to 240, not '{}'",
+            value
+        )),
+    }
+}
+
+/// Run the simulation headlessly with audits after every hour, writing a
+/// report and a save to reproduce from at the first violation. Returns
+/// whether every audit passed.
+fn run_soak(cli: &Cli, data_dir: &Path, hours: u64) -> Result<bool> {
+    let saves = SaveManager::new(data_dir);
+    let mut notice = None;
+    let mut simulation = match choose_start(cli, &saves, &mut notice)? {
+        Start::New(preset, seed) => Simulation::with_world(new_world(preset, seed)),
+        Start::Scenario(scenario) => Simulation::from_scenario(scenario),
+        Start::Save(save, _) => Simulation::from_save(save),
+    };
+    if let Some(notice) = notice {
+        eprintln!("{}", notice);
+    }
+
+    let report = soak::soak(&mut simulation, hours);
+    for line in report.lines() {
+        println!("{}", line);
+    }
+    if report.passed() {
+        return Ok(true);
+    }
+    for path in report.store(&data_dir.join(SOAK_DIR))? {
+        println!("Wrote {}", path.display());
+    }
+    Ok(false)
+}
+
+/// Print trend charts for the named saves, or the most recent ones, without
+/// starting a game
+fn compare_saves(data_dir: &Path, mut paths: Vec<PathBuf>) -> Result<()> {
+    if paths.is_empty() {
+        paths = compare::recent_saves(data_dir, compare::DEFAULT_RUNS);
+    }
+    if paths.is_empty() {
+        println!("No saves found in {}", data_dir.display());
+        return Ok(());
+    }
+
+    let mut runs = Vec::new();
+    for path in &paths {
+        runs.push(Run::from_save(path, &SaveGame::read_from(path)?));
+    }
+    for line in compare::comparison(&runs) {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// Print what changed between two saves, the second the newest save in the
+/// data directory unless named, without starting a game
+fn diff_saves(data_dir: &Path, mut paths: Vec<PathBuf>) -> Result<()> {
+    if paths.len() == 1 {
+        match compare::recent_saves(data_dir, 1).pop() {
+            Some(newest) => paths.push(newest),
+            None => {
+                println!("No saves found in {}", data_dir.display());
+                return Ok(());
+            }
+        }
+    }
+
+    let calendar = Calendar::default();
+    let read = |path: &Path| -> Result<(WorldState, String)> {
+        let save = SaveGame::read_from(path)?;
+        let label = diff::save_label(path, &save, &calendar);
+        Ok((save.world, label))
+    };
+    let (before, from) = read(&paths[0])?;
+    let (after, to) = read(&paths[1])?;
+    for line in WorldDiff::between(&before, &after, &from, &to).lines() {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// The last session did not shut down cleanly; offer to resume from the
+/// newest autosave before the terminal switches to the game screen
+fn prompt_recovery(data_dir: &Path) -> Result<Option<SaveGame>> {
+    let Some(path) = Autosave::latest(data_dir) else {
+        return Ok(None);
+    };
+
+    print!(
+        "The previous session ended unexpectedly. Recover from {}? [Y/n] ",
+        path.display()
+    );
+    stdout().flush()?;
+
+    let mut answer = String::new();
+    stdin().lock().read_line(&mut answer)?;
+    if answer.trim().eq_ignore_ascii_case("n") {
+        return Ok(None);
+    }
+
+    Ok(Some(SaveGame::read_from(&path)?))
+}
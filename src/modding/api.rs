@@ -0,0 +1,194 @@
+//! Functions scripts can call.
+//!
+//! Reads come from a [`WorldView`] gathered before each hook runs; writes
+//! are queued as [`ModAction`]s and applied once the hook returns, so a
+//! script never holds a reference into the world.
+
+use rhai::{Engine, EvalAltResult, FLOAT, INT};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::economy::GoodCategory;
+use crate::game::WorldState;
+use crate::render::Glyphs;
+use crate::zoom::ZoomLevel;
+
+/// A change a script asked for
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModAction {
+    Log(String),
+    SetPopulation {
+        target: String,
+        population: u64,
+    },
+    AddGood {
+        name: String,
+        category: GoodCategory,
+        base_value: f64,
+    },
+    /// The mod's win condition was met
+    Victory(String),
+    /// Draw a glyph key with another symbol
+    SetGlyph {
+        key: String,
+        glyph: char,
+    },
+}
+
+/// The facts a hook can read
+#[derive(Debug, Clone, Default)]
+pub struct WorldView {
+    day: INT,
+    firms: INT,
+    price_level: FLOAT,
+    money: FLOAT,
+    /// Keyed by lowercased planet or area name
+    populations: HashMap<String, INT>,
+    /// Keyed by lowercased area name, then good name
+    prices: HashMap<(String, String), FLOAT>,
+}
+
+impl WorldView {
+    pub fn capture(world: &WorldState) -> Self {
+        let economy = world.economy();
+        let supply = economy.money_supply();
+        let mut view = Self {
+            day: (world.elapsed().as_secs() / 86_400) as INT,
+            firms: economy.firm_count() as INT,
+            price_level: supply.price_level(),
+            money: supply.issued().amount(),
+            ..Self::default()
+        };
+
+        for level in [ZoomLevel::Planet, ZoomLevel::LocalArea] {
+            for id in world.ids(level) {
+                if let Some(population) = world.population(level, id) {
+                    let name = world.entity_name(level, id).to_lowercase();
+                    view.populations.insert(name, population as INT);
+                }
+            }
+        }
+
+        for (area_id, market) in economy.markets() {
+            let area = world
+                .entity_name(ZoomLevel::LocalArea, area_id)
+                .to_lowercase();
+            for (good_id, good) in economy.catalog().iter() {
+                view.prices.insert(
+                    (area.clone(), good.name.clone()),
+                    market.price(good_id).amount(),
+                );
+            }
+        }
+        view
+    }
+}
+
+/// What hooks share with the functions they call
+#[derive(Debug, Default)]
+pub struct ScriptContext {
+    pub view: WorldView,
+    pub actions: Vec<ModAction>,
+}
+
+pub type SharedContext = Rc<RefCell<ScriptContext>>;
+
+fn category(name: &str) -> Option<GoodCategory> {
+    Some(match name.to_ascii_lowercase().as_str() {
+        "raw" => GoodCategory::Raw,
+        "food" => GoodCategory::Food,
+        "material" => GoodCategory::Material,
+        "tool" => GoodCategory::Tool,
+        "luxury" => GoodCategory::Luxury,
+        _ => return None,
+    })
+}
+
+/// Register the world API on an engine
+pub fn register(engine: &mut Engine, context: &SharedContext) {
+    let ctx = context.clone();
+    engine.register_fn("day", move || ctx.borrow().view.day);
+    let ctx = context.clone();
+    engine.register_fn("firm_count", move || ctx.borrow().view.firms);
+    let ctx = context.clone();
+    engine.register_fn("price_level", move || ctx.borrow().view.price_level);
+    let ctx = context.clone();
+    engine.register_fn("money_supply", move || ctx.borrow().view.money);
+
+    let ctx = context.clone();
+    engine.register_fn("population", move |name: &str| {
+        let view = &ctx.borrow().view;
+        view.populations
+            .get(&name.to_lowercase())
+            .copied()
+            .unwrap_or(0)
+    });
+    let ctx = context.clone();
+    engine.register_fn("price", move |area: &str, good: &str| {
+        let view = &ctx.borrow().view;
+        view.prices
+            .get(&(area.to_lowercase(), good.to_string()))
+            .copied()
+            .unwrap_or(0.0)
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("log", move |message: &str| {
+        let action = ModAction::Log(message.to_string());
+        ctx.borrow_mut().actions.push(action);
+    });
+    let ctx = context.clone();
+    engine.on_print(move |message| {
+        let action = ModAction::Log(message.to_string());
+        ctx.borrow_mut().actions.push(action);
+    });
+    engine.on_debug(|_, _, _| {});
+
+    let ctx = context.clone();
+    engine.register_fn("set_population", move |target: &str, population: INT| {
+        let action = ModAction::SetPopulation {
+            target: target.to_string(),
+            population: population.max(0) as u64,
+        };
+        ctx.borrow_mut().actions.push(action);
+    });
+    let ctx = context.clone();
+    engine.register_fn(
+        "add_good",
+        move |name: &str, kind: &str, base_value: FLOAT| -> Result<(), Box<EvalAltResult>> {
+            let category = category(kind).ok_or_else(|| format!("unknown category '{}'", kind))?;
+            let action = ModAction::AddGood {
+                name: name.to_string(),
+                category,
+                base_value,
+            };
+            ctx.borrow_mut().actions.push(action);
+            Ok(())
+        },
+    );
+    let ctx = context.clone();
+    engine.register_fn(
+        "set_glyph",
+        move |key: &str, glyph: &str| -> Result<(), Box<EvalAltResult>> {
+            if !Glyphs::is_key(key) {
+                return Err(format!("unknown glyph '{}'", key).into());
+            }
+            let mut chars = glyph.chars();
+            let (Some(glyph), None) = (chars.next(), chars.next()) else {
+                return Err(format!("glyph for '{}' must be one character", key).into());
+            };
+            let action = ModAction::SetGlyph {
+                key: key.to_string(),
+                glyph,
+            };
+            ctx.borrow_mut().actions.push(action);
+            Ok(())
+        },
+    );
+    let ctx = context.clone();
+    engine.register_fn("victory", move |message: &str| {
+        let action = ModAction::Victory(message.to_string());
+        ctx.borrow_mut().actions.push(action);
+    });
+}
@@ -0,0 +1,373 @@
+//! The Rhai engine mods run in, and the hooks it calls them through.
+
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{AST, CallFnOptions, Dynamic, Engine, FLOAT, INT, Scope};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::economy::Good;
+use crate::game::WorldState;
+use crate::zoom::ZoomLevel;
+
+use super::ModError;
+use super::api::{self, ModAction, ScriptContext, SharedContext, WorldView};
+
+/// Script operations allowed per hook call before it is aborted
+const MAX_OPERATIONS: u64 = 100_000;
+const MAX_CALL_LEVELS: usize = 32;
+const MAX_STRING_SIZE: usize = 4_096;
+
+const HOOKS: [&str; 3] = ["on_tick", "on_entity_created", "on_market_clear"];
+
+struct LoadedMod {
+    name: String,
+    ast: AST,
+    /// Hook functions the script defines
+    hooks: Vec<&'static str>,
+    /// Whether the mod has declared victory; later declarations are ignored
+    won: bool,
+}
+
+impl LoadedMod {
+    fn defines(&self, hook: &str) -> bool {
+        self.hooks.contains(&hook)
+    }
+}
+
+/// Runs loaded mods against a world
+pub struct ModHost {
+    engine: Engine,
+    mods: Vec<LoadedMod>,
+    context: SharedContext,
+    /// Simulation time when hooks last ran
+    last_elapsed: Duration,
+    /// Glyphs mods replaced since they were last taken
+    glyphs: Vec<(String, char)>,
+}
+
+impl ModHost {
+    pub fn new() -> Self {
+        let context: SharedContext = Rc::new(RefCell::new(ScriptContext::default()));
+        let mut engine = Engine::new();
+        engine.set_module_resolver(DummyModuleResolver::new());
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_call_levels(MAX_CALL_LEVELS);
+        engine.set_max_string_size(MAX_STRING_SIZE);
+        api::register(&mut engine, &context);
+
+        Self {
+            engine,
+            mods: Vec::new(),
+            context,
+            last_elapsed: Duration::ZERO,
+            glyphs: Vec::new(),
+        }
+    }
+
+    /// Load every `*.rhai` file in `dir` in name order. A missing directory
+    /// loads nothing; mods that fail are reported and skipped.
+    pub fn load_dir(&mut self, dir: &Path, world: &mut WorldState) -> Vec<ModError> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+            .collect();
+        paths.sort();
+
+        let mut errors = Vec::new();
+        for path in paths {
+            let name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let result = std::fs::read_to_string(&path)
+                .map_err(|source| ModError::Read {
+                    path: path.clone(),
+                    source,
+                })
+                .and_then(|source| self.load_script(&name, &source, world));
+            if let Err(error) = result {
+                errors.push(error);
+            }
+        }
+        errors
+    }
+
+    /// Compile a script and run its top-level statements
+    pub fn load_script(
+        &mut self,
+        name: &str,
+        source: &str,
+        world: &mut WorldState,
+    ) -> Result<(), ModError> {
+        let script_error = |message: String| ModError::Script {
+            name: name.to_string(),
+            message,
+        };
+        let ast = self
+            .engine
+            .compile(source)
+            .map_err(|e| script_error(e.to_string()))?;
+
+        self.context.borrow_mut().view = WorldView::capture(world);
+        let result = self.engine.run_ast(&ast);
+        let victories = self.apply_actions(name, world);
+        result.map_err(|e| script_error(e.to_string()))?;
+        if !victories.is_empty() {
+            world.log(format!("Mod {} declared victory while loading", name));
+        }
+
+        let hooks = HOOKS
+            .into_iter()
+            .filter(|hook| ast.iter_functions().any(|f| f.name == *hook))
+            .collect();
+        self.mods.push(LoadedMod {
+            name: name.to_string(),
+            ast,
+            hooks,
+            won: false,
+        });
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.mods.len()
+    }
+
+    /// Glyph replacements mods asked for since the last call, in order
+    pub fn take_glyphs(&mut self) -> Vec<(String, char)> {
+        std::mem::take(&mut self.glyphs)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mods.is_empty()
+    }
+
+    /// Call every mod's hooks for what happened since the last run, returning
+    /// the messages of any victories declared
+    pub fn run(&mut self, world: &mut WorldState) -> Vec<String> {
+        let spawned = world.take_spawned();
+        let elapsed = world.elapsed();
+        let advanced = elapsed.saturating_sub(self.last_elapsed);
+        self.last_elapsed = elapsed;
+        if self.mods.is_empty() || (spawned.is_empty() && advanced.is_zero()) {
+            return Vec::new();
+        }
+
+        let mut victories = Vec::new();
+        for index in 0..self.mods.len() {
+            self.context.borrow_mut().view = WorldView::capture(world);
+            let mut calls: Vec<(&str, Vec<Dynamic>)> = Vec::new();
+            let loaded = &self.mods[index];
+
+            if loaded.defines("on_entity_created") {
+                for (level, id) in &spawned {
+                    let args = vec![level.to_string().into(), (*id as INT).into()];
+                    calls.push(("on_entity_created", args));
+                }
+            }
+            if !advanced.is_zero() {
+                if loaded.defines("on_tick") {
+                    let hours = advanced.as_secs_f64() / 3600.0;
+                    calls.push(("on_tick", vec![(hours as FLOAT).into()]));
+                }
+                if loaded.defines("on_market_clear") {
+                    let economy = world.economy();
+                    for (area_id, market) in economy.markets() {
+                        let area = world.entity_name(ZoomLevel::LocalArea, area_id);
+                        let index = market.price_index(economy.catalog());
+                        calls.push((
+                            "on_market_clear",
+                            vec![area.into(), (index as FLOAT).into()],
+                        ));
+                    }
+                }
+            }
+
+            for (hook, args) in calls {
+                let options = CallFnOptions::new().eval_ast(false);
+                let result = self.engine.call_fn_with_options::<Dynamic>(
+                    options,
+                    &mut Scope::new(),
+                    &loaded.ast,
+                    hook,
+                    args,
+                );
+                if let Err(error) = result {
+                    world.log(format!("Mod {}: {}", loaded.name, error));
+                    break;
+                }
+            }
+
+            let name = loaded.name.clone();
+            let declared = self.apply_actions(&name, world);
+            let loaded = &mut self.mods[index];
+            if !loaded.won
+                && let Some(message) = declared.into_iter().next()
+            {
+                loaded.won = true;
+                victories.push(message);
+            }
+        }
+        victories
+    }
+
+    /// Carry out what a script asked for, returning any victory messages
+    fn apply_actions(&mut self, name: &str, world: &mut WorldState) -> Vec<String> {
+        let actions = std::mem::take(&mut self.context.borrow_mut().actions);
+        let mut victories = Vec::new();
+        for action in actions {
+            match action {
+                ModAction::Log(message) => world.log(format!("[{}] {}", name, message)),
+                ModAction::SetPopulation { target, population } => {
+                    let set = world
+                        .find_entity(&target)
+                        .is_some_and(|(level, id)| world.set_population(level, id, population));
+                    if !set {
+                        world.log(format!("Mod {}: no population in '{}'", name, target));
+                    }
+                }
+                ModAction::AddGood {
+                    name: good,
+                    category,
+                    base_value,
+                } => {
+                    // A resumed save already lists the goods its mods added
+                    world.economy_mut().add_good(Good {
+                        name: good,
+                        category,
+                        base_value,
+                        weight: 1.0,
+                        volume: 1.0,
+                    });
+                }
+                ModAction::Victory(message) => victories.push(message),
+                ModAction::SetGlyph { key, glyph } => self.glyphs.push((key, glyph)),
+            }
+        }
+        victories
+    }
+}
+
+impl Default for ModHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(world: &WorldState) -> Vec<String> {
+        let log = world.events();
+        log.recent(log.len()).map(|e| e.message.clone()).collect()
+    }
+
+    #[test]
+    fn test_script_adds_good() {
+        let mut world = WorldState::new();
+        let mut host = ModHost::new();
+        host.load_script("spice", r#"add_good("Spice", "luxury", 40.0);"#, &mut world)
+            .unwrap();
+
+        let catalog = world.economy().catalog();
+        let spice = catalog.id("Spice").unwrap();
+        assert_eq!(
+            catalog.get(spice).unwrap().category,
+            crate::economy::GoodCategory::Luxury
+        );
+        assert!(
+            host.load_script("bad", r#"add_good("Gold", "shiny", 1.0);"#, &mut world)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_tick_hook_runs_when_time_advances() {
+        let mut world = WorldState::new();
+        let mut host = ModHost::new();
+        host.load_script(
+            "ticker",
+            r#"fn on_tick(hours) { log("tick"); }"#,
+            &mut world,
+        )
+        .unwrap();
+
+        host.run(&mut world);
+        assert!(!messages(&world).contains(&String::from("[ticker] tick")));
+
+        world.update(Duration::from_secs(3_600));
+        host.run(&mut world);
+        assert!(messages(&world).contains(&String::from("[ticker] tick")));
+    }
+
+    #[test]
+    fn test_script_sets_population() {
+        let mut world = WorldState::new();
+        let mut host = ModHost::new();
+        let script = r#"fn on_tick(hours) { set_population("Terra", population("terra") / 2); }"#;
+        host.load_script("halve", script, &mut world).unwrap();
+        let before = world.population(ZoomLevel::Planet, 1).unwrap();
+
+        world.update(Duration::from_secs(1));
+        host.run(&mut world);
+        assert_eq!(world.population(ZoomLevel::Planet, 1).unwrap(), before / 2);
+    }
+
+    #[test]
+    fn test_victory_is_returned() {
+        let mut world = WorldState::new();
+        let mut host = ModHost::new();
+        let script = r#"fn on_market_clear(area, index) { if index > 0.0 { victory("Markets open in " + area); } }"#;
+        host.load_script("win", script, &mut world).unwrap();
+
+        world.update(Duration::from_secs(60));
+        let victories = host.run(&mut world);
+        assert_eq!(victories.len(), 1);
+        assert!(victories[0].starts_with("Markets open in "));
+
+        world.update(Duration::from_secs(60));
+        assert!(host.run(&mut world).is_empty());
+    }
+
+    #[test]
+    fn test_script_replaces_glyphs() {
+        let mut world = WorldState::new();
+        let mut host = ModHost::new();
+        host.load_script("theme", r#"set_glyph("forest", "T");"#, &mut world)
+            .unwrap();
+        assert_eq!(host.take_glyphs(), vec![(String::from("forest"), 'T')]);
+        assert!(host.take_glyphs().is_empty());
+        assert!(
+            host.load_script("bad", r#"set_glyph("forest", "TT");"#, &mut world)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_compile_error_is_reported() {
+        let mut world = WorldState::new();
+        let mut host = ModHost::new();
+        let error = host.load_script("broken", "fn on_tick( {", &mut world);
+
+        assert!(matches!(error, Err(ModError::Script { name, .. }) if name == "broken"));
+        assert!(host.is_empty());
+    }
+
+    #[test]
+    fn test_entity_created_hook() {
+        let mut world = WorldState::new();
+        let mut host = ModHost::new();
+        let script = r#"fn on_entity_created(level, id) { log("new " + level); }"#;
+        host.load_script("watch", script, &mut world).unwrap();
+
+        world.spawn(ZoomLevel::Planet);
+        host.run(&mut world);
+        assert!(messages(&world).contains(&format!("[watch] new {}", ZoomLevel::Planet)));
+    }
+}
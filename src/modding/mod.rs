@@ -0,0 +1,62 @@
+//! Player mods written in Rhai.
+//!
+//! Every `*.rhai` file in the mods directory is compiled at startup and its
+//! top-level statements run once, which is where a mod adds its goods. After
+//! that the host calls whichever of these functions a script defines:
+//!
+//! - `on_tick(hours)` each time the simulation advances
+//! - `on_entity_created(level, id)` for every newly spawned entity
+//! - `on_market_clear(area, price_index)` for every market after it reprices
+//!
+//! Scripts read the world through the functions in `api` and never touch
+//! it directly; what they ask for is applied once the hook returns.
+//!
+//! All of this needs the `scripting` feature. Without it, [`ModHost`]
+//! reports each mod it finds and loads none, and [`Query`] refuses every
+//! expression.
+
+#[cfg(feature = "scripting")]
+mod api;
+#[cfg(feature = "scripting")]
+mod host;
+#[cfg(feature = "scripting")]
+pub mod query;
+#[cfg(not(feature = "scripting"))]
+mod unscripted;
+
+#[cfg(feature = "scripting")]
+pub use api::ModAction;
+#[cfg(feature = "scripting")]
+pub use host::ModHost;
+#[cfg(feature = "scripting")]
+pub use query::Query;
+#[cfg(not(feature = "scripting"))]
+pub use unscripted::{ModHost, Query};
+
+use std::path::PathBuf;
+use thiserror::Error as ThisError;
+
+/// Directory under the data directory that mods are loaded from
+pub const MOD_DIR: &str = "mods";
+
+#[derive(ThisError, Debug)]
+pub enum ModError {
+    #[error("could not read mod {path}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("mod {name}: {message}")]
+    Script { name: String, message: String },
+}
+
+#[derive(ThisError, Debug, Clone, PartialEq)]
+pub enum QueryError {
+    #[error("'{expression}' is not a valid query: {message}")]
+    Parse { expression: String, message: String },
+    #[error("query failed: {0}")]
+    Evaluate(String),
+    #[error("query gave {0} rather than a number")]
+    NotANumber(String),
+}
@@ -0,0 +1,228 @@
+//! Expressions over a region's economy, for custom map overlays and the
+//! console's watches.
+//!
+//! A query is a Rhai expression such as `price(grain) / wage` worked out
+//! once for each region. It can read:
+//!
+//! - `price(good)`: the good's average price across the region's markets
+//! - `wage`: the average wage across them
+//! - `workforce` and `firms`: totals for the region's local areas
+//! - `farming`, `mining`, `logging` and `fishing`: how well the land and
+//!   what is left of its deposits suit each, where 1.0 is the usual output
+//!
+//! Each good's name can be written bare, as in `price(grain)`, with any
+//! character that can't appear in a name replaced by `_`. Queries only
+//! read; they have none of the functions mods use to change the world.
+
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{AST, Dynamic, Engine, FLOAT, INT, Scope};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::economy::Market;
+use crate::game::WorldState;
+use crate::game::state::EntityId;
+use crate::zoom::ZoomLevel;
+
+use super::QueryError;
+
+/// Script operations allowed for one region before the query is aborted
+const MAX_OPERATIONS: u64 = 10_000;
+
+/// Averages of a region's markets, for `price(good)` to read
+type Prices = Rc<RefCell<HashMap<String, f64>>>;
+
+/// A compiled query, ready to be worked out for any region
+pub struct Query {
+    expression: String,
+    engine: Engine,
+    ast: AST,
+    prices: Prices,
+}
+
+impl Query {
+    pub fn compile(expression: &str) -> Result<Self, QueryError> {
+        let prices: Prices = Rc::default();
+        let mut engine = Engine::new();
+        engine.set_module_resolver(DummyModuleResolver::new());
+        engine.set_max_operations(MAX_OPERATIONS);
+        let read = prices.clone();
+        engine.register_fn("price", move |good: &str| {
+            read.borrow()
+                .get(&good.to_lowercase())
+                .copied()
+                .unwrap_or(0.0) as FLOAT
+        });
+        let ast = engine
+            .compile_expression(expression)
+            .map_err(|e| QueryError::Parse {
+                expression: expression.to_string(),
+                message: e.to_string(),
+            })?;
+        Ok(Self {
+            expression: expression.to_string(),
+            engine,
+            ast,
+            prices,
+        })
+    }
+
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// The query's value for a region, or None where it has no meaning,
+    /// such as a division by zero in a region without markets
+    pub fn evaluate(
+        &self,
+        world: &WorldState,
+        region: EntityId,
+    ) -> Result<Option<f64>, QueryError> {
+        let economy = world.economy();
+        let areas: Vec<EntityId> = world
+            .placed_in(ZoomLevel::LocalArea, Some(region))
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        let markets: Vec<_> = areas.iter().filter_map(|&id| economy.market(id)).collect();
+        let average = |value: &dyn Fn(&Market) -> f64| {
+            if markets.is_empty() {
+                return 0.0;
+            }
+            markets.iter().map(|market| value(market)).sum::<f64>() / markets.len() as f64
+        };
+
+        let mut scope = Scope::new();
+        let mut prices = HashMap::new();
+        for (good_id, good) in economy.catalog().iter() {
+            let name = good.name.to_lowercase();
+            prices.insert(
+                name.clone(),
+                average(&|market| market.price(good_id).amount()),
+            );
+            scope.push_constant(identifier(&name), name);
+        }
+        *self.prices.borrow_mut() = prices;
+
+        let yields = world.region_yields(region);
+        let workforce: u32 = areas
+            .iter()
+            .filter_map(|&id| world.population(ZoomLevel::LocalArea, id))
+            .map(|people| people as u32)
+            .sum();
+        let firms: usize = areas
+            .iter()
+            .map(|&id| economy.firms_in_area(id).count())
+            .sum();
+        scope.push_constant("wage", average(&|market| market.wage().amount()) as FLOAT);
+        scope.push_constant("workforce", workforce as INT);
+        scope.push_constant("firms", firms as INT);
+        scope.push_constant("farming", yields.farming as FLOAT);
+        scope.push_constant("mining", yields.mining as FLOAT);
+        scope.push_constant("logging", yields.logging as FLOAT);
+        scope.push_constant("fishing", yields.fishing as FLOAT);
+
+        let value: Dynamic = self
+            .engine
+            .eval_ast_with_scope(&mut scope, &self.ast)
+            .map_err(|e| QueryError::Evaluate(e.to_string()))?;
+        let number = if let Some(float) = value.clone().try_cast::<FLOAT>() {
+            float
+        } else if let Some(int) = value.clone().try_cast::<INT>() {
+            int as f64
+        } else if let Some(flag) = value.clone().try_cast::<bool>() {
+            if flag { 1.0 } else { 0.0 }
+        } else {
+            return Err(QueryError::NotANumber(value.type_name().to_string()));
+        };
+        Ok(number.is_finite().then_some(number))
+    }
+
+    /// The query's value for every region placed on a planet, in region id
+    /// order, skipping regions it has no value for. Fails only if it
+    /// fails for a region.
+    pub fn evaluate_planet(
+        &self,
+        world: &WorldState,
+        planet: EntityId,
+    ) -> Result<Vec<(EntityId, f64)>, QueryError> {
+        let mut regions: Vec<EntityId> = world
+            .placed_in(ZoomLevel::Region, Some(planet))
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        regions.sort_unstable();
+        let mut values = Vec::new();
+        for region in regions {
+            if let Some(value) = self.evaluate(world, region)? {
+                values.push((region, value));
+            }
+        }
+        Ok(values)
+    }
+}
+
+/// A good's name as a Rhai variable name
+fn identifier(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_reads_the_region() {
+        let world = WorldState::new();
+        let query = Query::compile("price(grain) / wage").unwrap();
+        let market = world.economy().market(1).unwrap();
+        let grain = world.economy().catalog().id("grain").unwrap();
+        let expected = market.price(grain).amount() / market.wage().amount();
+
+        let value = query.evaluate(&world, 1).unwrap().unwrap();
+        assert!((value - expected).abs() < 1e-9);
+        assert_eq!(query.evaluate_planet(&world, 1).unwrap(), vec![(1, value)]);
+
+        let firms = Query::compile("firms * 2").unwrap();
+        assert_eq!(firms.evaluate(&world, 1).unwrap(), Some(12.0));
+        assert_eq!(
+            Query::compile("mining > 0.0").unwrap().evaluate(&world, 1),
+            Ok(Some(1.0))
+        );
+    }
+
+    #[test]
+    fn test_bad_queries_are_reported() {
+        let world = WorldState::new();
+        assert!(matches!(
+            Query::compile("price(grain) /"),
+            Err(QueryError::Parse { .. })
+        ));
+        assert!(matches!(
+            Query::compile("spice * 2").unwrap().evaluate(&world, 1),
+            Err(QueryError::Evaluate(_))
+        ));
+        assert!(matches!(
+            Query::compile("\"text\"").unwrap().evaluate(&world, 1),
+            Err(QueryError::NotANumber(_))
+        ));
+        // Nothing to divide by in a region without markets
+        let mut world = WorldState::new();
+        let region = world.spawn(ZoomLevel::Region).unwrap();
+        assert_eq!(
+            Query::compile("price(grain) / wage")
+                .unwrap()
+                .evaluate(&world, region),
+            Ok(None)
+        );
+    }
+}
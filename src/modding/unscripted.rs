@@ -0,0 +1,118 @@
+//! Stand-ins for the mod host and queries in builds without the
+//! `scripting` feature. Mods are reported and skipped, and every query
+//! fails to compile, so the rest of the game runs as it would with no
+//! mods installed.
+
+use std::path::Path;
+
+use crate::game::WorldState;
+use crate::game::state::EntityId;
+
+use super::{ModError, QueryError};
+
+const UNSUPPORTED: &str = "this build has no scripting; rebuild with the `scripting` feature";
+
+/// Loads no mods, reporting any it finds
+#[derive(Default)]
+pub struct ModHost;
+
+impl ModHost {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Report every `*.rhai` file in `dir` as unable to load
+    pub fn load_dir(&mut self, dir: &Path, _world: &mut WorldState) -> Vec<ModError> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+            .filter_map(|path| path.file_stem().map(|s| s.to_string_lossy().into_owned()))
+            .collect();
+        names.sort();
+        names
+            .into_iter()
+            .map(|name| ModError::Script {
+                name,
+                message: UNSUPPORTED.to_string(),
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        0
+    }
+
+    pub fn take_glyphs(&mut self) -> Vec<(String, char)> {
+        Vec::new()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        true
+    }
+
+    /// Drain what spawned since the last run, as the real host does
+    pub fn run(&mut self, world: &mut WorldState) -> Vec<String> {
+        world.take_spawned();
+        Vec::new()
+    }
+}
+
+/// A query that can never be compiled
+pub struct Query {
+    expression: String,
+}
+
+impl Query {
+    pub fn compile(expression: &str) -> Result<Self, QueryError> {
+        Err(QueryError::Parse {
+            expression: expression.to_string(),
+            message: UNSUPPORTED.to_string(),
+        })
+    }
+
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    pub fn evaluate(
+        &self,
+        _world: &WorldState,
+        _region: EntityId,
+    ) -> Result<Option<f64>, QueryError> {
+        Ok(None)
+    }
+
+    pub fn evaluate_planet(
+        &self,
+        _world: &WorldState,
+        _planet: EntityId,
+    ) -> Result<Vec<(EntityId, f64)>, QueryError> {
+        Ok(Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mods_and_queries_are_refused() {
+        let dir = crate::save::test_dir("unscripted_mods");
+        std::fs::write(dir.join("spice.rhai"), "").unwrap();
+        std::fs::write(dir.join("notes.txt"), "").unwrap();
+        let mut world = WorldState::new();
+        let mut host = ModHost::new();
+        let errors = host.load_dir(&dir, &mut world);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().starts_with("mod spice: "));
+        assert!(host.is_empty());
+
+        assert!(matches!(
+            Query::compile("firms"),
+            Err(QueryError::Parse { .. })
+        ));
+    }
+}
@@ -1,10 +1,19 @@
 use crate::Result;
-use tty_interface::{Device, Interface, Position, pos};
+use tty_interface::{Color, Device, Interface, Position, pos};
 
+use super::cells::CellGrid;
+
+/// The terminal, double-buffered.
+///
+/// Frames are drawn into a back buffer of cells. Presenting one compares it
+/// with the frame shown before and only sends the cells that changed, so
+/// borders and labels redrawn every frame cost nothing on the wire.
 pub struct Canvas<'a> {
     interface: Interface<'a>,
-    width: u16,
-    height: u16,
+    /// The frame being drawn
+    back: CellGrid,
+    /// The frame on screen
+    front: CellGrid,
 }
 
 impl<'a> Canvas<'a> {
@@ -13,20 +22,34 @@ impl<'a> Canvas<'a> {
 
         let mut canvas = Self {
             interface,
-            width: 0,
-            height: 0,
+            back: CellGrid::new(0, 0),
+            front: CellGrid::new(0, 0),
         };
         canvas.update_size()?;
 
         Ok(canvas)
     }
 
+    /// Present the back buffer, sending only the cells that differ from
+    /// the frame on screen
     pub fn apply_staged_updates(&mut self) -> Result<()> {
-        Ok(self.interface.apply()?)
+        for run in self.back.changes_from(&self.front) {
+            let position = pos!(run.x, run.y);
+            match run.color {
+                Some(color) => self
+                    .interface
+                    .set_styled(position, &run.text, color.as_style()),
+                None => self.interface.set(position, &run.text),
+            }
+        }
+        self.interface.apply()?;
+        self.front.clone_from(&self.back);
+        Ok(())
     }
 
+    /// Blank the back buffer for a new frame
     pub fn clear(&mut self) {
-        self.interface.clear_rest_of_interface(pos!(0, 0));
+        self.back.clear();
     }
 
     pub fn exit(self) -> Result<()> {
@@ -34,47 +57,55 @@ impl<'a> Canvas<'a> {
     }
 
     pub fn update_size(&mut self) -> Result<()> {
-        (self.width, self.height) = crossterm::terminal::size()?;
+        let (width, height) = crossterm::terminal::size()?;
+        self.set_size(width, height);
+        Ok(())
+    }
+
+    fn set_size(&mut self, width: u16, height: u16) {
+        self.back = CellGrid::new(width, height);
+        self.front = CellGrid::new(width, height);
+    }
+
+    /// Adopt a new terminal size, blanking everything drawn at the old size
+    /// so no stale cells survive the reflow
+    pub fn resize(&mut self, width: u16, height: u16) -> Result<()> {
+        self.interface.clear_rest_of_interface(pos!(0, 0));
+        self.interface.apply()?;
+        self.set_size(width, height);
         Ok(())
     }
 
     pub fn width(&self) -> u16 {
-        self.width
+        self.back.width()
     }
 
     pub fn height(&self) -> u16 {
-        self.height
+        self.back.height()
     }
 
+    /// Draw text, clipped at the right edge so it never wraps
     pub fn draw_text(&mut self, x: u16, y: u16, text: &str) {
-        self.interface.set(pos!(x, y), text);
+        self.draw_colored(x, y, text, None);
     }
 
-    pub fn draw_horizontal_line(&mut self, x: u16, y: u16, mut length: u16, ch: char) {
-        let available_space = self.width - x;
-        if length > available_space {
-            length = available_space;
-        }
-
-        let line = ch.to_string().repeat(length as usize);
-        self.interface.set(pos!(x, y), &line);
+    /// Like [`Canvas::draw_text`], in a foreground color if one is given
+    pub fn draw_colored(&mut self, x: u16, y: u16, text: &str, color: Option<Color>) {
+        self.back.put(x, y, text, color);
     }
 
-    pub fn draw_box(&mut self, x: u16, y: u16, width: u16, height: u16) {
-        // Top border
-        self.draw_text(x, y, "┌");
-        self.draw_horizontal_line(x + 1, y, width - 2, '─');
-        self.draw_text(x + width - 1, y, "┐");
-
-        // Sides
-        for i in 1..height - 1 {
-            self.draw_text(x, y + i, "│");
-            self.draw_text(x + width - 1, y + i, "│");
-        }
-
-        // Bottom border
-        self.draw_text(x, y + height - 1, "└");
-        self.draw_horizontal_line(x + 1, y + height - 1, width - 2, '─');
-        self.draw_text(x + width - 1, y + height - 1, "┘");
+    /// Draw pieces of text one after another, each in its own color,
+    /// returning the column after the last
+    pub fn draw_spans<S: AsRef<str>>(
+        &mut self,
+        x: u16,
+        y: u16,
+        spans: impl IntoIterator<Item = (S, Option<Color>)>,
+    ) -> u16 {
+        spans.into_iter().fold(x, |x, (text, color)| {
+            let text = text.as_ref();
+            self.draw_colored(x, y, text, color);
+            x.saturating_add(text.chars().count() as u16)
+        })
     }
 }
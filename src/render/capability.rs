@@ -0,0 +1,164 @@
+//! What the terminal can show.
+//!
+//! Not every terminal can draw colour or anything beyond ASCII: dumb
+//! terminals, legacy Windows consoles and SSH sessions without a UTF-8
+//! locale all mangle box drawing and map symbols. [`Capabilities::detect`]
+//! makes a best guess from the environment at startup, and the renderer
+//! degrades what it draws to match.
+
+use std::borrow::Cow;
+
+use super::glyphs::ascii_glyph;
+
+/// Heatmap shading per step, coolest first, for frontends without colour
+const SHADES: [(char, char); 5] = [('·', '.'), ('░', ':'), ('▒', '+'), ('▓', '*'), ('█', '#')];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Whether text can be drawn in colour
+    pub color: bool,
+    /// Whether characters beyond ASCII display correctly
+    pub unicode: bool,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            color: true,
+            unicode: true,
+        }
+    }
+}
+
+impl Capabilities {
+    /// Guess from this process's environment
+    pub fn detect() -> Self {
+        Self::from_env(cfg!(windows), |name| std::env::var(name).ok())
+    }
+
+    /// Guess from environment variables looked up with `var`.
+    ///
+    /// Colour is off for `NO_COLOR` and dumb terminals. Unicode needs a
+    /// UTF-8 locale, or on Windows a terminal known to handle it, since the
+    /// classic console's code page doesn't.
+    pub fn from_env(windows: bool, var: impl Fn(&str) -> Option<String>) -> Self {
+        let set = |name: &str| var(name).filter(|value| !value.is_empty());
+        let dumb = set("TERM").is_some_and(|term| term == "dumb");
+
+        let color = !dumb && set("NO_COLOR").is_none();
+        let unicode = !dumb
+            && if windows {
+                set("WT_SESSION").is_some() || set("TERM_PROGRAM").is_some()
+            } else {
+                // The first locale variable set is the one that applies
+                ["LC_ALL", "LC_CTYPE", "LANG"]
+                    .into_iter()
+                    .find_map(set)
+                    .is_some_and(|locale| {
+                        let locale = locale.to_ascii_lowercase();
+                        locale.contains("utf-8") || locale.contains("utf8")
+                    })
+            };
+        Self { color, unicode }
+    }
+}
+
+/// A plain ASCII stand-in for a character, keeping box drawing, bars and
+/// map glyphs recognisable. Characters without one become '?'.
+pub fn ascii_char(ch: char) -> char {
+    if ch.is_ascii() {
+        return ch;
+    }
+    if let Some(&(_, ascii)) = SHADES.iter().find(|(unicode, _)| *unicode == ch) {
+        return ascii;
+    }
+    match ch {
+        '═' | '─' => '-',
+        '║' | '│' => '|',
+        '╔' | '╗' | '╚' | '╝' | '╠' | '╣' | '┌' | '┐' | '└' | '┘' => '+',
+        '▁' | '▂' => '_',
+        '▃' | '▄' => '-',
+        '▅' | '▆' => '=',
+        '▇' => '#',
+        '▏' | '▎' | '▍' | '▌' | '▋' | '▊' | '▉' => '|',
+        '→' => '>',
+        '←' => '<',
+        '↑' => '^',
+        '↓' => 'v',
+        '×' => 'x',
+        '…' => '.',
+        '₵' => '$',
+        '‼' => '!',
+        '⊙' | '☉' => 'O',
+        '♠' => 'f',
+        _ => ascii_glyph(ch).unwrap_or('?'),
+    }
+}
+
+/// Text with every character replaced by [`ascii_char`]
+pub fn ascii_text(text: &str) -> Cow<'_, str> {
+    if text.is_ascii() {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(text.chars().map(ascii_char).collect())
+    }
+}
+
+/// A heatmap step drawn as shading rather than colour
+pub fn shade(step: u8, unicode: bool) -> char {
+    let (full, ascii) = SHADES[usize::from(step).min(SHADES.len() - 1)];
+    if unicode { full } else { ascii }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(windows: bool, vars: &[(&str, &str)]) -> Capabilities {
+        Capabilities::from_env(windows, |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn test_detection_from_environment() {
+        let full = Capabilities::default();
+        assert_eq!(detect(false, &[("LANG", "en_US.UTF-8")]), full);
+        assert_eq!(
+            detect(false, &[("LC_ALL", "C"), ("LANG", "en_US.UTF-8")]),
+            Capabilities {
+                color: true,
+                unicode: false
+            }
+        );
+        assert_eq!(
+            detect(false, &[("LANG", "C.utf8"), ("NO_COLOR", "1")]),
+            Capabilities {
+                color: false,
+                unicode: true
+            }
+        );
+        assert_eq!(
+            detect(false, &[("TERM", "dumb"), ("LANG", "en_US.UTF-8")]),
+            Capabilities {
+                color: false,
+                unicode: false
+            }
+        );
+        assert!(!detect(true, &[]).unicode);
+        assert_eq!(detect(true, &[("WT_SESSION", "abc")]), full);
+    }
+
+    #[test]
+    fn test_ascii_fallback() {
+        assert_eq!(ascii_text("plain"), Cow::Borrowed("plain"));
+        assert_eq!(ascii_text("╔══╗"), "+--+");
+        assert_eq!(ascii_text("║ ₵5 → ₵6 ║"), "| $5 > $6 |");
+        assert_eq!(ascii_text("▁▄█"), "_-#");
+        assert_eq!(ascii_text("✦ ☺ ◆ ♣"), "* @ + f");
+        assert_eq!(shade(0, false), '.');
+        assert_eq!(shade(9, true), '█');
+    }
+}
@@ -0,0 +1,137 @@
+use super::Renderer;
+use crate::Result;
+
+/// A renderer that keeps each finished frame as text instead of showing
+/// it, for tests and headless runs
+#[derive(Debug, Clone)]
+pub struct FrameCapture {
+    width: u16,
+    height: u16,
+    cells: Vec<Vec<char>>,
+    frames: Vec<String>,
+}
+
+impl FrameCapture {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            cells: blank(width, height),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Every finished frame, oldest first
+    pub fn frames(&self) -> &[String] {
+        &self.frames
+    }
+
+    pub fn last_frame(&self) -> Option<&str> {
+        self.frames.last().map(String::as_str)
+    }
+
+    /// The last finished frame, one string per row from the top, or no rows
+    /// if nothing has been presented yet
+    pub fn rows(&self) -> Vec<String> {
+        self.last_frame().map_or_else(Vec::new, |frame| {
+            frame.split('\n').map(String::from).collect()
+        })
+    }
+}
+
+fn blank(width: u16, height: u16) -> Vec<Vec<char>> {
+    vec![vec![' '; width as usize]; height as usize]
+}
+
+impl Renderer for FrameCapture {
+    fn width(&self) -> u16 {
+        self.width
+    }
+
+    fn height(&self) -> u16 {
+        self.height
+    }
+
+    fn draw_text(&mut self, x: u16, y: u16, text: &str) {
+        let Some(row) = self.cells.get_mut(y as usize) else {
+            return;
+        };
+        for (cell, ch) in row.iter_mut().skip(x as usize).zip(text.chars()) {
+            *cell = ch;
+        }
+    }
+
+    fn begin_frame(&mut self) -> Result<()> {
+        self.cells = blank(self.width, self.height);
+        Ok(())
+    }
+
+    /// Store the frame with trailing spaces trimmed from each row
+    fn end_frame(&mut self) -> Result<()> {
+        let rows: Vec<String> = self
+            .cells
+            .iter()
+            .map(|row| row.iter().collect::<String>().trim_end().to_string())
+            .collect();
+        self.frames.push(rows.join("\n"));
+        Ok(())
+    }
+
+    fn resize(&mut self, width: u16, height: u16) -> Result<()> {
+        self.width = width;
+        self.height = height;
+        self.cells = blank(width, height);
+        Ok(())
+    }
+
+    fn exit(self: Box<Self>) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::{Line, Trend};
+
+    #[test]
+    fn test_frames_are_captured_as_text() {
+        let mut capture = FrameCapture::new(6, 3);
+        capture.begin_frame().unwrap();
+        capture.draw_box(0, 0, 4, 3);
+        capture.draw_text(4, 1, "wrapped");
+        capture.end_frame().unwrap();
+
+        assert_eq!(capture.last_frame(), Some("┌──┐\n│  │wr\n└──┘"));
+
+        capture.begin_frame().unwrap();
+        capture.draw_text(0, 5, "offscreen");
+        capture.end_frame().unwrap();
+        assert_eq!(capture.frames().len(), 2);
+        assert_eq!(capture.last_frame(), Some("\n\n"));
+        assert_eq!(capture.rows(), vec![""; 3]);
+    }
+
+    #[test]
+    fn test_boxes_clip_at_screen_edges() {
+        let mut capture = FrameCapture::new(6, 3);
+        capture.begin_frame().unwrap();
+        capture.draw_box(3, 1, 10, 100);
+        capture.draw_box(u16::MAX - 1, u16::MAX - 1, 10, 10);
+        capture.draw_box(0, 0, 1, 1);
+        capture.end_frame().unwrap();
+
+        assert_eq!(capture.last_frame(), Some("\n   ┌──\n   │"));
+    }
+
+    #[test]
+    fn test_line_spans_are_drawn_in_sequence() {
+        let mut capture = FrameCapture::new(20, 1);
+        capture.begin_frame().unwrap();
+        let line = Line::plain("GDP ").value("12", Trend::Rising);
+        capture.draw_line(1, 0, &line);
+        capture.end_frame().unwrap();
+
+        assert_eq!(capture.last_frame(), Some(" GDP 12 ↑"));
+    }
+}
@@ -0,0 +1,169 @@
+use tty_interface::Color;
+
+/// One character on screen and the color it is drawn in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub color: Option<Color>,
+}
+
+impl Cell {
+    pub const BLANK: Cell = Cell {
+        ch: ' ',
+        color: None,
+    };
+}
+
+/// Changed cells next to each other on a row, sharing one color
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Run {
+    pub x: u16,
+    pub y: u16,
+    pub text: String,
+    pub color: Option<Color>,
+}
+
+/// A screen's worth of cells, drawn into and then compared against the
+/// last frame presented so only what changed is sent to the terminal
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellGrid {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+}
+
+impl CellGrid {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Cell::BLANK; width as usize * height as usize],
+        }
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Blank every cell
+    pub fn clear(&mut self) {
+        self.cells.fill(Cell::BLANK);
+    }
+
+    /// Write text from a cell rightwards, dropping whatever falls off the
+    /// right edge
+    pub fn put(&mut self, x: u16, y: u16, text: &str, color: Option<Color>) {
+        let Some(start) = self.index(x, y) else {
+            return;
+        };
+        let row_end = start - x as usize + self.width as usize;
+        for (cell, ch) in self.cells[start..row_end].iter_mut().zip(text.chars()) {
+            *cell = Cell { ch, color };
+        }
+    }
+
+    /// Runs of cells that differ from `previous`, top to bottom. Every cell
+    /// counts as changed when the grids differ in size.
+    pub fn changes_from(&self, previous: &CellGrid) -> Vec<Run> {
+        let resized = (self.width, self.height) != (previous.width, previous.height);
+        let mut runs: Vec<Run> = Vec::new();
+        for y in 0..self.height {
+            let mut open = false;
+            for x in 0..self.width {
+                let index = y as usize * self.width as usize + x as usize;
+                let cell = self.cells[index];
+                if !resized && previous.cells[index] == cell {
+                    open = false;
+                    continue;
+                }
+                match runs.last_mut() {
+                    Some(run) if open && run.color == cell.color => run.text.push(cell.ch),
+                    _ => runs.push(Run {
+                        x,
+                        y,
+                        text: cell.ch.to_string(),
+                        color: cell.color,
+                    }),
+                }
+                open = true;
+            }
+        }
+        runs
+    }
+
+    fn index(&self, x: u16, y: u16) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| y as usize * self.width as usize + x as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl CellGrid {
+        fn get(&self, x: u16, y: u16) -> Option<Cell> {
+            self.index(x, y).map(|index| self.cells[index])
+        }
+    }
+
+    #[test]
+    fn test_put_clips_at_right_edge() {
+        let mut grid = CellGrid::new(5, 2);
+        grid.put(3, 0, "abc", None);
+        assert_eq!(grid.get(4, 0).unwrap().ch, 'b');
+        assert_eq!(grid.get(0, 1), Some(Cell::BLANK));
+        grid.put(0, 2, "off screen", None);
+        assert_eq!(grid.get(0, 2), None);
+    }
+
+    #[test]
+    fn test_only_changed_cells_are_emitted() {
+        let mut previous = CellGrid::new(10, 2);
+        previous.put(0, 0, "Day 1 ok", None);
+        let mut next = previous.clone();
+        next.put(4, 0, "2", None);
+        next.put(6, 0, "  ", None);
+        next.put(0, 1, "up", Some(Color::Green));
+
+        assert_eq!(
+            next.changes_from(&previous),
+            vec![
+                Run {
+                    x: 4,
+                    y: 0,
+                    text: String::from("2"),
+                    color: None
+                },
+                Run {
+                    x: 6,
+                    y: 0,
+                    text: String::from("  "),
+                    color: None
+                },
+                Run {
+                    x: 0,
+                    y: 1,
+                    text: String::from("up"),
+                    color: Some(Color::Green)
+                },
+            ]
+        );
+        assert!(next.changes_from(&next.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_color_change_starts_a_new_run() {
+        let previous = CellGrid::new(4, 1);
+        let mut next = CellGrid::new(4, 1);
+        next.put(0, 0, "ab", None);
+        next.put(2, 0, "cd", Some(Color::Red));
+        let runs = next.changes_from(&previous);
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[1].text, "cd");
+        assert_eq!(CellGrid::new(2, 1).changes_from(&previous).len(), 1);
+    }
+}
@@ -0,0 +1,110 @@
+/// Block characters from lowest to highest, one eighth of a cell apart
+const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Partial blocks for the fractional end of a horizontal bar, in eighths
+const PARTIAL_BARS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// A one-line chart of the newest `width` values, scaled between the
+/// smallest and largest of them. A flat series draws at mid height.
+pub fn sparkline(values: &[f64], width: usize) -> String {
+    let shown = &values[values.len().saturating_sub(width)..];
+    let (min, max) = range(shown);
+    sparkline_between(values, width, min, max)
+}
+
+/// Like [`sparkline`], but on a fixed scale so that several series can be
+/// compared. Values outside the scale are clamped to it.
+pub fn sparkline_between(values: &[f64], width: usize, min: f64, max: f64) -> String {
+    let shown = &values[values.len().saturating_sub(width)..];
+    let range = max - min;
+
+    shown
+        .iter()
+        .map(|&value| {
+            if range <= f64::EPSILON {
+                return LEVELS[LEVELS.len() / 2 - 1];
+            }
+            let fraction = ((value - min) / range).clamp(0.0, 1.0);
+            let level = (fraction * (LEVELS.len() - 1) as f64).round();
+            LEVELS[level as usize]
+        })
+        .collect()
+}
+
+/// Smallest and largest of some values
+pub fn range(values: &[f64]) -> (f64, f64) {
+    values
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+            (lo.min(v), hi.max(v))
+        })
+}
+
+/// A horizontal bar `width` cells long at full scale, drawn to eighths of a
+/// cell and padded with spaces so bars line up
+pub fn bar(value: f64, max: f64, width: usize) -> String {
+    let fraction = if max > 0.0 {
+        (value / max).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let eighths = (fraction * (width * 8) as f64).round() as usize;
+    let (full, partial) = (eighths / 8, eighths % 8);
+
+    let mut bar = "█".repeat(full);
+    if full < width {
+        bar.push(PARTIAL_BARS[partial]);
+        bar.push_str(&" ".repeat(width - full - 1));
+    }
+    bar
+}
+
+/// A number shortened to a few significant characters, e.g. `1.25M`
+pub fn compact(value: f64) -> String {
+    let (scaled, suffix) = match value.abs() {
+        v if v >= 1e9 => (value / 1e9, "B"),
+        v if v >= 1e6 => (value / 1e6, "M"),
+        v if v >= 1e3 => (value / 1e3, "K"),
+        _ => (value, ""),
+    };
+    format!("{:.2}{}", scaled, suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparkline_scales_to_range() {
+        assert_eq!(sparkline(&[0.0, 1.0, 2.0, 7.0], 10), "▁▂▃█");
+        assert_eq!(sparkline(&[3.0, 3.0], 10), "▄▄");
+        assert_eq!(sparkline(&[], 10), "");
+    }
+
+    #[test]
+    fn test_sparkline_keeps_newest_values() {
+        assert_eq!(sparkline(&[100.0, 0.0, 1.0], 2), "▁█");
+    }
+
+    #[test]
+    fn test_shared_scale_sparklines() {
+        assert_eq!(sparkline_between(&[0.0, 7.0], 10, 0.0, 14.0), "▁▅");
+        assert_eq!(sparkline_between(&[-5.0, 20.0], 10, 0.0, 14.0), "▁█");
+        assert_eq!(range(&[3.0, -1.0, 2.0]), (-1.0, 3.0));
+    }
+
+    #[test]
+    fn test_bar_fills_eighths() {
+        assert_eq!(bar(1.0, 1.0, 4), "████");
+        assert_eq!(bar(0.5, 1.0, 4), "██  ");
+        assert_eq!(bar(1.0, 16.0, 2), "▏ ");
+        assert_eq!(bar(0.0, 0.0, 3), "   ");
+    }
+
+    #[test]
+    fn test_compact_abbreviates() {
+        assert_eq!(compact(12.5), "12.50");
+        assert_eq!(compact(4_200.0), "4.20K");
+        assert_eq!(compact(8_000_000_000.0), "8.00B");
+    }
+}
@@ -1,11 +1,15 @@
+use std::borrow::Cow;
 use std::time::{Duration, Instant};
-use tty_interface::Device;
+use tty_interface::{Color, Device};
 
-use super::Canvas;
+use super::capability::{self, Capabilities};
+use super::{Canvas, Line, Palette, Renderer, Tone};
 use crate::Result;
 
 pub struct RenderEngine<'a> {
     canvas: Canvas<'a>,
+    palette: Palette,
+    capabilities: Capabilities,
     frame_count: u64,
     last_fps_update: Instant,
     current_fps: f32,
@@ -16,6 +20,8 @@ impl<'a> RenderEngine<'a> {
     pub fn new(device: &'a mut dyn Device) -> Result<RenderEngine<'a>> {
         Ok(Self {
             canvas: Canvas::new(device)?,
+            palette: Palette::default(),
+            capabilities: Capabilities::default(),
             frame_count: 0,
             last_fps_update: Instant::now(),
             current_fps: 0.0,
@@ -23,19 +29,38 @@ impl<'a> RenderEngine<'a> {
         })
     }
 
-    pub fn canvas_mut(&mut self) -> &mut Canvas<'a> {
-        &mut self.canvas
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
     }
 
-    pub fn begin_frame(&mut self) -> Result<()> {
-        self.canvas.clear();
-        Ok(())
+    /// Draw only what the terminal can show: ASCII in place of anything
+    /// else, and shading in place of heatmap colours
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.capabilities = capabilities;
     }
 
-    pub fn end_frame(&mut self) -> Result<()> {
-        self.canvas.apply_staged_updates()?;
-        self.update_fps();
-        Ok(())
+    /// Text and color to draw a tone in, given what the terminal can show
+    fn styled<'t>(&self, text: &'t str, tone: Tone) -> (Cow<'t, str>, Option<Color>) {
+        let Capabilities { color, unicode } = self.capabilities;
+        if let (false, Tone::Heat(step)) = (color, tone) {
+            let shade = capability::shade(step, unicode);
+            let text = text
+                .chars()
+                .map(|ch| if ch == ' ' { ch } else { shade })
+                .collect();
+            return (Cow::Owned(text), None);
+        }
+        let text = if unicode {
+            text.into()
+        } else {
+            capability::ascii_text(text)
+        };
+        let color = if color {
+            self.palette.color(tone)
+        } else {
+            None
+        };
+        (text, color)
     }
 
     fn update_fps(&mut self) {
@@ -49,12 +74,55 @@ impl<'a> RenderEngine<'a> {
             self.last_fps_update = Instant::now();
         }
     }
+}
+
+impl Renderer for RenderEngine<'_> {
+    fn width(&self) -> u16 {
+        self.canvas.width()
+    }
+
+    fn height(&self) -> u16 {
+        self.canvas.height()
+    }
+
+    fn draw_text(&mut self, x: u16, y: u16, text: &str) {
+        self.draw_toned(x, y, text, Tone::Plain);
+    }
+
+    fn draw_toned(&mut self, x: u16, y: u16, text: &str, tone: Tone) {
+        let (text, color) = self.styled(text, tone);
+        self.canvas.draw_colored(x, y, &text, color);
+    }
+
+    fn draw_line(&mut self, x: u16, y: u16, line: &Line) {
+        let spans: Vec<(Cow<str>, Option<Color>)> = line
+            .spans()
+            .iter()
+            .map(|span| self.styled(&span.text, span.tone))
+            .collect();
+        self.canvas.draw_spans(x, y, spans);
+    }
+
+    fn begin_frame(&mut self) -> Result<()> {
+        self.canvas.clear();
+        Ok(())
+    }
+
+    fn end_frame(&mut self) -> Result<()> {
+        self.canvas.apply_staged_updates()?;
+        self.update_fps();
+        Ok(())
+    }
+
+    fn resize(&mut self, width: u16, height: u16) -> Result<()> {
+        self.canvas.resize(width, height)
+    }
 
-    pub fn fps(&self) -> f32 {
+    fn fps(&self) -> f32 {
         self.current_fps
     }
 
-    pub fn exit(self) -> Result<()> {
+    fn exit(self: Box<Self>) -> Result<()> {
         self.canvas.exit()
     }
 }
@@ -0,0 +1,277 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::economy::{BuildingKind, GoodCategory};
+use crate::game::furniture::FurnitureKind;
+use crate::game::stars::{Brightness, StarClass};
+use crate::game::terrain::Biome;
+use crate::zoom::ZoomLevel;
+
+/// Which built-in glyphs to draw with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GlyphSet {
+    #[default]
+    Unicode,
+    /// Plain ASCII, for terminals and fonts without the symbols
+    Ascii,
+}
+
+/// Every glyph key with its Unicode and ASCII forms
+const GLYPHS: [(&str, char, char); 42] = [
+    ("galaxy", 'G', 'G'),
+    ("system", '*', '*'),
+    ("bright_star", '✶', '*'),
+    ("sunlike_star", '☼', 'o'),
+    ("dim_star", '•', '+'),
+    ("planet", 'o', 'o'),
+    ("region", '^', '^'),
+    ("area", '#', '#'),
+    ("room", 'r', 'r'),
+    ("building", 'B', 'B'),
+    ("farm", 'F', 'F'),
+    ("mine", 'M', 'M'),
+    ("lumber_camp", 'L', 'L'),
+    ("fishery", 'H', 'H'),
+    ("factory", 'I', 'I'),
+    ("workshop", 'W', 'W'),
+    ("foundations", '▁', '_'),
+    ("frame", '▄', '='),
+    ("roof", '▇', '&'),
+    ("stall", '⌂', 'S'),
+    ("bed", '≡', '='),
+    ("workbench", 'π', 'T'),
+    ("plains", '„', '"'),
+    ("mountains", '▲', '^'),
+    ("forest", '♣', 'f'),
+    ("desert", '∴', ':'),
+    ("coast", '≈', '~'),
+    ("ocean", '∼', '-'),
+    ("heat", '█', '#'),
+    ("raw", '◆', '+'),
+    ("food", '♥', '%'),
+    ("material", '■', '='),
+    ("tool", '†', '/'),
+    ("luxury", '♦', '$'),
+    ("empty", '·', '.'),
+    ("character", '☺', '@'),
+    ("you", '◉', '@'),
+    ("unexplored", '░', ' '),
+    ("festival", '✦', '*'),
+    ("border", '┊', '|'),
+    ("ship", '»', '>'),
+    ("unknown", '?', '?'),
+];
+
+/// The ASCII form of a built-in Unicode glyph
+pub(super) fn ascii_glyph(ch: char) -> Option<char> {
+    GLYPHS
+        .iter()
+        .find(|(_, unicode, _)| *unicode == ch)
+        .map(|(_, _, ascii)| *ascii)
+}
+
+/// Display glyphs for entities, terrain and goods.
+///
+/// Everything that draws a map symbol looks it up here by key, so the
+/// glyph set can be switched and mods can replace individual glyphs.
+#[derive(Debug, Clone, Default)]
+pub struct Glyphs {
+    set: GlyphSet,
+    overrides: HashMap<String, char>,
+}
+
+impl Glyphs {
+    pub fn new(set: GlyphSet) -> Self {
+        Self {
+            set,
+            overrides: HashMap::new(),
+        }
+    }
+
+    pub fn glyph_set(&self) -> GlyphSet {
+        self.set
+    }
+
+    /// Switch glyph sets, keeping any overrides
+    pub fn set_glyph_set(&mut self, set: GlyphSet) {
+        self.set = set;
+    }
+
+    /// Whether `key` names a glyph
+    pub fn is_key(key: &str) -> bool {
+        GLYPHS.iter().any(|(name, _, _)| *name == key)
+    }
+
+    /// Replace one glyph, whichever set is in use. Returns false for an
+    /// unknown key.
+    pub fn override_glyph(&mut self, key: &str, glyph: char) -> bool {
+        if !Self::is_key(key) {
+            return false;
+        }
+        self.overrides.insert(key.to_string(), glyph);
+        true
+    }
+
+    /// The glyph for a key, or the unknown glyph if there is none
+    pub fn get(&self, key: &str) -> char {
+        if let Some(glyph) = self.overrides.get(key) {
+            return *glyph;
+        }
+        match GLYPHS.iter().find(|(name, _, _)| *name == key) {
+            Some((_, unicode, ascii)) => match self.set {
+                GlyphSet::Unicode => *unicode,
+                GlyphSet::Ascii => *ascii,
+            },
+            None => self.get("unknown"),
+        }
+    }
+
+    pub fn level(&self, level: ZoomLevel) -> char {
+        self.get(match level {
+            ZoomLevel::Galaxy => "galaxy",
+            ZoomLevel::SolarSystem => "system",
+            ZoomLevel::Planet => "planet",
+            ZoomLevel::Region => "region",
+            ZoomLevel::LocalArea => "area",
+            ZoomLevel::Room => "room",
+        })
+    }
+
+    pub fn building(&self, kind: BuildingKind) -> char {
+        self.get(match kind {
+            BuildingKind::Farm => "farm",
+            BuildingKind::Mine => "mine",
+            BuildingKind::LumberCamp => "lumber_camp",
+            BuildingKind::Fishery => "fishery",
+            BuildingKind::Factory => "factory",
+            BuildingKind::Workshop => "workshop",
+        })
+    }
+
+    /// A system's star on the galaxy map, by how bright its class burns
+    pub fn star(&self, class: StarClass) -> char {
+        self.get(match class.brightness() {
+            Brightness::Bright => "bright_star",
+            Brightness::Sunlike => "sunlike_star",
+            Brightness::Dim => "dim_star",
+        })
+    }
+
+    /// A piece of furniture on a room's map
+    pub fn furniture(&self, kind: FurnitureKind) -> char {
+        self.get(match kind {
+            FurnitureKind::MarketStall => "stall",
+            FurnitureKind::Bed => "bed",
+            FurnitureKind::Workbench => "workbench",
+        })
+    }
+
+    /// Glyph for a region's terrain, falling back to the region glyph for
+    /// terrain without one
+    pub fn terrain(&self, terrain: &str) -> char {
+        let key = terrain.to_lowercase();
+        if Self::is_key(&key) {
+            self.get(&key)
+        } else {
+            self.level(ZoomLevel::Region)
+        }
+    }
+
+    /// Glyph for one tile of a planet's surface
+    pub fn biome(&self, biome: Biome) -> char {
+        self.get(&biome.name().to_lowercase())
+    }
+
+    pub fn category(&self, category: GoodCategory) -> char {
+        self.get(match category {
+            GoodCategory::Raw => "raw",
+            GoodCategory::Food => "food",
+            GoodCategory::Material => "material",
+            GoodCategory::Tool => "tool",
+            GoodCategory::Luxury => "luxury",
+        })
+    }
+
+    /// An empty spot on a map
+    pub fn empty(&self) -> char {
+        self.get("empty")
+    }
+
+    pub fn character(&self) -> char {
+        self.get("character")
+    }
+
+    /// A region's tile on a heatmap overlay
+    pub fn heat(&self) -> char {
+        self.get("heat")
+    }
+
+    /// The player's spot on a map
+    pub fn you(&self) -> char {
+        self.get("you")
+    }
+
+    /// Decoration around a festival's name
+    pub fn festival(&self) -> char {
+        self.get("festival")
+    }
+
+    /// A ship under way
+    pub fn ship(&self) -> char {
+        self.get("ship")
+    }
+
+    /// The frontier between land held by different polities
+    pub fn border(&self) -> char {
+        self.get("border")
+    }
+
+    /// A spot the player has not been near
+    pub fn unexplored(&self) -> char {
+        self.get("unexplored")
+    }
+
+    /// A building under construction, rising with the share of the work
+    /// done
+    pub fn site(&self, progress: f64) -> char {
+        self.get(match progress {
+            p if p < 1.0 / 3.0 => "foundations",
+            p if p < 2.0 / 3.0 => "frame",
+            _ => "roof",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sets_differ_only_where_needed() {
+        let unicode = Glyphs::new(GlyphSet::Unicode);
+        let ascii = Glyphs::new(GlyphSet::Ascii);
+        assert_eq!(unicode.terrain("Forest"), '♣');
+        assert_eq!(ascii.terrain("Forest"), 'f');
+        assert_eq!(
+            ascii.level(ZoomLevel::Planet),
+            unicode.level(ZoomLevel::Planet)
+        );
+        assert!(GLYPHS.iter().all(|(_, _, ascii)| ascii.is_ascii()));
+        assert_eq!(unicode.site(0.0), '▁');
+        assert_eq!(ascii.site(0.5), '=');
+        assert_eq!(unicode.site(0.9), '▇');
+        assert_eq!(ascii.star(StarClass::O), '*');
+        assert_ne!(unicode.star(StarClass::G), unicode.star(StarClass::M));
+    }
+
+    #[test]
+    fn test_overrides_replace_either_set() {
+        let mut glyphs = Glyphs::new(GlyphSet::Ascii);
+        assert!(glyphs.override_glyph("desert", 'd'));
+        assert!(!glyphs.override_glyph("swamp", 's'));
+        assert_eq!(glyphs.terrain("Desert"), 'd');
+        assert_eq!(glyphs.terrain("Swamp"), '^');
+        assert_eq!(glyphs.get("swamp"), '?');
+    }
+}
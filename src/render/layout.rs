@@ -0,0 +1,311 @@
+use super::minimap::{MINIMAP_COLUMNS, MINIMAP_ROWS};
+
+/// Smallest terminal the game screen can be drawn in
+pub const MIN_WIDTH: u16 = 44;
+pub const MIN_HEIGHT: u16 = 24;
+
+/// Narrowest terminals given the full and compact presets
+const FULL_WIDTH: u16 = 120;
+const COMPACT_WIDTH: u16 = 80;
+
+/// Width of the inspector sidebar, including its border
+const SIDEBAR_WIDTH: u16 = 30;
+
+/// Rows taken by the tallest fixed-size overlay, the help box
+const OVERLAY_ROWS: u16 = 19;
+
+/// Width of the zoom view box, which holds fixed-size art
+const VIEW_WIDTH: u16 = 38;
+const VIEW_HEIGHT: u16 = 10;
+
+/// Narrowest event panel worth drawing beside the zoom view
+const MIN_EVENT_PANEL_WIDTH: u16 = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Rect {
+    pub fn new(x: u16, y: u16, width: u16, height: u16) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+/// How much of the screen furniture fits, chosen from the terminal width
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    /// Five info lines with the event log beside the zoom view
+    Full,
+    /// Three info lines with the event log beside the zoom view
+    Compact,
+    /// Three info lines, an abbreviated status line and the event log
+    /// stacked under the zoom view
+    Minimal,
+}
+
+impl Preset {
+    pub fn for_width(width: u16) -> Self {
+        match width {
+            w if w >= FULL_WIDTH => Preset::Full,
+            w if w >= COMPACT_WIDTH => Preset::Compact,
+            _ => Preset::Minimal,
+        }
+    }
+
+    /// Rows of text at the top of the content box
+    pub fn info_rows(self) -> u16 {
+        match self {
+            Preset::Full => 5,
+            Preset::Compact | Preset::Minimal => 3,
+        }
+    }
+}
+
+/// Positions of every panel on the game screen for one terminal size.
+///
+/// Recomputed each frame so the screen reflows when the terminal is resized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    pub preset: Preset,
+    /// Status line box across the top
+    pub header: Rect,
+    /// Single line under the header showing where the player is
+    pub breadcrumb: Rect,
+    /// Main box holding the info lines, zoom view and event log
+    pub content: Rect,
+    /// Text lines at the top of the content box
+    pub info: Rect,
+    /// Zoom view art or the editor map
+    pub view: Rect,
+    /// Recent events beside or under the view, when there is room for them
+    pub events: Option<Rect>,
+    /// Inspector and watchlist down the right edge, once opened with
+    /// [`Layout::with_sidebar`]
+    pub sidebar: Option<Rect>,
+    /// Mini-map box in the bottom-right corner, once opened with
+    /// [`Layout::with_minimap`]
+    pub minimap: Option<Rect>,
+    /// Overlays such as help, forms and prompts
+    pub overlay: Rect,
+    /// Controls box across the bottom
+    pub footer: Rect,
+}
+
+impl Layout {
+    /// Lay out a terminal of the given size, or None if it is too small
+    pub fn compute(width: u16, height: u16) -> Option<Self> {
+        if width < MIN_WIDTH || height < MIN_HEIGHT {
+            return None;
+        }
+
+        let header = Rect::new(0, 0, width, 3);
+        let footer = Rect::new(0, height - 2, width, 2);
+        let content = Rect::new(0, 4, width, footer.y - 4);
+        let inner_width = width - 4;
+
+        let breadcrumb = Rect::new(2, header.height, inner_width, 1);
+        let preset = Preset::for_width(width);
+        let info = Rect::new(2, content.y + 2, inner_width, preset.info_rows());
+        let view = Rect::new(2, info.y + info.height, VIEW_WIDTH, VIEW_HEIGHT);
+        let content_bottom = content.y + content.height - 1;
+
+        // Start overlays higher on short screens so the help box still fits
+        let overlay_y = info
+            .y
+            .min(content_bottom.saturating_sub(OVERLAY_ROWS).max(content.y));
+        let overlay = Rect::new(2, overlay_y, inner_width, content_bottom - overlay_y);
+
+        let events = match preset {
+            Preset::Minimal => {
+                let events_y = view.y + view.height;
+                (content_bottom >= events_y + 2)
+                    .then(|| Rect::new(2, events_y, inner_width, content_bottom - events_y))
+            }
+            Preset::Full | Preset::Compact => {
+                let events_x = view.x + VIEW_WIDTH + 4;
+                (width >= events_x + MIN_EVENT_PANEL_WIDTH + 2).then(|| {
+                    Rect::new(
+                        events_x,
+                        view.y,
+                        width - events_x - 2,
+                        content_bottom - view.y,
+                    )
+                })
+            }
+        };
+
+        Some(Self {
+            preset,
+            header,
+            breadcrumb,
+            content,
+            info,
+            view,
+            events,
+            sidebar: None,
+            minimap: None,
+            overlay,
+            footer,
+        })
+    }
+}
+
+impl Layout {
+    /// Make room for the sidebar along the right edge of the content box,
+    /// narrowing the info lines and event log beside it. The layout is left
+    /// as is when the zoom view would not fit beside the sidebar.
+    pub fn with_sidebar(mut self) -> Self {
+        let right = self.content.x + self.content.width - 2;
+        let sidebar_x = right.saturating_sub(SIDEBAR_WIDTH);
+        if sidebar_x < self.view.x + VIEW_WIDTH + 2 {
+            return self;
+        }
+        let content_bottom = self.content.y + self.content.height - 1;
+        self.sidebar = Some(Rect::new(
+            sidebar_x,
+            self.info.y,
+            SIDEBAR_WIDTH,
+            content_bottom - self.info.y,
+        ));
+
+        let left_width = sidebar_x - 2 - self.info.x;
+        self.info.width = left_width;
+        self.events = self.events.and_then(|events| {
+            let width = (sidebar_x - 2).saturating_sub(events.x);
+            (width >= MIN_EVENT_PANEL_WIDTH).then_some(Rect { width, ..events })
+        });
+        self
+    }
+}
+
+impl Layout {
+    /// Place the mini-map in the bottom-right corner of the content box,
+    /// left of the sidebar if it is open, cutting the event log short
+    /// above it. The layout is left as is when the mini-map would cover
+    /// the zoom view.
+    pub fn with_minimap(mut self) -> Self {
+        let width = MINIMAP_COLUMNS as u16 + 2;
+        let height = MINIMAP_ROWS as u16 + 2;
+        let right = match self.sidebar {
+            Some(sidebar) => sidebar.x.saturating_sub(1),
+            None => self.content.x + self.content.width - 2,
+        };
+        let bottom = self.content.y + self.content.height - 1;
+        let (Some(x), Some(y)) = (right.checked_sub(width), bottom.checked_sub(height)) else {
+            return self;
+        };
+        let beside_view = x >= self.view.x + self.view.width + 2;
+        let below_view = y >= self.view.y + self.view.height;
+        if !(beside_view || below_view) || y < self.info.y + self.info.height {
+            return self;
+        }
+        self.minimap = Some(Rect::new(x, y, width, height));
+
+        self.events = self.events.and_then(|events| {
+            if events.x + events.width <= x || events.y + events.height <= y {
+                return Some(events);
+            }
+            let height = y.saturating_sub(events.y);
+            (height >= 2).then_some(Rect { height, ..events })
+        });
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_terminal() {
+        let layout = Layout::compute(120, 40).unwrap();
+        assert_eq!(layout.breadcrumb, Rect::new(2, 3, 116, 1));
+        assert_eq!(layout.footer, Rect::new(0, 38, 120, 2));
+        assert_eq!(layout.content, Rect::new(0, 4, 120, 34));
+        assert_eq!(layout.view, Rect::new(2, 11, 38, 10));
+        assert_eq!(layout.events, Some(Rect::new(44, 11, 74, 26)));
+    }
+
+    #[test]
+    fn test_standard_terminal_overlay() {
+        let layout = Layout::compute(120, 40).unwrap();
+        assert_eq!(layout.preset, Preset::Full);
+        assert_eq!(layout.overlay, Rect::new(2, 6, 116, 31));
+    }
+
+    #[test]
+    fn test_compact_terminal_keeps_side_panel() {
+        let layout = Layout::compute(80, 24).unwrap();
+        assert_eq!(layout.preset, Preset::Compact);
+        assert_eq!(layout.info.height, 3);
+        assert_eq!(layout.view, Rect::new(2, 9, 38, 10));
+        assert_eq!(layout.events, Some(Rect::new(44, 9, 34, 12)));
+        // The help box starts at the top of the content box to fit
+        assert_eq!(layout.overlay.y, 4);
+    }
+
+    #[test]
+    fn test_narrow_terminal_stacks_event_panel() {
+        let layout = Layout::compute(50, 30).unwrap();
+        assert_eq!(layout.preset, Preset::Minimal);
+        assert_eq!(layout.info.width, 46);
+        assert_eq!(layout.events, Some(Rect::new(2, 19, 46, 8)));
+
+        let short = Layout::compute(50, MIN_HEIGHT).unwrap();
+        assert_eq!(short.events, Some(Rect::new(2, 19, 46, 2)));
+    }
+
+    #[test]
+    fn test_sidebar_narrows_side_panels() {
+        let layout = Layout::compute(120, 40).unwrap().with_sidebar();
+        assert_eq!(layout.sidebar, Some(Rect::new(88, 6, 30, 31)));
+        assert_eq!(layout.info.width, 84);
+        assert_eq!(layout.view, Rect::new(2, 11, 38, 10));
+        assert_eq!(layout.events, Some(Rect::new(44, 11, 42, 26)));
+
+        let cramped = Layout::compute(80, 24).unwrap().with_sidebar();
+        assert_eq!(cramped.sidebar, Some(Rect::new(48, 6, 30, 15)));
+        assert_eq!(cramped.events, None);
+
+        let narrow = Layout::compute(60, 30).unwrap().with_sidebar();
+        assert_eq!(narrow.sidebar, None);
+    }
+
+    #[test]
+    fn test_minimap_takes_bottom_right_corner() {
+        let layout = Layout::compute(120, 40).unwrap().with_minimap();
+        assert_eq!(layout.minimap, Some(Rect::new(103, 30, 15, 7)));
+        assert_eq!(layout.events, Some(Rect::new(44, 11, 74, 19)));
+
+        let beside_sidebar = Layout::compute(120, 40)
+            .unwrap()
+            .with_sidebar()
+            .with_minimap();
+        assert_eq!(beside_sidebar.minimap, Some(Rect::new(72, 30, 15, 7)));
+
+        let cramped = Layout::compute(80, 24).unwrap().with_minimap();
+        assert_eq!(cramped.minimap, Some(Rect::new(63, 14, 15, 7)));
+        assert_eq!(cramped.events, Some(Rect::new(44, 9, 34, 5)));
+
+        let narrow = Layout::compute(MIN_WIDTH, MIN_HEIGHT)
+            .unwrap()
+            .with_minimap();
+        assert_eq!(narrow.minimap, None);
+    }
+
+    #[test]
+    fn test_too_small() {
+        assert!(Layout::compute(MIN_WIDTH - 1, 40).is_none());
+        assert!(Layout::compute(120, MIN_HEIGHT - 1).is_none());
+        assert!(Layout::compute(80, 24).is_some());
+    }
+}
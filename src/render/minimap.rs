@@ -0,0 +1,62 @@
+use super::Glyphs;
+
+/// Map cells shown across and down the mini-map
+pub const MINIMAP_COLUMNS: usize = 13;
+pub const MINIMAP_ROWS: usize = 5;
+
+/// What the mini-map shows at one spot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    /// Not yet seen; drawn the same whether or not anything is there
+    Unexplored,
+    Empty,
+    /// Something seen there, drawn with its glyph
+    Occupied(char),
+}
+
+/// Rows of a mini-map centered on the player, one character per spot
+pub fn minimap_rows(
+    center: (i32, i32),
+    glyphs: &Glyphs,
+    cell: impl Fn((i32, i32)) -> Cell,
+) -> Vec<String> {
+    let left = center.0 - MINIMAP_COLUMNS as i32 / 2;
+    let top = center.1 - MINIMAP_ROWS as i32 / 2;
+    (top..top + MINIMAP_ROWS as i32)
+        .map(|y| {
+            (left..left + MINIMAP_COLUMNS as i32)
+                .map(|x| {
+                    if (x, y) == center {
+                        return glyphs.you();
+                    }
+                    match cell((x, y)) {
+                        Cell::Unexplored => glyphs.unexplored(),
+                        Cell::Empty => glyphs.empty(),
+                        Cell::Occupied(glyph) => glyph,
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::GlyphSet;
+
+    #[test]
+    fn test_player_centered_among_explored_cells() {
+        let glyphs = Glyphs::new(GlyphSet::Ascii);
+        let rows = minimap_rows((10, 10), &glyphs, |(x, y)| match (x - 10, y - 10) {
+            (1, 0) => Cell::Occupied('*'),
+            (dx, dy) if dx.abs() <= 1 && dy.abs() <= 1 => Cell::Empty,
+            _ => Cell::Unexplored,
+        });
+
+        assert_eq!(rows.len(), MINIMAP_ROWS);
+        assert_eq!(rows[2], "     .@*     ");
+        assert_eq!(rows[1], "     ...     ");
+        assert_eq!(rows[0].trim(), "");
+    }
+}
@@ -1,5 +1,26 @@
 mod canvas;
+pub mod capability;
+mod capture;
+mod cells;
+pub mod charts;
 mod engine;
+mod glyphs;
+mod layout;
+pub mod minimap;
+pub mod notifications;
+mod palette;
+mod renderer;
+#[cfg(test)]
+pub(crate) mod snapshot;
+pub mod value;
 
+pub use crate::spans;
 pub use canvas::Canvas;
+pub use capability::Capabilities;
+pub use capture::FrameCapture;
 pub use engine::RenderEngine;
+pub use glyphs::{GlyphSet, Glyphs};
+pub use layout::{Layout, MIN_HEIGHT, MIN_WIDTH, Preset, Rect};
+pub use palette::Palette;
+pub use renderer::Renderer;
+pub use value::{HEAT_LEVELS, Line, Span, Tone, Trend};
@@ -0,0 +1,135 @@
+//! Short-lived messages shown across the top of the screen.
+//!
+//! Anything that wants the player's attention at once, such as a famine
+//! or a failed autosave, pushes a [`Notice`] here. It stays on screen for
+//! a few seconds of real time, styled by its severity, with the newest on
+//! top. Every notice also goes into the event log, which keeps the
+//! history once the toast has faded.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::game::{Notice, Severity};
+
+use super::{Line, Tone};
+
+/// How long a toast stays on screen
+pub const TOAST_LIFETIME: Duration = Duration::from_secs(4);
+
+/// Most toasts shown at once; older ones give way to newer
+pub const MAX_TOASTS: usize = 3;
+
+impl Severity {
+    pub fn tone(self) -> Tone {
+        match self {
+            Severity::Info => Tone::Plain,
+            Severity::Warning => Tone::Warning,
+            Severity::Critical => Tone::Critical,
+        }
+    }
+
+    fn marker(self) -> char {
+        match self {
+            Severity::Info => 'i',
+            Severity::Warning => '!',
+            Severity::Critical => '‼',
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Toast {
+    notice: Notice,
+    raised: Instant,
+}
+
+/// Toasts on screen, newest last
+#[derive(Debug, Clone, Default)]
+pub struct Notifications {
+    toasts: VecDeque<Toast>,
+}
+
+impl Notifications {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, notice: Notice, now: Instant) {
+        if self.toasts.len() == MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+        self.toasts.push_back(Toast {
+            notice,
+            raised: now,
+        });
+    }
+
+    /// Toasts still showing at `now`, newest first, each as a line in its
+    /// severity's tone. Expired ones are dropped.
+    pub fn lines(&mut self, now: Instant) -> Vec<Line> {
+        self.toasts
+            .retain(|toast| now.saturating_duration_since(toast.raised) < TOAST_LIFETIME);
+        self.toasts
+            .iter()
+            .rev()
+            .map(|toast| {
+                let severity = toast.notice.severity;
+                Line::default().push(
+                    format!(" {} {} ", severity.marker(), toast.notice.message),
+                    severity.tone(),
+                )
+            })
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.toasts.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notice(severity: Severity, message: &str) -> Notice {
+        Notice {
+            severity,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_toasts_fade_after_their_lifetime() {
+        let start = Instant::now();
+        let mut notifications = Notifications::new();
+        notifications.push(notice(Severity::Critical, "Famine in Millbrook"), start);
+        notifications.push(
+            notice(Severity::Info, "Autosaved"),
+            start + TOAST_LIFETIME / 2,
+        );
+
+        let lines = notifications.lines(start + TOAST_LIFETIME / 2);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].spans()[0].text, " i Autosaved ");
+        assert_eq!(lines[1].spans()[0].tone, Tone::Critical);
+
+        assert_eq!(notifications.lines(start + TOAST_LIFETIME).len(), 1);
+        assert!(notifications.lines(start + TOAST_LIFETIME * 2).is_empty());
+        assert!(notifications.is_empty());
+    }
+
+    #[test]
+    fn test_only_the_newest_are_kept() {
+        let now = Instant::now();
+        let mut notifications = Notifications::new();
+        for i in 0..MAX_TOASTS + 2 {
+            notifications.push(notice(Severity::Warning, &format!("event {}", i)), now);
+        }
+        let lines = notifications.lines(now);
+        assert_eq!(lines.len(), MAX_TOASTS);
+        assert_eq!(
+            lines[0].spans()[0].text,
+            format!(" ! event {} ", MAX_TOASTS + 1)
+        );
+    }
+}
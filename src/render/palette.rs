@@ -0,0 +1,85 @@
+use tty_interface::Color;
+
+use super::{HEAT_LEVELS, Tone};
+
+/// Terminal colors for each [`Tone`]
+#[derive(Debug, Clone, Copy)]
+pub struct Palette {
+    pub rising: Color,
+    pub falling: Color,
+    pub steady: Color,
+    pub festive: Color,
+    pub warning: Color,
+    pub critical: Color,
+    pub water: Color,
+    pub shallows: Color,
+    pub grassland: Color,
+    pub woodland: Color,
+    pub arid: Color,
+    pub rocky: Color,
+    pub border: Color,
+    pub selected: Color,
+    pub hotkey: Color,
+    pub paused: Color,
+    pub healthy: Color,
+    /// Heatmap steps, coolest first
+    pub heat: [Color; HEAT_LEVELS as usize],
+}
+
+impl Palette {
+    /// Colour for a tone, or None to draw in the terminal's default
+    pub fn color(&self, tone: Tone) -> Option<Color> {
+        match tone {
+            Tone::Plain => None,
+            Tone::Rising => Some(self.rising),
+            Tone::Falling => Some(self.falling),
+            Tone::Steady => Some(self.steady),
+            Tone::Festive => Some(self.festive),
+            Tone::Warning => Some(self.warning),
+            Tone::Critical => Some(self.critical),
+            Tone::Water => Some(self.water),
+            Tone::Shallows => Some(self.shallows),
+            Tone::Grassland => Some(self.grassland),
+            Tone::Woodland => Some(self.woodland),
+            Tone::Arid => Some(self.arid),
+            Tone::Rocky => Some(self.rocky),
+            Tone::Border => Some(self.border),
+            Tone::Selected => Some(self.selected),
+            Tone::Hotkey => Some(self.hotkey),
+            Tone::Paused => Some(self.paused),
+            Tone::Healthy => Some(self.healthy),
+            Tone::Heat(step) => Some(self.heat[usize::from(step.min(HEAT_LEVELS - 1))]),
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            rising: Color::Green,
+            falling: Color::Red,
+            steady: Color::DarkGrey,
+            festive: Color::Yellow,
+            warning: Color::DarkYellow,
+            critical: Color::Red,
+            water: Color::Blue,
+            shallows: Color::Cyan,
+            grassland: Color::Green,
+            woodland: Color::DarkGreen,
+            arid: Color::DarkYellow,
+            rocky: Color::Grey,
+            border: Color::Magenta,
+            selected: Color::Yellow,
+            hotkey: Color::Cyan,
+            paused: Color::Red,
+            healthy: Color::Green,
+            heat: [
+                Color::DarkBlue,
+                Color::Blue,
+                Color::Green,
+                Color::Yellow,
+                Color::Red,
+            ],
+        }
+    }
+}
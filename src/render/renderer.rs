@@ -0,0 +1,135 @@
+use super::{Line, Tone};
+use crate::Result;
+
+/// A frontend the game draws its frames to.
+///
+/// Implementors provide text placement and the frame lifecycle; lines and
+/// boxes are built from text so every frontend draws them the same way.
+pub trait Renderer {
+    fn width(&self) -> u16;
+
+    fn height(&self) -> u16;
+
+    /// Draw text, clipped at the right edge so it never wraps
+    fn draw_text(&mut self, x: u16, y: u16, text: &str);
+
+    /// Start a new frame from a blank screen
+    fn begin_frame(&mut self) -> Result<()>;
+
+    /// Present everything drawn since [`Renderer::begin_frame`]
+    fn end_frame(&mut self) -> Result<()>;
+
+    /// Adopt a new screen size, blanking everything drawn at the old size
+    fn resize(&mut self, width: u16, height: u16) -> Result<()>;
+
+    /// Frames presented per second, where the frontend measures it
+    fn fps(&self) -> f32 {
+        0.0
+    }
+
+    /// Restore whatever the frontend took over
+    fn exit(self: Box<Self>) -> Result<()>;
+
+    /// Draw text in a tone's color. Frontends without color ignore the
+    /// tone.
+    fn draw_toned(&mut self, x: u16, y: u16, text: &str, _tone: Tone) {
+        self.draw_text(x, y, text);
+    }
+
+    /// Draw each span of a line in turn
+    fn draw_line(&mut self, x: u16, y: u16, line: &Line) {
+        let mut x = x;
+        for span in line.spans() {
+            self.draw_toned(x, y, &span.text, span.tone);
+            x = x.saturating_add(span.text.chars().count() as u16);
+        }
+    }
+
+    /// Repeat a character rightwards, stopping at the right edge
+    fn draw_horizontal_line(&mut self, x: u16, y: u16, length: u16, ch: char) {
+        let length = length.min(self.width().saturating_sub(x));
+        let line = ch.to_string().repeat(length as usize);
+        self.draw_text(x, y, &line);
+    }
+
+    /// Outline a rectangle, clipped to the screen. Boxes narrower or
+    /// shorter than their two borders are not drawn.
+    fn draw_box(&mut self, x: u16, y: u16, width: u16, height: u16) {
+        let width = width.min(u16::MAX - x);
+        let height = height.min(u16::MAX - y);
+        if width < 2 || height < 2 {
+            return;
+        }
+        let (right, bottom) = (x + width - 1, y + height - 1);
+
+        // Top border
+        self.draw_text(x, y, "┌");
+        self.draw_horizontal_line(x + 1, y, width - 2, '─');
+        self.draw_text(right, y, "┐");
+
+        // Sides, only as far down as the screen goes
+        for row in y + 1..bottom.min(self.height()) {
+            self.draw_text(x, row, "│");
+            self.draw_text(right, row, "│");
+        }
+
+        // Bottom border
+        self.draw_text(x, bottom, "└");
+        self.draw_horizontal_line(x + 1, bottom, width - 2, '─');
+        self.draw_text(right, bottom, "┘");
+    }
+}
+
+/// Draw through a borrowed renderer, so whoever lent it can read what was
+/// drawn once the borrower is done, as tests do with a
+/// [`FrameCapture`](super::FrameCapture). Exiting leaves the lender to
+/// restore the frontend.
+impl<R: Renderer + ?Sized> Renderer for &mut R {
+    fn width(&self) -> u16 {
+        (**self).width()
+    }
+
+    fn height(&self) -> u16 {
+        (**self).height()
+    }
+
+    fn draw_text(&mut self, x: u16, y: u16, text: &str) {
+        (**self).draw_text(x, y, text)
+    }
+
+    fn begin_frame(&mut self) -> Result<()> {
+        (**self).begin_frame()
+    }
+
+    fn end_frame(&mut self) -> Result<()> {
+        (**self).end_frame()
+    }
+
+    fn resize(&mut self, width: u16, height: u16) -> Result<()> {
+        (**self).resize(width, height)
+    }
+
+    fn fps(&self) -> f32 {
+        (**self).fps()
+    }
+
+    fn exit(self: Box<Self>) -> Result<()> {
+        Ok(())
+    }
+
+    fn draw_toned(&mut self, x: u16, y: u16, text: &str, tone: Tone) {
+        (**self).draw_toned(x, y, text, tone)
+    }
+
+    fn draw_line(&mut self, x: u16, y: u16, line: &Line) {
+        (**self).draw_line(x, y, line)
+    }
+
+    fn draw_horizontal_line(&mut self, x: u16, y: u16, length: u16, ch: char) {
+        (**self).draw_horizontal_line(x, y, length, ch)
+    }
+
+    fn draw_box(&mut self, x: u16, y: u16, width: u16, height: u16) {
+        (**self).draw_box(x, y, width, height)
+    }
+}
@@ -0,0 +1,152 @@
+//! Checking captured frames against snapshots kept in the repository.
+//!
+//! A test draws to a [`FrameCapture`](super::FrameCapture) and hands its
+//! rows to [`assert_snapshot`], which compares them with
+//! `tests/snapshots/<name>.txt`. A snapshot that doesn't exist yet is
+//! written from the frame, and running the tests with `UPDATE_SNAPSHOTS=1`
+//! rewrites every snapshot a test touches, so a deliberate layout change is
+//! one run and a review of the diff. [`box_faults`] finds box corners left
+//! dangling by something drawn over or beside them, which catch overlaps and
+//! off-by-one borders a snapshot would only show.
+
+use std::fs;
+use std::path::PathBuf;
+
+/// Directory the snapshots are kept in, from the crate root
+const SNAPSHOT_DIR: &str = "tests/snapshots";
+
+/// Set to rewrite snapshots from the frames drawn instead of checking them
+const UPDATE_VAR: &str = "UPDATE_SNAPSHOTS";
+
+fn snapshot_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join(SNAPSHOT_DIR)
+        .join(format!("{}.txt", name))
+}
+
+/// Check a frame's rows against the snapshot called `name`, writing the
+/// snapshot instead if there is none yet or `UPDATE_SNAPSHOTS` is set
+pub fn assert_snapshot(name: &str, rows: &[String]) {
+    let path = snapshot_path(name);
+    let frame = rows.join("\n") + "\n";
+    let expected = fs::read_to_string(&path).ok();
+    if expected.is_none() || std::env::var_os(UPDATE_VAR).is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, &frame).unwrap();
+        return;
+    }
+
+    let expected = expected.unwrap();
+    if expected == frame {
+        return;
+    }
+    let (old, new): (Vec<&str>, Vec<&str>) = (expected.lines().collect(), frame.lines().collect());
+    let mut diff = Vec::new();
+    for row in 0..old.len().max(new.len()) {
+        let (was, now) = (old.get(row), new.get(row));
+        if was != now {
+            diff.push(format!("row {:>3} was |{}|", row, was.unwrap_or(&"")));
+            diff.push(format!("        now |{}|", now.unwrap_or(&"")));
+        }
+    }
+    panic!(
+        "frame differs from snapshot {} (rerun with {}=1 to accept it):\n{}",
+        path.display(),
+        UPDATE_VAR,
+        diff.join("\n")
+    );
+}
+
+/// Which ways a box-drawing character has lines leaving it: up, right,
+/// down, left. Characters that aren't box drawing have none.
+fn arms(ch: char) -> [bool; 4] {
+    match ch {
+        '─' | '═' => [false, true, false, true],
+        '│' | '║' => [true, false, true, false],
+        '┌' | '╔' => [false, true, true, false],
+        '┐' | '╗' => [false, false, true, true],
+        '└' | '╚' => [true, true, false, false],
+        '┘' | '╝' => [true, false, false, true],
+        '├' | '╠' => [true, true, true, false],
+        '┤' | '╣' => [true, false, true, true],
+        '┬' | '╦' => [false, true, true, true],
+        '┴' | '╩' => [true, true, false, true],
+        '┼' | '╬' => [true, true, true, true],
+        _ => [false; 4],
+    }
+}
+
+/// Box corners in a frame `width` cells wide with an arm leading nowhere:
+/// to a cell that isn't box drawing, rather than off the screen's edge.
+/// Each fault names the row, column and corner.
+pub fn box_faults(rows: &[String], width: usize) -> Vec<String> {
+    let grid: Vec<Vec<char>> = rows.iter().map(|row| row.chars().collect()).collect();
+    let at = |row: usize, column: usize| {
+        grid.get(row)
+            .and_then(|cells| cells.get(column))
+            .copied()
+            .unwrap_or(' ')
+    };
+
+    let mut faults = Vec::new();
+    for (row, cells) in grid.iter().enumerate() {
+        for (column, &ch) in cells.iter().enumerate() {
+            if !"┌┐└┘╔╗╚╝".contains(ch) {
+                continue;
+            }
+            let [up, right, down, left] = arms(ch);
+            let neighbours = [
+                (up, row.checked_sub(1).map(|r| (r, column)), 2),
+                (right, (column + 1 < width).then_some((row, column + 1)), 3),
+                (down, (row + 1 < grid.len()).then_some((row + 1, column)), 0),
+                (left, column.checked_sub(1).map(|c| (row, c)), 1),
+            ];
+            for (arm, neighbour, facing) in neighbours {
+                let Some((r, c)) = neighbour.filter(|_| arm) else {
+                    continue;
+                };
+                if !arms(at(r, c))[facing] {
+                    faults.push(format!(
+                        "row {}, column {}: {} leads to {:?}",
+                        row,
+                        column,
+                        ch,
+                        at(r, c)
+                    ));
+                }
+            }
+        }
+    }
+    faults
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::{FrameCapture, Renderer};
+
+    fn rows(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_whole_and_clipped_boxes_have_no_faults() {
+        let mut capture = FrameCapture::new(12, 4);
+        capture.begin_frame().unwrap();
+        capture.draw_box(0, 0, 5, 3);
+        capture.draw_box(8, 2, 10, 10);
+        capture.end_frame().unwrap();
+        assert!(box_faults(&capture.rows(), 12).is_empty());
+    }
+
+    #[test]
+    fn test_overwritten_borders_are_faults() {
+        // Text run over a right-hand corner, and a box cut short a row
+        let frame = rows(&["┌───┐", "│ text", "└───┘", "╔══╗", "╚  ╝"]);
+        let faults = box_faults(&frame, 10);
+        assert_eq!(faults.len(), 4, "{:#?}", faults);
+        assert!(faults[0].starts_with("row 0, column 4: ┐"));
+        assert!(faults[1].starts_with("row 2, column 4: ┘"));
+        assert!(faults[2].starts_with("row 4, column 0: ╚"));
+    }
+}
@@ -0,0 +1,310 @@
+//! Numbers drawn with a color and arrow showing which way they are moving.
+//!
+//! Text is built as a [`Line`] of [`Span`]s, each carrying a semantic
+//! [`Tone`] that the frontend's palette turns into a real color.
+
+use std::fmt;
+
+/// Samples looked back over when judging a trend
+pub const TREND_WINDOW: usize = 7;
+
+/// Relative change below which a value counts as stable
+const STEADY_TOLERANCE: f64 = 0.01;
+
+/// Which way a value has moved recently
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Rising,
+    Falling,
+    Steady,
+}
+
+impl Trend {
+    /// Compare the newest sample with the one [`TREND_WINDOW`] samples
+    /// earlier, or the oldest if the history is shorter
+    pub fn of(history: &[f64]) -> Self {
+        let Some(&last) = history.last() else {
+            return Trend::Steady;
+        };
+        let earlier = history[history.len().saturating_sub(TREND_WINDOW + 1)];
+        let scale = earlier.abs().max(f64::EPSILON);
+        match (last - earlier) / scale {
+            change if change > STEADY_TOLERANCE => Trend::Rising,
+            change if change < -STEADY_TOLERANCE => Trend::Falling,
+            _ => Trend::Steady,
+        }
+    }
+
+    pub fn arrow(self) -> char {
+        match self {
+            Trend::Rising => '↑',
+            Trend::Falling => '↓',
+            Trend::Steady => '→',
+        }
+    }
+
+    pub fn tone(self) -> Tone {
+        match self {
+            Trend::Rising => Tone::Rising,
+            Trend::Falling => Tone::Falling,
+            Trend::Steady => Tone::Steady,
+        }
+    }
+}
+
+/// What a piece of text means, for the palette to color
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Tone {
+    #[default]
+    Plain,
+    Rising,
+    Falling,
+    Steady,
+    /// Celebrations such as festivals
+    Festive,
+    /// Something the player should look into
+    Warning,
+    /// Something going badly wrong
+    Critical,
+    /// Open sea on a map
+    Water,
+    /// Shallow water and shore
+    Shallows,
+    /// Open land such as plains
+    Grassland,
+    Woodland,
+    Arid,
+    Rocky,
+    /// A frontier between polities on a map
+    Border,
+    /// The option a menu has selected
+    Selected,
+    /// A key the player can press, such as `[Q]` in a list of controls
+    Hotkey,
+    /// The clock is stopped
+    Paused,
+    /// Something running as it should, such as the frame rate
+    Healthy,
+    /// A step on a heatmap, from 0 for the lowest values up to
+    /// `HEAT_LEVELS - 1` for the highest
+    Heat(u8),
+}
+
+/// Steps a heatmap is colored in
+pub const HEAT_LEVELS: u8 = 5;
+
+impl Tone {
+    /// The heat step for a value's share of the way from the lowest value
+    /// shown to the highest
+    pub fn heat(share: f64) -> Self {
+        let step = (share.clamp(0.0, 1.0) * f64::from(HEAT_LEVELS)) as u8;
+        Tone::Heat(step.min(HEAT_LEVELS - 1))
+    }
+}
+
+/// Text drawn in a single tone
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub tone: Tone,
+}
+
+/// A row of text made of differently toned spans
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Line {
+    spans: Vec<Span>,
+}
+
+impl Line {
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self::default().push(text, Tone::Plain)
+    }
+
+    pub fn push(mut self, text: impl Into<String>, tone: Tone) -> Self {
+        self.spans.push(Span {
+            text: text.into(),
+            tone,
+        });
+        self
+    }
+
+    /// Append a formatted value followed by its trend arrow, both in the
+    /// trend's tone
+    pub fn value(self, text: impl Into<String>, trend: Trend) -> Self {
+        let text = format!("{} {}", text.into(), trend.arrow());
+        self.push(text, trend.tone())
+    }
+
+    /// A list of controls such as `[Q] Quit | [H] Help`, with each
+    /// bracketed key highlighted. A key ends at the first `]` followed by a
+    /// space, so `[[/]]` reads as the key `[/]`.
+    pub fn hotkeys(text: &str) -> Self {
+        let mut line = Self::default();
+        let mut rest = text;
+        while let Some(start) = rest.find('[') {
+            let key_end = rest[start..]
+                .match_indices(']')
+                .map(|(i, _)| start + i + 1)
+                .find(|&end| rest[end..].is_empty() || rest[end..].starts_with(' '));
+            let Some(end) = key_end else {
+                break;
+            };
+            if start > 0 {
+                line = line.push(&rest[..start], Tone::Plain);
+            }
+            line = line.push(&rest[start..end], Tone::Hotkey);
+            rest = &rest[end..];
+        }
+        if !rest.is_empty() {
+            line = line.push(rest, Tone::Plain);
+        }
+        line
+    }
+
+    pub fn append(mut self, other: Line) -> Self {
+        self.spans.extend(other.spans);
+        self
+    }
+
+    /// Cut the line to at most `width` characters
+    pub fn truncated(mut self, width: usize) -> Self {
+        let mut remaining = width;
+        for span in &mut self.spans {
+            let length = span.text.chars().count();
+            if length > remaining {
+                span.text = span.text.chars().take(remaining).collect();
+            }
+            remaining = remaining.saturating_sub(length);
+        }
+        self.spans.retain(|span| !span.text.is_empty());
+        self
+    }
+
+    pub fn spans(&self) -> &[Span] {
+        &self.spans
+    }
+
+    /// Length in characters
+    pub fn width(&self) -> usize {
+        self.spans
+            .iter()
+            .map(|span| span.text.chars().count())
+            .sum()
+    }
+}
+
+/// Build a [`Line`] from pieces of text, each plain or followed by
+/// `=> tone`:
+///
+/// ```
+/// use econogenesis::render::Tone;
+///
+/// let line = econogenesis::spans!["Speed ", "2.0x" => Tone::Rising];
+/// assert_eq!(line.to_string(), "Speed 2.0x");
+/// assert_eq!(line.spans()[1].tone, Tone::Rising);
+/// ```
+#[macro_export]
+macro_rules! spans {
+    (@tone) => {
+        $crate::render::Tone::Plain
+    };
+    (@tone $tone:expr) => {
+        $tone
+    };
+    ($($text:expr $(=> $tone:expr)?),* $(,)?) => {{
+        let line = $crate::render::Line::default();
+        $(let line = line.push($text, $crate::spans!(@tone $($tone)?));)*
+        line
+    }};
+}
+
+impl From<String> for Line {
+    fn from(text: String) -> Self {
+        Self::plain(text)
+    }
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for span in &self.spans {
+            f.write_str(&span.text)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trend_compares_against_window() {
+        assert_eq!(Trend::of(&[]), Trend::Steady);
+        assert_eq!(Trend::of(&[1.0, 2.0]), Trend::Rising);
+        assert_eq!(Trend::of(&[2.0, 1.0]), Trend::Falling);
+        assert_eq!(Trend::of(&[100.0, 100.5]), Trend::Steady);
+
+        // Only the last TREND_WINDOW + 1 samples count
+        let mut history = vec![50.0];
+        history.extend([10.0; TREND_WINDOW]);
+        history.push(10.0);
+        assert_eq!(Trend::of(&history), Trend::Steady);
+    }
+
+    #[test]
+    fn test_value_spans_carry_tone() {
+        let line = Line::plain("GDP ").value("1.25K", Trend::Falling);
+
+        assert_eq!(line.to_string(), "GDP 1.25K ↓");
+        assert_eq!(line.width(), 11);
+        assert_eq!(line.spans()[1].tone, Tone::Falling);
+
+        let short = line.truncated(6);
+        assert_eq!(short.to_string(), "GDP 1.");
+        assert_eq!(short.spans()[1].tone, Tone::Falling);
+        assert_eq!(
+            Line::plain("ab")
+                .value("1", Trend::Steady)
+                .truncated(2)
+                .spans()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_hotkeys_are_highlighted() {
+        let line = Line::hotkeys("[Q] Quit | [[/]] Palette");
+        let keys: Vec<&str> = line
+            .spans()
+            .iter()
+            .filter(|span| span.tone == Tone::Hotkey)
+            .map(|span| span.text.as_str())
+            .collect();
+        assert_eq!(keys, vec!["[Q]", "[[/]]"]);
+        assert_eq!(line.to_string(), "[Q] Quit | [[/]] Palette");
+        assert_eq!(Line::hotkeys("no keys [here").spans().len(), 1);
+    }
+
+    #[test]
+    fn test_spans_macro_tones_each_piece() {
+        let paused = true;
+        let line = crate::spans![
+            "Status ",
+            "[PAUSED]" => if paused { Tone::Paused } else { Tone::Plain },
+            format!(" | FPS: {:.1}", 30.0) => Tone::Healthy,
+        ];
+        assert_eq!(line.to_string(), "Status [PAUSED] | FPS: 30.0");
+        let tones: Vec<Tone> = line.spans().iter().map(|span| span.tone).collect();
+        assert_eq!(tones, vec![Tone::Plain, Tone::Paused, Tone::Healthy]);
+    }
+
+    #[test]
+    fn test_heat_steps_span_the_range() {
+        assert_eq!(Tone::heat(0.0), Tone::Heat(0));
+        assert_eq!(Tone::heat(0.5), Tone::Heat(2));
+        assert_eq!(Tone::heat(1.0), Tone::Heat(HEAT_LEVELS - 1));
+        assert_eq!(Tone::heat(-3.0), Tone::Heat(0));
+        assert_eq!(Tone::heat(f64::NAN), Tone::Heat(0));
+    }
+}
@@ -1,11 +1,33 @@
+use crate::economy::data::GoodsDataError;
+use crate::game::worldgen::WorldConfigError;
+use crate::save::MigrationError;
+use std::path::PathBuf;
 use thiserror::Error as ThisError;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(ThisError, Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Error {
     #[error("terminal IO error")]
     TerminalError(#[from] std::io::Error),
     #[error("terminal interface error")]
     InterfaceError(#[from] tty_interface::Error),
+    #[error("save file error at {path}")]
+    SaveFileError {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("save data error")]
+    SaveFormatError(#[from] serde_json::Error),
+    #[error("save version error")]
+    SaveVersionError(#[from] MigrationError),
+    #[error("world config error")]
+    WorldConfigError(#[from] WorldConfigError),
+    #[error("goods data error")]
+    GoodsDataError(#[from] GoodsDataError),
+    #[cfg(feature = "telemetry")]
+    #[error("log file error")]
+    LogFileError(#[from] tracing_appender::rolling::InitError),
 }
@@ -0,0 +1,160 @@
+use super::{SaveGame, ensure_dir};
+use crate::result::Result;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const SLOT_PREFIX: &str = "autosave-";
+
+/// Periodically writes the game into a fixed number of rotating slots,
+/// always overwriting the oldest one
+pub struct Autosave {
+    dir: PathBuf,
+    interval: Duration,
+    slots: usize,
+    next_slot: usize,
+    last_save_time: Duration,
+}
+
+impl Autosave {
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(30 * 60);
+    pub const DEFAULT_SLOTS: usize = 3;
+
+    pub fn new(dir: &Path, interval: Duration, slots: usize) -> Self {
+        let slots = slots.max(1);
+        let next_slot = Self::oldest_slot(dir, slots);
+
+        Self {
+            dir: dir.to_path_buf(),
+            interval,
+            slots,
+            next_slot,
+            last_save_time: Duration::ZERO,
+        }
+    }
+
+    /// Start counting the interval from the given simulation time, e.g.
+    /// after loading a save
+    pub fn reset(&mut self, simulation_time: Duration) {
+        self.last_save_time = simulation_time;
+    }
+
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    pub fn is_due(&self, simulation_time: Duration) -> bool {
+        simulation_time.saturating_sub(self.last_save_time) >= self.interval
+    }
+
+    pub fn save(&mut self, game: &SaveGame) -> Result<PathBuf> {
+        ensure_dir(&self.dir)?;
+        let path = Self::slot_path(&self.dir, self.next_slot);
+        game.write_to(&path)?;
+
+        self.next_slot = (self.next_slot + 1) % self.slots;
+        self.last_save_time = game.simulation_time;
+        Ok(path)
+    }
+
+    /// The most recently written autosave in `dir`, if any
+    pub fn latest(dir: &Path) -> Option<PathBuf> {
+        Self::slot_files(dir)
+            .into_iter()
+            .max_by_key(|(_, modified)| *modified)
+            .map(|(path, _)| path)
+    }
+
+    fn slot_path(dir: &Path, slot: usize) -> PathBuf {
+        dir.join(format!("{}{}.json", SLOT_PREFIX, slot))
+    }
+
+    pub(super) fn slot_files(dir: &Path) -> Vec<(PathBuf, std::time::SystemTime)> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+
+        entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                name.starts_with(SLOT_PREFIX) && name.ends_with(".json")
+            })
+            .filter_map(|entry| {
+                let modified = entry.metadata().ok()?.modified().ok()?;
+                Some((entry.path(), modified))
+            })
+            .collect()
+    }
+
+    /// First empty slot, or the least recently written one
+    fn oldest_slot(dir: &Path, slots: usize) -> usize {
+        let mut oldest = None;
+        for slot in 0..slots {
+            let path = Self::slot_path(dir, slot);
+            match path.metadata().and_then(|m| m.modified()) {
+                Ok(modified) => {
+                    if oldest.is_none_or(|(_, time)| modified < time) {
+                        oldest = Some((slot, modified));
+                    }
+                }
+                Err(_) => return slot,
+            }
+        }
+        oldest.map_or(0, |(slot, _)| slot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::WorldState;
+    use crate::save::test_dir;
+    use crate::zoom::{Position, ZoomLevel};
+
+    fn save_at(seconds: u64) -> SaveGame {
+        SaveGame::new(
+            Duration::from_secs(seconds),
+            ZoomLevel::Galaxy,
+            Position::new(),
+            WorldState::new(),
+        )
+    }
+
+    #[test]
+    fn test_due_after_interval() {
+        let dir = test_dir("autosave-due");
+        let mut autosave = Autosave::new(&dir, Duration::from_secs(60), 2);
+
+        assert!(!autosave.is_due(Duration::from_secs(59)));
+        assert!(autosave.is_due(Duration::from_secs(60)));
+
+        autosave.save(&save_at(60)).unwrap();
+        assert!(!autosave.is_due(Duration::from_secs(90)));
+        assert!(autosave.is_due(Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn test_slots_rotate() {
+        let dir = test_dir("autosave-rotate");
+        let mut autosave = Autosave::new(&dir, Duration::from_secs(60), 2);
+
+        let first = autosave.save(&save_at(60)).unwrap();
+        let second = autosave.save(&save_at(120)).unwrap();
+        let third = autosave.save(&save_at(180)).unwrap();
+
+        assert_ne!(first, second);
+        assert_eq!(first, third);
+        assert_eq!(Autosave::slot_files(&dir).len(), 2);
+
+        let latest = Autosave::latest(&dir).unwrap();
+        let loaded = SaveGame::read_from(&latest).unwrap();
+        assert_eq!(loaded.simulation_time, Duration::from_secs(180));
+    }
+
+    #[test]
+    fn test_latest_without_saves() {
+        let dir = test_dir("autosave-empty");
+        assert!(Autosave::latest(&dir).is_none());
+    }
+}
@@ -0,0 +1,160 @@
+//! Side-by-side trend charts for several saves, for comparing runs of the
+//! same scenario or the effect of a balance change.
+
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+use super::{Autosave, QUIT_SAVE_FILE, SaveGame};
+use crate::game::stats::{GALAXY_ID, Trends};
+use crate::render::charts;
+use crate::zoom::ZoomLevel;
+
+/// Saves compared when none are named
+pub const DEFAULT_RUNS: usize = 3;
+
+/// Days of history drawn per run
+const CHART_WIDTH: usize = 40;
+
+/// Picks one metric's series out of a run's trends
+type Metric = fn(&Trends) -> &VecDeque<f64>;
+
+/// One save's galaxy-wide history
+#[derive(Debug, Clone)]
+pub struct Run {
+    pub label: String,
+    pub trends: Trends,
+}
+
+impl Run {
+    /// The galaxy trends of a save, labelled with its file name
+    pub fn from_save(path: &Path, save: &SaveGame) -> Self {
+        let label = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let trends = save
+            .world
+            .stats()
+            .trends(ZoomLevel::Galaxy, GALAXY_ID)
+            .cloned()
+            .unwrap_or_default();
+        Self { label, trends }
+    }
+}
+
+/// The `count` most recently written quit and autosave files in `dir`,
+/// newest first
+pub fn recent_saves(dir: &Path, count: usize) -> Vec<PathBuf> {
+    let mut saves = Autosave::slot_files(dir);
+    let quit_save = dir.join(QUIT_SAVE_FILE);
+    if let Ok(modified) = quit_save.metadata().and_then(|m| m.modified()) {
+        saves.push((quit_save, modified));
+    }
+    saves.sort_by_key(|(_, modified)| Reverse(*modified));
+    saves
+        .into_iter()
+        .take(count)
+        .map(|(path, _)| path)
+        .collect()
+}
+
+/// One chart per metric with a line per run, each drawn on a scale shared
+/// by all runs so their curves can be compared directly
+pub fn comparison(runs: &[Run]) -> Vec<String> {
+    let metrics: [(&str, Metric); 3] = [
+        ("GDP per day", |t| &t.gdp),
+        ("Price index", |t| &t.prices),
+        ("Population", |t| &t.population),
+    ];
+    let label_width = runs.iter().map(|run| run.label.len()).max().unwrap_or(0);
+
+    let mut lines = Vec::new();
+    for (title, series) in metrics {
+        let all: Vec<Vec<f64>> = runs
+            .iter()
+            .map(|run| series(&run.trends).iter().copied().collect())
+            .collect();
+        let shown: Vec<f64> = all
+            .iter()
+            .flat_map(|values| &values[values.len().saturating_sub(CHART_WIDTH)..])
+            .copied()
+            .collect();
+        let (min, max) = charts::range(&shown);
+
+        lines.push(title.to_string());
+        for (run, values) in runs.iter().zip(&all) {
+            let line = match values.last() {
+                Some(last) => format!(
+                    "  {:<width$}  {:<chart$}  {}",
+                    run.label,
+                    charts::sparkline_between(values, CHART_WIDTH, min, max),
+                    charts::compact(*last),
+                    width = label_width,
+                    chart = CHART_WIDTH,
+                ),
+                None => format!("  {:<width$}  no history", run.label, width = label_width),
+            };
+            lines.push(line);
+        }
+        if !shown.is_empty() {
+            lines.push(format!(
+                "  {:<width$}  scale {} to {}",
+                "",
+                charts::compact(min),
+                charts::compact(max),
+                width = label_width,
+            ));
+        }
+        lines.push(String::new());
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::WorldState;
+    use crate::save::test_dir;
+    use std::time::Duration;
+
+    fn run(label: &str, gdp: &[f64]) -> Run {
+        let mut trends = Trends::default();
+        trends.gdp.extend(gdp);
+        Run {
+            label: label.to_string(),
+            trends,
+        }
+    }
+
+    #[test]
+    fn test_runs_share_a_scale() {
+        let lines = comparison(&[run("a", &[0.0, 7.0]), run("longer", &[14.0])]);
+
+        assert_eq!(lines[0], "GDP per day");
+        assert!(lines[1].starts_with("  a       ▁▅ "));
+        assert!(lines[1].ends_with("7.00"));
+        assert!(lines[2].starts_with("  longer  █ "));
+        assert!(lines[3].ends_with("scale 0.00 to 14.00"));
+        assert!(lines.contains(&String::from("  a       no history")));
+    }
+
+    #[test]
+    fn test_recent_saves_are_newest_first() {
+        let dir = test_dir("compare-recent");
+        let save = SaveGame::new(
+            Duration::ZERO,
+            ZoomLevel::Galaxy,
+            Default::default(),
+            WorldState::new(),
+        );
+        let mut autosave = Autosave::new(&dir, Duration::ZERO, 2);
+        let first = autosave.save(&save).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        save.write_to(&dir.join(QUIT_SAVE_FILE)).unwrap();
+
+        let recent = recent_saves(&dir, DEFAULT_RUNS);
+        assert_eq!(recent, vec![dir.join(QUIT_SAVE_FILE), first.clone()]);
+        assert_eq!(recent_saves(&dir, 1), vec![dir.join(QUIT_SAVE_FILE)]);
+    }
+}
@@ -0,0 +1,172 @@
+use super::{Autosave, CustomOverlay, Profile, SaveGame, Settings};
+use crate::game::tutorial::Tutorial;
+use crate::result::Result;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Save written when the player chooses to save on quit
+pub const QUIT_SAVE_FILE: &str = "savegame.json";
+
+/// Owns the data directory layout: autosave slots, the quit save, and the
+/// player profile that remembers which save was written last
+pub struct SaveManager {
+    dir: PathBuf,
+    autosave: Autosave,
+    profile: Profile,
+}
+
+impl SaveManager {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            autosave: Autosave::new(dir, Autosave::DEFAULT_INTERVAL, Autosave::DEFAULT_SLOTS),
+            profile: Profile::load(dir),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
+    pub fn is_autosave_due(&self, simulation_time: Duration) -> bool {
+        self.autosave.is_due(simulation_time)
+    }
+
+    pub fn set_autosave_interval(&mut self, interval: Duration) {
+        self.autosave.set_interval(interval);
+    }
+
+    /// Restart the autosave interval, e.g. after loading a save
+    pub fn reset_autosave(&mut self, simulation_time: Duration) {
+        self.autosave.reset(simulation_time);
+    }
+
+    pub fn autosave(&mut self, game: &SaveGame) -> Result<PathBuf> {
+        let path = self.autosave.save(game)?;
+        self.remember(&path)?;
+        Ok(path)
+    }
+
+    pub fn save(&mut self, game: &SaveGame) -> Result<PathBuf> {
+        let path = self.dir.join(QUIT_SAVE_FILE);
+        game.write_to(&path)?;
+        self.remember(&path)?;
+        Ok(path)
+    }
+
+    /// Whether there is a previous save for [`Self::load_last`] to load
+    pub fn has_last_save(&self) -> bool {
+        self.profile
+            .last_save
+            .as_deref()
+            .is_some_and(|path| path.exists())
+    }
+
+    /// Load whichever save was written most recently
+    pub fn load_last(&self) -> Option<Result<SaveGame>> {
+        let path = self.profile.last_save.as_ref()?;
+        Some(SaveGame::read_from(path))
+    }
+
+    /// Add an overlay to the profile, replacing any with the same name
+    pub fn save_overlay(&mut self, overlay: CustomOverlay) -> Result<()> {
+        let overlays = &mut self.profile.overlays;
+        match overlays.iter_mut().find(|o| o.name == overlay.name) {
+            Some(existing) => *existing = overlay,
+            None => overlays.push(overlay),
+        }
+        self.profile.store(&self.dir)
+    }
+
+    /// Change the settings every game follows where its save doesn't
+    /// override them
+    pub fn save_settings(&mut self, settings: Settings) -> Result<()> {
+        self.profile.settings = settings;
+        self.profile.store(&self.dir)
+    }
+
+    /// Record the player's progress through the tutorial
+    pub fn save_tutorial(&mut self, tutorial: Tutorial) -> Result<()> {
+        self.profile.tutorial = tutorial;
+        self.profile.store(&self.dir)
+    }
+
+    fn remember(&mut self, path: &Path) -> Result<()> {
+        self.profile.last_save = Some(path.to_path_buf());
+        self.profile.store(&self.dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::WorldState;
+    use crate::save::test_dir;
+    use crate::zoom::{Position, ZoomLevel};
+
+    fn game_at(seconds: u64) -> SaveGame {
+        SaveGame::new(
+            Duration::from_secs(seconds),
+            ZoomLevel::Galaxy,
+            Position::new(),
+            WorldState::new(),
+        )
+    }
+
+    #[test]
+    fn test_no_last_save_initially() {
+        let dir = test_dir("manager-empty");
+        let manager = SaveManager::new(&dir);
+        assert!(manager.load_last().is_none());
+        assert!(!manager.has_last_save());
+    }
+
+    #[test]
+    fn test_last_save_survives_restart() {
+        let dir = test_dir("manager-last");
+        let mut manager = SaveManager::new(&dir);
+        manager.autosave(&game_at(60)).unwrap();
+        let quit_path = manager.save(&game_at(120)).unwrap();
+
+        let manager = SaveManager::new(&dir);
+        assert_eq!(
+            manager.profile().last_save.as_deref(),
+            Some(quit_path.as_path())
+        );
+        assert!(manager.has_last_save());
+        let loaded = manager.load_last().unwrap().unwrap();
+        assert_eq!(loaded.simulation_time, Duration::from_secs(120));
+    }
+
+    #[test]
+    fn test_overlays_are_kept_by_name() {
+        let dir = test_dir("manager-overlays");
+        let mut manager = SaveManager::new(&dir);
+        let overlay = |name: &str, expression: &str| CustomOverlay {
+            name: name.to_string(),
+            expression: expression.to_string(),
+        };
+        manager
+            .save_overlay(overlay("Cost of bread", "price(bread)"))
+            .unwrap();
+        manager
+            .save_overlay(overlay("Farmland", "farming"))
+            .unwrap();
+        manager
+            .save_overlay(overlay("Cost of bread", "price(bread) / wage"))
+            .unwrap();
+
+        let manager = SaveManager::new(&dir);
+        assert_eq!(
+            manager.profile().overlays,
+            vec![
+                overlay("Cost of bread", "price(bread) / wage"),
+                overlay("Farmland", "farming"),
+            ]
+        );
+    }
+}
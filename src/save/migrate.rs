@@ -0,0 +1,402 @@
+//! Upgrading saves written by older builds.
+//!
+//! Every save records the [`SAVE_VERSION`] of the build that wrote it.
+//! Reading one goes through its JSON first: each [`Migration`] in
+//! [`MIGRATIONS`] rewrites the save from one version to the next until it
+//! reaches the current one, and only then is it deserialized. A change to
+//! the shape of the world that `#[serde(default)]` can't paper over bumps
+//! the version and registers a migration from the old one.
+
+use serde_json::{Map, Value, json};
+use thiserror::Error as ThisError;
+
+use crate::economy::{Capacity, STOREHOUSE};
+use crate::game::characters::CARRY;
+use crate::game::ships::HOLD;
+
+/// The version of the save format this build writes
+pub const SAVE_VERSION: u32 = 3;
+
+/// Field of a save holding its version. Saves from before versioning lack
+/// it and count as version 0.
+pub const VERSION_FIELD: &str = "version";
+
+#[derive(ThisError, Debug, Clone, PartialEq)]
+pub enum MigrationError {
+    #[error("the save is corrupt: {0}")]
+    Corrupt(String),
+    #[error("the save is version {0}, older than this build can upgrade (from {1})")]
+    TooOld(u32, u32),
+    #[error("the save is version {0}, newer than this build's {SAVE_VERSION}")]
+    TooNew(u32),
+    #[error("upgrading the save from version {from} failed: {reason}")]
+    Failed { from: u32, reason: String },
+}
+
+/// A step that rewrites a save of one version into the next
+pub struct Migration {
+    /// The version the migration upgrades from
+    pub from: u32,
+    pub description: &'static str,
+    pub apply: fn(&mut Map<String, Value>) -> Result<(), String>,
+}
+
+/// Every migration, oldest first, forming a chain up to [`SAVE_VERSION`]
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 0,
+        description: "Add the version header",
+        apply: |_| Ok(()),
+    },
+    Migration {
+        from: 1,
+        description: "Move places into the entity store",
+        apply: move_places_into_entity_store,
+    },
+    Migration {
+        from: 2,
+        description: "Give goods a volume and holdings a capacity",
+        apply: add_capacities,
+    },
+];
+
+/// Version 1 kept a map of id to place for each of `systems`, `planets`,
+/// `regions`, `areas` and `rooms`; version 2 keeps one entity store with a
+/// list of `[level, id, component]` entries per component
+fn move_places_into_entity_store(save: &mut Map<String, Value>) -> Result<(), String> {
+    let world = save
+        .get_mut("world")
+        .and_then(Value::as_object_mut)
+        .ok_or("no world")?;
+
+    let mut entities = Vec::new();
+    let mut components: Map<String, Value> = Map::new();
+    let mut add = |component: &str, level: &str, id: &Value, value: Value| {
+        let list = components
+            .entry(component)
+            .or_insert_with(|| Value::Array(Vec::new()));
+        if let Value::Array(list) = list {
+            list.push(json!([level, id, value]));
+        }
+    };
+
+    for (field, level) in [
+        ("systems", "SolarSystem"),
+        ("planets", "Planet"),
+        ("regions", "Region"),
+        ("areas", "LocalArea"),
+        ("rooms", "Room"),
+    ] {
+        let Some(places) = world.remove(field) else {
+            continue;
+        };
+        let Value::Object(places) = places else {
+            return Err(format!("{} isn't a map", field));
+        };
+        for (_, place) in places {
+            let Value::Object(mut place) = place else {
+                return Err(format!("a place in {} isn't a map", field));
+            };
+            let id = place.remove("id").ok_or("a place has no id")?;
+            let name = place.remove("name").unwrap_or_default();
+            let placement = place
+                .remove("placement")
+                .unwrap_or_else(|| json!({ "parent": null, "coords": [0, 0] }));
+            entities.push(json!([level, id]));
+            add("named", level, &id, json!({ "name": name }));
+            add("placements", level, &id, placement);
+
+            match level {
+                "Planet" => {
+                    let population = place.remove("population").unwrap_or(json!(0));
+                    add("populated", level, &id, json!({ "population": population }));
+                    continue;
+                }
+                "LocalArea" => {
+                    let workforce = place.remove("workforce").unwrap_or(json!(0));
+                    add("populated", level, &id, json!({ "population": workforce }));
+                    add("market_hosts", level, &id, Value::Null);
+                }
+                _ => {}
+            }
+            add(field, level, &id, Value::Object(place));
+        }
+    }
+
+    components.insert(String::from("entities"), Value::Array(entities));
+    world.insert(String::from("entities"), Value::Object(components));
+    Ok(())
+}
+
+/// The version recorded in a save
+pub fn version_of(save: &Map<String, Value>) -> Result<u32, MigrationError> {
+    match save.get(VERSION_FIELD) {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| MigrationError::Corrupt(format!("bad version {}", version))),
+    }
+}
+
+/// Bring a save's JSON up to [`SAVE_VERSION`] with the registered
+/// migrations, returning the version it started at
+pub fn upgrade(save: &mut Value) -> Result<u32, MigrationError> {
+    upgrade_with(save, MIGRATIONS, SAVE_VERSION)
+}
+
+fn upgrade_with(
+    save: &mut Value,
+    migrations: &[Migration],
+    current: u32,
+) -> Result<u32, MigrationError> {
+    let Value::Object(fields) = save else {
+        return Err(MigrationError::Corrupt(String::from("not a saved game")));
+    };
+    let original = version_of(fields)?;
+    if original > current {
+        return Err(MigrationError::TooNew(original));
+    }
+    let mut version = original;
+    while version < current {
+        let Some(migration) = migrations.iter().find(|m| m.from == version) else {
+            let oldest = migrations.first().map_or(current, |m| m.from);
+            return Err(MigrationError::TooOld(original, oldest));
+        };
+        tracing::info!(
+            "Upgrading save from version {}: {}",
+            version,
+            migration.description
+        );
+        (migration.apply)(fields).map_err(|reason| MigrationError::Failed {
+            from: version,
+            reason,
+        })?;
+        version += 1;
+        fields.insert(String::from(VERSION_FIELD), Value::from(version));
+    }
+    Ok(original)
+}
+
+/// Version 2 measured goods only by weight, kept firm stock and character
+/// inventories as plain maps of good to units, and gave ships a hold of so
+/// many units. Version 3 gives each good a volume, a litre per kilogram,
+/// wraps each map in an inventory with the capacity new ones get, and
+/// sizes each hold in proportion to a new ship's, which took 200 units.
+fn add_capacities(save: &mut Map<String, Value>) -> Result<(), String> {
+    let world = save
+        .get_mut("world")
+        .and_then(Value::as_object_mut)
+        .ok_or("no world")?;
+    let wrap = |value: &mut Value, field: &str, limit: Capacity| {
+        // Saves from before versioning may already be in the new shape
+        match value.get_mut(field) {
+            Some(held) if held.get("goods").is_none() => {
+                let held = held.take();
+                value[field] = json!({ "goods": held, "capacity": limit });
+            }
+            _ => {}
+        }
+    };
+
+    if let Some(economy) = world.get_mut("economy") {
+        if let Some(Value::Array(goods)) = economy.pointer_mut("/catalog/goods") {
+            for good in goods.iter_mut().filter(|good| good.get("volume").is_none()) {
+                let weight = good.get("weight").cloned().unwrap_or(json!(1.0));
+                good["volume"] = weight;
+            }
+        }
+        for firm in entries(economy.get_mut("firms")) {
+            wrap(firm, "stock", STOREHOUSE);
+        }
+    }
+    for character in entries(world.get_mut("characters")) {
+        wrap(character, "inventory", CARRY);
+    }
+    for ship in entries(world.get_mut("ships")) {
+        let Some(units) = ship.get("capacity").and_then(Value::as_f64) else {
+            continue;
+        };
+        let scale = |limit: Option<f64>| limit.map(|limit| limit * units / 200.0);
+        ship["hold"] = json!({ "mass": scale(HOLD.mass), "volume": scale(HOLD.volume) });
+        if let Value::Object(ship) = ship {
+            ship.remove("capacity");
+        }
+    }
+    Ok(())
+}
+
+/// The values of a map, as saved for a `BTreeMap` keyed by id
+fn entries(value: Option<&mut Value>) -> Vec<&mut Value> {
+    match value {
+        Some(Value::Object(map)) => map.values_mut().collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::{Firm, GoodId, RecipeId};
+    use crate::game::characters::Character;
+    use crate::game::entities::EntityStore;
+    use crate::game::ships::Ship;
+    use crate::zoom::ZoomLevel;
+    use serde_json::json;
+
+    /// Version 1 renamed `cash` to `money`; version 2 doubled it
+    const TEST_MIGRATIONS: &[Migration] = &[
+        Migration {
+            from: 0,
+            description: "Rename cash",
+            apply: |save| {
+                let cash = save.remove("cash").ok_or("no cash")?;
+                save.insert(String::from("money"), cash);
+                Ok(())
+            },
+        },
+        Migration {
+            from: 1,
+            description: "Double money",
+            apply: |save| {
+                let money = save["money"].as_f64().ok_or("money isn't a number")?;
+                save.insert(String::from("money"), json!(money * 2.0));
+                Ok(())
+            },
+        },
+    ];
+
+    #[test]
+    fn test_saves_upgrade_through_each_version() {
+        let mut save = json!({ "cash": 5.0 });
+        assert_eq!(upgrade_with(&mut save, TEST_MIGRATIONS, 2), Ok(0));
+        assert_eq!(save, json!({ "money": 10.0, "version": 2 }));
+
+        let mut save = json!({ "money": 5.0, "version": 1 });
+        assert_eq!(upgrade_with(&mut save, TEST_MIGRATIONS, 2), Ok(1));
+        assert_eq!(save["money"], json!(10.0));
+
+        // Already current: left alone
+        let mut save = json!({ "money": 5.0, "version": 2 });
+        assert_eq!(upgrade_with(&mut save, TEST_MIGRATIONS, 2), Ok(2));
+        assert_eq!(save["money"], json!(5.0));
+    }
+
+    #[test]
+    fn test_places_move_into_the_entity_store() {
+        let mut save = json!({
+            "version": 1,
+            "world": {
+                "planets": { "1": { "id": 1, "name": "Terra", "population": 9 } },
+                "areas": {
+                    "1": {
+                        "id": 1,
+                        "name": "Dockside",
+                        "building_count": 4,
+                        "workforce": 30,
+                        "placement": { "parent": 1, "coords": [2, 0] }
+                    }
+                }
+            }
+        });
+        assert_eq!(upgrade(&mut save), Ok(1));
+
+        let entities = &save["world"]["entities"];
+        assert_eq!(
+            entities["entities"],
+            json!([["Planet", 1], ["LocalArea", 1]])
+        );
+        assert_eq!(
+            entities["populated"],
+            json!([
+                ["Planet", 1, { "population": 9 }],
+                ["LocalArea", 1, { "population": 30 }]
+            ])
+        );
+        assert_eq!(
+            entities["areas"],
+            json!([["LocalArea", 1, { "building_count": 4 }]])
+        );
+        assert_eq!(entities["market_hosts"], json!([["LocalArea", 1, null]]));
+        assert_eq!(entities["placements"][1][2]["coords"], json!([2, 0]));
+        assert!(save["world"].get("planets").is_none());
+
+        let store: EntityStore = serde_json::from_value(entities.clone()).unwrap();
+        assert_eq!(store.name(ZoomLevel::LocalArea, 1), Some("Dockside"));
+        assert!(store.market_hosts.contains(ZoomLevel::LocalArea, 1));
+    }
+
+    #[test]
+    fn test_holdings_get_capacities() {
+        let firm = serde_json::to_value(Firm::new(7, "Bakery", 1, RecipeId(0))).unwrap();
+        let character = serde_json::to_value(Character::new(8, "Ada", 1)).unwrap();
+        let ship = serde_json::to_value(Ship::new(9, "Wayfarer", 2)).unwrap();
+        let mut world = json!({
+            "economy": {
+                "catalog": { "goods": [{ "name": "ore", "weight": 5.0 }] },
+                "firms": { "7": firm },
+            },
+            "characters": { "8": character },
+            "ships": { "9": ship },
+        });
+        world["economy"]["firms"]["7"]["stock"] = json!({ "0": 3.0 });
+        world["characters"]["8"]["inventory"] = json!({ "0": 2.0 });
+        world["ships"]["9"]["capacity"] = json!(100.0);
+        world["ships"]["9"].as_object_mut().unwrap().remove("hold");
+        let mut save = json!({ "version": 2, "world": world });
+        assert_eq!(upgrade(&mut save), Ok(2));
+
+        let world = &save["world"];
+        assert_eq!(
+            world["economy"]["catalog"]["goods"][0]["volume"],
+            json!(5.0)
+        );
+        let firm: Firm = serde_json::from_value(world["economy"]["firms"]["7"].clone()).unwrap();
+        assert_eq!(firm.held(GoodId(0)), 3.0);
+        assert_eq!(firm.stock.capacity(), STOREHOUSE);
+        let character: Character =
+            serde_json::from_value(world["characters"]["8"].clone()).unwrap();
+        assert_eq!(character.inventory.get(GoodId(0)), 2.0);
+        assert_eq!(character.inventory.capacity(), CARRY);
+        let ship: Ship = serde_json::from_value(world["ships"]["9"].clone()).unwrap();
+        assert_eq!(ship.hold.mass, HOLD.mass.map(|mass| mass / 2.0));
+    }
+
+    #[test]
+    fn test_registered_migrations_reach_the_current_version() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.from, i as u32);
+        }
+        assert_eq!(MIGRATIONS.len() as u32, SAVE_VERSION);
+    }
+
+    #[test]
+    fn test_unreadable_saves_are_refused() {
+        let mut save = json!({ "money": 5.0, "version": 3 });
+        assert_eq!(
+            upgrade_with(&mut save, TEST_MIGRATIONS, 2),
+            Err(MigrationError::TooNew(3))
+        );
+        let mut save = json!({ "money": 5.0, "version": 0 });
+        assert_eq!(
+            upgrade_with(&mut save, &TEST_MIGRATIONS[1..], 2),
+            Err(MigrationError::TooOld(0, 1))
+        );
+        let mut save = json!({ "money": 5.0 });
+        assert_eq!(
+            upgrade_with(&mut save, TEST_MIGRATIONS, 2),
+            Err(MigrationError::Failed {
+                from: 0,
+                reason: String::from("no cash")
+            })
+        );
+        assert!(matches!(
+            upgrade_with(&mut json!([1, 2]), TEST_MIGRATIONS, 2),
+            Err(MigrationError::Corrupt(_))
+        ));
+        assert!(matches!(
+            upgrade_with(&mut json!({ "version": "two" }), TEST_MIGRATIONS, 2),
+            Err(MigrationError::Corrupt(_))
+        ));
+    }
+}
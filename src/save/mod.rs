@@ -0,0 +1,201 @@
+mod autosave;
+pub mod compare;
+mod manager;
+mod migrate;
+mod profile;
+mod scenario;
+mod session;
+mod settings;
+
+pub use autosave::Autosave;
+pub use manager::{QUIT_SAVE_FILE, SaveManager};
+pub use migrate::{MIGRATIONS, Migration, MigrationError, SAVE_VERSION};
+pub use profile::{CustomOverlay, Profile};
+pub use scenario::{SCENARIO_DIR, Scenario};
+pub use session::SessionLock;
+pub use settings::{SettingSources, Settings, SettingsOverrides, Source};
+
+use crate::game::WorldState;
+use crate::result::{Error, Result};
+use crate::zoom::{Position, ZoomLevel};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Everything needed to resume a session exactly where it left off
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveGame {
+    /// The [`SAVE_VERSION`] of the build that wrote the save
+    #[serde(default)]
+    pub version: u32,
+    /// Wall-clock time of the save, in seconds since the Unix epoch
+    pub saved_at: u64,
+    pub simulation_time: Duration,
+    pub zoom_level: ZoomLevel,
+    pub position: Position,
+    pub world: WorldState,
+}
+
+impl SaveGame {
+    pub fn new(
+        simulation_time: Duration,
+        zoom_level: ZoomLevel,
+        position: Position,
+        world: WorldState,
+    ) -> Self {
+        Self {
+            version: SAVE_VERSION,
+            saved_at: unix_now(),
+            simulation_time,
+            zoom_level,
+            position,
+            world,
+        }
+    }
+
+    /// Write the save atomically so a crash mid-write never leaves a
+    /// truncated file in place of a good one
+    pub fn write_to(&self, path: &Path) -> Result<()> {
+        write_json(path, self)
+    }
+
+    /// Read a save, upgrading it first if an older build wrote it
+    pub fn read_from(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path).map_err(|source| Error::SaveFileError {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Ok(Self::from_json(&json)?)
+    }
+
+    fn from_json(json: &str) -> std::result::Result<Self, MigrationError> {
+        let corrupt = |e: serde_json::Error| MigrationError::Corrupt(e.to_string());
+        let mut save = serde_json::from_str(json).map_err(corrupt)?;
+        migrate::upgrade(&mut save)?;
+        serde_json::from_value(save).map_err(corrupt)
+    }
+}
+
+/// Serialize to a temporary file, then rename it over the destination
+fn write_json(path: &Path, value: &impl Serialize) -> Result<()> {
+    let json = serde_json::to_string(value)?;
+    let tmp_path = path.with_extension("tmp");
+
+    fs::write(&tmp_path, json).map_err(|source| Error::SaveFileError {
+        path: tmp_path.clone(),
+        source,
+    })?;
+    fs::rename(&tmp_path, path).map_err(|source| Error::SaveFileError {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let json = fs::read_to_string(path).map_err(|source| Error::SaveFileError {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// Wall-clock seconds since the Unix epoch, or 0 if the clock is unset
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Directory holding saves and session state.
+///
+/// Uses `ECONOGENESIS_DATA_DIR` when set, otherwise `~/.econogenesis`.
+pub fn data_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("ECONOGENESIS_DATA_DIR") {
+        return PathBuf::from(dir);
+    }
+
+    std::env::var_os("HOME")
+        .map(|home| PathBuf::from(home).join(".econogenesis"))
+        .unwrap_or_else(|| PathBuf::from(".econogenesis"))
+}
+
+pub fn ensure_dir(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir).map_err(|source| Error::SaveFileError {
+        path: dir.to_path_buf(),
+        source,
+    })
+}
+
+#[cfg(test)]
+pub(crate) fn test_dir(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("econogenesis-test-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_round_trip() {
+        let dir = test_dir("round-trip");
+        let path = dir.join("game.json");
+
+        let mut world = WorldState::new();
+        world.update(Duration::from_secs(3600));
+        let mut position = Position::new();
+        position.galaxy_coords = (4, -2);
+        let save = SaveGame::new(
+            Duration::from_secs(3600),
+            ZoomLevel::Planet,
+            position,
+            world,
+        );
+        save.write_to(&path).unwrap();
+
+        let loaded = SaveGame::read_from(&path).unwrap();
+        assert_eq!(loaded.simulation_time, Duration::from_secs(3600));
+        assert_eq!(loaded.zoom_level, ZoomLevel::Planet);
+        assert_eq!(loaded.position.galaxy_coords, (4, -2));
+        assert_eq!(loaded.world.tick_count(), 1);
+        assert!(!path.with_extension("tmp").exists());
+    }
+
+    #[test]
+    fn test_saves_from_before_versioning_are_upgraded() {
+        let save = SaveGame::new(
+            Duration::ZERO,
+            ZoomLevel::Galaxy,
+            Position::new(),
+            WorldState::new(),
+        );
+        assert_eq!(save.version, SAVE_VERSION);
+        let mut json = serde_json::to_value(&save).unwrap();
+        json.as_object_mut().unwrap().remove("version");
+
+        let loaded = SaveGame::from_json(&json.to_string()).unwrap();
+        assert_eq!(loaded.version, SAVE_VERSION);
+
+        json["version"] = serde_json::json!(SAVE_VERSION + 1);
+        assert_eq!(
+            SaveGame::from_json(&json.to_string()).unwrap_err(),
+            MigrationError::TooNew(SAVE_VERSION + 1)
+        );
+        assert!(matches!(
+            SaveGame::from_json("{\"version\": 1, \"world\": "),
+            Err(MigrationError::Corrupt(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_missing_save_fails() {
+        let dir = test_dir("missing");
+        assert!(SaveGame::read_from(&dir.join("nope.json")).is_err());
+    }
+}
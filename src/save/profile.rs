@@ -0,0 +1,54 @@
+use super::{Settings, ensure_dir};
+use crate::game::tutorial::Tutorial;
+use crate::render::GlyphSet;
+use crate::result::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const PROFILE_FILE: &str = "profile.json";
+
+/// Player-wide state that outlives any single save
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Profile {
+    /// Most recently written save, autosave or manual
+    pub last_save: Option<PathBuf>,
+    /// Settings for every game that its save doesn't override
+    #[serde(default)]
+    pub settings: Settings,
+    /// Symbols maps are drawn with
+    #[serde(default)]
+    pub glyphs: GlyphSet,
+    /// Map overlays the player has defined, in the order they cycle
+    #[serde(default)]
+    pub overlays: Vec<CustomOverlay>,
+    /// How far the player has got through the first-run tutorial
+    #[serde(default)]
+    pub tutorial: Tutorial,
+}
+
+/// A named query drawn over the planet map as a heatmap
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomOverlay {
+    pub name: String,
+    /// Expression worked out for each region; see `modding::query`
+    pub expression: String,
+}
+
+impl Profile {
+    /// Load the profile, falling back to a fresh one if it is missing or
+    /// unreadable so a damaged profile never blocks starting the game
+    pub fn load(dir: &Path) -> Self {
+        fs::read_to_string(dir.join(PROFILE_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn store(&self, dir: &Path) -> Result<()> {
+        ensure_dir(dir)?;
+        let path = dir.join(PROFILE_FILE);
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&path, json).map_err(|source| Error::SaveFileError { path, source })
+    }
+}
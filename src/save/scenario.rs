@@ -0,0 +1,116 @@
+use crate::game::WorldState;
+use crate::game::campaign::Campaign;
+use crate::game::schedule::ScriptedEvent;
+use crate::result::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use super::{ensure_dir, read_json, unix_now, write_json};
+
+/// Subdirectory of the data directory holding scenario files
+pub const SCENARIO_DIR: &str = "scenarios";
+
+/// A hand-built starting world, authored in the sandbox editor.
+///
+/// Unlike a [`super::SaveGame`] a scenario carries no view or clock state;
+/// starting one begins a fresh session in the stored world.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    /// Wall-clock time the scenario was written, in seconds since the Unix epoch
+    pub created_at: u64,
+    pub world: WorldState,
+    /// Authored events, dated from the start of the scenario
+    #[serde(default)]
+    pub events: Vec<ScriptedEvent>,
+    /// Chapters to play through, starting when the scenario begins
+    #[serde(default)]
+    pub campaign: Option<Campaign>,
+    /// Multiplier on the chance of random events, replacing the one the
+    /// world was built with; 0 turns them off
+    #[serde(default)]
+    pub event_frequency: Option<f64>,
+}
+
+impl Scenario {
+    pub fn new(name: impl Into<String>, world: WorldState) -> Self {
+        Self {
+            name: name.into(),
+            created_at: unix_now(),
+            world,
+            events: Vec::new(),
+            campaign: None,
+            event_frequency: None,
+        }
+    }
+
+    /// File name for a scenario: its name lowercased, with runs of other
+    /// characters collapsed to dashes
+    pub fn file_name(name: &str) -> String {
+        let mut slug = String::new();
+        for c in name.chars() {
+            if c.is_ascii_alphanumeric() {
+                slug.push(c.to_ascii_lowercase());
+            } else if !slug.is_empty() && !slug.ends_with('-') {
+                slug.push('-');
+            }
+        }
+        let slug = slug.trim_end_matches('-');
+        if slug.is_empty() {
+            String::from("scenario.json")
+        } else {
+            format!("{}.json", slug)
+        }
+    }
+
+    /// Write into the scenarios folder of a data directory, returning the path
+    pub fn store(&self, data_dir: &Path) -> Result<PathBuf> {
+        let dir = data_dir.join(SCENARIO_DIR);
+        ensure_dir(&dir)?;
+        let path = dir.join(Self::file_name(&self.name));
+        self.write_to(&path)?;
+        Ok(path)
+    }
+
+    pub fn write_to(&self, path: &Path) -> Result<()> {
+        write_json(path, self)
+    }
+
+    pub fn read_from(path: &Path) -> Result<Self> {
+        read_json(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::save::test_dir;
+    use crate::zoom::ZoomLevel;
+
+    #[test]
+    fn test_file_name_is_slugged() {
+        assert_eq!(
+            Scenario::file_name("Andromeda Prime"),
+            "andromeda-prime.json"
+        );
+        assert_eq!(
+            Scenario::file_name("  Trade Wars: II! "),
+            "trade-wars-ii.json"
+        );
+        assert_eq!(Scenario::file_name("???"), "scenario.json");
+    }
+
+    #[test]
+    fn test_store_round_trip() {
+        let dir = test_dir("scenario");
+        let mut world = WorldState::new();
+        let id = world.spawn(ZoomLevel::SolarSystem).unwrap();
+
+        let path = Scenario::new("Frontier", world).store(&dir).unwrap();
+        assert_eq!(path, dir.join(SCENARIO_DIR).join("frontier.json"));
+
+        let loaded = Scenario::read_from(&path).unwrap();
+        assert_eq!(loaded.name, "Frontier");
+        assert!(loaded.world.get_system(id).is_some());
+    }
+}
@@ -0,0 +1,95 @@
+use super::ensure_dir;
+use crate::result::{Error, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const LOCK_FILE: &str = "session.lock";
+
+/// Marker file present while a session is running.
+///
+/// The lock is only removed by [`SessionLock::release`] on a clean exit, so
+/// finding one at startup means the previous session died unexpectedly.
+pub struct SessionLock {
+    path: PathBuf,
+    previous_crashed: bool,
+}
+
+impl SessionLock {
+    pub fn acquire(dir: &Path) -> Result<Self> {
+        ensure_dir(dir)?;
+        let path = dir.join(LOCK_FILE);
+        let previous_crashed = path.exists();
+
+        fs::write(&path, std::process::id().to_string()).map_err(|source| {
+            Error::SaveFileError {
+                path: path.clone(),
+                source,
+            }
+        })?;
+
+        Ok(Self {
+            path,
+            previous_crashed,
+        })
+    }
+
+    pub fn previous_crashed(&self) -> bool {
+        self.previous_crashed
+    }
+
+    /// Give the lock up without having run a session, such as when
+    /// start-up fails. A crashed session's lock is kept for the next start
+    /// to find; otherwise this releases it.
+    pub fn abandon(self) -> Result<()> {
+        if self.previous_crashed {
+            return Ok(());
+        }
+        self.release()
+    }
+
+    pub fn release(self) -> Result<()> {
+        fs::remove_file(&self.path).map_err(|source| Error::SaveFileError {
+            path: self.path.clone(),
+            source,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::save::test_dir;
+
+    #[test]
+    fn test_clean_release_is_not_a_crash() {
+        let dir = test_dir("session-clean");
+        let lock = SessionLock::acquire(&dir).unwrap();
+        assert!(!lock.previous_crashed());
+        lock.release().unwrap();
+
+        let lock = SessionLock::acquire(&dir).unwrap();
+        assert!(!lock.previous_crashed());
+    }
+
+    #[test]
+    fn test_unreleased_lock_is_a_crash() {
+        let dir = test_dir("session-crash");
+        let lock = SessionLock::acquire(&dir).unwrap();
+        drop(lock);
+
+        let lock = SessionLock::acquire(&dir).unwrap();
+        assert!(lock.previous_crashed());
+    }
+
+    #[test]
+    fn test_abandoned_lock_is_left_as_found() {
+        let dir = test_dir("session-abandon");
+        SessionLock::acquire(&dir).unwrap().abandon().unwrap();
+        let lock = SessionLock::acquire(&dir).unwrap();
+        assert!(!lock.previous_crashed());
+
+        drop(lock);
+        SessionLock::acquire(&dir).unwrap().abandon().unwrap();
+        assert!(SessionLock::acquire(&dir).unwrap().previous_crashed());
+    }
+}
@@ -0,0 +1,238 @@
+use super::Autosave;
+use crate::economy::{Difficulty, Retention};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// Gameplay settings that can be changed mid-game.
+///
+/// Each value comes from the first of these that sets it: the current
+/// save's [`SettingsOverrides`], the player's profile, then the built-in
+/// default.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Multiplier on what firms produce
+    pub productivity: f64,
+    /// Multiplier on what households eat
+    pub appetite: f64,
+    /// Pause when a scripted event fires
+    pub pause_on_events: bool,
+    /// Pause when a campaign objective or chapter is completed
+    pub pause_on_objectives: bool,
+    /// Simulated minutes between autosaves
+    pub autosave_minutes: u32,
+    /// Days of price history each market keeps
+    pub price_history_days: u32,
+}
+
+impl Settings {
+    pub fn difficulty(&self) -> Difficulty {
+        Difficulty {
+            productivity: self.productivity,
+            appetite: self.appetite,
+        }
+    }
+
+    pub fn autosave_interval(&self) -> Duration {
+        Duration::from_secs(u64::from(self.autosave_minutes) * 60)
+    }
+
+    pub fn history_retention(&self) -> Retention {
+        Retention {
+            days: self.price_history_days as usize,
+            ..Retention::default()
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            productivity: 1.0,
+            appetite: 1.0,
+            pause_on_events: false,
+            pause_on_objectives: false,
+            autosave_minutes: (Autosave::DEFAULT_INTERVAL.as_secs() / 60) as u32,
+            price_history_days: Retention::default().days as u32,
+        }
+    }
+}
+
+/// Where a setting's effective value comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    Profile,
+    Save,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Source::Default => "default",
+            Source::Profile => "profile",
+            Source::Save => "this save",
+        })
+    }
+}
+
+/// Settings one save changes from the profile; unset values follow the
+/// profile
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SettingsOverrides {
+    pub productivity: Option<f64>,
+    pub appetite: Option<f64>,
+    pub pause_on_events: Option<bool>,
+    pub pause_on_objectives: Option<bool>,
+    pub autosave_minutes: Option<u32>,
+    pub price_history_days: Option<u32>,
+}
+
+impl SettingsOverrides {
+    /// The overrides that turn `profile` into `desired`, leaving out any
+    /// value the profile already has
+    pub fn between(profile: &Settings, desired: &Settings) -> Self {
+        fn differing<T: PartialEq + Copy>(profile: T, desired: T) -> Option<T> {
+            (profile != desired).then_some(desired)
+        }
+
+        Self {
+            productivity: differing(profile.productivity, desired.productivity),
+            appetite: differing(profile.appetite, desired.appetite),
+            pause_on_events: differing(profile.pause_on_events, desired.pause_on_events),
+            pause_on_objectives: differing(
+                profile.pause_on_objectives,
+                desired.pause_on_objectives,
+            ),
+            autosave_minutes: differing(profile.autosave_minutes, desired.autosave_minutes),
+            price_history_days: differing(profile.price_history_days, desired.price_history_days),
+        }
+    }
+
+    /// Effective settings for a save played under `profile`
+    pub fn apply(&self, profile: &Settings) -> Settings {
+        Settings {
+            productivity: self.productivity.unwrap_or(profile.productivity),
+            appetite: self.appetite.unwrap_or(profile.appetite),
+            pause_on_events: self.pause_on_events.unwrap_or(profile.pause_on_events),
+            pause_on_objectives: self
+                .pause_on_objectives
+                .unwrap_or(profile.pause_on_objectives),
+            autosave_minutes: self.autosave_minutes.unwrap_or(profile.autosave_minutes),
+            price_history_days: self
+                .price_history_days
+                .unwrap_or(profile.price_history_days),
+        }
+    }
+
+    /// Where each setting's effective value comes from, in field order
+    pub fn sources(&self, profile: &Settings) -> SettingSources {
+        fn source<T: PartialEq>(overridden: bool, profile: T, default: T) -> Source {
+            if overridden {
+                Source::Save
+            } else if profile != default {
+                Source::Profile
+            } else {
+                Source::Default
+            }
+        }
+
+        let default = Settings::default();
+        SettingSources {
+            productivity: source(
+                self.productivity.is_some(),
+                profile.productivity,
+                default.productivity,
+            ),
+            appetite: source(self.appetite.is_some(), profile.appetite, default.appetite),
+            pause_on_events: source(
+                self.pause_on_events.is_some(),
+                profile.pause_on_events,
+                default.pause_on_events,
+            ),
+            pause_on_objectives: source(
+                self.pause_on_objectives.is_some(),
+                profile.pause_on_objectives,
+                default.pause_on_objectives,
+            ),
+            autosave_minutes: source(
+                self.autosave_minutes.is_some(),
+                profile.autosave_minutes,
+                default.autosave_minutes,
+            ),
+            price_history_days: source(
+                self.price_history_days.is_some(),
+                profile.price_history_days,
+                default.price_history_days,
+            ),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// The [`Source`] of every setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SettingSources {
+    pub productivity: Source,
+    pub appetite: Source,
+    pub pause_on_events: Source,
+    pub pause_on_objectives: Source,
+    pub autosave_minutes: Source,
+    pub price_history_days: Source,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_overrides_take_precedence() {
+        let profile = Settings {
+            productivity: 1.5,
+            ..Settings::default()
+        };
+        let overrides = SettingsOverrides {
+            autosave_minutes: Some(10),
+            ..SettingsOverrides::default()
+        };
+
+        let settings = overrides.apply(&profile);
+        assert_eq!(settings.productivity, 1.5);
+        assert_eq!(settings.autosave_minutes, 10);
+        assert_eq!(settings.appetite, 1.0);
+
+        let sources = overrides.sources(&profile);
+        assert_eq!(sources.productivity, Source::Profile);
+        assert_eq!(sources.autosave_minutes, Source::Save);
+        assert_eq!(sources.appetite, Source::Default);
+    }
+
+    #[test]
+    fn test_overrides_only_record_differences() {
+        let profile = Settings::default();
+        let desired = Settings {
+            pause_on_events: true,
+            ..profile
+        };
+
+        let overrides = SettingsOverrides::between(&profile, &desired);
+        assert_eq!(overrides.pause_on_events, Some(true));
+        assert_eq!(overrides.productivity, None);
+        assert!(SettingsOverrides::between(&profile, &profile).is_empty());
+    }
+
+    #[test]
+    fn test_missing_values_parse_as_defaults() {
+        let settings: Settings = serde_json::from_str(r#"{"appetite": 2.0}"#).unwrap();
+        assert_eq!(settings.appetite, 2.0);
+        assert_eq!(settings.autosave_minutes, 30);
+
+        let overrides: SettingsOverrides = serde_json::from_str("{}").unwrap();
+        assert!(overrides.is_empty());
+    }
+}
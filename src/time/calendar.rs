@@ -0,0 +1,173 @@
+use std::fmt;
+use std::time::Duration;
+
+pub const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+pub const DAYS_PER_MONTH: u32 = 30;
+pub const MONTHS_PER_YEAR: u32 = MONTH_NAMES.len() as u32;
+pub const DAYS_PER_YEAR: u32 = DAYS_PER_MONTH * MONTHS_PER_YEAR;
+
+/// A date on the in-game calendar. Months and days are 1-based.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CalendarDate {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl CalendarDate {
+    pub fn month_name(&self) -> &'static str {
+        MONTH_NAMES[(self.month - 1) as usize]
+    }
+
+    /// 1-based day within the year, useful for seasonal cycles
+    pub fn day_of_year(&self) -> u32 {
+        (self.month - 1) * DAYS_PER_MONTH + self.day
+    }
+
+    pub fn is_month_start(&self) -> bool {
+        self.day == 1
+    }
+
+    pub fn is_year_start(&self) -> bool {
+        self.month == 1 && self.day == 1
+    }
+}
+
+impl fmt::Display for CalendarDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:02}, Y{}", self.month_name(), self.day, self.year)
+    }
+}
+
+/// Converts raw simulation time into calendar dates.
+///
+/// The calendar is deliberately regular (12 months of 30 days) so economic
+/// cycles such as harvests or tax seasons can key off month/day values
+/// without worrying about leap years.
+#[derive(Debug, Clone, Copy)]
+pub struct Calendar {
+    epoch_year: i64,
+    day_length: Duration,
+}
+
+impl Calendar {
+    pub const DEFAULT_EPOCH_YEAR: i64 = 3000;
+    pub const DEFAULT_DAY_LENGTH: Duration = Duration::from_secs(86_400);
+
+    pub fn new(epoch_year: i64, day_length: Duration) -> Self {
+        assert!(
+            !day_length.is_zero(),
+            "calendar day length must be non-zero"
+        );
+        Self {
+            epoch_year,
+            day_length,
+        }
+    }
+
+    pub fn day_length(&self) -> Duration {
+        self.day_length
+    }
+
+    /// Whole days elapsed since the epoch
+    pub fn days_elapsed(&self, simulation_time: Duration) -> u64 {
+        (simulation_time.as_nanos() / self.day_length.as_nanos()) as u64
+    }
+
+    /// Fraction of the current day that has elapsed, in [0, 1)
+    pub fn time_of_day(&self, simulation_time: Duration) -> f64 {
+        let into_day = simulation_time.as_nanos() % self.day_length.as_nanos();
+        into_day as f64 / self.day_length.as_nanos() as f64
+    }
+
+    pub fn date_at(&self, simulation_time: Duration) -> CalendarDate {
+        let days = self.days_elapsed(simulation_time);
+        let years = days / DAYS_PER_YEAR as u64;
+        let day_of_year = (days % DAYS_PER_YEAR as u64) as u32;
+
+        CalendarDate {
+            year: self.epoch_year + years as i64,
+            month: day_of_year / DAYS_PER_MONTH + 1,
+            day: day_of_year % DAYS_PER_MONTH + 1,
+        }
+    }
+
+    /// Simulation time at which the given date begins
+    pub fn start_of(&self, date: CalendarDate) -> Duration {
+        let years = (date.year - self.epoch_year).max(0) as u64;
+        let days = years * DAYS_PER_YEAR as u64 + (date.day_of_year() - 1) as u64;
+        self.day_length * days as u32
+    }
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_EPOCH_YEAR, Self::DEFAULT_DAY_LENGTH)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(86_400);
+
+    #[test]
+    fn test_epoch_is_first_day() {
+        let calendar = Calendar::default();
+        let date = calendar.date_at(Duration::ZERO);
+        assert_eq!(
+            date,
+            CalendarDate {
+                year: 3000,
+                month: 1,
+                day: 1
+            }
+        );
+        assert!(date.is_year_start());
+    }
+
+    #[test]
+    fn test_month_and_year_rollover() {
+        let calendar = Calendar::default();
+
+        let date = calendar.date_at(DAY * 30);
+        assert_eq!((date.month, date.day), (2, 1));
+        assert!(date.is_month_start());
+
+        let date = calendar.date_at(DAY * 360);
+        assert_eq!((date.year, date.month, date.day), (3001, 1, 1));
+
+        let date = calendar.date_at(DAY * 359 + Duration::from_secs(86_399));
+        assert_eq!((date.year, date.month, date.day), (3000, 12, 30));
+    }
+
+    #[test]
+    fn test_custom_day_length_and_epoch() {
+        let calendar = Calendar::new(1, Duration::from_secs(60));
+        let date = calendar.date_at(Duration::from_secs(60 * 45));
+        assert_eq!((date.year, date.month, date.day), (1, 2, 16));
+        assert!((calendar.time_of_day(Duration::from_secs(90)) - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_start_of_round_trips() {
+        let calendar = Calendar::default();
+        let date = CalendarDate {
+            year: 3002,
+            month: 7,
+            day: 12,
+        };
+        assert_eq!(calendar.date_at(calendar.start_of(date)), date);
+        assert_eq!(date.day_of_year(), 192);
+    }
+
+    #[test]
+    fn test_display() {
+        let calendar = Calendar::default();
+        assert_eq!(calendar.date_at(DAY * 42).to_string(), "Feb 13, Y3000");
+    }
+}
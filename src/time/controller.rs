@@ -1,11 +1,35 @@
 use std::time::{Duration, Instant};
 
+use super::{Calendar, CalendarDate};
+
+pub const MIN_SPEED: f64 = 0.1;
+pub const MAX_SPEED: f64 = 50.0;
+
+/// Multipliers selected directly by the number keys 1 to 5
+pub const SPEED_PRESETS: [f64; 5] = [0.5, 1.0, 5.0, 20.0, MAX_SPEED];
+
+/// Simulation time one world tick advances by. Faster speeds run more
+/// ticks a frame rather than longer ones.
+pub const TICK_LENGTH: Duration = Duration::from_millis(250);
+
+/// Most ticks run in one frame. Time built up beyond them is dropped, and
+/// the clock reports that the simulation is lagging.
+pub const MAX_TICKS_PER_FRAME: u32 = 8;
+
 pub struct TimeController {
     is_paused: bool,
     speed_multiplier: f64,
+    /// Run frames back to back, each advancing a full frame's worth of
+    /// time at maximum speed
+    turbo: bool,
     simulation_time: Duration,
+    /// Scaled time built up since the last whole tick was taken
+    backlog: Duration,
+    /// Whether the last frame had more ticks due than it could run
+    lagging: bool,
     last_update: Instant,
     target_fps: u32,
+    calendar: Calendar,
 }
 
 impl TimeController {
@@ -13,12 +37,21 @@ impl TimeController {
         Self {
             is_paused: true,
             speed_multiplier: 1.0,
+            turbo: false,
             simulation_time: Duration::ZERO,
+            backlog: Duration::ZERO,
+            lagging: false,
             last_update: Instant::now(),
             target_fps,
+            calendar: Calendar::default(),
         }
     }
 
+    /// Frames per second the clock paces itself against
+    pub fn set_target_fps(&mut self, target_fps: u32) {
+        self.target_fps = target_fps.max(1);
+    }
+
     pub fn is_paused(&self) -> bool {
         self.is_paused
     }
@@ -32,7 +65,25 @@ impl TimeController {
         self.speed_multiplier
     }
 
+    /// Set an arbitrary multiplier, clamped to the supported range
+    pub fn set_speed(&mut self, multiplier: f64) {
+        self.turbo = false;
+        self.speed_multiplier = multiplier.clamp(MIN_SPEED, MAX_SPEED);
+    }
+
+    pub fn is_turbo(&self) -> bool {
+        self.turbo
+    }
+
+    /// Fast-forward as quickly as frames can be produced. Any speed change
+    /// ends turbo.
+    pub fn set_turbo(&mut self, turbo: bool) {
+        self.turbo = turbo;
+        self.last_update = Instant::now();
+    }
+
     pub fn increase_speed(&mut self) {
+        self.turbo = false;
         self.speed_multiplier = match self.speed_multiplier {
             x if x < 0.5 => 0.5,
             x if x < 1.0 => 1.0,
@@ -45,6 +96,7 @@ impl TimeController {
     }
 
     pub fn decrease_speed(&mut self) {
+        self.turbo = false;
         self.speed_multiplier = match self.speed_multiplier {
             x if x <= 0.5 => 0.1,
             x if x <= 1.0 => 0.5,
@@ -56,16 +108,28 @@ impl TimeController {
         };
     }
 
-    #[allow(dead_code)]
     pub fn simulation_time(&self) -> Duration {
         self.simulation_time
     }
 
+    /// Jump the clock, e.g. when restoring a saved game
+    pub fn set_simulation_time(&mut self, simulation_time: Duration) {
+        self.simulation_time = simulation_time;
+        self.last_update = Instant::now();
+    }
+
     pub fn delta_time(&self) -> Duration {
+        self.scaled(self.last_update.elapsed())
+    }
+
+    /// Simulation time that `real_delta` of wall-clock time is worth at
+    /// the current speed
+    fn scaled(&self, real_delta: Duration) -> Duration {
         if self.is_paused {
             Duration::ZERO
+        } else if self.turbo {
+            self.frame_duration().mul_f64(MAX_SPEED)
         } else {
-            let real_delta = self.last_update.elapsed();
             let scaled_seconds = real_delta.as_secs_f64() * self.speed_multiplier;
             Duration::from_secs_f64(scaled_seconds)
         }
@@ -82,10 +146,73 @@ impl TimeController {
         delta
     }
 
+    /// Build up the scaled time since the last frame and take the whole
+    /// ticks of [`TICK_LENGTH`] now due, at most [`MAX_TICKS_PER_FRAME`].
+    /// The caller advances the clock as it runs each one. None are due
+    /// while paused.
+    pub fn take_ticks(&mut self) -> u32 {
+        let elapsed = self.last_update.elapsed();
+        self.last_update = Instant::now();
+        self.take_ticks_for(elapsed)
+    }
+
+    /// [`Self::take_ticks`] for a frame `elapsed` of wall-clock time long
+    fn take_ticks_for(&mut self, elapsed: Duration) -> u32 {
+        if self.is_paused {
+            self.backlog = Duration::ZERO;
+            self.lagging = false;
+            return 0;
+        }
+        self.backlog += self.scaled(elapsed);
+
+        let due = self.backlog.as_nanos() / TICK_LENGTH.as_nanos();
+        self.lagging = due > MAX_TICKS_PER_FRAME as u128;
+        let ticks = due.min(MAX_TICKS_PER_FRAME as u128) as u32;
+        self.backlog = if self.lagging {
+            Duration::ZERO
+        } else {
+            self.backlog - TICK_LENGTH * ticks
+        };
+        ticks
+    }
+
+    /// Whether the last frame dropped time it had no room to simulate, so
+    /// the clock runs slower than the speed set
+    pub fn is_lagging(&self) -> bool {
+        self.lagging
+    }
+
+    /// Add simulation time directly, bypassing pause state and speed
+    pub fn advance(&mut self, delta: Duration) {
+        self.simulation_time += delta;
+        self.last_update = Instant::now();
+    }
+
+    /// Real time to wait between frames; none while in turbo
     pub fn target_frame_duration(&self) -> Duration {
+        if self.turbo {
+            Duration::ZERO
+        } else {
+            self.frame_duration()
+        }
+    }
+
+    fn frame_duration(&self) -> Duration {
         Duration::from_secs_f64(1.0 / self.target_fps as f64)
     }
 
+    pub fn calendar(&self) -> &Calendar {
+        &self.calendar
+    }
+
+    pub fn set_calendar(&mut self, calendar: Calendar) {
+        self.calendar = calendar;
+    }
+
+    pub fn current_date(&self) -> CalendarDate {
+        self.calendar.date_at(self.simulation_time)
+    }
+
     pub fn format_time(&self) -> String {
         let total_secs = self.simulation_time.as_secs();
         let days = total_secs / 86400;
@@ -158,6 +285,58 @@ mod tests {
         assert_eq!(controller.speed_multiplier(), 0.1);
     }
 
+    #[test]
+    fn test_turbo_advances_a_fixed_step_per_frame() {
+        let mut controller = TimeController::new(50);
+        controller.toggle_pause();
+        controller.set_turbo(true);
+
+        assert_eq!(controller.target_frame_duration(), Duration::ZERO);
+        assert_eq!(controller.step(), Duration::from_secs(1));
+        assert_eq!(controller.simulation_time(), Duration::from_secs(1));
+
+        controller.set_speed(SPEED_PRESETS[1]);
+        assert!(!controller.is_turbo());
+        assert_eq!(
+            controller.target_frame_duration(),
+            Duration::from_millis(20)
+        );
+    }
+
+    #[test]
+    fn test_ticks_are_batched_by_speed_up_to_a_cap() {
+        let mut controller = TimeController::new(60);
+        controller.toggle_pause();
+        controller.set_speed(MAX_SPEED);
+
+        // A tenth of a second at top speed is five seconds of ticks, too
+        // many for one frame
+        let ms = Duration::from_millis;
+        assert_eq!(controller.take_ticks_for(ms(100)), MAX_TICKS_PER_FRAME);
+        assert!(controller.is_lagging());
+        assert_eq!(controller.backlog, Duration::ZERO);
+
+        // A frame on time runs a few and keeps the remainder for the next
+        let near = |backlog: Duration, expected| backlog.abs_diff(expected) < ms(1);
+        assert_eq!(controller.take_ticks_for(ms(17)), 3);
+        assert!(!controller.is_lagging());
+        assert!(near(controller.backlog, ms(100)));
+        assert_eq!(controller.take_ticks_for(ms(17)), 3);
+        assert!(near(controller.backlog, ms(200)));
+        assert_eq!(controller.take_ticks_for(ms(17)), 4);
+        assert!(near(controller.backlog, ms(50)));
+
+        // At normal speed a frame is far short of a tick
+        controller.set_speed(1.0);
+        controller.backlog = Duration::ZERO;
+        assert_eq!(controller.take_ticks_for(ms(20)), 0);
+        assert!(near(controller.backlog, ms(20)));
+
+        controller.toggle_pause();
+        assert_eq!(controller.take_ticks(), 0);
+        assert_eq!(controller.backlog, Duration::ZERO);
+    }
+
     #[test]
     fn test_paused_time_doesnt_advance() {
         let mut controller = TimeController::new(60);
@@ -186,4 +365,14 @@ mod tests {
         controller.simulation_time = Duration::from_secs(90061);
         assert_eq!(controller.format_time(), "1d 1h 1m 1s");
     }
+
+    #[test]
+    fn test_current_date_follows_simulation_time() {
+        let mut controller = TimeController::new(60);
+        controller.set_calendar(Calendar::new(2100, Duration::from_secs(10)));
+        assert_eq!(controller.current_date().to_string(), "Jan 01, Y2100");
+
+        controller.simulation_time = Duration::from_secs(10 * 31);
+        assert_eq!(controller.current_date().to_string(), "Feb 02, Y2100");
+    }
 }
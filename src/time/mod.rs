@@ -1,3 +1,14 @@
+mod calendar;
 mod controller;
+mod pacer;
+mod profiler;
+mod scheduler;
 
-pub use controller::TimeController;
+pub use calendar::{Calendar, CalendarDate, DAYS_PER_MONTH, DAYS_PER_YEAR};
+pub use controller::{MAX_SPEED, MAX_TICKS_PER_FRAME, SPEED_PRESETS, TICK_LENGTH, TimeController};
+pub use pacer::{FramePacer, MAX_SKIPPED_FRAMES};
+pub use profiler::{
+    FrameBreakdown, Histogram, Profiler, RECENT_FRAMES, Stutter, TickTimings, WORST_TICKS, millis,
+    resident_memory,
+};
+pub use scheduler::{Scheduler, next_multiple};
@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+/// Most render frames skipped in a row, so the screen still refreshes
+/// while the simulation catches up
+pub const MAX_SKIPPED_FRAMES: u32 = 4;
+
+/// Keeps the frame rate steady by sleeping only for what remains of each
+/// frame, and skips drawing while frames take longer than their budget
+#[derive(Debug, Clone, Default)]
+pub struct FramePacer {
+    /// Whether the last frame's work overran its budget
+    behind: bool,
+    skipped: u32,
+}
+
+impl FramePacer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether to draw this frame. Drawing is skipped while behind, up to
+    /// [`MAX_SKIPPED_FRAMES`] times in a row.
+    pub fn should_render(&mut self) -> bool {
+        if self.behind && self.skipped < MAX_SKIPPED_FRAMES {
+            self.skipped += 1;
+            false
+        } else {
+            self.skipped = 0;
+            true
+        }
+    }
+
+    /// Record how long a frame's update and drawing took against its
+    /// budget, returning how long to sleep before the next frame
+    pub fn finish_frame(&mut self, work: Duration, budget: Duration) -> Duration {
+        self.behind = work > budget;
+        budget.saturating_sub(work)
+    }
+
+    /// Whether recent frames have been taking longer than their budget
+    pub fn is_behind(&self) -> bool {
+        self.behind
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUDGET: Duration = Duration::from_millis(33);
+
+    #[test]
+    fn test_sleep_covers_remaining_budget() {
+        let mut pacer = FramePacer::new();
+        assert_eq!(
+            pacer.finish_frame(Duration::from_millis(10), BUDGET),
+            Duration::from_millis(23)
+        );
+        assert!(!pacer.is_behind());
+        assert_eq!(
+            pacer.finish_frame(Duration::from_millis(50), BUDGET),
+            Duration::ZERO
+        );
+        assert!(pacer.is_behind());
+    }
+
+    #[test]
+    fn test_skips_are_bounded_while_behind() {
+        let mut pacer = FramePacer::new();
+        assert!(pacer.should_render());
+
+        pacer.finish_frame(BUDGET * 2, BUDGET);
+        let rendered: Vec<bool> = (0..=MAX_SKIPPED_FRAMES)
+            .map(|_| pacer.should_render())
+            .collect();
+        assert_eq!(
+            rendered.iter().filter(|r| !**r).count(),
+            MAX_SKIPPED_FRAMES as usize
+        );
+        assert_eq!(rendered.last(), Some(&true));
+
+        pacer.finish_frame(Duration::ZERO, BUDGET);
+        assert!(pacer.should_render());
+    }
+}
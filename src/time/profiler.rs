@@ -0,0 +1,379 @@
+//! Frame and tick timings gathered over a session.
+//!
+//! Every frame's work and every simulation tick is counted in a histogram,
+//! so percentiles can be quoted for a whole session without keeping each
+//! sample. The slowest ticks are kept along with the system that took
+//! longest in each, so a stutter can be traced to what caused it. The
+//! latest frames are also kept whole, split into the stages of the frame,
+//! for the debug overlay.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Upper bound of a histogram's first bucket
+const FIRST_BUCKET: Duration = Duration::from_micros(10);
+/// Buckets for each doubling in duration
+const BUCKETS_PER_DOUBLING: u32 = 4;
+/// Buckets in a histogram; the last one catches anything over about 0.6s
+const BUCKET_COUNT: usize = 64;
+
+/// Slowest ticks kept for the stutter report
+pub const WORST_TICKS: usize = 5;
+
+/// Latest frames the debug overlay averages over
+pub const RECENT_FRAMES: usize = 30;
+
+/// Where one frame's time went
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameBreakdown {
+    /// Reading and acting on input
+    pub input: Duration,
+    /// Advancing the simulation
+    pub update: Duration,
+    /// Drawing the frame into the back buffer
+    pub render: Duration,
+    /// Writing the frame to the terminal
+    pub present: Duration,
+    /// Simulation ticks run during the frame
+    pub ticks: u32,
+}
+
+impl FrameBreakdown {
+    pub fn total(&self) -> Duration {
+        self.input + self.update + self.render + self.present
+    }
+}
+
+/// How long each system took during one tick, in the order they ran
+#[derive(Debug, Clone, Default)]
+pub struct TickTimings {
+    spans: Vec<(&'static str, Duration)>,
+    lap_started: Option<Instant>,
+}
+
+impl TickTimings {
+    /// Start timing a tick; each [`TickTimings::lap`] closes one system
+    pub fn start() -> Self {
+        Self {
+            spans: Vec::new(),
+            lap_started: Some(Instant::now()),
+        }
+    }
+
+    /// Credit the time since the last lap to a system
+    pub fn lap(&mut self, system: &'static str) {
+        let now = Instant::now();
+        if let Some(started) = self.lap_started {
+            self.record(system, now - started);
+        }
+        self.lap_started = Some(now);
+    }
+
+    pub fn record(&mut self, system: &'static str, took: Duration) {
+        self.spans.push((system, took));
+    }
+
+    pub fn spans(&self) -> &[(&'static str, Duration)] {
+        &self.spans
+    }
+
+    pub fn total(&self) -> Duration {
+        self.spans.iter().map(|&(_, took)| took).sum()
+    }
+
+    /// The system that took longest, if any ran
+    pub fn slowest(&self) -> Option<(&'static str, Duration)> {
+        self.spans.iter().copied().max_by_key(|&(_, took)| took)
+    }
+}
+
+/// Counts of durations in buckets that grow geometrically, so short and
+/// long samples are both resolved to within about a fifth
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    buckets: [u64; BUCKET_COUNT],
+    count: u64,
+    max: Duration,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            buckets: [0; BUCKET_COUNT],
+            count: 0,
+            max: Duration::ZERO,
+        }
+    }
+}
+
+impl Histogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, sample: Duration) {
+        self.buckets[Self::bucket(sample)] += 1;
+        self.count += 1;
+        self.max = self.max.max(sample);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// The duration a share of samples, from 0.0 to 1.0, took no longer
+    /// than, rounded up to its bucket. Zero with no samples.
+    pub fn percentile(&self, share: f64) -> Duration {
+        let rank = ((share * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, &count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Self::upper_bound(index).min(self.max);
+            }
+        }
+        self.max
+    }
+
+    fn bucket(sample: Duration) -> usize {
+        if sample <= FIRST_BUCKET {
+            return 0;
+        }
+        let doublings = (sample.as_secs_f64() / FIRST_BUCKET.as_secs_f64()).log2();
+        ((doublings * BUCKETS_PER_DOUBLING as f64).ceil() as usize).min(BUCKET_COUNT - 1)
+    }
+
+    fn upper_bound(index: usize) -> Duration {
+        if index == BUCKET_COUNT - 1 {
+            return Duration::MAX;
+        }
+        FIRST_BUCKET.mul_f64(2f64.powf(index as f64 / BUCKETS_PER_DOUBLING as f64))
+    }
+}
+
+/// One of the slowest ticks of the session
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stutter {
+    pub tick: u64,
+    pub took: Duration,
+    /// The system that took longest during the tick
+    pub system: Option<(&'static str, Duration)>,
+}
+
+/// Frame and tick timings for a whole session
+#[derive(Debug, Clone, Default)]
+pub struct Profiler {
+    frames: Histogram,
+    ticks: Histogram,
+    /// Slowest first
+    worst: Vec<Stutter>,
+    /// Oldest first
+    recent: VecDeque<FrameBreakdown>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record how long a frame's update and drawing took
+    pub fn record_frame(&mut self, work: Duration) {
+        self.frames.record(work);
+    }
+
+    /// Keep a frame's breakdown for the debug overlay
+    pub fn record_breakdown(&mut self, frame: FrameBreakdown) {
+        if self.recent.len() == RECENT_FRAMES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(frame);
+    }
+
+    /// The latest frame's breakdown, if one has been recorded
+    pub fn last_frame(&self) -> Option<FrameBreakdown> {
+        self.recent.back().copied()
+    }
+
+    /// How many frames [`Profiler::recent_frames`] covers
+    pub fn recent_len(&self) -> usize {
+        self.recent.len()
+    }
+
+    /// The mean of the latest frames' breakdowns; ticks are totalled
+    pub fn recent_frames(&self) -> FrameBreakdown {
+        let count = self.recent.len().max(1) as u32;
+        let mut sum = self
+            .recent
+            .iter()
+            .fold(FrameBreakdown::default(), |sum, frame| FrameBreakdown {
+                input: sum.input + frame.input,
+                update: sum.update + frame.update,
+                render: sum.render + frame.render,
+                present: sum.present + frame.present,
+                ticks: sum.ticks + frame.ticks,
+            });
+        sum.input /= count;
+        sum.update /= count;
+        sum.render /= count;
+        sum.present /= count;
+        sum
+    }
+
+    pub fn record_tick(&mut self, tick: u64, timings: &TickTimings) {
+        let took = timings.total();
+        self.ticks.record(took);
+        if self.worst.len() == WORST_TICKS && self.worst.last().is_some_and(|s| s.took >= took) {
+            return;
+        }
+        let stutter = Stutter {
+            tick,
+            took,
+            system: timings.slowest(),
+        };
+        let at = self.worst.partition_point(|s| s.took >= took);
+        self.worst.insert(at, stutter);
+        self.worst.truncate(WORST_TICKS);
+    }
+
+    pub fn frames(&self) -> &Histogram {
+        &self.frames
+    }
+
+    pub fn ticks(&self) -> &Histogram {
+        &self.ticks
+    }
+
+    /// The slowest ticks so far, slowest first
+    pub fn worst(&self) -> &[Stutter] {
+        &self.worst
+    }
+
+    /// Percentiles for frames and ticks and the slowest ticks, as shown on
+    /// the profiler screen and printed when the game exits
+    pub fn report(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "{:<7}{:>8}{:>9}{:>9}{:>9}{:>9}",
+            "", "count", "p50", "p95", "p99", "max"
+        )];
+        for (name, histogram) in [("Frames", &self.frames), ("Ticks", &self.ticks)] {
+            lines.push(format!(
+                "{:<7}{:>8}{:>9}{:>9}{:>9}{:>9}",
+                name,
+                histogram.count(),
+                millis(histogram.percentile(0.50)),
+                millis(histogram.percentile(0.95)),
+                millis(histogram.percentile(0.99)),
+                millis(histogram.max()),
+            ));
+        }
+        if !self.worst.is_empty() {
+            lines.push(String::new());
+            lines.push(String::from("Slowest ticks"));
+        }
+        for stutter in &self.worst {
+            let system = stutter.system.map_or(String::new(), |(name, took)| {
+                format!("  mostly {} {}", name, millis(took))
+            });
+            lines.push(format!(
+                "  tick {:<8}{:>9}{}",
+                stutter.tick,
+                millis(stutter.took),
+                system
+            ));
+        }
+        lines
+    }
+}
+
+pub fn millis(duration: Duration) -> String {
+    format!("{:.2}ms", duration.as_secs_f64() * 1000.0)
+}
+
+/// Memory the process has resident, in bytes, where the platform says
+pub fn resident_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kilobytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_percentiles_round_up_to_a_bucket() {
+        let mut histogram = Histogram::new();
+        assert_eq!(histogram.percentile(0.5), Duration::ZERO);
+
+        for _ in 0..90 {
+            histogram.record(ms(2));
+        }
+        for _ in 0..9 {
+            histogram.record(ms(20));
+        }
+        histogram.record(ms(200));
+
+        let p50 = histogram.percentile(0.50);
+        assert!(p50 >= ms(2) && p50 < ms(3), "{:?}", p50);
+        let p95 = histogram.percentile(0.95);
+        assert!(p95 >= ms(20) && p95 < ms(24), "{:?}", p95);
+        assert_eq!(histogram.percentile(1.0), ms(200));
+        assert_eq!(histogram.max(), ms(200));
+        assert_eq!(histogram.count(), 100);
+    }
+
+    #[test]
+    fn test_recent_frames_are_averaged() {
+        let mut profiler = Profiler::new();
+        assert_eq!(profiler.last_frame(), None);
+        assert_eq!(profiler.recent_frames(), FrameBreakdown::default());
+
+        let frame = |millis: u64, ticks: u32| FrameBreakdown {
+            update: Duration::from_millis(millis),
+            present: Duration::from_millis(2),
+            ticks,
+            ..FrameBreakdown::default()
+        };
+        for _ in 0..RECENT_FRAMES {
+            profiler.record_breakdown(frame(100, 3));
+        }
+        profiler.record_breakdown(frame(10, 1));
+        profiler.record_breakdown(frame(10, 1));
+
+        assert_eq!(profiler.last_frame(), Some(frame(10, 1)));
+        let recent = profiler.recent_frames();
+        assert_eq!(recent.update, Duration::from_millis(94));
+        assert_eq!(recent.total(), Duration::from_millis(96));
+        assert_eq!(recent.ticks, 3 * (RECENT_FRAMES as u32 - 2) + 2);
+    }
+
+    #[test]
+    fn test_keeps_the_slowest_ticks_and_their_worst_system() {
+        let mut profiler = Profiler::new();
+        for tick in 1..=20 {
+            let mut timings = TickTimings::default();
+            timings.record("markets", ms(tick));
+            timings.record("census", ms(1));
+            profiler.record_tick(tick, &timings);
+        }
+
+        let ticks: Vec<u64> = profiler.worst().iter().map(|s| s.tick).collect();
+        assert_eq!(ticks, vec![20, 19, 18, 17, 16]);
+        assert_eq!(profiler.worst()[0].took, ms(21));
+        assert_eq!(profiler.worst()[0].system, Some(("markets", ms(20))));
+        assert_eq!(profiler.ticks().count(), 20);
+
+        let report = profiler.report();
+        assert!(report[2].starts_with("Ticks"));
+        assert!(report.iter().any(|line| line.contains("tick 20")));
+    }
+}
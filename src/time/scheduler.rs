@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Events waiting for a point in simulation time, soonest first.
+///
+/// Subsystems register what should happen later ("harvest in 30 days",
+/// "ship arrives at tick N") and take back whatever is due as the clock
+/// passes it, instead of checking every tick. Events due at the same time
+/// come back in the order they were added.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scheduler<E> {
+    pending: Vec<(Duration, E)>,
+}
+
+impl<E> Default for Scheduler<E> {
+    fn default() -> Self {
+        Self {
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<E> Scheduler<E> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fire `event` once the simulation reaches `at`
+    pub fn schedule_at(&mut self, at: Duration, event: E) {
+        let index = self.pending.partition_point(|(time, _)| *time <= at);
+        self.pending.insert(index, (at, event));
+    }
+
+    /// Fire `event` once `delay` has passed after `now`
+    pub fn schedule_in(&mut self, now: Duration, delay: Duration, event: E) {
+        self.schedule_at(now + delay, event);
+    }
+
+    /// Remove and return every event due by `now`, in order
+    pub fn take_due(&mut self, now: Duration) -> Vec<E> {
+        let due = self.pending.partition_point(|(time, _)| *time <= now);
+        self.pending.drain(..due).map(|(_, event)| event).collect()
+    }
+
+    /// When the soonest event is due
+    pub fn next_due(&self) -> Option<Duration> {
+        self.pending.first().map(|(time, _)| *time)
+    }
+
+    /// Pending events with their times, soonest first
+    pub fn iter(&self) -> impl Iterator<Item = (Duration, &E)> {
+        self.pending.iter().map(|(time, event)| (*time, event))
+    }
+
+    /// Drop every pending event `keep` returns false for
+    pub fn retain(&mut self, mut keep: impl FnMut(&E) -> bool) {
+        self.pending.retain(|(_, event)| keep(event));
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+/// The first multiple of `interval` after `now`, for events that recur on a
+/// fixed cadence however late they were handled
+pub fn next_multiple(now: Duration, interval: Duration) -> Duration {
+    if interval.is_zero() {
+        return now;
+    }
+    let periods = now.as_nanos() / interval.as_nanos() + 1;
+    Duration::from_nanos((periods * interval.as_nanos()) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn test_releases_due_events_in_order() {
+        let mut scheduler = Scheduler::new();
+        scheduler.schedule_at(SECOND * 20, "second");
+        scheduler.schedule_at(SECOND * 10, "first");
+        scheduler.schedule_in(SECOND * 10, SECOND * 20, "third");
+        scheduler.schedule_at(SECOND * 20, "also second");
+
+        assert_eq!(scheduler.next_due(), Some(SECOND * 10));
+        assert_eq!(
+            scheduler.take_due(SECOND * 20),
+            vec!["first", "second", "also second"]
+        );
+        assert!(scheduler.take_due(SECOND * 29).is_empty());
+        assert_eq!(scheduler.take_due(SECOND * 30), vec!["third"]);
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn test_retain_cancels_events() {
+        let mut scheduler = Scheduler::new();
+        scheduler.schedule_at(SECOND, 1);
+        scheduler.schedule_at(SECOND * 2, 2);
+        scheduler.retain(|event| *event != 1);
+        assert_eq!(scheduler.iter().collect::<Vec<_>>(), vec![(SECOND * 2, &2)]);
+    }
+
+    #[test]
+    fn test_next_multiple_skips_missed_periods() {
+        let day = SECOND * 10;
+        assert_eq!(next_multiple(Duration::ZERO, day), day);
+        assert_eq!(next_multiple(day, day), day * 2);
+        assert_eq!(next_multiple(day * 5 + SECOND, day), day * 6);
+    }
+}
@@ -0,0 +1,375 @@
+use super::{TextEdit, TextInput};
+
+/// What a field accepts; values are validated when the form is committed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldKind {
+    /// Non-empty text of at most `max_len` characters
+    Text {
+        max_len: usize,
+    },
+    Integer {
+        min: i64,
+        max: i64,
+    },
+    Number {
+        min: f64,
+        max: f64,
+    },
+    /// `yes` or `no`
+    Flag,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Text(String),
+    Integer(i64),
+    Number(f64),
+    Flag(bool),
+}
+
+/// A labelled input within a [`Form`]
+#[derive(Debug, Clone)]
+pub struct Field {
+    key: &'static str,
+    label: String,
+    kind: FieldKind,
+    input: TextInput,
+    error: Option<String>,
+}
+
+impl Field {
+    pub fn new(key: &'static str, label: &str, kind: FieldKind, initial: &str) -> Self {
+        Self {
+            key,
+            label: label.to_string(),
+            kind,
+            input: TextInput::with_text(initial),
+            error: None,
+        }
+    }
+
+    pub fn text(key: &'static str, label: &str, initial: &str) -> Self {
+        Self::new(key, label, FieldKind::Text { max_len: 32 }, initial)
+    }
+
+    pub fn integer(key: &'static str, label: &str, initial: i64, min: i64, max: i64) -> Self {
+        Self::new(
+            key,
+            label,
+            FieldKind::Integer { min, max },
+            &initial.to_string(),
+        )
+    }
+
+    pub fn number(key: &'static str, label: &str, initial: f64, min: f64, max: f64) -> Self {
+        Self::new(
+            key,
+            label,
+            FieldKind::Number { min, max },
+            &format!("{}", initial),
+        )
+    }
+
+    pub fn flag(key: &'static str, label: &str, initial: bool) -> Self {
+        Self::new(
+            key,
+            label,
+            FieldKind::Flag,
+            if initial { "yes" } else { "no" },
+        )
+    }
+
+    pub fn key(&self) -> &'static str {
+        self.key
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    fn parse(&self) -> Result<FieldValue, String> {
+        let raw = self.input.text().trim();
+        match self.kind {
+            FieldKind::Text { max_len } => {
+                if raw.is_empty() {
+                    Err(String::from("required"))
+                } else if raw.chars().count() > max_len {
+                    Err(format!("at most {} characters", max_len))
+                } else {
+                    Ok(FieldValue::Text(raw.to_string()))
+                }
+            }
+            FieldKind::Integer { min, max } => {
+                let value: i64 = raw
+                    .replace('_', "")
+                    .parse()
+                    .map_err(|_| String::from("not a whole number"))?;
+                if (min..=max).contains(&value) {
+                    Ok(FieldValue::Integer(value))
+                } else {
+                    Err(format!("must be {} to {}", min, max))
+                }
+            }
+            FieldKind::Number { min, max } => {
+                let value: f64 = raw
+                    .parse()
+                    .ok()
+                    .filter(|v: &f64| v.is_finite())
+                    .ok_or_else(|| String::from("not a number"))?;
+                if (min..=max).contains(&value) {
+                    Ok(FieldValue::Number(value))
+                } else {
+                    Err(format!("must be {} to {}", min, max))
+                }
+            }
+            FieldKind::Flag => match raw.to_ascii_lowercase().as_str() {
+                "yes" | "y" | "on" | "true" => Ok(FieldValue::Flag(true)),
+                "no" | "n" | "off" | "false" => Ok(FieldValue::Flag(false)),
+                _ => Err(String::from("yes or no")),
+            },
+        }
+    }
+}
+
+/// Values of a committed form, looked up by field key
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FormValues(Vec<(&'static str, FieldValue)>);
+
+impl FormValues {
+    /// Every value in field order
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &FieldValue)> {
+        self.0.iter().map(|(k, v)| (*k, v))
+    }
+
+    pub fn get(&self, key: &str) -> Option<&FieldValue> {
+        self.0.iter().find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
+
+    pub fn text(&self, key: &str) -> Option<&str> {
+        match self.get(key)? {
+            FieldValue::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn integer(&self, key: &str) -> Option<i64> {
+        match self.get(key)? {
+            FieldValue::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn number(&self, key: &str) -> Option<f64> {
+        match self.get(key)? {
+            FieldValue::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn flag(&self, key: &str) -> Option<bool> {
+        match self.get(key)? {
+            FieldValue::Flag(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// A titled list of fields edited one at a time.
+///
+/// Key presses go to the focused field; [`Form::commit`] validates every
+/// field and either returns the parsed values or marks the failing fields
+/// so the form can stay open for correction.
+#[derive(Debug, Clone)]
+pub struct Form {
+    title: String,
+    fields: Vec<Field>,
+    focused: usize,
+}
+
+impl Form {
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            fields: Vec::new(),
+            focused: 0,
+        }
+    }
+
+    pub fn add_field(&mut self, field: Field) {
+        self.fields.push(field);
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    pub fn focused(&self) -> usize {
+        self.focused
+    }
+
+    pub fn focus_next(&mut self) {
+        if !self.fields.is_empty() {
+            self.focused = (self.focused + 1) % self.fields.len();
+        }
+    }
+
+    pub fn focus_previous(&mut self) {
+        if !self.fields.is_empty() {
+            self.focused = (self.focused + self.fields.len() - 1) % self.fields.len();
+        }
+    }
+
+    /// Edit the focused field, clearing any error shown for it
+    pub fn edit(&mut self, edit: TextEdit) {
+        if let Some(field) = self.fields.get_mut(self.focused) {
+            field.input.apply(edit);
+            field.error = None;
+        }
+    }
+
+    /// Validate every field. On failure the first invalid field is focused.
+    pub fn commit(&mut self) -> Option<FormValues> {
+        let mut values = Vec::with_capacity(self.fields.len());
+        let mut first_invalid = None;
+
+        for (i, field) in self.fields.iter_mut().enumerate() {
+            match field.parse() {
+                Ok(value) => {
+                    field.error = None;
+                    values.push((field.key, value));
+                }
+                Err(error) => {
+                    field.error = Some(error);
+                    first_invalid.get_or_insert(i);
+                }
+            }
+        }
+
+        match first_invalid {
+            Some(i) => {
+                self.focused = i;
+                None
+            }
+            None => Some(FormValues(values)),
+        }
+    }
+
+    /// One line per field: a focus marker, the label, the value and any error
+    pub fn lines(&self) -> Vec<String> {
+        let label_width = self
+            .fields
+            .iter()
+            .map(|f| f.label.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        self.fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let focused = i == self.focused;
+                let value = if focused {
+                    field.input.with_cursor()
+                } else {
+                    field.input.text().to_string()
+                };
+                let mut line = format!(
+                    "{} {:<width$}  {}",
+                    if focused { '>' } else { ' ' },
+                    field.label,
+                    value,
+                    width = label_width
+                );
+                if let Some(error) = &field.error {
+                    line.push_str(&format!("  ! {}", error));
+                }
+                line
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn planet_form() -> Form {
+        let mut form = Form::new("Planet");
+        form.add_field(Field::text("name", "Name", "Terra"));
+        form.add_field(Field::integer("population", "Population", 100, 0, 1_000));
+        form
+    }
+
+    fn type_text(form: &mut Form, text: &str) {
+        for c in text.chars() {
+            form.edit(TextEdit::Insert(c));
+        }
+    }
+
+    #[test]
+    fn test_commit_returns_parsed_values() {
+        let mut form = planet_form();
+        type_text(&mut form, " Nova");
+        form.focus_next();
+        form.edit(TextEdit::Backspace);
+
+        let values = form.commit().unwrap();
+        assert_eq!(values.text("name"), Some("Terra Nova"));
+        assert_eq!(values.integer("population"), Some(10));
+        assert_eq!(values.number("population"), None);
+    }
+
+    #[test]
+    fn test_invalid_fields_block_commit() {
+        let mut form = planet_form();
+        type_text(&mut form, &"x".repeat(40));
+        form.focus_next();
+        type_text(&mut form, "00");
+
+        assert!(form.commit().is_none());
+        assert_eq!(form.focused(), 0);
+        assert_eq!(form.fields()[0].error(), Some("at most 32 characters"));
+        assert_eq!(form.fields()[1].error(), Some("must be 0 to 1000"));
+
+        form.focus_previous();
+        form.edit(TextEdit::Backspace);
+        assert_eq!(form.fields()[1].error(), None);
+    }
+
+    #[test]
+    fn test_flags_accept_yes_or_no() {
+        let mut form = Form::new("Options");
+        form.add_field(Field::flag("pause", "Pause", true));
+        assert_eq!(form.commit().unwrap().flag("pause"), Some(true));
+
+        for _ in 0..3 {
+            form.edit(TextEdit::Backspace);
+        }
+        type_text(&mut form, "Off");
+        assert_eq!(form.commit().unwrap().flag("pause"), Some(false));
+
+        type_text(&mut form, "?");
+        assert!(form.commit().is_none());
+        assert_eq!(form.fields()[0].error(), Some("yes or no"));
+    }
+
+    #[test]
+    fn test_lines_mark_focus_and_errors() {
+        let mut form = planet_form();
+        form.focus_next();
+        type_text(&mut form, "x");
+        form.commit();
+
+        assert_eq!(
+            form.lines(),
+            vec![
+                String::from("  Name        Terra"),
+                String::from("> Population  100x_  ! not a whole number"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,91 @@
+/// A list of options to pick one of, each with a line describing it
+#[derive(Debug, Clone)]
+pub struct Menu {
+    title: String,
+    options: Vec<(String, String)>,
+    selected: usize,
+}
+
+impl Menu {
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            options: Vec::new(),
+            selected: 0,
+        }
+    }
+
+    pub fn add_option(&mut self, label: &str, description: &str) {
+        self.options
+            .push((label.to_string(), description.to_string()));
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn select(&mut self, index: usize) {
+        if index < self.options.len() {
+            self.selected = index;
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.options.is_empty() {
+            self.selected = (self.selected + 1) % self.options.len();
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        if !self.options.is_empty() {
+            self.selected = (self.selected + self.options.len() - 1) % self.options.len();
+        }
+    }
+
+    /// One line per option, the selected one marked
+    pub fn lines(&self) -> Vec<String> {
+        let label_width = self
+            .options
+            .iter()
+            .map(|(label, _)| label.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        self.options
+            .iter()
+            .enumerate()
+            .map(|(i, (label, description))| {
+                format!(
+                    "{} {:<width$}  {}",
+                    if i == self.selected { '>' } else { ' ' },
+                    label,
+                    description,
+                    width = label_width
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selection_wraps() {
+        let mut menu = Menu::new("Difficulty");
+        menu.add_option("Easy", "Gentle");
+        menu.add_option("Hard", "Harsh");
+        menu.select_previous();
+        assert_eq!(menu.selected(), 1);
+        menu.select_next();
+        assert_eq!(menu.selected(), 0);
+        menu.select(5);
+        assert_eq!(menu.selected(), 0);
+        assert_eq!(menu.lines(), vec!["> Easy  Gentle", "  Hard  Harsh"]);
+    }
+}
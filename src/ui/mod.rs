@@ -0,0 +1,14 @@
+//! Reusable interactive widgets.
+//!
+//! Widgets hold editing state and produce text lines; the game loop decides
+//! where on the canvas those lines go.
+
+mod form;
+mod menu;
+mod progress;
+mod text_input;
+
+pub use form::{Field, FieldKind, FieldValue, Form, FormValues};
+pub use menu::Menu;
+pub use progress::{Clock, Progress};
+pub use text_input::{TextEdit, TextInput};
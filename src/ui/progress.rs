@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use crate::render::charts;
+
+/// Length of the bar drawn by [`Progress::render`], in cells
+const BAR_WIDTH: usize = 12;
+
+/// Which clock an estimate is measured on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Clock {
+    /// Simulation time, e.g. a deadline in the game's calendar
+    Game,
+    /// Wall-clock time, e.g. how long a fast-forward will keep the player
+    /// waiting
+    Real,
+}
+
+/// How far through an operation something is, with an optional estimate of
+/// the time left
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    label: String,
+    /// From 0 (not started) to 1 (done)
+    fraction: f64,
+    remaining: Option<(Duration, Clock)>,
+}
+
+impl Progress {
+    pub fn new(label: impl Into<String>, done: f64, total: f64) -> Self {
+        let fraction = if total > 0.0 {
+            (done / total).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        Self {
+            label: label.into(),
+            fraction,
+            remaining: None,
+        }
+    }
+
+    /// Show a known amount of time left
+    pub fn with_remaining(mut self, remaining: Duration, clock: Clock) -> Self {
+        self.remaining = Some((remaining, clock));
+        self
+    }
+
+    /// Estimate the time left from how long the work so far has taken,
+    /// assuming the rate stays the same. Nothing is shown until some
+    /// progress has been made.
+    pub fn with_estimate(self, elapsed: Duration, clock: Clock) -> Self {
+        if self.fraction <= 0.0 {
+            return self;
+        }
+        let total = elapsed.div_f64(self.fraction);
+        let remaining = total.saturating_sub(elapsed);
+        self.with_remaining(remaining, clock)
+    }
+
+    pub fn fraction(&self) -> f64 {
+        self.fraction
+    }
+
+    /// e.g. `Label [██████▌     ]  54% ETA 2m 5s`
+    pub fn render(&self) -> String {
+        let mut text = format!(
+            "{} [{}] {:>3.0}%",
+            self.label,
+            charts::bar(self.fraction, 1.0, BAR_WIDTH),
+            self.fraction * 100.0
+        );
+        match self.remaining {
+            Some((remaining, Clock::Real)) => {
+                text.push_str(&format!(" ETA {}", format_real(remaining)));
+            }
+            Some((remaining, Clock::Game)) => {
+                text.push_str(&format!(" {} left", format_game(remaining)));
+            }
+            None => {}
+        }
+        text
+    }
+}
+
+/// Wall-clock time to the second, e.g. `2m 5s`
+fn format_real(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match (secs / 60, secs % 60) {
+        (0, seconds) => format!("{}s", seconds),
+        (minutes, seconds) => format!("{}m {}s", minutes, seconds),
+    }
+}
+
+/// Game time to the hour, e.g. `3d 4h`
+fn format_game(duration: Duration) -> String {
+    let hours = duration.as_secs() / 3_600;
+    match (hours / 24, hours % 24) {
+        (0, hours) => format!("{}h", hours),
+        (days, hours) => format!("{}d {}h", days, hours),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renders_bar_and_time_left() {
+        let progress = Progress::new("Build", 1.0, 2.0)
+            .with_remaining(Duration::from_secs(3 * 86_400 + 4 * 3_600), Clock::Game);
+        assert_eq!(progress.render(), "Build [██████      ]  50% 3d 4h left");
+
+        let done = Progress::new("Save", 5.0, 0.0);
+        assert_eq!(done.fraction(), 1.0);
+    }
+
+    #[test]
+    fn test_estimate_extrapolates_rate() {
+        let progress =
+            Progress::new("Skip", 1.0, 4.0).with_estimate(Duration::from_secs(25), Clock::Real);
+        assert!(progress.render().ends_with("ETA 1m 15s"));
+
+        let unstarted =
+            Progress::new("Skip", 0.0, 4.0).with_estimate(Duration::from_secs(25), Clock::Real);
+        assert!(!unstarted.render().contains("ETA"));
+    }
+}
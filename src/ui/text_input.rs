@@ -0,0 +1,126 @@
+/// A single editing operation on a line of text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEdit {
+    Insert(char),
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Home,
+    End,
+}
+
+/// A one-line text buffer with a cursor
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextInput {
+    text: String,
+    /// Cursor position in characters, from 0 to the text's length
+    cursor: usize,
+}
+
+impl TextInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A buffer holding `text` with the cursor at the end
+    pub fn with_text(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            cursor: text.chars().count(),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    /// Take the text, leaving the buffer empty
+    pub fn take(&mut self) -> String {
+        self.cursor = 0;
+        std::mem::take(&mut self.text)
+    }
+
+    pub fn apply(&mut self, edit: TextEdit) {
+        let len = self.text.chars().count();
+        match edit {
+            TextEdit::Insert(c) => {
+                let at = self.byte_offset(self.cursor);
+                self.text.insert(at, c);
+                self.cursor += 1;
+            }
+            TextEdit::Backspace => {
+                if self.cursor > 0 {
+                    self.cursor -= 1;
+                    let at = self.byte_offset(self.cursor);
+                    self.text.remove(at);
+                }
+            }
+            TextEdit::Delete => {
+                if self.cursor < len {
+                    let at = self.byte_offset(self.cursor);
+                    self.text.remove(at);
+                }
+            }
+            TextEdit::Left => self.cursor = self.cursor.saturating_sub(1),
+            TextEdit::Right => self.cursor = (self.cursor + 1).min(len),
+            TextEdit::Home => self.cursor = 0,
+            TextEdit::End => self.cursor = len,
+        }
+    }
+
+    /// The text with `_` marking the cursor
+    pub fn with_cursor(&self) -> String {
+        let at = self.byte_offset(self.cursor);
+        format!("{}_{}", &self.text[..at], &self.text[at..])
+    }
+
+    fn byte_offset(&self, chars: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(chars)
+            .map_or(self.text.len(), |(i, _)| i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_and_delete_around_cursor() {
+        let mut input = TextInput::with_text("trde");
+        input.apply(TextEdit::Left);
+        input.apply(TextEdit::Left);
+        input.apply(TextEdit::Insert('a'));
+        assert_eq!(input.text(), "trade");
+        assert_eq!(input.with_cursor(), "tra_de");
+
+        input.apply(TextEdit::Delete);
+        input.apply(TextEdit::Backspace);
+        assert_eq!(input.text(), "tre");
+
+        input.apply(TextEdit::Home);
+        input.apply(TextEdit::Backspace);
+        assert_eq!(input.with_cursor(), "_tre");
+    }
+
+    #[test]
+    fn test_multibyte_characters() {
+        let mut input = TextInput::with_text("₵5");
+        input.apply(TextEdit::Left);
+        input.apply(TextEdit::Backspace);
+        assert_eq!(input.text(), "5");
+        assert_eq!(input.take(), "5");
+        assert!(input.is_empty());
+    }
+}
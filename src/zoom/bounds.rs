@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+
+use super::ZoomLevel;
+
+/// What moving off the edge of a map does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Edge {
+    /// The cursor stays where it is
+    Stop,
+    /// Leave for the parent's map and carry on moving there
+    Ascend,
+}
+
+/// The extent of one map, centered on the origin. A map `width` wide runs
+/// from `-width / 2` to `(width - 1) / 2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bounds {
+    pub width: u32,
+    pub height: u32,
+    pub edge: Edge,
+}
+
+impl Bounds {
+    pub const fn new(width: u32, height: u32, edge: Edge) -> Self {
+        Self {
+            width,
+            height,
+            edge,
+        }
+    }
+
+    /// A square map reaching `radius` spots out from the center
+    pub const fn around(radius: u32, edge: Edge) -> Self {
+        Self::new(2 * radius + 1, 2 * radius + 1, edge)
+    }
+
+    pub fn min(&self) -> (i32, i32) {
+        (-(self.width as i32 / 2), -(self.height as i32 / 2))
+    }
+
+    pub fn max(&self) -> (i32, i32) {
+        (
+            (self.width.max(1) as i32 - 1) / 2,
+            (self.height.max(1) as i32 - 1) / 2,
+        )
+    }
+
+    pub fn contains(&self, (x, y): (i32, i32)) -> bool {
+        let (min, max) = (self.min(), self.max());
+        (min.0..=max.0).contains(&x) && (min.1..=max.1).contains(&y)
+    }
+
+    /// The nearest spot on the map
+    pub fn clamp(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        let (min, max) = (self.min(), self.max());
+        (x.clamp(min.0, max.0), y.clamp(min.1, max.1))
+    }
+}
+
+/// The size of the maps at each zoom level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MapBounds {
+    pub galaxy: Bounds,
+    pub system: Bounds,
+    pub planet: Bounds,
+    pub region: Bounds,
+    pub area: Bounds,
+    /// A room's interior
+    pub room: Bounds,
+}
+
+impl MapBounds {
+    pub fn get(&self, level: ZoomLevel) -> Bounds {
+        match level {
+            ZoomLevel::Galaxy => self.galaxy,
+            ZoomLevel::SolarSystem => self.system,
+            ZoomLevel::Planet => self.planet,
+            ZoomLevel::Region => self.region,
+            ZoomLevel::LocalArea => self.area,
+            ZoomLevel::Room => self.room,
+        }
+    }
+
+    pub fn set(&mut self, level: ZoomLevel, bounds: Bounds) {
+        match level {
+            ZoomLevel::Galaxy => self.galaxy = bounds,
+            ZoomLevel::SolarSystem => self.system = bounds,
+            ZoomLevel::Planet => self.planet = bounds,
+            ZoomLevel::Region => self.region = bounds,
+            ZoomLevel::LocalArea => self.area = bounds,
+            ZoomLevel::Room => self.room = bounds,
+        }
+    }
+}
+
+impl Default for MapBounds {
+    /// Walls stop the cursor in the galaxy and in rooms; walking off any
+    /// other map leads out onto its parent's
+    fn default() -> Self {
+        Self {
+            galaxy: Bounds::new(100, 100, Edge::Stop),
+            system: Bounds::around(7, Edge::Ascend),
+            planet: Bounds::around(7, Edge::Ascend),
+            region: Bounds::around(7, Edge::Ascend),
+            area: Bounds::new(33, 11, Edge::Ascend),
+            room: Bounds::new(9, 5, Edge::Stop),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounds_are_centered() {
+        let bounds = Bounds::new(100, 5, Edge::Stop);
+        assert_eq!(bounds.min(), (-50, -2));
+        assert_eq!(bounds.max(), (49, 2));
+        assert!(bounds.contains((49, -2)));
+        assert!(!bounds.contains((50, 0)));
+        assert_eq!(bounds.clamp((80, -9)), (49, -2));
+        assert_eq!(Bounds::around(3, Edge::Stop).max(), (3, 3));
+    }
+}
@@ -1,8 +1,12 @@
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
+use crate::game::WorldState;
 use crate::game::state::EntityId;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+use super::Edge;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum ZoomLevel {
     Room,
     LocalArea,
@@ -12,7 +16,8 @@ pub enum ZoomLevel {
     Galaxy,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Direction {
     Up,
     Down,
@@ -21,7 +26,7 @@ pub enum Direction {
 }
 
 impl Direction {
-    pub fn to_offset(&self) -> (i32, i32) {
+    pub fn to_offset(self) -> (i32, i32) {
         match self {
             Direction::Up => (0, -1),
             Direction::Down => (0, 1),
@@ -68,8 +73,7 @@ impl fmt::Display for ZoomLevel {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-#[derive(Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Position {
     // Entity ID tracking - which specific entity at each level
     pub current_system_id: Option<EntityId>,
@@ -87,7 +91,6 @@ pub struct Position {
     pub room_coords: (i32, i32),
 }
 
-
 impl Position {
     pub fn new() -> Self {
         Self::default()
@@ -125,6 +128,26 @@ impl Position {
             ZoomLevel::Room => self.current_room_id,
         }
     }
+
+    /// The entity whose map is shown at a level: nothing at the galaxy,
+    /// otherwise the current entity, defaulting to the starting location
+    pub fn map_owner(&self, level: ZoomLevel) -> Option<EntityId> {
+        match level {
+            ZoomLevel::Galaxy => None,
+            level => Some(self.current_entity_id(level).unwrap_or(1)),
+        }
+    }
+
+    pub fn set_entity_id(&mut self, level: ZoomLevel, id: Option<EntityId>) {
+        match level {
+            ZoomLevel::Galaxy => {}
+            ZoomLevel::SolarSystem => self.current_system_id = id,
+            ZoomLevel::Planet => self.current_planet_id = id,
+            ZoomLevel::Region => self.current_region_id = id,
+            ZoomLevel::LocalArea => self.current_area_id = id,
+            ZoomLevel::Room => self.current_room_id = id,
+        }
+    }
 }
 
 pub struct ZoomManager {
@@ -148,32 +171,67 @@ impl ZoomManager {
         &self.position
     }
 
-    pub fn zoom_in(&mut self) -> bool {
-        if let Some(new_level) = self.current_level.zoom_in() {
-            self.current_level = new_level;
-            true
-        } else {
-            false
-        }
+    /// Restore a previously saved view
+    pub fn restore(&mut self, level: ZoomLevel, position: Position) {
+        self.current_level = level;
+        self.position = position;
     }
 
-    pub fn zoom_out(&mut self) -> bool {
-        if let Some(new_level) = self.current_level.zoom_out() {
-            self.current_level = new_level;
-            true
-        } else {
-            false
+    /// Descend into the child entity under the cursor. Returns false at the
+    /// innermost level or when the cursor is over empty space.
+    pub fn zoom_in(&mut self, world: &WorldState) -> bool {
+        let Some(child_level) = self.current_level.zoom_in() else {
+            return false;
+        };
+        let parent = self.position.map_owner(self.current_level);
+        let coords = self.position.coords_for_level(self.current_level);
+        let Some(id) = world.entity_at(child_level, parent, coords) else {
+            return false;
+        };
+
+        self.position.set_entity_id(child_level, Some(id));
+        self.current_level = child_level;
+        true
+    }
+
+    /// Leave the current entity for its parent's map, with the cursor
+    /// placed over the entity that was left
+    pub fn zoom_out(&mut self, world: &WorldState) -> bool {
+        let Some(parent_level) = self.current_level.zoom_out() else {
+            return false;
+        };
+        let left = self.position.current_entity_id(self.current_level);
+        if let Some(placement) = left.and_then(|id| world.placement_of(self.current_level, id)) {
+            self.position
+                .set_coords_for_level(parent_level, placement.coords);
         }
+
+        self.position.set_entity_id(self.current_level, None);
+        self.current_level = parent_level;
+        true
     }
 
-    /// Move in a direction within the current zoom level
-    /// Returns true if the movement was successful
-    pub fn move_in_direction(&mut self, direction: Direction) -> bool {
+    /// Move the cursor a spot within the current map. At the map's edge it
+    /// either stays put or leaves for the parent's map and moves on there,
+    /// as the level's bounds say. A cursor left outside the map, as by an
+    /// older save, is brought back onto it. Returns true if the view moved.
+    pub fn move_in_direction(&mut self, world: &WorldState, direction: Direction) -> bool {
+        let bounds = world.map_bounds(self.current_level);
         let current_coords = self.position.coords_for_level(self.current_level);
         let offset = direction.to_offset();
-        let new_coords = (current_coords.0 + offset.0, current_coords.1 + offset.1);
+        let mut new_coords = (current_coords.0 + offset.0, current_coords.1 + offset.1);
+
+        if !bounds.contains(new_coords) {
+            if !bounds.contains(current_coords) {
+                new_coords = bounds.clamp(new_coords);
+            } else if bounds.edge == Edge::Ascend && self.zoom_out(world) {
+                self.move_in_direction(world, direction);
+                return true;
+            } else {
+                return false;
+            }
+        }
 
-        // For now, allow unlimited movement (will be constrained by map boundaries later)
         self.position
             .set_coords_for_level(self.current_level, new_coords);
         true
@@ -194,6 +252,8 @@ impl Default for ZoomManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::game::state::Placement;
+    use crate::zoom::Bounds;
 
     #[test]
     fn zoom_in_transitions() {
@@ -223,33 +283,78 @@ mod tests {
 
     #[test]
     fn zoom_manager_can_zoom_in() {
+        let world = WorldState::new();
         let mut manager = ZoomManager::new();
-        assert!(manager.zoom_in());
+        assert!(manager.zoom_in(&world));
         assert_eq!(manager.current_level(), ZoomLevel::SolarSystem);
-        assert!(manager.zoom_in());
+        assert!(manager.zoom_in(&world));
         assert_eq!(manager.current_level(), ZoomLevel::Planet);
     }
 
     #[test]
     fn zoom_manager_can_zoom_out() {
+        let world = WorldState::new();
         let mut manager = ZoomManager::new();
-        manager.zoom_in();
-        manager.zoom_in();
-        assert!(manager.zoom_out());
+        manager.zoom_in(&world);
+        manager.zoom_in(&world);
+        assert!(manager.zoom_out(&world));
         assert_eq!(manager.current_level(), ZoomLevel::SolarSystem);
     }
 
     #[test]
     fn zoom_manager_cannot_zoom_beyond_limits() {
+        let world = WorldState::new();
         let mut manager = ZoomManager::new();
-        assert!(!manager.zoom_out());
+        assert!(!manager.zoom_out(&world));
         assert_eq!(manager.current_level(), ZoomLevel::Galaxy);
 
         for _ in 0..6 {
-            manager.zoom_in();
+            manager.zoom_in(&world);
         }
         assert_eq!(manager.current_level(), ZoomLevel::Room);
-        assert!(!manager.zoom_in());
+        assert!(!manager.zoom_in(&world));
+    }
+
+    #[test]
+    fn zoom_in_selects_entity_under_cursor() {
+        let mut world = WorldState::new();
+        let planet = world
+            .spawn_at(ZoomLevel::Planet, Placement::new(Some(1), (2, -1)))
+            .unwrap();
+        let mut manager = ZoomManager::new();
+        manager.zoom_in(&world);
+        assert_eq!(manager.position().current_system_id, Some(1));
+
+        manager.position_mut().system_coords = (2, -1);
+        assert!(manager.zoom_in(&world));
+        assert_eq!(manager.position().current_planet_id, Some(planet));
+    }
+
+    #[test]
+    fn zoom_in_refuses_empty_space() {
+        let world = WorldState::new();
+        let mut manager = ZoomManager::new();
+        manager.move_in_direction(&world, Direction::Right);
+
+        assert!(!manager.zoom_in(&world));
+        assert_eq!(manager.current_level(), ZoomLevel::Galaxy);
+        assert_eq!(manager.position().current_system_id, None);
+    }
+
+    #[test]
+    fn zoom_out_returns_cursor_to_entity_left() {
+        let mut world = WorldState::new();
+        world.spawn_at(ZoomLevel::Planet, Placement::new(Some(1), (3, 4)));
+        let mut manager = ZoomManager::new();
+        manager.zoom_in(&world);
+        manager.position_mut().system_coords = (3, 4);
+        manager.zoom_in(&world);
+        manager.position_mut().system_coords = (0, 0);
+
+        assert!(manager.zoom_out(&world));
+        assert_eq!(manager.position().system_coords, (3, 4));
+        assert_eq!(manager.position().current_planet_id, None);
+        assert_eq!(manager.position().current_system_id, Some(1));
     }
 
     #[test]
@@ -306,50 +411,54 @@ mod tests {
 
     #[test]
     fn zoom_manager_movement() {
+        let world = WorldState::new();
         let mut manager = ZoomManager::new();
         assert_eq!(manager.position().galaxy_coords, (0, 0));
 
         // Move right
-        manager.move_in_direction(Direction::Right);
+        manager.move_in_direction(&world, Direction::Right);
         assert_eq!(manager.position().galaxy_coords, (1, 0));
 
         // Move down
-        manager.move_in_direction(Direction::Down);
+        manager.move_in_direction(&world, Direction::Down);
         assert_eq!(manager.position().galaxy_coords, (1, 1));
 
         // Move left
-        manager.move_in_direction(Direction::Left);
+        manager.move_in_direction(&world, Direction::Left);
         assert_eq!(manager.position().galaxy_coords, (0, 1));
 
         // Move up
-        manager.move_in_direction(Direction::Up);
+        manager.move_in_direction(&world, Direction::Up);
         assert_eq!(manager.position().galaxy_coords, (0, 0));
     }
 
     #[test]
     fn zoom_manager_movement_different_levels() {
+        let mut world = WorldState::new();
+        let system = world.spawn_at(ZoomLevel::SolarSystem, Placement::new(None, (1, 0)));
+        world.spawn_at(ZoomLevel::Planet, Placement::new(system, (0, 1)));
         let mut manager = ZoomManager::new();
 
         // Move at galaxy level
-        manager.move_in_direction(Direction::Right);
+        manager.move_in_direction(&world, Direction::Right);
         assert_eq!(manager.position().galaxy_coords, (1, 0));
 
         // Zoom into solar system
-        manager.zoom_in();
+        manager.zoom_in(&world);
         assert_eq!(manager.current_level(), ZoomLevel::SolarSystem);
 
         // Movement at solar system level shouldn't affect galaxy coords
-        manager.move_in_direction(Direction::Down);
+        manager.move_in_direction(&world, Direction::Down);
         assert_eq!(manager.position().system_coords, (0, 1));
         assert_eq!(manager.position().galaxy_coords, (1, 0)); // unchanged
 
         // Zoom into planet
-        manager.zoom_in();
+        manager.zoom_in(&world);
         assert_eq!(manager.current_level(), ZoomLevel::Planet);
 
         // Movement at planet level
-        manager.move_in_direction(Direction::Left);
-        manager.move_in_direction(Direction::Left);
+        manager.move_in_direction(&world, Direction::Left);
+        manager.move_in_direction(&world, Direction::Left);
         assert_eq!(manager.position().planet_coords, (-2, 0));
         assert_eq!(manager.position().system_coords, (0, 1)); // unchanged
         assert_eq!(manager.position().galaxy_coords, (1, 0)); // unchanged
@@ -357,14 +466,51 @@ mod tests {
 
     #[test]
     fn zoom_manager_allows_negative_coordinates() {
+        let world = WorldState::new();
         let mut manager = ZoomManager::new();
 
         // Move left from origin - should allow negative coordinates
-        manager.move_in_direction(Direction::Left);
+        manager.move_in_direction(&world, Direction::Left);
         assert_eq!(manager.position().galaxy_coords, (-1, 0));
 
         // Move up from origin
-        manager.move_in_direction(Direction::Up);
+        manager.move_in_direction(&world, Direction::Up);
         assert_eq!(manager.position().galaxy_coords, (-1, -1));
     }
+
+    #[test]
+    fn zoom_manager_stops_at_walls() {
+        let world = WorldState::new();
+        let mut manager = ZoomManager::new();
+        manager.position_mut().galaxy_coords = (49, 0);
+
+        assert!(!manager.move_in_direction(&world, Direction::Right));
+        assert_eq!(manager.position().galaxy_coords, (49, 0));
+        assert!(manager.move_in_direction(&world, Direction::Left));
+        assert_eq!(manager.position().galaxy_coords, (48, 0));
+    }
+
+    #[test]
+    fn zoom_manager_ascends_off_the_edge() {
+        let mut world = WorldState::new();
+        world.set_map_bounds(ZoomLevel::Planet, Bounds::around(1, Edge::Ascend));
+        let mut manager = ZoomManager::new();
+        manager.zoom_in(&world);
+        manager.zoom_in(&world);
+        manager.position_mut().planet_coords = (1, 0);
+
+        assert!(manager.move_in_direction(&world, Direction::Right));
+        assert_eq!(manager.current_level(), ZoomLevel::SolarSystem);
+        assert_eq!(manager.position().system_coords, (1, 0));
+    }
+
+    #[test]
+    fn zoom_manager_brings_stray_cursor_back() {
+        let world = WorldState::new();
+        let mut manager = ZoomManager::new();
+        manager.position_mut().galaxy_coords = (500, -500);
+
+        assert!(manager.move_in_direction(&world, Direction::Up));
+        assert_eq!(manager.position().galaxy_coords, (49, -50));
+    }
 }
@@ -1,3 +1,5 @@
+mod bounds;
 mod manager;
 
+pub use bounds::{Bounds, Edge, MapBounds};
 pub use manager::{Direction, Position, ZoomLevel, ZoomManager};
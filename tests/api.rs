@@ -0,0 +1,69 @@
+use econogenesis::api::{API_VERSION, Command, Core, Event, Snapshot};
+use econogenesis::zoom::ZoomLevel;
+
+#[test]
+fn plays_through_json_commands() {
+    let mut core = Core::new();
+    let commands = [
+        r#"{ "kind": "goto", "name": "Market District" }"#,
+        r#"{ "kind": "found_business", "name": "Corner Bakery", "recipe": "baking", "workers": 3, "capital": 500.0 }"#,
+        r#"{ "kind": "advance", "seconds": 3600 }"#,
+    ];
+    for json in commands {
+        let command: Command = serde_json::from_str(json).unwrap();
+        core.apply(command).unwrap();
+    }
+
+    let snapshot = core.snapshot();
+    assert_eq!(snapshot.api_version, API_VERSION);
+    assert_eq!(snapshot.tick, 1);
+    assert_eq!(snapshot.view.level, ZoomLevel::LocalArea);
+    assert_eq!(snapshot.view.name, "Market District");
+    assert!(
+        snapshot
+            .market
+            .is_some_and(|market| !market.quotes.is_empty())
+    );
+    let firm = &snapshot.business.firms[0];
+    assert_eq!(firm.name, "Corner Bakery");
+    assert_eq!(firm.workers, 3);
+
+    let events = core.poll_events();
+    assert!(events.iter().any(|event| matches!(
+        event,
+        Event::Logged { message, .. } if message.starts_with("Founded Corner Bakery")
+    )));
+}
+
+#[test]
+fn snapshots_cross_the_wire_unchanged() {
+    let core = Core::new();
+    let json = serde_json::to_string(&core.snapshot()).unwrap();
+    let snapshot: Snapshot = serde_json::from_str(&json).unwrap();
+    assert_eq!(snapshot, core.snapshot());
+}
+
+#[test]
+fn refused_commands_explain_why() {
+    let mut core = Core::new();
+    let error = core
+        .apply(serde_json::from_str(r#"{ "kind": "goto", "name": "Atlantis" }"#).unwrap())
+        .unwrap_err();
+    assert_eq!(error.to_string(), "no place named 'Atlantis'");
+}
+
+#[test]
+fn markets_keep_price_history() {
+    let mut core = Core::new();
+    for _ in 0..24 {
+        core.apply(Command::Advance { seconds: 3600 }).unwrap();
+    }
+
+    let history = core.price_history(1, "grain").unwrap();
+    assert_eq!(history.points.len(), 24);
+    assert_eq!(history.points[0].hour, 0);
+    assert!(history.points.iter().all(|point| point.price > 0.0));
+    assert!(core.price_history(1, "unobtainium").is_none());
+
+    assert!(history.points.iter().any(|point| point.volume > 0.0));
+}
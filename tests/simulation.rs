@@ -0,0 +1,84 @@
+use econogenesis::economy::Money;
+use econogenesis::zoom::{Direction, ZoomLevel};
+use econogenesis::{Simulation, World};
+use std::time::Duration;
+
+#[test]
+fn drives_simulation_without_a_terminal() {
+    let mut simulation = Simulation::with_world(World::new());
+
+    for _ in 0..24 {
+        simulation.advance(Duration::from_secs(3600));
+    }
+
+    assert_eq!(simulation.world().tick_count(), 24);
+    assert_eq!(
+        simulation.time().current_date().to_string(),
+        "Jan 02, Y3000"
+    );
+}
+
+#[test]
+fn navigates_between_zoom_levels() {
+    let mut simulation = Simulation::new();
+    simulation.zoom_in();
+    simulation.move_cursor(Direction::Right);
+
+    assert_eq!(simulation.zoom().current_level(), ZoomLevel::SolarSystem);
+    assert_eq!(simulation.zoom().position().system_coords, (1, 0));
+    assert_eq!(
+        simulation
+            .world()
+            .get_current_entity_name(simulation.zoom().current_level()),
+        "Sol System"
+    );
+}
+
+#[test]
+fn economy_produces_goods_over_time() {
+    let mut simulation = Simulation::new();
+    simulation.force_full_detail(true);
+    let economy = simulation.world().economy();
+    let bread = economy.catalog().id("bread").unwrap();
+    let before = economy.market(1).unwrap().stock(bread);
+
+    simulation.advance(Duration::from_secs(3600 * 6));
+
+    let after = simulation.world().economy().market(1).unwrap().stock(bread);
+    assert!(after > before);
+}
+
+#[test]
+fn distant_markets_catch_up_daily() {
+    let mut simulation = Simulation::new();
+    let economy = simulation.world().economy();
+    let bread = economy.catalog().id("bread").unwrap();
+    let before = economy.market(1).unwrap().stock(bread);
+
+    // Seen from the galaxy, the sample market only updates once a day
+    simulation.advance(Duration::from_secs(3600 * 6));
+    assert_eq!(
+        simulation.world().economy().market(1).unwrap().stock(bread),
+        before
+    );
+
+    simulation.advance(Duration::from_secs(3600 * 18));
+    let after = simulation.world().economy().market(1).unwrap().stock(bread);
+    assert!(after > before);
+}
+
+#[test]
+fn firms_bank_their_surplus_and_borrow_when_short() {
+    let mut simulation = Simulation::new();
+    simulation.force_full_detail(true);
+    for _ in 0..24 * 60 {
+        simulation.advance(Duration::from_secs(3600));
+    }
+
+    let economy = simulation.world().economy();
+    let bank = economy.bank();
+    assert!(bank.deposits() > Money::ZERO);
+    assert!(bank.history().any(|day| day.lent > Money::ZERO));
+    let drift = economy.total_balances() - economy.money_supply().issued();
+    assert!(drift.amount().abs() < 1e-3);
+}